@@ -363,6 +363,13 @@ pub enum Error {
         expected: usize,
         found: usize,
     },
+
+    #[snafu(display(
+        "Densification would split a segment into more than {max_parts} parts, use a larger segment length"
+    ))]
+    TooManyDensificationParts {
+        max_parts: usize,
+    },
 }
 
 impl From<arrow::error::ArrowError> for Error {
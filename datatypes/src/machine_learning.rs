@@ -26,6 +26,8 @@ pub enum MlModelNameError {
     InvalidCharacter { invalid_char: String },
     #[snafu(display("ml model name must consist of at most two parts"))]
     TooManyParts,
+    #[snafu(display("invalid ml model version '{version}', expected a number or `latest`"))]
+    InvalidVersion { version: String },
 }
 
 impl MlModelName {
@@ -160,6 +162,117 @@ impl Display for MlModelName {
     }
 }
 
+const LATEST_VERSION: &str = "latest";
+
+/// Selects a version of a model.
+/// Versions are immutable and numbered consecutively, starting at 1.
+/// `Latest` is an alias for the most recently added version.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Default)]
+pub enum MlModelVersion {
+    #[default]
+    Latest,
+    Version(u32),
+}
+
+impl MlModelVersion {
+    pub fn version(self) -> Option<u32> {
+        match self {
+            Self::Latest => None,
+            Self::Version(version) => Some(version),
+        }
+    }
+}
+
+impl From<u32> for MlModelVersion {
+    fn from(version: u32) -> Self {
+        Self::Version(version)
+    }
+}
+
+impl FromStr for MlModelVersion {
+    type Err = MlModelNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == LATEST_VERSION {
+            return Ok(Self::Latest);
+        }
+
+        s.parse()
+            .map(Self::Version)
+            .map_err(|_| MlModelNameError::InvalidVersion {
+                version: s.to_string(),
+            })
+    }
+}
+
+impl Display for MlModelVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Latest => f.write_str(LATEST_VERSION),
+            Self::Version(version) => write!(f, "{version}"),
+        }
+    }
+}
+
+impl Serialize for MlModelVersion {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Latest => serializer.serialize_str(LATEST_VERSION),
+            Self::Version(version) => serializer.serialize_u32(*version),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MlModelVersion {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(MlModelVersionDeserializeVisitor)
+    }
+}
+
+struct MlModelVersionDeserializeVisitor;
+
+impl Visitor<'_> for MlModelVersionDeserializeVisitor {
+    type Value = MlModelVersion;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            formatter,
+            "a positive version number or \"{LATEST_VERSION}\""
+        )
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        u32::try_from(v)
+            .map(MlModelVersion::Version)
+            .map_err(|_| E::invalid_value(serde::de::Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        u32::try_from(v)
+            .map(MlModelVersion::Version)
+            .map_err(|_| E::invalid_value(serde::de::Unexpected::Signed(v), &self))
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        MlModelVersion::from_str(s).map_err(|e| E::custom(e.to_string()))
+    }
+}
+
 /// A struct describing tensor shape for `MlModelMetadata`
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Deserialize, Serialize, ToSql, FromSql)]
 pub struct MlTensorShape3D {
@@ -225,4 +338,31 @@ mod tests {
         assert_eq!(mln.name, "myModelName".to_string());
         assert!(mln.namespace.is_none());
     }
+
+    #[test]
+    fn ml_model_version_serde() {
+        assert_eq!(
+            serde_json::from_str::<MlModelVersion>("\"latest\"").unwrap(),
+            MlModelVersion::Latest
+        );
+        assert_eq!(
+            serde_json::from_str::<MlModelVersion>("3").unwrap(),
+            MlModelVersion::Version(3)
+        );
+        assert_eq!(
+            serde_json::from_str::<MlModelVersion>("\"3\"").unwrap(),
+            MlModelVersion::Version(3)
+        );
+        assert!(serde_json::from_str::<MlModelVersion>("-1").is_err());
+        assert!(serde_json::from_str::<MlModelVersion>("\"newest\"").is_err());
+
+        assert_eq!(
+            serde_json::to_string(&MlModelVersion::Latest).unwrap(),
+            "\"latest\""
+        );
+        assert_eq!(
+            serde_json::to_string(&MlModelVersion::Version(3)).unwrap(),
+            "3"
+        );
+    }
 }
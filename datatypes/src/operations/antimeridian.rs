@@ -0,0 +1,345 @@
+use crate::{
+    primitives::{
+        Coordinate2D, MultiLineString, MultiLineStringAccess, MultiPolygon, MultiPolygonAccess,
+    },
+    util::Result,
+};
+
+/// Split geometries in geographic coordinates (longitude, latitude) where they cross the antimeridian.
+///
+/// A segment crosses the antimeridian if its longitudes differ by more than 180 degrees,
+/// i.e., the shorter way between its vertices is across the antimeridian.
+pub trait SplitAtAntimeridian {
+    type Out;
+
+    fn split_at_antimeridian(&self) -> Result<Self::Out>;
+}
+
+impl SplitAtAntimeridian for MultiLineString {
+    type Out = MultiLineString;
+
+    fn split_at_antimeridian(&self) -> Result<Self::Out> {
+        if !self.lines().iter().any(|line| crosses_antimeridian(line)) {
+            return Ok(self.clone());
+        }
+
+        let lines = self
+            .lines()
+            .iter()
+            .flat_map(|line| split_line(line))
+            .collect();
+
+        MultiLineString::new(lines)
+    }
+}
+
+impl SplitAtAntimeridian for MultiPolygon {
+    type Out = MultiPolygon;
+
+    fn split_at_antimeridian(&self) -> Result<Self::Out> {
+        if !self
+            .polygons()
+            .iter()
+            .any(|polygon| polygon.iter().any(|ring| crosses_antimeridian(ring)))
+        {
+            return Ok(self.clone());
+        }
+
+        let polygons = self
+            .polygons()
+            .iter()
+            .flat_map(|polygon| split_polygon(polygon))
+            .collect::<Vec<_>>();
+
+        if polygons.is_empty() {
+            // all parts were degenerated
+            return Ok(self.clone());
+        }
+
+        MultiPolygon::new(polygons)
+    }
+}
+
+fn crosses_antimeridian(coordinates: &[Coordinate2D]) -> bool {
+    coordinates
+        .windows(2)
+        .any(|segment| (segment[1].x - segment[0].x).abs() > 180.)
+}
+
+/// The latitude where the segment crosses the `longitude` if the end is shifted by `shift` degrees
+fn crossing_latitude(start: Coordinate2D, end: Coordinate2D, shift: f64, longitude: f64) -> f64 {
+    let end_x = end.x + shift;
+    let fraction = (longitude - start.x) / (end_x - start.x);
+    start.y + fraction * (end.y - start.y)
+}
+
+fn split_line(line: &[Coordinate2D]) -> Vec<Vec<Coordinate2D>> {
+    let mut parts = Vec::new();
+    let mut part = Vec::new();
+
+    for segment in line.windows(2) {
+        let (start, end) = (segment[0], segment[1]);
+        part.push(start);
+
+        let delta = end.x - start.x;
+        if delta.abs() <= 180. {
+            continue;
+        }
+
+        // going west across the antimeridian if the longitude jumps up
+        let (shift, leave, enter) = if delta > 180. {
+            (-360., -180., 180.)
+        } else {
+            (360., 180., -180.)
+        };
+
+        let latitude = crossing_latitude(start, end, shift, leave);
+        part.push(Coordinate2D::new(leave, latitude));
+        parts.push(std::mem::take(&mut part));
+        part.push(Coordinate2D::new(enter, latitude));
+    }
+    part.extend(line.last());
+    parts.push(part);
+
+    parts.retain(|part| part.len() >= 2);
+    parts
+}
+
+/// Make the longitudes continuous by shifting them by multiples of 360 degrees
+fn unwrap_longitudes(ring: &[Coordinate2D]) -> Vec<Coordinate2D> {
+    let mut unwrapped: Vec<Coordinate2D> = Vec::with_capacity(ring.len());
+
+    for &coordinate in ring {
+        let x = match unwrapped.last() {
+            Some(previous) => coordinate.x + ((previous.x - coordinate.x) / 360.).round() * 360.,
+            None => coordinate.x,
+        };
+        unwrapped.push(Coordinate2D::new(x, coordinate.y));
+    }
+
+    unwrapped
+}
+
+/// Unwrap the ring and close it over the pole if it encircles one
+fn unwrap_ring(ring: &[Coordinate2D]) -> Vec<Coordinate2D> {
+    let mut unwrapped = unwrap_longitudes(ring);
+
+    let (Some(&first), Some(&last)) = (unwrapped.first(), unwrapped.last()) else {
+        return unwrapped;
+    };
+
+    if (last.x - first.x).abs() > 180. {
+        // the ring encircles a pole, so we walk along the pole back to the start
+        let mean_latitude = unwrapped.iter().map(|c| c.y).sum::<f64>() / unwrapped.len() as f64;
+        let pole = if mean_latitude < 0. { -90. } else { 90. };
+
+        unwrapped.push(Coordinate2D::new(last.x, pole));
+        unwrapped.push(Coordinate2D::new(first.x, pole));
+        unwrapped.push(first);
+    }
+
+    unwrapped
+}
+
+/// Clip a closed ring to the longitudes `[min_x, max_x]` using the Sutherland–Hodgman algorithm.
+/// Returns `None` if nothing remains.
+fn clip_ring(ring: &[Coordinate2D], min_x: f64, max_x: f64) -> Option<Vec<Coordinate2D>> {
+    let open_ring = &ring[..ring.len().saturating_sub(1)];
+
+    let clipped = clip_at(open_ring, min_x, |c| c.x >= min_x);
+    let mut clipped = clip_at(&clipped, max_x, |c| c.x <= max_x);
+
+    // discard rings that collapsed onto the boundary
+    if clipped.len() < 3 || ring_area(&clipped).abs() <= f64::EPSILON {
+        return None;
+    }
+
+    clipped.push(clipped[0]);
+    Some(clipped)
+}
+
+/// The signed area of an open ring (shoelace formula)
+fn ring_area(ring: &[Coordinate2D]) -> f64 {
+    let Some(&last) = ring.last() else {
+        return 0.;
+    };
+
+    let mut previous = last;
+    let mut area = 0.;
+    for &current in ring {
+        area += previous.x * current.y - current.x * previous.y;
+        previous = current;
+    }
+
+    area / 2.
+}
+
+fn clip_at(
+    ring: &[Coordinate2D],
+    boundary_x: f64,
+    inside: impl Fn(&Coordinate2D) -> bool,
+) -> Vec<Coordinate2D> {
+    let mut output = Vec::with_capacity(ring.len());
+
+    let Some(&last) = ring.last() else {
+        return output;
+    };
+
+    let intersection = |a: Coordinate2D, b: Coordinate2D| {
+        Coordinate2D::new(boundary_x, crossing_latitude(a, b, 0., boundary_x))
+    };
+
+    let mut previous = last;
+    for &current in ring {
+        match (inside(&previous), inside(&current)) {
+            (true, true) => output.push(current),
+            (true, false) => output.push(intersection(previous, current)),
+            (false, true) => {
+                output.push(intersection(previous, current));
+                output.push(current);
+            }
+            (false, false) => {}
+        }
+        previous = current;
+    }
+
+    output
+}
+
+fn split_polygon<R: AsRef<[Coordinate2D]>>(polygon: &[R]) -> Vec<Vec<Vec<Coordinate2D>>> {
+    let Some((exterior, interiors)) = polygon.split_first() else {
+        return Vec::new();
+    };
+
+    let exterior = unwrap_ring(exterior.as_ref());
+    let interiors = interiors
+        .iter()
+        .map(|ring| unwrap_longitudes(ring.as_ref()))
+        .collect::<Vec<_>>();
+
+    let (min_x, max_x) = exterior
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), c| {
+            (min.min(c.x), max.max(c.x))
+        });
+
+    let first_window = ((min_x + 180.) / 360.).floor() as i64;
+    let last_window = ((max_x + 180.) / 360.).ceil() as i64;
+
+    let mut polygons = Vec::new();
+
+    // clip the polygon to each 360 degree window and shift the parts back into [-180, 180]
+    for window in first_window..last_window {
+        let shift = window as f64 * 360.;
+        let (window_min_x, window_max_x) = (shift - 180., shift + 180.);
+
+        let Some(clipped_exterior) = clip_ring(&exterior, window_min_x, window_max_x) else {
+            continue;
+        };
+
+        let mut rings = vec![clipped_exterior];
+
+        // the holes may be unwrapped relative to another window than the exterior
+        for interior in &interiors {
+            for hole_shift in [-360., 0., 360.] {
+                let shifted = interior
+                    .iter()
+                    .map(|c| Coordinate2D::new(c.x + hole_shift, c.y))
+                    .collect::<Vec<_>>();
+                rings.extend(clip_ring(&shifted, window_min_x, window_max_x));
+            }
+        }
+
+        for ring in &mut rings {
+            for coordinate in ring.iter_mut() {
+                coordinate.x -= shift;
+            }
+        }
+
+        polygons.push(rings);
+    }
+
+    polygons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_splits_lines() {
+        let line = MultiLineString::new(vec![vec![
+            (170., 0.).into(),
+            (-170., 10.).into(),
+            (-160., 10.).into(),
+        ]])
+        .unwrap();
+
+        let split = line.split_at_antimeridian().unwrap();
+
+        assert_eq!(
+            split,
+            MultiLineString::new(vec![
+                vec![(170., 0.).into(), (180., 5.).into()],
+                vec![(-180., 5.).into(), (-170., 10.).into(), (-160., 10.).into()],
+            ])
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn it_keeps_lines_that_do_not_cross() {
+        let line = MultiLineString::new(vec![vec![(-90., 0.).into(), (90., 10.).into()]]).unwrap();
+        assert_eq!(line.split_at_antimeridian().unwrap(), line);
+    }
+
+    #[test]
+    fn it_splits_polygons() {
+        let polygon = MultiPolygon::new(vec![vec![vec![
+            (170., 0.).into(),
+            (-170., 0.).into(),
+            (-170., 10.).into(),
+            (170., 10.).into(),
+            (170., 0.).into(),
+        ]]])
+        .unwrap();
+
+        let split = polygon.split_at_antimeridian().unwrap();
+
+        assert_eq!(split.polygons().len(), 2);
+        for part in split.polygons() {
+            let exterior = &part[0];
+            assert_eq!(exterior.first(), exterior.last());
+
+            let (min_x, max_x) = exterior
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), c| {
+                    (min.min(c.x), max.max(c.x))
+                });
+            assert!((max_x - min_x - 10.).abs() < 1e-9);
+            assert!(min_x >= -180. && max_x <= 180.);
+        }
+    }
+
+    #[test]
+    fn it_closes_polygons_around_the_pole() {
+        let polygon = MultiPolygon::new(vec![vec![vec![
+            (0., -80.).into(),
+            (120., -80.).into(),
+            (-120., -80.).into(),
+            (0., -80.).into(),
+        ]]])
+        .unwrap();
+
+        let split = polygon.split_at_antimeridian().unwrap();
+
+        // the ring is split into the eastern and the western hemisphere
+        assert_eq!(split.polygons().len(), 2);
+
+        for part in split.polygons() {
+            let exterior = &part[0];
+            assert!(exterior.iter().any(|c| (c.y + 90.).abs() < 1e-9));
+            assert!(exterior.iter().all(|c| (-180. ..=180.).contains(&c.x)));
+        }
+    }
+}
@@ -0,0 +1,331 @@
+use crate::{
+    error,
+    operations::reproject::CoordinateProjection,
+    primitives::{
+        Coordinate2D, MultiLineString, MultiLineStringAccess, MultiLineStringRef, MultiPolygon,
+        MultiPolygonAccess, MultiPolygonRef,
+    },
+    util::Result,
+};
+use serde::{Deserialize, Serialize};
+use snafu::ensure;
+
+/// How to insert additional vertices into lines and polygon edges before reprojecting them.
+/// Without densification, straight edges stay straight in the target projection even if they should be curved.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum Densification {
+    /// Split the segments so that no segment is longer than `max_segment_length` units of the source projection
+    /// Fails if a segment would be split into more than 1024 parts
+    #[serde(rename_all = "camelCase")]
+    MaxSegmentLength { max_segment_length: f64 },
+    /// Recursively split the segments at their midpoints until the projected midpoint deviates at most
+    /// `tolerance` units of the target projection from the projected segment
+    ErrorTolerance { tolerance: f64 },
+}
+
+impl Densification {
+    /// The maximum number of recursive subdivisions of a segment for `ErrorTolerance`
+    const MAX_SUBDIVISION_DEPTH: u32 = 10;
+
+    /// The maximum number of parts a segment is split into, which is the same for both methods
+    const MAX_SEGMENT_PARTS: usize = 1 << Self::MAX_SUBDIVISION_DEPTH;
+
+    /// Checks whether the length or tolerance is positive and finite
+    pub fn is_valid(&self) -> bool {
+        let value = match self {
+            Self::MaxSegmentLength { max_segment_length } => *max_segment_length,
+            Self::ErrorTolerance { tolerance } => *tolerance,
+        };
+
+        value.is_finite() && value > 0.
+    }
+
+    /// Densify the line string `coordinates` and project it
+    pub fn project_line<P: CoordinateProjection>(
+        &self,
+        coordinates: &[Coordinate2D],
+        projector: &P,
+    ) -> Result<Vec<Coordinate2D>> {
+        match *self {
+            Self::MaxSegmentLength { max_segment_length } => {
+                projector.project_coordinates(densify_by_length(coordinates, max_segment_length)?)
+            }
+            Self::ErrorTolerance { tolerance } => {
+                densify_by_tolerance(coordinates, projector, tolerance)
+            }
+        }
+    }
+}
+
+fn densify_by_length(
+    coordinates: &[Coordinate2D],
+    max_segment_length: f64,
+) -> Result<Vec<Coordinate2D>> {
+    let mut densified = Vec::with_capacity(coordinates.len());
+
+    for segment in coordinates.windows(2) {
+        let (start, end) = (segment[0], segment[1]);
+        let number_of_parts = (start.euclidean_distance(&end) / max_segment_length).ceil();
+
+        ensure!(
+            number_of_parts <= Densification::MAX_SEGMENT_PARTS as f64,
+            error::TooManyDensificationParts {
+                max_parts: Densification::MAX_SEGMENT_PARTS
+            }
+        );
+        let number_of_parts = number_of_parts as usize;
+
+        densified.push(start);
+        for i in 1..number_of_parts {
+            let fraction = i as f64 / number_of_parts as f64;
+            densified.push(start + (end - start) * fraction);
+        }
+    }
+    densified.extend(coordinates.last());
+
+    Ok(densified)
+}
+
+fn densify_by_tolerance<P: CoordinateProjection>(
+    coordinates: &[Coordinate2D],
+    projector: &P,
+    tolerance: f64,
+) -> Result<Vec<Coordinate2D>> {
+    let projected = projector.project_coordinates(coordinates)?;
+
+    let mut densified = Vec::with_capacity(coordinates.len());
+
+    for (segment, projected_segment) in coordinates.windows(2).zip(projected.windows(2)) {
+        densified.push(projected_segment[0]);
+        subdivide_segment(
+            (segment[0], segment[1]),
+            (projected_segment[0], projected_segment[1]),
+            projector,
+            tolerance,
+            Densification::MAX_SUBDIVISION_DEPTH,
+            &mut densified,
+        )?;
+    }
+    densified.extend(projected.last());
+
+    Ok(densified)
+}
+
+/// Push the projected inner vertices of the segment to `output`
+fn subdivide_segment<P: CoordinateProjection>(
+    (start, end): (Coordinate2D, Coordinate2D),
+    (projected_start, projected_end): (Coordinate2D, Coordinate2D),
+    projector: &P,
+    tolerance: f64,
+    remaining_depth: u32,
+    output: &mut Vec<Coordinate2D>,
+) -> Result<()> {
+    if remaining_depth == 0 {
+        return Ok(());
+    }
+
+    let midpoint = (start + end) / 2.;
+    let projected_midpoint = projector.project_coordinate(midpoint)?;
+
+    let straight_midpoint = (projected_start + projected_end) / 2.;
+    if projected_midpoint.euclidean_distance(&straight_midpoint) <= tolerance {
+        return Ok(());
+    }
+
+    subdivide_segment(
+        (start, midpoint),
+        (projected_start, projected_midpoint),
+        projector,
+        tolerance,
+        remaining_depth - 1,
+        output,
+    )?;
+    output.push(projected_midpoint);
+    subdivide_segment(
+        (midpoint, end),
+        (projected_midpoint, projected_end),
+        projector,
+        tolerance,
+        remaining_depth - 1,
+        output,
+    )
+}
+
+pub trait ReprojectDensified<P: CoordinateProjection> {
+    type Out;
+    /// Reproject the geometry after inserting vertices into its edges
+    fn reproject_densified(&self, projector: &P, densification: Densification)
+    -> Result<Self::Out>;
+}
+
+fn reproject_lines_densified<P, A>(
+    geometry: &A,
+    projector: &P,
+    densification: Densification,
+) -> Result<MultiLineString>
+where
+    P: CoordinateProjection,
+    A: MultiLineStringAccess,
+{
+    let lines = geometry
+        .lines()
+        .iter()
+        .map(|line| densification.project_line(line.as_ref(), projector))
+        .collect::<Result<Vec<_>>>()?;
+
+    MultiLineString::new(lines)
+}
+
+fn reproject_polygons_densified<P, A>(
+    geometry: &A,
+    projector: &P,
+    densification: Densification,
+) -> Result<MultiPolygon>
+where
+    P: CoordinateProjection,
+    A: MultiPolygonAccess,
+{
+    let polygons = geometry
+        .polygons()
+        .iter()
+        .map(|polygon| {
+            polygon
+                .as_ref()
+                .iter()
+                .map(|ring| densification.project_line(ring.as_ref(), projector))
+                .collect::<Result<Vec<_>>>()
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    MultiPolygon::new(polygons)
+}
+
+impl<P: CoordinateProjection> ReprojectDensified<P> for MultiLineString {
+    type Out = MultiLineString;
+
+    fn reproject_densified(
+        &self,
+        projector: &P,
+        densification: Densification,
+    ) -> Result<Self::Out> {
+        reproject_lines_densified(self, projector, densification)
+    }
+}
+
+impl<P: CoordinateProjection> ReprojectDensified<P> for MultiLineStringRef<'_> {
+    type Out = MultiLineString;
+
+    fn reproject_densified(
+        &self,
+        projector: &P,
+        densification: Densification,
+    ) -> Result<Self::Out> {
+        reproject_lines_densified(self, projector, densification)
+    }
+}
+
+impl<P: CoordinateProjection> ReprojectDensified<P> for MultiPolygon {
+    type Out = MultiPolygon;
+
+    fn reproject_densified(
+        &self,
+        projector: &P,
+        densification: Densification,
+    ) -> Result<Self::Out> {
+        reproject_polygons_densified(self, projector, densification)
+    }
+}
+
+impl<P: CoordinateProjection> ReprojectDensified<P> for MultiPolygonRef<'_> {
+    type Out = MultiPolygon;
+
+    fn reproject_densified(
+        &self,
+        projector: &P,
+        densification: Densification,
+    ) -> Result<Self::Out> {
+        reproject_polygons_densified(self, projector, densification)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operations::reproject::{CoordinateProjector, Reproject};
+    use crate::spatial_reference::{SpatialReference, SpatialReferenceAuthority};
+    use crate::util::well_known_data::{COLOGNE_EPSG_4326, HAMBURG_EPSG_4326};
+
+    #[test]
+    fn it_densifies_by_length() {
+        let densified =
+            densify_by_length(&[(0., 0.).into(), (3., 0.).into(), (3., 1.).into()], 1.5).unwrap();
+
+        assert_eq!(
+            densified,
+            vec![
+                (0., 0.).into(),
+                (1.5, 0.).into(),
+                (3., 0.).into(),
+                (3., 1.).into()
+            ]
+        );
+    }
+
+    #[test]
+    fn it_limits_the_parts_of_a_segment() {
+        assert!(densify_by_length(&[(0., 0.).into(), (1., 0.).into()], 1. / 1024.).is_ok());
+        assert!(densify_by_length(&[(0., 0.).into(), (1., 0.).into()], 1e-12).is_err());
+    }
+
+    #[test]
+    fn it_validates() {
+        assert!(
+            Densification::MaxSegmentLength {
+                max_segment_length: 1.
+            }
+            .is_valid()
+        );
+        assert!(!Densification::ErrorTolerance { tolerance: 0. }.is_valid());
+        assert!(
+            !Densification::ErrorTolerance {
+                tolerance: f64::NAN
+            }
+            .is_valid()
+        );
+    }
+
+    #[test]
+    fn it_reprojects_densified_lines() {
+        let projector = CoordinateProjector::from_known_srs(
+            SpatialReference::epsg_4326(),
+            SpatialReference::new(SpatialReferenceAuthority::Epsg, 3857),
+        )
+        .unwrap();
+
+        let line = MultiLineString::new(vec![vec![COLOGNE_EPSG_4326, HAMBURG_EPSG_4326]]).unwrap();
+
+        let by_length = line
+            .reproject_densified(
+                &projector,
+                Densification::MaxSegmentLength {
+                    max_segment_length: 1.,
+                },
+            )
+            .unwrap();
+        // the segment is about four degrees long
+        assert_eq!(by_length.lines()[0].len(), 6);
+
+        let by_tolerance = line
+            .reproject_densified(&projector, Densification::ErrorTolerance { tolerance: 1. })
+            .unwrap();
+        assert!(by_tolerance.lines()[0].len() > 2);
+
+        // the end points are the same as without densification
+        let plain = line.reproject(&projector).unwrap();
+        for densified in [by_length, by_tolerance] {
+            assert_eq!(densified.lines()[0].first(), plain.lines()[0].first());
+            assert_eq!(densified.lines()[0].last(), plain.lines()[0].last());
+        }
+    }
+}
@@ -1,3 +1,5 @@
+pub mod antimeridian;
+pub mod densify;
 pub mod image;
 pub mod reproject;
 mod spatial_relation;
@@ -1,5 +1,7 @@
 use crate::error;
-use crate::plots::{Plot, PlotData, PlotMetaData};
+use crate::operations::image::RgbaColor;
+use crate::plots::figure::value_range;
+use crate::plots::{Axis, Figure, Mark, Plot, PlotData, PlotMetaData, category_color};
 use crate::primitives::{Measurement, TimeInstance};
 use crate::util::Result;
 use snafu::ensure;
@@ -84,6 +86,60 @@ impl Plot for AreaLineChart {
             metadata: PlotMetaData::None,
         })
     }
+
+    fn to_figure(&self) -> Figure {
+        let points: Vec<(f64, f64)> = self
+            .timestamps
+            .iter()
+            .zip(&self.values)
+            .map(|(timestamp, &value)| (timestamp.inner() as f64, value))
+            .collect();
+
+        let (mut min_value, mut max_value) = value_range(self.values.iter().copied());
+        if self.draw_area {
+            // the area starts at zero
+            min_value = min_value.min(0.);
+            max_value = max_value.max(0.);
+        }
+
+        let mut figure = Figure::new(
+            Axis::time(
+                "Time",
+                self.timestamps
+                    .iter()
+                    .copied()
+                    .min()
+                    .unwrap_or(TimeInstance::EPOCH_START),
+                self.timestamps
+                    .iter()
+                    .copied()
+                    .max()
+                    .unwrap_or(TimeInstance::EPOCH_START),
+            ),
+            Axis::linear(self.measurement.to_string(), min_value, max_value),
+        );
+
+        let color = category_color(0);
+        if self.draw_area {
+            let [red, green, blue, _] = color.into_inner();
+            figure.add_mark(Mark::Area {
+                points: points.clone(),
+                baseline: 0.,
+                color: RgbaColor::new(red, green, blue, 178),
+            });
+        }
+        for &(x, y) in &points {
+            figure.add_mark(Mark::Point {
+                x,
+                y,
+                radius: 3.,
+                color,
+            });
+        }
+        figure.add_mark(Mark::Line { points, color });
+
+        figure
+    }
 }
 
 #[cfg(test)]
@@ -1,4 +1,4 @@
-use super::{Plot, PlotData, PlotMetaData};
+use super::{Axis, Figure, Mark, Plot, PlotData, PlotMetaData, category_color};
 use crate::util::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
@@ -60,6 +60,25 @@ impl Plot for BarChart {
             metadata: PlotMetaData::None,
         })
     }
+
+    fn to_figure(&self) -> Figure {
+        let max_height = self.bars.values().copied().max().unwrap_or_default();
+
+        let mut figure = Figure::new(
+            Axis::band(self.x_label.clone(), self.bars.keys().cloned().collect()),
+            Axis::linear(self.y_label.clone(), 0., max_height as f64),
+        );
+
+        for (i, &bar_height) in self.bars.values().enumerate() {
+            figure.add_mark(Mark::Rect {
+                x: (i as f64 + 0.1, i as f64 + 0.9),
+                y: (0., bar_height as f64),
+                color: category_color(0),
+            });
+        }
+
+        figure
+    }
 }
 
 #[cfg(test)]
@@ -118,7 +137,7 @@ mod tests {
                   }
                 })
                 .to_string(),
-                metadata: PlotMetaData::None
+                metadata: PlotMetaData::None,
             }
         );
     }
@@ -1,5 +1,7 @@
 use crate::error;
-use crate::plots::{Plot, PlotData, PlotMetaData};
+use crate::operations::image::RgbaColor;
+use crate::plots::figure::value_range;
+use crate::plots::{Axis, Figure, Mark, Plot, PlotData, PlotMetaData, category_color};
 use crate::util::Result;
 use serde::{Deserialize, Serialize};
 use snafu::ensure;
@@ -40,6 +42,9 @@ pub struct BoxPlotAttribute {
     pub q1: f64,
     pub q3: f64,
     pub is_exact: bool,
+    /// The group of the box, if the values are grouped, e.g., by a class
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
 }
 
 impl BoxPlotAttribute {
@@ -76,13 +81,21 @@ impl BoxPlotAttribute {
             q1,
             q3,
             is_exact,
+            group: None,
         })
     }
+
+    /// Assigns the box to a group. Boxes of the same name are shown side by side for each group.
+    #[must_use]
+    pub fn with_group(mut self, group: String) -> Self {
+        self.group = Some(group);
+        self
+    }
 }
 
 impl Plot for BoxPlot {
     fn to_vega_embeddable(&self, _allow_interactions: bool) -> Result<PlotData> {
-        let vega_spec = serde_json::json!({
+        let mut vega_spec = serde_json::json!({
             "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
             "width": "container",
             "data": self,
@@ -117,11 +130,80 @@ impl Plot for BoxPlot {
             }
         });
 
+        if self.values.iter().any(|value| value.group.is_some()) {
+            // place the boxes of the groups side by side and color them by group
+            vega_spec["encoding"]["xOffset"] =
+                serde_json::json!({"field": "group", "type": "nominal"});
+            vega_spec["layer"][1]["encoding"]["color"] =
+                serde_json::json!({"field": "group", "type": "nominal", "title": null});
+            vega_spec["config"]["legend"]["disable"] = false.into();
+        }
+
         Ok(PlotData {
             vega_string: vega_spec.to_string(),
             metadata: PlotMetaData::None,
         })
     }
+
+    fn to_figure(&self) -> Figure {
+        let mut names: Vec<&str> = Vec::new();
+        let mut groups: Vec<&str> = Vec::new();
+        for value in &self.values {
+            if !names.contains(&value.name.as_str()) {
+                names.push(&value.name);
+            }
+            if let Some(group) = value
+                .group
+                .as_deref()
+                .filter(|group| !groups.contains(group))
+            {
+                groups.push(group);
+            }
+        }
+
+        let (min, max) = value_range(self.values.iter().flat_map(|value| [value.min, value.max]));
+        let mut figure = Figure::new(
+            Axis::band("", names.iter().map(ToString::to_string).collect()),
+            Axis::linear("", min, max),
+        );
+
+        // the boxes of the groups are placed side by side within the band of their name
+        let band_width = 1. / groups.len().max(1) as f64;
+        for value in &self.values {
+            let name_index = names
+                .iter()
+                .position(|&name| name == value.name)
+                .expect("names were collected above");
+            let group_index = value
+                .group
+                .as_deref()
+                .and_then(|group| groups.iter().position(|&g| g == group));
+
+            let left = name_index as f64 + group_index.unwrap_or_default() as f64 * band_width;
+            let center = left + band_width / 2.;
+            let (box_left, box_right) = (left + band_width / 8., left + band_width * 7. / 8.);
+
+            figure.add_mark(Mark::Line {
+                points: vec![(center, value.min), (center, value.max)],
+                color: RgbaColor::new(85, 85, 85, 255),
+            });
+            figure.add_mark(Mark::Rect {
+                x: (box_left, box_right),
+                y: (value.q1, value.q3),
+                color: category_color(group_index.unwrap_or(name_index)),
+            });
+            figure.add_mark(Mark::Line {
+                points: vec![(box_left, value.median), (box_right, value.median)],
+                color: RgbaColor::white(),
+            });
+        }
+
+        for (group_index, group) in groups.into_iter().enumerate() {
+            figure.add_legend_entry(group, category_color(group_index));
+        }
+
+        figure
+    }
 }
 
 #[cfg(test)]
@@ -149,7 +231,7 @@ mod tests {
             bp.to_vega_embeddable(false).unwrap(),
             PlotData {
                 vega_string: r#"{"$schema":"https://vega.github.io/schema/vega-lite/v5.json","config":{"axisXDiscrete":{"title":null},"axisYQuantitative":{"title":null},"legend":{"disable":true}},"data":{"values":[{"isExact":true,"max":83.0,"median":35.0,"min":12.0,"name":"A1","q1":20.0,"q3":55.0}]},"encoding":{"x":{"field":"name","type":"nominal"}},"layer":[{"encoding":{"y":{"field":"min","scale":{"zero":false},"type":"quantitative"},"y2":{"field":"max"}},"mark":{"type":"rule"}},{"encoding":{"color":{"field":"name","type":"nominal"},"y":{"field":"q1","type":"quantitative"},"y2":{"field":"q3"}},"mark":{"cornerRadius":5,"type":"bar","width":{"band":0.75}}},{"encoding":{"y":{"field":"median","type":"quantitative"}},"mark":{"color":"white","height":1,"type":"rect","width":{"band":0.75}}}],"width":"container"}"#.to_owned(),
-                metadata: PlotMetaData::None
+                metadata: PlotMetaData::None,
             }
         );
     }
@@ -178,4 +260,30 @@ mod tests {
             BoxPlotAttribute::new("A1".to_string(), 12.0, 35.0, 83.0, 20.0, 55.0, true).is_err()
         );
     }
+
+    #[test]
+    fn grouped() {
+        let mut bp = BoxPlot::new();
+        bp.add_attribute(
+            BoxPlotAttribute::new("A1".to_string(), 1.0, 5.0, 3.0, 2.0, 4.0, true)
+                .unwrap()
+                .with_group("forest".to_string()),
+        );
+        bp.add_attribute(
+            BoxPlotAttribute::new("A1".to_string(), 2.0, 6.0, 4.0, 3.0, 5.0, true)
+                .unwrap()
+                .with_group("water".to_string()),
+        );
+
+        let vega: serde_json::Value =
+            serde_json::from_str(&bp.to_vega_embeddable(false).unwrap().vega_string).unwrap();
+
+        assert_eq!(vega["data"]["values"][1]["group"], "water");
+        assert_eq!(
+            vega["encoding"]["xOffset"],
+            serde_json::json!({"field": "group", "type": "nominal"})
+        );
+        assert_eq!(vega["layer"][1]["encoding"]["color"]["field"], "group");
+        assert_eq!(vega["config"]["legend"]["disable"], false);
+    }
 }
@@ -0,0 +1,442 @@
+use serde::{Deserialize, Serialize};
+use snafu::ensure;
+
+use crate::error;
+use crate::operations::image::RgbaColor;
+use crate::plots::{Axis, ColorRamp, Figure, Mark, Plot, PlotData, PlotMetaData};
+use crate::util::Result;
+
+/// A confusion matrix that compares the classes of a classification with reference classes.
+///
+/// The `counts` are indexed by the reference class first and the predicted class second.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfusionMatrix {
+    classes: Vec<String>,
+    counts: Vec<Vec<u64>>,
+}
+
+/// The accuracy assessment of a classification that is derived from a `ConfusionMatrix`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccuracyAssessment {
+    /// The class names in the order of the rows and columns of the `confusion_matrix`
+    pub classes: Vec<String>,
+    /// The counts of the reference classes (rows) and the predicted classes (columns)
+    pub confusion_matrix: Vec<Vec<u64>>,
+    pub sample_count: u64,
+    /// The share of correctly classified samples, if there are any samples
+    pub overall_accuracy: Option<f64>,
+    /// Cohen's kappa, if the agreement by chance is below one
+    pub kappa: Option<f64>,
+    pub class_accuracies: Vec<ClassAccuracy>,
+}
+
+/// The accuracy of a single class.
+/// Metrics that would require a division by zero are omitted.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassAccuracy {
+    pub class: String,
+    /// The number of samples that have this class as reference
+    pub reference_count: u64,
+    /// The number of samples that are classified as this class
+    pub predicted_count: u64,
+    /// The share of samples classified as this class that have this class as reference (user's accuracy)
+    pub precision: Option<f64>,
+    /// The share of samples with this class as reference that are classified as this class (producer's accuracy)
+    pub recall: Option<f64>,
+    /// The harmonic mean of `precision` and `recall`
+    pub f1_score: Option<f64>,
+}
+
+impl ConfusionMatrix {
+    /// Creates a new empty confusion matrix for the given classes
+    pub fn new(classes: Vec<String>) -> Self {
+        let counts = vec![vec![0; classes.len()]; classes.len()];
+        Self { classes, counts }
+    }
+
+    /// Creates a confusion matrix from the counts of the reference classes (rows) and the predicted classes (columns)
+    ///
+    /// # Errors
+    /// This method fails if the `counts` are not a square matrix with a row for each class.
+    pub fn from_counts(classes: Vec<String>, counts: Vec<Vec<u64>>) -> Result<Self> {
+        ensure!(
+            counts.len() == classes.len() && counts.iter().all(|row| row.len() == classes.len()),
+            error::Plot {
+                details: format!(
+                    "The confusion matrix must have {n} rows and {n} columns.",
+                    n = classes.len()
+                )
+            }
+        );
+
+        Ok(Self { classes, counts })
+    }
+
+    /// Adds a sample with the class index of the reference and the class index of the prediction
+    ///
+    /// # Panics
+    /// Panics if one of the indices is not a valid class index.
+    pub fn add(&mut self, reference: usize, predicted: usize) {
+        self.add_count(reference, predicted, 1);
+    }
+
+    /// Adds `count` samples with the class index of the reference and the class index of the prediction
+    ///
+    /// # Panics
+    /// Panics if one of the indices is not a valid class index.
+    pub fn add_count(&mut self, reference: usize, predicted: usize, count: u64) {
+        self.counts[reference][predicted] += count;
+    }
+
+    pub fn classes(&self) -> &[String] {
+        &self.classes
+    }
+
+    pub fn counts(&self) -> &[Vec<u64>] {
+        &self.counts
+    }
+
+    /// Returns the total number of samples
+    pub fn sample_count(&self) -> u64 {
+        self.counts.iter().flatten().sum()
+    }
+
+    /// Computes the overall accuracy, Cohen's kappa and the precision, recall and F1 score of each class
+    pub fn accuracy_assessment(&self) -> AccuracyAssessment {
+        let n = self.classes.len();
+        let total = self.sample_count();
+
+        let reference_counts = self
+            .counts
+            .iter()
+            .map(|row| row.iter().sum::<u64>())
+            .collect::<Vec<_>>();
+        let predicted_counts = (0..n)
+            .map(|column| self.counts.iter().map(|row| row[column]).sum::<u64>())
+            .collect::<Vec<_>>();
+        let correct_counts = (0..n).map(|i| self.counts[i][i]).collect::<Vec<_>>();
+
+        let ratio = |numerator: u64, denominator: u64| {
+            (denominator > 0).then(|| numerator as f64 / denominator as f64)
+        };
+
+        let overall_accuracy = ratio(correct_counts.iter().sum(), total);
+
+        let kappa = overall_accuracy.and_then(|observed_agreement| {
+            let total = total as f64;
+            let chance_agreement = reference_counts
+                .iter()
+                .zip(&predicted_counts)
+                .map(|(&r, &p)| r as f64 * p as f64)
+                .sum::<f64>()
+                / (total * total);
+
+            (chance_agreement < 1.)
+                .then(|| (observed_agreement - chance_agreement) / (1. - chance_agreement))
+        });
+
+        let class_accuracies = (0..n)
+            .map(|i| {
+                let precision = ratio(correct_counts[i], predicted_counts[i]);
+                let recall = ratio(correct_counts[i], reference_counts[i]);
+                let f1_score = precision
+                    .zip(recall)
+                    .filter(|(precision, recall)| precision + recall > 0.)
+                    .map(|(precision, recall)| 2. * precision * recall / (precision + recall));
+
+                ClassAccuracy {
+                    class: self.classes[i].clone(),
+                    reference_count: reference_counts[i],
+                    predicted_count: predicted_counts[i],
+                    precision,
+                    recall,
+                    f1_score,
+                }
+            })
+            .collect();
+
+        AccuracyAssessment {
+            classes: self.classes.clone(),
+            confusion_matrix: self.counts.clone(),
+            sample_count: total,
+            overall_accuracy,
+            kappa,
+            class_accuracies,
+        }
+    }
+}
+
+impl Plot for ConfusionMatrix {
+    fn to_vega_embeddable(&self, _allow_interactions: bool) -> Result<PlotData> {
+        let mut values = Vec::with_capacity(self.classes.len() * self.classes.len());
+        for (reference, row) in self.classes.iter().zip(&self.counts) {
+            for (predicted, count) in self.classes.iter().zip(row) {
+                values.push(serde_json::json!({
+                    "reference": reference,
+                    "predicted": predicted,
+                    "count": count,
+                }));
+            }
+        }
+
+        let vega_spec = serde_json::json!({
+            "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+            "width": "container",
+            "height": "container",
+            "data": {
+                "values": values,
+            },
+            "encoding": {
+                "x": {
+                    "field": "predicted",
+                    "type": "nominal",
+                    "sort": self.classes,
+                    "axis": {
+                        "title": "Predicted",
+                        "labelAngle": -45,
+                    },
+                },
+                "y": {
+                    "field": "reference",
+                    "type": "nominal",
+                    "sort": self.classes,
+                    "axis": {
+                        "title": "Reference",
+                    },
+                },
+            },
+            "layer": [
+                {
+                    "mark": "rect",
+                    "encoding": {
+                        "color": {
+                            "field": "count",
+                            "type": "quantitative",
+                            "title": "Count",
+                        },
+                    },
+                },
+                {
+                    "mark": "text",
+                    "encoding": {
+                        "text": {
+                            "field": "count",
+                            "type": "quantitative",
+                        },
+                    },
+                },
+            ],
+        });
+
+        Ok(PlotData {
+            vega_string: vega_spec.to_string(),
+            metadata: PlotMetaData::None,
+        })
+    }
+
+    fn to_figure(&self) -> Figure {
+        let max_count = self
+            .counts
+            .iter()
+            .flatten()
+            .copied()
+            .max()
+            .unwrap_or_default();
+
+        let mut figure = Figure::new(
+            Axis::band("Predicted", self.classes.clone()),
+            Axis::band("Reference", self.classes.clone()),
+        );
+
+        for (reference, row) in self.counts.iter().enumerate() {
+            for (predicted, &count) in row.iter().enumerate() {
+                let fraction = if max_count > 0 {
+                    count as f64 / max_count as f64
+                } else {
+                    0.
+                };
+                let (x, y) = (predicted as f64, reference as f64);
+
+                figure.add_mark(Mark::Rect {
+                    x: (x, x + 1.),
+                    y: (y, y + 1.),
+                    color: ColorRamp::Blues.color(fraction),
+                });
+                figure.add_mark(Mark::Text {
+                    x: x + 0.5,
+                    y: y + 0.5,
+                    text: count.to_string(),
+                    color: if fraction > 0.5 {
+                        RgbaColor::white()
+                    } else {
+                        RgbaColor::black()
+                    },
+                });
+            }
+        }
+
+        figure.set_legend_title("Count");
+        figure.set_legend_gradient(0., max_count as f64, ColorRamp::Blues);
+
+        figure
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_cmp::approx_eq;
+
+    #[test]
+    fn accuracy_assessment() {
+        // example from Congalton & Green, Assessing the Accuracy of Remotely Sensed Data
+        let matrix = ConfusionMatrix::from_counts(
+            vec![
+                "D".to_string(),
+                "C".to_string(),
+                "AG".to_string(),
+                "SB".to_string(),
+            ],
+            vec![
+                vec![65, 6, 0, 4],
+                vec![4, 81, 11, 7],
+                vec![22, 5, 85, 3],
+                vec![24, 8, 19, 90],
+            ],
+        )
+        .unwrap();
+
+        let assessment = matrix.accuracy_assessment();
+
+        assert_eq!(assessment.sample_count, 434);
+        assert!(approx_eq!(
+            f64,
+            assessment.overall_accuracy.unwrap(),
+            321. / 434.
+        ));
+        assert!(approx_eq!(
+            f64,
+            assessment.kappa.unwrap(),
+            0.653_516_270_788_882_4,
+            epsilon = 1e-9
+        ));
+
+        let d = &assessment.class_accuracies[0];
+        assert_eq!(d.class, "D");
+        assert_eq!(d.reference_count, 75);
+        assert_eq!(d.predicted_count, 115);
+        assert!(approx_eq!(f64, d.precision.unwrap(), 65. / 115.));
+        assert!(approx_eq!(f64, d.recall.unwrap(), 65. / 75.));
+        assert!(approx_eq!(
+            f64,
+            d.f1_score.unwrap(),
+            2. * 65. / (75. + 115.),
+            epsilon = 1e-12
+        ));
+    }
+
+    #[test]
+    fn accuracy_assessment_without_samples() {
+        let mut matrix = ConfusionMatrix::new(vec!["a".to_string(), "b".to_string()]);
+
+        let assessment = matrix.accuracy_assessment();
+        assert_eq!(assessment.sample_count, 0);
+        assert_eq!(assessment.overall_accuracy, None);
+        assert_eq!(assessment.kappa, None);
+        assert_eq!(assessment.class_accuracies[0].precision, None);
+
+        // a single class leads to a perfect agreement by chance
+        matrix.add_count(0, 0, 3);
+
+        let assessment = matrix.accuracy_assessment();
+        assert_eq!(assessment.overall_accuracy, Some(1.));
+        assert_eq!(assessment.kappa, None);
+        assert_eq!(assessment.class_accuracies[0].f1_score, Some(1.));
+        assert_eq!(assessment.class_accuracies[1].precision, None);
+        assert_eq!(assessment.class_accuracies[1].recall, None);
+    }
+
+    #[test]
+    fn it_rejects_non_square_counts() {
+        assert!(
+            ConfusionMatrix::from_counts(
+                vec!["a".to_string(), "b".to_string()],
+                vec![vec![1, 2], vec![3]]
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_to_vega_embeddable() {
+        let matrix = ConfusionMatrix::from_counts(
+            vec!["a".to_string(), "b".to_string()],
+            vec![vec![1, 2], vec![0, 3]],
+        )
+        .unwrap();
+
+        assert_eq!(
+            matrix.to_vega_embeddable(false).unwrap(),
+            PlotData {
+                vega_string: serde_json::json!({
+                    "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+                    "width": "container",
+                    "height": "container",
+                    "data": {
+                        "values": [
+                            { "reference": "a", "predicted": "a", "count": 1 },
+                            { "reference": "a", "predicted": "b", "count": 2 },
+                            { "reference": "b", "predicted": "a", "count": 0 },
+                            { "reference": "b", "predicted": "b", "count": 3 },
+                        ]
+                    },
+                    "encoding": {
+                        "x": {
+                            "field": "predicted",
+                            "type": "nominal",
+                            "sort": ["a", "b"],
+                            "axis": {
+                                "title": "Predicted",
+                                "labelAngle": -45,
+                            },
+                        },
+                        "y": {
+                            "field": "reference",
+                            "type": "nominal",
+                            "sort": ["a", "b"],
+                            "axis": {
+                                "title": "Reference",
+                            },
+                        },
+                    },
+                    "layer": [
+                        {
+                            "mark": "rect",
+                            "encoding": {
+                                "color": {
+                                    "field": "count",
+                                    "type": "quantitative",
+                                    "title": "Count",
+                                },
+                            },
+                        },
+                        {
+                            "mark": "text",
+                            "encoding": {
+                                "text": {
+                                    "field": "count",
+                                    "type": "quantitative",
+                                },
+                            },
+                        },
+                    ],
+                })
+                .to_string(),
+                metadata: PlotMetaData::None,
+            }
+        );
+    }
+}
@@ -0,0 +1,316 @@
+use serde::{Deserialize, Serialize};
+
+use crate::operations::image::RgbaColor;
+use crate::plots::{Axis, ColorRamp, Figure, Mark, Plot, PlotData, PlotMetaData};
+use crate::util::Result;
+
+/// A matrix of the pairwise Pearson correlation coefficients of a set of attributes.
+///
+/// The coefficients are computed in a single pass over the samples.
+/// Each pair of attributes uses all samples that have a value for both attributes.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrelationMatrix {
+    attributes: Vec<String>,
+    /// The moments of each pair of attributes `(i, j)` with `i <= j`, stored row-wise
+    moments: Vec<PairMoments>,
+}
+
+/// The co-moments of two attributes, updated with Welford's online algorithm
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PairMoments {
+    count: u64,
+    mean_x: f64,
+    mean_y: f64,
+    m2_x: f64,
+    m2_y: f64,
+    co_moment: f64,
+}
+
+impl PairMoments {
+    fn update(&mut self, x: f64, y: f64) {
+        self.count += 1;
+        let n = self.count as f64;
+
+        let dx = x - self.mean_x;
+        self.mean_x += dx / n;
+        let dy = y - self.mean_y;
+        self.mean_y += dy / n;
+
+        self.m2_x += dx * (x - self.mean_x);
+        self.m2_y += dy * (y - self.mean_y);
+        self.co_moment += dx * (y - self.mean_y);
+    }
+
+    fn correlation(&self) -> Option<f64> {
+        if self.count < 2 || self.m2_x <= 0. || self.m2_y <= 0. {
+            return None;
+        }
+
+        Some((self.co_moment / (self.m2_x * self.m2_y).sqrt()).clamp(-1., 1.))
+    }
+}
+
+impl CorrelationMatrix {
+    /// Creates a new empty correlation matrix for the given attributes
+    pub fn new(attributes: Vec<String>) -> Self {
+        let n = attributes.len();
+        Self {
+            attributes,
+            moments: vec![PairMoments::default(); n * (n + 1) / 2],
+        }
+    }
+
+    fn pair_index(&self, i: usize, j: usize) -> usize {
+        let (i, j) = if i <= j { (i, j) } else { (j, i) };
+        let n = self.attributes.len();
+        // skip the `i` previous rows of decreasing length
+        i * n - i * (i.saturating_sub(1)) / 2 - i + j
+    }
+
+    /// Adds a sample with a value (or no value) for each attribute
+    ///
+    /// # Panics
+    /// Panics if the sample does not have a value for each attribute.
+    pub fn update(&mut self, sample: &[Option<f64>]) {
+        assert_eq!(sample.len(), self.attributes.len());
+
+        for (i, x) in sample.iter().enumerate() {
+            let Some(x) = x.filter(|x| x.is_finite()) else {
+                continue;
+            };
+
+            for (j, y) in sample.iter().enumerate().skip(i) {
+                let Some(y) = y.filter(|y| y.is_finite()) else {
+                    continue;
+                };
+
+                let index = self.pair_index(i, j);
+                self.moments[index].update(x, y);
+            }
+        }
+    }
+
+    pub fn attributes(&self) -> &[String] {
+        &self.attributes
+    }
+
+    /// Returns the correlation coefficient of the attributes `i` and `j`.
+    /// It is `None` if there are fewer than two samples or one of the attributes is constant.
+    pub fn correlation(&self, i: usize, j: usize) -> Option<f64> {
+        self.moments[self.pair_index(i, j)].correlation()
+    }
+
+    /// Returns the number of samples that have a value for both attributes `i` and `j`
+    pub fn sample_count(&self, i: usize, j: usize) -> u64 {
+        self.moments[self.pair_index(i, j)].count
+    }
+}
+
+impl Plot for CorrelationMatrix {
+    fn to_vega_embeddable(&self, _allow_interactions: bool) -> Result<PlotData> {
+        let n = self.attributes.len();
+
+        let mut values = Vec::with_capacity(n * n);
+        for (i, x) in self.attributes.iter().enumerate() {
+            for (j, y) in self.attributes.iter().enumerate() {
+                values.push(serde_json::json!({
+                    "x": x,
+                    "y": y,
+                    "correlation": self.correlation(i, j),
+                    "count": self.sample_count(i, j),
+                }));
+            }
+        }
+
+        let vega_spec = serde_json::json!({
+            "$schema": "https://vega.github.io/schema/vega-lite/v5.json",
+            "width": "container",
+            "height": "container",
+            "data": {
+                "values": values,
+            },
+            "encoding": {
+                "x": {
+                    "field": "x",
+                    "type": "nominal",
+                    "sort": self.attributes,
+                    "axis": {
+                        "title": null,
+                        "labelAngle": -45,
+                    },
+                },
+                "y": {
+                    "field": "y",
+                    "type": "nominal",
+                    "sort": self.attributes,
+                    "axis": {
+                        "title": null,
+                    },
+                },
+            },
+            "layer": [
+                {
+                    "mark": "rect",
+                    "encoding": {
+                        "color": {
+                            "field": "correlation",
+                            "type": "quantitative",
+                            "title": "Correlation",
+                            "scale": {
+                                "scheme": "redblue",
+                                "domain": [-1, 1],
+                            },
+                        },
+                        "tooltip": [
+                            { "field": "x", "type": "nominal" },
+                            { "field": "y", "type": "nominal" },
+                            { "field": "correlation", "type": "quantitative", "format": ".3f" },
+                            { "field": "count", "type": "quantitative" },
+                        ],
+                    },
+                },
+                {
+                    "mark": "text",
+                    "encoding": {
+                        "text": {
+                            "field": "correlation",
+                            "type": "quantitative",
+                            "format": ".2f",
+                        },
+                    },
+                },
+            ],
+        });
+
+        Ok(PlotData {
+            vega_string: vega_spec.to_string(),
+            metadata: PlotMetaData::None,
+        })
+    }
+
+    fn to_figure(&self) -> Figure {
+        let n = self.attributes.len();
+
+        let mut figure = Figure::new(
+            Axis::band("", self.attributes.clone()),
+            Axis::band("", self.attributes.clone()),
+        );
+
+        for i in 0..n {
+            for j in 0..n {
+                let (x, y) = (i as f64, j as f64);
+                let correlation = self.correlation(i, j);
+
+                figure.add_mark(Mark::Rect {
+                    x: (x, x + 1.),
+                    y: (y, y + 1.),
+                    color: correlation.map_or(RgbaColor::new(221, 221, 221, 255), |c| {
+                        ColorRamp::RedBlue.color((c + 1.) / 2.)
+                    }),
+                });
+
+                if let Some(correlation) = correlation {
+                    figure.add_mark(Mark::Text {
+                        x: x + 0.5,
+                        y: y + 0.5,
+                        text: format!("{correlation:.2}"),
+                        color: if correlation.abs() > 0.5 {
+                            RgbaColor::white()
+                        } else {
+                            RgbaColor::black()
+                        },
+                    });
+                }
+            }
+        }
+
+        figure.set_legend_title("Correlation");
+        figure.set_legend_gradient(-1., 1., ColorRamp::RedBlue);
+
+        figure
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_cmp::approx_eq;
+
+    #[test]
+    fn correlations() {
+        let mut matrix = CorrelationMatrix::new(vec![
+            "a".to_string(),
+            "b".to_string(),
+            "c".to_string(),
+            "d".to_string(),
+        ]);
+
+        for (a, b, c, d) in [
+            (1., 2., 5., 7.),
+            (2., 4., 4., 7.),
+            (3., 6., 3., 7.),
+            (4., 8., 2., 7.),
+            (5., 10., 10., 7.),
+        ] {
+            matrix.update(&[Some(a), Some(b), Some(c), Some(d)]);
+        }
+        matrix.update(&[Some(6.), None, Some(f64::NAN), Some(7.)]);
+
+        assert!(approx_eq!(f64, matrix.correlation(0, 0).unwrap(), 1.));
+        assert!(approx_eq!(f64, matrix.correlation(0, 1).unwrap(), 1.));
+        assert!(approx_eq!(f64, matrix.correlation(1, 0).unwrap(), 1.));
+
+        // reference value computed with Python's `statistics.correlation`
+        assert!(approx_eq!(
+            f64,
+            matrix.correlation(0, 2).unwrap(),
+            0.406_138_466_053_447_56,
+            epsilon = 1e-12
+        ));
+
+        // constant attribute
+        assert_eq!(matrix.correlation(0, 3), None);
+        assert_eq!(matrix.correlation(3, 3), None);
+
+        assert_eq!(matrix.sample_count(0, 0), 6);
+        assert_eq!(matrix.sample_count(0, 1), 5);
+        assert_eq!(matrix.sample_count(2, 3), 5);
+        assert_eq!(matrix.sample_count(3, 3), 6);
+    }
+
+    #[test]
+    fn pair_indices_are_unique() {
+        let matrix = CorrelationMatrix::new(vec!["a".to_string(); 5]);
+
+        let mut indices = (0..5)
+            .flat_map(|i| (i..5).map(move |j| (i, j)))
+            .map(|(i, j)| matrix.pair_index(i, j))
+            .collect::<Vec<_>>();
+        indices.sort_unstable();
+
+        assert_eq!(indices, (0..15).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_to_vega_embeddable() {
+        let mut matrix = CorrelationMatrix::new(vec!["a".to_string(), "b".to_string()]);
+        matrix.update(&[Some(1.), Some(2.)]);
+        matrix.update(&[Some(2.), Some(1.)]);
+
+        let plot_data = matrix.to_vega_embeddable(false).unwrap();
+        let vega: serde_json::Value = serde_json::from_str(&plot_data.vega_string).unwrap();
+
+        assert_eq!(
+            vega["data"]["values"],
+            serde_json::json!([
+                { "x": "a", "y": "a", "correlation": 1.0, "count": 2 },
+                { "x": "a", "y": "b", "correlation": -1.0, "count": 2 },
+                { "x": "b", "y": "a", "correlation": -1.0, "count": 2 },
+                { "x": "b", "y": "b", "correlation": 1.0, "count": 2 },
+            ])
+        );
+        assert_eq!(plot_data.metadata, PlotMetaData::None);
+    }
+}
@@ -0,0 +1,1156 @@
+//! A description of a chart that can be rendered to SVG and PNG without a browser.
+//!
+//! Plots describe their content as [`Mark`]s in data coordinates.
+//! The [`Figure`] takes care of the layout, i.e., the axes, their ticks and the legend.
+
+mod font;
+mod png;
+mod svg;
+
+use crate::operations::image::RgbaColor;
+use crate::primitives::{DateTimeParseFormat, TimeInstance};
+use crate::util::Result;
+
+/// The margin around the figure in pixels
+const MARGIN: f64 = 10.;
+/// The gap between neighboring elements, e.g., tick labels and their ticks
+const GAP: f64 = 6.;
+const TICK_SIZE: f64 = 5.;
+/// The size of a font pixel in image pixels
+const FONT_SCALE: f64 = 2.;
+const TEXT_HEIGHT: f64 = font::GLYPH_HEIGHT as f64 * FONT_SCALE;
+/// Longer labels are truncated
+const MAX_LABEL_CHARS: usize = 24;
+const LEGEND_SWATCH_SIZE: f64 = 12.;
+const LEGEND_LINE_HEIGHT: f64 = 20.;
+const LEGEND_GRADIENT_WIDTH: f64 = 14.;
+const LEGEND_GRADIENT_HEIGHT: f64 = 150.;
+const LINE_WIDTH: f64 = 2.;
+
+const AXIS_COLOR: [u8; 4] = [136, 136, 136, 255];
+const GRID_COLOR: [u8; 4] = [221, 221, 221, 255];
+const TEXT_COLOR: [u8; 4] = [51, 51, 51, 255];
+
+/// The categorical colors, cf. the `tableau10` scheme of Vega
+const CATEGORY_COLORS: [[u8; 3]; 10] = [
+    [0x4c, 0x78, 0xa8],
+    [0xf5, 0x85, 0x18],
+    [0xe4, 0x57, 0x56],
+    [0x72, 0xb7, 0xb2],
+    [0x54, 0xa2, 0x4b],
+    [0xee, 0xca, 0x3b],
+    [0xb2, 0x79, 0xa2],
+    [0xff, 0x9d, 0xa6],
+    [0x9d, 0x75, 0x5d],
+    [0xba, 0xb0, 0xac],
+];
+
+/// Returns the color of the `index`-th category. Colors repeat after ten categories.
+pub fn category_color(index: usize) -> RgbaColor {
+    let [r, g, b] = CATEGORY_COLORS[index % CATEGORY_COLORS.len()];
+    RgbaColor::new(r, g, b, 255)
+}
+
+/// Returns the minimum and maximum of the finite values, or `(0, 1)` if there are none
+pub(super) fn value_range(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    values
+        .filter(|value| value.is_finite())
+        .fold(None, |range: Option<(f64, f64)>, value| {
+            Some(range.map_or((value, value), |(min, max)| {
+                (min.min(value), max.max(value))
+            }))
+        })
+        .unwrap_or((0., 1.))
+}
+
+/// A chart of marks, optionally with axes and a legend
+#[derive(Debug, Clone, PartialEq)]
+pub struct Figure {
+    axes: Option<(Axis, Axis)>,
+    marks: Vec<Mark>,
+    legend: Legend,
+}
+
+impl Figure {
+    /// Creates a figure with an x and a y axis
+    pub fn new(x_axis: Axis, y_axis: Axis) -> Self {
+        Self {
+            axes: Some((x_axis, y_axis)),
+            marks: Vec::new(),
+            legend: Legend::default(),
+        }
+    }
+
+    /// Creates a figure without axes, e.g., for pie charts
+    pub fn without_axes() -> Self {
+        Self {
+            axes: None,
+            marks: Vec::new(),
+            legend: Legend::default(),
+        }
+    }
+
+    /// Adds a mark on top of the existing marks
+    pub fn add_mark(&mut self, mark: Mark) {
+        self.marks.push(mark);
+    }
+
+    pub fn set_legend_title(&mut self, title: impl Into<String>) {
+        self.legend.title = Some(title.into()).filter(|title| !title.is_empty());
+    }
+
+    pub fn add_legend_entry(&mut self, label: impl Into<String>, color: RgbaColor) {
+        self.legend.entries.push((label.into(), color));
+    }
+
+    /// Shows the `ramp` for values from `min` to `max` in the legend
+    pub fn set_legend_gradient(&mut self, min: f64, max: f64, ramp: ColorRamp) {
+        self.legend.gradient = Some(LegendGradient { min, max, ramp });
+    }
+
+    /// Renders the figure as an SVG document of the given size in pixels
+    pub fn to_svg(&self, width: u32, height: u32) -> String {
+        let mut canvas = svg::SvgCanvas::new(width.max(1), height.max(1));
+        self.render(
+            &mut canvas,
+            f64::from(width.max(1)),
+            f64::from(height.max(1)),
+        );
+        canvas.into_svg()
+    }
+
+    /// Renders the figure as a PNG image of the given size in pixels
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the image cannot be encoded.
+    ///
+    pub fn to_png(&self, width: u32, height: u32) -> Result<Vec<u8>> {
+        let mut canvas = png::PngCanvas::new(width.max(1), height.max(1));
+        self.render(
+            &mut canvas,
+            f64::from(width.max(1)),
+            f64::from(height.max(1)),
+        );
+        canvas.into_png()
+    }
+
+    fn render(&self, canvas: &mut impl Canvas, width: f64, height: f64) {
+        canvas.rect(PixelRect::new(0., 0., width, height), RgbaColor::white());
+
+        let legend_width = self.legend.width().min(width / 3.);
+        let legend_space = if legend_width > 0. {
+            legend_width + 2. * GAP
+        } else {
+            0.
+        };
+        let mut area = PixelRect::new(
+            MARGIN,
+            MARGIN,
+            width - MARGIN - legend_space,
+            height - MARGIN,
+        );
+
+        if legend_width > 0. {
+            self.legend
+                .render(canvas, width - MARGIN - legend_width, MARGIN, height);
+        }
+
+        let Some((x_axis, y_axis)) = &self.axes else {
+            area.ensure_non_empty();
+            canvas.set_clip(Some(area));
+            for mark in &self.marks {
+                mark.render_without_axes(canvas, area);
+            }
+            canvas.set_clip(None);
+            return;
+        };
+
+        let y_ticks = y_axis.resolve(tick_count(area.height(), 50.));
+        area.x0 += y_ticks.max_label_width() + TICK_SIZE + GAP;
+        if y_axis.title.is_some() {
+            area.x0 += TEXT_HEIGHT + GAP;
+        }
+
+        let x_spacing = if matches!(x_axis.scale, Scale::Time { .. }) {
+            150.
+        } else {
+            80.
+        };
+        let x_ticks = x_axis.resolve(tick_count(area.width(), x_spacing));
+        let vertical_x_labels = matches!(x_axis.scale, Scale::Band { .. })
+            && x_ticks.max_label_width() + GAP > area.width() / x_ticks.domain_span();
+        area.y1 -= TICK_SIZE
+            + GAP
+            + if vertical_x_labels {
+                x_ticks.max_label_width()
+            } else {
+                TEXT_HEIGHT
+            };
+        if x_axis.title.is_some() {
+            area.y1 -= TEXT_HEIGHT + GAP;
+        }
+        area.ensure_non_empty();
+
+        let transform = Transform {
+            area,
+            x: &x_ticks,
+            y: &y_ticks,
+            y_top_down: matches!(y_axis.scale, Scale::Band { .. }),
+        };
+
+        if !x_ticks.is_band {
+            for tick in &x_ticks.ticks {
+                let x = transform.x(tick.value);
+                canvas.polyline(&[(x, area.y0), (x, area.y1)], 1., color(GRID_COLOR));
+            }
+        }
+        if !y_ticks.is_band {
+            for tick in &y_ticks.ticks {
+                let y = transform.y(tick.value);
+                canvas.polyline(&[(area.x0, y), (area.x1, y)], 1., color(GRID_COLOR));
+            }
+        }
+
+        canvas.set_clip(Some(area));
+        for mark in &self.marks {
+            mark.render(canvas, &transform);
+        }
+        canvas.set_clip(None);
+
+        canvas.polyline(
+            &[(area.x0, area.y0), (area.x0, area.y1), (area.x1, area.y1)],
+            1.,
+            color(AXIS_COLOR),
+        );
+
+        for tick in &x_ticks.ticks {
+            let x = transform.x(tick.value);
+            canvas.polyline(
+                &[(x, area.y1), (x, area.y1 + TICK_SIZE)],
+                1.,
+                color(AXIS_COLOR),
+            );
+            if vertical_x_labels {
+                canvas.text(
+                    (x, area.y1 + TICK_SIZE + GAP),
+                    &tick.label,
+                    TextAnchor::End,
+                    true,
+                    color(TEXT_COLOR),
+                );
+            } else {
+                canvas.text(
+                    (x, area.y1 + TICK_SIZE + GAP + TEXT_HEIGHT / 2.),
+                    &tick.label,
+                    TextAnchor::Middle,
+                    false,
+                    color(TEXT_COLOR),
+                );
+            }
+        }
+
+        for tick in &y_ticks.ticks {
+            let y = transform.y(tick.value);
+            canvas.polyline(
+                &[(area.x0 - TICK_SIZE, y), (area.x0, y)],
+                1.,
+                color(AXIS_COLOR),
+            );
+            canvas.text(
+                (area.x0 - TICK_SIZE - GAP, y),
+                &tick.label,
+                TextAnchor::End,
+                false,
+                color(TEXT_COLOR),
+            );
+        }
+
+        if let Some(title) = &x_axis.title {
+            canvas.text(
+                (
+                    f64::midpoint(area.x0, area.x1),
+                    height - MARGIN - TEXT_HEIGHT / 2.,
+                ),
+                &truncate(title),
+                TextAnchor::Middle,
+                false,
+                color(TEXT_COLOR),
+            );
+        }
+        if let Some(title) = &y_axis.title {
+            canvas.text(
+                (MARGIN + TEXT_HEIGHT / 2., f64::midpoint(area.y0, area.y1)),
+                &truncate(title),
+                TextAnchor::Middle,
+                true,
+                color(TEXT_COLOR),
+            );
+        }
+    }
+}
+
+/// An axis of a figure
+#[derive(Debug, Clone, PartialEq)]
+pub struct Axis {
+    title: Option<String>,
+    scale: Scale,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Scale {
+    /// A continuous scale. If `nice` is set, the domain is extended to the surrounding ticks.
+    Linear { min: f64, max: f64, nice: bool },
+    /// A continuous scale of milliseconds since the epoch
+    Time { min: f64, max: f64 },
+    /// A scale of categories, where the `i`-th category spans the domain `[i, i + 1]`
+    Band { labels: Vec<String> },
+}
+
+impl Axis {
+    /// Creates a continuous axis whose domain is extended to nice values.
+    /// An empty title is omitted.
+    pub fn linear(title: impl Into<String>, min: f64, max: f64) -> Self {
+        Self::new(
+            title,
+            Scale::Linear {
+                min,
+                max,
+                nice: true,
+            },
+        )
+    }
+
+    /// Creates a continuous axis with exactly the given domain, e.g., for binned data
+    pub fn linear_exact(title: impl Into<String>, min: f64, max: f64) -> Self {
+        Self::new(
+            title,
+            Scale::Linear {
+                min,
+                max,
+                nice: false,
+            },
+        )
+    }
+
+    /// Creates an axis of time instances, whose values are given in milliseconds
+    pub fn time(title: impl Into<String>, min: TimeInstance, max: TimeInstance) -> Self {
+        Self::new(
+            title,
+            Scale::Time {
+                min: min.inner() as f64,
+                max: max.inner() as f64,
+            },
+        )
+    }
+
+    /// Creates an axis of categories, where the `i`-th category spans the domain `[i, i + 1]`.
+    /// On a y axis, the categories are listed from top to bottom.
+    pub fn band(title: impl Into<String>, labels: Vec<String>) -> Self {
+        Self::new(title, Scale::Band { labels })
+    }
+
+    fn new(title: impl Into<String>, scale: Scale) -> Self {
+        let title = title.into();
+        Self {
+            title: Some(title).filter(|title| !title.is_empty()),
+            scale,
+        }
+    }
+
+    fn resolve(&self, tick_count: usize) -> Ticks {
+        match &self.scale {
+            Scale::Linear { min, max, nice } => {
+                let (mut min, mut max) = non_empty_domain(*min, *max, 1.);
+                let step = nice_step((max - min) / tick_count as f64);
+                if *nice {
+                    min = (min / step).floor() * step;
+                    max = (max / step).ceil() * step;
+                }
+                Ticks {
+                    min,
+                    max,
+                    ticks: tick_values(min, max, step)
+                        .map(|value| Tick {
+                            value,
+                            label: format_number(value, step),
+                        })
+                        .collect(),
+                    is_band: false,
+                }
+            }
+            Scale::Time { min, max } => {
+                let (min, max) = non_empty_domain(*min, *max, DAY);
+                let step = time_step((max - min) / tick_count as f64);
+                let format = DateTimeParseFormat::custom(
+                    if step >= DAY {
+                        "%Y-%m-%d"
+                    } else if step >= MINUTE {
+                        "%Y-%m-%d %H:%M"
+                    } else {
+                        "%H:%M:%S"
+                    }
+                    .to_string(),
+                );
+                Ticks {
+                    min,
+                    max,
+                    ticks: tick_values(min, max, step)
+                        .map(|value| Tick {
+                            value,
+                            label: TimeInstance::from_millis_unchecked(value as i64)
+                                .as_date_time()
+                                .map_or_else(String::new, |date_time| date_time.format(&format)),
+                        })
+                        .collect(),
+                    is_band: false,
+                }
+            }
+            Scale::Band { labels } => Ticks {
+                min: 0.,
+                max: labels.len().max(1) as f64,
+                ticks: labels
+                    .iter()
+                    .enumerate()
+                    .map(|(i, label)| Tick {
+                        value: i as f64 + 0.5,
+                        label: truncate(label),
+                    })
+                    .collect(),
+                is_band: true,
+            },
+        }
+    }
+}
+
+const MINUTE: f64 = 60. * 1000.;
+const DAY: f64 = 24. * 60. * MINUTE;
+
+/// The tick intervals of time axes in milliseconds. Larger intervals are multiples of years.
+const TIME_STEPS: [f64; 17] = [
+    1000.,
+    5000.,
+    15_000.,
+    30_000.,
+    MINUTE,
+    5. * MINUTE,
+    15. * MINUTE,
+    30. * MINUTE,
+    60. * MINUTE,
+    3. * 60. * MINUTE,
+    6. * 60. * MINUTE,
+    12. * 60. * MINUTE,
+    DAY,
+    2. * DAY,
+    7. * DAY,
+    30. * DAY,
+    91. * DAY,
+];
+
+fn time_step(raw_step: f64) -> f64 {
+    const YEAR: f64 = 365. * DAY;
+
+    if raw_step > TIME_STEPS[TIME_STEPS.len() - 1] {
+        return nice_step(raw_step / YEAR).max(1.) * YEAR;
+    }
+
+    TIME_STEPS
+        .iter()
+        .copied()
+        .find(|&step| step >= raw_step)
+        .unwrap_or(TIME_STEPS[0])
+}
+
+/// Rounds the step to 1, 2 or 5 times a power of ten
+fn nice_step(raw_step: f64) -> f64 {
+    if !raw_step.is_finite() || raw_step <= 0. {
+        return 1.;
+    }
+
+    let magnitude = 10_f64.powf(raw_step.log10().floor());
+    let fraction = raw_step / magnitude;
+    let nice_fraction = if fraction <= 1. {
+        1.
+    } else if fraction <= 2. {
+        2.
+    } else if fraction <= 5. {
+        5.
+    } else {
+        10.
+    };
+    nice_fraction * magnitude
+}
+
+fn non_empty_domain(min: f64, max: f64, padding: f64) -> (f64, f64) {
+    if !(min.is_finite() && max.is_finite()) {
+        return (0., padding);
+    }
+
+    if min < max {
+        (min, max)
+    } else if min == 0. {
+        (-padding, padding)
+    } else {
+        (min - min.abs() / 10., min + min.abs() / 10.)
+    }
+}
+
+fn tick_values(min: f64, max: f64, step: f64) -> impl Iterator<Item = f64> {
+    let epsilon = step * 1e-9;
+    let first = ((min - epsilon) / step).ceil();
+    (0_u32..)
+        .map(move |i| (first + f64::from(i)) * step)
+        .take_while(move |&value| value <= max + epsilon)
+}
+
+fn format_number(value: f64, step: f64) -> String {
+    if step.is_nan() || step <= 0. {
+        return value.to_string();
+    }
+
+    // avoid labels like `-0`
+    let value = if value.abs() < step * 1e-6 { 0. } else { value };
+
+    if value.abs() >= 1e7 {
+        return format!("{value:.1e}");
+    }
+
+    let decimals = if step >= 1. {
+        0
+    } else {
+        (-step.log10().floor()).min(10.) as usize
+    };
+    format!("{value:.decimals$}")
+}
+
+fn tick_count(length: f64, spacing: f64) -> usize {
+    ((length / spacing) as usize).max(2)
+}
+
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_LABEL_CHARS {
+        return text.to_string();
+    }
+
+    let mut truncated: String = text.chars().take(MAX_LABEL_CHARS - 2).collect();
+    truncated.push_str("..");
+    truncated
+}
+
+fn text_width(text: &str) -> f64 {
+    let chars = text.chars().count();
+    if chars == 0 {
+        return 0.;
+    }
+    (chars * font::ADVANCE) as f64 * FONT_SCALE - FONT_SCALE
+}
+
+fn color([r, g, b, a]: [u8; 4]) -> RgbaColor {
+    RgbaColor::new(r, g, b, a)
+}
+
+/// The resolved domain and ticks of an axis
+struct Ticks {
+    min: f64,
+    max: f64,
+    ticks: Vec<Tick>,
+    is_band: bool,
+}
+
+struct Tick {
+    value: f64,
+    label: String,
+}
+
+impl Ticks {
+    fn max_label_width(&self) -> f64 {
+        self.ticks
+            .iter()
+            .map(|tick| text_width(&tick.label))
+            .fold(0., f64::max)
+    }
+
+    fn domain_span(&self) -> f64 {
+        self.max - self.min
+    }
+
+    fn fraction(&self, value: f64) -> f64 {
+        (value - self.min) / self.domain_span()
+    }
+}
+
+/// Maps data coordinates to pixel coordinates
+struct Transform<'t> {
+    area: PixelRect,
+    x: &'t Ticks,
+    y: &'t Ticks,
+    y_top_down: bool,
+}
+
+impl Transform<'_> {
+    fn x(&self, value: f64) -> f64 {
+        self.area.x0 + self.x.fraction(value) * self.area.width()
+    }
+
+    fn y(&self, value: f64) -> f64 {
+        if self.y_top_down {
+            self.area.y0 + self.y.fraction(value) * self.area.height()
+        } else {
+            self.area.y1 - self.y.fraction(value) * self.area.height()
+        }
+    }
+
+    fn point(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        (self.x(x), self.y(y))
+    }
+}
+
+/// A graphical element of a figure in data coordinates
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mark {
+    /// A rectangle spanning the `x` and `y` ranges
+    Rect {
+        x: (f64, f64),
+        y: (f64, f64),
+        color: RgbaColor,
+    },
+    /// A line through the `points`
+    Line {
+        points: Vec<(f64, f64)>,
+        color: RgbaColor,
+    },
+    /// The area between the `points` and the horizontal `baseline`
+    Area {
+        points: Vec<(f64, f64)>,
+        baseline: f64,
+        color: RgbaColor,
+    },
+    /// A filled circle whose `radius` is given in pixels
+    Point {
+        x: f64,
+        y: f64,
+        radius: f64,
+        color: RgbaColor,
+    },
+    /// A slice of a pie that fills the plot area.
+    /// The angles are in radians, clockwise from twelve o'clock,
+    /// and the `inner_radius` is a fraction of the outer radius.
+    Wedge {
+        start_angle: f64,
+        end_angle: f64,
+        inner_radius: f64,
+        color: RgbaColor,
+    },
+    /// A text that is centered at the position
+    Text {
+        x: f64,
+        y: f64,
+        text: String,
+        color: RgbaColor,
+    },
+}
+
+impl Mark {
+    fn render(&self, canvas: &mut impl Canvas, transform: &Transform) {
+        match self {
+            Mark::Rect { x, y, color } => {
+                let (x0, y0) = transform.point((x.0, y.0));
+                let (x1, y1) = transform.point((x.1, y.1));
+                canvas.rect(
+                    PixelRect::new(x0.min(x1), y0.min(y1), x0.max(x1), y0.max(y1)),
+                    *color,
+                );
+            }
+            Mark::Line { points, color } => {
+                let points: Vec<_> = points.iter().map(|&p| transform.point(p)).collect();
+                canvas.polyline(&points, LINE_WIDTH, *color);
+            }
+            Mark::Area {
+                points,
+                baseline,
+                color,
+            } => {
+                let (Some(first), Some(last)) = (points.first(), points.last()) else {
+                    return;
+                };
+                let mut polygon: Vec<_> = points.iter().map(|&p| transform.point(p)).collect();
+                polygon.push(transform.point((last.0, *baseline)));
+                polygon.push(transform.point((first.0, *baseline)));
+                canvas.polygon(&polygon, *color);
+            }
+            Mark::Point {
+                x,
+                y,
+                radius,
+                color,
+            } => canvas.circle(transform.point((*x, *y)), *radius, *color),
+            Mark::Wedge { .. } => self.render_without_axes(canvas, transform.area),
+            Mark::Text { x, y, text, color } => canvas.text(
+                transform.point((*x, *y)),
+                &truncate(text),
+                TextAnchor::Middle,
+                false,
+                *color,
+            ),
+        }
+    }
+
+    fn render_without_axes(&self, canvas: &mut impl Canvas, area: PixelRect) {
+        let Mark::Wedge {
+            start_angle,
+            end_angle,
+            inner_radius,
+            color,
+        } = self
+        else {
+            // other marks need axes
+            return;
+        };
+
+        let center = (
+            f64::midpoint(area.x0, area.x1),
+            f64::midpoint(area.y0, area.y1),
+        );
+        let radius = (area.width().min(area.height()) / 2. - GAP).max(1.);
+        let inner_radius = radius * inner_radius.clamp(0., 1.);
+
+        let segments = (((end_angle - start_angle).abs() / (2. * std::f64::consts::PI) * 180.)
+            .ceil() as usize)
+            .max(1);
+        let arc = move |radius: f64| {
+            (0..=segments).map(move |i| {
+                let angle = start_angle + (end_angle - start_angle) * i as f64 / segments as f64;
+                (
+                    center.0 + radius * angle.sin(),
+                    center.1 - radius * angle.cos(),
+                )
+            })
+        };
+
+        let mut polygon: Vec<_> = arc(radius).collect();
+        if inner_radius > 0. {
+            let inner: Vec<_> = arc(inner_radius).collect();
+            polygon.extend(inner.into_iter().rev());
+        } else {
+            polygon.push(center);
+        }
+        canvas.polygon(&polygon, *color);
+    }
+}
+
+/// A continuous color scheme for values in `[0, 1]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorRamp {
+    /// From light to dark blue, cf. the `blues` scheme of Vega
+    Blues,
+    /// From red over white to blue, cf. the `redblue` scheme of Vega
+    RedBlue,
+}
+
+impl ColorRamp {
+    /// Returns the color for the `fraction`, which is clamped to `[0, 1]`
+    pub fn color(self, fraction: f64) -> RgbaColor {
+        let stops: &[[u8; 3]] = match self {
+            ColorRamp::Blues => &[
+                [0xcf, 0xe1, 0xf2],
+                [0x93, 0xc4, 0xde],
+                [0x4a, 0x98, 0xc9],
+                [0x21, 0x64, 0xab],
+                [0x0a, 0x4a, 0x90],
+            ],
+            ColorRamp::RedBlue => &[
+                [0x67, 0x00, 0x1f],
+                [0xd6, 0x60, 0x4d],
+                [0xf7, 0xf7, 0xf7],
+                [0x43, 0x93, 0xc3],
+                [0x05, 0x30, 0x61],
+            ],
+        };
+
+        let position = if fraction.is_finite() {
+            fraction.clamp(0., 1.) * (stops.len() - 1) as f64
+        } else {
+            0.
+        };
+        let index = (position.floor() as usize).min(stops.len() - 2);
+
+        let [r, g, b] = stops[index];
+        let [r2, g2, b2] = stops[index + 1];
+        RgbaColor::new(r, g, b, 255)
+            .factor_add(RgbaColor::new(r2, g2, b2, 255), position - index as f64)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Legend {
+    title: Option<String>,
+    entries: Vec<(String, RgbaColor)>,
+    gradient: Option<LegendGradient>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct LegendGradient {
+    min: f64,
+    max: f64,
+    ramp: ColorRamp,
+}
+
+impl LegendGradient {
+    fn labels(&self) -> (String, String) {
+        let step = (self.max - self.min).abs() / 10.;
+        (format_number(self.max, step), format_number(self.min, step))
+    }
+}
+
+impl Legend {
+    /// The width of the legend in pixels, which is zero if there is no legend
+    fn width(&self) -> f64 {
+        let title_width = self
+            .title
+            .as_deref()
+            .map_or(0., |title| text_width(&truncate(title)));
+
+        let entries_width = self
+            .entries
+            .iter()
+            .map(|(label, _)| LEGEND_SWATCH_SIZE + GAP + text_width(&truncate(label)))
+            .fold(0., f64::max);
+
+        let gradient_width = self.gradient.as_ref().map_or(0., |gradient| {
+            let (max, min) = gradient.labels();
+            LEGEND_GRADIENT_WIDTH + GAP + text_width(&max).max(text_width(&min))
+        });
+
+        if entries_width == 0. && gradient_width == 0. {
+            return 0.;
+        }
+
+        title_width.max(entries_width).max(gradient_width)
+    }
+
+    fn render(&self, canvas: &mut impl Canvas, x: f64, mut y: f64, height: f64) {
+        if let Some(title) = &self.title {
+            canvas.text(
+                (x, y + TEXT_HEIGHT / 2.),
+                &truncate(title),
+                TextAnchor::Start,
+                false,
+                color(TEXT_COLOR),
+            );
+            y += LEGEND_LINE_HEIGHT;
+        }
+
+        for (label, entry_color) in &self.entries {
+            canvas.rect(
+                PixelRect::new(
+                    x,
+                    y + (TEXT_HEIGHT - LEGEND_SWATCH_SIZE) / 2.,
+                    x + LEGEND_SWATCH_SIZE,
+                    y + (TEXT_HEIGHT + LEGEND_SWATCH_SIZE) / 2.,
+                ),
+                *entry_color,
+            );
+            canvas.text(
+                (x + LEGEND_SWATCH_SIZE + GAP, y + TEXT_HEIGHT / 2.),
+                &truncate(label),
+                TextAnchor::Start,
+                false,
+                color(TEXT_COLOR),
+            );
+            y += LEGEND_LINE_HEIGHT;
+        }
+
+        if let Some(gradient) = &self.gradient {
+            let gradient_height = LEGEND_GRADIENT_HEIGHT.min(height - MARGIN - y).max(0.);
+            let steps = (gradient_height / 2.).ceil().max(1.) as usize;
+            let step_height = gradient_height / steps as f64;
+            for i in 0..steps {
+                // the maximum is at the top
+                let fraction = 1. - (i as f64 + 0.5) / steps as f64;
+                canvas.rect(
+                    PixelRect::new(
+                        x,
+                        y + i as f64 * step_height,
+                        x + LEGEND_GRADIENT_WIDTH,
+                        y + (i + 1) as f64 * step_height,
+                    ),
+                    gradient.ramp.color(fraction),
+                );
+            }
+
+            let (max, min) = gradient.labels();
+            canvas.text(
+                (x + LEGEND_GRADIENT_WIDTH + GAP, y + TEXT_HEIGHT / 2.),
+                &max,
+                TextAnchor::Start,
+                false,
+                color(TEXT_COLOR),
+            );
+            canvas.text(
+                (
+                    x + LEGEND_GRADIENT_WIDTH + GAP,
+                    y + gradient_height - TEXT_HEIGHT / 2.,
+                ),
+                &min,
+                TextAnchor::Start,
+                false,
+                color(TEXT_COLOR),
+            );
+        }
+    }
+}
+
+/// A rectangle in pixel coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PixelRect {
+    x0: f64,
+    y0: f64,
+    x1: f64,
+    y1: f64,
+}
+
+impl PixelRect {
+    fn new(x0: f64, y0: f64, x1: f64, y1: f64) -> Self {
+        Self { x0, y0, x1, y1 }
+    }
+
+    fn width(&self) -> f64 {
+        self.x1 - self.x0
+    }
+
+    fn height(&self) -> f64 {
+        self.y1 - self.y0
+    }
+
+    /// Keeps at least one pixel for tiny images
+    fn ensure_non_empty(&mut self) {
+        self.x1 = self.x1.max(self.x0 + 1.);
+        self.y1 = self.y1.max(self.y0 + 1.);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TextAnchor {
+    Start,
+    Middle,
+    End,
+}
+
+/// The drawing primitives of the output formats in pixel coordinates
+trait Canvas {
+    fn rect(&mut self, rect: PixelRect, color: RgbaColor);
+
+    fn polygon(&mut self, points: &[(f64, f64)], color: RgbaColor);
+
+    fn polyline(&mut self, points: &[(f64, f64)], width: f64, color: RgbaColor);
+
+    fn circle(&mut self, center: (f64, f64), radius: f64, color: RgbaColor);
+
+    /// Draws a text that is vertically centered at the position.
+    /// A `vertical` text is rotated counterclockwise, i.e., it reads from bottom to top.
+    fn text(
+        &mut self,
+        position: (f64, f64),
+        text: &str,
+        anchor: TextAnchor,
+        vertical: bool,
+        color: RgbaColor,
+    );
+
+    /// Restricts the subsequent drawing to the `clip` rectangle, or removes the restriction
+    fn set_clip(&mut self, clip: Option<PixelRect>);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar_figure() -> Figure {
+        let mut figure = Figure::new(
+            Axis::band("Class", vec!["a".to_string(), "b".to_string()]),
+            Axis::linear("Frequency", 0., 7.),
+        );
+        figure.add_mark(Mark::Rect {
+            x: (0.1, 0.9),
+            y: (0., 3.),
+            color: category_color(0),
+        });
+        figure.add_mark(Mark::Rect {
+            x: (1.1, 1.9),
+            y: (0., 7.),
+            color: category_color(1),
+        });
+        figure.add_legend_entry("a", category_color(0));
+        figure.add_legend_entry("b", category_color(1));
+        figure
+    }
+
+    #[test]
+    fn nice_steps() {
+        assert_eq!(nice_step(0.13), 0.2);
+        assert_eq!(nice_step(3.), 5.);
+        assert_eq!(nice_step(7.), 10.);
+        assert_eq!(nice_step(1000.), 1000.);
+        assert_eq!(nice_step(0.), 1.);
+    }
+
+    #[test]
+    fn linear_ticks() {
+        let ticks = Axis::linear("", 0.3, 9.2).resolve(5);
+
+        assert_eq!(ticks.min, 0.);
+        assert_eq!(ticks.max, 10.);
+        assert_eq!(
+            ticks
+                .ticks
+                .iter()
+                .map(|tick| tick.label.as_str())
+                .collect::<Vec<_>>(),
+            vec!["0", "2", "4", "6", "8", "10"]
+        );
+
+        let ticks = Axis::linear_exact("", 0.3, 0.9).resolve(3);
+
+        assert_eq!(ticks.min, 0.3);
+        assert_eq!(ticks.max, 0.9);
+        assert_eq!(
+            ticks
+                .ticks
+                .iter()
+                .map(|tick| tick.label.as_str())
+                .collect::<Vec<_>>(),
+            vec!["0.4", "0.6", "0.8"]
+        );
+    }
+
+    #[test]
+    fn time_ticks() {
+        let ticks = Axis::time(
+            "Time",
+            TimeInstance::from_millis_unchecked(0),
+            TimeInstance::from_millis_unchecked(4 * DAY as i64),
+        )
+        .resolve(4);
+
+        assert_eq!(
+            ticks
+                .ticks
+                .iter()
+                .map(|tick| tick.label.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "1970-01-01",
+                "1970-01-02",
+                "1970-01-03",
+                "1970-01-04",
+                "1970-01-05"
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_domains() {
+        let ticks = Axis::linear("", 5., 5.).resolve(5);
+        assert!(ticks.min < 5. && ticks.max > 5.);
+
+        let ticks = Axis::linear("", f64::NAN, f64::NAN).resolve(5);
+        assert_eq!((ticks.min, ticks.max), (0., 1.));
+    }
+
+    #[test]
+    fn color_ramps() {
+        assert_eq!(
+            ColorRamp::Blues.color(0.),
+            RgbaColor::new(0xcf, 0xe1, 0xf2, 255)
+        );
+        assert_eq!(
+            ColorRamp::Blues.color(1.),
+            RgbaColor::new(0x0a, 0x4a, 0x90, 255)
+        );
+        assert_eq!(
+            ColorRamp::RedBlue.color(0.5),
+            RgbaColor::new(0xf7, 0xf7, 0xf7, 255)
+        );
+        assert_eq!(
+            ColorRamp::RedBlue.color(f64::NAN),
+            ColorRamp::RedBlue.color(0.)
+        );
+    }
+
+    #[test]
+    fn svg() {
+        let svg = bar_figure().to_svg(400, 300);
+
+        assert!(
+            svg.starts_with(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"400\" height=\"300\""
+            )
+        );
+        assert!(svg.ends_with("</svg>"));
+        assert!(svg.contains(">Frequency</text>"));
+        assert!(svg.contains(">Class</text>"));
+        assert!(svg.contains("fill=\"rgb(76,120,168)\""));
+        assert!(svg.contains("clip-path"));
+    }
+
+    #[test]
+    fn svg_escapes_text() {
+        let mut figure = Figure::without_axes();
+        figure.add_legend_entry("<a & b>", category_color(0));
+
+        let svg = figure.to_svg(400, 300);
+
+        assert!(svg.contains("&lt;a &amp; b&gt;"));
+        assert!(!svg.contains("<a & b>"));
+    }
+
+    #[test]
+    fn png() {
+        let png = bar_figure().to_png(400, 300).unwrap();
+
+        let image = image::load_from_memory_with_format(&png, image::ImageFormat::Png)
+            .unwrap()
+            .to_rgba8();
+
+        assert_eq!(image.dimensions(), (400, 300));
+        // background
+        assert_eq!(image.get_pixel(1, 1).0, [255, 255, 255, 255]);
+        // the bars are drawn
+        assert!(
+            image
+                .pixels()
+                .any(|pixel| pixel.0 == [0x4c, 0x78, 0xa8, 255])
+        );
+        assert!(
+            image
+                .pixels()
+                .any(|pixel| pixel.0 == [0xf5, 0x85, 0x18, 255])
+        );
+    }
+
+    #[test]
+    fn png_of_pie() {
+        let mut figure = Figure::without_axes();
+        figure.add_mark(Mark::Wedge {
+            start_angle: 0.,
+            end_angle: std::f64::consts::PI,
+            inner_radius: 0.,
+            color: category_color(0),
+        });
+
+        let png = figure.to_png(100, 100).unwrap();
+        let image = image::load_from_memory_with_format(&png, image::ImageFormat::Png)
+            .unwrap()
+            .to_rgba8();
+
+        // the right half is filled, the left half is empty
+        assert_eq!(image.get_pixel(75, 50).0, [0x4c, 0x78, 0xa8, 255]);
+        assert_eq!(image.get_pixel(25, 50).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn tiny_images() {
+        assert!(bar_figure().to_png(1, 1).is_ok());
+        assert!(bar_figure().to_png(0, 0).is_ok());
+    }
+}
@@ -0,0 +1,222 @@
+use std::io::Cursor;
+
+use image::{DynamicImage, ImageFormat, RgbaImage};
+
+use super::font;
+use super::{Canvas, FONT_SCALE, PixelRect, TextAnchor, text_width};
+use crate::error;
+use crate::operations::image::RgbaColor;
+use crate::util::Result;
+
+/// Rasterizes the figure into an RGBA image without anti-aliasing
+pub struct PngCanvas {
+    image: RgbaImage,
+    clip: Option<PixelRect>,
+}
+
+impl PngCanvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            image: RgbaImage::new(width, height),
+            clip: None,
+        }
+    }
+
+    pub fn into_png(self) -> Result<Vec<u8>> {
+        let mut buffer = Cursor::new(Vec::new());
+
+        DynamicImage::ImageRgba8(self.image)
+            .write_to(&mut buffer, ImageFormat::Png)
+            .map_err(|error| error::Error::Plot {
+                details: format!("Unable to encode plot as PNG: {error}"),
+            })?;
+
+        Ok(buffer.into_inner())
+    }
+
+    /// Blends the color onto the pixel, if it is inside the image and the clip rectangle
+    fn blend(&mut self, x: i64, y: i64, color: RgbaColor) {
+        if x < 0
+            || y < 0
+            || x >= i64::from(self.image.width())
+            || y >= i64::from(self.image.height())
+        {
+            return;
+        }
+
+        if let Some(clip) = self.clip {
+            let (center_x, center_y) = (x as f64 + 0.5, y as f64 + 0.5);
+            if center_x < clip.x0 || center_x > clip.x1 || center_y < clip.y0 || center_y > clip.y1
+            {
+                return;
+            }
+        }
+
+        let [red, green, blue, opacity] = color.into_inner();
+        let pixel = self.image.get_pixel_mut(x as u32, y as u32);
+
+        if opacity == 255 {
+            pixel.0 = [red, green, blue, opacity];
+            return;
+        }
+
+        let alpha = f64::from(opacity) / 255.;
+        let [dst_red, dst_green, dst_blue, dst_alpha] = pixel.0;
+        let mix = |src: u8, dst: u8| {
+            (f64::from(src) * alpha + f64::from(dst) * (1. - alpha)).round() as u8
+        };
+        pixel.0 = [
+            mix(red, dst_red),
+            mix(green, dst_green),
+            mix(blue, dst_blue),
+            ((alpha + f64::from(dst_alpha) / 255. * (1. - alpha)) * 255.).round() as u8,
+        ];
+    }
+
+    /// Returns the range of pixels whose centers are in `[start, end)`, but at least one pixel.
+    /// The range is restricted to the `size` of the image.
+    fn pixel_range(start: f64, end: f64, size: u32) -> std::ops::RangeInclusive<i64> {
+        let first = (start - 0.5).ceil() as i64;
+        let last = ((end - 0.5).ceil() as i64 - 1).max(first);
+        first.max(0)..=last.min(i64::from(size) - 1)
+    }
+}
+
+impl Canvas for PngCanvas {
+    fn rect(&mut self, rect: PixelRect, color: RgbaColor) {
+        for y in Self::pixel_range(rect.y0, rect.y1, self.image.height()) {
+            for x in Self::pixel_range(rect.x0, rect.x1, self.image.width()) {
+                self.blend(x, y, color);
+            }
+        }
+    }
+
+    fn polygon(&mut self, points: &[(f64, f64)], color: RgbaColor) {
+        if points.len() < 3 {
+            return;
+        }
+
+        let (min_y, max_y) = points
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &(_, y)| {
+                (min.min(y), max.max(y))
+            });
+        let first_row = ((min_y - 0.5).ceil() as i64).max(0);
+        let last_row = ((max_y - 0.5).floor() as i64).min(i64::from(self.image.height()) - 1);
+
+        let mut crossings = Vec::new();
+        for y in first_row..=last_row {
+            let center_y = y as f64 + 0.5;
+
+            // even-odd rule with the crossings of the scanline
+            crossings.clear();
+            for (i, &(x0, y0)) in points.iter().enumerate() {
+                let (x1, y1) = points[(i + 1) % points.len()];
+                if (y0 <= center_y && center_y < y1) || (y1 <= center_y && center_y < y0) {
+                    crossings.push(x0 + (center_y - y0) / (y1 - y0) * (x1 - x0));
+                }
+            }
+            crossings.sort_by(f64::total_cmp);
+
+            for span in crossings.chunks_exact(2) {
+                let first = ((span[0] - 0.5).ceil() as i64).max(0);
+                let last =
+                    ((span[1] - 0.5).ceil() as i64 - 1).min(i64::from(self.image.width()) - 1);
+                for x in first..=last {
+                    self.blend(x, y, color);
+                }
+            }
+        }
+    }
+
+    fn polyline(&mut self, points: &[(f64, f64)], width: f64, color: RgbaColor) {
+        let half_width = width / 2.;
+
+        for segment in points.windows(2) {
+            let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+            let length = (x1 - x0).hypot(y1 - y0);
+            if length == 0. {
+                continue;
+            }
+
+            // widen thin axis-parallel lines to full pixels
+            let (nx, ny) = (
+                -(y1 - y0) / length * half_width.max(0.5),
+                (x1 - x0) / length * half_width.max(0.5),
+            );
+            self.polygon(
+                &[
+                    (x0 + nx, y0 + ny),
+                    (x1 + nx, y1 + ny),
+                    (x1 - nx, y1 - ny),
+                    (x0 - nx, y0 - ny),
+                ],
+                color,
+            );
+        }
+
+        // round joins
+        if width > 1. && points.len() > 2 {
+            for &point in &points[1..points.len() - 1] {
+                self.circle(point, half_width, color);
+            }
+        }
+    }
+
+    fn circle(&mut self, (center_x, center_y): (f64, f64), radius: f64, color: RgbaColor) {
+        for y in Self::pixel_range(center_y - radius, center_y + radius, self.image.height()) {
+            for x in Self::pixel_range(center_x - radius, center_x + radius, self.image.width()) {
+                let (dx, dy) = (x as f64 + 0.5 - center_x, y as f64 + 0.5 - center_y);
+                if dx.hypot(dy) <= radius {
+                    self.blend(x, y, color);
+                }
+            }
+        }
+    }
+
+    fn text(
+        &mut self,
+        (x, y): (f64, f64),
+        text: &str,
+        anchor: TextAnchor,
+        vertical: bool,
+        color: RgbaColor,
+    ) {
+        let width = text_width(text);
+        let start = match anchor {
+            TextAnchor::Start => 0.,
+            TextAnchor::Middle => -width / 2.,
+            TextAnchor::End => -width,
+        };
+        let top = -(font::GLYPH_HEIGHT as f64) * FONT_SCALE / 2.;
+
+        for (i, c) in text.chars().enumerate() {
+            for (row, bits) in font::glyph(c).iter().enumerate() {
+                for column in 0..font::GLYPH_WIDTH {
+                    if bits & (1 << (font::GLYPH_WIDTH - 1 - column)) == 0 {
+                        continue;
+                    }
+
+                    // offsets along and across the reading direction
+                    let along = start + ((i * font::ADVANCE + column) as f64) * FONT_SCALE;
+                    let across = top + row as f64 * FONT_SCALE;
+
+                    let (x0, y0) = if vertical {
+                        (x + across, y - along - FONT_SCALE)
+                    } else {
+                        (x + along, y + across)
+                    };
+
+                    self.rect(
+                        PixelRect::new(x0, y0, x0 + FONT_SCALE, y0 + FONT_SCALE),
+                        color,
+                    );
+                }
+            }
+        }
+    }
+
+    fn set_clip(&mut self, clip: Option<PixelRect>) {
+        self.clip = clip;
+    }
+}
@@ -0,0 +1,162 @@
+use super::{Canvas, FONT_SCALE, PixelRect, TEXT_HEIGHT, TextAnchor, text_width};
+use crate::operations::image::RgbaColor;
+
+/// Writes the figure as SVG elements
+pub struct SvgCanvas {
+    width: u32,
+    height: u32,
+    body: String,
+    clip_count: usize,
+    is_clipped: bool,
+}
+
+impl SvgCanvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            body: String::new(),
+            clip_count: 0,
+            is_clipped: false,
+        }
+    }
+
+    pub fn into_svg(mut self) -> String {
+        self.set_clip(None);
+
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\" font-family=\"monospace\" font-size=\"{font_size}\">{body}</svg>",
+            width = self.width,
+            height = self.height,
+            font_size = TEXT_HEIGHT,
+            body = self.body,
+        )
+    }
+}
+
+fn paint(attribute: &str, color: RgbaColor) -> String {
+    let [r, g, b, a] = color.into_inner();
+    if a == 255 {
+        format!("{attribute}=\"rgb({r},{g},{b})\"")
+    } else {
+        format!(
+            "{attribute}=\"rgb({r},{g},{b})\" {attribute}-opacity=\"{:.3}\"",
+            f64::from(a) / 255.
+        )
+    }
+}
+
+fn points_attribute(points: &[(f64, f64)]) -> String {
+    points
+        .iter()
+        .map(|(x, y)| format!("{x:.2},{y:.2}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl Canvas for SvgCanvas {
+    fn rect(&mut self, rect: PixelRect, color: RgbaColor) {
+        self.body.push_str(&format!(
+            "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" {}/>",
+            rect.x0,
+            rect.y0,
+            rect.width(),
+            rect.height(),
+            paint("fill", color)
+        ));
+    }
+
+    fn polygon(&mut self, points: &[(f64, f64)], color: RgbaColor) {
+        if points.len() < 3 {
+            return;
+        }
+
+        self.body.push_str(&format!(
+            "<polygon points=\"{}\" {}/>",
+            points_attribute(points),
+            paint("fill", color)
+        ));
+    }
+
+    fn polyline(&mut self, points: &[(f64, f64)], width: f64, color: RgbaColor) {
+        if points.len() < 2 {
+            return;
+        }
+
+        self.body.push_str(&format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke-width=\"{width}\" stroke-linejoin=\"round\" {}/>",
+            points_attribute(points),
+            paint("stroke", color)
+        ));
+    }
+
+    fn circle(&mut self, (x, y): (f64, f64), radius: f64, color: RgbaColor) {
+        self.body.push_str(&format!(
+            "<circle cx=\"{x:.2}\" cy=\"{y:.2}\" r=\"{radius:.2}\" {}/>",
+            paint("fill", color)
+        ));
+    }
+
+    fn text(
+        &mut self,
+        (x, y): (f64, f64),
+        text: &str,
+        anchor: TextAnchor,
+        vertical: bool,
+        color: RgbaColor,
+    ) {
+        let anchor = match anchor {
+            TextAnchor::Start => "start",
+            TextAnchor::Middle => "middle",
+            TextAnchor::End => "end",
+        };
+        let rotation = if vertical {
+            format!(" transform=\"rotate(-90 {x:.2} {y:.2})\"")
+        } else {
+            String::new()
+        };
+
+        // fit the text to the width of the bitmap font of the PNG output, which the layout uses
+        self.body.push_str(&format!(
+            "<text x=\"{x:.2}\" y=\"{y:.2}\" text-anchor=\"{anchor}\" dominant-baseline=\"central\" textLength=\"{length:.2}\" lengthAdjust=\"spacingAndGlyphs\"{rotation} {fill}>{text}</text>",
+            length = text_width(text) + FONT_SCALE,
+            fill = paint("fill", color),
+            text = escape(text),
+        ));
+    }
+
+    fn set_clip(&mut self, clip: Option<PixelRect>) {
+        if self.is_clipped {
+            self.body.push_str("</g>");
+            self.is_clipped = false;
+        }
+
+        if let Some(clip) = clip {
+            self.clip_count += 1;
+            self.body.push_str(&format!(
+                "<clipPath id=\"clip{id}\"><rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\"/></clipPath><g clip-path=\"url(#clip{id})\">",
+                clip.x0,
+                clip.y0,
+                clip.width(),
+                clip.height(),
+                id = self.clip_count,
+            ));
+            self.is_clipped = true;
+        }
+    }
+}
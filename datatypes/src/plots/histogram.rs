@@ -1,11 +1,12 @@
 use std::cmp;
+use std::collections::BTreeMap;
 
 use float_cmp::*;
 use serde::{Deserialize, Serialize};
 use snafu::ensure;
 
 use crate::error;
-use crate::plots::{Plot, PlotData, PlotMetaData};
+use crate::plots::{Axis, Figure, Mark, Plot, PlotData, PlotMetaData, category_color};
 use crate::primitives::{DataRef, FeatureDataRef, Measurement};
 use crate::raster::Pixel;
 use crate::util::Result;
@@ -321,6 +322,43 @@ impl Plot for Histogram {
             }),
         })
     }
+
+    fn to_figure(&self) -> Figure {
+        let buckets = bucket_ranges(self.min, self.max, self.counts.len());
+        let max_count = self.counts.iter().copied().max().unwrap_or_default();
+
+        let mut figure = Figure::new(
+            Axis::linear_exact(
+                self.measurement.to_string(),
+                buckets.first().map_or(self.min, |bucket| bucket.0),
+                buckets.last().map_or(self.max, |bucket| bucket.1),
+            ),
+            Axis::linear("Frequency", 0., max_count as f64),
+        );
+
+        for (&count, x) in self.counts.iter().zip(buckets) {
+            figure.add_mark(Mark::Rect {
+                x,
+                y: (0., count as f64),
+                color: category_color(0),
+            });
+        }
+
+        figure
+    }
+}
+
+/// The ranges of the buckets for drawing them.
+/// A single bucket without extent is drawn with a width of one around its value.
+fn bucket_ranges(min: f64, max: f64, number_of_buckets: usize) -> Vec<(f64, f64)> {
+    if number_of_buckets == 1 && max <= min {
+        return vec![(min - 0.5, min + 0.5)];
+    }
+
+    let step = (max - min) / (number_of_buckets as f64);
+    (0..number_of_buckets)
+        .map(|i| (min + i as f64 * step, min + (i + 1) as f64 * step))
+        .collect()
 }
 
 pub struct HistogramBuilder {
@@ -398,6 +436,181 @@ impl HistogramBuilder {
     }
 }
 
+/// Histograms with the same buckets for several groups, e.g., per land-cover class.
+/// The groups are shown as stacked bars.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupedHistogram {
+    number_of_buckets: usize,
+    min: f64,
+    max: f64,
+    measurement: Measurement,
+    groups: BTreeMap<String, Histogram>,
+}
+
+impl GroupedHistogram {
+    /// Creates a new grouped histogram without any groups
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the `Histogram`'s preconditions are not met
+    ///
+    pub fn new(
+        number_of_buckets: usize,
+        min: f64,
+        max: f64,
+        measurement: Measurement,
+    ) -> Result<Self> {
+        // validate the parameters once, so that adding groups cannot fail
+        Histogram::new(number_of_buckets, min, max, measurement.clone(), None, None)?;
+
+        Ok(Self {
+            number_of_buckets,
+            min,
+            max,
+            measurement,
+            groups: BTreeMap::new(),
+        })
+    }
+
+    /// Adds a value to the histogram of the `group`.
+    /// `None` and non-finite values are counted as no data.
+    pub fn add_value(&mut self, group: &str, value: Option<f64>) {
+        if !self.groups.contains_key(group) {
+            let histogram = Histogram::new(
+                self.number_of_buckets,
+                self.min,
+                self.max,
+                self.measurement.clone(),
+                None,
+                None,
+            )
+            .expect("parameters were validated in `new`");
+            self.groups.insert(group.to_string(), histogram);
+        }
+
+        let histogram = self
+            .groups
+            .get_mut(group)
+            .expect("histogram was inserted above");
+        histogram.handle_data_item(value.unwrap_or(f64::NAN), value.is_none());
+    }
+
+    /// Returns the histogram of each group, ordered by the group name
+    pub fn groups(&self) -> &BTreeMap<String, Histogram> {
+        &self.groups
+    }
+}
+
+impl Plot for GroupedHistogram {
+    fn to_vega_embeddable(&self, _allow_interactions: bool) -> Result<PlotData> {
+        let mut step = (self.max - self.min) / (self.number_of_buckets as f64);
+
+        let mut values = Vec::with_capacity(self.groups.len() * self.number_of_buckets);
+        for (group, histogram) in &self.groups {
+            let mut bin_start = self.min;
+            for &count in &histogram.counts {
+                let bin_end = bin_start + step;
+                values.push(serde_json::json!({
+                    "group": group,
+                    "binStart": bin_start,
+                    "binEnd": bin_end,
+                    "Frequency": count,
+                }));
+                bin_start = bin_end;
+            }
+        }
+
+        // step in spec must not be 0, so add a fake step
+        if step == 0. {
+            step = 1.;
+        }
+
+        let vega_spec = serde_json::json!({
+            "$schema": "https://vega.github.io/schema/vega-lite/v4.json",
+            "data": {
+                "values": values,
+            },
+            "mark": "bar",
+            "encoding": {
+                "x": {
+                    "field": "binStart",
+                    "bin": {
+                        "binned": true,
+                        "step": step,
+                    },
+                    "axis": {
+                        "title": self.measurement.to_string(),
+                    },
+                },
+                "x2": {
+                    "field": "binEnd",
+                },
+                "y": {
+                    "field": "Frequency",
+                    "type": "quantitative",
+                    "stack": "zero",
+                },
+                "color": {
+                    "field": "group",
+                    "type": "nominal",
+                    "title": null,
+                },
+            },
+        });
+
+        Ok(PlotData {
+            vega_string: vega_spec.to_string(),
+            metadata: PlotMetaData::None,
+        })
+    }
+
+    fn to_figure(&self) -> Figure {
+        let buckets = bucket_ranges(self.min, self.max, self.number_of_buckets);
+
+        // the groups are stacked on top of each other
+        let mut stack_heights = vec![0.; self.number_of_buckets];
+        let mut marks = Vec::new();
+        for (group_index, histogram) in self.groups.values().enumerate() {
+            for ((&count, &x), stack_height) in histogram
+                .counts
+                .iter()
+                .zip(&buckets)
+                .zip(&mut stack_heights)
+            {
+                let bottom = *stack_height;
+                *stack_height += count as f64;
+                marks.push(Mark::Rect {
+                    x,
+                    y: (bottom, *stack_height),
+                    color: category_color(group_index),
+                });
+            }
+        }
+
+        let mut figure = Figure::new(
+            Axis::linear_exact(
+                self.measurement.to_string(),
+                buckets.first().map_or(self.min, |bucket| bucket.0),
+                buckets.last().map_or(self.max, |bucket| bucket.1),
+            ),
+            Axis::linear(
+                "Frequency",
+                0.,
+                stack_heights.iter().copied().fold(0., f64::max),
+            ),
+        );
+        for mark in marks {
+            figure.add_mark(mark);
+        }
+        for (group_index, group) in self.groups.keys().enumerate() {
+            figure.add_legend_entry(group.clone(), category_color(group_index));
+        }
+
+        figure
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -561,7 +774,7 @@ mod tests {
            histogram.to_vega_embeddable(false).unwrap(),
             PlotData {
                 vega_string: r#"{"$schema":"https://vega.github.io/schema/vega-lite/v4.json","data":{"values":[{"Frequency":2,"binEnd":0.5,"binStart":0.0},{"Frequency":2,"binEnd":1.0,"binStart":0.5}]},"encoding":{"x":{"axis":{"title":""},"bin":{"binned":true,"step":0.5},"field":"binStart"},"x2":{"field":"binEnd"},"y":{"field":"Frequency","type":"quantitative"}},"mark":"bar"}"#.to_owned(),
-                metadata: PlotMetaData::None
+                metadata: PlotMetaData::None,
             }
         );
         assert_eq!(
@@ -570,7 +783,7 @@ mod tests {
                 vega_string: r#"{"$schema":"https://vega.github.io/schema/vega-lite/v4.json","data":{"values":[{"Frequency":2,"binEnd":0.5,"binStart":0.0},{"Frequency":2,"binEnd":1.0,"binStart":0.5}]},"encoding":{"x":{"axis":{"title":""},"bin":{"binned":true,"step":0.5},"field":"binStart"},"x2":{"field":"binEnd"},"y":{"field":"Frequency","type":"quantitative"}},"mark":"bar","selection":{"range_selection":{"encodings":["x"],"type":"interval"}}}"#.to_owned(),
                 metadata: PlotMetaData::Selection {
                     selection_name: "range_selection".to_string(),
-                }
+                },
             }
         );
     }
@@ -599,7 +812,7 @@ mod tests {
             histogram.to_vega_embeddable(false).unwrap(),
             PlotData {
                 vega_string: r#"{"$schema":"https://vega.github.io/schema/vega-lite/v4.json","data":{"values":[{"Frequency":2500,"binEnd":0.99,"binStart":0.0},{"Frequency":2401,"binEnd":1.98,"binStart":0.99},{"Frequency":2304,"binEnd":2.9699999999999998,"binStart":1.98},{"Frequency":2209,"binEnd":3.96,"binStart":2.9699999999999998},{"Frequency":2116,"binEnd":4.95,"binStart":3.96},{"Frequency":2025,"binEnd":5.94,"binStart":4.95},{"Frequency":1936,"binEnd":6.930000000000001,"binStart":5.94},{"Frequency":1849,"binEnd":7.920000000000001,"binStart":6.930000000000001},{"Frequency":1764,"binEnd":8.91,"binStart":7.920000000000001},{"Frequency":1681,"binEnd":9.9,"binStart":8.91},{"Frequency":1600,"binEnd":10.89,"binStart":9.9},{"Frequency":1521,"binEnd":11.88,"binStart":10.89},{"Frequency":1444,"binEnd":12.870000000000001,"binStart":11.88},{"Frequency":1369,"binEnd":13.860000000000001,"binStart":12.870000000000001},{"Frequency":1296,"binEnd":14.850000000000001,"binStart":13.860000000000001},{"Frequency":1225,"binEnd":15.840000000000002,"binStart":14.850000000000001},{"Frequency":1156,"binEnd":16.830000000000002,"binStart":15.840000000000002},{"Frequency":1089,"binEnd":17.82,"binStart":16.830000000000002},{"Frequency":1024,"binEnd":18.81,"binStart":17.82},{"Frequency":961,"binEnd":19.799999999999997,"binStart":18.81},{"Frequency":900,"binEnd":20.789999999999996,"binStart":19.799999999999997},{"Frequency":841,"binEnd":21.779999999999994,"binStart":20.789999999999996},{"Frequency":784,"binEnd":22.769999999999992,"binStart":21.779999999999994},{"Frequency":729,"binEnd":23.75999999999999,"binStart":22.769999999999992},{"Frequency":676,"binEnd":24.74999999999999,"binStart":23.75999999999999},{"Frequency":625,"binEnd":25.739999999999988,"binStart":24.74999999999999},{"Frequency":576,"binEnd":26.729999999999986,"binStart":25.739999999999988},{"Frequency":529,"binEnd":27.719999999999985,"binStart":26.729999999999986},{"Frequency":484,"binEnd":28.709999999999983,"binStart":27.719999999999985},{"Frequency":441,"binEnd":29.69999999999998,"binStart":28.709999999999983},{"Frequency":400,"binEnd":30.68999999999998,"binStart":29.69999999999998},{"Frequency":361,"binEnd":31.67999999999998,"binStart":30.68999999999998},{"Frequency":324,"binEnd":32.66999999999998,"binStart":31.67999999999998},{"Frequency":289,"binEnd":33.65999999999998,"binStart":32.66999999999998},{"Frequency":256,"binEnd":34.649999999999984,"binStart":33.65999999999998},{"Frequency":225,"binEnd":35.639999999999986,"binStart":34.649999999999984},{"Frequency":196,"binEnd":36.62999999999999,"binStart":35.639999999999986},{"Frequency":169,"binEnd":37.61999999999999,"binStart":36.62999999999999},{"Frequency":144,"binEnd":38.60999999999999,"binStart":37.61999999999999},{"Frequency":121,"binEnd":39.599999999999994,"binStart":38.60999999999999},{"Frequency":100,"binEnd":40.589999999999996,"binStart":39.599999999999994},{"Frequency":81,"binEnd":41.58,"binStart":40.589999999999996},{"Frequency":64,"binEnd":42.57,"binStart":41.58},{"Frequency":49,"binEnd":43.56,"binStart":42.57},{"Frequency":36,"binEnd":44.550000000000004,"binStart":43.56},{"Frequency":25,"binEnd":45.540000000000006,"binStart":44.550000000000004},{"Frequency":16,"binEnd":46.53000000000001,"binStart":45.540000000000006},{"Frequency":9,"binEnd":47.52000000000001,"binStart":46.53000000000001},{"Frequency":4,"binEnd":48.51000000000001,"binStart":47.52000000000001},{"Frequency":1,"binEnd":49.500000000000014,"binStart":48.51000000000001},{"Frequency":0,"binEnd":50.490000000000016,"binStart":49.500000000000014},{"Frequency":1,"binEnd":51.48000000000002,"binStart":50.490000000000016},{"Frequency":4,"binEnd":52.47000000000002,"binStart":51.48000000000002},{"Frequency":9,"binEnd":53.46000000000002,"binStart":52.47000000000002},{"Frequency":16,"binEnd":54.450000000000024,"binStart":53.46000000000002},{"Frequency":25,"binEnd":55.440000000000026,"binStart":54.450000000000024},{"Frequency":36,"binEnd":56.43000000000003,"binStart":55.440000000000026},{"Frequency":49,"binEnd":57.42000000000003,"binStart":56.43000000000003},{"Frequency":64,"binEnd":58.41000000000003,"binStart":57.42000000000003},{"Frequency":81,"binEnd":59.400000000000034,"binStart":58.41000000000003},{"Frequency":100,"binEnd":60.390000000000036,"binStart":59.400000000000034},{"Frequency":121,"binEnd":61.38000000000004,"binStart":60.390000000000036},{"Frequency":144,"binEnd":62.37000000000004,"binStart":61.38000000000004},{"Frequency":169,"binEnd":63.36000000000004,"binStart":62.37000000000004},{"Frequency":196,"binEnd":64.35000000000004,"binStart":63.36000000000004},{"Frequency":225,"binEnd":65.34000000000003,"binStart":64.35000000000004},{"Frequency":256,"binEnd":66.33000000000003,"binStart":65.34000000000003},{"Frequency":289,"binEnd":67.32000000000002,"binStart":66.33000000000003},{"Frequency":324,"binEnd":68.31000000000002,"binStart":67.32000000000002},{"Frequency":361,"binEnd":69.30000000000001,"binStart":68.31000000000002},{"Frequency":400,"binEnd":70.29,"binStart":69.30000000000001},{"Frequency":441,"binEnd":71.28,"binStart":70.29},{"Frequency":484,"binEnd":72.27,"binStart":71.28},{"Frequency":529,"binEnd":73.25999999999999,"binStart":72.27},{"Frequency":576,"binEnd":74.24999999999999,"binStart":73.25999999999999},{"Frequency":625,"binEnd":75.23999999999998,"binStart":74.24999999999999},{"Frequency":676,"binEnd":76.22999999999998,"binStart":75.23999999999998},{"Frequency":729,"binEnd":77.21999999999997,"binStart":76.22999999999998},{"Frequency":784,"binEnd":78.20999999999997,"binStart":77.21999999999997},{"Frequency":841,"binEnd":79.19999999999996,"binStart":78.20999999999997},{"Frequency":900,"binEnd":80.18999999999996,"binStart":79.19999999999996},{"Frequency":961,"binEnd":81.17999999999995,"binStart":80.18999999999996},{"Frequency":1024,"binEnd":82.16999999999994,"binStart":81.17999999999995},{"Frequency":1089,"binEnd":83.15999999999994,"binStart":82.16999999999994},{"Frequency":1156,"binEnd":84.14999999999993,"binStart":83.15999999999994},{"Frequency":1225,"binEnd":85.13999999999993,"binStart":84.14999999999993},{"Frequency":1296,"binEnd":86.12999999999992,"binStart":85.13999999999993},{"Frequency":1369,"binEnd":87.11999999999992,"binStart":86.12999999999992},{"Frequency":1444,"binEnd":88.10999999999991,"binStart":87.11999999999992},{"Frequency":1521,"binEnd":89.09999999999991,"binStart":88.10999999999991},{"Frequency":1600,"binEnd":90.0899999999999,"binStart":89.09999999999991},{"Frequency":1681,"binEnd":91.0799999999999,"binStart":90.0899999999999},{"Frequency":1764,"binEnd":92.0699999999999,"binStart":91.0799999999999},{"Frequency":1849,"binEnd":93.05999999999989,"binStart":92.0699999999999},{"Frequency":1936,"binEnd":94.04999999999988,"binStart":93.05999999999989},{"Frequency":2025,"binEnd":95.03999999999988,"binStart":94.04999999999988},{"Frequency":2116,"binEnd":96.02999999999987,"binStart":95.03999999999988},{"Frequency":2209,"binEnd":97.01999999999987,"binStart":96.02999999999987},{"Frequency":2304,"binEnd":98.00999999999986,"binStart":97.01999999999987},{"Frequency":2401,"binEnd":98.99999999999986,"binStart":98.00999999999986}]},"encoding":{"x":{"axis":{"title":""},"bin":{"binned":true,"step":0.99},"field":"binStart"},"x2":{"field":"binEnd"},"y":{"field":"Frequency","type":"quantitative"}},"mark":"bar"}"#.to_owned(),
-                metadata: PlotMetaData::None
+                metadata: PlotMetaData::None,
             }
         );
     }
@@ -610,8 +823,43 @@ mod tests {
             HistogramBuilder::new(1, 0., 0., Measurement::continuous("foo".to_string(), Some("bar".to_string()))).build().unwrap().to_vega_embeddable(false).unwrap(),
             PlotData {
                 vega_string: r#"{"$schema":"https://vega.github.io/schema/vega-lite/v4.json","data":{"values":[{"Frequency":0,"binEnd":0.0,"binStart":0.0}]},"encoding":{"x":{"axis":{"title":"foo in bar"},"bin":{"binned":true,"step":1.0},"field":"binStart"},"x2":{"field":"binEnd"},"y":{"field":"Frequency","type":"quantitative"}},"mark":"bar"}"#.to_owned(),
-                metadata: PlotMetaData::None
+                metadata: PlotMetaData::None,
             }
         );
     }
+
+    #[test]
+    fn grouped_histogram() {
+        let mut histogram = GroupedHistogram::new(2, 0., 1., Measurement::Unitless).unwrap();
+
+        histogram.add_value("b", Some(0.2));
+        histogram.add_value("a", Some(0.7));
+        histogram.add_value("b", Some(0.9));
+        histogram.add_value("b", None);
+        histogram.add_value("a", Some(f64::NAN));
+        histogram.add_value("a", Some(2.));
+
+        let groups = histogram.groups();
+        assert_eq!(groups.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(groups["a"].counts, vec![0, 1]);
+        assert_eq!(groups["a"].nodata_count, 1);
+        assert_eq!(groups["b"].counts, vec![1, 1]);
+        assert_eq!(groups["b"].nodata_count, 1);
+
+        let vega: serde_json::Value =
+            serde_json::from_str(&histogram.to_vega_embeddable(false).unwrap().vega_string)
+                .unwrap();
+        assert_eq!(
+            vega["data"]["values"],
+            serde_json::json!([
+                { "group": "a", "binStart": 0.0, "binEnd": 0.5, "Frequency": 0 },
+                { "group": "a", "binStart": 0.5, "binEnd": 1.0, "Frequency": 1 },
+                { "group": "b", "binStart": 0.0, "binEnd": 0.5, "Frequency": 1 },
+                { "group": "b", "binStart": 0.5, "binEnd": 1.0, "Frequency": 1 },
+            ])
+        );
+        assert_eq!(vega["encoding"]["color"]["field"], "group");
+
+        assert!(GroupedHistogram::new(0, 0., 1., Measurement::Unitless).is_err());
+    }
 }
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use snafu::ensure;
 
 use crate::error;
-use crate::plots::{Plot, PlotData, PlotMetaData};
+use crate::plots::{Axis, ColorRamp, Figure, Mark, Plot, PlotData, PlotMetaData};
 use crate::primitives::Coordinate2D;
 use crate::util::Result;
 
@@ -77,6 +77,17 @@ impl HistogramDimension {
         })
     }
 
+    /// Returns the range of the bucket with the given index.
+    /// A dimension without extent has a range of width one around its value.
+    fn bucket_range(&self, idx: usize) -> (f64, f64) {
+        if self.bucket_size > 0. {
+            let start = self.min + idx as f64 * self.bucket_size;
+            (start, start + self.bucket_size)
+        } else {
+            (self.min - 0.5, self.min + 0.5)
+        }
+    }
+
     /// Computes the bucket index for the given value.
     /// This method returns `None` if the given value is
     /// not within the domain `[min, max]`.
@@ -198,6 +209,36 @@ impl Plot for Histogram2D {
             metadata: PlotMetaData::None,
         })
     }
+
+    fn to_figure(&self) -> Figure {
+        let mut figure = Figure::new(
+            Axis::linear_exact(
+                self.x.column.clone(),
+                self.x.bucket_range(0).0,
+                self.x.bucket_range(self.x.bucket_count - 1).1,
+            ),
+            Axis::linear_exact(
+                self.y.column.clone(),
+                self.y.bucket_range(0).0,
+                self.y.bucket_range(self.y.bucket_count - 1).1,
+            ),
+        );
+
+        for (idx_x, value) in self.counts.iter().enumerate() {
+            for (&idx_y, &count) in value {
+                figure.add_mark(Mark::Rect {
+                    x: self.x.bucket_range(idx_x),
+                    y: self.y.bucket_range(idx_y),
+                    color: ColorRamp::Blues.color(count as f64 / self.max_count as f64),
+                });
+            }
+        }
+
+        figure.set_legend_title("Frequency");
+        figure.set_legend_gradient(0., self.max_count as f64, ColorRamp::Blues);
+
+        figure
+    }
 }
 
 #[cfg(test)]
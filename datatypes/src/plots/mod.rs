@@ -1,6 +1,9 @@
 mod area_line_plot;
 mod bar_chart;
 mod box_plot;
+mod confusion_matrix;
+mod correlation_matrix;
+mod figure;
 mod histogram;
 mod histogram2d;
 mod multi_line_plot;
@@ -10,7 +13,10 @@ mod scatter_plot;
 pub use area_line_plot::AreaLineChart;
 pub use bar_chart::BarChart;
 pub use box_plot::{BoxPlot, BoxPlotAttribute};
-pub use histogram::{Histogram, HistogramBuilder};
+pub use confusion_matrix::{AccuracyAssessment, ClassAccuracy, ConfusionMatrix};
+pub use correlation_matrix::CorrelationMatrix;
+pub use figure::{Axis, ColorRamp, Figure, Mark, category_color};
+pub use histogram::{GroupedHistogram, Histogram, HistogramBuilder};
 pub use histogram2d::{Histogram2D, HistogramDimension};
 pub use multi_line_plot::{DataPoint, MultiLineChart};
 pub use pie_chart::PieChart;
@@ -29,8 +35,23 @@ pub trait Plot {
     ///
     fn to_vega_embeddable(&self, allow_interactions: bool) -> Result<PlotData>;
 
-    // TODO: create some PNG output, cf. https://github.com/procyon-rs/vega_lite_3.rs/issues/18
-    // fn to_png(&self, width_px: u16, height_px: u16) -> Vec<u8>;
+    /// Describes the plot as a figure that can be rendered without a browser
+    fn to_figure(&self) -> Figure;
+
+    /// Renders the plot as a PNG image of the given size in pixels
+    ///
+    /// # Errors
+    ///
+    /// This method fails if the image cannot be encoded.
+    ///
+    fn to_png(&self, width_px: u32, height_px: u32) -> Result<Vec<u8>> {
+        self.to_figure().to_png(width_px, height_px)
+    }
+
+    /// Renders the plot as an SVG document of the given size in pixels
+    fn to_svg(&self, width_px: u32, height_px: u32) -> String {
+        self.to_figure().to_svg(width_px, height_px)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Eq, Serialize)]
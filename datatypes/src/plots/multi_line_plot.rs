@@ -1,4 +1,7 @@
-use crate::plots::{Plot, PlotData, PlotMetaData};
+use std::collections::BTreeMap;
+
+use crate::plots::figure::value_range;
+use crate::plots::{Axis, Figure, Mark, Plot, PlotData, PlotMetaData, category_color};
 use crate::primitives::{Measurement, TimeInstance};
 use crate::util::Result;
 
@@ -85,6 +88,53 @@ impl Plot for MultiLineChart {
             metadata: PlotMetaData::None,
         })
     }
+
+    fn to_figure(&self) -> Figure {
+        let mut series: BTreeMap<&str, Vec<(f64, f64)>> = BTreeMap::new();
+        for data_point in &self.data {
+            series
+                .entry(&data_point.series)
+                .or_default()
+                .push((data_point.time.inner() as f64, data_point.value));
+        }
+
+        let (min_value, max_value) =
+            value_range(self.data.iter().map(|data_point| data_point.value));
+        let mut figure = Figure::new(
+            Axis::time(
+                "Time",
+                self.data
+                    .iter()
+                    .map(|data_point| data_point.time)
+                    .min()
+                    .unwrap_or(TimeInstance::EPOCH_START),
+                self.data
+                    .iter()
+                    .map(|data_point| data_point.time)
+                    .max()
+                    .unwrap_or(TimeInstance::EPOCH_START),
+            ),
+            Axis::linear(self.measurement.to_string(), min_value, max_value),
+        );
+
+        for (i, (name, mut points)) in series.into_iter().enumerate() {
+            points.sort_by(|(time_a, _), (time_b, _)| time_a.total_cmp(time_b));
+
+            let color = category_color(i);
+            for &(x, y) in &points {
+                figure.add_mark(Mark::Point {
+                    x,
+                    y,
+                    radius: 3.,
+                    color,
+                });
+            }
+            figure.add_mark(Mark::Line { points, color });
+            figure.add_legend_entry(name, color);
+        }
+
+        figure
+    }
 }
 
 #[cfg(test)]
@@ -1,5 +1,5 @@
 use crate::error;
-use crate::plots::{Plot, PlotData, PlotMetaData};
+use crate::plots::{Figure, Mark, Plot, PlotData, PlotMetaData, category_color};
 use crate::util::Result;
 use serde::ser::SerializeMap;
 use serde::{Deserialize, Serialize};
@@ -90,6 +90,28 @@ impl Plot for PieChart {
             metadata: PlotMetaData::None,
         })
     }
+
+    fn to_figure(&self) -> Figure {
+        let total: f64 = self.slices.values().sum();
+
+        let mut figure = Figure::without_axes();
+        figure.set_legend_title(self.legend_label.clone());
+
+        let mut start_angle = 0.;
+        for (i, (label, value)) in self.slices.iter().enumerate() {
+            let end_angle = start_angle + value / total * std::f64::consts::TAU;
+            figure.add_mark(Mark::Wedge {
+                start_angle,
+                end_angle,
+                inner_radius: if self.donut { 0.5 } else { 0. },
+                color: category_color(i),
+            });
+            figure.add_legend_entry(label.clone(), category_color(i));
+            start_angle = end_angle;
+        }
+
+        figure
+    }
 }
 
 #[cfg(test)]
@@ -1,4 +1,6 @@
-use crate::plots::{Plot, PlotData, PlotMetaData};
+use crate::operations::image::RgbaColor;
+use crate::plots::figure::value_range;
+use crate::plots::{Axis, Figure, Mark, Plot, PlotData, PlotMetaData, category_color};
 use crate::primitives::Coordinate2D;
 use crate::util::Result;
 use serde::{Deserialize, Serialize};
@@ -63,6 +65,30 @@ impl Plot for ScatterPlot {
             metadata: PlotMetaData::None,
         })
     }
+
+    fn to_figure(&self) -> Figure {
+        let (min_x, max_x) = value_range(self.values.iter().map(|value| value.x));
+        let (min_y, max_y) = value_range(self.values.iter().map(|value| value.y));
+
+        let mut figure = Figure::new(
+            Axis::linear(self.title_x.clone(), min_x, max_x),
+            Axis::linear(self.title_y.clone(), min_y, max_y),
+        );
+
+        // translucent, so that overlapping points remain visible
+        let [red, green, blue, _] = category_color(0).into_inner();
+        let color = RgbaColor::new(red, green, blue, 178);
+        for value in &self.values {
+            figure.add_mark(Mark::Point {
+                x: value.x,
+                y: value.y,
+                radius: 3.,
+                color,
+            });
+        }
+
+        figure
+    }
 }
 
 #[cfg(test)]
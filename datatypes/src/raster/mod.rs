@@ -20,8 +20,9 @@ pub use self::grid_traits::{
 pub use self::grid_typed::{TypedGrid, TypedGrid2D, TypedGrid3D};
 pub use self::operations::{
     blit::Blit, convert_data_type::ConvertDataType, convert_data_type::ConvertDataTypeParallel,
-    grid_blit::GridBlit, interpolation::Bilinear, interpolation::InterpolationAlgorithm,
-    interpolation::NearestNeighbor,
+    grid_blit::GridBlit, interpolation::Bicubic, interpolation::Bilinear,
+    interpolation::InterpolationAlgorithm, interpolation::Lanczos, interpolation::NearestNeighbor,
+    interpolation::kernel_interpolation_at,
 };
 pub use self::raster_tile::{
     BaseTile, MaterializedRasterTile, MaterializedRasterTile2D, MaterializedRasterTile3D,
@@ -7,9 +7,13 @@ use crate::raster::{
 use crate::util::Result;
 
 pub trait InterpolationAlgorithm<P: Pixel>: Send + Sync + Clone + 'static {
+    /// The number of input pixels that are used in each direction of an interpolated position.
+    /// The input must have `KERNEL_RADIUS - 1` additional rows and columns before and `KERNEL_RADIUS` after the output.
+    const KERNEL_RADIUS: usize = 1;
+
     /// interpolate the given input tile into the output tile
-    /// the output must be fully contained in the input tile and have an additional row and column in order
-    /// to have all the required neighbor pixels.
+    /// the output must be fully contained in the input tile and have additional rows and columns in order
+    /// to have all the required neighbor pixels (cf. `KERNEL_RADIUS`).
     /// Also the output must have a finer resolution than the input
     fn interpolate(
         input: &RasterTile2D<P>,
@@ -97,6 +101,11 @@ impl Bilinear {
             + d_v * (x - a_x) * (y - a_y))
             / ((c_x - a_x) * (b_y - a_y))
     }
+
+    /// The triangle kernel, which is equivalent to bilinear interpolation
+    pub fn kernel(distance: f64) -> f64 {
+        (1. - distance.abs()).max(0.)
+    }
 }
 
 impl<P> InterpolationAlgorithm<P> for Bilinear
@@ -180,6 +189,163 @@ where
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct Bicubic {}
+
+impl Bicubic {
+    /// The cubic convolution kernel of Keys (1981) with `a = -0.5`
+    pub fn kernel(distance: f64) -> f64 {
+        const A: f64 = -0.5;
+
+        let x = distance.abs();
+        if x <= 1. {
+            ((A + 2.) * x - (A + 3.)) * x * x + 1.
+        } else if x < 2. {
+            ((A * x - 5. * A) * x + 8. * A) * x - 4. * A
+        } else {
+            0.
+        }
+    }
+}
+
+impl<P> InterpolationAlgorithm<P> for Bicubic
+where
+    P: Pixel,
+{
+    const KERNEL_RADIUS: usize = 2;
+
+    fn interpolate(input: &RasterTile2D<P>, info_out: &TileInformation) -> Result<RasterTile2D<P>> {
+        interpolate_with_kernel(input, info_out, 2, Self::kernel)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Lanczos {}
+
+impl Lanczos {
+    /// The Lanczos kernel with a window of three pixels
+    pub fn kernel(distance: f64) -> f64 {
+        const WINDOW: f64 = 3.;
+
+        let x = distance.abs();
+        if x < f64::EPSILON {
+            1.
+        } else if x < WINDOW {
+            let pi_x = std::f64::consts::PI * x;
+            WINDOW * pi_x.sin() * (pi_x / WINDOW).sin() / (pi_x * pi_x)
+        } else {
+            0.
+        }
+    }
+}
+
+impl<P> InterpolationAlgorithm<P> for Lanczos
+where
+    P: Pixel,
+{
+    const KERNEL_RADIUS: usize = 3;
+
+    fn interpolate(input: &RasterTile2D<P>, info_out: &TileInformation) -> Result<RasterTile2D<P>> {
+        interpolate_with_kernel(input, info_out, 3, Self::kernel)
+    }
+}
+
+/// Interpolate the value at the fractional grid index (`y`, `x`) of `input` by weighting the surrounding
+/// pixels with the separable `kernel`.
+/// The weights are normalized by their sum.
+/// Returns `None` if any of the weighted pixels is NODATA or outside of the input.
+pub fn kernel_interpolation_at<P, G>(
+    input: &G,
+    y: f64,
+    x: f64,
+    kernel_radius: usize,
+    kernel: fn(f64) -> f64,
+) -> Option<f64>
+where
+    P: Pixel,
+    G: GridIndexAccess<Option<P>, GridIdx2D>,
+{
+    let y_floor = y.floor() as isize;
+    let x_floor = x.floor() as isize;
+    let radius = kernel_radius as isize;
+
+    let mut sum = 0.;
+    let mut weight_sum = 0.;
+
+    for y_idx in (y_floor - radius + 1)..=(y_floor + radius) {
+        let y_weight = kernel(y - y_idx as f64);
+
+        for x_idx in (x_floor - radius + 1)..=(x_floor + radius) {
+            let weight = y_weight * kernel(x - x_idx as f64);
+
+            // pixels without weight do not need to be valid
+            if weight == 0. {
+                continue;
+            }
+
+            let value: f64 = input.get_at_grid_index([y_idx, x_idx].into()).ok()??.as_();
+
+            sum += weight * value;
+            weight_sum += weight;
+        }
+    }
+
+    if weight_sum == 0. {
+        return None;
+    }
+
+    Some(sum / weight_sum)
+}
+
+fn interpolate_with_kernel<P: Pixel>(
+    input: &RasterTile2D<P>,
+    info_out: &TileInformation,
+    kernel_radius: usize,
+    kernel: fn(f64) -> f64,
+) -> Result<RasterTile2D<P>> {
+    if input.is_empty() {
+        return Ok(RasterTile2D::new_with_tile_info(
+            input.time,
+            *info_out,
+            input.band,
+            EmptyGrid::new(info_out.tile_size_in_pixels).into(),
+            input.cache_hint.clone_with_current_datetime(),
+        ));
+    }
+
+    let info_in = input.tile_information();
+    let in_upper_left = info_in.spatial_partition().upper_left();
+    let in_x_size = info_in.global_geo_transform.x_pixel_size();
+    let in_y_size = info_in.global_geo_transform.y_pixel_size();
+
+    let out_upper_left = info_out.spatial_partition().upper_left();
+    let out_x_size = info_out.global_geo_transform.x_pixel_size();
+    let out_y_size = info_out.global_geo_transform.y_pixel_size();
+
+    let map_fn = |g_idx: GridIdx2D| {
+        let GridIdx([y_idx, x_idx]) = g_idx;
+
+        let out_y = out_upper_left.y + y_idx as f64 * out_y_size;
+        let out_x = out_upper_left.x + x_idx as f64 * out_x_size;
+
+        let in_y = (out_y - in_upper_left.y) / in_y_size;
+        let in_x = (out_x - in_upper_left.x) / in_x_size;
+
+        kernel_interpolation_at(input, in_y, in_x, kernel_radius, kernel).map(P::from_)
+    };
+
+    let out_data = GridOrEmpty::from_index_fn_parallel(&info_out.tile_size_in_pixels, map_fn); // TODO: this will check for empty tiles. Change to MaskedGrid::from.. to avoid this.
+
+    Ok(RasterTile2D::new(
+        input.time,
+        info_out.global_tile_position,
+        input.band,
+        info_out.global_geo_transform,
+        out_data,
+        input.cache_hint.clone_with_current_datetime(),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use rayon::ThreadPoolBuilder;
@@ -324,4 +490,79 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn kernels() {
+        assert_eq!(Bicubic::kernel(0.), 1.);
+        assert_eq!(Bicubic::kernel(1.), 0.);
+        assert_eq!(Bicubic::kernel(-2.), 0.);
+        assert_eq!(Bicubic::kernel(0.5), 0.5625);
+        assert_eq!(Bicubic::kernel(1.5), -0.0625);
+
+        assert_eq!(Lanczos::kernel(0.), 1.);
+        assert!(Lanczos::kernel(1.).abs() < 1e-12);
+        assert!(Lanczos::kernel(2.).abs() < 1e-12);
+        assert_eq!(Lanczos::kernel(3.), 0.);
+        assert!(Lanczos::kernel(1.5) < 0.);
+    }
+
+    fn linear_input() -> RasterTile2D<f64> {
+        // values rise by 1 per pixel to the east and by 10 per pixel to the south
+        let data = (0..6)
+            .flat_map(|y| (0..6).map(move |x| f64::from(10 * y + x)))
+            .collect::<Vec<_>>();
+
+        RasterTile2D::new_with_tile_info(
+            Default::default(),
+            TileInformation {
+                global_tile_position: [0, 0].into(),
+                tile_size_in_pixels: [6, 6].into(),
+                global_geo_transform: GeoTransform::new((-2.0, 2.0).into(), 1.0, -1.0),
+            },
+            0,
+            GridOrEmpty::Grid(MaskedGrid::from(Grid2D::new([6, 6].into(), data).unwrap())),
+            CacheHint::default(),
+        )
+    }
+
+    #[test]
+    fn bicubic_and_lanczos() {
+        let input = linear_input();
+
+        // the output starts at the third input pixel to have enough neighbors
+        let output_info = TileInformation {
+            global_tile_position: [0, 0].into(),
+            tile_size_in_pixels: [2, 2].into(),
+            global_geo_transform: GeoTransform::new((0.0, 0.0).into(), 0.5, -0.5),
+        };
+
+        let pool = ThreadPoolBuilder::new().num_threads(0).build().unwrap();
+
+        for output in [
+            pool.install(|| Bicubic::interpolate(&input, &output_info))
+                .unwrap(),
+            pool.install(|| Lanczos::interpolate(&input, &output_info))
+                .unwrap(),
+        ] {
+            let output_data = output.grid_array.as_masked_grid().unwrap();
+
+            // both kernels reproduce a linear surface in the interior
+            let expected = [22., 22.5, 27., 27.5];
+            for (value, expected) in output_data.masked_element_deref_iterator().zip(expected) {
+                assert!((value.unwrap() - expected).abs() < 1e-9);
+            }
+        }
+
+        // at the border, there are not enough neighbors
+        let border_info = TileInformation {
+            global_tile_position: [0, 0].into(),
+            tile_size_in_pixels: [1, 1].into(),
+            global_geo_transform: GeoTransform::new((-1.75, 1.75).into(), 0.5, -0.5),
+        };
+        let output = pool
+            .install(|| Bicubic::interpolate(&input, &border_info))
+            .unwrap();
+        assert!(output.is_empty());
+    }
 }
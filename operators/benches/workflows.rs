@@ -27,8 +27,8 @@ use geoengine_operators::engine::{
 };
 use geoengine_operators::mock::{MockRasterSource, MockRasterSourceParams};
 use geoengine_operators::processing::{
-    Expression, ExpressionParams, RasterStacker, RasterStackerParams, Reprojection,
-    ReprojectionParams,
+    Expression, ExpressionParams, InterpolationMethod, RasterStacker, RasterStackerParams,
+    Reprojection, ReprojectionParams,
 };
 use geoengine_operators::source::GdalSource;
 use geoengine_operators::{
@@ -503,6 +503,10 @@ fn bench_mock_source_operator_with_identity_reprojection(bench_collector: &mut B
         Reprojection {
             params: ReprojectionParams {
                 target_spatial_reference: SpatialReference::epsg_4326(),
+                interpolation: InterpolationMethod::NearestNeighbor,
+                downsampling: None,
+                densification: None,
+                split_at_antimeridian: false,
             },
             sources: SingleRasterOrVectorSource::from(mock_raster_operator.boxed()),
         }
@@ -586,6 +590,10 @@ fn bench_mock_source_operator_with_4326_to_3857_reprojection(
         Reprojection {
             params: ReprojectionParams {
                 target_spatial_reference: SpatialReference::epsg_4326(),
+                interpolation: InterpolationMethod::NearestNeighbor,
+                downsampling: None,
+                densification: None,
+                split_at_antimeridian: false,
             },
             sources: SingleRasterOrVectorSource::from(mock_raster_operator.boxed()),
         }
@@ -796,6 +804,10 @@ fn bench_gdal_source_operator_with_identity_reprojection(bench_collector: &mut B
     let projection_operator = Reprojection {
         params: ReprojectionParams {
             target_spatial_reference: SpatialReference::epsg_4326(),
+            interpolation: InterpolationMethod::NearestNeighbor,
+            downsampling: None,
+            densification: None,
+            split_at_antimeridian: false,
         },
         sources: SingleRasterOrVectorSource::from(gdal_operator.boxed()),
     }
@@ -863,6 +875,10 @@ fn bench_gdal_source_operator_with_4326_to_3857_reprojection(
                 geoengine_datatypes::spatial_reference::SpatialReferenceAuthority::Epsg,
                 3857,
             ),
+            interpolation: InterpolationMethod::NearestNeighbor,
+            downsampling: None,
+            densification: None,
+            split_at_antimeridian: false,
         },
         sources: SingleRasterOrVectorSource::from(gdal_operator.boxed()),
     }
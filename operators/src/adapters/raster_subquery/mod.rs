@@ -6,5 +6,6 @@ pub use raster_subquery_adapter::{
 };
 
 pub use raster_subquery_reprojection::{
-    TileReprojectionSubQuery, fold_by_coordinate_lookup_future,
+    Resampling, TileReprojectionResamplingSubQuery, TileReprojectionSubQuery,
+    fold_by_coordinate_lookup_future,
 };
@@ -2,6 +2,7 @@ use std::marker::PhantomData;
 use std::sync::Arc;
 
 use crate::error;
+use crate::processing::{DownsamplingMethod, downsample_area};
 use crate::util::Result;
 use async_trait::async_trait;
 use futures::future::BoxFuture;
@@ -9,10 +10,11 @@ use futures::{Future, FutureExt, TryFuture, TryFutureExt};
 use geoengine_datatypes::operations::reproject::Reproject;
 use geoengine_datatypes::primitives::CacheHint;
 use geoengine_datatypes::primitives::{
-    RasterQueryRectangle, SpatialPartition2D, SpatialPartitioned,
+    AxisAlignedRectangle, RasterQueryRectangle, SpatialPartition2D, SpatialPartitioned,
 };
 use geoengine_datatypes::raster::{
-    Grid2D, GridIndexAccess, GridSize, UpdateIndexedElementsParallel,
+    Blit, EmptyGrid2D, GeoTransform, Grid2D, GridIndexAccess, GridOrEmpty, GridSize,
+    UpdateIndexedElementsParallel, kernel_interpolation_at,
 };
 use geoengine_datatypes::{
     operations::reproject::{CoordinateProjection, CoordinateProjector},
@@ -86,31 +88,24 @@ where
         start_time: TimeInstance,
         band_idx: u32,
     ) -> Result<Option<RasterQueryRectangle>> {
-        // this is the spatial partition we are interested in
-        let valid_spatial_bounds = self
-            .valid_bounds_out
-            .intersection(&tile_info.spatial_partition())
-            .and_then(|vo| vo.intersection(&query_rect.spatial_partition()));
-        if let Some(bounds) = valid_spatial_bounds {
-            let proj = CoordinateProjector::from_known_srs(self.out_srs, self.in_srs)?;
-            let projected_bounds = bounds.reproject(&proj);
-
-            match projected_bounds {
-                Ok(pb) => Ok(Some(RasterQueryRectangle {
-                    spatial_bounds: pb,
+        let projected_bounds = projected_tile_bounds(
+            tile_info,
+            &query_rect,
+            self.valid_bounds_out,
+            self.out_srs,
+            self.in_srs,
+        )?;
+
+        projected_bounds
+            .map(|spatial_bounds| {
+                Ok(RasterQueryRectangle {
+                    spatial_bounds,
                     time_interval: TimeInterval::new_instant(start_time)?,
                     spatial_resolution: self.in_spatial_res,
                     attributes: band_idx.into(),
-                })),
-                // In some strange cases the reprojection can return an empty box.
-                // We ignore it since it contains no pixels.
-                Err(geoengine_datatypes::error::Error::OutputBboxEmpty { bbox: _ }) => Ok(None),
-                Err(e) => Err(e.into()),
-            }
-        } else {
-            // output query rectangle is not valid in source projection => produce empty tile
-            Ok(None)
-        }
+                })
+            })
+            .transpose()
     }
 
     fn fold_method(&self) -> Self::FoldMethod {
@@ -118,6 +113,286 @@ where
     }
 }
 
+/// The bounds of the output tile in the input projection.
+/// Returns `None` if the tile does not intersect the valid output bounds.
+fn projected_tile_bounds(
+    tile_info: TileInformation,
+    query_rect: &RasterQueryRectangle,
+    valid_bounds_out: SpatialPartition2D,
+    out_srs: SpatialReference,
+    in_srs: SpatialReference,
+) -> Result<Option<SpatialPartition2D>> {
+    // this is the spatial partition we are interested in
+    let valid_spatial_bounds = valid_bounds_out
+        .intersection(&tile_info.spatial_partition())
+        .and_then(|vo| vo.intersection(&query_rect.spatial_partition()));
+    if let Some(bounds) = valid_spatial_bounds {
+        let proj = CoordinateProjector::from_known_srs(out_srs, in_srs)?;
+        let projected_bounds = bounds.reproject(&proj);
+
+        match projected_bounds {
+            Ok(pb) => Ok(Some(pb)),
+            // In some strange cases the reprojection can return an empty box.
+            // We ignore it since it contains no pixels.
+            Err(geoengine_datatypes::error::Error::OutputBboxEmpty { bbox: _ }) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    } else {
+        // output query rectangle is not valid in source projection => produce empty tile
+        Ok(None)
+    }
+}
+
+/// How to compute the value of an output pixel from the input pixels around its projected coordinate
+#[derive(Debug, Clone, Copy)]
+pub enum Resampling {
+    /// Weight the input pixels around the projected coordinate with a separable kernel
+    Kernel {
+        radius: usize,
+        kernel: fn(f64) -> f64,
+    },
+    /// Aggregate all input pixels whose centers fall into the projected footprint of the output pixel
+    Aggregate(DownsamplingMethod),
+}
+
+/// A sub-query aggregator that collects all input tiles of an output tile and then resamples them
+/// at the projected coordinates of the output pixels.
+/// In contrast to `TileReprojectionSubQuery`, the output pixels depend on more than one input pixel.
+#[derive(Debug)]
+pub struct TileReprojectionResamplingSubQuery<T> {
+    pub in_srs: SpatialReference,
+    pub out_srs: SpatialReference,
+    /// The size of an output pixel in the input projection
+    pub in_spatial_res: SpatialResolution,
+    /// The resolution in which the input is queried
+    pub query_spatial_res: SpatialResolution,
+    pub valid_bounds_in: SpatialPartition2D,
+    pub valid_bounds_out: SpatialPartition2D,
+    pub resampling: Resampling,
+    pub _phantom_data: PhantomData<T>,
+}
+
+impl<'a, T> SubQueryTileAggregator<'a, T> for TileReprojectionResamplingSubQuery<T>
+where
+    T: Pixel,
+{
+    type FoldFuture = futures::future::Ready<Result<ResamplingAccu<T>>>;
+
+    type FoldMethod = fn(ResamplingAccu<T>, RasterTile2D<T>) -> Self::FoldFuture;
+
+    type TileAccu = ResamplingAccu<T>;
+    type TileAccuFuture = BoxFuture<'a, Result<Self::TileAccu>>;
+
+    fn new_fold_accu(
+        &self,
+        tile_info: TileInformation,
+        query_rect: RasterQueryRectangle,
+        pool: &Arc<ThreadPool>,
+    ) -> Self::TileAccuFuture {
+        let resampling = self.resampling;
+        let footprint = Coordinate2D::new(self.in_spatial_res.x, -self.in_spatial_res.y);
+
+        build_accu(
+            &query_rect,
+            pool.clone(),
+            tile_info,
+            self.valid_bounds_out,
+            self.out_srs,
+            self.in_srs,
+        )
+        .map_ok(move |accu| ResamplingAccu {
+            accu,
+            input_tiles: Vec::new(),
+            resampling,
+            footprint,
+        })
+        .boxed()
+    }
+
+    fn tile_query_rectangle(
+        &self,
+        tile_info: TileInformation,
+        query_rect: RasterQueryRectangle,
+        start_time: TimeInstance,
+        band_idx: u32,
+    ) -> Result<Option<RasterQueryRectangle>> {
+        let Some(projected_bounds) = projected_tile_bounds(
+            tile_info,
+            &query_rect,
+            self.valid_bounds_out,
+            self.out_srs,
+            self.in_srs,
+        )?
+        else {
+            return Ok(None);
+        };
+
+        // enlarge the bounds to have all input pixels that contribute to the border pixels
+        let margin = match self.resampling {
+            Resampling::Kernel { radius, .. } => Coordinate2D::new(
+                self.query_spatial_res.x * radius as f64,
+                self.query_spatial_res.y * radius as f64,
+            ),
+            Resampling::Aggregate(_) => {
+                Coordinate2D::new(self.in_spatial_res.x, self.in_spatial_res.y)
+            }
+        };
+        let margin = Coordinate2D::new(margin.x, -margin.y);
+
+        let spatial_bounds = SpatialPartition2D::new(
+            projected_bounds.upper_left() - margin,
+            projected_bounds.lower_right() + margin,
+        )?;
+
+        Ok(Some(RasterQueryRectangle {
+            spatial_bounds,
+            time_interval: TimeInterval::new_instant(start_time)?,
+            spatial_resolution: self.query_spatial_res,
+            attributes: band_idx.into(),
+        }))
+    }
+
+    fn fold_method(&self) -> Self::FoldMethod {
+        |accu, tile| futures::future::ready(collect_input_tile(accu, tile))
+    }
+}
+
+fn collect_input_tile<T: Pixel>(
+    mut accu: ResamplingAccu<T>,
+    tile: RasterTile2D<T>,
+) -> Result<ResamplingAccu<T>> {
+    let t_union = accu.accu.accu_tile.time.union(&tile.time)?;
+
+    accu.tile_mut().time = t_union;
+    accu.tile_mut().cache_hint.merge_with(&tile.cache_hint);
+
+    if !tile.grid_array.is_empty() {
+        accu.input_tiles.push(tile);
+    }
+
+    Ok(accu)
+}
+
+/// Combine the input tiles into a single tile that covers all of them
+fn mosaic_input_tiles<T: Pixel>(input_tiles: Vec<RasterTile2D<T>>) -> Result<RasterTile2D<T>> {
+    let geo_transform = input_tiles[0].global_geo_transform;
+
+    let mut upper_left = input_tiles[0].spatial_partition().upper_left();
+    let mut lower_right = input_tiles[0].spatial_partition().lower_right();
+    for tile in &input_tiles[1..] {
+        let bounds = tile.spatial_partition();
+        upper_left = Coordinate2D::new(
+            upper_left.x.min(bounds.upper_left().x),
+            upper_left.y.max(bounds.upper_left().y),
+        );
+        lower_right = Coordinate2D::new(
+            lower_right.x.max(bounds.lower_right().x),
+            lower_right.y.min(bounds.lower_right().y),
+        );
+    }
+
+    let shape = [
+        ((lower_right.y - upper_left.y) / geo_transform.y_pixel_size()).round() as usize,
+        ((lower_right.x - upper_left.x) / geo_transform.x_pixel_size()).round() as usize,
+    ];
+
+    let mut mosaic = RasterTile2D::new(
+        input_tiles[0].time,
+        [0, 0].into(),
+        0,
+        GeoTransform::new(
+            upper_left,
+            geo_transform.x_pixel_size(),
+            geo_transform.y_pixel_size(),
+        ),
+        GridOrEmpty::from(EmptyGrid2D::new(shape.into())),
+        CacheHint::max_duration(),
+    )
+    .into_materialized_tile();
+
+    for tile in input_tiles {
+        mosaic.blit(tile)?;
+    }
+
+    Ok(mosaic.into())
+}
+
+fn resample<T: Pixel>(accu: ResamplingAccu<T>) -> Result<RasterTile2D<T>> {
+    let ResamplingAccu {
+        accu:
+            TileWithProjectionCoordinates {
+                mut accu_tile,
+                coords,
+                pool,
+            },
+        input_tiles,
+        resampling,
+        footprint,
+    } = accu;
+
+    if input_tiles.is_empty() {
+        return Ok(accu_tile);
+    }
+
+    let mosaic = mosaic_input_tiles(input_tiles)?;
+    let mosaic_upper_left = mosaic.spatial_partition().upper_left();
+    let x_pixel_size = mosaic.global_geo_transform.x_pixel_size();
+    let y_pixel_size = mosaic.global_geo_transform.y_pixel_size();
+
+    pool.install(|| {
+        let map_fn = |grid_idx: GridIdx2D, _accu_value: Option<T>| {
+            let coord = coords.get_at_grid_index_unchecked(grid_idx)?;
+
+            match resampling {
+                Resampling::Kernel { radius, kernel } => kernel_interpolation_at(
+                    &mosaic,
+                    (coord.y - mosaic_upper_left.y) / y_pixel_size,
+                    (coord.x - mosaic_upper_left.x) / x_pixel_size,
+                    radius,
+                    kernel,
+                )
+                .map(T::from_),
+                Resampling::Aggregate(method) => {
+                    downsample_area(&mosaic, coord, coord + footprint, method)
+                }
+            }
+        };
+
+        accu_tile.update_indexed_elements_parallel(map_fn);
+    });
+
+    Ok(accu_tile)
+}
+
+#[derive(Debug, Clone)]
+pub struct ResamplingAccu<T> {
+    accu: TileWithProjectionCoordinates<T>,
+    input_tiles: Vec<RasterTile2D<T>>,
+    resampling: Resampling,
+    /// The extent of an output pixel in the input projection
+    footprint: Coordinate2D,
+}
+
+#[async_trait]
+impl<T: Pixel> FoldTileAccu for ResamplingAccu<T> {
+    type RasterType = T;
+
+    async fn into_tile(self) -> Result<RasterTile2D<Self::RasterType>> {
+        // now that we collected all the input tiles we compute the output pixels
+        crate::util::spawn_blocking(move || resample(self)).await?
+    }
+
+    fn thread_pool(&self) -> &Arc<ThreadPool> {
+        &self.accu.pool
+    }
+}
+
+impl<T: Pixel> FoldTileAccuMut for ResamplingAccu<T> {
+    fn tile_mut(&mut self) -> &mut RasterTile2D<Self::RasterType> {
+        &mut self.accu.accu_tile
+    }
+}
+
 fn build_accu<T: Pixel>(
     query_rect: &RasterQueryRectangle,
     pool: Arc<ThreadPool>,
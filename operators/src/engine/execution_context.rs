@@ -14,7 +14,7 @@ use crate::source::{GdalLoadingInfo, OgrSourceDataset};
 use crate::util::{Result, create_rayon_thread_pool};
 use async_trait::async_trait;
 use geoengine_datatypes::dataset::{DataId, NamedData};
-use geoengine_datatypes::machine_learning::MlModelName;
+use geoengine_datatypes::machine_learning::{MlModelName, MlModelVersion};
 use geoengine_datatypes::primitives::{RasterQueryRectangle, VectorQueryRectangle};
 use geoengine_datatypes::raster::TilingSpecification;
 use geoengine_datatypes::util::test::TestDefault;
@@ -57,7 +57,11 @@ pub trait ExecutionContext: Send
 
     async fn resolve_named_data(&self, data: &NamedData) -> Result<DataId>;
 
-    async fn ml_model_loading_info(&self, name: &MlModelName) -> Result<MlModelLoadingInfo>;
+    async fn ml_model_loading_info(
+        &self,
+        name: &MlModelName,
+        version: MlModelVersion,
+    ) -> Result<MlModelLoadingInfo>;
 }
 
 #[async_trait]
@@ -92,6 +96,7 @@ pub struct MockExecutionContext {
     pub thread_pool: Arc<ThreadPool>,
     pub meta_data: HashMap<DataId, Box<dyn Any + Send + Sync>>,
     pub named_data: HashMap<NamedData, DataId>,
+    /// the mock holds a single version of each model, i.e., version 1
     pub ml_models: HashMap<MlModelName, MlModelLoadingInfo>,
     pub tiling_specification: TilingSpecification,
 }
@@ -212,11 +217,23 @@ impl ExecutionContext for MockExecutionContext {
             .ok_or_else(|| Error::UnknownDatasetName { name: data.clone() })
     }
 
-    async fn ml_model_loading_info(&self, name: &MlModelName) -> Result<MlModelLoadingInfo> {
-        self.ml_models
+    async fn ml_model_loading_info(
+        &self,
+        name: &MlModelName,
+        version: MlModelVersion,
+    ) -> Result<MlModelLoadingInfo> {
+        let model = self
+            .ml_models
             .get(name)
-            .cloned()
-            .ok_or_else(|| Error::UnknownMlModelName { name: name.clone() })
+            .ok_or_else(|| Error::UnknownMlModelName { name: name.clone() })?;
+
+        match version {
+            MlModelVersion::Latest | MlModelVersion::Version(1) => Ok(model.clone()),
+            MlModelVersion::Version(version) => Err(Error::UnknownMlModelVersion {
+                name: name.clone(),
+                version,
+            }),
+        }
     }
 }
 
@@ -401,8 +418,12 @@ impl ExecutionContext for StatisticsWrappingMockExecutionContext {
         self.inner.resolve_named_data(data).await
     }
 
-    async fn ml_model_loading_info(&self, name: &MlModelName) -> Result<MlModelLoadingInfo> {
-        self.inner.ml_model_loading_info(name).await
+    async fn ml_model_loading_info(
+        &self,
+        name: &MlModelName,
+        version: MlModelVersion,
+    ) -> Result<MlModelLoadingInfo> {
+        self.inner.ml_model_loading_info(name, version).await
     }
 }
 
@@ -11,7 +11,7 @@ use futures::stream::BoxStream;
 use geoengine_datatypes::collections::{
     DataCollection, MultiLineStringCollection, MultiPolygonCollection,
 };
-use geoengine_datatypes::plots::{PlotData, PlotOutputFormat};
+use geoengine_datatypes::plots::{Figure, PlotData, PlotOutputFormat};
 use geoengine_datatypes::primitives::{
     AxisAlignedRectangle, BandSelection, BoundingBox2D, ColumnSelection, PlotQueryRectangle,
     QueryAttributeSelection, QueryRectangle, RasterQueryRectangle, SpatialPartition2D,
@@ -190,6 +190,16 @@ pub trait PlotQueryProcessor: Sync + Send {
         ctx: &'a dyn QueryContext,
     ) -> Result<Self::OutputFormat>;
 
+    /// Queries the plot as a figure for rendering it as an image on the server.
+    /// Returns `None` if the plot cannot be described as a figure.
+    async fn plot_figure_query<'a>(
+        &'a self,
+        _query: PlotQueryRectangle,
+        _ctx: &'a dyn QueryContext,
+    ) -> Result<Option<Figure>> {
+        Ok(None)
+    }
+
     fn boxed(self) -> Box<dyn PlotQueryProcessor<OutputFormat = Self::OutputFormat>>
     where
         Self: Sized + 'static,
@@ -175,6 +175,11 @@ pub enum Error {
         name: MlModelName,
     },
 
+    UnknownMlModelVersion {
+        name: MlModelName,
+        version: u32,
+    },
+
     CannotResolveMlModelName {
         name: MlModelName,
         source: Box<dyn ErrorSource>,
@@ -359,6 +364,22 @@ pub enum Error {
     InterpolationOperator {
         source: crate::processing::InterpolationError,
     },
+    #[snafu(display("RasterVectorization error: {source}"), context(false))]
+    RasterVectorization {
+        source: crate::processing::RasterVectorizationError,
+    },
+    #[snafu(display("Reclassify error: {source}"), context(false))]
+    Reclassify {
+        source: crate::processing::ReclassifyError,
+    },
+    #[snafu(display("TemporalTrend error: {source}"), context(false))]
+    TemporalTrend {
+        source: crate::processing::TemporalTrendError,
+    },
+    #[snafu(display("Terrain error: {source}"), context(false))]
+    Terrain {
+        source: crate::processing::TerrainError,
+    },
     #[snafu(display("TimeShift error: {source}"), context(false))]
     TimeShift {
         source: crate::processing::TimeShiftError,
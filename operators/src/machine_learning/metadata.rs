@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use crate::engine::RasterBandDescriptor;
 use geoengine_datatypes::{machine_learning::MlTensorShape3D, raster::RasterDataType};
 use postgres_types::{FromSql, ToSql};
 
@@ -51,16 +52,24 @@ impl MlModelLoadingInfo {
     }
 }
 
-// For now we assume all models are pixel-wise, i.e., they take a single pixel with multiple bands as input and produce a single output value.
-// To support different inputs, we would need a more sophisticated logic to produce the inputs for the model.
+// Models are either pixel-wise, i.e., they take a single pixel with multiple bands as input and produce one value per output band,
+// or patch-based, i.e., they take a patch of `y` x `x` pixels with multiple bands and produce a patch of the same size with one value per output band.
 #[derive(Debug, Clone, PartialEq, ToSql, FromSql)]
 pub struct MlModelMetadata {
     pub input_type: RasterDataType,
     pub output_type: RasterDataType,
     pub input_shape: MlTensorShape3D,
-    pub output_shape: MlTensorShape3D, // TODO: output measurement, e.g. classification or regression, label names for classification. This would have to be provided by the model creator along the model file as it cannot be extracted from the model file(?)
+    pub output_shape: MlTensorShape3D,
     pub input_no_data_handling: MlModelInputNoDataHandling,
     pub output_no_data_handling: MlModelOutputNoDataHandling,
+    /// The names and measurements of the output bands, e.g., the classes of a classification.
+    /// This cannot be extracted from the model file and has to be provided by the model creator.
+    /// If empty, the output bands are unitless and named `prediction`.
+    pub output_bands: Vec<RasterBandDescriptor>,
+    /// The number of consecutive time steps the model consumes, e.g., for time-series classification.
+    /// The bands of all time steps are stacked in the input features, ordered from the oldest to the most recent time step.
+    /// Thus, `input_shape.bands` must be `input_time_steps` times the number of source bands.
+    pub input_time_steps: u32,
 }
 
 impl MlModelMetadata {
@@ -72,6 +81,10 @@ impl MlModelMetadata {
         self.output_shape.bands
     }
 
+    pub fn input_is_time_series(&self) -> bool {
+        self.input_time_steps > 1
+    }
+
     pub fn input_is_single_pixel(&self) -> bool {
         self.input_shape.x == 1 && self.input_shape.y == 1
     }
@@ -83,4 +96,19 @@ impl MlModelMetadata {
     pub fn output_is_single_attribute(&self) -> bool {
         self.num_output_bands() == 1
     }
+
+    /// The descriptors of the output bands, falling back to unitless bands if the metadata does not specify them
+    pub fn output_band_descriptors(&self) -> Vec<RasterBandDescriptor> {
+        if !self.output_bands.is_empty() {
+            return self.output_bands.clone();
+        }
+
+        if self.output_is_single_attribute() {
+            return vec![RasterBandDescriptor::new_unitless("prediction".to_string())];
+        }
+
+        (0..self.num_output_bands())
+            .map(|band| RasterBandDescriptor::new_unitless(format!("prediction_{band}")))
+            .collect()
+    }
 }
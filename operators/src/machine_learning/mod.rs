@@ -1,6 +1,5 @@
 use geoengine_datatypes::{
-    machine_learning::MlTensorShape3D,
-    raster::{GridShape2D, RasterDataType},
+    machine_learning::MlTensorShape3D, primitives::TimeStep, raster::RasterDataType,
 };
 pub use metadata::{
     MlModelInputNoDataHandling, MlModelLoadingInfo, MlModelMetadata, MlModelOutputNoDataHandling,
@@ -11,6 +10,7 @@ pub mod db_types;
 mod metadata;
 pub mod onnx;
 pub mod onnx_util;
+mod patches;
 
 #[derive(Debug, Snafu)]
 #[snafu(visibility(pub(crate)))]
@@ -27,15 +27,6 @@ pub enum MachineLearningError {
         dimensions
     ))]
     InvalidDimensions { dimensions: Vec<i64> },
-    #[snafu(display(
-        "Onnx model must have one dimensional output or match the tiling tile size. Found {:?} and {:?}.",
-        tensor_shape,
-        tiling_shape
-    ))]
-    InvalidInputPixelShape {
-        tensor_shape: MlTensorShape3D,
-        tiling_shape: GridShape2D,
-    },
     #[snafu(display(
         "Onnx model tensor shape must match the combination of raster y,x pixels * bands. Found {:?} and {:?}.",
         model_shape,
@@ -46,18 +37,37 @@ pub enum MachineLearningError {
         input_shape: MlTensorShape3D,
     },
     #[snafu(display(
-        "Onnx model must have one dimensional output or match the tiling tile size. Found {:?} and {:?}.",
-        tensor_shape,
-        tiling_shape
+        "The model metadata describes {output_bands} output bands, but the model has {output_attributes} output features (bands)."
+    ))]
+    OutputBandsMismatch {
+        output_bands: usize,
+        output_attributes: u32,
+    },
+    #[snafu(display(
+        "The patch overlap ({overlap}) must be smaller than the model's patch size ({patch_shape:?})."
     ))]
-    InvalidOutputPixelShape {
-        tensor_shape: MlTensorShape3D,
-        tiling_shape: GridShape2D,
+    InvalidPatchOverlap {
+        overlap: u32,
+        patch_shape: MlTensorShape3D,
     },
     #[snafu(display(
-        "Currently only a single (1) output feature (band) is supported. Found {output_attributes}"
+        "The number of input time steps ({time_steps}) must be positive and evenly divide the number of input bands ({input_bands})."
+    ))]
+    InvalidNumberOfInputTimeSteps { time_steps: u32, input_bands: u32 },
+    #[snafu(display(
+        "The model consumes {time_steps} time steps, but no input time step is specified."
+    ))]
+    MissingInputTimeStep { time_steps: u32 },
+    #[snafu(display(
+        "An input time step is specified, but the model consumes only a single time step."
+    ))]
+    UnexpectedInputTimeStep,
+    #[snafu(display(
+        "The input time step ({step:?}) must be positive and the covered time window must not overflow."
     ))]
-    UnsupportedNumberOfOutputAttributes { output_attributes: u32 },
+    InvalidInputTimeStep { step: TimeStep },
+    #[snafu(display("The model produced {found} output values, but {expected} were expected."))]
+    UnexpectedNumberOfOutputValues { expected: usize, found: usize },
     #[snafu(display("Onnx model must have Tensor output. Found {:?}.", output_type))]
     InvalidOutputType { output_type: ort::value::ValueType },
     #[snafu(display("Onnx tensor element type {:?} is not supported.", element_type))]
@@ -1,55 +1,97 @@
 use crate::{
     engine::{
         CanonicOperatorName, ExecutionContext, InitializedRasterOperator, InitializedSources,
-        Operator, OperatorName, QueryContext, RasterBandDescriptor, RasterOperator,
-        RasterQueryProcessor, RasterResultDescriptor, SingleRasterSource,
-        TypedRasterQueryProcessor, WorkflowOperatorPath,
+        MultipleRasterSources, Operator, OperatorName, QueryContext, RasterBandDescriptors,
+        RasterOperator, RasterQueryProcessor, RasterResultDescriptor, SingleRasterOrVectorSource,
+        SingleRasterSource, TypedRasterQueryProcessor, WorkflowOperatorPath,
     },
     error,
     machine_learning::{
-        MachineLearningError, MlModelInputNoDataHandling, MlModelLoadingInfo,
-        error::{InputTypeMismatch, Ort},
+        MachineLearningError, MlModelInputNoDataHandling, MlModelLoadingInfo, MlModelMetadata,
+        error::{
+            InputTypeMismatch, InvalidInputTimeStep, InvalidPatchOverlap, MissingInputTimeStep,
+            Ort, UnexpectedInputTimeStep, UnexpectedNumberOfOutputValues,
+        },
         onnx_util::{
             check_model_input_features, check_model_shape, load_onnx_model_from_loading_info,
         },
+        patches::{PatchBlender, PatchLayout, PatchWindow},
     },
-    util::Result,
+    processing::{RasterStacker, RasterStackerParams, TimeShift, TimeShiftParams},
+    util::{Result, input::RasterOrVectorOperator, safe_lock_mutex},
 };
 use async_trait::async_trait;
 use float_cmp::approx_eq;
-use futures::StreamExt;
 use futures::stream::BoxStream;
-use geoengine_datatypes::machine_learning::MlModelName;
-use geoengine_datatypes::primitives::{Measurement, RasterQueryRectangle};
+use futures::{StreamExt, TryStreamExt};
+use geoengine_datatypes::machine_learning::{MlModelName, MlModelVersion};
+use geoengine_datatypes::primitives::{
+    BandSelection, RasterQueryRectangle, SpatialPartition2D, TimeInterval, TimeStep,
+};
 use geoengine_datatypes::raster::{
-    EmptyGrid2D, Grid2D, GridIdx2D, GridIndexAccess, GridShape2D, GridShapeAccess, GridSize,
-    MaskedGrid, Pixel, RasterTile2D, UpdateIndexedElements,
+    EmptyGrid2D, GeoTransform, Grid2D, GridIdx2D, GridIndexAccess, GridShape2D, GridSize,
+    MaskedGrid, Pixel, RasterTile2D, RenameBands, UpdateIndexedElements,
 };
 use ndarray::{Array2, Array4};
 use ort::{
+    session::Session,
     tensor::{IntoTensorElementType, PrimitiveTensorElementType},
     value::TensorRef,
 };
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, ensure};
+use std::sync::{Arc, Mutex};
 
-/// The Onnx operator applies an onnx model to a stack of raster bands and produces a single output tile from each stack.
+/// The Onnx operator applies an onnx model to a stack of raster bands and produces an output tile for each output band of the model from each stack.
 /// *Each of the bands might be empty.*
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OnnxParams {
     /// the name of the model
     pub model: MlModelName,
+    /// the version of the model, i.e., a version number to pin the workflow to a fixed model or `latest`
+    #[serde(default)]
+    pub model_version: MlModelVersion,
+    /// the number of pixels that neighboring patches overlap (only used for patch-based models)
+    #[serde(default)]
+    pub patch_overlap: u32,
+    /// how the predictions of overlapping patches are combined (only used for patch-based models)
+    #[serde(default)]
+    pub patch_blending: PatchBlending,
+    /// the distance between the time steps that are stacked into the input (only used for time-series models)
+    #[serde(default)]
+    pub input_time_step: Option<TimeStep>,
 }
 
 impl OnnxParams {
     pub fn new(model: MlModelName) -> Self {
-        Self { model }
+        Self {
+            model,
+            model_version: MlModelVersion::Latest,
+            patch_overlap: 0,
+            patch_blending: PatchBlending::default(),
+            input_time_step: None,
+        }
     }
 }
 
+/// Strategies to combine the predictions of patches that overlap
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PatchBlending {
+    /// Each pixel is taken from the patch whose center is closest, i.e., the overlap is cropped.
+    /// This is suitable for classifications.
+    #[default]
+    Center,
+    /// The predictions of all patches that cover a pixel are averaged.
+    Average,
+    /// The predictions are averaged with weights that decrease linearly towards the patch borders.
+    Feather,
+}
+
 /// This `QueryProcessor` applies a ml model in Onnx format on all bands of its input raster series.
-/// For now, the model has to be for a single pixel and multiple bands.
+/// The model is either applied to single pixels or to patches of pixels, cf. [`crate::machine_learning::MlModelMetadata`].
+/// Patches may reach beyond the tile borders. In this case, the neighboring pixels are queried from the source.
 pub type Onnx = Operator<OnnxParams, SingleRasterSource>;
 
 impl OperatorName for Onnx {
@@ -66,61 +108,139 @@ impl RasterOperator for Onnx {
     ) -> Result<Box<dyn InitializedRasterOperator>> {
         let name = CanonicOperatorName::from(&self);
 
-        let source = self
-            .sources
+        let Onnx { params, sources } = *self;
+
+        let model_loading_info = context
+            .ml_model_loading_info(&params.model, params.model_version)
+            .await?;
+        let metadata = &model_loading_info.metadata;
+
+        // check that we can use the model input / output shape with the operator
+        check_model_shape(metadata)?;
+
+        let sources = time_series_sources(sources, metadata, params.input_time_step)?;
+
+        let source = sources
             .initialize_sources(path.clone(), context)
             .await?
             .raster;
 
         let in_descriptor = source.result_descriptor();
 
-        let model_loading_info = context.ml_model_loading_info(&self.params.model).await?;
-
-        let tiling_shape = context.tiling_specification().tile_size_in_pixels;
+        check_model_input_features(metadata, in_descriptor.bands.count())?;
 
-        // check that we can use the model input / output shape with the operator
-        check_model_shape(&model_loading_info.metadata, tiling_shape)?;
-        check_model_input_features(
-            &model_loading_info.metadata,
-            tiling_shape,
-            in_descriptor.bands.count(),
-        )?;
+        ensure!(
+            params.patch_overlap < metadata.input_shape.y.min(metadata.input_shape.x),
+            InvalidPatchOverlap {
+                overlap: params.patch_overlap,
+                patch_shape: metadata.input_shape,
+            }
+        );
 
         // check that input type fits model input type
         ensure!(
-            model_loading_info.metadata.input_type == in_descriptor.data_type,
+            metadata.input_type == in_descriptor.data_type,
             InputTypeMismatch {
-                model_input_type: model_loading_info.metadata.input_type,
+                model_input_type: metadata.input_type,
                 source_type: in_descriptor.data_type,
             }
         );
 
         let out_descriptor = RasterResultDescriptor {
-            data_type: model_loading_info.metadata.output_type,
+            data_type: metadata.output_type,
             spatial_reference: in_descriptor.spatial_reference,
             time: in_descriptor.time,
             bbox: in_descriptor.bbox,
             resolution: in_descriptor.resolution,
-            bands: vec![RasterBandDescriptor::new(
-                "prediction".to_string(), // TODO: parameter of the operator?
-                Measurement::Unitless,    // TODO: get output measurement from model metadata
-            )]
-            .try_into()?,
+            bands: RasterBandDescriptors::new(metadata.output_band_descriptors())?,
         };
 
+        let tile_shape = context.tiling_specification().grid_shape();
+
+        let patch_layout = (!metadata.input_is_single_pixel()).then(|| {
+            PatchLayout::new(
+                tile_shape,
+                [
+                    metadata.input_shape.axis_size_y() as usize,
+                    metadata.input_shape.axis_size_x() as usize,
+                ]
+                .into(),
+                params.patch_overlap as usize,
+            )
+        });
+
         Ok(Box::new(InitializedOnnx {
             name,
             path,
             result_descriptor: out_descriptor,
             source,
             model_loading_info,
-            tile_shape: context.tiling_specification().grid_shape(),
+            tile_shape,
+            patch_layout,
+            patch_blending: params.patch_blending,
         }))
     }
 
     span_fn!(Onnx);
 }
 
+/// Stacks the bands of the `input_time_steps` most recent time steps of the source, ordered from the oldest to the most recent one.
+/// Each time step is a separate band of the stack, s.t., empty pixels are handled per time step.
+fn time_series_sources(
+    sources: SingleRasterSource,
+    metadata: &MlModelMetadata,
+    time_step: Option<TimeStep>,
+) -> Result<SingleRasterSource> {
+    let time_steps = metadata.input_time_steps;
+
+    let time_step = match time_step {
+        None if metadata.input_is_time_series() => {
+            return Err(MissingInputTimeStep { time_steps }.build().into());
+        }
+        None => return Ok(sources),
+        Some(_) if !metadata.input_is_time_series() => {
+            return Err(UnexpectedInputTimeStep.build().into());
+        }
+        Some(time_step) => time_step,
+    };
+
+    let rasters = (0..time_steps)
+        .rev()
+        .map(|steps_back| {
+            if steps_back == 0 {
+                return Ok(sources.raster.clone());
+            }
+
+            let value = time_step
+                .step
+                .checked_mul(steps_back)
+                .and_then(|offset| i32::try_from(offset).ok())
+                .filter(|offset| *offset > 0)
+                .ok_or_else(|| InvalidInputTimeStep { step: time_step }.build())?;
+
+            Ok(RasterOperator::boxed(TimeShift {
+                params: TimeShiftParams::Relative {
+                    granularity: time_step.granularity,
+                    value: -value,
+                },
+                sources: SingleRasterOrVectorSource {
+                    source: RasterOrVectorOperator::Raster(sources.raster.clone()),
+                },
+            }))
+        })
+        .collect::<Result<Vec<_>, MachineLearningError>>()?;
+
+    Ok(SingleRasterSource {
+        raster: RasterStacker {
+            params: RasterStackerParams {
+                rename_bands: RenameBands::Default,
+            },
+            sources: MultipleRasterSources { rasters },
+        }
+        .boxed(),
+    })
+}
+
 pub struct InitializedOnnx {
     name: CanonicOperatorName,
     path: WorkflowOperatorPath,
@@ -128,6 +248,8 @@ pub struct InitializedOnnx {
     source: Box<dyn InitializedRasterOperator>,
     model_loading_info: MlModelLoadingInfo,
     tile_shape: GridShape2D,
+    patch_layout: Option<PatchLayout>,
+    patch_blending: PatchBlending,
 }
 
 impl InitializedRasterOperator for InitializedOnnx {
@@ -147,6 +269,8 @@ impl InitializedRasterOperator for InitializedOnnx {
                         self.result_descriptor.clone(),
                         self.model_loading_info.clone(),
                         self.tile_shape,
+                        self.patch_layout,
+                        self.patch_blending,
                     )
                     .boxed()
                 )
@@ -173,6 +297,9 @@ pub(crate) struct OnnxProcessor<TIn, TOut> {
     model_loading_info: MlModelLoadingInfo,
     phantom: std::marker::PhantomData<TOut>,
     tile_shape: GridShape2D,
+    /// the arrangement of the patches for patch-based models, `None` for pixel-wise models
+    patch_layout: Option<PatchLayout>,
+    patch_blending: PatchBlending,
 }
 
 impl<TIn, TOut> OnnxProcessor<TIn, TOut> {
@@ -181,6 +308,8 @@ impl<TIn, TOut> OnnxProcessor<TIn, TOut> {
         result_descriptor: RasterResultDescriptor,
         model_loading_info: MlModelLoadingInfo,
         tile_shape: GridShape2D,
+        patch_layout: Option<PatchLayout>,
+        patch_blending: PatchBlending,
     ) -> Self {
         Self {
             source,
@@ -188,211 +317,395 @@ impl<TIn, TOut> OnnxProcessor<TIn, TOut> {
             model_loading_info,
             phantom: Default::default(),
             tile_shape,
+            patch_layout,
+            patch_blending,
         }
     }
 }
 
-#[async_trait]
-impl<TIn, TOut> RasterQueryProcessor for OnnxProcessor<TIn, TOut>
+impl<TIn, TOut> OnnxProcessor<TIn, TOut>
 where
     TIn: Pixel + NoDataValueIn + IntoTensorElementType + PrimitiveTensorElementType,
     TOut: Pixel + NoDataValueOut + IntoTensorElementType + PrimitiveTensorElementType,
 {
-    type RasterType = TOut;
-
-    #[allow(clippy::too_many_lines)]
-    async fn raster_query<'a>(
-        &'a self,
-        query: RasterQueryRectangle,
-        ctx: &'a dyn QueryContext,
-    ) -> Result<BoxStream<'a, Result<RasterTile2D<TOut>>>> {
-        let num_bands = self.source.raster_result_descriptor().bands.count() as usize;
-
-        let in_no_data_value = self
-            .model_loading_info
+    fn in_no_data_value(&self) -> TIn {
+        self.model_loading_info
             .metadata
             .input_no_data_handling
             .no_data_value_encoding()
-            .map_or(TIn::NO_DATA_IN_FALLBACK, |v| TIn::from_(v));
+            .map_or(TIn::NO_DATA_IN_FALLBACK, |v| TIn::from_(v))
+    }
 
-        let out_no_data_value = self
-            .model_loading_info
+    fn out_no_data_value(&self) -> Option<TOut> {
+        self.model_loading_info
             .metadata
             .output_no_data_handling
             .no_data_value_encoding()
             .map(|v| TOut::from_(v))
-            .or(TOut::NO_DATA_OUT_FALLBACK); // Int types return Some or None while float types fallback to Some(NaN)
-
-        let mut source_query = query.clone();
-        source_query.attributes = (0..num_bands as u32).collect::<Vec<u32>>().try_into()?;
-
-        // TODO: re-use session accross queries?
-        // TODO: use another method: https://github.com/pykeio/ort/issues/402#issuecomment-2949993914
-        let mut session = load_onnx_model_from_loading_info(&self.model_loading_info)?;
-        let input_name = session.inputs[0].name.clone(); // clone input name to avoid mutability problems
+            .or(TOut::NO_DATA_OUT_FALLBACK) // Int types return Some or None while float types fallback to Some(NaN)
+    }
 
-        let stream = self
-            .source
-            .raster_query(source_query, ctx)
-            .await?
-            .chunks(num_bands) // chunk the tiles to get all bands for a spatial index at once
-            // TODO: this does not scale for large number of bands.
-            //       In that case we would need to collect only a fixed number of pixel from each each,
-            //       and repeat the process until the whole tile is finished
-            .map(move |chunk| {
-                // TODO: spawn task and await
+    /// Apply the model to the tiles of all input bands of one tile position and produce the tiles of the queried output bands
+    #[allow(clippy::too_many_lines)]
+    async fn process_tile_stack(
+        &self,
+        chunk: Vec<Result<RasterTile2D<TIn>>>,
+        query: &RasterQueryRectangle,
+        ctx: &dyn QueryContext,
+        session: &Arc<Mutex<Session>>,
+    ) -> Result<Vec<RasterTile2D<TOut>>> {
+        let num_bands = self.source.raster_result_descriptor().bands.count() as usize;
 
-                if chunk.len() != num_bands {
-                    // if there are not exactly N tiles, it should mean the last tile was an error and the chunker ended prematurely
-                    if let Some(Err(e)) = chunk.into_iter().next_back() {
-                        return Err(e);
-                    }
-                    // if there is no error, the source did not produce all bands, which likely means a bug in an operator
-                    return Err(error::Error::MustNotHappen {
-                        message: "source did not produce all bands".to_string(),
-                    });
-                }
+        if chunk.len() != num_bands {
+            // if there are not exactly N tiles, it should mean the last tile was an error and the chunker ended prematurely
+            if let Some(Err(e)) = chunk.into_iter().next_back() {
+                return Err(e);
+            }
+            // if there is no error, the source did not produce all bands, which likely means a bug in an operator
+            return Err(error::Error::MustNotHappen {
+                message: "source did not produce all bands".to_string(),
+            });
+        }
 
-                // TODO: collect into a ndarray directly
-                let tiles = chunk.into_iter().collect::<Result<Vec<_>>>()?;
-
-                let first_tile = &tiles[0];
-                let time = first_tile.time;
-                let tile_position = first_tile.tile_position;
-                let global_geo_transform = first_tile.global_geo_transform;
-                let cache_hint = first_tile.cache_hint;
-
-                let tile_shape = self.tile_shape;
-                let width = tile_shape.axis_size_x();
-                let height = tile_shape.axis_size_y();
-
-                // This determines if the tile the operator currently processes can be skipped entirely.
-                // The Operator uses a "stack" of bands where each band is represented by a raster tile.
-                // Each of the bands might be an empty tile. To determine if the processing should be skipped (onnx model is not called and output is an empty tile) we use the following match block:
-                let skip_tile = match self.model_loading_info.metadata.input_no_data_handling {
-                    // The production of the output tile is never skipped --> the onnx model is called even if all inputs are empty.
-                    // The onnx model is not called if all inputs are empty. This is usefull if the onnx model can handle missing data.
-                    MlModelInputNoDataHandling::EncodedNoData { no_data_value: _} => tiles.iter().all(geoengine_datatypes::raster::BaseTile::is_empty),
-                    // The onnx model is not called if any band is empty. This is usefull if the onnx model can't handle missing data.
-                    MlModelInputNoDataHandling::SkipIfNoData => tiles.iter().any(geoengine_datatypes::raster::BaseTile::is_empty),
-                };
-                tracing::debug!("skip_tile is set to {skip_tile} after evaluating input empty tiles.");
-
-                // If the model was applied, we need to handle single pixels based on the input pixel validity (mask).
-                // The validity mask is a positive mask (0 == no-data, 1 == valid data).
-                // To generate the output mask, we fold over the input masks starting with an appropriate accu value which is genernated as follows:
-                let output_mask_fill_value = match self.model_loading_info.metadata.input_no_data_handling {
-                    MlModelInputNoDataHandling::EncodedNoData{no_data_value: _} => false,
-                    MlModelInputNoDataHandling::SkipIfNoData => true
-                };
+        // TODO: collect into a ndarray directly
+        let tiles = chunk.into_iter().collect::<Result<Vec<_>>>()?;
+
+        let first_tile = &tiles[0];
+        let time = first_tile.time;
+        let tile_position = first_tile.tile_position;
+        let global_geo_transform = first_tile.global_geo_transform;
+        let cache_hint = first_tile.cache_hint;
+
+        // This determines if the tile the operator currently processes can be skipped entirely.
+        // The Operator uses a "stack" of bands where each band is represented by a raster tile.
+        // Each of the bands might be an empty tile. To determine if the processing should be skipped (onnx model is not called and output is an empty tile) we use the following match block:
+        let skip_tile = match self.model_loading_info.metadata.input_no_data_handling {
+            // The production of the output tile is never skipped --> the onnx model is called even if all inputs are empty.
+            // The onnx model is not called if all inputs are empty. This is usefull if the onnx model can handle missing data.
+            MlModelInputNoDataHandling::EncodedNoData { no_data_value: _ } => tiles
+                .iter()
+                .all(geoengine_datatypes::raster::BaseTile::is_empty),
+            // The onnx model is not called if any band is empty. This is usefull if the onnx model can't handle missing data.
+            MlModelInputNoDataHandling::SkipIfNoData => tiles
+                .iter()
+                .any(geoengine_datatypes::raster::BaseTile::is_empty),
+        };
+        tracing::debug!("skip_tile is set to {skip_tile} after evaluating input empty tiles.");
+
+        // If the model was applied, we need to handle single pixels based on the input pixel validity (mask).
+        // The validity mask is a positive mask (0 == no-data, 1 == valid data).
+        // To generate the output mask, we fold over the input masks starting with an appropriate accu value which is genernated as follows:
+        let output_mask_fill_value = match self.model_loading_info.metadata.input_no_data_handling {
+            MlModelInputNoDataHandling::EncodedNoData { no_data_value: _ } => false,
+            MlModelInputNoDataHandling::SkipIfNoData => true,
+        };
 
-                let mut output_mask = Grid2D::new_filled(self.tile_shape, output_mask_fill_value);
-                if !(skip_tile) {
-                    for c in &tiles {
-                        if let Some(mg) = c.grid_array.as_masked_grid() {
-                            output_mask.update_indexed_elements(|idx: GridIdx2D, value| {
-                                let mask_value = mg.mask_ref().get_at_grid_index_unchecked(idx);
-                                match self.model_loading_info.metadata.input_no_data_handling {
-                                    MlModelInputNoDataHandling::EncodedNoData { no_data_value: _} => mask_value || value, // if any pixel is valid this sticks to true
-                                    MlModelInputNoDataHandling::SkipIfNoData  => mask_value && value, // if any pixel is invalid, this sticks to false
-                                }
-                            });
+        let mut output_mask = Grid2D::new_filled(self.tile_shape, output_mask_fill_value);
+        if !(skip_tile) {
+            for c in &tiles {
+                if let Some(mg) = c.grid_array.as_masked_grid() {
+                    output_mask.update_indexed_elements(|idx: GridIdx2D, value| {
+                        let mask_value = mg.mask_ref().get_at_grid_index_unchecked(idx);
+                        match self.model_loading_info.metadata.input_no_data_handling {
+                            MlModelInputNoDataHandling::EncodedNoData { no_data_value: _ } => {
+                                mask_value || value
+                            } // if any pixel is valid this sticks to true
+                            MlModelInputNoDataHandling::SkipIfNoData => mask_value && value, // if any pixel is invalid, this sticks to false
                         }
-                    }
+                    });
                 }
+            }
+        }
 
-                let skip_tile = skip_tile || output_mask.data.iter().all(|&v| !v);
-                tracing::debug!("skip_tile is set to {skip_tile} after merging all input masks.");
+        let skip_tile = skip_tile || output_mask.data.iter().all(|&v| !v);
+        tracing::debug!("skip_tile is set to {skip_tile} after merging all input masks.");
 
-                // if the tile is skipable or the output mask has no valid pixels:
-                if skip_tile {
-                    tracing::trace!("Skipping Tile {tile_position:?}");
-                    return Ok(RasterTile2D::new(
+        // if the tile is skipable or the output mask has no valid pixels:
+        if skip_tile {
+            tracing::trace!("Skipping Tile {tile_position:?}");
+            return Ok((0..query.attributes.count())
+                .map(|band| {
+                    RasterTile2D::new(
                         time,
                         tile_position,
-                        0,
+                        band,
                         global_geo_transform,
-                        EmptyGrid2D::new(tile_shape).into(),
+                        EmptyGrid2D::new(self.tile_shape).into(),
                         cache_hint,
-                    ))
-                }
-
-                // TODO: use flat array instead of nested Vecs
-                let mut move_axis_pixels: Vec<Vec<TIn>> = vec![vec![TIn::zero(); num_bands]; width * height];
-
-                for (tile_index, tile) in tiles.into_iter().enumerate() {
-                    // TODO: use map_elements or map_elements_parallel to avoid the double loop
-                    for y in 0..height {
-                        for x in 0..width {
-                            let pixel_index = y * width + x;
-                            let pixel_value = tile
-                                .get_at_grid_index(GridIdx2D::from([y as isize, x as isize]))?
-                                .unwrap_or(in_no_data_value); // TODO: properly handle missing values or skip the pixel entirely instead
-                            move_axis_pixels[pixel_index][tile_index] = pixel_value;
-                        }
-                    }
-                }
-
-                let pixels = move_axis_pixels.into_iter().flatten().collect::<Vec<TIn>>();
-                let outputs = if self.model_loading_info.metadata.input_is_single_pixel() {
-                    let samples = Array2::from_shape_vec((width*height, num_bands), pixels).expect(
-                        "Array2 should be valid because it is created from a Vec with the correct size",
-                    );
-                    session
-                        .run(ort::inputs![&input_name => TensorRef::from_array_view(&samples).context(Ort)?])
-                        .context(Ort)
-                } else if self.model_loading_info.metadata.input_shape.yx_matches_tile_shape(&tile_shape){
-                    let samples = Array4::from_shape_vec((1, height, width, num_bands), pixels).expect( // y,x, attributes
-                        "Array4 should be valid because it is created from a Vec with the correct size",
-                    );
-                    session
-                        .run(ort::inputs![&input_name => TensorRef::from_array_view(&samples).context(Ort)?])
-                        .context(Ort)
-                } else {
-                    Err(
-                        MachineLearningError::InvalidInputPixelShape {
-                            tensor_shape: self.model_loading_info.metadata.input_shape,
-                            tiling_shape: tile_shape
-                        }
                     )
-                }.map_err(error::Error::from)?;
+                })
+                .collect());
+        }
 
-                // assume the first output is the prediction and ignore the other outputs (e.g. probabilities for classification)
-                // we don't access the output by name because it can vary, e.g. "output_label" vs "variable"
-                let predictions = outputs[0].try_extract_tensor::<TOut>().context(Ort)?;
+        let mut predictions = match self.patch_layout {
+            None => self.predict_pixels(tiles, session)?,
+            Some(patch_layout) => {
+                self.predict_patches(&tiles, patch_layout, query, ctx, session)
+                    .await?
+            }
+        };
 
-                // extract the values as a raw vector because we expect one prediction per pixel.
-                // this works for 1d tensors as well as 2d tensors with a single column
-                let (shape, out_tensor_data) = predictions.to_owned();
-                debug_assert!(shape.num_elements() == width*height); // TODO: use shape directly to check
+        let out_no_data_value = self.out_no_data_value();
 
-                // transform the output intp a grid
-                let out_grid = Grid2D::new([width, height].into(), Vec::from(out_tensor_data))?;
+        query
+            .attributes
+            .as_slice()
+            .iter()
+            .enumerate()
+            .map(|(band, &model_band)| -> Result<RasterTile2D<TOut>> {
+                // transform the output into a grid
+                let out_grid = Grid2D::new(
+                    self.tile_shape,
+                    std::mem::take(&mut predictions[model_band as usize]),
+                )?;
 
                 // update the mask based on out no data value
-                if let Some(out_no_data) = out_no_data_value { // For float types this will always be Some(NaN) while int might be None!
-                    output_mask.update_indexed_elements(|idx: GridIdx2D, validity_mask| {
+                let mut band_mask = output_mask.clone();
+                if let Some(out_no_data) = out_no_data_value {
+                    // For float types this will always be Some(NaN) while int might be None!
+                    band_mask.update_indexed_elements(|idx: GridIdx2D, validity_mask| {
                         let out_value = out_grid.get_at_grid_index_unchecked(idx);
                         validity_mask && !out_no_data.is_no_data(out_value) // Impl for f32 and f64 will always set NaN as invalid
                     });
                 }
 
                 // if the validity mask has valid pixels --> return the model output + mask. Otherwise return empty tile.
-                let final_grid = if output_mask.data.iter().any(|&v| v) {
-                    MaskedGrid::new(out_grid, output_mask)?.into()
+                let final_grid = if band_mask.data.iter().any(|&v| v) {
+                    MaskedGrid::new(out_grid, band_mask)?.into()
                 } else {
-                    EmptyGrid2D::new(out_grid.shape).into()
+                    EmptyGrid2D::new(self.tile_shape).into()
                 };
 
                 Ok(RasterTile2D::new(
                     time,
                     tile_position,
-                    0,
+                    band as u32,
                     global_geo_transform,
                     final_grid,
                     cache_hint,
                 ))
-            });
+            })
+            .collect()
+    }
+
+    /// Apply a pixel-wise model to all pixels of the tiles and return the predictions of each model output band
+    fn predict_pixels(
+        &self,
+        tiles: Vec<RasterTile2D<TIn>>,
+        session: &Arc<Mutex<Session>>,
+    ) -> Result<Vec<Vec<TOut>>> {
+        let num_bands = tiles.len();
+        let num_pixels = self.tile_shape.number_of_elements();
+        let width = self.tile_shape.axis_size_x();
+        let height = self.tile_shape.axis_size_y();
+        let in_no_data_value = self.in_no_data_value();
+
+        // TODO: use flat array instead of nested Vecs
+        let mut move_axis_pixels: Vec<Vec<TIn>> = vec![vec![TIn::zero(); num_bands]; num_pixels];
+
+        for (tile_index, tile) in tiles.into_iter().enumerate() {
+            // TODO: use map_elements or map_elements_parallel to avoid the double loop
+            for y in 0..height {
+                for x in 0..width {
+                    let pixel_index = y * width + x;
+                    let pixel_value = tile
+                        .get_at_grid_index(GridIdx2D::from([y as isize, x as isize]))?
+                        .unwrap_or(in_no_data_value); // TODO: properly handle missing values or skip the pixel entirely instead
+                    move_axis_pixels[pixel_index][tile_index] = pixel_value;
+                }
+            }
+        }
+
+        let pixels = move_axis_pixels.into_iter().flatten().collect::<Vec<TIn>>();
+        let samples = Array2::from_shape_vec((num_pixels, num_bands), pixels).expect(
+            "Array2 should be valid because it is created from a Vec with the correct size",
+        );
+
+        let num_output_bands = self.model_loading_info.metadata.num_output_bands() as usize;
+
+        let mut session = safe_lock_mutex(session);
+        let input_name = session.inputs[0].name.clone(); // clone input name to avoid mutability problems
+        let outputs = session
+            .run(ort::inputs![&input_name => TensorRef::from_array_view(&samples).context(Ort)?])
+            .context(Ort)?;
+
+        // assume the first output is the prediction and ignore the other outputs (e.g. probabilities for classification)
+        // we don't access the output by name because it can vary, e.g. "output_label" vs "variable"
+        // this works for 1d tensors as well as 2d tensors with one column per output band
+        let (_shape, predictions) = outputs[0].try_extract_tensor::<TOut>().context(Ort)?;
+
+        ensure!(
+            predictions.len() == num_pixels * num_output_bands,
+            UnexpectedNumberOfOutputValues {
+                expected: num_pixels * num_output_bands,
+                found: predictions.len(),
+            }
+        );
+
+        Ok((0..num_output_bands)
+            .map(|band| {
+                predictions
+                    .iter()
+                    .skip(band)
+                    .step_by(num_output_bands)
+                    .copied()
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Apply a patch-based model to the patches that cover the tiles and return the blended predictions of each model output band
+    async fn predict_patches(
+        &self,
+        tiles: &[RasterTile2D<TIn>],
+        patch_layout: PatchLayout,
+        query: &RasterQueryRectangle,
+        ctx: &dyn QueryContext,
+        session: &Arc<Mutex<Session>>,
+    ) -> Result<Vec<Vec<TOut>>> {
+        let num_bands = tiles.len();
+        let first_tile = &tiles[0];
+        let global_geo_transform = first_tile.global_geo_transform;
+        let window_origin = first_tile.tile_information().global_upper_left_pixel_idx()
+            - patch_layout.tile_offset();
+
+        let mut window = PatchWindow::new(
+            window_origin,
+            patch_layout.window_shape(),
+            num_bands,
+            self.in_no_data_value(),
+        );
+
+        if patch_layout.has_margin() {
+            // the patches reach beyond the tile, so we query the surrounding pixels as well
+            let window_query = RasterQueryRectangle {
+                spatial_bounds: window_spatial_bounds(
+                    global_geo_transform,
+                    window_origin,
+                    patch_layout.window_shape(),
+                )?,
+                time_interval: TimeInterval::new_instant(first_tile.time.start())?,
+                spatial_resolution: query.spatial_resolution,
+                attributes: BandSelection::first_n(num_bands as u32),
+            };
+
+            let mut window_tiles = self.source.raster_query(window_query, ctx).await?;
+            while let Some(tile) = window_tiles.next().await {
+                window.add_tile(&tile?);
+            }
+        } else {
+            for tile in tiles {
+                window.add_tile(tile);
+            }
+        }
+
+        let patch_shape = patch_layout.patch_shape();
+        let (patch_height, patch_width) = (patch_shape.axis_size_y(), patch_shape.axis_size_x());
+        let num_output_bands = self.model_loading_info.metadata.num_output_bands() as usize;
+        let num_output_values = patch_shape.number_of_elements() * num_output_bands;
+
+        let mut blender = PatchBlender::new(
+            patch_layout,
+            self.patch_blending,
+            self.tile_shape,
+            num_output_bands,
+        );
+
+        let mut session = safe_lock_mutex(session);
+        let input_name = session.inputs[0].name.clone(); // clone input name to avoid mutability problems
+
+        for patch_origin in patch_layout.patch_origins() {
+            let samples = Array4::from_shape_vec(
+                (1, patch_height, patch_width, num_bands), // y,x, attributes
+                window.patch(patch_origin, patch_shape),
+            )
+            .expect(
+                "Array4 should be valid because it is created from a Vec with the correct size",
+            );
+
+            let outputs = session
+                .run(
+                    ort::inputs![&input_name => TensorRef::from_array_view(&samples).context(Ort)?],
+                )
+                .context(Ort)?;
+
+            // assume the first output is the prediction, cf. `predict_pixels`
+            let (_shape, predictions) = outputs[0].try_extract_tensor::<TOut>().context(Ort)?;
+
+            ensure!(
+                predictions.len() == num_output_values,
+                UnexpectedNumberOfOutputValues {
+                    expected: num_output_values,
+                    found: predictions.len(),
+                }
+            );
+
+            blender.add_patch(patch_origin, predictions);
+        }
+
+        Ok(blender.into_bands())
+    }
+}
+
+/// The spatial bounds of the window with the given upper left pixel and shape
+fn window_spatial_bounds(
+    global_geo_transform: GeoTransform,
+    window_origin: GridIdx2D,
+    window_shape: GridShape2D,
+) -> Result<SpatialPartition2D> {
+    let window_end = window_origin
+        + [
+            window_shape.axis_size_y() as isize,
+            window_shape.axis_size_x() as isize,
+        ];
+
+    Ok(SpatialPartition2D::new(
+        global_geo_transform.grid_idx_to_pixel_upper_left_coordinate_2d(window_origin),
+        global_geo_transform.grid_idx_to_pixel_upper_left_coordinate_2d(window_end),
+    )?)
+}
+
+#[async_trait]
+impl<TIn, TOut> RasterQueryProcessor for OnnxProcessor<TIn, TOut>
+where
+    TIn: Pixel + NoDataValueIn + IntoTensorElementType + PrimitiveTensorElementType,
+    TOut: Pixel + NoDataValueOut + IntoTensorElementType + PrimitiveTensorElementType,
+{
+    type RasterType = TOut;
+
+    async fn raster_query<'a>(
+        &'a self,
+        query: RasterQueryRectangle,
+        ctx: &'a dyn QueryContext,
+    ) -> Result<BoxStream<'a, Result<RasterTile2D<TOut>>>> {
+        let num_bands = self.source.raster_result_descriptor().bands.count() as usize;
+
+        let mut source_query = query.clone();
+        source_query.attributes = (0..num_bands as u32).collect::<Vec<u32>>().try_into()?;
+
+        // TODO: re-use session accross queries?
+        // TODO: use another method: https://github.com/pykeio/ort/issues/402#issuecomment-2949993914
+        let session = Arc::new(Mutex::new(load_onnx_model_from_loading_info(
+            &self.model_loading_info,
+        )?));
+
+        let stream = self
+            .source
+            .raster_query(source_query, ctx)
+            .await?
+            .chunks(num_bands) // chunk the tiles to get all bands for a spatial index at once
+            // TODO: this does not scale for large number of bands.
+            //       In that case we would need to collect only a fixed number of pixel from each each,
+            //       and repeat the process until the whole tile is finished
+            .then(move |chunk| {
+                // TODO: spawn task and await
+                let query = query.clone();
+                let session = session.clone();
+                async move { self.process_tile_stack(chunk, &query, ctx, &session).await }
+            })
+            // each tile stack produces one tile per queried output band
+            .map_ok(|tiles| futures::stream::iter(tiles.into_iter().map(Ok::<_, error::Error>)))
+            .try_flatten();
 
         Ok(stream.boxed())
     }
@@ -476,7 +789,8 @@ mod tests {
     use super::*;
     use crate::{
         engine::{
-            MockExecutionContext, MockQueryContext, MultipleRasterSources, RasterBandDescriptors,
+            MockExecutionContext, MockQueryContext, MultipleRasterSources, RasterBandDescriptor,
+            RasterBandDescriptors,
         },
         machine_learning::{MlModelMetadata, MlModelOutputNoDataHandling},
         mock::{MockRasterSource, MockRasterSourceParams},
@@ -485,7 +799,10 @@ mod tests {
     use approx::assert_abs_diff_eq;
     use geoengine_datatypes::{
         machine_learning::MlTensorShape3D,
-        primitives::{CacheHint, SpatialPartition2D, SpatialResolution, TimeInterval},
+        primitives::{
+            CacheHint, Measurement, SpatialPartition2D, SpatialResolution, TimeGranularity,
+            TimeInterval, TimeStep,
+        },
         raster::{
             Grid, GridOrEmpty, GridShape, RasterDataType, RenameBands, TilesEqualIgnoringCacheHint,
         },
@@ -709,6 +1026,8 @@ mod tests {
                 output_type: RasterDataType::I64,
                 input_no_data_handling: MlModelInputNoDataHandling::SkipIfNoData,
                 output_no_data_handling: MlModelOutputNoDataHandling::NanIsNoData,
+                output_bands: vec![],
+                input_time_steps: 1,
             },
         };
 
@@ -924,6 +1243,8 @@ mod tests {
                 output_type: RasterDataType::F32,
                 input_no_data_handling: MlModelInputNoDataHandling::SkipIfNoData,
                 output_no_data_handling: MlModelOutputNoDataHandling::NanIsNoData,
+                output_bands: vec![],
+                input_time_steps: 1,
             },
         };
 
@@ -1088,6 +1409,8 @@ mod tests {
                 output_type: RasterDataType::F32,
                 input_no_data_handling: MlModelInputNoDataHandling::SkipIfNoData,
                 output_no_data_handling: MlModelOutputNoDataHandling::NanIsNoData,
+                output_bands: vec![],
+                input_time_steps: 1,
             },
         };
 
@@ -1151,4 +1474,339 @@ mod tests {
 
         assert!(expected.tiles_equal_ignoring_cache_hint(&result));
     }
+
+    fn single_band_source(tiles: Vec<(isize, f32)>, tile_size: usize) -> Box<dyn RasterOperator> {
+        single_band_time_series_source(
+            tiles
+                .into_iter()
+                .map(|(x, value)| (TimeInterval::new_unchecked(0, 5), x, value))
+                .collect(),
+            tile_size,
+        )
+    }
+
+    fn single_band_time_series_source(
+        tiles: Vec<(TimeInterval, isize, f32)>,
+        tile_size: usize,
+    ) -> Box<dyn RasterOperator> {
+        MockRasterSource {
+            params: MockRasterSourceParams {
+                data: tiles
+                    .into_iter()
+                    .map(|(time, x, value)| RasterTile2D {
+                        time,
+                        tile_position: [-1, x].into(),
+                        band: 0,
+                        global_geo_transform: TestDefault::test_default(),
+                        grid_array: Grid::new(
+                            [tile_size, tile_size].into(),
+                            vec![value; tile_size * tile_size],
+                        )
+                        .unwrap()
+                        .into(),
+                        properties: Default::default(),
+                        cache_hint: CacheHint::default(),
+                    })
+                    .collect(),
+                result_descriptor: RasterResultDescriptor {
+                    data_type: RasterDataType::F32,
+                    spatial_reference: SpatialReference::epsg_4326().into(),
+                    time: None,
+                    bbox: None,
+                    resolution: None,
+                    bands: RasterBandDescriptors::new_single_band(),
+                },
+            },
+        }
+        .boxed()
+    }
+
+    #[tokio::test]
+    async fn it_applies_patches_across_tiles() {
+        let stacker = RasterStacker {
+            params: RasterStackerParams {
+                rename_bands: RenameBands::Default,
+            },
+            sources: MultipleRasterSources {
+                rasters: vec![
+                    single_band_source(vec![(0, 0.1), (1, 1.0)], 256),
+                    single_band_source(vec![(0, 0.2), (1, 2.0)], 256),
+                ],
+            },
+        }
+        .boxed();
+
+        let model_name = MlModelName {
+            namespace: None,
+            name: "test_a_plus_b".into(),
+        };
+
+        let onnx = Onnx {
+            params: OnnxParams {
+                model: model_name.clone(),
+                model_version: MlModelVersion::Latest,
+                patch_overlap: 0,
+                patch_blending: PatchBlending::Center,
+                input_time_step: None,
+            },
+            sources: SingleRasterSource { raster: stacker },
+        }
+        .boxed();
+
+        // the model patches are larger than the tiles, so each patch reaches into the neighboring tiles
+        let ml_model_loading_info = MlModelLoadingInfo {
+            storage_path: test_data!("ml/onnx/test_a_plus_b.onnx").to_owned(),
+            metadata: MlModelMetadata {
+                input_type: RasterDataType::F32,
+                input_shape: MlTensorShape3D::new_y_x_bands(512, 512, 2),
+                output_shape: MlTensorShape3D::new_y_x_bands(512, 512, 1),
+                output_type: RasterDataType::F32,
+                input_no_data_handling: MlModelInputNoDataHandling::SkipIfNoData,
+                output_no_data_handling: MlModelOutputNoDataHandling::NanIsNoData,
+                output_bands: vec![RasterBandDescriptor::new(
+                    "sum".to_string(),
+                    Measurement::continuous("sum".to_string(), None),
+                )],
+                input_time_steps: 1,
+            },
+        };
+
+        let mut exe_ctx = MockExecutionContext::test_default();
+        exe_ctx.tiling_specification.tile_size_in_pixels = GridShape {
+            shape_array: [256, 256],
+        };
+        exe_ctx.ml_models.insert(model_name, ml_model_loading_info);
+
+        let op = onnx
+            .initialize(WorkflowOperatorPath::initialize_root(), &exe_ctx)
+            .await
+            .unwrap();
+
+        assert_eq!(op.result_descriptor().bands[0].name, "sum");
+
+        let qp = op.query_processor().unwrap().get_f32().unwrap();
+
+        let query_rect = RasterQueryRectangle {
+            spatial_bounds: SpatialPartition2D::new_unchecked((0., 256.).into(), (512., 0.).into()),
+            time_interval: TimeInterval::new_unchecked(0, 5),
+            spatial_resolution: SpatialResolution::one(),
+            attributes: [0].try_into().unwrap(),
+        };
+
+        let result = qp
+            .raster_query(query_rect, &MockQueryContext::test_default())
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+        let result = result.into_iter().collect::<Result<Vec<_>>>().unwrap();
+
+        assert_eq!(result.len(), 2);
+
+        for (tile, expected) in result.iter().zip([0.3f32, 3.0]) {
+            let GridOrEmpty::Grid(result_array) = &tile.grid_array else {
+                panic!("no result array")
+            };
+
+            assert!(result_array.validity_mask.data.iter().all(|&valid| valid));
+            assert_abs_diff_eq!(
+                result_array.inner_grid.data.as_slice(),
+                vec![expected; 256 * 256].as_slice(),
+                epsilon = 1e-6
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn it_stacks_time_steps() {
+        let model_name = MlModelName {
+            namespace: None,
+            name: "test_a_plus_b".into(),
+        };
+
+        // the model sums up the band of two consecutive time steps
+        let ml_model_loading_info = MlModelLoadingInfo {
+            storage_path: test_data!("ml/onnx/test_a_plus_b.onnx").to_owned(),
+            metadata: MlModelMetadata {
+                input_type: RasterDataType::F32,
+                input_shape: MlTensorShape3D::new_y_x_bands(512, 512, 2),
+                output_shape: MlTensorShape3D::new_y_x_bands(512, 512, 1),
+                output_type: RasterDataType::F32,
+                input_no_data_handling: MlModelInputNoDataHandling::SkipIfNoData,
+                output_no_data_handling: MlModelOutputNoDataHandling::NanIsNoData,
+                output_bands: vec![],
+                input_time_steps: 2,
+            },
+        };
+
+        let mut exe_ctx = MockExecutionContext::test_default();
+        exe_ctx.tiling_specification.tile_size_in_pixels = GridShape {
+            shape_array: [256, 256],
+        };
+        exe_ctx
+            .ml_models
+            .insert(model_name.clone(), ml_model_loading_info);
+
+        // the second tile is missing in the first time step
+        let source = single_band_time_series_source(
+            vec![
+                (TimeInterval::new_unchecked(0, 5), 0, 0.1),
+                (TimeInterval::new_unchecked(5, 10), 0, 0.2),
+                (TimeInterval::new_unchecked(5, 10), 1, 2.0),
+            ],
+            256,
+        );
+
+        let onnx = |input_time_step: Option<TimeStep>| Onnx {
+            params: OnnxParams {
+                input_time_step,
+                ..OnnxParams::new(model_name.clone())
+            },
+            sources: SingleRasterSource {
+                raster: source.clone(),
+            },
+        };
+
+        assert!(
+            onnx(None)
+                .boxed()
+                .initialize(WorkflowOperatorPath::initialize_root(), &exe_ctx)
+                .await
+                .is_err()
+        );
+
+        let op = onnx(Some(TimeStep {
+            granularity: TimeGranularity::Millis,
+            step: 5,
+        }))
+        .boxed()
+        .initialize(WorkflowOperatorPath::initialize_root(), &exe_ctx)
+        .await
+        .unwrap();
+
+        let qp = op.query_processor().unwrap().get_f32().unwrap();
+
+        let query_rect = RasterQueryRectangle {
+            spatial_bounds: SpatialPartition2D::new_unchecked((0., 256.).into(), (512., 0.).into()),
+            time_interval: TimeInterval::new_unchecked(5, 10),
+            spatial_resolution: SpatialResolution::one(),
+            attributes: [0].try_into().unwrap(),
+        };
+
+        let result = qp
+            .raster_query(query_rect, &MockQueryContext::test_default())
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+        let result = result.into_iter().collect::<Result<Vec<_>>>().unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert!(
+            result
+                .iter()
+                .all(|tile| tile.time == TimeInterval::new_unchecked(5, 10))
+        );
+
+        let GridOrEmpty::Grid(result_array) = &result[0].grid_array else {
+            panic!("no result array")
+        };
+        assert!(result_array.validity_mask.data.iter().all(|&valid| valid));
+        assert_abs_diff_eq!(
+            result_array.inner_grid.data.as_slice(),
+            vec![0.3f32; 256 * 256].as_slice(),
+            epsilon = 1e-6
+        );
+
+        // the missing time step is no-data, so the prediction is skipped
+        assert!(result[1].is_empty());
+    }
+
+    #[tokio::test]
+    async fn it_validates_output_bands_and_overlap() {
+        let model_name = MlModelName {
+            namespace: None,
+            name: "test_classification".into(),
+        };
+
+        let metadata = MlModelMetadata {
+            input_type: RasterDataType::F32,
+            input_shape: MlTensorShape3D::new_single_pixel_bands(2),
+            output_shape: MlTensorShape3D::new_single_pixel_single_band(),
+            output_type: RasterDataType::I64,
+            input_no_data_handling: MlModelInputNoDataHandling::SkipIfNoData,
+            output_no_data_handling: MlModelOutputNoDataHandling::NanIsNoData,
+            output_bands: vec![RasterBandDescriptor::new(
+                "class".to_string(),
+                Measurement::classification(
+                    "label".to_string(),
+                    [(33, "a".to_string()), (42, "b".to_string())].into(),
+                ),
+            )],
+            input_time_steps: 1,
+        };
+
+        let initialize = |params: OnnxParams, metadata: MlModelMetadata| {
+            let mut exe_ctx = MockExecutionContext::test_default();
+            exe_ctx.ml_models.insert(
+                model_name.clone(),
+                MlModelLoadingInfo {
+                    storage_path: test_data!("ml/onnx/test_classification.onnx").to_owned(),
+                    metadata,
+                },
+            );
+
+            let onnx = Onnx {
+                params,
+                sources: SingleRasterSource {
+                    raster: RasterStacker {
+                        params: RasterStackerParams {
+                            rename_bands: RenameBands::Default,
+                        },
+                        sources: MultipleRasterSources {
+                            rasters: vec![
+                                single_band_source(vec![(0, 0.1)], 2),
+                                single_band_source(vec![(0, 0.2)], 2),
+                            ],
+                        },
+                    }
+                    .boxed(),
+                },
+            }
+            .boxed();
+
+            async move {
+                onnx.initialize(WorkflowOperatorPath::initialize_root(), &exe_ctx)
+                    .await
+            }
+        };
+
+        let op = initialize(OnnxParams::new(model_name.clone()), metadata.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            op.result_descriptor().bands.bands(),
+            metadata.output_bands.as_slice()
+        );
+
+        // a pixel-wise model has no patches that could overlap
+        let params = OnnxParams {
+            patch_overlap: 1,
+            ..OnnxParams::new(model_name.clone())
+        };
+        assert!(initialize(params, metadata.clone()).await.is_err());
+
+        // the model has a single output band
+        let mut two_bands = metadata;
+        two_bands
+            .output_bands
+            .push(RasterBandDescriptor::new_unitless(
+                "probability".to_string(),
+            ));
+        assert!(
+            initialize(OnnxParams::new(model_name.clone()), two_bands)
+                .await
+                .is_err()
+        );
+    }
 }
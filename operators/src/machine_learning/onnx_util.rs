@@ -2,82 +2,141 @@ use super::{MachineLearningError, error::Ort};
 use crate::machine_learning::{
     MlModelLoadingInfo, MlModelMetadata,
     error::{
-        InvalidInputPixelShape, InvalidInputTensorShape, InvalidOutputPixelShape,
-        InvalidOutputType, MetadataModelInputShapeMismatch, MetadataModelInputTypeMismatch,
-        MetadataModelOutputShapeMismatch, MultipleInputsNotSupported, UnsupportedInOutMapping,
-        UnsupportedNumberOfOutputAttributes,
+        InvalidInputTensorShape, InvalidNumberOfInputTimeSteps, InvalidOutputType,
+        MetadataModelInputShapeMismatch, MetadataModelInputTypeMismatch,
+        MetadataModelOutputShapeMismatch, MetadataModelOutputTypeMismatch,
+        MultipleInputsNotSupported, OutputBandsMismatch, UnsupportedInOutMapping,
     },
 };
-use geoengine_datatypes::{
-    machine_learning::MlTensorShape3D,
-    raster::{GridShape2D, GridSize, RasterDataType},
+use geoengine_datatypes::{machine_learning::MlTensorShape3D, raster::RasterDataType};
+use ort::{
+    session::Session,
+    tensor::{Shape, TensorElementType},
 };
-use ort::session::Session;
 use snafu::{ResultExt, ensure};
+use std::path::Path;
 
 pub fn load_onnx_model_from_loading_info(
     ml_model_loading_info: &MlModelLoadingInfo,
 ) -> Result<Session, MachineLearningError> {
+    load_onnx_model_from_path(&ml_model_loading_info.storage_path)
+}
+
+pub fn load_onnx_model_from_path(path: &Path) -> Result<Session, MachineLearningError> {
     ort::session::Session::builder()
         .context(Ort)?
-        .commit_from_file(&ml_model_loading_info.storage_path)
+        .commit_from_file(path)
         .context(Ort)
         .inspect_err(|e| {
             tracing::debug!(
                 "Could not create ONNX session for {:?}. Error: {}",
-                ml_model_loading_info.storage_path.file_name(),
+                path.file_name(),
                 e
             );
         })
 }
 
-pub fn check_model_shape(
-    model_metadata: &MlModelMetadata,
-    tiling_shape: GridShape2D,
-) -> Result<(), MachineLearningError> {
-    check_model_input_shape_supported(model_metadata, tiling_shape)?;
-    check_model_output_shape_supported(model_metadata, tiling_shape)?;
-    check_input_output_mapping_supported(model_metadata)
+/// The types and shapes of the input and output tensors of an onnx model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MlModelTensors {
+    pub input_type: RasterDataType,
+    pub input_shape: MlTensorShape3D,
+    pub output_type: RasterDataType,
+    pub output_shape: MlTensorShape3D,
 }
 
-pub fn check_model_input_shape_supported(
-    model_metadata: &MlModelMetadata,
-    tiling_shape: GridShape2D,
-) -> Result<(), MachineLearningError> {
-    // check that we can use the model input shape with the operator
+/// Inspect the input and output tensors of the model, e.g., to infer the metadata of a model file.
+pub fn onnx_model_tensors(session: &Session) -> Result<MlModelTensors, MachineLearningError> {
+    let (input_tensor_type, input_dimensions) = onnx_model_input_tensor(session)?;
+    let (output_tensor_type, output_dimensions) = onnx_model_output_tensor(session)?;
+
+    Ok(MlModelTensors {
+        input_type: try_raster_datatype_from_tensor_element_type(input_tensor_type)?,
+        input_shape: try_onnx_tensor_to_ml_tensorshape_3d(input_dimensions)?,
+        output_type: try_raster_datatype_from_tensor_element_type(output_tensor_type)?,
+        output_shape: try_onnx_tensor_to_ml_tensorshape_3d(output_dimensions)?,
+    })
+}
+
+/// The element type and dimensions of the single input tensor of the model
+fn onnx_model_input_tensor(
+    session: &Session,
+) -> Result<(TensorElementType, &Shape), MachineLearningError> {
+    let inputs = &session.inputs;
     ensure!(
-        model_metadata.input_is_single_pixel()
-            || model_metadata
-                .input_shape
-                .yx_matches_tile_shape(&tiling_shape),
-        InvalidInputPixelShape {
-            tensor_shape: model_metadata.input_shape,
-            tiling_shape
+        inputs.len() == 1,
+        MultipleInputsNotSupported {
+            num_inputs: inputs.len()
         }
     );
 
-    Ok(())
+    let input = &inputs[0];
+
+    let (Some(input_tensor_type), Some(tensor_shape)) = (
+        input.input_type.tensor_type(),
+        input.input_type.tensor_shape(),
+    ) else {
+        return Err(MachineLearningError::InvalidInputType {
+            input_type: input.input_type.clone(),
+        });
+    };
+
+    Ok((input_tensor_type, tensor_shape))
 }
 
-pub fn check_model_output_shape_supported(
+/// The element type and dimensions of the output tensor of the model
+fn onnx_model_output_tensor(
+    session: &Session,
+) -> Result<(TensorElementType, &Shape), MachineLearningError> {
+    // we assume that the first output is the one to use
+    // TODO: make this configurable?
+    let output = &session.outputs[0];
+
+    let (Some(output_tensor_type), Some(tensor_shape)) = (
+        output.output_type.tensor_type(),
+        output.output_type.tensor_shape(),
+    ) else {
+        return Err(MachineLearningError::InvalidOutputType {
+            output_type: output.output_type.clone(),
+        });
+    };
+
+    Ok((output_tensor_type, tensor_shape))
+}
+
+pub fn check_model_shape(model_metadata: &MlModelMetadata) -> Result<(), MachineLearningError> {
+    check_model_output_bands_supported(model_metadata)?;
+    check_model_input_time_steps_supported(model_metadata)?;
+    check_input_output_mapping_supported(model_metadata)
+}
+
+pub fn check_model_input_time_steps_supported(
     model_metadata: &MlModelMetadata,
-    tiling_shape: GridShape2D,
 ) -> Result<(), MachineLearningError> {
-    // check that we can use the model output shape with the operator
+    // the input features consist of the same bands for each time step
     ensure!(
-        model_metadata.output_is_single_pixel()
-            || model_metadata
-                .output_shape
-                .yx_matches_tile_shape(&tiling_shape),
-        InvalidOutputPixelShape {
-            tensor_shape: model_metadata.output_shape,
-            tiling_shape
+        model_metadata.input_time_steps > 0
+            && model_metadata
+                .num_input_bands()
+                .is_multiple_of(model_metadata.input_time_steps),
+        InvalidNumberOfInputTimeSteps {
+            time_steps: model_metadata.input_time_steps,
+            input_bands: model_metadata.num_input_bands()
         }
     );
 
+    Ok(())
+}
+
+pub fn check_model_output_bands_supported(
+    model_metadata: &MlModelMetadata,
+) -> Result<(), MachineLearningError> {
+    // check that the output band descriptors fit the model output
     ensure!(
-        model_metadata.output_is_single_attribute(),
-        UnsupportedNumberOfOutputAttributes {
+        model_metadata.output_bands.is_empty()
+            || model_metadata.output_bands.len() == model_metadata.num_output_bands() as usize,
+        OutputBandsMismatch {
+            output_bands: model_metadata.output_bands.len(),
             output_attributes: model_metadata.num_output_bands()
         }
     );
@@ -135,36 +194,13 @@ pub fn try_onnx_tensor_to_ml_tensorshape_3d(
     }
 }
 
-///
 /// Check that the session input is a tensor with the dimension specified in the metadata.
-///
-/// # Panics
-///
-/// If the input is a tensor but no `tensor_dimension` is provided.
-///
 pub fn check_onnx_model_input_matches_metadata(
     session: &Session,
     metadata_input: MlTensorShape3D,
     metadata_input_type: RasterDataType,
 ) -> Result<(), MachineLearningError> {
-    let inputs = &session.inputs;
-    ensure!(
-        inputs.len() == 1,
-        MultipleInputsNotSupported {
-            num_inputs: inputs.len()
-        }
-    );
-
-    let input = &inputs[0];
-
-    let (Some(input_tensor_type), Some(tensor_shape)) = (
-        input.input_type.tensor_type(),
-        input.input_type.tensor_shape(),
-    ) else {
-        return Err(MachineLearningError::InvalidInputType {
-            input_type: input.input_type.clone(),
-        });
-    };
+    let (input_tensor_type, tensor_shape) = onnx_model_input_tensor(session)?;
 
     let shape = try_onnx_tensor_to_ml_tensorshape_3d(tensor_shape)?;
 
@@ -191,34 +227,13 @@ pub fn check_onnx_model_input_matches_metadata(
     Ok(())
 }
 
-///
-/// Check that the session output is a tensor with the dimension speified in the metadata.
-///
-/// # Panics
-///
-/// If the output is a tensor but no `tensor_dimension` is provided.
-///
+/// Check that the session output is a tensor with the dimension specified in the metadata.
 pub fn check_onnx_model_output_matches_metadata(
     session: &Session,
     metadata_output: MlTensorShape3D,
     metadata_output_type: RasterDataType,
 ) -> Result<(), MachineLearningError> {
-    let outputs = &session.outputs;
-
-    // we assume that the first output is the one to use
-    // TODO: make this configurable?
-    let output = &outputs[0];
-    ensure!(
-        output.output_type.is_tensor(),
-        InvalidOutputType {
-            output_type: output.output_type.clone()
-        }
-    );
-
-    let dimensions = output
-        .output_type
-        .tensor_shape()
-        .expect("input must be a tensor. checked before!");
+    let (output_tensor_type, dimensions) = onnx_model_output_tensor(session)?;
 
     let shape = try_onnx_tensor_to_ml_tensorshape_3d(dimensions)?;
 
@@ -231,15 +246,11 @@ pub fn check_onnx_model_output_matches_metadata(
         }
     );
 
-    let output_tensor_type = output
-        .output_type
-        .tensor_type()
-        .expect("output must be a tensor. ckecked above!");
     let output_raster_type = try_raster_datatype_from_tensor_element_type(output_tensor_type)?;
 
     ensure!(
         output_raster_type == metadata_output_type,
-        MetadataModelInputTypeMismatch {
+        MetadataModelOutputTypeMismatch {
             model_tensor_type: output_tensor_type,
             model_raster_type: output_raster_type,
             metadata_type: metadata_output_type
@@ -267,18 +278,13 @@ pub fn check_onnx_model_matches_metadata(
 
 pub fn check_model_input_features(
     model_metadata: &MlModelMetadata,
-    tiling_shape: GridShape2D,
     num_bands: u32,
 ) -> Result<(), MachineLearningError> {
-    let used_in_shape = if model_metadata.input_is_single_pixel() {
-        MlTensorShape3D::new_single_pixel_bands(num_bands)
-    } else {
-        MlTensorShape3D::new_y_x_bands(
-            tiling_shape.axis_size_y() as u32,
-            tiling_shape.axis_size_x() as u32,
-            num_bands,
-        )
-    };
+    let used_in_shape = MlTensorShape3D::new_y_x_bands(
+        model_metadata.input_shape.axis_size_y(),
+        model_metadata.input_shape.axis_size_x(),
+        num_bands,
+    );
 
     // check that number of input bands fits number of model features
     ensure!(
@@ -314,3 +320,39 @@ pub(crate) fn try_raster_datatype_from_tensor_element_type(
         }),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geoengine_datatypes::test_data;
+
+    #[test]
+    fn it_infers_the_tensors_of_models() {
+        let session =
+            load_onnx_model_from_path(&test_data!("ml/onnx/test_classification.onnx")).unwrap();
+
+        let tensors = onnx_model_tensors(&session).unwrap();
+
+        assert_eq!(
+            tensors,
+            MlModelTensors {
+                input_type: RasterDataType::F32,
+                input_shape: MlTensorShape3D::new_single_pixel_bands(2),
+                output_type: RasterDataType::I64,
+                output_shape: MlTensorShape3D::new_single_pixel_single_band(),
+            }
+        );
+
+        check_onnx_model_input_matches_metadata(&session, tensors.input_shape, tensors.input_type)
+            .unwrap();
+
+        assert!(matches!(
+            check_onnx_model_output_matches_metadata(
+                &session,
+                tensors.output_shape,
+                RasterDataType::F32,
+            ),
+            Err(MachineLearningError::MetadataModelOutputTypeMismatch { .. })
+        ));
+    }
+}
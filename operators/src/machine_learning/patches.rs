@@ -0,0 +1,383 @@
+use crate::machine_learning::onnx::PatchBlending;
+use geoengine_datatypes::raster::{GridIdx, GridIdx2D, GridShape2D, GridSize, Pixel, RasterTile2D};
+use num_traits::AsPrimitive;
+
+/// The arrangement of the model patches that cover an output tile.
+///
+/// The patches are placed on a regular grid with a stride of `patch size - overlap`.
+/// The grid is centered on the tile, so that the patches may reach beyond the tile.
+/// The area that is covered by all patches is called the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PatchLayout {
+    y: AxisLayout,
+    x: AxisLayout,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct AxisLayout {
+    patch_size: usize,
+    stride: usize,
+    num_patches: usize,
+    /// The number of pixels the window reaches beyond the start of the tile
+    margin_before: usize,
+    /// The number of pixels the window reaches beyond the end of the tile
+    margin_after: usize,
+}
+
+impl AxisLayout {
+    /// Requires `overlap < patch_size`
+    fn new(tile_size: usize, patch_size: usize, overlap: usize) -> Self {
+        debug_assert!(overlap < patch_size);
+
+        let stride = patch_size - overlap;
+
+        // the pixels at the tile border should have the same context as the inner pixels
+        let covered_size = tile_size + 2 * (overlap / 2);
+        let num_patches = if covered_size <= patch_size {
+            1
+        } else {
+            (covered_size - patch_size).div_ceil(stride) + 1
+        };
+
+        let window_size = (num_patches - 1) * stride + patch_size;
+        let margin_before = (window_size - tile_size) / 2;
+
+        Self {
+            patch_size,
+            stride,
+            num_patches,
+            margin_before,
+            margin_after: window_size - tile_size - margin_before,
+        }
+    }
+
+    fn window_size(&self) -> usize {
+        (self.num_patches - 1) * self.stride + self.patch_size
+    }
+
+    /// The weight of a patch pixel, i.e., its distance to the patch border.
+    /// The pixels at the patch border have weight 1.
+    fn weight(&self, index: usize) -> usize {
+        (index + 1).min(self.patch_size - index)
+    }
+}
+
+impl PatchLayout {
+    /// Requires the overlap to be smaller than the patch size in both dimensions
+    pub fn new(tile_shape: GridShape2D, patch_shape: GridShape2D, overlap: usize) -> Self {
+        Self {
+            y: AxisLayout::new(tile_shape.axis_size_y(), patch_shape.axis_size_y(), overlap),
+            x: AxisLayout::new(tile_shape.axis_size_x(), patch_shape.axis_size_x(), overlap),
+        }
+    }
+
+    pub fn patch_shape(&self) -> GridShape2D {
+        [self.y.patch_size, self.x.patch_size].into()
+    }
+
+    pub fn window_shape(&self) -> GridShape2D {
+        [self.y.window_size(), self.x.window_size()].into()
+    }
+
+    /// The offset of the tile's upper left pixel inside the window
+    pub fn tile_offset(&self) -> GridIdx2D {
+        [self.y.margin_before as isize, self.x.margin_before as isize].into()
+    }
+
+    /// Whether the window is larger than the tile, i.e., pixels of neighboring tiles are required
+    pub fn has_margin(&self) -> bool {
+        self.y.margin_before + self.y.margin_after + self.x.margin_before + self.x.margin_after > 0
+    }
+
+    /// The upper left pixels of the patches inside the window
+    pub fn patch_origins(&self) -> impl Iterator<Item = GridIdx2D> {
+        let (y, x) = (self.y, self.x);
+        (0..y.num_patches).flat_map(move |patch_y| {
+            (0..x.num_patches).map(move |patch_x| {
+                [(patch_y * y.stride) as isize, (patch_x * x.stride) as isize].into()
+            })
+        })
+    }
+}
+
+/// The pixels of all bands inside a window around an output tile.
+/// The bands of each pixel are stored next to each other.
+pub(crate) struct PatchWindow<T> {
+    /// The global pixel index of the upper left pixel
+    origin: GridIdx2D,
+    shape: GridShape2D,
+    num_bands: usize,
+    pixels: Vec<T>,
+}
+
+impl<T: Pixel> PatchWindow<T> {
+    pub fn new(origin: GridIdx2D, shape: GridShape2D, num_bands: usize, fill_value: T) -> Self {
+        Self {
+            origin,
+            shape,
+            num_bands,
+            pixels: vec![fill_value; shape.number_of_elements() * num_bands],
+        }
+    }
+
+    /// Copy the valid pixels of the tile that lie inside the window.
+    /// Invalid pixels keep the fill value.
+    pub fn add_tile(&mut self, tile: &RasterTile2D<T>) {
+        let Some(grid) = tile.grid_array.as_masked_grid() else {
+            return;
+        };
+
+        let tile_shape = tile.tile_information().tile_size_in_pixels;
+        let tile_columns = tile_shape.axis_size_x();
+        let GridIdx([tile_y, tile_x]) = tile.tile_information().global_upper_left_pixel_idx();
+        let GridIdx([origin_y, origin_x]) = self.origin;
+        let (rows, columns) = (self.shape.axis_size_y(), self.shape.axis_size_x());
+        let band = tile.band as usize;
+
+        for (i, value) in grid.masked_element_deref_iterator().enumerate() {
+            let Some(value) = value else {
+                continue;
+            };
+
+            let y = tile_y + (i / tile_columns) as isize - origin_y;
+            let x = tile_x + (i % tile_columns) as isize - origin_x;
+
+            if y < 0 || x < 0 || y as usize >= rows || x as usize >= columns {
+                continue;
+            }
+
+            self.pixels[(y as usize * columns + x as usize) * self.num_bands + band] = value;
+        }
+    }
+
+    /// The pixels of the patch with the given upper left pixel (relative to the window)
+    pub fn patch(&self, origin: GridIdx2D, shape: GridShape2D) -> Vec<T> {
+        let GridIdx([origin_y, origin_x]) = origin;
+        let (origin_y, origin_x) = (origin_y as usize, origin_x as usize);
+        let columns = self.shape.axis_size_x();
+        let row_length = shape.axis_size_x() * self.num_bands;
+
+        let mut pixels = Vec::with_capacity(shape.number_of_elements() * self.num_bands);
+        for y in origin_y..origin_y + shape.axis_size_y() {
+            let start = (y * columns + origin_x) * self.num_bands;
+            pixels.extend_from_slice(&self.pixels[start..start + row_length]);
+        }
+
+        pixels
+    }
+}
+
+/// Combines the predictions of overlapping patches into the pixels of an output tile.
+pub(crate) struct PatchBlender {
+    layout: PatchLayout,
+    blending: PatchBlending,
+    tile_shape: GridShape2D,
+    num_bands: usize,
+    values: Vec<f64>,
+    weights: Vec<f64>,
+}
+
+impl PatchBlender {
+    pub fn new(
+        layout: PatchLayout,
+        blending: PatchBlending,
+        tile_shape: GridShape2D,
+        num_bands: usize,
+    ) -> Self {
+        let num_values = tile_shape.number_of_elements() * num_bands;
+        Self {
+            layout,
+            blending,
+            tile_shape,
+            num_bands,
+            values: vec![0.; num_values],
+            weights: vec![0.; num_values],
+        }
+    }
+
+    /// Add the predictions of the patch with the given upper left pixel (relative to the window).
+    /// The bands of each pixel are expected next to each other.
+    pub fn add_patch<T: Pixel>(&mut self, origin: GridIdx2D, predictions: &[T]) {
+        let GridIdx([origin_y, origin_x]) = origin - self.layout.tile_offset();
+        let (patch_rows, patch_columns) = (self.layout.y.patch_size, self.layout.x.patch_size);
+        let (rows, columns) = (
+            self.tile_shape.axis_size_y() as isize,
+            self.tile_shape.axis_size_x() as isize,
+        );
+
+        for patch_y in 0..patch_rows {
+            let y = origin_y + patch_y as isize;
+            if y < 0 || y >= rows {
+                continue;
+            }
+
+            for patch_x in 0..patch_columns {
+                let x = origin_x + patch_x as isize;
+                if x < 0 || x >= columns {
+                    continue;
+                }
+
+                let (weight_y, weight_x) =
+                    (self.layout.y.weight(patch_y), self.layout.x.weight(patch_x));
+                let weight = match self.blending {
+                    PatchBlending::Center => weight_y.min(weight_x) as f64,
+                    PatchBlending::Average => 1.,
+                    PatchBlending::Feather => (weight_y * weight_x) as f64,
+                };
+
+                let patch_index = (patch_y * patch_columns + patch_x) * self.num_bands;
+                let tile_index = (y * columns + x) as usize * self.num_bands;
+
+                for band in 0..self.num_bands {
+                    let prediction: f64 = predictions[patch_index + band].as_();
+                    let (value, weight_sum) = (
+                        &mut self.values[tile_index + band],
+                        &mut self.weights[tile_index + band],
+                    );
+
+                    if self.blending == PatchBlending::Center {
+                        // keep the prediction of the patch whose center is closest
+                        if weight > *weight_sum {
+                            *value = prediction;
+                            *weight_sum = weight;
+                        }
+                    } else {
+                        *value += weight * prediction;
+                        *weight_sum += weight;
+                    }
+                }
+            }
+        }
+    }
+
+    /// The blended pixels of each band
+    pub fn into_bands<T: Pixel>(self) -> Vec<Vec<T>> {
+        let num_pixels = self.tile_shape.number_of_elements();
+        let blended = self
+            .values
+            .iter()
+            .zip(&self.weights)
+            .map(|(&value, &weight)| match self.blending {
+                PatchBlending::Center => T::from_(value),
+                PatchBlending::Average | PatchBlending::Feather => T::from_(value / weight),
+            })
+            .collect::<Vec<T>>();
+
+        (0..self.num_bands)
+            .map(|band| {
+                (0..num_pixels)
+                    .map(|pixel| blended[pixel * self.num_bands + band])
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_abs_diff_eq;
+    use geoengine_datatypes::primitives::{CacheHint, TimeInterval};
+    use geoengine_datatypes::raster::{Grid2D, TileInformation};
+    use geoengine_datatypes::util::test::TestDefault;
+
+    #[test]
+    fn it_lays_out_patches() {
+        // the patch equals the tile
+        let layout = PatchLayout::new([4, 4].into(), [4, 4].into(), 0);
+        assert!(!layout.has_margin());
+        assert_eq!(layout.window_shape(), [4, 4].into());
+        assert_eq!(layout.patch_origins().count(), 1);
+
+        // the patches split the tile
+        let layout = PatchLayout::new([4, 4].into(), [2, 2].into(), 0);
+        assert!(!layout.has_margin());
+        assert_eq!(
+            layout.patch_origins().collect::<Vec<_>>(),
+            vec![[0, 0].into(), [0, 2].into(), [2, 0].into(), [2, 2].into()]
+        );
+
+        // the patches overlap and reach beyond the tile
+        let layout = PatchLayout::new([4, 4].into(), [4, 4].into(), 2);
+        assert!(layout.has_margin());
+        assert_eq!(layout.window_shape(), [6, 6].into());
+        assert_eq!(layout.tile_offset(), [1, 1].into());
+        assert_eq!(layout.patch_origins().count(), 4);
+
+        // the patch is larger than the tile
+        let layout = PatchLayout::new([2, 2].into(), [4, 4].into(), 0);
+        assert_eq!(layout.window_shape(), [4, 4].into());
+        assert_eq!(layout.tile_offset(), [1, 1].into());
+        assert_eq!(layout.patch_origins().count(), 1);
+    }
+
+    #[test]
+    fn it_fills_windows() {
+        let tile = |x: isize, band: u32, data: Vec<u8>| {
+            RasterTile2D::new_with_tile_info(
+                TimeInterval::default(),
+                TileInformation {
+                    global_tile_position: [-1, x].into(),
+                    tile_size_in_pixels: [2, 2].into(),
+                    global_geo_transform: TestDefault::test_default(),
+                },
+                band,
+                Grid2D::new([2, 2].into(), data).unwrap().into(),
+                CacheHint::default(),
+            )
+        };
+
+        // window of 2x3 pixels starting at the second column of the left tile
+        let mut window = PatchWindow::new([-2, 1].into(), [2, 3].into(), 2, 0);
+        window.add_tile(&tile(0, 0, vec![1, 2, 3, 4]));
+        window.add_tile(&tile(0, 1, vec![10, 20, 30, 40]));
+        window.add_tile(&tile(1, 0, vec![5, 6, 7, 8]));
+
+        assert_eq!(
+            window.patch([0, 0].into(), [2, 3].into()),
+            vec![2, 20, 5, 0, 6, 0, 4, 40, 7, 0, 8, 0]
+        );
+        assert_eq!(window.patch([1, 1].into(), [1, 2].into()), vec![7, 0, 8, 0]);
+    }
+
+    #[test]
+    fn it_blends_patches() {
+        let layout = PatchLayout::new([3, 3].into(), [3, 3].into(), 2);
+        assert_eq!(layout.window_shape(), [5, 5].into());
+        assert_eq!(layout.tile_offset(), [1, 1].into());
+
+        let blend = |blending| {
+            let mut blender = PatchBlender::new(layout, blending, [3, 3].into(), 1);
+            for origin in layout.patch_origins() {
+                let GridIdx([y, x]) = origin;
+                blender.add_patch(origin, &[(12 * y + 4 * x) as f64; 9]);
+            }
+            blender.into_bands::<f64>().remove(0)
+        };
+
+        // the upper left pixel is covered by four patches, the center pixel by all nine
+        let center = blend(PatchBlending::Center);
+        assert_abs_diff_eq!(center[0], 0.);
+        assert_abs_diff_eq!(center[4], 16.);
+
+        let average = blend(PatchBlending::Average);
+        assert_abs_diff_eq!(average[0], 8.);
+        assert_abs_diff_eq!(average[4], 16.);
+
+        let feather = blend(PatchBlending::Feather);
+        assert_abs_diff_eq!(feather[0], 16. / 3., epsilon = 1e-9);
+        assert_abs_diff_eq!(feather[4], 16., epsilon = 1e-9);
+    }
+
+    #[test]
+    fn it_blends_multiple_bands() {
+        let layout = PatchLayout::new([2, 2].into(), [2, 2].into(), 0);
+        let mut blender = PatchBlender::new(layout, PatchBlending::Center, [2, 2].into(), 2);
+        blender.add_patch([0, 0].into(), &[1u8, 5, 2, 6, 3, 7, 4, 8]);
+
+        assert_eq!(
+            blender.into_bands::<u8>(),
+            vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8]]
+        );
+    }
+}
@@ -74,6 +74,11 @@ impl QuotaTracking {
 pub trait QuotaCheck {
     /// checks if the quota is available and if not, returns an error
     async fn ensure_quota_available(&self) -> Result<()>;
+
+    /// checks if the quota for loading the given `data` is available and if not, returns an error
+    async fn ensure_data_quota_available(&self, _data: &str) -> Result<()> {
+        Ok(())
+    }
 }
 
 pub type QuotaChecker = Box<dyn QuotaCheck + Send + Sync>;
@@ -280,6 +280,10 @@ where
         // TODO: check the quota only once per query and not for every operator
         quota_checker.ensure_quota_available().await?;
 
+        if let Some(data) = &self.data {
+            quota_checker.ensure_data_quota_available(data).await?;
+        }
+
         let quota_tracker = ctx
             .quota_tracking()
             .expect("`QuotaTracking` extension should be set during `ProContext` creation")
@@ -0,0 +1,300 @@
+use crate::engine::{
+    ExecutionContext, InitializedRasterOperator, InitializedVectorOperator, PlotResultDescriptor,
+    QueryContext, QueryProcessor, TypedRasterQueryProcessor, TypedVectorQueryProcessor,
+    WorkflowOperatorPath,
+};
+use crate::error::{self, Error};
+use crate::util::Result;
+use crate::util::input::RasterOrVectorOperator;
+use futures::StreamExt;
+use geoengine_datatypes::collections::FeatureCollectionInfos;
+use geoengine_datatypes::primitives::{
+    AxisAlignedRectangle, BandSelection, BoundingBox2D, PlotQueryRectangle, RasterQueryRectangle,
+};
+use geoengine_datatypes::raster::{GridOrEmpty, GridSize};
+use num_traits::AsPrimitive;
+use snafu::ensure;
+
+/// A raster or vector source of a plot that samples several numeric attributes jointly.
+/// The attributes are the bands of a raster or the numeric columns of a vector input.
+pub(crate) enum InitializedAttributeSource {
+    Raster {
+        source: Box<dyn InitializedRasterOperator>,
+        attributes: Vec<String>,
+        bands: Vec<u32>,
+    },
+    Vector {
+        source: Box<dyn InitializedVectorOperator>,
+        attributes: Vec<String>,
+    },
+}
+
+impl InitializedAttributeSource {
+    /// Initializes the source and resolves the `attribute_names` to bands or numeric columns.
+    /// If `attribute_names` is empty, all bands or numeric columns are selected.
+    pub async fn initialize(
+        source: RasterOrVectorOperator,
+        attribute_names: &[String],
+        path: WorkflowOperatorPath,
+        context: &dyn ExecutionContext,
+    ) -> Result<Self> {
+        match source {
+            RasterOrVectorOperator::Raster(raster_source) => {
+                let source = raster_source
+                    .initialize(path.clone_and_append(0), context)
+                    .await?;
+                let bands_desc = &source.result_descriptor().bands;
+
+                let (attributes, bands) = if attribute_names.is_empty() {
+                    bands_desc
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, band)| (band.name.clone(), idx as u32))
+                        .unzip()
+                } else {
+                    let bands = attribute_names
+                        .iter()
+                        .map(|name| {
+                            bands_desc
+                                .iter()
+                                .position(|band| &band.name == name)
+                                .map(|idx| idx as u32)
+                                .ok_or_else(|| Error::InvalidOperatorSpec {
+                                    reason: format!("Band `{name}` does not exist"),
+                                })
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    (attribute_names.to_vec(), bands)
+                };
+
+                Ok(Self::Raster {
+                    source,
+                    attributes,
+                    bands,
+                })
+            }
+            RasterOrVectorOperator::Vector(vector_source) => {
+                let source = vector_source
+                    .initialize(path.clone_and_append(0), context)
+                    .await?;
+                let in_desc = source.result_descriptor();
+
+                let attributes = if attribute_names.is_empty() {
+                    let mut numeric_columns = in_desc
+                        .columns
+                        .iter()
+                        .filter(|(_, column)| column.data_type.is_numeric())
+                        .map(|(name, _)| name.clone())
+                        .collect::<Vec<_>>();
+                    numeric_columns.sort();
+                    numeric_columns
+                } else {
+                    for name in attribute_names {
+                        match in_desc.column_data_type(name) {
+                            Some(data_type) if !data_type.is_numeric() => {
+                                return Err(Error::InvalidOperatorSpec {
+                                    reason: format!("Column `{name}` is not numeric."),
+                                });
+                            }
+                            Some(_) => {}
+                            None => {
+                                return Err(Error::ColumnDoesNotExist {
+                                    column: name.clone(),
+                                });
+                            }
+                        }
+                    }
+                    attribute_names.to_vec()
+                };
+
+                Ok(Self::Vector { source, attributes })
+            }
+        }
+    }
+
+    /// The names of the bands or columns, in the order of the values of the samples
+    pub fn attributes(&self) -> &[String] {
+        match self {
+            Self::Raster { attributes, .. } | Self::Vector { attributes, .. } => attributes,
+        }
+    }
+
+    pub fn plot_result_descriptor(&self) -> PlotResultDescriptor {
+        match self {
+            Self::Raster { source, .. } => {
+                let in_desc = source.result_descriptor();
+                PlotResultDescriptor {
+                    spatial_reference: in_desc.spatial_reference,
+                    time: in_desc.time,
+                    // converting `SpatialPartition2D` to `BoundingBox2D` is ok here, because is makes the covered area only larger
+                    bbox: in_desc
+                        .bbox
+                        .and_then(|p| BoundingBox2D::new(p.lower_left(), p.upper_right()).ok()),
+                }
+            }
+            Self::Vector { source, .. } => source.result_descriptor().clone().into(),
+        }
+    }
+
+    pub fn query_processor(&self) -> Result<AttributeSampleProcessor> {
+        Ok(match self {
+            Self::Raster { source, bands, .. } => AttributeSampleProcessor::Raster {
+                input: source.query_processor()?,
+                bands: bands.clone(),
+            },
+            Self::Vector {
+                source, attributes, ..
+            } => AttributeSampleProcessor::Vector {
+                input: source.query_processor()?,
+                columns: attributes.clone(),
+            },
+        })
+    }
+}
+
+/// Queries the samples of an `InitializedAttributeSource`
+pub(crate) enum AttributeSampleProcessor {
+    Raster {
+        input: TypedRasterQueryProcessor,
+        bands: Vec<u32>,
+    },
+    Vector {
+        input: TypedVectorQueryProcessor,
+        columns: Vec<String>,
+    },
+}
+
+impl AttributeSampleProcessor {
+    /// Calls `f` with the values of all attributes of each pixel or feature.
+    /// Missing values (no data or null) are `None`.
+    pub async fn for_each_sample<F>(
+        &self,
+        query: &PlotQueryRectangle,
+        ctx: &dyn QueryContext,
+        f: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&[Option<f64>]) + Send,
+    {
+        match self {
+            Self::Raster { input, bands } => {
+                Self::for_each_pixel(input, bands, query, ctx, f).await
+            }
+            Self::Vector { input, columns } => {
+                Self::for_each_feature(input, columns, query, ctx, f).await
+            }
+        }
+    }
+
+    async fn for_each_pixel<F>(
+        input: &TypedRasterQueryProcessor,
+        bands: &[u32],
+        query: &PlotQueryRectangle,
+        ctx: &dyn QueryContext,
+        mut f: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&[Option<f64>]) + Send,
+    {
+        // a band selection must not contain duplicates, so query each band once and map it to the attributes
+        let mut selected_bands = bands.to_vec();
+        selected_bands.sort_unstable();
+        selected_bands.dedup();
+        let positions = bands
+            .iter()
+            .map(|band| {
+                selected_bands
+                    .binary_search(band)
+                    .expect("all bands are selected")
+            })
+            .collect::<Vec<_>>();
+
+        let number_of_bands = selected_bands.len();
+        let band_selection = BandSelection::new(selected_bands)?;
+
+        let mut sample = vec![None; bands.len()];
+
+        call_on_generic_raster_processor!(input, processor => {
+            // chunk up the stream to get all bands for a spatial tile at once
+            let mut stream = processor
+                .query(RasterQueryRectangle::from_qrect_and_bands(query, band_selection), ctx)
+                .await?
+                .chunks(number_of_bands);
+
+            while let Some(chunk) = stream.next().await {
+                let tiles = chunk.into_iter().collect::<Result<Vec<_>>>()?;
+
+                ensure!(
+                    tiles.len() == number_of_bands,
+                    error::MustNotHappen {
+                        message: "source did not produce all bands".to_string(),
+                    }
+                );
+
+                if tiles.iter().all(|tile| tile.grid_array.is_empty()) {
+                    continue;
+                }
+
+                let band_values = tiles
+                    .iter()
+                    .map(|tile| match &tile.grid_array {
+                        GridOrEmpty::Grid(g) => g
+                            .masked_element_deref_iterator()
+                            .map(|v| v.map(AsPrimitive::<f64>::as_))
+                            .collect(),
+                        GridOrEmpty::Empty(e) => vec![None; e.number_of_elements()],
+                    })
+                    .collect::<Vec<Vec<Option<f64>>>>();
+
+                for pixel_idx in 0..band_values[0].len() {
+                    for (value, &position) in sample.iter_mut().zip(&positions) {
+                        *value = band_values[position][pixel_idx];
+                    }
+                    f(&sample);
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    async fn for_each_feature<F>(
+        input: &TypedVectorQueryProcessor,
+        columns: &[String],
+        query: &PlotQueryRectangle,
+        ctx: &dyn QueryContext,
+        mut f: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&[Option<f64>]) + Send,
+    {
+        let mut sample = vec![None; columns.len()];
+
+        call_on_generic_vector_processor!(input, processor => {
+            let mut stream = processor.query(query.clone().into(), ctx).await?;
+
+            while let Some(collection) = stream.next().await {
+                let collection = collection?;
+
+                let column_values = columns
+                    .iter()
+                    .map(|column| {
+                        Ok(collection
+                            .data(column)?
+                            .float_options_iter()
+                            .collect::<Vec<_>>())
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                for feature_idx in 0..collection.len() {
+                    for (value, values) in sample.iter_mut().zip(&column_values) {
+                        *value = values[feature_idx];
+                    }
+                    f(&sample);
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
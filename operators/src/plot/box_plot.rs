@@ -1,14 +1,15 @@
 use async_trait::async_trait;
 use futures::StreamExt;
 use geoengine_datatypes::primitives::{
-    AxisAlignedRectangle, BandSelection, BoundingBox2D, PlotQueryRectangle, RasterQueryRectangle,
-    partitions_extent, time_interval_extent,
+    AxisAlignedRectangle, BandSelection, BoundingBox2D, Measurement, PlotQueryRectangle,
+    RasterQueryRectangle, partitions_extent, time_interval_extent,
 };
 use num_traits::AsPrimitive;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 use geoengine_datatypes::collections::FeatureCollectionInfos;
-use geoengine_datatypes::plots::{BoxPlotAttribute, Plot, PlotData};
+use geoengine_datatypes::plots::{BoxPlotAttribute, Figure, Plot, PlotData};
 use geoengine_datatypes::raster::GridOrEmpty;
 
 use crate::engine::{
@@ -19,6 +20,9 @@ use crate::engine::{
     WorkflowOperatorPath,
 };
 use crate::error::{self, Error};
+use crate::plot::group_by::{
+    ensure_group_by_column, feature_groups, for_each_zoned_pixel, zone_name,
+};
 use crate::util::Result;
 use crate::util::input::MultiRasterOrVectorOperator;
 use crate::util::statistics::PSquareQuantileEstimator;
@@ -30,6 +34,8 @@ const BATCH_SIZE: usize = 1_000;
 const MAX_NUMBER_OF_RASTER_INPUTS: usize = 8;
 
 /// A box plot about vector data attribute values
+///
+/// If `group_by` is set, there is a box per attribute and group, e.g., per land-cover class.
 pub type BoxPlot = Operator<BoxPlotParams, MultipleRasterOrSingleVectorSource>;
 
 impl OperatorName for BoxPlot {
@@ -43,6 +49,10 @@ pub struct BoxPlotParams {
     /// Name of the (numeric) attributes to compute the box plots on.
     #[serde(default)]
     pub column_names: Vec<String>,
+    /// Name of a column (category, text or integer) to compute the box plots per group.
+    /// For raster data, it names one of the inputs ('column_names' parameter), which is then used as a zone raster.
+    #[serde(default)]
+    pub group_by: Option<String>,
 }
 
 #[typetag::serde]
@@ -97,6 +107,17 @@ impl PlotOperator for BoxPlot {
                     self.params.column_names.clone()
                 };
 
+                if let Some(group_by) = &self.params.group_by {
+                    ensure!(
+                        raster_sources.len() > 1 && output_names.contains(group_by),
+                        error::InvalidOperatorSpec {
+                            reason: format!(
+                                "Grouped box plots on raster data require 'group_by' to name one of at least two inputs, found '{group_by}'."
+                            ),
+                        }
+                    );
+                }
+
                 if raster_sources.len() > 1 {
                     let srs = raster_sources[0].result_descriptor().spatial_reference;
                     ensure!(
@@ -125,6 +146,7 @@ impl PlotOperator for BoxPlot {
                             .and_then(|p| BoundingBox2D::new(p.lower_left(), p.upper_right()).ok()),
                     },
                     output_names,
+                    self.params.group_by,
                     raster_sources,
                 )
                 .boxed())
@@ -162,6 +184,10 @@ impl PlotOperator for BoxPlot {
 
                 let in_desc = vector_source.result_descriptor();
 
+                if let Some(group_by) = &self.params.group_by {
+                    ensure_group_by_column(in_desc, group_by)?;
+                }
+
                 Ok(InitializedBoxPlot::new(
                     name,
                     PlotResultDescriptor {
@@ -170,6 +196,7 @@ impl PlotOperator for BoxPlot {
                         bbox: in_desc.bbox,
                     },
                     self.params.column_names.clone(),
+                    self.params.group_by,
                     vector_source,
                 )
                 .boxed())
@@ -185,6 +212,7 @@ pub struct InitializedBoxPlot<Op> {
     name: CanonicOperatorName,
     result_descriptor: PlotResultDescriptor,
     names: Vec<String>,
+    group_by: Option<String>,
 
     source: Op,
 }
@@ -194,12 +222,14 @@ impl<Op> InitializedBoxPlot<Op> {
         name: CanonicOperatorName,
         result_descriptor: PlotResultDescriptor,
         names: Vec<String>,
+        group_by: Option<String>,
         source: Op,
     ) -> Self {
         Self {
             name,
             result_descriptor,
             names,
+            group_by,
             source,
         }
     }
@@ -213,6 +243,7 @@ impl InitializedPlotOperator for InitializedBoxPlot<Box<dyn InitializedVectorOpe
         let processor = BoxPlotVectorQueryProcessor {
             input: self.source.query_processor()?,
             column_names: self.names.clone(),
+            group_by: self.group_by.clone(),
         };
 
         Ok(TypedPlotQueryProcessor::JsonVega(processor.boxed()))
@@ -235,9 +266,22 @@ impl InitializedPlotOperator for InitializedBoxPlot<Vec<Box<dyn InitializedRaste
             .map(InitializedRasterOperator::query_processor)
             .collect::<Result<Vec<_>>>()?;
 
+        let zones = self.group_by.as_ref().map(|group_by| {
+            let zone_idx = self
+                .names
+                .iter()
+                .position(|name| name == group_by)
+                .expect("zone raster was checked during initialization");
+            let zone_measurement = self.source[zone_idx].result_descriptor().bands[0]
+                .measurement
+                .clone();
+            (zone_idx, zone_measurement)
+        });
+
         let processor = BoxPlotRasterQueryProcessor {
             input,
             names: self.names.clone(),
+            zones,
         };
         Ok(TypedPlotQueryProcessor::JsonVega(processor.boxed()))
     }
@@ -251,6 +295,7 @@ impl InitializedPlotOperator for InitializedBoxPlot<Vec<Box<dyn InitializedRaste
 pub struct BoxPlotVectorQueryProcessor {
     input: TypedVectorQueryProcessor,
     column_names: Vec<String>,
+    group_by: Option<String>,
 }
 
 #[async_trait]
@@ -266,6 +311,28 @@ impl PlotQueryProcessor for BoxPlotVectorQueryProcessor {
         query: PlotQueryRectangle,
         ctx: &'p dyn QueryContext,
     ) -> Result<Self::OutputFormat> {
+        Ok(self.box_plot(query, ctx).await?.to_vega_embeddable(false)?)
+    }
+
+    async fn plot_figure_query<'p>(
+        &'p self,
+        query: PlotQueryRectangle,
+        ctx: &'p dyn QueryContext,
+    ) -> Result<Option<Figure>> {
+        Ok(Some(self.box_plot(query, ctx).await?.to_figure()))
+    }
+}
+
+impl BoxPlotVectorQueryProcessor {
+    async fn box_plot(
+        &self,
+        query: PlotQueryRectangle,
+        ctx: &dyn QueryContext,
+    ) -> Result<geoengine_datatypes::plots::BoxPlot> {
+        if let Some(group_by) = &self.group_by {
+            return self.grouped_box_plot(group_by, query, ctx).await;
+        }
+
         let mut accums: Vec<BoxPlotAccum> = self
             .column_names
             .iter()
@@ -294,7 +361,56 @@ impl PlotQueryProcessor for BoxPlotVectorQueryProcessor {
                 chart.add_attribute(attrib);
             }
         }
-        Ok(chart.to_vega_embeddable(false)?)
+        Ok(chart)
+    }
+
+    /// Computes a box for each column and each group of the `group_by` column
+    async fn grouped_box_plot(
+        &self,
+        group_by: &str,
+        query: PlotQueryRectangle,
+        ctx: &dyn QueryContext,
+    ) -> Result<geoengine_datatypes::plots::BoxPlot> {
+        let mut accums: Vec<BTreeMap<String, BoxPlotAccum>> =
+            self.column_names.iter().map(|_| BTreeMap::new()).collect();
+
+        call_on_generic_vector_processor!(&self.input, processor => {
+            let mut query = processor.query(query.into(), ctx).await?;
+            while let Some(collection) = query.next().await {
+                let collection = collection?;
+
+                let groups = feature_groups(&collection.data(group_by)?);
+
+                for (column, column_accums) in self.column_names.iter().zip(&mut accums) {
+                    let feature_data = collection.data(column).expect("checked in param");
+
+                    for (value, group) in feature_data.float_options_iter().zip(&groups) {
+                        // features without a value or a group are ignored
+                        let (Some(value), Some(group)) = (value, group) else {
+                            continue;
+                        };
+
+                        if !column_accums.contains_key(group) {
+                            column_accums.insert(group.clone(), BoxPlotAccum::new(column.clone()));
+                        }
+                        column_accums
+                            .get_mut(group)
+                            .expect("inserted above")
+                            .update(std::iter::once(value))?;
+                    }
+                }
+            }
+        });
+
+        let mut chart = geoengine_datatypes::plots::BoxPlot::new();
+        for column_accums in &mut accums {
+            for (group, accum) in column_accums {
+                if let Some(attrib) = accum.finish()? {
+                    chart.add_attribute(attrib.with_group(group.clone()));
+                }
+            }
+        }
+        Ok(chart)
     }
 }
 
@@ -302,9 +418,81 @@ impl PlotQueryProcessor for BoxPlotVectorQueryProcessor {
 pub struct BoxPlotRasterQueryProcessor {
     input: Vec<TypedRasterQueryProcessor>,
     names: Vec<String>,
+    /// The index and the measurement of the zone raster if the box plots are grouped
+    zones: Option<(usize, Measurement)>,
 }
 
 impl BoxPlotRasterQueryProcessor {
+    async fn box_plot(
+        &self,
+        query: PlotQueryRectangle,
+        ctx: &dyn QueryContext,
+    ) -> Result<geoengine_datatypes::plots::BoxPlot> {
+        if let Some((zone_idx, zone_measurement)) = &self.zones {
+            return self
+                .grouped_box_plot(*zone_idx, zone_measurement, &query, ctx)
+                .await;
+        }
+
+        let results: Vec<_> = self
+            .input
+            .iter()
+            .zip(self.names.iter())
+            .map(|(proc, name)| Self::process_raster(name.clone(), proc, query.clone(), ctx))
+            .collect();
+
+        let results = futures::future::join_all(results)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>();
+
+        let mut chart = geoengine_datatypes::plots::BoxPlot::new();
+        results?
+            .into_iter()
+            .flatten()
+            .for_each(|a| chart.add_attribute(a));
+        Ok(chart)
+    }
+
+    /// Computes a box for each raster except the zone raster and each zone
+    async fn grouped_box_plot(
+        &self,
+        zone_idx: usize,
+        zone_measurement: &Measurement,
+        query: &PlotQueryRectangle,
+        ctx: &dyn QueryContext,
+    ) -> Result<geoengine_datatypes::plots::BoxPlot> {
+        let zones = &self.input[zone_idx];
+        let mut chart = geoengine_datatypes::plots::BoxPlot::new();
+
+        for (i, (raster, name)) in self.input.iter().zip(&self.names).enumerate() {
+            if i == zone_idx {
+                continue;
+            }
+
+            let mut accums: BTreeMap<i64, BoxPlotAccum> = BTreeMap::new();
+
+            for_each_zoned_pixel(raster, zones, query, ctx, |zone, value| {
+                if let Some(value) = value {
+                    accums
+                        .entry(zone)
+                        .or_insert_with(|| BoxPlotAccum::new(name.clone()))
+                        .update(std::iter::once(value))?;
+                }
+                Ok(())
+            })
+            .await?;
+
+            for (zone, accum) in &mut accums {
+                if let Some(attrib) = accum.finish()? {
+                    chart.add_attribute(attrib.with_group(zone_name(*zone, zone_measurement)));
+                }
+            }
+        }
+
+        Ok(chart)
+    }
+
     async fn process_raster(
         name: String,
         input: &TypedRasterQueryProcessor,
@@ -346,24 +534,15 @@ impl PlotQueryProcessor for BoxPlotRasterQueryProcessor {
         query: PlotQueryRectangle,
         ctx: &'p dyn QueryContext,
     ) -> Result<Self::OutputFormat> {
-        let results: Vec<_> = self
-            .input
-            .iter()
-            .zip(self.names.iter())
-            .map(|(proc, name)| Self::process_raster(name.clone(), proc, query.clone(), ctx))
-            .collect();
-
-        let results = futures::future::join_all(results)
-            .await
-            .into_iter()
-            .collect::<Result<Vec<_>>>();
+        Ok(self.box_plot(query, ctx).await?.to_vega_embeddable(false)?)
+    }
 
-        let mut chart = geoengine_datatypes::plots::BoxPlot::new();
-        results?
-            .into_iter()
-            .flatten()
-            .for_each(|a| chart.add_attribute(a));
-        Ok(chart.to_vega_embeddable(false)?)
+    async fn plot_figure_query<'p>(
+        &'p self,
+        query: PlotQueryRectangle,
+        ctx: &'p dyn QueryContext,
+    ) -> Result<Option<Figure>> {
+        Ok(Some(self.box_plot(query, ctx).await?.to_figure()))
     }
 }
 
@@ -514,6 +693,7 @@ mod tests {
         let histogram = BoxPlot {
             params: BoxPlotParams {
                 column_names: vec!["foobar".to_string()],
+                group_by: None,
             },
             sources: MockFeatureCollectionSource::<MultiPoint>::multiple(vec![])
                 .boxed()
@@ -548,6 +728,7 @@ mod tests {
         let histogram = BoxPlot {
             params: BoxPlotParams {
                 column_names: vec![],
+                group_by: None,
             },
             sources: MockFeatureCollectionSource::<MultiPoint>::multiple(vec![])
                 .boxed()
@@ -603,6 +784,7 @@ mod tests {
         let box_plot = BoxPlot {
             params: BoxPlotParams {
                 column_names: vec!["foo".to_string(), "bar".to_string()],
+                group_by: None,
             },
             sources: vector_source.into(),
         };
@@ -670,6 +852,7 @@ mod tests {
         let box_plot = BoxPlot {
             params: BoxPlotParams {
                 column_names: vec!["foo".to_string()],
+                group_by: None,
             },
             sources: vector_source.into(),
         };
@@ -723,6 +906,7 @@ mod tests {
         let box_plot = BoxPlot {
             params: BoxPlotParams {
                 column_names: vec!["foo".to_string()],
+                group_by: None,
             },
             sources: vector_source.into(),
         };
@@ -752,6 +936,7 @@ mod tests {
         let box_plot = BoxPlot {
             params: BoxPlotParams {
                 column_names: vec![],
+                group_by: None,
             },
             sources: vector_source.into(),
         };
@@ -781,6 +966,7 @@ mod tests {
         let box_plot = BoxPlot {
             params: BoxPlotParams {
                 column_names: vec!["foo".to_string()],
+                group_by: None,
             },
             sources: vector_source.into(),
         };
@@ -834,6 +1020,7 @@ mod tests {
         let box_plot = BoxPlot {
             params: BoxPlotParams {
                 column_names: vec!["foo".to_string()],
+                group_by: None,
             },
             sources: vector_source.into(),
         };
@@ -889,6 +1076,7 @@ mod tests {
         let box_plot = BoxPlot {
             params: BoxPlotParams {
                 column_names: vec!["foo".to_string()],
+                group_by: None,
             },
             sources: vector_source.into(),
         };
@@ -946,6 +1134,7 @@ mod tests {
         let box_plot = BoxPlot {
             params: BoxPlotParams {
                 column_names: vec![],
+                group_by: None,
             },
             sources: MockRasterSource {
                 params: MockRasterSourceParams {
@@ -1015,6 +1204,7 @@ mod tests {
         let box_plot = BoxPlot {
             params: BoxPlotParams {
                 column_names: vec![],
+                group_by: None,
             },
             sources: MockRasterSource {
                 params: MockRasterSourceParams {
@@ -1088,6 +1278,7 @@ mod tests {
         let box_plot = BoxPlot {
             params: BoxPlotParams {
                 column_names: vec![],
+                group_by: None,
             },
             sources: MockRasterSource {
                 params: MockRasterSourceParams {
@@ -1158,6 +1349,7 @@ mod tests {
         let histogram = BoxPlot {
             params: BoxPlotParams {
                 column_names: vec![],
+                group_by: None,
             },
             sources: MockRasterSource {
                 params: MockRasterSourceParams {
@@ -1233,6 +1425,7 @@ mod tests {
         let histogram = BoxPlot {
             params: BoxPlotParams {
                 column_names: vec![],
+                group_by: None,
             },
             sources: MockRasterSource {
                 params: MockRasterSourceParams {
@@ -1316,6 +1509,7 @@ mod tests {
         let histogram = BoxPlot {
             params: BoxPlotParams {
                 column_names: vec![],
+                group_by: None,
             },
             sources: MockRasterSource {
                 params: MockRasterSourceParams {
@@ -1425,6 +1619,7 @@ mod tests {
         let histogram = BoxPlot {
             params: BoxPlotParams {
                 column_names: vec![],
+                group_by: None,
             },
             sources: vec![
                 src.clone().boxed(),
@@ -1474,4 +1669,155 @@ mod tests {
 
         assert_eq!(expected.to_vega_embeddable(false).unwrap(), result);
     }
+
+    #[tokio::test]
+    async fn vector_data_grouped() {
+        let vector_source = MockFeatureCollectionSource::single(
+            DataCollection::from_slices(
+                &[] as &[NoGeometry],
+                &[TimeInterval::default(); 8],
+                &[
+                    ("foo", FeatureData::Int(vec![1, 2, 3, 4, 5, 6, 7, 8])),
+                    ("class", FeatureData::Int(vec![1, 2, 1, 2, 1, 2, 1, 2])),
+                ],
+            )
+            .unwrap(),
+        )
+        .boxed();
+
+        let box_plot = BoxPlot {
+            params: BoxPlotParams {
+                column_names: vec!["foo".to_string()],
+                group_by: Some("class".to_string()),
+            },
+            sources: vector_source.into(),
+        };
+
+        let execution_context = MockExecutionContext::test_default();
+
+        let query_processor = box_plot
+            .boxed()
+            .initialize(WorkflowOperatorPath::initialize_root(), &execution_context)
+            .await
+            .unwrap()
+            .query_processor()
+            .unwrap()
+            .json_vega()
+            .unwrap();
+
+        let result = query_processor
+            .plot_query(
+                PlotQueryRectangle {
+                    spatial_bounds: BoundingBox2D::new((-180., -90.).into(), (180., 90.).into())
+                        .unwrap(),
+                    time_interval: TimeInterval::default(),
+                    spatial_resolution: SpatialResolution::one(),
+                    attributes: PlotSeriesSelection::all(),
+                },
+                &MockQueryContext::new(ChunkByteSize::MIN),
+            )
+            .await
+            .unwrap();
+
+        let mut expected = geoengine_datatypes::plots::BoxPlot::new();
+        expected.add_attribute(
+            BoxPlotAttribute::new("foo".to_string(), 1.0, 7.0, 4.0, 2.0, 6.0, true)
+                .unwrap()
+                .with_group("1".to_string()),
+        );
+        expected.add_attribute(
+            BoxPlotAttribute::new("foo".to_string(), 2.0, 8.0, 5.0, 3.0, 7.0, true)
+                .unwrap()
+                .with_group("2".to_string()),
+        );
+
+        assert_eq!(expected.to_vega_embeddable(false).unwrap(), result);
+    }
+
+    #[tokio::test]
+    async fn raster_data_grouped_by_zone_raster() {
+        let tile_size_in_pixels = [4, 2].into();
+        let tiling_specification = TilingSpecification {
+            origin_coordinate: [0.0, 0.0].into(),
+            tile_size_in_pixels,
+        };
+        let execution_context = MockExecutionContext::new_with_tiling_spec(tiling_specification);
+
+        let raster_source = |data: Vec<u8>| {
+            MockRasterSource {
+                params: MockRasterSourceParams {
+                    data: vec![RasterTile2D::new_with_tile_info(
+                        TimeInterval::default(),
+                        TileInformation {
+                            global_geo_transform: TestDefault::test_default(),
+                            global_tile_position: [0, 0].into(),
+                            tile_size_in_pixels,
+                        },
+                        0,
+                        Grid2D::new(tile_size_in_pixels, data).unwrap().into(),
+                        CacheHint::default(),
+                    )],
+                    result_descriptor: RasterResultDescriptor {
+                        data_type: RasterDataType::U8,
+                        spatial_reference: SpatialReference::epsg_4326().into(),
+                        time: None,
+                        bbox: None,
+                        resolution: None,
+                        bands: RasterBandDescriptors::new_single_band(),
+                    },
+                },
+            }
+            .boxed()
+        };
+
+        let box_plot = BoxPlot {
+            params: BoxPlotParams {
+                column_names: vec![],
+                group_by: Some("Raster-2".to_string()),
+            },
+            sources: vec![
+                raster_source(vec![1, 2, 3, 4, 5, 6, 7, 8]),
+                raster_source(vec![1, 1, 1, 1, 2, 2, 2, 2]),
+            ]
+            .into(),
+        };
+
+        let query_processor = box_plot
+            .boxed()
+            .initialize(WorkflowOperatorPath::initialize_root(), &execution_context)
+            .await
+            .unwrap()
+            .query_processor()
+            .unwrap()
+            .json_vega()
+            .unwrap();
+
+        let result = query_processor
+            .plot_query(
+                PlotQueryRectangle {
+                    spatial_bounds: BoundingBox2D::new((-180., -90.).into(), (180., 90.).into())
+                        .unwrap(),
+                    time_interval: TimeInterval::default(),
+                    spatial_resolution: SpatialResolution::one(),
+                    attributes: PlotSeriesSelection::all(),
+                },
+                &MockQueryContext::test_default(),
+            )
+            .await
+            .unwrap();
+
+        let mut expected = geoengine_datatypes::plots::BoxPlot::new();
+        expected.add_attribute(
+            BoxPlotAttribute::new("Raster-1".to_string(), 1.0, 4.0, 2.5, 1.5, 3.5, true)
+                .unwrap()
+                .with_group("1".to_string()),
+        );
+        expected.add_attribute(
+            BoxPlotAttribute::new("Raster-1".to_string(), 5.0, 8.0, 6.5, 5.5, 7.5, true)
+                .unwrap()
+                .with_group("2".to_string()),
+        );
+
+        assert_eq!(expected.to_vega_embeddable(false).unwrap(), result);
+    }
 }
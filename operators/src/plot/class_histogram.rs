@@ -12,7 +12,7 @@ use crate::util::input::RasterOrVectorOperator;
 use async_trait::async_trait;
 use futures::StreamExt;
 use geoengine_datatypes::collections::FeatureCollectionInfos;
-use geoengine_datatypes::plots::{BarChart, Plot, PlotData};
+use geoengine_datatypes::plots::{BarChart, Figure, Plot, PlotData};
 use geoengine_datatypes::primitives::{
     AxisAlignedRectangle, BandSelection, BoundingBox2D, ClassificationMeasurement, FeatureDataType,
     Measurement, PlotQueryRectangle, RasterQueryRectangle,
@@ -256,7 +256,15 @@ impl PlotQueryProcessor for ClassHistogramRasterQueryProcessor {
         query: PlotQueryRectangle,
         ctx: &'p dyn QueryContext,
     ) -> Result<Self::OutputFormat> {
-        self.process(query, ctx).await
+        Ok(self.process(query, ctx).await?.to_vega_embeddable(false)?)
+    }
+
+    async fn plot_figure_query<'p>(
+        &'p self,
+        query: PlotQueryRectangle,
+        ctx: &'p dyn QueryContext,
+    ) -> Result<Option<Figure>> {
+        Ok(Some(self.process(query, ctx).await?.to_figure()))
     }
 }
 
@@ -273,7 +281,15 @@ impl PlotQueryProcessor for ClassHistogramVectorQueryProcessor {
         query: PlotQueryRectangle,
         ctx: &'p dyn QueryContext,
     ) -> Result<Self::OutputFormat> {
-        self.process(query, ctx).await
+        Ok(self.process(query, ctx).await?.to_vega_embeddable(false)?)
+    }
+
+    async fn plot_figure_query<'p>(
+        &'p self,
+        query: PlotQueryRectangle,
+        ctx: &'p dyn QueryContext,
+    ) -> Result<Option<Figure>> {
+        Ok(Some(self.process(query, ctx).await?.to_figure()))
     }
 }
 
@@ -282,7 +298,7 @@ impl ClassHistogramRasterQueryProcessor {
         &'p self,
         query: PlotQueryRectangle,
         ctx: &'p dyn QueryContext,
-    ) -> Result<<ClassHistogramRasterQueryProcessor as PlotQueryProcessor>::OutputFormat> {
+    ) -> Result<BarChart> {
         let mut class_counts: HashMap<u8, u64> = self
             .measurement
             .classes
@@ -327,9 +343,8 @@ impl ClassHistogramRasterQueryProcessor {
             Measurement::Classification(self.measurement.clone()).to_string(),
             "Frequency".to_string(),
         );
-        let chart = bar_chart.to_vega_embeddable(false)?;
 
-        Ok(chart)
+        Ok(bar_chart)
     }
 }
 
@@ -338,7 +353,7 @@ impl ClassHistogramVectorQueryProcessor {
         &'p self,
         query: PlotQueryRectangle,
         ctx: &'p dyn QueryContext,
-    ) -> Result<<ClassHistogramRasterQueryProcessor as PlotQueryProcessor>::OutputFormat> {
+    ) -> Result<BarChart> {
         let mut class_counts: HashMap<u8, u64> = self
             .measurement
             .classes
@@ -387,9 +402,8 @@ impl ClassHistogramVectorQueryProcessor {
             Measurement::Classification(self.measurement.clone()).to_string(),
             "Frequency".to_string(),
         );
-        let chart = bar_chart.to_vega_embeddable(false)?;
 
-        Ok(chart)
+        Ok(bar_chart)
     }
 }
 
@@ -0,0 +1,753 @@
+use crate::engine::{
+    CanonicOperatorName, ExecutionContext, InitializedPlotOperator, InitializedRasterOperator,
+    InitializedSources, InitializedVectorOperator, Operator, OperatorData, OperatorName,
+    PlotOperator, PlotQueryProcessor, PlotResultDescriptor, QueryContext, QueryProcessor,
+    RasterOperator, TypedPlotQueryProcessor, TypedRasterQueryProcessor, TypedVectorQueryProcessor,
+    VectorOperator, WorkflowOperatorPath,
+};
+use crate::error::{self, Error};
+use crate::processing::{CoveredPixels, FeatureTimeSpanIter, PixelCoverCreator};
+use crate::util::Result;
+use async_trait::async_trait;
+use futures::StreamExt;
+use geoengine_datatypes::collections::{FeatureCollection, FeatureCollectionInfos, VectorDataType};
+use geoengine_datatypes::dataset::NamedData;
+use geoengine_datatypes::plots::{Figure, Plot, PlotData};
+use geoengine_datatypes::primitives::{
+    BandSelection, ColumnSelection, FeatureDataType, Geometry, Measurement, PlotQueryRectangle,
+    RasterQueryRectangle, VectorQueryRectangle,
+};
+use geoengine_datatypes::raster::GridIndexAccess;
+use geoengine_datatypes::util::arrow::ArrowTyped;
+use num_traits::AsPrimitive;
+use serde::{Deserialize, Serialize};
+use snafu::ensure;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+pub const CONFUSION_MATRIX_OPERATOR_NAME: &str = "ConfusionMatrix";
+
+/// A confusion matrix that assesses the accuracy of a classified raster against
+/// reference points or polygons with a class column.
+///
+/// Each raster pixel that is covered by a reference feature is a sample.
+/// The predicted class is the pixel value and the reference class is the value of the feature's class column.
+pub type ConfusionMatrix = Operator<ConfusionMatrixParams, ConfusionMatrixSources>;
+
+impl OperatorName for ConfusionMatrix {
+    const TYPE_NAME: &'static str = "ConfusionMatrix";
+}
+
+/// The parameter spec for `ConfusionMatrix`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfusionMatrixParams {
+    /// Name of the (numeric) column of the reference features that contains the reference classes
+    pub column_name: String,
+    /// Whether to output the accuracy assessment as JSON or the confusion matrix as a Vega heatmap
+    #[serde(default)]
+    pub output_format: ConfusionMatrixOutputFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfusionMatrixOutputFormat {
+    /// The confusion matrix, overall accuracy, Cohen's kappa and per-class precision, recall and F1 score
+    #[default]
+    Json,
+    /// A heatmap of the confusion matrix
+    Vega,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfusionMatrixSources {
+    /// the classified raster
+    pub raster: Box<dyn RasterOperator>,
+    /// the reference points or polygons
+    pub vector: Box<dyn VectorOperator>,
+}
+
+impl OperatorData for ConfusionMatrixSources {
+    fn data_names_collect(&self, data_names: &mut Vec<NamedData>) {
+        self.raster.data_names_collect(data_names);
+        self.vector.data_names_collect(data_names);
+    }
+}
+
+struct InitializedConfusionMatrixSources {
+    raster: Box<dyn InitializedRasterOperator>,
+    vector: Box<dyn InitializedVectorOperator>,
+}
+
+#[async_trait]
+impl InitializedSources<InitializedConfusionMatrixSources> for ConfusionMatrixSources {
+    async fn initialize_sources(
+        self,
+        path: WorkflowOperatorPath,
+        context: &dyn ExecutionContext,
+    ) -> Result<InitializedConfusionMatrixSources> {
+        let raster_path = path.clone_and_append(0);
+        let vector_path = path.clone_and_append(1);
+
+        Ok(InitializedConfusionMatrixSources {
+            raster: self.raster.initialize(raster_path, context).await?,
+            vector: self.vector.initialize(vector_path, context).await?,
+        })
+    }
+}
+
+#[typetag::serde]
+#[async_trait]
+impl PlotOperator for ConfusionMatrix {
+    async fn _initialize(
+        self: Box<Self>,
+        path: WorkflowOperatorPath,
+        context: &dyn ExecutionContext,
+    ) -> Result<Box<dyn InitializedPlotOperator>> {
+        let name = CanonicOperatorName::from(&self);
+
+        let initialized_sources = self.sources.initialize_sources(path, context).await?;
+        let raster_desc = initialized_sources.raster.result_descriptor();
+        let vector_desc = initialized_sources.vector.result_descriptor();
+
+        ensure!(
+            raster_desc.bands.len() == 1,
+            error::OperatorDoesNotSupportMultiBandsSourcesYet {
+                operator: ConfusionMatrix::TYPE_NAME
+            }
+        );
+
+        ensure!(
+            matches!(
+                vector_desc.data_type,
+                VectorDataType::MultiPoint | VectorDataType::MultiPolygon
+            ),
+            error::InvalidType {
+                expected: format!(
+                    "{} or {}",
+                    VectorDataType::MultiPoint,
+                    VectorDataType::MultiPolygon
+                ),
+                found: vector_desc.data_type.to_string(),
+            }
+        );
+
+        ensure!(
+            raster_desc.spatial_reference == vector_desc.spatial_reference,
+            error::InvalidSpatialReference {
+                expected: vector_desc.spatial_reference,
+                found: raster_desc.spatial_reference,
+            }
+        );
+
+        let column_name = &self.params.column_name;
+        match vector_desc.column_data_type(column_name) {
+            None => {
+                return Err(Error::ColumnDoesNotExist {
+                    column: column_name.clone(),
+                });
+            }
+            Some(FeatureDataType::Text | FeatureDataType::DateTime) => {
+                return Err(Error::InvalidOperatorSpec {
+                    reason: format!("column `{column_name}` must be numerical"),
+                });
+            }
+            Some(
+                FeatureDataType::Int
+                | FeatureDataType::Float
+                | FeatureDataType::Bool
+                | FeatureDataType::Category,
+            ) => {
+                // okay
+            }
+        }
+
+        // prefer the class names of the classified raster over the ones of the reference column
+        let class_names = match (
+            &raster_desc.bands[0].measurement,
+            vector_desc.column_measurement(column_name),
+        ) {
+            (Measurement::Classification(measurement), _)
+            | (_, Some(Measurement::Classification(measurement))) => measurement
+                .classes
+                .iter()
+                .map(|(class, name)| (i64::from(*class), name.clone()))
+                .collect(),
+            _ => BTreeMap::new(),
+        };
+
+        let result_descriptor = vector_desc.clone().into();
+
+        Ok(InitializedConfusionMatrix {
+            name,
+            result_descriptor,
+            raster: initialized_sources.raster,
+            vector: initialized_sources.vector,
+            column_name: self.params.column_name,
+            class_names,
+            output_format: self.params.output_format,
+        }
+        .boxed())
+    }
+
+    span_fn!(ConfusionMatrix);
+}
+
+/// The initialization of `ConfusionMatrix`
+pub struct InitializedConfusionMatrix {
+    name: CanonicOperatorName,
+    result_descriptor: PlotResultDescriptor,
+    raster: Box<dyn InitializedRasterOperator>,
+    vector: Box<dyn InitializedVectorOperator>,
+    column_name: String,
+    class_names: BTreeMap<i64, String>,
+    output_format: ConfusionMatrixOutputFormat,
+}
+
+impl InitializedPlotOperator for InitializedConfusionMatrix {
+    fn query_processor(&self) -> Result<TypedPlotQueryProcessor> {
+        let processor = ConfusionMatrixQueryProcessor {
+            raster: self.raster.query_processor()?,
+            vector: self.vector.query_processor()?,
+            column_name: self.column_name.clone(),
+            class_names: self.class_names.clone(),
+        };
+
+        Ok(match self.output_format {
+            ConfusionMatrixOutputFormat::Json => TypedPlotQueryProcessor::JsonPlain(
+                ConfusionMatrixJsonQueryProcessor { processor }.boxed(),
+            ),
+            ConfusionMatrixOutputFormat::Vega => TypedPlotQueryProcessor::JsonVega(
+                ConfusionMatrixVegaQueryProcessor { processor }.boxed(),
+            ),
+        })
+    }
+
+    fn result_descriptor(&self) -> &PlotResultDescriptor {
+        &self.result_descriptor
+    }
+
+    fn canonic_name(&self) -> CanonicOperatorName {
+        self.name.clone()
+    }
+}
+
+/// A query processor that samples the classified raster at the reference features.
+pub struct ConfusionMatrixQueryProcessor {
+    raster: TypedRasterQueryProcessor,
+    vector: TypedVectorQueryProcessor,
+    column_name: String,
+    class_names: BTreeMap<i64, String>,
+}
+
+/// Outputs the accuracy assessment of the `ConfusionMatrixQueryProcessor` as JSON.
+pub struct ConfusionMatrixJsonQueryProcessor {
+    processor: ConfusionMatrixQueryProcessor,
+}
+
+/// Outputs the confusion matrix of the `ConfusionMatrixQueryProcessor` as a Vega heatmap.
+pub struct ConfusionMatrixVegaQueryProcessor {
+    processor: ConfusionMatrixQueryProcessor,
+}
+
+#[async_trait]
+impl PlotQueryProcessor for ConfusionMatrixJsonQueryProcessor {
+    type OutputFormat = serde_json::Value;
+
+    fn plot_type(&self) -> &'static str {
+        CONFUSION_MATRIX_OPERATOR_NAME
+    }
+
+    async fn plot_query<'p>(
+        &'p self,
+        query: PlotQueryRectangle,
+        ctx: &'p dyn QueryContext,
+    ) -> Result<Self::OutputFormat> {
+        let confusion_matrix = self.processor.confusion_matrix(query, ctx).await?;
+
+        serde_json::to_value(confusion_matrix.accuracy_assessment()).map_err(Into::into)
+    }
+}
+
+#[async_trait]
+impl PlotQueryProcessor for ConfusionMatrixVegaQueryProcessor {
+    type OutputFormat = PlotData;
+
+    fn plot_type(&self) -> &'static str {
+        CONFUSION_MATRIX_OPERATOR_NAME
+    }
+
+    async fn plot_query<'p>(
+        &'p self,
+        query: PlotQueryRectangle,
+        ctx: &'p dyn QueryContext,
+    ) -> Result<Self::OutputFormat> {
+        let confusion_matrix = self.processor.confusion_matrix(query, ctx).await?;
+
+        confusion_matrix
+            .to_vega_embeddable(false)
+            .map_err(Into::into)
+    }
+
+    async fn plot_figure_query<'p>(
+        &'p self,
+        query: PlotQueryRectangle,
+        ctx: &'p dyn QueryContext,
+    ) -> Result<Option<Figure>> {
+        let confusion_matrix = self.processor.confusion_matrix(query, ctx).await?;
+
+        Ok(Some(confusion_matrix.to_figure()))
+    }
+}
+
+/// The number of samples for each pair of reference class and predicted class
+type SampleCounts = HashMap<(i64, i64), u64>;
+
+impl ConfusionMatrixQueryProcessor {
+    async fn confusion_matrix(
+        &self,
+        query: PlotQueryRectangle,
+        ctx: &dyn QueryContext,
+    ) -> Result<geoengine_datatypes::plots::ConfusionMatrix> {
+        let query = VectorQueryRectangle {
+            spatial_bounds: query.spatial_bounds,
+            time_interval: query.time_interval,
+            spatial_resolution: query.spatial_resolution,
+            attributes: ColumnSelection::all(),
+        };
+
+        let mut counts = SampleCounts::new();
+
+        match &self.vector {
+            TypedVectorQueryProcessor::MultiPoint(processor) => {
+                let mut collections = processor.query(query.clone(), ctx).await?;
+                while let Some(collection) = collections.next().await {
+                    self.add_samples(&mut counts, &collection?, &query, ctx)
+                        .await?;
+                }
+            }
+            TypedVectorQueryProcessor::MultiPolygon(processor) => {
+                let mut collections = processor.query(query.clone(), ctx).await?;
+                while let Some(collection) = collections.next().await {
+                    self.add_samples(&mut counts, &collection?, &query, ctx)
+                        .await?;
+                }
+            }
+            TypedVectorQueryProcessor::Data(_) | TypedVectorQueryProcessor::MultiLineString(_) => {
+                unreachable!("checked during initialization")
+            }
+        }
+
+        Ok(self.confusion_matrix_from_counts(&counts))
+    }
+
+    /// Adds a sample for each pixel that is covered by a feature with a reference class
+    async fn add_samples<G>(
+        &self,
+        counts: &mut SampleCounts,
+        collection: &FeatureCollection<G>,
+        query: &VectorQueryRectangle,
+        ctx: &dyn QueryContext,
+    ) -> Result<()>
+    where
+        G: Geometry + ArrowTyped,
+        FeatureCollection<G>: PixelCoverCreator<G>,
+    {
+        if collection.is_empty() {
+            return Ok(());
+        }
+
+        let collection = collection.sort_by_time_asc()?;
+
+        let reference_classes = collection
+            .data(&self.column_name)?
+            .float_options_iter()
+            .map(|class| {
+                class
+                    .filter(|class| class.is_finite())
+                    .map(|class| class as i64)
+            })
+            .collect::<Vec<_>>();
+
+        let covered_pixels = collection.create_covered_pixels();
+        let collection = covered_pixels.collection_ref();
+
+        for time_span in FeatureTimeSpanIter::new(collection.time_intervals()) {
+            let raster_query = RasterQueryRectangle::from_qrect_and_bands(
+                &VectorQueryRectangle {
+                    spatial_bounds: query.spatial_bounds,
+                    time_interval: time_span.time_interval,
+                    spatial_resolution: query.spatial_resolution,
+                    attributes: ColumnSelection::all(),
+                },
+                BandSelection::first(),
+            );
+
+            call_on_generic_raster_processor!(&self.raster, processor => {
+                let mut tiles = processor.raster_query(raster_query, ctx).await?;
+
+                while let Some(tile) = tiles.next().await {
+                    let tile = tile?;
+
+                    if tile.is_empty() {
+                        continue;
+                    }
+
+                    for feature_index in time_span.feature_index_start..=time_span.feature_index_end {
+                        let Some(reference_class) = reference_classes[feature_index] else {
+                            continue;
+                        };
+
+                        if !collection.time_intervals()[feature_index].intersects(&tile.time) {
+                            continue;
+                        }
+
+                        for grid_idx in covered_pixels.covered_pixels(feature_index, &tile) {
+                            if let Ok(Some(value)) = tile.get_at_grid_index(grid_idx) {
+                                let predicted_class: i64 = value.as_();
+                                *counts.entry((reference_class, predicted_class)).or_default() += 1;
+                            }
+                        }
+                    }
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Creates the confusion matrix for the known classes and all classes that occur in the samples
+    fn confusion_matrix_from_counts(
+        &self,
+        counts: &SampleCounts,
+    ) -> geoengine_datatypes::plots::ConfusionMatrix {
+        let classes = self
+            .class_names
+            .keys()
+            .copied()
+            .chain(
+                counts
+                    .keys()
+                    .flat_map(|(reference, predicted)| [*reference, *predicted]),
+            )
+            .collect::<BTreeSet<i64>>();
+
+        let class_indices = classes
+            .iter()
+            .enumerate()
+            .map(|(index, class)| (*class, index))
+            .collect::<HashMap<_, _>>();
+
+        let mut confusion_matrix = geoengine_datatypes::plots::ConfusionMatrix::new(
+            classes
+                .iter()
+                .map(|class| {
+                    self.class_names
+                        .get(class)
+                        .cloned()
+                        .unwrap_or_else(|| class.to_string())
+                })
+                .collect(),
+        );
+
+        for ((reference, predicted), count) in counts {
+            confusion_matrix.add_count(class_indices[reference], class_indices[predicted], *count);
+        }
+
+        confusion_matrix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{
+        ChunkByteSize, MockExecutionContext, MockQueryContext, RasterBandDescriptor,
+        RasterBandDescriptors, RasterResultDescriptor,
+    };
+    use crate::mock::{MockFeatureCollectionSource, MockRasterSource, MockRasterSourceParams};
+    use geoengine_datatypes::collections::{MultiPointCollection, MultiPolygonCollection};
+    use geoengine_datatypes::plots::AccuracyAssessment;
+    use geoengine_datatypes::primitives::{
+        BoundingBox2D, CacheHint, FeatureData, MultiLineString, MultiPolygon, PlotSeriesSelection,
+        SpatialResolution, TimeInterval,
+    };
+    use geoengine_datatypes::raster::{
+        Grid2D, RasterDataType, RasterTile2D, TileInformation, TilingSpecification,
+    };
+    use geoengine_datatypes::spatial_reference::SpatialReference;
+    use geoengine_datatypes::util::test::TestDefault;
+    use serde_json::json;
+
+    #[test]
+    fn serialization() {
+        let confusion_matrix = ConfusionMatrix {
+            params: ConfusionMatrixParams {
+                column_name: "class".to_string(),
+                output_format: ConfusionMatrixOutputFormat::Vega,
+            },
+            sources: ConfusionMatrixSources {
+                raster: mock_raster_source(),
+                vector: MockFeatureCollectionSource::<MultiPolygon>::multiple(vec![]).boxed(),
+            },
+        };
+
+        let serialized = serde_json::to_string(&confusion_matrix).unwrap();
+        assert!(serialized.contains(r#""outputFormat":"vega""#));
+
+        let deserialized: ConfusionMatrix = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(deserialized.params, confusion_matrix.params);
+
+        let params: ConfusionMatrixParams =
+            serde_json::from_value(json!({ "columnName": "class" })).unwrap();
+        assert_eq!(params.output_format, ConfusionMatrixOutputFormat::Json);
+    }
+
+    fn mock_raster_source() -> Box<dyn RasterOperator> {
+        MockRasterSource {
+            params: MockRasterSourceParams {
+                data: vec![RasterTile2D::new_with_tile_info(
+                    TimeInterval::default(),
+                    TileInformation {
+                        global_geo_transform: TestDefault::test_default(),
+                        global_tile_position: [0, 0].into(),
+                        tile_size_in_pixels: [3, 2].into(),
+                    },
+                    0,
+                    Grid2D::new([3, 2].into(), vec![1_u8, 2, 3, 3, 2, 1])
+                        .unwrap()
+                        .into(),
+                    CacheHint::default(),
+                )],
+                result_descriptor: RasterResultDescriptor {
+                    data_type: RasterDataType::U8,
+                    spatial_reference: SpatialReference::epsg_4326().into(),
+                    time: None,
+                    bbox: None,
+                    resolution: None,
+                    bands: RasterBandDescriptors::new(vec![RasterBandDescriptor::new(
+                        "classes".into(),
+                        Measurement::classification(
+                            "land cover".to_string(),
+                            [
+                                (1, "water".to_string()),
+                                (2, "forest".to_string()),
+                                (3, "urban".to_string()),
+                            ]
+                            .into_iter()
+                            .collect(),
+                        ),
+                    )])
+                    .unwrap(),
+                },
+            },
+        }
+        .boxed()
+    }
+
+    fn execution_context() -> MockExecutionContext {
+        MockExecutionContext::new_with_tiling_spec(TilingSpecification {
+            origin_coordinate: [0.0, 0.0].into(),
+            tile_size_in_pixels: [3, 2].into(),
+        })
+    }
+
+    fn query() -> PlotQueryRectangle {
+        PlotQueryRectangle {
+            spatial_bounds: BoundingBox2D::new((0., -3.).into(), (2., 0.).into()).unwrap(),
+            time_interval: TimeInterval::default(),
+            spatial_resolution: SpatialResolution::one(),
+            attributes: PlotSeriesSelection::all(),
+        }
+    }
+
+    #[tokio::test]
+    async fn points() {
+        // the raster has the classes
+        //   1 2
+        //   3 3
+        //   2 1
+        let points = MultiPointCollection::from_slices(
+            &[
+                (0.5, -0.5),
+                (1.5, -0.5),
+                (0.5, -1.5),
+                (1.5, -1.5),
+                (0.5, -2.5),
+                (1.5, -2.5),
+            ],
+            &[TimeInterval::default(); 6],
+            &[(
+                "class",
+                FeatureData::NullableInt(vec![Some(1), Some(2), Some(3), Some(2), Some(2), None]),
+            )],
+        )
+        .unwrap();
+
+        let confusion_matrix = ConfusionMatrix {
+            params: ConfusionMatrixParams {
+                column_name: "class".to_string(),
+                output_format: ConfusionMatrixOutputFormat::Json,
+            },
+            sources: ConfusionMatrixSources {
+                raster: mock_raster_source(),
+                vector: MockFeatureCollectionSource::single(points).boxed(),
+            },
+        };
+
+        let query_processor = confusion_matrix
+            .boxed()
+            .initialize(
+                WorkflowOperatorPath::initialize_root(),
+                &execution_context(),
+            )
+            .await
+            .unwrap()
+            .query_processor()
+            .unwrap()
+            .json_plain()
+            .unwrap();
+
+        let result = query_processor
+            .plot_query(query(), &MockQueryContext::new(ChunkByteSize::MIN))
+            .await
+            .unwrap();
+
+        let assessment: AccuracyAssessment = serde_json::from_value(result).unwrap();
+
+        assert_eq!(assessment.classes, vec!["water", "forest", "urban"]);
+        assert_eq!(
+            assessment.confusion_matrix,
+            vec![vec![1, 0, 0], vec![0, 2, 1], vec![0, 0, 1]]
+        );
+        assert_eq!(assessment.sample_count, 5);
+        assert_eq!(assessment.overall_accuracy, Some(0.8));
+
+        let forest = &assessment.class_accuracies[1];
+        assert_eq!(forest.precision, Some(1.));
+        assert_eq!(forest.recall, Some(2. / 3.));
+
+        let urban = &assessment.class_accuracies[2];
+        assert_eq!(urban.precision, Some(0.5));
+        assert_eq!(urban.recall, Some(1.));
+    }
+
+    #[tokio::test]
+    async fn polygons() {
+        // covers the pixels of the upper two rows, i.e., the classes 1, 2, 3 and 3
+        let polygons = MultiPolygonCollection::from_slices(
+            &[MultiPolygon::new(vec![vec![vec![
+                (-0.1, 0.1).into(),
+                (1.9, 0.1).into(),
+                (1.9, -1.9).into(),
+                (-0.1, -1.9).into(),
+                (-0.1, 0.1).into(),
+            ]]])
+            .unwrap()],
+            &[TimeInterval::default()],
+            &[("class", FeatureData::Int(vec![3]))],
+        )
+        .unwrap();
+
+        let confusion_matrix = ConfusionMatrix {
+            params: ConfusionMatrixParams {
+                column_name: "class".to_string(),
+                output_format: ConfusionMatrixOutputFormat::Vega,
+            },
+            sources: ConfusionMatrixSources {
+                raster: mock_raster_source(),
+                vector: MockFeatureCollectionSource::single(polygons).boxed(),
+            },
+        };
+
+        let query_processor = confusion_matrix
+            .boxed()
+            .initialize(
+                WorkflowOperatorPath::initialize_root(),
+                &execution_context(),
+            )
+            .await
+            .unwrap()
+            .query_processor()
+            .unwrap()
+            .json_vega()
+            .unwrap();
+
+        let result = query_processor
+            .plot_query(query(), &MockQueryContext::new(ChunkByteSize::MIN))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result,
+            geoengine_datatypes::plots::ConfusionMatrix::from_counts(
+                vec![
+                    "water".to_string(),
+                    "forest".to_string(),
+                    "urban".to_string()
+                ],
+                vec![vec![0, 0, 0], vec![0, 0, 0], vec![1, 1, 2]],
+            )
+            .unwrap()
+            .to_vega_embeddable(false)
+            .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn it_checks_the_sources() {
+        let lines = MockFeatureCollectionSource::<MultiLineString>::multiple(vec![]).boxed();
+
+        let confusion_matrix = ConfusionMatrix {
+            params: ConfusionMatrixParams {
+                column_name: "class".to_string(),
+                output_format: ConfusionMatrixOutputFormat::Json,
+            },
+            sources: ConfusionMatrixSources {
+                raster: mock_raster_source(),
+                vector: lines,
+            },
+        };
+
+        assert!(matches!(
+            confusion_matrix
+                .boxed()
+                .initialize(
+                    WorkflowOperatorPath::initialize_root(),
+                    &execution_context()
+                )
+                .await,
+            Err(Error::InvalidType { .. })
+        ));
+
+        let points = MultiPointCollection::from_slices(
+            &[(0.5, -0.5)],
+            &[TimeInterval::default()],
+            &[("class", FeatureData::Int(vec![1]))],
+        )
+        .unwrap();
+
+        let confusion_matrix = ConfusionMatrix {
+            params: ConfusionMatrixParams {
+                column_name: "label".to_string(),
+                output_format: ConfusionMatrixOutputFormat::Json,
+            },
+            sources: ConfusionMatrixSources {
+                raster: mock_raster_source(),
+                vector: MockFeatureCollectionSource::single(points).boxed(),
+            },
+        };
+
+        assert!(matches!(
+            confusion_matrix
+                .boxed()
+                .initialize(
+                    WorkflowOperatorPath::initialize_root(),
+                    &execution_context()
+                )
+                .await,
+            Err(Error::ColumnDoesNotExist { .. })
+        ));
+    }
+}
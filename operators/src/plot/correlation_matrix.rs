@@ -0,0 +1,438 @@
+use crate::engine::{
+    CanonicOperatorName, ExecutionContext, InitializedPlotOperator, Operator, OperatorName,
+    PlotOperator, PlotQueryProcessor, PlotResultDescriptor, QueryContext,
+    SingleRasterOrVectorSource, TypedPlotQueryProcessor, WorkflowOperatorPath,
+};
+use crate::error;
+use crate::plot::attribute_samples::{AttributeSampleProcessor, InitializedAttributeSource};
+use crate::util::Result;
+use async_trait::async_trait;
+use geoengine_datatypes::plots::{Figure, Plot, PlotData};
+use geoengine_datatypes::primitives::PlotQueryRectangle;
+use serde::{Deserialize, Serialize};
+use snafu::ensure;
+
+pub const CORRELATION_MATRIX_OPERATOR_NAME: &str = "CorrelationMatrix";
+
+/// A matrix of the pairwise Pearson correlation coefficients of the bands of a raster
+/// or the numeric attributes of a vector input.
+///
+/// Each pair of attributes is correlated over all pixels or features that have a value for both attributes.
+pub type CorrelationMatrix = Operator<CorrelationMatrixParams, SingleRasterOrVectorSource>;
+
+impl OperatorName for CorrelationMatrix {
+    const TYPE_NAME: &'static str = "CorrelationMatrix";
+}
+
+/// The parameter spec for `CorrelationMatrix`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrelationMatrixParams {
+    /// Names of the raster bands or (numeric) vector attributes to correlate.
+    /// All bands or numeric attributes are used if it is empty (default).
+    #[serde(default)]
+    pub attribute_names: Vec<String>,
+}
+
+#[typetag::serde]
+#[async_trait]
+impl PlotOperator for CorrelationMatrix {
+    async fn _initialize(
+        self: Box<Self>,
+        path: WorkflowOperatorPath,
+        context: &dyn ExecutionContext,
+    ) -> Result<Box<dyn InitializedPlotOperator>> {
+        let name = CanonicOperatorName::from(&self);
+
+        let source = InitializedAttributeSource::initialize(
+            self.sources.source,
+            &self.params.attribute_names,
+            path,
+            context,
+        )
+        .await?;
+
+        ensure!(
+            source.attributes().len() >= 2,
+            error::InvalidOperatorSpec {
+                reason: "CorrelationMatrix requires at least two attributes".to_string(),
+            }
+        );
+
+        Ok(InitializedCorrelationMatrix {
+            name,
+            result_descriptor: source.plot_result_descriptor(),
+            source,
+        }
+        .boxed())
+    }
+
+    span_fn!(CorrelationMatrix);
+}
+
+/// The initialization of `CorrelationMatrix`
+pub struct InitializedCorrelationMatrix {
+    name: CanonicOperatorName,
+    result_descriptor: PlotResultDescriptor,
+    source: InitializedAttributeSource,
+}
+
+impl InitializedPlotOperator for InitializedCorrelationMatrix {
+    fn query_processor(&self) -> Result<TypedPlotQueryProcessor> {
+        let processor = CorrelationMatrixQueryProcessor {
+            input: self.source.query_processor()?,
+            attributes: self.source.attributes().to_vec(),
+        };
+
+        Ok(TypedPlotQueryProcessor::JsonVega(processor.boxed()))
+    }
+
+    fn result_descriptor(&self) -> &PlotResultDescriptor {
+        &self.result_descriptor
+    }
+
+    fn canonic_name(&self) -> CanonicOperatorName {
+        self.name.clone()
+    }
+}
+
+/// A query processor that calculates the correlation matrix of its raster bands or vector attributes.
+pub struct CorrelationMatrixQueryProcessor {
+    input: AttributeSampleProcessor,
+    attributes: Vec<String>,
+}
+
+#[async_trait]
+impl PlotQueryProcessor for CorrelationMatrixQueryProcessor {
+    type OutputFormat = PlotData;
+
+    fn plot_type(&self) -> &'static str {
+        CORRELATION_MATRIX_OPERATOR_NAME
+    }
+
+    async fn plot_query<'p>(
+        &'p self,
+        query: PlotQueryRectangle,
+        ctx: &'p dyn QueryContext,
+    ) -> Result<Self::OutputFormat> {
+        Ok(self.matrix(query, ctx).await?.to_vega_embeddable(false)?)
+    }
+
+    async fn plot_figure_query<'p>(
+        &'p self,
+        query: PlotQueryRectangle,
+        ctx: &'p dyn QueryContext,
+    ) -> Result<Option<Figure>> {
+        Ok(Some(self.matrix(query, ctx).await?.to_figure()))
+    }
+}
+
+impl CorrelationMatrixQueryProcessor {
+    async fn matrix(
+        &self,
+        query: PlotQueryRectangle,
+        ctx: &dyn QueryContext,
+    ) -> Result<geoengine_datatypes::plots::CorrelationMatrix> {
+        let mut matrix =
+            geoengine_datatypes::plots::CorrelationMatrix::new(self.attributes.clone());
+
+        self.input
+            .for_each_sample(&query, ctx, |sample| matrix.update(sample))
+            .await?;
+
+        Ok(matrix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{
+        ChunkByteSize, MockExecutionContext, MockQueryContext, RasterBandDescriptor,
+        RasterBandDescriptors, RasterOperator, RasterResultDescriptor, VectorOperator,
+    };
+    use crate::error::Error;
+    use crate::mock::{MockFeatureCollectionSource, MockRasterSource, MockRasterSourceParams};
+    use geoengine_datatypes::collections::DataCollection;
+    use geoengine_datatypes::primitives::{
+        BoundingBox2D, CacheHint, FeatureData, NoGeometry, PlotSeriesSelection, SpatialResolution,
+        TimeInterval,
+    };
+    use geoengine_datatypes::raster::{
+        Grid2D, GridOrEmpty2D, MaskedGrid2D, RasterDataType, RasterTile2D, TileInformation,
+        TilingSpecification,
+    };
+    use geoengine_datatypes::spatial_reference::SpatialReference;
+    use geoengine_datatypes::util::test::TestDefault;
+
+    fn query() -> PlotQueryRectangle {
+        PlotQueryRectangle {
+            spatial_bounds: BoundingBox2D::new((0., -3.).into(), (2., 0.).into()).unwrap(),
+            time_interval: TimeInterval::default(),
+            spatial_resolution: SpatialResolution::one(),
+            attributes: PlotSeriesSelection::all(),
+        }
+    }
+
+    async fn correlations(
+        operator: CorrelationMatrix,
+        execution_context: &MockExecutionContext,
+    ) -> serde_json::Value {
+        let query_processor = operator
+            .boxed()
+            .initialize(WorkflowOperatorPath::initialize_root(), execution_context)
+            .await
+            .unwrap()
+            .query_processor()
+            .unwrap()
+            .json_vega()
+            .unwrap();
+
+        let result = query_processor
+            .plot_query(query(), &MockQueryContext::new(ChunkByteSize::MIN))
+            .await
+            .unwrap();
+
+        let vega: serde_json::Value = serde_json::from_str(&result.vega_string).unwrap();
+        vega["data"]["values"].clone()
+    }
+
+    #[test]
+    fn serialization() {
+        let correlation_matrix = CorrelationMatrix {
+            params: CorrelationMatrixParams {
+                attribute_names: vec!["foo".to_string(), "bar".to_string()],
+            },
+            sources: MockFeatureCollectionSource::<NoGeometry>::multiple(vec![])
+                .boxed()
+                .into(),
+        };
+
+        let serialized = serde_json::to_value(&correlation_matrix).unwrap();
+
+        assert_eq!(
+            serialized["params"],
+            serde_json::json!({
+                "attributeNames": ["foo", "bar"],
+            })
+        );
+
+        let deserialized: CorrelationMatrix = serde_json::from_value(serialized).unwrap();
+        assert_eq!(deserialized.params, correlation_matrix.params);
+
+        let deserialized: CorrelationMatrixParams =
+            serde_json::from_value(serde_json::json!({})).unwrap();
+        assert!(deserialized.attribute_names.is_empty());
+    }
+
+    #[tokio::test]
+    async fn raster_bands() {
+        let tile = |band: u32, data: GridOrEmpty2D<u8>| {
+            RasterTile2D::new_with_tile_info(
+                TimeInterval::default(),
+                TileInformation {
+                    global_geo_transform: TestDefault::test_default(),
+                    global_tile_position: [0, 0].into(),
+                    tile_size_in_pixels: [3, 2].into(),
+                },
+                band,
+                data,
+                CacheHint::default(),
+            )
+        };
+
+        let raster_source = MockRasterSource {
+            params: MockRasterSourceParams {
+                data: vec![
+                    tile(
+                        0,
+                        Grid2D::new([3, 2].into(), vec![1, 2, 3, 4, 5, 6])
+                            .unwrap()
+                            .into(),
+                    ),
+                    tile(
+                        1,
+                        MaskedGrid2D::new(
+                            Grid2D::new([3, 2].into(), vec![12, 10, 8, 6, 4, 0]).unwrap(),
+                            Grid2D::new([3, 2].into(), vec![true, true, true, true, true, false])
+                                .unwrap(),
+                        )
+                        .unwrap()
+                        .into(),
+                    ),
+                    tile(
+                        2,
+                        Grid2D::new([3, 2].into(), vec![2, 1, 2, 1, 2, 1])
+                            .unwrap()
+                            .into(),
+                    ),
+                ],
+                result_descriptor: RasterResultDescriptor {
+                    data_type: RasterDataType::U8,
+                    spatial_reference: SpatialReference::epsg_4326().into(),
+                    time: None,
+                    bbox: None,
+                    resolution: None,
+                    bands: RasterBandDescriptors::new(vec![
+                        RasterBandDescriptor::new_unitless("a".into()),
+                        RasterBandDescriptor::new_unitless("b".into()),
+                        RasterBandDescriptor::new_unitless("c".into()),
+                    ])
+                    .unwrap(),
+                },
+            },
+        }
+        .boxed();
+
+        let execution_context = MockExecutionContext::new_with_tiling_spec(TilingSpecification {
+            origin_coordinate: [0.0, 0.0].into(),
+            tile_size_in_pixels: [3, 2].into(),
+        });
+
+        let values = correlations(
+            CorrelationMatrix {
+                params: CorrelationMatrixParams {
+                    attribute_names: vec![],
+                },
+                sources: raster_source.into(),
+            },
+            &execution_context,
+        )
+        .await;
+
+        let values = values.as_array().unwrap();
+        assert_eq!(values.len(), 9);
+
+        // a and b are perfectly negatively correlated, b has no data in the last pixel
+        assert_eq!(values[1]["x"], "a");
+        assert_eq!(values[1]["y"], "b");
+        assert!(float_cmp::approx_eq!(
+            f64,
+            values[1]["correlation"].as_f64().unwrap(),
+            -1.,
+            epsilon = 1e-12
+        ));
+        assert_eq!(values[1]["count"], 5);
+
+        // reference value computed with Python's `statistics.correlation`
+        assert_eq!(values[2]["y"], "c");
+        assert!(float_cmp::approx_eq!(
+            f64,
+            values[2]["correlation"].as_f64().unwrap(),
+            -0.292_770_021_884_559_97,
+            epsilon = 1e-12
+        ));
+        assert_eq!(values[2]["count"], 6);
+    }
+
+    #[tokio::test]
+    async fn vector_attributes() {
+        let vector_source = MockFeatureCollectionSource::multiple(vec![
+            DataCollection::from_slices(
+                &[] as &[NoGeometry],
+                &[TimeInterval::default(); 3],
+                &[
+                    ("foo", FeatureData::Int(vec![1, 2, 3])),
+                    (
+                        "bar",
+                        FeatureData::NullableFloat(vec![Some(2.), None, Some(6.)]),
+                    ),
+                    (
+                        "baz",
+                        FeatureData::Text(vec!["a".into(), "b".into(), "c".into()]),
+                    ),
+                ],
+            )
+            .unwrap(),
+            DataCollection::from_slices(
+                &[] as &[NoGeometry],
+                &[TimeInterval::default(); 2],
+                &[
+                    ("foo", FeatureData::Int(vec![4, 5])),
+                    ("bar", FeatureData::NullableFloat(vec![Some(8.), Some(10.)])),
+                    ("baz", FeatureData::Text(vec!["d".into(), "e".into()])),
+                ],
+            )
+            .unwrap(),
+        ])
+        .boxed();
+
+        let values = correlations(
+            CorrelationMatrix {
+                params: CorrelationMatrixParams {
+                    attribute_names: vec![],
+                },
+                sources: vector_source.into(),
+            },
+            &MockExecutionContext::test_default(),
+        )
+        .await;
+
+        // the text column is skipped and the numeric columns are sorted by name
+        let expected = [
+            ("bar", "bar", 4),
+            ("bar", "foo", 4),
+            ("foo", "bar", 4),
+            ("foo", "foo", 5),
+        ];
+        let values = values.as_array().unwrap();
+        assert_eq!(values.len(), expected.len());
+
+        for (value, (x, y, count)) in values.iter().zip(expected) {
+            assert_eq!(value["x"], x);
+            assert_eq!(value["y"], y);
+            assert_eq!(value["count"], count);
+            assert!(float_cmp::approx_eq!(
+                f64,
+                value["correlation"].as_f64().unwrap(),
+                1.,
+                epsilon = 1e-12
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn it_checks_the_attributes() {
+        let vector_source = || {
+            MockFeatureCollectionSource::single(
+                DataCollection::from_slices(
+                    &[] as &[NoGeometry],
+                    &[TimeInterval::default(); 2],
+                    &[
+                        ("foo", FeatureData::Int(vec![1, 2])),
+                        ("baz", FeatureData::Text(vec!["a".into(), "b".into()])),
+                    ],
+                )
+                .unwrap(),
+            )
+            .boxed()
+        };
+
+        let execution_context = MockExecutionContext::test_default();
+
+        let initialize = |attribute_names: Vec<&str>| {
+            CorrelationMatrix {
+                params: CorrelationMatrixParams {
+                    attribute_names: attribute_names.into_iter().map(String::from).collect(),
+                },
+                sources: vector_source().into(),
+            }
+            .boxed()
+            .initialize(WorkflowOperatorPath::initialize_root(), &execution_context)
+        };
+
+        assert!(matches!(
+            initialize(vec!["foo", "missing"]).await,
+            Err(Error::ColumnDoesNotExist { .. })
+        ));
+        assert!(matches!(
+            initialize(vec!["foo", "baz"]).await,
+            Err(Error::InvalidOperatorSpec { .. })
+        ));
+        assert!(matches!(
+            initialize(vec![]).await,
+            Err(Error::InvalidOperatorSpec { .. })
+        ));
+        assert!(initialize(vec!["foo", "foo"]).await.is_ok());
+    }
+}
@@ -0,0 +1,104 @@
+use crate::engine::{
+    QueryContext, QueryProcessor, TypedRasterQueryProcessor, VectorResultDescriptor,
+};
+use crate::error::Error;
+use crate::util::Result;
+use futures::StreamExt;
+use futures::stream::BoxStream;
+use geoengine_datatypes::primitives::{
+    BandSelection, FeatureDataRef, FeatureDataType, Measurement, PlotQueryRectangle,
+    RasterQueryRectangle,
+};
+use geoengine_datatypes::raster::{GridOrEmpty, GridSize};
+use num_traits::AsPrimitive;
+
+/// Checks that the `group_by` column exists and contains categories, text or integers
+pub(crate) fn ensure_group_by_column(
+    result_descriptor: &VectorResultDescriptor,
+    column: &str,
+) -> Result<()> {
+    match result_descriptor.column_data_type(column) {
+        Some(FeatureDataType::Category | FeatureDataType::Text | FeatureDataType::Int) => Ok(()),
+        Some(_) => Err(Error::InvalidOperatorSpec {
+            reason: format!(
+                "Column '{column}' cannot be used for grouping, it must contain categories, text or integers."
+            ),
+        }),
+        None => Err(Error::ColumnDoesNotExist {
+            column: column.to_string(),
+        }),
+    }
+}
+
+/// Returns the group of each feature. Features without a group value are `None`.
+pub(crate) fn feature_groups(data: &FeatureDataRef) -> Vec<Option<String>> {
+    data.strings_iter()
+        .zip(data.nulls())
+        .map(|(group, is_null)| (!is_null).then_some(group))
+        .collect()
+}
+
+/// Names a zone by its class name if the zone raster is a classification
+pub(crate) fn zone_name(zone: i64, measurement: &Measurement) -> String {
+    let class = match measurement {
+        Measurement::Classification(classification) => u8::try_from(zone)
+            .ok()
+            .and_then(|zone| classification.classes.get(&zone)),
+        Measurement::Unitless | Measurement::Continuous(_) => None,
+    };
+
+    class.map_or_else(|| zone.to_string(), Clone::clone)
+}
+
+/// Calls `f` with the zone and the value of each pixel of the `values` raster that lies in a zone,
+/// i.e., where the `zones` raster has data. Zone values are truncated to integers.
+pub(crate) async fn for_each_zoned_pixel<F>(
+    values: &TypedRasterQueryProcessor,
+    zones: &TypedRasterQueryProcessor,
+    query: &PlotQueryRectangle,
+    ctx: &dyn QueryContext,
+    mut f: F,
+) -> Result<()>
+where
+    F: FnMut(i64, Option<f64>) -> Result<()> + Send,
+{
+    let query = RasterQueryRectangle::from_qrect_and_bands(query, BandSelection::first());
+
+    // both rasters use the same tiling, so their tiles can be processed pairwise
+    let mut tiles = pixel_values(values, query.clone(), ctx)
+        .await?
+        .zip(pixel_values(zones, query, ctx).await?);
+
+    while let Some((values, zones)) = tiles.next().await {
+        for (value, zone) in values?.into_iter().zip(zones?) {
+            if let Some(zone) = zone {
+                f(zone as i64, value)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Queries the pixel values of a raster as `f64`, tile by tile
+async fn pixel_values<'a>(
+    input: &'a TypedRasterQueryProcessor,
+    query: RasterQueryRectangle,
+    ctx: &'a dyn QueryContext,
+) -> Result<BoxStream<'a, Result<Vec<Option<f64>>>>> {
+    Ok(call_on_generic_raster_processor!(input, processor => {
+        processor
+            .query(query, ctx)
+            .await?
+            .map(|tile| {
+                tile.map(|tile| match tile.grid_array {
+                    GridOrEmpty::Grid(g) => g
+                        .masked_element_deref_iterator()
+                        .map(|v| v.map(AsPrimitive::<f64>::as_))
+                        .collect(),
+                    GridOrEmpty::Empty(e) => vec![None; e.number_of_elements()],
+                })
+            })
+            .boxed()
+    }))
+}
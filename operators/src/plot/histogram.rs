@@ -7,6 +7,7 @@ use crate::engine::{
 use crate::engine::{QueryProcessor, WorkflowOperatorPath};
 use crate::error;
 use crate::error::Error;
+use crate::plot::group_by::{ensure_group_by_column, feature_groups};
 use crate::string_token;
 use crate::util::Result;
 use crate::util::input::RasterOrVectorOperator;
@@ -14,7 +15,7 @@ use async_trait::async_trait;
 use float_cmp::approx_eq;
 use futures::stream::BoxStream;
 use futures::{StreamExt, TryFutureExt};
-use geoengine_datatypes::plots::{Plot, PlotData};
+use geoengine_datatypes::plots::{Figure, Plot, PlotData};
 use geoengine_datatypes::primitives::{
     AxisAlignedRectangle, BandSelection, BoundingBox2D, DataRef, FeatureDataRef, FeatureDataType,
     Geometry, Measurement, PlotQueryRectangle, RasterQueryRectangle,
@@ -33,6 +34,7 @@ pub const HISTOGRAM_OPERATOR_NAME: &str = "Histogram";
 /// A histogram plot about either a raster or a vector input.
 ///
 /// For vector inputs, it calculates the histogram on one of its attributes.
+/// If `group_by` is set, it stacks a histogram per group of another attribute.
 ///
 pub type Histogram = Operator<HistogramParams, SingleRasterOrVectorSource>;
 
@@ -53,6 +55,9 @@ pub struct HistogramParams {
     /// Whether to create an interactive output (`false` by default)
     #[serde(default)]
     pub interactive: bool,
+    /// Name of a vector column (category, text or integer) to compute a histogram per group.
+    #[serde(default)]
+    pub group_by: Option<String>,
 }
 
 /// Options for how to derive the histogram's number of buckets.
@@ -73,14 +78,70 @@ fn default_max_number_of_buckets() -> u8 {
 }
 
 string_token!(Data, "data");
+string_token!(MeasurementBounds, "measurement");
 
-/// Let the bounds either be computed or given.
+/// Let the bounds either be computed, derived from the measurement or given.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum HistogramBounds {
     Data(Data),
-    Values { min: f64, max: f64 },
-    // TODO: use bounds in measurement if they are available
+    /// Uses the range of the classes of a classification measurement, which requires no pass over the data
+    Measurement(MeasurementBounds),
+    Values {
+        min: f64,
+        max: f64,
+    },
+}
+
+impl HistogramParams {
+    /// Replaces `HistogramBounds::Measurement` by the range of the classes of the `measurement`.
+    /// If the number of buckets is derived, there is a bucket per class value, so that the
+    /// histogram needs no pre-pass over the data.
+    fn resolve_measurement_bounds(&mut self, measurement: &Measurement) -> Result<()> {
+        if !matches!(self.bounds, HistogramBounds::Measurement(_)) {
+            return Ok(());
+        }
+
+        let classes = match measurement {
+            Measurement::Classification(classification) => &classification.classes,
+            Measurement::Unitless | Measurement::Continuous(_) => {
+                return Err(Error::InvalidOperatorSpec {
+                    reason: format!(
+                        "The measurement of `{}` has no bounds, it must be a classification",
+                        self.attribute_name
+                    ),
+                });
+            }
+        };
+
+        let (Some(&min), Some(&max)) = (classes.keys().min(), classes.keys().max()) else {
+            return Err(Error::InvalidOperatorSpec {
+                reason: format!(
+                    "The classification of `{}` has no classes",
+                    self.attribute_name
+                ),
+            });
+        };
+
+        self.bounds = HistogramBounds::Values {
+            min: f64::from(min),
+            max: f64::from(max),
+        };
+
+        if let HistogramBuckets::SquareRootChoiceRule {
+            max_number_of_buckets,
+        } = self.buckets
+        {
+            let number_of_class_values = u16::from(max - min) + 1;
+            self.buckets = HistogramBuckets::Number {
+                value: u8::try_from(number_of_class_values)
+                    .unwrap_or(u8::MAX)
+                    .min(max_number_of_buckets),
+            };
+        }
+
+        Ok(())
+    }
 }
 
 #[typetag::serde]
@@ -102,15 +163,23 @@ impl PlotOperator for Histogram {
                 let in_desc = raster_source.result_descriptor();
 
                 ensure!(
-                    in_desc
-                        .bands
-                        .iter()
-                        .any(|b| b.name == self.params.attribute_name),
+                    self.params.group_by.is_none(),
                     error::InvalidOperatorSpec {
-                        reason: "Band with given `attribute_name` does not exist".to_string(),
+                        reason: "Grouped histograms are only supported for vector data".to_string(),
                     }
                 );
 
+                let band = in_desc
+                    .bands
+                    .iter()
+                    .find(|b| b.name == self.params.attribute_name)
+                    .ok_or_else(|| Error::InvalidOperatorSpec {
+                        reason: "Band with given `attribute_name` does not exist".to_string(),
+                    })?;
+
+                let mut params = self.params;
+                params.resolve_measurement_bounds(&band.measurement)?;
+
                 InitializedHistogram::new(
                     name,
                     PlotResultDescriptor {
@@ -121,7 +190,7 @@ impl PlotOperator for Histogram {
                             .bbox
                             .and_then(|p| BoundingBox2D::new(p.lower_left(), p.upper_right()).ok()),
                     },
-                    self.params,
+                    params,
                     raster_source,
                 )
                 .boxed()
@@ -157,9 +226,21 @@ impl PlotOperator for Histogram {
                     }
                 }
 
+                if let Some(group_by) = &self.params.group_by {
+                    ensure_group_by_column(vector_source.result_descriptor(), group_by)?;
+                }
+
                 let in_desc = vector_source.result_descriptor().clone();
 
-                InitializedHistogram::new(name, in_desc.into(), self.params, vector_source).boxed()
+                let mut params = self.params;
+                params.resolve_measurement_bounds(
+                    &in_desc
+                        .column_measurement(&params.attribute_name)
+                        .cloned()
+                        .into(),
+                )?;
+
+                InitializedHistogram::new(name, in_desc.into(), params, vector_source).boxed()
             }
         })
     }
@@ -175,6 +256,7 @@ pub struct InitializedHistogram<Op> {
     source: Op,
     interactive: bool,
     attribute_name: String,
+    group_by: Option<String>,
 }
 
 impl<Op> InitializedHistogram<Op> {
@@ -211,6 +293,7 @@ impl<Op> InitializedHistogram<Op> {
             source,
             interactive: params.interactive,
             attribute_name: params.attribute_name,
+            group_by: params.group_by,
         }
     }
 }
@@ -249,6 +332,7 @@ impl InitializedPlotOperator for InitializedHistogram<Box<dyn InitializedVectorO
         let processor = HistogramVectorQueryProcessor {
             input: self.source.query_processor()?,
             column_name: self.attribute_name.clone(),
+            group_by: self.group_by.clone(),
             measurement: self
                 .source
                 .result_descriptor()
@@ -284,6 +368,7 @@ pub struct HistogramRasterQueryProcessor {
 pub struct HistogramVectorQueryProcessor {
     input: TypedVectorQueryProcessor,
     column_name: String,
+    group_by: Option<String>,
     measurement: Measurement,
     metadata: HistogramMetadataOptions,
     interactive: bool,
@@ -302,17 +387,18 @@ impl PlotQueryProcessor for HistogramRasterQueryProcessor {
         query: PlotQueryRectangle,
         ctx: &'p dyn QueryContext,
     ) -> Result<Self::OutputFormat> {
-        self.preprocess(query.clone(), ctx)
-            .and_then(move |mut histogram_metadata| async move {
-                histogram_metadata.sanitize();
-                if histogram_metadata.has_invalid_parameters() {
-                    // early return of empty histogram
-                    return self.empty_histogram();
-                }
+        Ok(self
+            .histogram(query, ctx)
+            .await?
+            .to_vega_embeddable(self.interactive)?)
+    }
 
-                self.process(histogram_metadata, query, ctx).await
-            })
-            .await
+    async fn plot_figure_query<'p>(
+        &'p self,
+        query: PlotQueryRectangle,
+        ctx: &'p dyn QueryContext,
+    ) -> Result<Option<Figure>> {
+        Ok(Some(self.histogram(query, ctx).await?.to_figure()))
     }
 }
 
@@ -329,6 +415,27 @@ impl PlotQueryProcessor for HistogramVectorQueryProcessor {
         query: PlotQueryRectangle,
         ctx: &'p dyn QueryContext,
     ) -> Result<Self::OutputFormat> {
+        Ok(self
+            .histogram(query, ctx)
+            .await?
+            .to_vega_embeddable(self.interactive)?)
+    }
+
+    async fn plot_figure_query<'p>(
+        &'p self,
+        query: PlotQueryRectangle,
+        ctx: &'p dyn QueryContext,
+    ) -> Result<Option<Figure>> {
+        Ok(Some(self.histogram(query, ctx).await?.to_figure()))
+    }
+}
+
+impl HistogramRasterQueryProcessor {
+    async fn histogram<'p>(
+        &'p self,
+        query: PlotQueryRectangle,
+        ctx: &'p dyn QueryContext,
+    ) -> Result<geoengine_datatypes::plots::Histogram> {
         self.preprocess(query.clone(), ctx)
             .and_then(move |mut histogram_metadata| async move {
                 histogram_metadata.sanitize();
@@ -341,9 +448,7 @@ impl PlotQueryProcessor for HistogramVectorQueryProcessor {
             })
             .await
     }
-}
 
-impl HistogramRasterQueryProcessor {
     async fn preprocess<'p>(
         &'p self,
         query: PlotQueryRectangle,
@@ -383,7 +488,7 @@ impl HistogramRasterQueryProcessor {
         metadata: HistogramMetadata,
         query: PlotQueryRectangle,
         ctx: &'p dyn QueryContext,
-    ) -> Result<<HistogramRasterQueryProcessor as PlotQueryProcessor>::OutputFormat> {
+    ) -> Result<geoengine_datatypes::plots::Histogram> {
         let mut histogram = geoengine_datatypes::plots::Histogram::builder(
             metadata.number_of_buckets,
             metadata.min,
@@ -406,26 +511,38 @@ impl HistogramRasterQueryProcessor {
             }
         });
 
-        let chart = histogram.to_vega_embeddable(self.interactive)?;
-
-        Ok(chart)
+        Ok(histogram)
     }
 
-    fn empty_histogram(
-        &self,
-    ) -> Result<<HistogramRasterQueryProcessor as PlotQueryProcessor>::OutputFormat> {
+    fn empty_histogram(&self) -> Result<geoengine_datatypes::plots::Histogram> {
         let histogram =
             geoengine_datatypes::plots::Histogram::builder(1, 0., 0., self.measurement.clone())
                 .build()
                 .map_err(Error::from)?;
 
-        let chart = histogram.to_vega_embeddable(self.interactive)?;
-
-        Ok(chart)
+        Ok(histogram)
     }
 }
 
 impl HistogramVectorQueryProcessor {
+    async fn histogram<'p>(
+        &'p self,
+        query: PlotQueryRectangle,
+        ctx: &'p dyn QueryContext,
+    ) -> Result<Box<dyn Plot>> {
+        self.preprocess(query.clone(), ctx)
+            .and_then(move |mut histogram_metadata| async move {
+                histogram_metadata.sanitize();
+                if histogram_metadata.has_invalid_parameters() {
+                    // early return of empty histogram
+                    return self.empty_histogram();
+                }
+
+                self.process(histogram_metadata, query, ctx).await
+            })
+            .await
+    }
+
     async fn preprocess<'p>(
         &'p self,
         query: PlotQueryRectangle,
@@ -468,7 +585,11 @@ impl HistogramVectorQueryProcessor {
         metadata: HistogramMetadata,
         query: PlotQueryRectangle,
         ctx: &'p dyn QueryContext,
-    ) -> Result<<HistogramRasterQueryProcessor as PlotQueryProcessor>::OutputFormat> {
+    ) -> Result<Box<dyn Plot>> {
+        if let Some(group_by) = &self.group_by {
+            return self.process_grouped(group_by, metadata, query, ctx).await;
+        }
+
         let mut histogram = geoengine_datatypes::plots::Histogram::builder(
             metadata.number_of_buckets,
             metadata.min,
@@ -490,22 +611,51 @@ impl HistogramVectorQueryProcessor {
             }
         });
 
-        let chart = histogram.to_vega_embeddable(self.interactive)?;
+        Ok(Box::new(histogram))
+    }
+
+    async fn process_grouped<'p>(
+        &'p self,
+        group_by: &str,
+        metadata: HistogramMetadata,
+        query: PlotQueryRectangle,
+        ctx: &'p dyn QueryContext,
+    ) -> Result<Box<dyn Plot>> {
+        let mut histogram = geoengine_datatypes::plots::GroupedHistogram::new(
+            metadata.number_of_buckets,
+            metadata.min,
+            metadata.max,
+            self.measurement.clone(),
+        )?;
+
+        call_on_generic_vector_processor!(&self.input, processor => {
+            let mut query = processor.query(query.into(), ctx).await?;
 
-        Ok(chart)
+            while let Some(collection) = query.next().await {
+                let collection = collection?;
+
+                let feature_data = collection.data(&self.column_name).expect("checked in param");
+                let groups = feature_groups(&collection.data(group_by)?);
+
+                for (value, group) in feature_data.float_options_iter().zip(&groups) {
+                    // features without a group are ignored
+                    if let Some(group) = group {
+                        histogram.add_value(group, value);
+                    }
+                }
+            }
+        });
+
+        Ok(Box::new(histogram))
     }
 
-    fn empty_histogram(
-        &self,
-    ) -> Result<<HistogramRasterQueryProcessor as PlotQueryProcessor>::OutputFormat> {
+    fn empty_histogram(&self) -> Result<Box<dyn Plot>> {
         let histogram =
             geoengine_datatypes::plots::Histogram::builder(1, 0., 0., self.measurement.clone())
                 .build()
                 .map_err(Error::from)?;
 
-        let chart = histogram.to_vega_embeddable(self.interactive)?;
-
-        Ok(chart)
+        Ok(Box::new(histogram))
     }
 }
 
@@ -654,9 +804,9 @@ mod tests {
     use super::*;
 
     use crate::engine::{
-        ChunkByteSize, MockExecutionContext, MockQueryContext, RasterBandDescriptors,
-        RasterOperator, RasterResultDescriptor, StaticMetaData, VectorColumnInfo, VectorOperator,
-        VectorResultDescriptor,
+        ChunkByteSize, MockExecutionContext, MockQueryContext, RasterBandDescriptor,
+        RasterBandDescriptors, RasterOperator, RasterResultDescriptor, StaticMetaData,
+        VectorColumnInfo, VectorOperator, VectorResultDescriptor,
     };
     use crate::mock::{MockFeatureCollectionSource, MockRasterSource, MockRasterSourceParams};
     use crate::source::{
@@ -692,6 +842,7 @@ mod tests {
                 },
                 buckets: HistogramBuckets::Number { value: 15 },
                 interactive: false,
+                group_by: None,
             },
             sources: MockFeatureCollectionSource::<MultiPoint>::multiple(vec![])
                 .boxed()
@@ -740,6 +891,7 @@ mod tests {
                     max_number_of_buckets: 100,
                 },
                 interactive: false,
+                group_by: None,
             },
             sources: MockFeatureCollectionSource::<MultiPoint>::multiple(vec![])
                 .boxed()
@@ -774,6 +926,25 @@ mod tests {
         assert_eq!(deserialized.params, histogram.params);
     }
 
+    #[test]
+    fn serialization_measurement_bounds() {
+        let serialized = json!({
+            "attributeName": "band",
+            "bounds": "measurement",
+            "buckets": {
+                "type": "squareRootChoiceRule",
+            },
+        })
+        .to_string();
+
+        let deserialized: HistogramParams = serde_json::from_str(&serialized).unwrap();
+
+        assert_eq!(
+            deserialized.bounds,
+            HistogramBounds::Measurement(Default::default())
+        );
+    }
+
     #[tokio::test]
     async fn column_name_for_raster_source() {
         let histogram = Histogram {
@@ -782,6 +953,7 @@ mod tests {
                 bounds: HistogramBounds::Values { min: 0.0, max: 8.0 },
                 buckets: HistogramBuckets::Number { value: 3 },
                 interactive: false,
+                group_by: None,
             },
             sources: mock_raster_source().into(),
         };
@@ -841,6 +1013,7 @@ mod tests {
                 bounds: HistogramBounds::Values { min: 0.0, max: 8.0 },
                 buckets: HistogramBuckets::Number { value: 3 },
                 interactive: false,
+                group_by: None,
             },
             sources: mock_raster_source().into(),
         };
@@ -879,6 +1052,124 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn raster_bounds_from_measurement() {
+        let tile_size_in_pixels = [3, 2].into();
+        let tiling_specification = TilingSpecification {
+            origin_coordinate: [0.0, 0.0].into(),
+            tile_size_in_pixels,
+        };
+        let execution_context = MockExecutionContext::new_with_tiling_spec(tiling_specification);
+
+        let measurement = Measurement::classification(
+            "land cover".to_string(),
+            (1..=6)
+                .map(|class| (class, format!("class {class}")))
+                .collect(),
+        );
+
+        let raster_source = MockRasterSource {
+            params: MockRasterSourceParams {
+                data: vec![RasterTile2D::new_with_tile_info(
+                    TimeInterval::default(),
+                    TileInformation {
+                        global_geo_transform: TestDefault::test_default(),
+                        global_tile_position: [0, 0].into(),
+                        tile_size_in_pixels,
+                    },
+                    0,
+                    Grid2D::new([3, 2].into(), vec![1, 2, 3, 4, 5, 6])
+                        .unwrap()
+                        .into(),
+                    CacheHint::default(),
+                )],
+                result_descriptor: RasterResultDescriptor {
+                    data_type: RasterDataType::U8,
+                    spatial_reference: SpatialReference::epsg_4326().into(),
+                    time: None,
+                    bbox: None,
+                    resolution: None,
+                    bands: RasterBandDescriptors::new(vec![RasterBandDescriptor::new(
+                        "band".to_string(),
+                        measurement.clone(),
+                    )])
+                    .unwrap(),
+                },
+            },
+        }
+        .boxed();
+
+        let histogram = Histogram {
+            params: HistogramParams {
+                attribute_name: "band".to_string(),
+                bounds: HistogramBounds::Measurement(Default::default()),
+                buckets: HistogramBuckets::SquareRootChoiceRule {
+                    max_number_of_buckets: 100,
+                },
+                interactive: false,
+                group_by: None,
+            },
+            sources: raster_source.into(),
+        };
+
+        let query_processor = histogram
+            .boxed()
+            .initialize(WorkflowOperatorPath::initialize_root(), &execution_context)
+            .await
+            .unwrap()
+            .query_processor()
+            .unwrap()
+            .json_vega()
+            .unwrap();
+
+        let result = query_processor
+            .plot_query(
+                PlotQueryRectangle {
+                    spatial_bounds: BoundingBox2D::new((0., -3.).into(), (2., 0.).into()).unwrap(),
+                    time_interval: TimeInterval::default(),
+                    spatial_resolution: SpatialResolution::one(),
+                    attributes: PlotSeriesSelection::all(),
+                },
+                &MockQueryContext::new(ChunkByteSize::MIN),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result,
+            geoengine_datatypes::plots::Histogram::builder(6, 1., 6., measurement)
+                .counts(vec![1; 6])
+                .build()
+                .unwrap()
+                .to_vega_embeddable(false)
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn unitless_measurement_has_no_bounds() {
+        let histogram = Histogram {
+            params: HistogramParams {
+                attribute_name: "band".to_string(),
+                bounds: HistogramBounds::Measurement(Default::default()),
+                buckets: HistogramBuckets::Number { value: 3 },
+                interactive: false,
+                group_by: None,
+            },
+            sources: mock_raster_source().into(),
+        };
+
+        let result = histogram
+            .boxed()
+            .initialize(
+                WorkflowOperatorPath::initialize_root(),
+                &MockExecutionContext::test_default(),
+            )
+            .await;
+
+        assert!(matches!(result, Err(Error::InvalidOperatorSpec { .. })));
+    }
+
     #[tokio::test]
     async fn simple_raster_without_spec() {
         let tile_size_in_pixels = [3, 2].into();
@@ -896,6 +1187,7 @@ mod tests {
                     max_number_of_buckets: 100,
                 },
                 interactive: false,
+                group_by: None,
             },
             sources: mock_raster_source().into(),
         };
@@ -958,6 +1250,7 @@ mod tests {
                 bounds: HistogramBounds::Values { min: 0.0, max: 8.0 },
                 buckets: HistogramBuckets::Number { value: 3 },
                 interactive: true,
+                group_by: None,
             },
             sources: vector_source.into(),
         };
@@ -999,6 +1292,101 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn vector_data_grouped() {
+        let vector_source = MockFeatureCollectionSource::single(
+            DataCollection::from_slices(
+                &[] as &[NoGeometry],
+                &[TimeInterval::default(); 6],
+                &[
+                    ("foo", FeatureData::Int(vec![1, 2, 3, 6, 7, 8])),
+                    (
+                        "species",
+                        FeatureData::NullableText(vec![
+                            Some("a".to_string()),
+                            Some("b".to_string()),
+                            Some("a".to_string()),
+                            Some("b".to_string()),
+                            Some("a".to_string()),
+                            None,
+                        ]),
+                    ),
+                ],
+            )
+            .unwrap(),
+        )
+        .boxed();
+
+        let histogram = Histogram {
+            params: HistogramParams {
+                attribute_name: "foo".to_string(),
+                bounds: HistogramBounds::Values { min: 0.0, max: 8.0 },
+                buckets: HistogramBuckets::Number { value: 2 },
+                interactive: false,
+                group_by: Some("species".to_string()),
+            },
+            sources: vector_source.into(),
+        };
+
+        let execution_context = MockExecutionContext::test_default();
+
+        let query_processor = histogram
+            .boxed()
+            .initialize(WorkflowOperatorPath::initialize_root(), &execution_context)
+            .await
+            .unwrap()
+            .query_processor()
+            .unwrap()
+            .json_vega()
+            .unwrap();
+
+        let result = query_processor
+            .plot_query(
+                PlotQueryRectangle {
+                    spatial_bounds: BoundingBox2D::new((-180., -90.).into(), (180., 90.).into())
+                        .unwrap(),
+                    time_interval: TimeInterval::default(),
+                    spatial_resolution: SpatialResolution::one(),
+                    attributes: PlotSeriesSelection::all(),
+                },
+                &MockQueryContext::new(ChunkByteSize::MIN),
+            )
+            .await
+            .unwrap();
+
+        let mut expected =
+            geoengine_datatypes::plots::GroupedHistogram::new(2, 0., 8., Measurement::Unitless)
+                .unwrap();
+        for (group, value) in [("a", 1.), ("b", 2.), ("a", 3.), ("b", 6.), ("a", 7.)] {
+            expected.add_value(group, Some(value));
+        }
+
+        assert_eq!(result, expected.to_vega_embeddable(false).unwrap());
+    }
+
+    #[tokio::test]
+    async fn raster_data_cannot_be_grouped() {
+        let histogram = Histogram {
+            params: HistogramParams {
+                attribute_name: "band".to_string(),
+                bounds: HistogramBounds::Data(Default::default()),
+                buckets: HistogramBuckets::Number { value: 2 },
+                interactive: false,
+                group_by: Some("zones".to_string()),
+            },
+            sources: mock_raster_source().into(),
+        };
+
+        let execution_context = MockExecutionContext::test_default();
+
+        let result = histogram
+            .boxed()
+            .initialize(WorkflowOperatorPath::initialize_root(), &execution_context)
+            .await;
+
+        assert!(matches!(result, Err(Error::InvalidOperatorSpec { .. })));
+    }
+
     #[tokio::test]
     async fn vector_data_with_nulls() {
         let vector_source = MockFeatureCollectionSource::single(
@@ -1029,6 +1417,7 @@ mod tests {
                     max_number_of_buckets: 100,
                 },
                 interactive: false,
+                group_by: None,
             },
             sources: vector_source.into(),
         };
@@ -1208,6 +1597,7 @@ mod tests {
                     max_number_of_buckets: 100,
                 },
                 interactive: false,
+                group_by: None,
             },
             sources: MockRasterSource {
                 params: MockRasterSourceParams {
@@ -1289,6 +1679,7 @@ mod tests {
                     max_number_of_buckets: 100,
                 },
                 interactive: false,
+                group_by: None,
             },
             sources: vector_source.into(),
         };
@@ -1357,6 +1748,7 @@ mod tests {
                     max_number_of_buckets: 100,
                 },
                 interactive: false,
+                group_by: None,
             },
             sources: vector_source.into(),
         };
@@ -1419,6 +1811,7 @@ mod tests {
                     max_number_of_buckets: 100,
                 },
                 interactive: false,
+                group_by: None,
             },
             sources: MockRasterSource {
                 params: MockRasterSourceParams {
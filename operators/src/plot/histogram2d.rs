@@ -0,0 +1,524 @@
+use crate::engine::{
+    CanonicOperatorName, ExecutionContext, InitializedPlotOperator, Operator, OperatorName,
+    PlotOperator, PlotQueryProcessor, PlotResultDescriptor, QueryContext,
+    SingleRasterOrVectorSource, TypedPlotQueryProcessor, WorkflowOperatorPath,
+};
+use crate::error::Error;
+use crate::plot::attribute_samples::{AttributeSampleProcessor, InitializedAttributeSource};
+use crate::plot::{HistogramBounds, HistogramBuckets};
+use crate::util::Result;
+use async_trait::async_trait;
+use float_cmp::approx_eq;
+use geoengine_datatypes::plots::{Figure, HistogramDimension, Plot, PlotData};
+use geoengine_datatypes::primitives::{Coordinate2D, PlotQueryRectangle};
+use serde::{Deserialize, Serialize};
+
+pub const HISTOGRAM2D_OPERATOR_NAME: &str = "Histogram2D";
+
+/// A 2D histogram (density plot) of two raster bands or two numeric vector attributes.
+///
+/// Only pixels or features that have a value for both attributes are counted.
+/// To compare a raster with a vector attribute, join the raster to the vectors with a
+/// `RasterVectorJoin` first and use the joined column.
+pub type Histogram2D = Operator<Histogram2DParams, SingleRasterOrVectorSource>;
+
+impl OperatorName for Histogram2D {
+    const TYPE_NAME: &'static str = "Histogram2D";
+}
+
+/// The parameter spec for `Histogram2D`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Histogram2DParams {
+    pub x: Histogram2DAxis,
+    pub y: Histogram2DAxis,
+}
+
+/// The attribute and the buckets of one axis of a `Histogram2D`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Histogram2DAxis {
+    /// Name of the (numeric) vector attribute or raster band.
+    pub attribute_name: String,
+    /// The bounds (min/max) of the axis.
+    pub bounds: HistogramBounds,
+    /// Specify the number of buckets or how it should be derived.
+    pub buckets: HistogramBuckets,
+}
+
+impl Histogram2DAxis {
+    fn requires_data(&self) -> bool {
+        matches!(self.bounds, HistogramBounds::Data(_))
+            || matches!(self.buckets, HistogramBuckets::SquareRootChoiceRule { .. })
+    }
+
+    /// Creates the histogram dimension from the parameters and the statistics of the data
+    fn dimension(&self, statistics: &AxisStatistics) -> Result<HistogramDimension> {
+        let (min, max) = match self.bounds {
+            HistogramBounds::Values { min, max } => (min, max),
+            HistogramBounds::Data(_) if statistics.count == 0 => (0., 0.),
+            HistogramBounds::Data(_) => (statistics.min, statistics.max),
+        };
+
+        let mut number_of_buckets = match self.buckets {
+            HistogramBuckets::Number { value } => value as usize,
+            HistogramBuckets::SquareRootChoiceRule {
+                max_number_of_buckets,
+            } => (f64::sqrt(statistics.count as f64) as usize)
+                .min(max_number_of_buckets as usize)
+                .max(1),
+        };
+
+        // prevent the rare case that min=max and you have more than one bucket
+        if approx_eq!(f64, min, max) {
+            number_of_buckets = 1;
+        }
+
+        HistogramDimension::new(self.attribute_name.clone(), min, max, number_of_buckets)
+            .map_err(Error::from)
+    }
+}
+
+#[typetag::serde]
+#[async_trait]
+impl PlotOperator for Histogram2D {
+    async fn _initialize(
+        self: Box<Self>,
+        path: WorkflowOperatorPath,
+        context: &dyn ExecutionContext,
+    ) -> Result<Box<dyn InitializedPlotOperator>> {
+        let name = CanonicOperatorName::from(&self);
+
+        let source = InitializedAttributeSource::initialize(
+            self.sources.source,
+            &[
+                self.params.x.attribute_name.clone(),
+                self.params.y.attribute_name.clone(),
+            ],
+            path,
+            context,
+        )
+        .await?;
+
+        Ok(InitializedHistogram2D {
+            name,
+            result_descriptor: source.plot_result_descriptor(),
+            source,
+            params: self.params,
+        }
+        .boxed())
+    }
+
+    span_fn!(Histogram2D);
+}
+
+/// The initialization of `Histogram2D`
+pub struct InitializedHistogram2D {
+    name: CanonicOperatorName,
+    result_descriptor: PlotResultDescriptor,
+    source: InitializedAttributeSource,
+    params: Histogram2DParams,
+}
+
+impl InitializedPlotOperator for InitializedHistogram2D {
+    fn query_processor(&self) -> Result<TypedPlotQueryProcessor> {
+        let processor = Histogram2DQueryProcessor {
+            input: self.source.query_processor()?,
+            params: self.params.clone(),
+        };
+
+        Ok(TypedPlotQueryProcessor::JsonVega(processor.boxed()))
+    }
+
+    fn result_descriptor(&self) -> &PlotResultDescriptor {
+        &self.result_descriptor
+    }
+
+    fn canonic_name(&self) -> CanonicOperatorName {
+        self.name.clone()
+    }
+}
+
+/// A query processor that calculates the 2D histogram of its raster bands or vector attributes.
+pub struct Histogram2DQueryProcessor {
+    input: AttributeSampleProcessor,
+    params: Histogram2DParams,
+}
+
+#[async_trait]
+impl PlotQueryProcessor for Histogram2DQueryProcessor {
+    type OutputFormat = PlotData;
+
+    fn plot_type(&self) -> &'static str {
+        HISTOGRAM2D_OPERATOR_NAME
+    }
+
+    async fn plot_query<'p>(
+        &'p self,
+        query: PlotQueryRectangle,
+        ctx: &'p dyn QueryContext,
+    ) -> Result<Self::OutputFormat> {
+        Ok(self
+            .histogram(query, ctx)
+            .await?
+            .to_vega_embeddable(false)?)
+    }
+
+    async fn plot_figure_query<'p>(
+        &'p self,
+        query: PlotQueryRectangle,
+        ctx: &'p dyn QueryContext,
+    ) -> Result<Option<Figure>> {
+        Ok(Some(self.histogram(query, ctx).await?.to_figure()))
+    }
+}
+
+impl Histogram2DQueryProcessor {
+    async fn histogram(
+        &self,
+        query: PlotQueryRectangle,
+        ctx: &dyn QueryContext,
+    ) -> Result<geoengine_datatypes::plots::Histogram2D> {
+        let mut x_statistics = AxisStatistics::default();
+        let mut y_statistics = AxisStatistics::default();
+
+        // compute the bounds and number of buckets in a first pass if they are not given
+        if self.params.x.requires_data() || self.params.y.requires_data() {
+            self.input
+                .for_each_sample(&query, ctx, |sample| {
+                    if let Some(value) = complete_sample(sample) {
+                        x_statistics.add(value.x);
+                        y_statistics.add(value.y);
+                    }
+                })
+                .await?;
+        }
+
+        let mut histogram = geoengine_datatypes::plots::Histogram2D::new(
+            self.params.x.dimension(&x_statistics)?,
+            self.params.y.dimension(&y_statistics)?,
+        );
+
+        self.input
+            .for_each_sample(&query, ctx, |sample| {
+                if let Some(value) = complete_sample(sample) {
+                    histogram.update(value);
+                }
+            })
+            .await?;
+
+        Ok(histogram)
+    }
+}
+
+/// Returns the sample as a coordinate if it has finite values for both attributes
+fn complete_sample(sample: &[Option<f64>]) -> Option<Coordinate2D> {
+    match sample {
+        [Some(x), Some(y)] if x.is_finite() && y.is_finite() => Some(Coordinate2D::new(*x, *y)),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct AxisStatistics {
+    count: usize,
+    min: f64,
+    max: f64,
+}
+
+impl Default for AxisStatistics {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            min: f64::MAX,
+            max: f64::MIN,
+        }
+    }
+}
+
+impl AxisStatistics {
+    fn add(&mut self, value: f64) {
+        self.count += 1;
+        self.min = f64::min(self.min, value);
+        self.max = f64::max(self.max, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{
+        ChunkByteSize, MockExecutionContext, MockQueryContext, RasterBandDescriptor,
+        RasterBandDescriptors, RasterOperator, RasterResultDescriptor, VectorOperator,
+    };
+    use crate::mock::{MockFeatureCollectionSource, MockRasterSource, MockRasterSourceParams};
+    use geoengine_datatypes::collections::DataCollection;
+    use geoengine_datatypes::primitives::{
+        BoundingBox2D, CacheHint, FeatureData, NoGeometry, PlotSeriesSelection, SpatialResolution,
+        TimeInterval,
+    };
+    use geoengine_datatypes::raster::{
+        Grid2D, GridOrEmpty2D, RasterDataType, RasterTile2D, TileInformation, TilingSpecification,
+    };
+    use geoengine_datatypes::spatial_reference::SpatialReference;
+    use geoengine_datatypes::util::test::TestDefault;
+
+    /// Returns the `(x, y, frequency)` values of the plot, sorted by `x` and `y`
+    async fn histogram_values(
+        operator: Histogram2D,
+        execution_context: &MockExecutionContext,
+    ) -> Vec<(f64, f64, u64)> {
+        let query_processor = operator
+            .boxed()
+            .initialize(WorkflowOperatorPath::initialize_root(), execution_context)
+            .await
+            .unwrap()
+            .query_processor()
+            .unwrap()
+            .json_vega()
+            .unwrap();
+
+        let result = query_processor
+            .plot_query(
+                PlotQueryRectangle {
+                    spatial_bounds: BoundingBox2D::new((0., -3.).into(), (2., 0.).into()).unwrap(),
+                    time_interval: TimeInterval::default(),
+                    spatial_resolution: SpatialResolution::one(),
+                    attributes: PlotSeriesSelection::all(),
+                },
+                &MockQueryContext::new(ChunkByteSize::MIN),
+            )
+            .await
+            .unwrap();
+
+        let vega: serde_json::Value = serde_json::from_str(&result.vega_string).unwrap();
+
+        let mut values = vega["data"]["values"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|value| {
+                (
+                    value["x"].as_f64().unwrap(),
+                    value["y"].as_f64().unwrap(),
+                    value["frequency"].as_u64().unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        values
+    }
+
+    #[test]
+    fn serialization() {
+        let histogram = Histogram2D {
+            params: Histogram2DParams {
+                x: Histogram2DAxis {
+                    attribute_name: "foo".to_string(),
+                    bounds: HistogramBounds::Values { min: 0., max: 8. },
+                    buckets: HistogramBuckets::Number { value: 4 },
+                },
+                y: Histogram2DAxis {
+                    attribute_name: "bar".to_string(),
+                    bounds: HistogramBounds::Data(Default::default()),
+                    buckets: HistogramBuckets::SquareRootChoiceRule {
+                        max_number_of_buckets: 20,
+                    },
+                },
+            },
+            sources: MockFeatureCollectionSource::<NoGeometry>::multiple(vec![])
+                .boxed()
+                .into(),
+        };
+
+        let serialized = serde_json::to_value(&histogram).unwrap();
+
+        assert_eq!(
+            serialized["params"],
+            serde_json::json!({
+                "x": {
+                    "attributeName": "foo",
+                    "bounds": { "min": 0.0, "max": 8.0 },
+                    "buckets": { "type": "number", "value": 4 },
+                },
+                "y": {
+                    "attributeName": "bar",
+                    "bounds": "data",
+                    "buckets": { "type": "squareRootChoiceRule", "maxNumberOfBuckets": 20 },
+                },
+            })
+        );
+
+        let deserialized: Histogram2D = serde_json::from_value(serialized).unwrap();
+        assert_eq!(deserialized.params, histogram.params);
+    }
+
+    #[tokio::test]
+    async fn raster_bands() {
+        let tile = |band: u32, data: GridOrEmpty2D<u8>| {
+            RasterTile2D::new_with_tile_info(
+                TimeInterval::default(),
+                TileInformation {
+                    global_geo_transform: TestDefault::test_default(),
+                    global_tile_position: [0, 0].into(),
+                    tile_size_in_pixels: [3, 2].into(),
+                },
+                band,
+                data,
+                CacheHint::default(),
+            )
+        };
+
+        let raster_source = MockRasterSource {
+            params: MockRasterSourceParams {
+                data: vec![
+                    tile(
+                        0,
+                        Grid2D::new([3, 2].into(), vec![1, 2, 3, 4, 5, 6])
+                            .unwrap()
+                            .into(),
+                    ),
+                    tile(
+                        1,
+                        Grid2D::new([3, 2].into(), vec![1, 1, 1, 2, 2, 9])
+                            .unwrap()
+                            .into(),
+                    ),
+                ],
+                result_descriptor: RasterResultDescriptor {
+                    data_type: RasterDataType::U8,
+                    spatial_reference: SpatialReference::epsg_4326().into(),
+                    time: None,
+                    bbox: None,
+                    resolution: None,
+                    bands: RasterBandDescriptors::new(vec![
+                        RasterBandDescriptor::new_unitless("red".into()),
+                        RasterBandDescriptor::new_unitless("nir".into()),
+                    ])
+                    .unwrap(),
+                },
+            },
+        }
+        .boxed();
+
+        let execution_context = MockExecutionContext::new_with_tiling_spec(TilingSpecification {
+            origin_coordinate: [0.0, 0.0].into(),
+            tile_size_in_pixels: [3, 2].into(),
+        });
+
+        let values = histogram_values(
+            Histogram2D {
+                params: Histogram2DParams {
+                    x: Histogram2DAxis {
+                        attribute_name: "red".to_string(),
+                        bounds: HistogramBounds::Values { min: 0., max: 6. },
+                        buckets: HistogramBuckets::Number { value: 2 },
+                    },
+                    y: Histogram2DAxis {
+                        attribute_name: "nir".to_string(),
+                        bounds: HistogramBounds::Values { min: 0., max: 4. },
+                        buckets: HistogramBuckets::Number { value: 2 },
+                    },
+                },
+                sources: raster_source.into(),
+            },
+            &execution_context,
+        )
+        .await;
+
+        // the last pixel is out of the bounds of the y axis
+        assert_eq!(values, vec![(1.5, 1., 2), (4.5, 1., 1), (4.5, 3., 2)]);
+    }
+
+    #[tokio::test]
+    async fn vector_attributes_with_data_bounds() {
+        let vector_source = MockFeatureCollectionSource::single(
+            DataCollection::from_slices(
+                &[] as &[NoGeometry],
+                &[TimeInterval::default(); 5],
+                &[
+                    ("foo", FeatureData::Int(vec![0, 1, 2, 3, 4])),
+                    (
+                        "bar",
+                        FeatureData::NullableFloat(vec![
+                            Some(0.),
+                            Some(10.),
+                            None,
+                            Some(20.),
+                            Some(40.),
+                        ]),
+                    ),
+                ],
+            )
+            .unwrap(),
+        )
+        .boxed();
+
+        let values = histogram_values(
+            Histogram2D {
+                params: Histogram2DParams {
+                    x: Histogram2DAxis {
+                        attribute_name: "foo".to_string(),
+                        bounds: HistogramBounds::Data(Default::default()),
+                        buckets: HistogramBuckets::SquareRootChoiceRule {
+                            max_number_of_buckets: 100,
+                        },
+                    },
+                    y: Histogram2DAxis {
+                        attribute_name: "bar".to_string(),
+                        bounds: HistogramBounds::Data(Default::default()),
+                        buckets: HistogramBuckets::Number { value: 4 },
+                    },
+                },
+                sources: vector_source.into(),
+            },
+            &MockExecutionContext::test_default(),
+        )
+        .await;
+
+        // four complete samples lead to two buckets on the x axis in [0, 4]
+        assert_eq!(
+            values,
+            vec![(1., 5., 1), (1., 15., 1), (3., 25., 1), (3., 35., 1)]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_checks_the_attributes() {
+        let vector_source = MockFeatureCollectionSource::single(
+            DataCollection::from_slices(
+                &[] as &[NoGeometry],
+                &[TimeInterval::default(); 1],
+                &[
+                    ("foo", FeatureData::Int(vec![1])),
+                    ("baz", FeatureData::Text(vec!["a".into()])),
+                ],
+            )
+            .unwrap(),
+        )
+        .boxed();
+
+        let axis = |attribute_name: &str| Histogram2DAxis {
+            attribute_name: attribute_name.to_string(),
+            bounds: HistogramBounds::Data(Default::default()),
+            buckets: HistogramBuckets::Number { value: 2 },
+        };
+
+        let result = Histogram2D {
+            params: Histogram2DParams {
+                x: axis("foo"),
+                y: axis("baz"),
+            },
+            sources: vector_source.into(),
+        }
+        .boxed()
+        .initialize(
+            WorkflowOperatorPath::initialize_root(),
+            &MockExecutionContext::test_default(),
+        )
+        .await;
+
+        assert!(matches!(result, Err(Error::InvalidOperatorSpec { .. })));
+    }
+}
@@ -1,6 +1,11 @@
+mod attribute_samples;
 mod box_plot;
 mod class_histogram;
+mod confusion_matrix;
+mod correlation_matrix;
+mod group_by;
 mod histogram;
+mod histogram2d;
 mod pie_chart;
 mod scatter_plot;
 mod statistics;
@@ -11,17 +16,30 @@ pub use self::class_histogram::{
     ClassHistogram, ClassHistogramParams, ClassHistogramRasterQueryProcessor,
     ClassHistogramVectorQueryProcessor, InitializedClassHistogram,
 };
+pub use self::confusion_matrix::{
+    ConfusionMatrix, ConfusionMatrixJsonQueryProcessor, ConfusionMatrixOutputFormat,
+    ConfusionMatrixParams, ConfusionMatrixQueryProcessor, ConfusionMatrixSources,
+    ConfusionMatrixVegaQueryProcessor, InitializedConfusionMatrix,
+};
+pub use self::correlation_matrix::{
+    CorrelationMatrix, CorrelationMatrixParams, CorrelationMatrixQueryProcessor,
+    InitializedCorrelationMatrix,
+};
 pub use self::histogram::{
     Histogram, HistogramBounds, HistogramBuckets, HistogramParams, HistogramRasterQueryProcessor,
     HistogramVectorQueryProcessor, InitializedHistogram,
 };
+pub use self::histogram2d::{
+    Histogram2D, Histogram2DAxis, Histogram2DParams, Histogram2DQueryProcessor,
+    InitializedHistogram2D,
+};
 pub use self::pie_chart::{
     CountPieChartVectorQueryProcessor, InitializedCountPieChart, PieChart, PieChartError,
     PieChartParams,
 };
 pub use self::statistics::{
-    InitializedStatistics, Statistics, StatisticsParams, StatisticsRasterQueryProcessor,
-    StatisticsVectorQueryProcessor,
+    InitializedStatistics, PercentileEstimation, Statistics, StatisticsParams,
+    StatisticsRasterQueryProcessor, StatisticsVectorQueryProcessor,
 };
 pub use self::temporal_raster_mean_plot::{
     InitializedMeanRasterPixelValuesOverTime, MeanRasterPixelValuesOverTime,
@@ -10,7 +10,7 @@ use crate::util::Result;
 use async_trait::async_trait;
 use futures::StreamExt;
 use geoengine_datatypes::collections::FeatureCollectionInfos;
-use geoengine_datatypes::plots::{Plot, PlotData};
+use geoengine_datatypes::plots::{Figure, Plot, PlotData};
 use geoengine_datatypes::primitives::{FeatureDataRef, Measurement, PlotQueryRectangle};
 use serde::{Deserialize, Serialize};
 use snafu::Snafu;
@@ -178,7 +178,15 @@ impl PlotQueryProcessor for CountPieChartVectorQueryProcessor {
         query: PlotQueryRectangle,
         ctx: &'p dyn QueryContext,
     ) -> Result<Self::OutputFormat> {
-        self.process(query, ctx).await
+        Ok(self.process(query, ctx).await?.to_vega_embeddable(false)?)
+    }
+
+    async fn plot_figure_query<'p>(
+        &'p self,
+        query: PlotQueryRectangle,
+        ctx: &'p dyn QueryContext,
+    ) -> Result<Option<Figure>> {
+        Ok(Some(self.process(query, ctx).await?.to_figure()))
     }
 }
 
@@ -223,7 +231,7 @@ impl CountPieChartVectorQueryProcessor {
         &'p self,
         query: PlotQueryRectangle,
         ctx: &'p dyn QueryContext,
-    ) -> Result<<CountPieChartVectorQueryProcessor as PlotQueryProcessor>::OutputFormat> {
+    ) -> Result<geoengine_datatypes::plots::PieChart> {
         let mut slices: HashMap<String, f64> = HashMap::new();
 
         // TODO: parallelize
@@ -254,12 +262,11 @@ impl CountPieChartVectorQueryProcessor {
 
         // TODO: display NO-DATA count?
 
-        let bar_chart = geoengine_datatypes::plots::PieChart::new(
+        let chart = geoengine_datatypes::plots::PieChart::new(
             slices.into_iter().collect(),
             self.column_label.clone(),
             self.donut,
         )?;
-        let chart = bar_chart.to_vega_embeddable(false)?;
 
         Ok(chart)
     }
@@ -3,7 +3,7 @@ use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 
 use geoengine_datatypes::collections::FeatureCollectionInfos;
-use geoengine_datatypes::plots::{Histogram2D, HistogramDimension, Plot, PlotData};
+use geoengine_datatypes::plots::{Figure, Histogram2D, HistogramDimension, Plot, PlotData};
 
 use crate::engine::{
     CanonicOperatorName, ExecutionContext, InitializedPlotOperator, InitializedSources,
@@ -151,6 +151,24 @@ impl PlotQueryProcessor for ScatterPlotQueryProcessor {
         query: PlotQueryRectangle,
         ctx: &'p dyn QueryContext,
     ) -> Result<Self::OutputFormat> {
+        Ok(self.plot(query, ctx).await?.to_vega_embeddable(false)?)
+    }
+
+    async fn plot_figure_query<'p>(
+        &'p self,
+        query: PlotQueryRectangle,
+        ctx: &'p dyn QueryContext,
+    ) -> Result<Option<Figure>> {
+        Ok(Some(self.plot(query, ctx).await?.to_figure()))
+    }
+}
+
+impl ScatterPlotQueryProcessor {
+    async fn plot(
+        &self,
+        query: PlotQueryRectangle,
+        ctx: &dyn QueryContext,
+    ) -> Result<Box<dyn Plot>> {
         let mut collector =
             CollectorKind::Values(Collector::new(self.column_x.clone(), self.column_y.clone()));
 
@@ -172,7 +190,7 @@ impl PlotQueryProcessor for ScatterPlotQueryProcessor {
                 }
             }
         });
-        Ok(collector.into_plot()?.to_vega_embeddable(false)?)
+        collector.into_plot()
     }
 }
 
@@ -7,17 +7,20 @@ use crate::engine::{
 };
 use crate::error;
 use crate::error::Error;
+use crate::plot::group_by::{
+    ensure_group_by_column, feature_groups, for_each_zoned_pixel, zone_name,
+};
 use crate::util::Result;
 use crate::util::input::MultiRasterOrVectorOperator;
 use crate::util::number_statistics::NumberStatistics;
-use crate::util::statistics::{SafePSquareQuantileEstimator, StatisticsError};
+use crate::util::statistics::{KllSketch, SafePSquareQuantileEstimator, StatisticsError};
 use async_trait::async_trait;
 use futures::stream::select_all;
 use futures::{FutureExt, StreamExt, TryFutureExt, TryStreamExt};
 use geoengine_datatypes::collections::FeatureCollectionInfos;
 use geoengine_datatypes::primitives::{
-    AxisAlignedRectangle, BandSelection, BoundingBox2D, PlotQueryRectangle, RasterQueryRectangle,
-    partitions_extent, time_interval_extent,
+    AxisAlignedRectangle, BandSelection, BoundingBox2D, Measurement, PlotQueryRectangle,
+    RasterQueryRectangle, partitions_extent, time_interval_extent,
 };
 use geoengine_datatypes::raster::ConvertDataTypeParallel;
 use geoengine_datatypes::raster::{GridOrEmpty, GridSize};
@@ -26,7 +29,7 @@ use num_traits::AsPrimitive;
 use ordered_float::NotNan;
 use serde::{Deserialize, Serialize};
 use snafu::ensure;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 pub const STATISTICS_OPERATOR_NAME: &str = "Statistics";
 
@@ -34,6 +37,9 @@ pub const STATISTICS_OPERATOR_NAME: &str = "Statistics";
 ///
 /// Does currently not use a weighted computations, so it assumes equally weighted
 /// time steps in the sources.
+///
+/// If `group_by` is set, the statistics are computed per group and output as
+/// `{ "<group>": { "<column>": <statistics> } }`.
 pub type Statistics = Operator<StatisticsParams, MultipleRasterOrSingleVectorSource>;
 
 impl OperatorName for Statistics {
@@ -49,6 +55,33 @@ pub struct StatisticsParams {
     pub column_names: Vec<String>,
     #[serde(default)]
     pub percentiles: Vec<NotNan<f64>>,
+    /// How to estimate the `percentiles`.
+    #[serde(default)]
+    pub percentile_estimation: PercentileEstimation,
+    /// Name of a column (category, text or integer) to compute the statistics per group.
+    /// For raster data, it names one of the inputs ('column_names' parameter), which is then used as a zone raster.
+    #[serde(default)]
+    pub group_by: Option<String>,
+}
+
+/// The estimation method for the percentiles of `Statistics`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum PercentileEstimation {
+    /// Estimates each percentile separately with the P^2 algorithm, which stores only five markers
+    #[default]
+    PSquare,
+    /// Computes all percentiles from a single KLL sketch. It is exact for small inputs and has
+    /// a rank error in the order of `1/k` for huge inputs, while storing about `3k` values.
+    #[serde(rename_all = "camelCase")]
+    Kll {
+        #[serde(default = "default_kll_accuracy")]
+        k: u16,
+    },
+}
+
+fn default_kll_accuracy() -> u16 {
+    200
 }
 
 #[typetag::serde]
@@ -62,12 +95,27 @@ impl PlotOperator for Statistics {
     ) -> Result<Box<dyn InitializedPlotOperator>> {
         let name = CanonicOperatorName::from(&self);
 
-        ensure!(
-            self.params.percentiles.len() <= 8,
-            error::InvalidOperatorSpec {
-                reason: "Only up to 8 percentiles can be computed at the same time.".to_string(),
+        match self.params.percentile_estimation {
+            PercentileEstimation::PSquare => ensure!(
+                self.params.percentiles.len() <= 8,
+                error::InvalidOperatorSpec {
+                    reason: "Only up to 8 percentiles can be computed at the same time."
+                        .to_string(),
+                }
+            ),
+            PercentileEstimation::Kll { k } => {
+                KllSketch::new(k.into())?;
+                ensure!(
+                    self.params
+                        .percentiles
+                        .iter()
+                        .all(|p| (0.0..=1.0).contains(&p.into_inner())),
+                    error::InvalidOperatorSpec {
+                        reason: "Percentiles must be in the interval [0,1].".to_string(),
+                    }
+                );
             }
-        );
+        }
 
         match self.sources.source {
             MultiRasterOrVectorOperator::Raster(rasters) => {
@@ -85,6 +133,17 @@ impl PlotOperator for Statistics {
                     self.params.column_names.clone()
                 };
 
+                if let Some(group_by) = &self.params.group_by {
+                    ensure!(
+                        rasters.len() > 1 && output_names.contains(group_by),
+                        error::InvalidOperatorSpec {
+                            reason: format!(
+                                "Grouped statistics on raster data require 'group_by' to name one of at least two inputs, found '{group_by}'."
+                            ),
+                        }
+                    );
+                }
+
                 let rasters = futures::future::try_join_all(
                     rasters
                         .into_iter()
@@ -134,6 +193,8 @@ impl PlotOperator for Statistics {
                         .iter()
                         .map(|p| p.into_inner())
                         .collect(),
+                    self.params.percentile_estimation,
+                    self.params.group_by,
                     rasters,
                 );
 
@@ -146,12 +207,19 @@ impl PlotOperator for Statistics {
 
                 let in_descriptor = initialized_vector.result_descriptor();
 
+                if let Some(group_by) = &self.params.group_by {
+                    ensure_group_by_column(in_descriptor, group_by)?;
+                }
+
                 let column_names = if self.params.column_names.is_empty() {
                     in_descriptor
                         .columns
                         .clone()
                         .into_iter()
-                        .filter(|(_, info)| info.data_type.is_numeric())
+                        .filter(|(name, info)| {
+                            info.data_type.is_numeric()
+                                && self.params.group_by.as_ref() != Some(name)
+                        })
                         .map(|(name, _)| name)
                         .collect()
                 } else {
@@ -186,6 +254,8 @@ impl PlotOperator for Statistics {
                         .iter()
                         .map(|p| p.into_inner())
                         .collect(),
+                    self.params.percentile_estimation,
+                    self.params.group_by,
                     initialized_vector,
                 );
 
@@ -203,6 +273,8 @@ pub struct InitializedStatistics<Op> {
     result_descriptor: PlotResultDescriptor,
     column_names: Vec<String>,
     percentiles: Vec<f64>,
+    percentile_estimation: PercentileEstimation,
+    group_by: Option<String>,
     source: Op,
 }
 
@@ -212,6 +284,8 @@ impl<Op> InitializedStatistics<Op> {
         result_descriptor: PlotResultDescriptor,
         column_names: Vec<String>,
         percentiles: Vec<f64>,
+        percentile_estimation: PercentileEstimation,
+        group_by: Option<String>,
         source: Op,
     ) -> Self {
         Self {
@@ -219,6 +293,8 @@ impl<Op> InitializedStatistics<Op> {
             result_descriptor,
             column_names,
             percentiles,
+            percentile_estimation,
+            group_by,
             source,
         }
     }
@@ -235,6 +311,8 @@ impl InitializedPlotOperator for InitializedStatistics<Box<dyn InitializedVector
                 vector: self.source.query_processor()?,
                 column_names: self.column_names.clone(),
                 percentiles: self.percentiles.clone(),
+                percentile_estimation: self.percentile_estimation,
+                group_by: self.group_by.clone(),
             }
             .boxed(),
         ))
@@ -251,6 +329,18 @@ impl InitializedPlotOperator for InitializedStatistics<Vec<Box<dyn InitializedRa
     }
 
     fn query_processor(&self) -> Result<TypedPlotQueryProcessor> {
+        let zones = self.group_by.as_ref().map(|group_by| {
+            let zone_idx = self
+                .column_names
+                .iter()
+                .position(|name| name == group_by)
+                .expect("zone raster was checked during initialization");
+            let zone_measurement = self.source[zone_idx].result_descriptor().bands[0]
+                .measurement
+                .clone();
+            (zone_idx, zone_measurement)
+        });
+
         Ok(TypedPlotQueryProcessor::JsonPlain(
             StatisticsRasterQueryProcessor {
                 rasters: self
@@ -260,6 +350,8 @@ impl InitializedPlotOperator for InitializedStatistics<Vec<Box<dyn InitializedRa
                     .collect::<Result<Vec<_>>>()?,
                 column_names: self.column_names.clone(),
                 percentiles: self.percentiles.clone(),
+                percentile_estimation: self.percentile_estimation,
+                zones,
             }
             .boxed(),
         ))
@@ -275,6 +367,8 @@ pub struct StatisticsVectorQueryProcessor {
     vector: TypedVectorQueryProcessor,
     column_names: Vec<String>,
     percentiles: Vec<f64>,
+    percentile_estimation: PercentileEstimation,
+    group_by: Option<String>,
 }
 
 #[async_trait]
@@ -290,13 +384,20 @@ impl PlotQueryProcessor for StatisticsVectorQueryProcessor {
         query: PlotQueryRectangle,
         ctx: &'a dyn QueryContext,
     ) -> Result<Self::OutputFormat> {
+        if let Some(group_by) = &self.group_by {
+            return self.grouped_statistics(group_by, query, ctx).await;
+        }
+
         let mut statistics: HashMap<String, StatisticsAggregator<f64>> = self
             .column_names
             .iter()
             .map(|column| {
                 (
                     column.clone(),
-                    StatisticsAggregator::with_percentiles(&self.percentiles),
+                    StatisticsAggregator::with_percentiles(
+                        &self.percentiles,
+                        self.percentile_estimation,
+                    ),
                 )
             })
             .collect();
@@ -332,11 +433,133 @@ impl PlotQueryProcessor for StatisticsVectorQueryProcessor {
     }
 }
 
+impl StatisticsVectorQueryProcessor {
+    /// Computes the statistics of each column for each group of the `group_by` column
+    async fn grouped_statistics(
+        &self,
+        group_by: &str,
+        query: PlotQueryRectangle,
+        ctx: &dyn QueryContext,
+    ) -> Result<serde_json::Value> {
+        let mut statistics: BTreeMap<String, Vec<StatisticsAggregator<f64>>> = BTreeMap::new();
+
+        call_on_generic_vector_processor!(&self.vector, processor => {
+            let mut query = processor.query(query.into(), ctx).await?;
+
+            while let Some(collection) = query.next().await {
+                let collection = collection?;
+
+                let groups = feature_groups(&collection.data(group_by)?);
+
+                for (column_idx, column) in self.column_names.iter().enumerate() {
+                    let data = collection.data(column)?;
+
+                    for (value, group) in data.float_options_iter().zip(&groups) {
+                        // features without a group are ignored
+                        let Some(group) = group else {
+                            continue;
+                        };
+
+                        if !statistics.contains_key(group) {
+                            statistics.insert(
+                                group.clone(),
+                                vec![StatisticsAggregator::with_percentiles(&self.percentiles, self.percentile_estimation); self.column_names.len()],
+                            );
+                        }
+                        let stats = &mut statistics.get_mut(group).expect("inserted above")[column_idx];
+
+                        match value {
+                            Some(v) => stats.add(v)?,
+                            None => stats.add_no_data(),
+                        }
+                    }
+                }
+            }
+        });
+
+        let output: BTreeMap<String, HashMap<String, StatisticsOutput>> = statistics
+            .iter()
+            .map(|(group, group_statistics)| {
+                (
+                    group.clone(),
+                    self.column_names
+                        .iter()
+                        .cloned()
+                        .zip(group_statistics.iter().map(StatisticsOutput::from))
+                        .collect(),
+                )
+            })
+            .collect();
+        serde_json::to_value(output).map_err(Into::into)
+    }
+}
+
 /// A query processor that calculates the statistics about its raster inputs.
 pub struct StatisticsRasterQueryProcessor {
     rasters: Vec<TypedRasterQueryProcessor>,
     column_names: Vec<String>,
     percentiles: Vec<f64>,
+    percentile_estimation: PercentileEstimation,
+    /// The index and the measurement of the zone raster if the statistics are grouped
+    zones: Option<(usize, Measurement)>,
+}
+
+impl StatisticsRasterQueryProcessor {
+    /// Computes the statistics of all rasters except the zone raster for each zone
+    async fn grouped_statistics(
+        &self,
+        zone_idx: usize,
+        zone_measurement: &Measurement,
+        query: &PlotQueryRectangle,
+        ctx: &dyn QueryContext,
+    ) -> Result<serde_json::Value> {
+        let zones = &self.rasters[zone_idx];
+        let mut statistics: BTreeMap<i64, Vec<StatisticsAggregator<f64>>> = BTreeMap::new();
+
+        for (i, raster) in self.rasters.iter().enumerate() {
+            if i == zone_idx {
+                continue;
+            }
+
+            for_each_zoned_pixel(raster, zones, query, ctx, |zone, value| {
+                let stats = &mut statistics.entry(zone).or_insert_with(|| {
+                    vec![
+                        StatisticsAggregator::with_percentiles(
+                            &self.percentiles,
+                            self.percentile_estimation
+                        );
+                        self.rasters.len()
+                    ]
+                })[i];
+
+                match value {
+                    Some(v) => stats.add(v)?,
+                    None => stats.add_no_data(),
+                }
+
+                Ok(())
+            })
+            .await?;
+        }
+
+        let output: BTreeMap<String, HashMap<String, StatisticsOutput>> = statistics
+            .iter()
+            .map(|(zone, zone_statistics)| {
+                (
+                    zone_name(*zone, zone_measurement),
+                    zone_statistics
+                        .iter()
+                        .enumerate()
+                        .filter(|(i, _)| *i != zone_idx)
+                        .map(|(i, stats)| {
+                            (self.column_names[i].clone(), StatisticsOutput::from(stats))
+                        })
+                        .collect(),
+                )
+            })
+            .collect();
+        serde_json::to_value(output).map_err(Into::into)
+    }
 }
 
 #[async_trait]
@@ -352,6 +575,12 @@ impl PlotQueryProcessor for StatisticsRasterQueryProcessor {
         query: PlotQueryRectangle,
         ctx: &'a dyn QueryContext,
     ) -> Result<Self::OutputFormat> {
+        if let Some((zone_idx, zone_measurement)) = &self.zones {
+            return self
+                .grouped_statistics(*zone_idx, zone_measurement, &query, ctx)
+                .await;
+        }
+
         let mut queries = Vec::with_capacity(self.rasters.len());
         let q: RasterQueryRectangle =
             RasterQueryRectangle::from_qrect_and_bands(&query, BandSelection::first());
@@ -365,8 +594,13 @@ impl PlotQueryProcessor for StatisticsRasterQueryProcessor {
             );
         }
 
-        let statistics =
-            vec![StatisticsAggregator::with_percentiles(&self.percentiles); self.rasters.len()];
+        let statistics = vec![
+            StatisticsAggregator::with_percentiles(
+                &self.percentiles,
+                self.percentile_estimation
+            );
+            self.rasters.len()
+        ];
 
         select_all(queries)
             .try_fold(
@@ -420,30 +654,36 @@ where
     Ok(())
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Clone)]
 struct StatisticsAggregator<T: AsPrimitive<f64>> {
     number_statistics: NumberStatistics,
-    percentile_estimators: Vec<PercentileEstimator<T>>,
+    percentiles: PercentileAggregator<T>,
 }
 
 impl<T: AsPrimitive<f64>> StatisticsAggregator<T> {
-    fn with_percentiles(percentiles: &[f64]) -> Self {
+    fn with_percentiles(percentiles: &[f64], estimation: PercentileEstimation) -> Self {
+        let percentiles = match estimation {
+            PercentileEstimation::PSquare => PercentileAggregator::PSquare(
+                percentiles
+                    .iter()
+                    .map(|p| PercentileEstimator::new(*p))
+                    .collect(),
+            ),
+            PercentileEstimation::Kll { k } => PercentileAggregator::Kll {
+                percentiles: percentiles.to_vec(),
+                sketch: KllSketch::new(k.into()).expect("checked during initialization"),
+            },
+        };
+
         Self {
             number_statistics: NumberStatistics::default(),
-            percentile_estimators: percentiles
-                .iter()
-                .map(|p| PercentileEstimator::new(*p))
-                .collect(),
+            percentiles,
         }
     }
 
     fn add(&mut self, value: T) -> Result<(), StatisticsError> {
         self.number_statistics.add(value);
-        for estimator in &mut self.percentile_estimators {
-            estimator.update(value)?;
-        }
-
-        Ok(())
+        self.percentiles.update(value)
     }
 
     fn add_no_data(&mut self) {
@@ -455,6 +695,53 @@ impl<T: AsPrimitive<f64>> StatisticsAggregator<T> {
     }
 }
 
+/// Estimates the percentiles either separately or from a common sketch
+#[derive(Debug, Clone)]
+enum PercentileAggregator<T: AsPrimitive<f64>> {
+    PSquare(Vec<PercentileEstimator<T>>),
+    Kll {
+        percentiles: Vec<f64>,
+        sketch: KllSketch,
+    },
+}
+
+impl<T: AsPrimitive<f64>> PercentileAggregator<T> {
+    fn update(&mut self, value: T) -> Result<(), StatisticsError> {
+        match self {
+            Self::PSquare(estimators) => {
+                for estimator in estimators {
+                    estimator.update(value)?;
+                }
+            }
+            Self::Kll { sketch, .. } => sketch.update(value.as_()),
+        }
+
+        Ok(())
+    }
+
+    fn outputs(&self) -> Vec<PercentileOutput> {
+        match self {
+            Self::PSquare(estimators) => estimators
+                .iter()
+                .map(|estimator| PercentileOutput {
+                    percentile: estimator.percentile_arg(),
+                    value: estimator.percentile_estimate().unwrap_or(f64::NAN),
+                })
+                .collect(),
+            Self::Kll {
+                percentiles,
+                sketch,
+            } => percentiles
+                .iter()
+                .map(|&percentile| PercentileOutput {
+                    percentile,
+                    value: sketch.quantile_estimate(percentile).unwrap_or(f64::NAN),
+                })
+                .collect(),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 enum PercentileEstimator<T: AsPrimitive<f64>> {
     Unitialized(f64),
@@ -525,14 +812,7 @@ impl From<&StatisticsAggregator<f64>> for StatisticsOutput {
             max: number_statistics.max(),
             mean: number_statistics.mean(),
             stddev: number_statistics.std_dev(),
-            percentiles: statistics
-                .percentile_estimators
-                .iter()
-                .map(|estimator| PercentileOutput {
-                    percentile: estimator.percentile_arg(),
-                    value: estimator.percentile_estimate().unwrap_or(f64::NAN),
-                })
-                .collect(),
+            percentiles: statistics.percentiles.outputs(),
         }
     }
 }
@@ -549,7 +829,7 @@ mod tests {
         ChunkByteSize, MockExecutionContext, MockQueryContext, RasterOperator,
         RasterResultDescriptor,
     };
-    use crate::engine::{RasterBandDescriptors, VectorOperator};
+    use crate::engine::{RasterBandDescriptor, RasterBandDescriptors, VectorOperator};
     use crate::mock::{MockFeatureCollectionSource, MockRasterSource, MockRasterSourceParams};
     use crate::util::input::MultiRasterOrVectorOperator::Raster;
     use geoengine_datatypes::primitives::{
@@ -566,6 +846,8 @@ mod tests {
             params: StatisticsParams {
                 column_names: vec![],
                 percentiles: vec![],
+                percentile_estimation: PercentileEstimation::PSquare,
+                group_by: None,
             },
             sources: MultipleRasterOrSingleVectorSource {
                 source: Raster(vec![]),
@@ -598,6 +880,8 @@ mod tests {
             params: StatisticsParams {
                 column_names: vec![],
                 percentiles: vec![],
+                percentile_estimation: PercentileEstimation::PSquare,
+                group_by: None,
             },
             sources: vec![].into(),
         };
@@ -668,6 +952,8 @@ mod tests {
             params: StatisticsParams {
                 column_names: vec![],
                 percentiles: vec![],
+                percentile_estimation: PercentileEstimation::PSquare,
+                group_by: None,
             },
             sources: vec![raster_source].into(),
         };
@@ -781,6 +1067,8 @@ mod tests {
             params: StatisticsParams {
                 column_names: vec![],
                 percentiles: vec![],
+                percentile_estimation: PercentileEstimation::PSquare,
+                group_by: None,
             },
             sources: raster_source.into(),
         };
@@ -902,6 +1190,8 @@ mod tests {
             params: StatisticsParams {
                 column_names: vec!["A".to_string(), "B".to_string()],
                 percentiles: vec![],
+                percentile_estimation: PercentileEstimation::PSquare,
+                group_by: None,
             },
             sources: raster_source.into(),
         };
@@ -1022,6 +1312,8 @@ mod tests {
             params: StatisticsParams {
                 column_names: vec!["A".to_string()],
                 percentiles: vec![],
+                percentile_estimation: PercentileEstimation::PSquare,
+                group_by: None,
             },
             sources: raster_source.into(),
         };
@@ -1085,6 +1377,8 @@ mod tests {
             params: StatisticsParams {
                 column_names: vec![],
                 percentiles: vec![],
+                percentile_estimation: PercentileEstimation::PSquare,
+                group_by: None,
             },
             sources: vector_source.into(),
         };
@@ -1185,6 +1479,8 @@ mod tests {
             params: StatisticsParams {
                 column_names: vec!["foo".to_string()],
                 percentiles: vec![],
+                percentile_estimation: PercentileEstimation::PSquare,
+                group_by: None,
             },
             sources: vector_source.into(),
         };
@@ -1277,6 +1573,8 @@ mod tests {
             params: StatisticsParams {
                 column_names: vec!["foo".to_string(), "bar".to_string()],
                 percentiles: vec![],
+                percentile_estimation: PercentileEstimation::PSquare,
+                group_by: None,
             },
             sources: vector_source.into(),
         };
@@ -1369,6 +1667,8 @@ mod tests {
             params: StatisticsParams {
                 column_names: vec![],
                 percentiles: vec![NotNan::new(0.25).unwrap(), NotNan::new(0.75).unwrap()],
+                percentile_estimation: PercentileEstimation::PSquare,
+                group_by: None,
             },
             sources: vec![raster_source].into(),
         };
@@ -1417,6 +1717,115 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn raster_percentile_sketch() {
+        let tile_size_in_pixels = [3, 2].into();
+        let tiling_specification = TilingSpecification {
+            origin_coordinate: [0.0, 0.0].into(),
+            tile_size_in_pixels,
+        };
+
+        let raster_source = MockRasterSource {
+            params: MockRasterSourceParams {
+                data: vec![RasterTile2D::new_with_tile_info(
+                    TimeInterval::default(),
+                    TileInformation {
+                        global_geo_transform: TestDefault::test_default(),
+                        global_tile_position: [0, 0].into(),
+                        tile_size_in_pixels,
+                    },
+                    0,
+                    Grid2D::new([3, 2].into(), vec![1, 2, 3, 4, 5, 6])
+                        .unwrap()
+                        .into(),
+                    CacheHint::default(),
+                )],
+                result_descriptor: RasterResultDescriptor {
+                    data_type: RasterDataType::U8,
+                    spatial_reference: SpatialReference::epsg_4326().into(),
+                    time: None,
+                    bbox: None,
+                    resolution: None,
+                    bands: RasterBandDescriptors::new_single_band(),
+                },
+            },
+        }
+        .boxed();
+
+        let statistics = Statistics {
+            params: StatisticsParams {
+                column_names: vec![],
+                percentiles: vec![NotNan::new(0.25).unwrap(), NotNan::new(0.75).unwrap()],
+                percentile_estimation: PercentileEstimation::Kll { k: 200 },
+                group_by: None,
+            },
+            sources: vec![raster_source].into(),
+        };
+
+        let execution_context = MockExecutionContext::new_with_tiling_spec(tiling_specification);
+
+        let statistics = statistics
+            .boxed()
+            .initialize(WorkflowOperatorPath::initialize_root(), &execution_context)
+            .await
+            .unwrap();
+
+        let processor = statistics.query_processor().unwrap().json_plain().unwrap();
+
+        let result = processor
+            .plot_query(
+                PlotQueryRectangle {
+                    spatial_bounds: BoundingBox2D::new((-180., -90.).into(), (180., 90.).into())
+                        .unwrap(),
+                    time_interval: TimeInterval::default(),
+                    spatial_resolution: SpatialResolution::one(),
+                    attributes: PlotSeriesSelection::all(),
+                },
+                &MockQueryContext::new(ChunkByteSize::MIN),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result.to_string(),
+            json!({
+                "Raster-1": {
+                    "valueCount": 66_246, // 362*183 Note: this is caused by the inclusive nature of the bounding box. Since the right and lower bounds are included this wraps to a new row/column of tiles. In this test the tiles are 3x2 pixels in size.
+                    "validCount": 6,
+                    "min": 1.0,
+                    "max": 6.0,
+                    "mean": 3.5,
+                    "stddev": 1.707_825_127_659_933,
+                    "percentiles": [
+                        {"percentile": 0.25, "value": 2.0},
+                        {"percentile": 0.75, "value": 5.0},
+                    ],
+                }
+            })
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn percentile_estimation_serialization() {
+        let params: StatisticsParams = serde_json::from_value(json!({
+            "percentiles": [0.5],
+            "percentileEstimation": {
+                "type": "kll",
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            params.percentile_estimation,
+            PercentileEstimation::Kll { k: 200 }
+        );
+
+        let params: StatisticsParams = serde_json::from_value(json!({})).unwrap();
+
+        assert_eq!(params.percentile_estimation, PercentileEstimation::PSquare);
+    }
+
     #[tokio::test]
     async fn vector_percentiles() {
         let tile_size_in_pixels = [3, 2].into();
@@ -1464,6 +1873,8 @@ mod tests {
             params: StatisticsParams {
                 column_names: vec!["foo".to_string()],
                 percentiles: vec![NotNan::new(0.25).unwrap(), NotNan::new(0.75).unwrap()],
+                percentile_estimation: PercentileEstimation::PSquare,
+                group_by: None,
             },
             sources: vector_source.into(),
         };
@@ -1511,4 +1922,266 @@ mod tests {
             .to_string()
         );
     }
+
+    #[tokio::test]
+    async fn vector_grouped_by_text_column() {
+        let vector_source = MockFeatureCollectionSource::multiple(vec![
+            DataCollection::from_slices(
+                &[] as &[NoGeometry],
+                &[TimeInterval::default(); 6],
+                &[
+                    (
+                        "foo",
+                        FeatureData::NullableFloat(vec![
+                            Some(1.0),
+                            Some(2.0),
+                            Some(3.0),
+                            Some(4.0),
+                            None,
+                            Some(6.0),
+                        ]),
+                    ),
+                    (
+                        "species",
+                        FeatureData::NullableText(vec![
+                            Some("a".to_string()),
+                            Some("b".to_string()),
+                            Some("a".to_string()),
+                            Some("b".to_string()),
+                            Some("b".to_string()),
+                            None,
+                        ]),
+                    ),
+                ],
+            )
+            .unwrap(),
+        ])
+        .boxed();
+
+        let statistics = Statistics {
+            params: StatisticsParams {
+                column_names: vec![],
+                percentiles: vec![],
+                percentile_estimation: PercentileEstimation::PSquare,
+                group_by: Some("species".to_string()),
+            },
+            sources: vector_source.into(),
+        };
+
+        let execution_context = MockExecutionContext::test_default();
+
+        let statistics = statistics
+            .boxed()
+            .initialize(WorkflowOperatorPath::initialize_root(), &execution_context)
+            .await
+            .unwrap();
+
+        let processor = statistics.query_processor().unwrap().json_plain().unwrap();
+
+        let result = processor
+            .plot_query(
+                PlotQueryRectangle {
+                    spatial_bounds: BoundingBox2D::new((-180., -90.).into(), (180., 90.).into())
+                        .unwrap(),
+                    time_interval: TimeInterval::default(),
+                    spatial_resolution: SpatialResolution::one(),
+                    attributes: PlotSeriesSelection::all(),
+                },
+                &MockQueryContext::new(ChunkByteSize::MIN),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result,
+            json!({
+                "a": {
+                    "foo": {
+                        "valueCount": 2,
+                        "validCount": 2,
+                        "min": 1.0,
+                        "max": 3.0,
+                        "mean": 2.0,
+                        "stddev": 1.0,
+                        "percentiles": [],
+                    },
+                },
+                "b": {
+                    "foo": {
+                        "valueCount": 3,
+                        "validCount": 2,
+                        "min": 2.0,
+                        "max": 4.0,
+                        "mean": 3.0,
+                        "stddev": 1.0,
+                        "percentiles": [],
+                    },
+                },
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn it_checks_the_group_by_column() {
+        let execution_context = MockExecutionContext::test_default();
+
+        for group_by in ["bar", "baz"] {
+            let vector_source = MockFeatureCollectionSource::multiple(vec![
+                DataCollection::from_slices(
+                    &[] as &[NoGeometry],
+                    &[TimeInterval::default(); 2],
+                    &[
+                        ("foo", FeatureData::Float(vec![1.0, 2.0])),
+                        ("bar", FeatureData::Float(vec![3.0, 4.0])),
+                    ],
+                )
+                .unwrap(),
+            ])
+            .boxed();
+
+            let statistics = Statistics {
+                params: StatisticsParams {
+                    column_names: vec!["foo".to_string()],
+                    percentiles: vec![],
+                    percentile_estimation: PercentileEstimation::PSquare,
+                    group_by: Some(group_by.to_string()),
+                },
+                sources: vector_source.into(),
+            };
+
+            let result = statistics
+                .boxed()
+                .initialize(WorkflowOperatorPath::initialize_root(), &execution_context)
+                .await;
+
+            match group_by {
+                "bar" => assert!(matches!(result, Err(Error::InvalidOperatorSpec { .. }))),
+                _ => assert!(matches!(result, Err(Error::ColumnDoesNotExist { .. }))),
+            }
+        }
+    }
+
+    #[tokio::test]
+    #[allow(clippy::too_many_lines)]
+    async fn raster_grouped_by_zone_raster() {
+        let tile_size_in_pixels = [3, 2].into();
+        let tiling_specification = TilingSpecification {
+            origin_coordinate: [0.0, 0.0].into(),
+            tile_size_in_pixels,
+        };
+
+        let raster_source = |data: Vec<u8>, measurement: Measurement| {
+            MockRasterSource {
+                params: MockRasterSourceParams {
+                    data: vec![RasterTile2D::new_with_tile_info(
+                        TimeInterval::default(),
+                        TileInformation {
+                            global_geo_transform: TestDefault::test_default(),
+                            global_tile_position: [0, 0].into(),
+                            tile_size_in_pixels,
+                        },
+                        0,
+                        Grid2D::new([3, 2].into(), data).unwrap().into(),
+                        CacheHint::default(),
+                    )],
+                    result_descriptor: RasterResultDescriptor {
+                        data_type: RasterDataType::U8,
+                        spatial_reference: SpatialReference::epsg_4326().into(),
+                        time: None,
+                        bbox: None,
+                        resolution: None,
+                        bands: RasterBandDescriptors::new(vec![RasterBandDescriptor::new(
+                            "band".into(),
+                            measurement,
+                        )])
+                        .unwrap(),
+                    },
+                },
+            }
+            .boxed()
+        };
+
+        let statistics = Statistics {
+            params: StatisticsParams {
+                column_names: vec!["values".to_string(), "land cover".to_string()],
+                percentiles: vec![],
+                percentile_estimation: PercentileEstimation::PSquare,
+                group_by: Some("land cover".to_string()),
+            },
+            sources: vec![
+                raster_source(vec![1, 2, 3, 4, 5, 6], Measurement::Unitless),
+                raster_source(
+                    vec![1, 1, 2, 2, 0, 0],
+                    Measurement::classification(
+                        "land cover".to_string(),
+                        [(1, "forest".to_string()), (2, "water".to_string())].into(),
+                    ),
+                ),
+            ]
+            .into(),
+        };
+
+        let execution_context = MockExecutionContext::new_with_tiling_spec(tiling_specification);
+
+        let statistics = statistics
+            .boxed()
+            .initialize(WorkflowOperatorPath::initialize_root(), &execution_context)
+            .await
+            .unwrap();
+
+        let processor = statistics.query_processor().unwrap().json_plain().unwrap();
+
+        let result = processor
+            .plot_query(
+                PlotQueryRectangle {
+                    spatial_bounds: BoundingBox2D::new((-180., -90.).into(), (180., 90.).into())
+                        .unwrap(),
+                    time_interval: TimeInterval::default(),
+                    spatial_resolution: SpatialResolution::one(),
+                    attributes: PlotSeriesSelection::all(),
+                },
+                &MockQueryContext::new(ChunkByteSize::MIN),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result,
+            json!({
+                "0": {
+                    "values": {
+                        "valueCount": 2,
+                        "validCount": 2,
+                        "min": 5.0,
+                        "max": 6.0,
+                        "mean": 5.5,
+                        "stddev": 0.5,
+                        "percentiles": [],
+                    },
+                },
+                "forest": {
+                    "values": {
+                        "valueCount": 2,
+                        "validCount": 2,
+                        "min": 1.0,
+                        "max": 2.0,
+                        "mean": 1.5,
+                        "stddev": 0.5,
+                        "percentiles": [],
+                    },
+                },
+                "water": {
+                    "values": {
+                        "valueCount": 2,
+                        "validCount": 2,
+                        "min": 3.0,
+                        "max": 4.0,
+                        "mean": 3.5,
+                        "stddev": 0.5,
+                        "percentiles": [],
+                    },
+                },
+            })
+        );
+    }
 }
@@ -9,7 +9,7 @@ use crate::util::math::average_floor;
 use async_trait::async_trait;
 use futures::StreamExt;
 use futures::stream::BoxStream;
-use geoengine_datatypes::plots::{AreaLineChart, Plot, PlotData};
+use geoengine_datatypes::plots::{AreaLineChart, Figure, Plot, PlotData};
 use geoengine_datatypes::primitives::{
     BandSelection, Measurement, PlotQueryRectangle, RasterQueryRectangle, TimeInstance,
     TimeInterval,
@@ -145,6 +145,28 @@ impl<P: Pixel> PlotQueryProcessor for MeanRasterPixelValuesOverTimeQueryProcesso
         query: PlotQueryRectangle,
         ctx: &'a dyn QueryContext,
     ) -> Result<Self::OutputFormat> {
+        let plot = self.plot(query, ctx).await?;
+
+        let plot_data = plot.to_vega_embeddable(false)?;
+
+        Ok(plot_data)
+    }
+
+    async fn plot_figure_query<'a>(
+        &'a self,
+        query: PlotQueryRectangle,
+        ctx: &'a dyn QueryContext,
+    ) -> Result<Option<Figure>> {
+        Ok(Some(self.plot(query, ctx).await?.to_figure()))
+    }
+}
+
+impl<P: Pixel> MeanRasterPixelValuesOverTimeQueryProcessor<P> {
+    async fn plot(
+        &self,
+        query: PlotQueryRectangle,
+        ctx: &dyn QueryContext,
+    ) -> Result<AreaLineChart> {
         let means = Self::calculate_means(
             self.raster
                 .query(
@@ -156,15 +178,9 @@ impl<P: Pixel> PlotQueryProcessor for MeanRasterPixelValuesOverTimeQueryProcesso
         )
         .await?;
 
-        let plot = Self::generate_plot(means, self.measurement.clone(), self.draw_area)?;
-
-        let plot_data = plot.to_vega_embeddable(false)?;
-
-        Ok(plot_data)
+        Self::generate_plot(means, self.measurement.clone(), self.draw_area)
     }
-}
 
-impl<P: Pixel> MeanRasterPixelValuesOverTimeQueryProcessor<P> {
     async fn calculate_means(
         mut tile_stream: BoxStream<'_, Result<RasterTile2D<P>>>,
         position: MeanRasterPixelValuesOverTimePosition,
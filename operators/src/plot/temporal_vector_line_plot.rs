@@ -12,7 +12,7 @@ use futures::TryStreamExt;
 use geoengine_datatypes::primitives::{FeatureDataType, PlotQueryRectangle};
 use geoengine_datatypes::{
     collections::FeatureCollection,
-    plots::{Plot, PlotData},
+    plots::{Figure, Plot, PlotData},
 };
 use geoengine_datatypes::{
     collections::FeatureCollectionInfos,
@@ -170,6 +170,30 @@ where
         query: PlotQueryRectangle,
         ctx: &'a dyn QueryContext,
     ) -> Result<Self::OutputFormat> {
+        self.chart(query, ctx)
+            .await?
+            .to_vega_embeddable(false)
+            .map_err(Into::into)
+    }
+
+    async fn plot_figure_query<'a>(
+        &'a self,
+        query: PlotQueryRectangle,
+        ctx: &'a dyn QueryContext,
+    ) -> Result<Option<Figure>> {
+        Ok(Some(self.chart(query, ctx).await?.to_figure()))
+    }
+}
+
+impl<G> FeatureAttributeValuesOverTimeQueryProcessor<G>
+where
+    G: Geometry + ArrowTyped + Sync + Send + 'static,
+{
+    async fn chart(
+        &self,
+        query: PlotQueryRectangle,
+        ctx: &dyn QueryContext,
+    ) -> Result<MultiLineChart> {
         let values = FeatureAttributeValues::<MAX_FEATURES>::default();
 
         let values = self
@@ -200,9 +224,7 @@ where
 
         let data_points = values.get_data_points();
         let measurement = Measurement::Unitless; // TODO: attach actual unit if we know it
-        MultiLineChart::new(data_points, measurement)
-            .to_vega_embeddable(false)
-            .map_err(Into::into)
+        Ok(MultiLineChart::new(data_points, measurement))
     }
 }
 
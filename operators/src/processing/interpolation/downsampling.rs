@@ -0,0 +1,292 @@
+use std::sync::Arc;
+
+use crate::adapters::{FoldTileAccu, FoldTileAccuMut, SubQueryTileAggregator};
+use crate::util::Result;
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use futures::{FutureExt, TryFutureExt};
+use geoengine_datatypes::primitives::{
+    AxisAlignedRectangle, Coordinate2D, RasterQueryRectangle, SpatialPartitioned,
+    SpatialResolution, TimeInstance, TimeInterval,
+};
+use geoengine_datatypes::raster::{
+    Blit, EmptyGrid, FromIndexFnParallel, GridIdx, GridIdx2D, GridIndexAccess, GridOrEmpty, Pixel,
+    RasterTile2D, TileInformation, TilingSpecification,
+};
+use rayon::ThreadPool;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+use super::create_mosaic_tile;
+
+/// How to combine the input pixels that fall into a coarser output pixel.
+///
+/// An input pixel belongs to an output pixel if its center lies within the output pixel.
+/// NODATA input pixels are ignored and an output pixel is NODATA if all of its input pixels are NODATA.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DownsamplingMethod {
+    Mean,
+    /// The most frequent value. Ties are resolved by choosing the smallest value.
+    Mode,
+    Min,
+    Max,
+    /// The sum of the values. It saturates at the bounds of the data type.
+    Sum,
+}
+
+impl DownsamplingMethod {
+    /// Aggregate the given values. Returns `None` if there are no values.
+    pub fn aggregate(self, mut values: Vec<f64>) -> Option<f64> {
+        if values.is_empty() {
+            return None;
+        }
+
+        Some(match self {
+            Self::Mean => values.iter().sum::<f64>() / values.len() as f64,
+            Self::Sum => values.iter().sum(),
+            Self::Min => values.into_iter().fold(f64::INFINITY, f64::min),
+            Self::Max => values.into_iter().fold(f64::NEG_INFINITY, f64::max),
+            Self::Mode => {
+                values.sort_unstable_by(f64::total_cmp);
+
+                let mut mode = values[0];
+                let mut mode_count = 0;
+                for run in values.chunk_by(|a, b| a.total_cmp(b).is_eq()) {
+                    // the runs are sorted ascending, so ties keep the smallest value
+                    if run.len() > mode_count {
+                        mode = run[0];
+                        mode_count = run.len();
+                    }
+                }
+                mode
+            }
+        })
+    }
+}
+
+/// Aggregate all pixels of `input` whose centers lie within the box spanned by `upper_left` and `lower_right`.
+/// If the box is smaller than an input pixel, the pixel containing the center of the box is used.
+pub(crate) fn downsample_area<P: Pixel>(
+    input: &RasterTile2D<P>,
+    upper_left: Coordinate2D,
+    lower_right: Coordinate2D,
+    method: DownsamplingMethod,
+) -> Option<P> {
+    let in_upper_left = input.tile_information().spatial_partition().upper_left();
+    let in_x_size = input.global_geo_transform.x_pixel_size();
+    let in_y_size = input.global_geo_transform.y_pixel_size();
+
+    let index_range = |start: f64, end: f64| {
+        // the pixel with index `i` has its center at `i + 0.5`
+        let first = (start - 0.5).ceil() as isize;
+        let last = ((end - 0.5).ceil() as isize).max(first + 1);
+        first..last
+    };
+
+    let y_range = index_range(
+        (upper_left.y - in_upper_left.y) / in_y_size,
+        (lower_right.y - in_upper_left.y) / in_y_size,
+    );
+    let x_range = index_range(
+        (upper_left.x - in_upper_left.x) / in_x_size,
+        (lower_right.x - in_upper_left.x) / in_x_size,
+    );
+
+    let mut values: Vec<f64> = Vec::new();
+    for y in y_range {
+        for x in x_range.clone() {
+            if let Ok(Some(value)) = input.get_at_grid_index(GridIdx([y, x])) {
+                values.push(value.as_());
+            }
+        }
+    }
+
+    method.aggregate(values).map(P::from_)
+}
+
+/// A sub-query aggregator that queries the input in its native resolution and aggregates
+/// the input pixels that fall into each of the coarser output pixels.
+#[derive(Debug, Clone)]
+pub struct DownsamplingSubQuery {
+    pub input_resolution: SpatialResolution,
+    pub method: DownsamplingMethod,
+    pub tiling_specification: TilingSpecification,
+}
+
+impl<'a, T: Pixel> SubQueryTileAggregator<'a, T> for DownsamplingSubQuery {
+    type FoldFuture = FoldFuture<T>;
+
+    type FoldMethod = fn(DownsamplingAccu<T>, RasterTile2D<T>) -> Self::FoldFuture;
+
+    type TileAccu = DownsamplingAccu<T>;
+    type TileAccuFuture = BoxFuture<'a, Result<Self::TileAccu>>;
+
+    fn new_fold_accu(
+        &self,
+        tile_info: TileInformation,
+        query_rect: RasterQueryRectangle,
+        pool: &Arc<ThreadPool>,
+    ) -> Self::TileAccuFuture {
+        let pool = pool.clone();
+        let tiling_specification = self.tiling_specification;
+        let method = self.method;
+
+        crate::util::spawn_blocking(move || DownsamplingAccu {
+            output_info: tile_info,
+            input_tile: create_mosaic_tile(&query_rect, tiling_specification),
+            method,
+            pool,
+        })
+        .map_err(From::from)
+        .boxed()
+    }
+
+    fn tile_query_rectangle(
+        &self,
+        tile_info: TileInformation,
+        _query_rect: RasterQueryRectangle,
+        start_time: TimeInstance,
+        band_idx: u32,
+    ) -> Result<Option<RasterQueryRectangle>> {
+        Ok(Some(RasterQueryRectangle {
+            spatial_bounds: tile_info.spatial_partition(),
+            time_interval: TimeInterval::new_instant(start_time)?,
+            spatial_resolution: self.input_resolution,
+            attributes: band_idx.into(),
+        }))
+    }
+
+    fn fold_method(&self) -> Self::FoldMethod {
+        |accu, tile| crate::util::spawn_blocking(|| fold_impl(accu, tile)).map(flatten_result)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DownsamplingAccu<T: Pixel> {
+    pub output_info: TileInformation,
+    pub input_tile: RasterTile2D<T>,
+    pub method: DownsamplingMethod,
+    pub pool: Arc<ThreadPool>,
+}
+
+#[async_trait]
+impl<T: Pixel> FoldTileAccu for DownsamplingAccu<T> {
+    type RasterType = T;
+
+    async fn into_tile(self) -> Result<RasterTile2D<Self::RasterType>> {
+        // now that we collected all the input tile pixels we aggregate them into the output pixels
+
+        let output_tile = crate::util::spawn_blocking_with_thread_pool(self.pool, move || {
+            downsample(&self.input_tile, &self.output_info, self.method)
+        })
+        .await?;
+
+        Ok(output_tile)
+    }
+
+    fn thread_pool(&self) -> &Arc<ThreadPool> {
+        &self.pool
+    }
+}
+
+impl<T: Pixel> FoldTileAccuMut for DownsamplingAccu<T> {
+    fn tile_mut(&mut self) -> &mut RasterTile2D<T> {
+        &mut self.input_tile
+    }
+}
+
+fn downsample<P: Pixel>(
+    input: &RasterTile2D<P>,
+    info_out: &TileInformation,
+    method: DownsamplingMethod,
+) -> RasterTile2D<P> {
+    if input.is_empty() {
+        return RasterTile2D::new_with_tile_info(
+            input.time,
+            *info_out,
+            input.band,
+            EmptyGrid::new(info_out.tile_size_in_pixels).into(),
+            input.cache_hint.clone_with_current_datetime(),
+        );
+    }
+
+    let tile_geo_transform = info_out.tile_geo_transform();
+
+    let map_fn = |g_idx: GridIdx2D| {
+        let GridIdx([y, x]) = g_idx;
+
+        let upper_left = tile_geo_transform.grid_idx_to_pixel_upper_left_coordinate_2d(g_idx);
+        let lower_right =
+            tile_geo_transform.grid_idx_to_pixel_upper_left_coordinate_2d(GridIdx([y + 1, x + 1]));
+
+        downsample_area(input, upper_left, lower_right, method)
+    };
+
+    let out_data = GridOrEmpty::from_index_fn_parallel(&info_out.tile_size_in_pixels, map_fn);
+
+    RasterTile2D::new(
+        input.time,
+        info_out.global_tile_position,
+        input.band,
+        info_out.global_geo_transform,
+        out_data,
+        input.cache_hint.clone_with_current_datetime(),
+    )
+}
+
+type FoldFutureFn<T> =
+    fn(Result<Result<DownsamplingAccu<T>>, tokio::task::JoinError>) -> Result<DownsamplingAccu<T>>;
+type FoldFuture<T> = futures::future::Map<JoinHandle<Result<DownsamplingAccu<T>>>, FoldFutureFn<T>>;
+
+/// Turn a result of results into a result
+fn flatten_result<T: Pixel>(
+    result: Result<Result<DownsamplingAccu<T>>, tokio::task::JoinError>,
+) -> Result<DownsamplingAccu<T>> {
+    match result {
+        Ok(r) => r,
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn fold_impl<T: Pixel>(
+    mut accu: DownsamplingAccu<T>,
+    tile: RasterTile2D<T>,
+) -> Result<DownsamplingAccu<T>> {
+    // get the time now because it is not known when the accu was created
+    accu.input_tile.time = tile.time;
+
+    if tile.is_empty() {
+        return Ok(accu);
+    }
+
+    let mut accu_input_tile = accu.input_tile.into_materialized_tile();
+    accu_input_tile.blit(tile)?;
+
+    Ok(DownsamplingAccu {
+        input_tile: accu_input_tile.into(),
+        ..accu
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn it_aggregates() {
+        let values = vec![3., 1., 3., 2., 1., 5.];
+
+        assert_eq!(
+            DownsamplingMethod::Mean.aggregate(values.clone()),
+            Some(2.5)
+        );
+        assert_eq!(DownsamplingMethod::Sum.aggregate(values.clone()), Some(15.));
+        assert_eq!(DownsamplingMethod::Min.aggregate(values.clone()), Some(1.));
+        assert_eq!(DownsamplingMethod::Max.aggregate(values.clone()), Some(5.));
+        assert_eq!(DownsamplingMethod::Mode.aggregate(values), Some(1.));
+
+        assert_eq!(DownsamplingMethod::Mean.aggregate(vec![]), None);
+    }
+}
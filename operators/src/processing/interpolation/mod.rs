@@ -1,3 +1,5 @@
+mod downsampling;
+
 use std::marker::PhantomData;
 use std::sync::Arc;
 
@@ -20,18 +22,27 @@ use geoengine_datatypes::primitives::{
 };
 use geoengine_datatypes::primitives::{BandSelection, CacheHint};
 use geoengine_datatypes::raster::{
-    Bilinear, Blit, EmptyGrid2D, GeoTransform, GridOrEmpty, GridSize, InterpolationAlgorithm,
-    NearestNeighbor, Pixel, RasterTile2D, TileInformation, TilingSpecification,
+    Bicubic, Bilinear, Blit, EmptyGrid2D, GeoTransform, GridOrEmpty, GridSize,
+    InterpolationAlgorithm, Lanczos, NearestNeighbor, Pixel, RasterTile2D, TileInformation,
+    TilingSpecification,
 };
 use rayon::ThreadPool;
 use serde::{Deserialize, Serialize};
 use snafu::{Snafu, ensure};
 
+pub use downsampling::DownsamplingMethod;
+use downsampling::DownsamplingSubQuery;
+pub(crate) use downsampling::downsample_area;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct InterpolationParams {
     pub interpolation: InterpolationMethod,
     pub input_resolution: InputResolution,
+    /// How to aggregate the input pixels if the query resolution is coarser than the input resolution.
+    /// If it is not set, the input is queried directly in the coarser resolution.
+    #[serde(default)]
+    pub downsampling: Option<DownsamplingMethod>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
@@ -41,11 +52,16 @@ pub enum InputResolution {
     Source,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum InterpolationMethod {
+    #[default]
     NearestNeighbor,
     BiLinear,
+    /// Cubic convolution over a 4x4 pixel window
+    BiCubic,
+    /// Lanczos resampling over a 6x6 pixel window
+    Lanczos,
 }
 
 #[derive(Debug, Snafu)]
@@ -107,6 +123,7 @@ impl RasterOperator for Interpolation {
             result_descriptor: out_descriptor,
             raster_source,
             interpolation_method: self.params.interpolation,
+            downsampling_method: self.params.downsampling,
             input_resolution,
             tiling_specification: context.tiling_specification(),
         };
@@ -123,6 +140,7 @@ pub struct InitializedInterpolation {
     result_descriptor: RasterResultDescriptor,
     raster_source: Box<dyn InitializedRasterOperator>,
     interpolation_method: InterpolationMethod,
+    downsampling_method: Option<DownsamplingMethod>,
     input_resolution: SpatialResolution,
     tiling_specification: TilingSpecification,
 }
@@ -137,6 +155,7 @@ impl InitializedRasterOperator for InitializedInterpolation {
                         p,
                         self.result_descriptor.clone(),
                         self.input_resolution,
+                        self.downsampling_method,
                         self.tiling_specification,
                     ).boxed()
                     .into(),
@@ -144,6 +163,23 @@ impl InitializedRasterOperator for InitializedInterpolation {
                         p,
                         self.result_descriptor.clone(),
                         self.input_resolution,
+                        self.downsampling_method,
+                        self.tiling_specification,
+                    ).boxed()
+                    .into(),
+                InterpolationMethod::BiCubic =>InterploationProcessor::<_,_, Bicubic>::new(
+                        p,
+                        self.result_descriptor.clone(),
+                        self.input_resolution,
+                        self.downsampling_method,
+                        self.tiling_specification,
+                    ).boxed()
+                    .into(),
+                InterpolationMethod::Lanczos =>InterploationProcessor::<_,_, Lanczos>::new(
+                        p,
+                        self.result_descriptor.clone(),
+                        self.input_resolution,
+                        self.downsampling_method,
                         self.tiling_specification,
                     ).boxed()
                     .into(),
@@ -179,6 +215,7 @@ where
     source: Q,
     result_descriptor: RasterResultDescriptor,
     input_resolution: SpatialResolution,
+    downsampling: Option<DownsamplingMethod>,
     tiling_specification: TilingSpecification,
     interpolation: PhantomData<I>,
 }
@@ -193,12 +230,14 @@ where
         source: Q,
         result_descriptor: RasterResultDescriptor,
         input_resolution: SpatialResolution,
+        downsampling: Option<DownsamplingMethod>,
         tiling_specification: TilingSpecification,
     ) -> Self {
         Self {
             source,
             result_descriptor,
             input_resolution,
+            downsampling,
             tiling_specification,
             interpolation: PhantomData,
         }
@@ -231,6 +270,29 @@ where
         if query.spatial_resolution.x >= self.input_resolution.x
             && query.spatial_resolution.y >= self.input_resolution.y
         {
+            let is_coarser = query.spatial_resolution.x > self.input_resolution.x
+                || query.spatial_resolution.y > self.input_resolution.y;
+
+            if let Some(method) = self.downsampling.filter(|_| is_coarser) {
+                // aggregate the input pixels into the coarser output pixels
+                let sub_query = DownsamplingSubQuery {
+                    input_resolution: self.input_resolution,
+                    method,
+                    tiling_specification: self.tiling_specification,
+                };
+
+                return Ok(RasterSubQueryAdapter::<'a, P, _, _>::new(
+                    &self.source,
+                    query,
+                    self.tiling_specification,
+                    ctx,
+                    sub_query,
+                )
+                .filter_and_fill(
+                    crate::adapters::FillerTileCacheExpirationStrategy::DerivedFromSurroundingTiles,
+                ));
+            }
+
             // TODO: should we use the query or the input resolution here?
             return self.source.query(query, ctx).await;
         }
@@ -307,10 +369,11 @@ where
     ) -> Result<Option<RasterQueryRectangle>> {
         // enlarge the spatial bounds in order to have the neighbor pixels for the interpolation
         let spatial_bounds = tile_info.spatial_partition();
-        let enlarge: Coordinate2D = (self.input_resolution.x, -self.input_resolution.y).into();
+        let pixel: Coordinate2D = (self.input_resolution.x, -self.input_resolution.y).into();
+        let kernel_radius = I::KERNEL_RADIUS as f64;
         let spatial_bounds = SpatialPartition2D::new(
-            spatial_bounds.upper_left(),
-            spatial_bounds.lower_right() + enlarge,
+            spatial_bounds.upper_left() - pixel * (kernel_radius - 1.),
+            spatial_bounds.lower_right() + pixel * kernel_radius,
         )?;
 
         Ok(Some(RasterQueryRectangle {
@@ -381,51 +444,59 @@ pub fn create_accu<T: Pixel, I: InterpolationAlgorithm<T>>(
     pool: Arc<ThreadPool>,
     tiling_specification: TilingSpecification,
 ) -> impl Future<Output = Result<InterpolationAccu<T, I>>> + use<T, I> {
-    // create an accumulator as a single tile that fits all the input tiles
-    let spatial_bounds = query_rect.spatial_bounds;
-    let spatial_resolution = query_rect.spatial_resolution;
-    let time_interval = query_rect.time_interval;
+    let query_rect = query_rect.clone();
 
     crate::util::spawn_blocking(move || {
-        let tiling = tiling_specification.strategy(spatial_resolution.x, -spatial_resolution.y);
-
-        let origin_coordinate = tiling
-            .tile_information_iterator(spatial_bounds)
-            .next()
-            .expect("a query contains at least one tile")
-            .spatial_partition()
-            .upper_left();
-
-        let geo_transform = GeoTransform::new(
-            origin_coordinate,
-            spatial_resolution.x,
-            -spatial_resolution.y,
-        );
-
-        let bbox = tiling.tile_grid_box(spatial_bounds);
-
-        let shape = [
-            bbox.axis_size_y() * tiling.tile_size_in_pixels.axis_size_y(),
-            bbox.axis_size_x() * tiling.tile_size_in_pixels.axis_size_x(),
-        ];
-
-        // create a non-aligned (w.r.t. the tiling specification) grid by setting the origin to the top-left of the tile and the tile-index to [0, 0]
-        let grid = EmptyGrid2D::new(shape.into());
-
-        let input_tile = RasterTile2D::new(
-            time_interval,
-            [0, 0].into(),
-            0,
-            geo_transform,
-            GridOrEmpty::from(grid),
-            CacheHint::max_duration(),
-        );
+        let input_tile = create_mosaic_tile(&query_rect, tiling_specification);
 
         InterpolationAccu::new(input_tile, tile_info, pool)
     })
     .map_err(From::from)
 }
 
+/// Create an empty tile that fits all the input tiles of the query rectangle
+fn create_mosaic_tile<T: Pixel>(
+    query_rect: &RasterQueryRectangle,
+    tiling_specification: TilingSpecification,
+) -> RasterTile2D<T> {
+    let spatial_bounds = query_rect.spatial_bounds;
+    let spatial_resolution = query_rect.spatial_resolution;
+
+    let tiling = tiling_specification.strategy(spatial_resolution.x, -spatial_resolution.y);
+
+    let origin_coordinate = tiling
+        .tile_information_iterator(spatial_bounds)
+        .next()
+        .expect("a query contains at least one tile")
+        .spatial_partition()
+        .upper_left();
+
+    let geo_transform = GeoTransform::new(
+        origin_coordinate,
+        spatial_resolution.x,
+        -spatial_resolution.y,
+    );
+
+    let bbox = tiling.tile_grid_box(spatial_bounds);
+
+    let shape = [
+        bbox.axis_size_y() * tiling.tile_size_in_pixels.axis_size_y(),
+        bbox.axis_size_x() * tiling.tile_size_in_pixels.axis_size_x(),
+    ];
+
+    // create a non-aligned (w.r.t. the tiling specification) grid by setting the origin to the top-left of the tile and the tile-index to [0, 0]
+    let grid = EmptyGrid2D::new(shape.into());
+
+    RasterTile2D::new(
+        query_rect.time_interval,
+        [0, 0].into(),
+        0,
+        geo_transform,
+        GridOrEmpty::from(grid),
+        CacheHint::max_duration(),
+    )
+}
+
 pub fn fold_future<T, I>(
     accu: InterpolationAccu<T, I>,
     tile: RasterTile2D<T>,
@@ -502,6 +573,7 @@ mod tests {
             params: InterpolationParams {
                 interpolation: InterpolationMethod::NearestNeighbor,
                 input_resolution: InputResolution::Value(SpatialResolution::one()),
+                downsampling: None,
             },
             sources: SingleRasterSource { raster },
         }
@@ -661,6 +733,7 @@ mod tests {
             params: InterpolationParams {
                 interpolation: InterpolationMethod::NearestNeighbor,
                 input_resolution: InputResolution::Value(SpatialResolution::one()),
+                downsampling: None,
             },
             sources: SingleRasterSource { raster },
         }
@@ -703,6 +776,7 @@ mod tests {
             params: InterpolationParams {
                 interpolation: InterpolationMethod::NearestNeighbor,
                 input_resolution: InputResolution::Value(SpatialResolution::one()),
+                downsampling: None,
             },
             sources: SingleRasterSource {
                 raster: RasterStacker {
@@ -807,4 +881,69 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn it_downsamples_with_aggregates() -> Result<()> {
+        let exe_ctx = MockExecutionContext::new_with_tiling_spec(TilingSpecification::new(
+            (0., 0.).into(),
+            [2, 2].into(),
+        ));
+
+        let operator = Interpolation {
+            params: InterpolationParams {
+                interpolation: InterpolationMethod::BiCubic,
+                input_resolution: InputResolution::Value(SpatialResolution::one()),
+                downsampling: Some(DownsamplingMethod::Max),
+            },
+            sources: SingleRasterSource {
+                raster: make_raster(CacheHint::max_duration()),
+            },
+        }
+        .boxed()
+        .initialize(WorkflowOperatorPath::initialize_root(), &exe_ctx)
+        .await?;
+
+        let processor = operator.query_processor()?.get_i8().unwrap();
+
+        let query_rect = RasterQueryRectangle {
+            spatial_bounds: SpatialPartition2D::new_unchecked((0., 2.).into(), (4., 0.).into()),
+            time_interval: TimeInterval::new_unchecked(0, 20),
+            spatial_resolution: SpatialResolution::new_unchecked(2., 2.),
+            attributes: BandSelection::first(),
+        };
+        let query_ctx = MockQueryContext::test_default();
+
+        let result_stream = processor.query(query_rect, &query_ctx).await?;
+
+        let result: Vec<Result<RasterTile2D<i8>>> = result_stream.collect().await;
+        let result = result.into_iter().collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(result.len(), 2);
+
+        // the upper row of the output tile lies outside of the input raster
+        let data = [vec![0, 0, 6, 8], vec![0, 0, 8, 6]];
+        let valid = vec![false, false, true, true];
+
+        for (tile, data) in result.into_iter().zip(data) {
+            let tile = tile.into_materialized_tile();
+            assert_eq!(tile.grid_array.inner_grid.data, data);
+            assert_eq!(tile.grid_array.validity_mask.data, valid);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_deserializes_params_with_defaults() {
+        let params: InterpolationParams = serde_json::from_value(serde_json::json!({
+            "interpolation": "lanczos",
+            "inputResolution": {
+                "type": "source"
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(params.interpolation, InterpolationMethod::Lanczos);
+        assert_eq!(params.downsampling, None);
+    }
 }
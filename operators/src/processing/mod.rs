@@ -13,9 +13,13 @@ mod raster_scaling;
 mod raster_stacker;
 mod raster_type_conversion;
 mod raster_vector_join;
+mod raster_vectorization;
 mod rasterization;
+mod reclassify;
 mod reprojection;
 mod temporal_raster_aggregation;
+mod temporal_trend;
+mod terrain;
 mod time_projection;
 mod time_shift;
 mod vector_join;
@@ -30,7 +34,11 @@ pub use expression::{
     Expression, ExpressionParams, RasterExpressionError, VectorExpression, VectorExpressionError,
     VectorExpressionParams, initialize_expression_dependencies,
 };
-pub use interpolation::{Interpolation, InterpolationError, InterpolationParams};
+pub(crate) use interpolation::downsample_area;
+pub use interpolation::{
+    DownsamplingMethod, InputResolution, Interpolation, InterpolationError, InterpolationMethod,
+    InterpolationParams,
+};
 pub use line_simplification::{
     LineSimplification, LineSimplificationError, LineSimplificationParams,
 };
@@ -50,11 +58,22 @@ pub use raster_vector_join::{
     ColumnNames, FeatureAggregationMethod, RasterVectorJoin, RasterVectorJoinParams,
     TemporalAggregationMethod,
 };
+pub(crate) use raster_vector_join::{CoveredPixels, FeatureTimeSpanIter, PixelCoverCreator};
+pub use raster_vectorization::{
+    ContourLevels, Contours, ContoursParams, Polygonize, PolygonizeParams, RasterVectorizationError,
+};
+pub use reclassify::{
+    RangeMapping, Reclassify, ReclassifyDefault, ReclassifyError, ReclassifyParams, ValueMapping,
+};
 pub use reprojection::{
     InitializedRasterReprojection, InitializedVectorReprojection, Reprojection, ReprojectionParams,
 };
 pub use temporal_raster_aggregation::{
     Aggregation, TemporalRasterAggregation, TemporalRasterAggregationParameters,
 };
+pub use temporal_trend::{
+    BreakDetectionParams, TemporalTrend, TemporalTrendError, TemporalTrendParams,
+};
+pub use terrain::{Terrain, TerrainAttribute, TerrainError, TerrainParams};
 pub use time_projection::{TimeProjection, TimeProjectionError, TimeProjectionParams};
 pub use time_shift::{TimeShift, TimeShiftError, TimeShiftParams};
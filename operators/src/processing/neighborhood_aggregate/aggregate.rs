@@ -1,14 +1,19 @@
 use super::{NeighborhoodAggregateError, error};
 use crate::util::number_statistics::NumberStatistics;
-use geoengine_datatypes::raster::{Grid2D, GridShape2D, GridSize, Pixel};
+use geoengine_datatypes::raster::{
+    FromIndexFn, Grid2D, GridIdx, GridIdx2D, GridShape2D, GridSize, Pixel,
+};
 use num::Integer;
 use num_traits::AsPrimitive;
 use snafu::ensure;
 
 /// A weight matrix that is applied for the neighborhood of pixels before aggregating them.
+///
+/// The footprint specifies which pixels of the matrix belong to the neighborhood, e.g., to form a circle.
 #[derive(Debug, Clone)]
 pub struct Neighborhood {
     matrix: Grid2D<f64>,
+    footprint: Grid2D<bool>,
 }
 
 impl Neighborhood {
@@ -20,14 +25,33 @@ impl Neighborhood {
             }
         );
 
-        Ok(Self { matrix })
+        let footprint = Grid2D::new_filled(matrix.shape, true);
+
+        Ok(Self { matrix, footprint })
+    }
+
+    /// A circular neighborhood of all pixels whose center is within `radius` pixels of the center pixel
+    pub fn circle(radius: usize) -> Self {
+        let width = 2 * radius + 1;
+        let shape = GridShape2D::new([width, width]);
+
+        let radius = radius as isize;
+        let footprint = Grid2D::from_index_fn(&shape, |GridIdx([y, x]): GridIdx2D| {
+            let (dy, dx) = (y - radius, x - radius);
+            dy * dy + dx * dx <= radius * radius
+        });
+
+        Self {
+            matrix: Grid2D::new_filled(shape, 1.),
+            footprint,
+        }
     }
 
-    /// Apply the weight matrix to the given pixel neighborhood and return the neighborhood.
+    /// Apply the weight matrix to the given pixel neighborhood and return the pixels of the footprint.
     ///
     // TODO: Think about returning only the f64 values and omitting NODATA values.
     //       We need more aggregate functions first to see if this would suffice.
-    pub fn apply(&self, mut values: Vec<Option<f64>>) -> Vec<Option<f64>> {
+    pub fn apply(&self, values: Vec<Option<f64>>) -> Vec<Option<f64>> {
         debug_assert!(
             values.len() == self.matrix.number_of_elements(),
             "Dimensions of `values` and neighborhood `matrix` do not match: {} != {}",
@@ -35,13 +59,13 @@ impl Neighborhood {
             self.matrix.number_of_elements()
         );
 
-        for (value, weight) in values.iter_mut().zip(self.matrix.data.iter()) {
-            if let Some(value) = value {
-                *value *= weight;
-            }
-        }
-
         values
+            .into_iter()
+            .zip(&self.matrix.data)
+            .zip(&self.footprint.data)
+            .filter(|(_, is_in_footprint)| **is_in_footprint)
+            .map(|((value, weight), _)| value.map(|value| value * weight))
+            .collect()
     }
 
     pub fn matrix(&self) -> &Grid2D<f64> {
@@ -70,7 +94,7 @@ impl Neighborhood {
 
 /// A function that aggregates a neighborhood of pixels to a single pixel value.
 pub trait AggregateFunction: Sync + Send + Clone {
-    fn apply<P>(values: &[Option<f64>]) -> Option<P>
+    fn apply<P>(&self, values: &[Option<f64>]) -> Option<P>
     where
         P: Pixel,
         f64: AsPrimitive<P>;
@@ -81,7 +105,7 @@ pub trait AggregateFunction: Sync + Send + Clone {
 pub struct StandardDeviation;
 
 impl AggregateFunction for StandardDeviation {
-    fn apply<P>(values: &[Option<f64>]) -> Option<P>
+    fn apply<P>(&self, values: &[Option<f64>]) -> Option<P>
     where
         P: Pixel,
         f64: AsPrimitive<P>,
@@ -106,11 +130,11 @@ impl AggregateFunction for StandardDeviation {
 }
 
 /// An aggregate function that computes the sum of a set of pixels.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 pub struct Sum;
 
 impl AggregateFunction for Sum {
-    fn apply<P>(value_options: &[Option<f64>]) -> Option<P>
+    fn apply<P>(&self, value_options: &[Option<f64>]) -> Option<P>
     where
         P: Pixel,
         f64: AsPrimitive<P>,
@@ -131,6 +155,179 @@ impl AggregateFunction for Sum {
     }
 }
 
+/// The valid values of a set of pixels, ignoring NODATA
+fn valid_values(values: &[Option<f64>]) -> impl Iterator<Item = f64> + '_ {
+    values.iter().filter_map(|value| *value)
+}
+
+/// The valid values of a set of pixels in ascending order, ignoring NODATA
+fn sorted_valid_values(values: &[Option<f64>]) -> Vec<f64> {
+    let mut values = valid_values(values).collect::<Vec<_>>();
+    values.sort_unstable_by(f64::total_cmp);
+    values
+}
+
+fn statistics(values: &[Option<f64>]) -> NumberStatistics {
+    let mut aggregator = NumberStatistics::default();
+    for value in valid_values(values) {
+        aggregator.add(value);
+    }
+    aggregator
+}
+
+fn finite_as<P>(value: f64) -> Option<P>
+where
+    P: Pixel,
+    f64: AsPrimitive<P>,
+{
+    value.is_finite().then(|| value.as_())
+}
+
+/// An aggregate function that computes the minimum of a set of pixels, ignoring NODATA.
+#[derive(Debug, Clone, Copy)]
+pub struct Min;
+
+impl AggregateFunction for Min {
+    fn apply<P>(&self, values: &[Option<f64>]) -> Option<P>
+    where
+        P: Pixel,
+        f64: AsPrimitive<P>,
+    {
+        finite_as(statistics(values).min())
+    }
+}
+
+/// An aggregate function that computes the maximum of a set of pixels, ignoring NODATA.
+#[derive(Debug, Clone, Copy)]
+pub struct Max;
+
+impl AggregateFunction for Max {
+    fn apply<P>(&self, values: &[Option<f64>]) -> Option<P>
+    where
+        P: Pixel,
+        f64: AsPrimitive<P>,
+    {
+        finite_as(statistics(values).max())
+    }
+}
+
+/// An aggregate function that computes the arithmetic mean of a set of pixels, ignoring NODATA.
+#[derive(Debug, Clone, Copy)]
+pub struct Mean;
+
+impl AggregateFunction for Mean {
+    fn apply<P>(&self, values: &[Option<f64>]) -> Option<P>
+    where
+        P: Pixel,
+        f64: AsPrimitive<P>,
+    {
+        finite_as(statistics(values).mean())
+    }
+}
+
+/// An aggregate function that computes the difference of the maximum and the minimum of a set of pixels, ignoring NODATA.
+#[derive(Debug, Clone, Copy)]
+pub struct Range;
+
+impl AggregateFunction for Range {
+    fn apply<P>(&self, values: &[Option<f64>]) -> Option<P>
+    where
+        P: Pixel,
+        f64: AsPrimitive<P>,
+    {
+        let statistics = statistics(values);
+        finite_as(statistics.max() - statistics.min())
+    }
+}
+
+/// An aggregate function that computes a percentile of a set of pixels, ignoring NODATA.
+///
+/// It interpolates linearly between the two closest ranks.
+#[derive(Debug, Clone, Copy)]
+pub struct Percentile {
+    /// The percentile in the range [0, 1]
+    percentile: f64,
+}
+
+impl Percentile {
+    pub fn new(percentile: f64) -> Result<Self, NeighborhoodAggregateError> {
+        ensure!(
+            (0. ..=1.).contains(&percentile),
+            error::InvalidPercentile { percentile }
+        );
+
+        Ok(Self { percentile })
+    }
+
+    pub fn median() -> Self {
+        Self { percentile: 0.5 }
+    }
+}
+
+impl AggregateFunction for Percentile {
+    fn apply<P>(&self, values: &[Option<f64>]) -> Option<P>
+    where
+        P: Pixel,
+        f64: AsPrimitive<P>,
+    {
+        let values = sorted_valid_values(values);
+        if values.is_empty() {
+            return None;
+        }
+
+        let rank = self.percentile * (values.len() - 1) as f64;
+        let lower = values[rank.floor() as usize];
+        let upper = values[rank.ceil() as usize];
+
+        finite_as(lower + (upper - lower) * rank.fract())
+    }
+}
+
+/// An aggregate function that computes the most frequent value of a set of pixels, ignoring NODATA.
+/// If several values are equally frequent, the smallest one is chosen.
+#[derive(Debug, Clone, Copy)]
+pub struct Mode;
+
+impl AggregateFunction for Mode {
+    fn apply<P>(&self, values: &[Option<f64>]) -> Option<P>
+    where
+        P: Pixel,
+        f64: AsPrimitive<P>,
+    {
+        let values = sorted_valid_values(values);
+
+        let mut mode: Option<(f64, usize)> = None;
+        for group in values.chunk_by(|a, b| a.total_cmp(b).is_eq()) {
+            if mode.is_none_or(|(_, count)| group.len() > count) {
+                mode = Some((group[0], group.len()));
+            }
+        }
+
+        finite_as(mode?.0)
+    }
+}
+
+/// An aggregate function that counts the distinct values of a set of pixels, ignoring NODATA.
+#[derive(Debug, Clone, Copy)]
+pub struct Variety;
+
+impl AggregateFunction for Variety {
+    fn apply<P>(&self, values: &[Option<f64>]) -> Option<P>
+    where
+        P: Pixel,
+        f64: AsPrimitive<P>,
+    {
+        let values = sorted_valid_values(values);
+        if values.is_empty() {
+            return None;
+        }
+
+        let variety = values.chunk_by(|a, b| a.total_cmp(b).is_eq()).count();
+
+        Some((variety as f64).as_())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -138,7 +335,7 @@ mod tests {
     #[test]
     #[allow(clippy::float_cmp)]
     fn test_standard_deviation() {
-        let result: Option<f64> = StandardDeviation::apply(&[
+        let result: Option<f64> = StandardDeviation.apply(&[
             Some(1.),
             Some(2.),
             Some(3.),
@@ -151,7 +348,7 @@ mod tests {
         ]);
         assert_eq!(result.unwrap(), 2.581_988_897_471_611);
 
-        let result: Option<f64> = StandardDeviation::apply(&[
+        let result: Option<f64> = StandardDeviation.apply(&[
             Some(1.),
             Some(2.),
             Some(3.),
@@ -164,13 +361,17 @@ mod tests {
         ]);
         assert_eq!(result.unwrap(), 2.291_287_847_477_92);
 
-        assert!(StandardDeviation::apply::<f64>(&[] as &[Option<f64>]).is_none());
+        assert!(
+            StandardDeviation
+                .apply::<f64>(&[] as &[Option<f64>])
+                .is_none()
+        );
     }
 
     #[test]
     #[allow(clippy::float_cmp)]
     fn test_sum_fn() {
-        let result = Sum::apply::<f64>(&[
+        let result = Sum.apply::<f64>(&[
             Some(1.),
             Some(2.),
             Some(3.),
@@ -183,7 +384,7 @@ mod tests {
         ]);
         assert_eq!(result.unwrap(), 45.);
 
-        let result = Sum::apply::<f64>(&[
+        let result = Sum.apply::<f64>(&[
             Some(1.),
             Some(2.),
             Some(3.),
@@ -281,4 +482,112 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_min_max_mean_range() {
+        let values = [Some(4.), Some(2.), None, Some(9.), Some(5.)];
+
+        assert_eq!(Min.apply::<f64>(&values).unwrap(), 2.);
+        assert_eq!(Max.apply::<f64>(&values).unwrap(), 9.);
+        assert_eq!(Mean.apply::<f64>(&values).unwrap(), 5.);
+        assert_eq!(Range.apply::<f64>(&values).unwrap(), 7.);
+
+        // integer outputs are truncated
+        assert_eq!(Mean.apply::<u8>(&[Some(1.), Some(2.)]).unwrap(), 1);
+
+        assert!(Min.apply::<f64>(&[None, None]).is_none());
+        assert!(Max.apply::<f64>(&[None, None]).is_none());
+        assert!(Mean.apply::<f64>(&[None, None]).is_none());
+        assert!(Range.apply::<f64>(&[None, None]).is_none());
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_percentile() {
+        let values = [Some(3.), Some(1.), None, Some(4.), Some(2.)];
+
+        assert_eq!(Percentile::median().apply::<f64>(&values).unwrap(), 2.5);
+        assert_eq!(
+            Percentile::median()
+                .apply::<f64>(&[Some(3.), Some(1.), Some(2.)])
+                .unwrap(),
+            2.
+        );
+        assert_eq!(
+            Percentile::new(0.).unwrap().apply::<f64>(&values).unwrap(),
+            1.
+        );
+        assert_eq!(
+            Percentile::new(1.).unwrap().apply::<f64>(&values).unwrap(),
+            4.
+        );
+        assert_eq!(
+            Percentile::new(0.25)
+                .unwrap()
+                .apply::<f64>(&values)
+                .unwrap(),
+            1.75
+        );
+
+        assert!(Percentile::median().apply::<f64>(&[None]).is_none());
+
+        assert!(Percentile::new(1.5).is_err());
+        assert!(Percentile::new(f64::NAN).is_err());
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_mode_and_variety() {
+        let values = [
+            Some(3.),
+            Some(1.),
+            Some(3.),
+            None,
+            Some(2.),
+            Some(1.),
+            Some(3.),
+        ];
+
+        assert_eq!(Mode.apply::<f64>(&values).unwrap(), 3.);
+        assert_eq!(Variety.apply::<u8>(&values).unwrap(), 3);
+
+        // ties are resolved by choosing the smallest value
+        assert_eq!(
+            Mode.apply::<f64>(&[Some(2.), Some(1.), Some(2.), Some(1.)])
+                .unwrap(),
+            1.
+        );
+
+        assert!(Mode.apply::<f64>(&[None]).is_none());
+        assert!(Variety.apply::<f64>(&[None]).is_none());
+    }
+
+    #[test]
+    fn test_circle() {
+        let circle = Neighborhood::circle(2);
+
+        assert_eq!(circle.x_width(), 5);
+        assert_eq!(circle.y_width(), 5);
+
+        #[rustfmt::skip]
+        let footprint = vec![
+            false, false, true,  false, false,
+            false, true,  true,  true,  false,
+            true,  true,  true,  true,  true,
+            false, true,  true,  true,  false,
+            false, false, true,  false, false,
+        ];
+        assert_eq!(circle.footprint.data, footprint);
+
+        let values = (0..25).map(|v| Some(f64::from(v))).collect::<Vec<_>>();
+
+        assert_eq!(
+            circle.apply(values),
+            [2, 6, 7, 8, 10, 11, 12, 13, 14, 16, 17, 18, 22]
+                .into_iter()
+                .map(|v| Some(f64::from(v)))
+                .collect::<Vec<_>>()
+        );
+    }
 }
@@ -1,14 +1,20 @@
 mod aggregate;
 mod tile_sub_query;
 
-use self::aggregate::{AggregateFunction, Neighborhood, StandardDeviation, Sum};
-use self::tile_sub_query::NeighborhoodAggregateTileNeighborhood;
+pub(crate) use self::tile_sub_query::{NeighborhoodKernel, NeighborhoodTileSubQuery};
+
+use self::aggregate::{
+    AggregateFunction, Max, Mean, Min, Mode, Neighborhood, Percentile, Range, StandardDeviation,
+    Sum, Variety,
+};
+use self::tile_sub_query::NeighborhoodAggregateKernel;
 use crate::adapters::RasterSubQueryAdapter;
 use crate::adapters::stack_individual_aligned_raster_bands;
 use crate::engine::{
-    CanonicOperatorName, ExecutionContext, InitializedRasterOperator, InitializedSources, Operator,
-    OperatorName, QueryContext, QueryProcessor, RasterOperator, RasterQueryProcessor,
-    RasterResultDescriptor, SingleRasterSource, TypedRasterQueryProcessor, WorkflowOperatorPath,
+    BoxRasterQueryProcessor, CanonicOperatorName, ExecutionContext, InitializedRasterOperator,
+    InitializedSources, Operator, OperatorName, QueryContext, QueryProcessor, RasterOperator,
+    RasterQueryProcessor, RasterResultDescriptor, SingleRasterSource, TypedRasterQueryProcessor,
+    WorkflowOperatorPath,
 };
 use crate::util::Result;
 use async_trait::async_trait;
@@ -45,18 +51,42 @@ pub struct NeighborhoodAggregateParams {
     pub aggregate_function: AggregateFunctionParams,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// The aggregate function that is applied to the weighted neighborhood of each pixel.
+///
+/// `Sum` and `StandardDeviation` consider NODATA values, i.e., `Sum` results in NODATA if any pixel is NODATA.
+/// All other functions ignore NODATA and only result in NODATA if all pixels of the neighborhood are NODATA.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
 pub enum AggregateFunctionParams {
     Sum,
     StandardDeviation,
+    Min,
+    Max,
+    Mean,
+    Median,
+    /// The percentile must be in the range [0, 1]
+    Percentile(f64),
+    /// The most frequent value
+    Mode,
+    /// The difference of the maximum and the minimum
+    Range,
+    /// The number of distinct values
+    Variety,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum NeighborhoodParams {
-    Rectangle { dimensions: [usize; 2] },
-    WeightsMatrix { weights: Vec<Vec<f64>> },
+    Rectangle {
+        dimensions: [usize; 2],
+    },
+    WeightsMatrix {
+        weights: Vec<Vec<f64>>,
+    },
+    /// All pixels whose center is within `radius` pixels of the center pixel
+    Circle {
+        radius: usize,
+    },
 }
 
 impl NeighborhoodParams {
@@ -68,6 +98,7 @@ impl NeighborhoodParams {
                 GridShape2D::new([x_size, y_size])
             }
             Self::Rectangle { dimensions } => GridShape2D::new(*dimensions),
+            Self::Circle { radius } => GridShape2D::new([2 * radius + 1, 2 * radius + 1]),
         }
     }
 }
@@ -76,6 +107,10 @@ impl TryFrom<NeighborhoodParams> for Neighborhood {
     type Error = NeighborhoodAggregateError;
 
     fn try_from(neighborhood: NeighborhoodParams) -> Result<Self, Self::Error> {
+        if let NeighborhoodParams::Circle { radius } = neighborhood {
+            return Ok(Self::circle(radius));
+        }
+
         let dimensions = neighborhood.dimensions();
 
         ensure!(dimensions.number_of_elements() > 0, error::DimensionsZero);
@@ -89,7 +124,9 @@ impl TryFrom<NeighborhoodParams> for Neighborhood {
                 Grid2D::new(dimensions, weights.into_iter().flatten().collect())
                     .map_err(|_| NeighborhoodAggregateError::IrregularDimensions)?
             }
-            NeighborhoodParams::Rectangle { .. } => Grid2D::new_filled(dimensions, 1.),
+            NeighborhoodParams::Rectangle { .. } | NeighborhoodParams::Circle { .. } => {
+                Grid2D::new_filled(dimensions, 1.)
+            }
         };
 
         Self::new(matrix)
@@ -122,6 +159,9 @@ pub enum NeighborhoodAggregateError {
 
     #[snafu(display("The kernel matrix must be rectangular"))]
     MatrixNotRectangular,
+
+    #[snafu(display("The percentile must be in the range [0, 1], but it is {percentile}"))]
+    InvalidPercentile { percentile: f64 },
 }
 
 #[typetag::serde]
@@ -148,6 +188,10 @@ impl RasterOperator for NeighborhoodAggregate {
             }
         );
 
+        if let AggregateFunctionParams::Percentile(percentile) = self.params.aggregate_function {
+            Percentile::new(percentile)?;
+        }
+
         let initialized_source = self
             .sources
             .initialize_sources(path.clone(), context)
@@ -180,24 +224,47 @@ pub struct InitializedNeighborhoodAggregate {
     tiling_specification: TilingSpecification,
 }
 
+impl InitializedNeighborhoodAggregate {
+    fn processor<P, A>(
+        &self,
+        source: BoxRasterQueryProcessor<P>,
+        aggregate_function: A,
+    ) -> TypedRasterQueryProcessor
+    where
+        P: Pixel,
+        f64: AsPrimitive<P>,
+        A: AggregateFunction + 'static,
+        BoxRasterQueryProcessor<P>: Into<TypedRasterQueryProcessor>,
+    {
+        NeighborhoodAggregateProcessor::new(
+            source,
+            self.tiling_specification,
+            self.neighborhood.clone(),
+            aggregate_function,
+        )
+        .boxed()
+        .into()
+    }
+}
+
 impl InitializedRasterOperator for InitializedNeighborhoodAggregate {
     fn query_processor(&self) -> Result<TypedRasterQueryProcessor> {
         let source_processor = self.raster_source.query_processor()?;
 
         let res = call_on_generic_raster_processor!(
-            source_processor, p => match &self.aggregate_function  {
-                AggregateFunctionParams::Sum => NeighborhoodAggregateProcessor::<_,_, Sum>::new(
-                        p,
-                        self.tiling_specification,
-                        self.neighborhood.clone(),
-                    ).boxed()
-                    .into(),
-                    AggregateFunctionParams::StandardDeviation => NeighborhoodAggregateProcessor::<_,_, StandardDeviation>::new(
-                        p,
-                        self.tiling_specification,
-                        self.neighborhood.clone(),
-                    ).boxed()
-                    .into(),
+            source_processor, p => match self.aggregate_function {
+                AggregateFunctionParams::Sum => self.processor(p, Sum),
+                AggregateFunctionParams::StandardDeviation => self.processor(p, StandardDeviation),
+                AggregateFunctionParams::Min => self.processor(p, Min),
+                AggregateFunctionParams::Max => self.processor(p, Max),
+                AggregateFunctionParams::Mean => self.processor(p, Mean),
+                AggregateFunctionParams::Median => self.processor(p, Percentile::median()),
+                AggregateFunctionParams::Percentile(percentile) => {
+                    self.processor(p, Percentile::new(percentile)?)
+                }
+                AggregateFunctionParams::Mode => self.processor(p, Mode),
+                AggregateFunctionParams::Range => self.processor(p, Range),
+                AggregateFunctionParams::Variety => self.processor(p, Variety),
             }
         );
 
@@ -225,7 +292,8 @@ pub struct NeighborhoodAggregateProcessor<Q, P, A> {
     source: Q,
     tiling_specification: TilingSpecification,
     neighborhood: Neighborhood,
-    _phantom_types: PhantomData<(P, A)>,
+    aggregate_function: A,
+    _phantom_types: PhantomData<P>,
 }
 
 impl<Q, P, A> NeighborhoodAggregateProcessor<Q, P, A>
@@ -237,11 +305,13 @@ where
         source: Q,
         tiling_specification: TilingSpecification,
         neighborhood: Neighborhood,
+        aggregate_function: A,
     ) -> Self {
         Self {
             source,
             tiling_specification,
             neighborhood,
+            aggregate_function,
             _phantom_types: PhantomData,
         }
     }
@@ -271,8 +341,11 @@ where
         ctx: &'a dyn QueryContext,
     ) -> Result<BoxStream<'a, Result<Self::Output>>> {
         stack_individual_aligned_raster_bands(&query, ctx, |query, ctx| async move {
-            let sub_query = NeighborhoodAggregateTileNeighborhood::<P, A>::new(
-                self.neighborhood.clone(),
+            let sub_query = NeighborhoodTileSubQuery::<P, _>::new(
+                NeighborhoodAggregateKernel {
+                    neighborhood: self.neighborhood.clone(),
+                    aggregate_function: self.aggregate_function.clone(),
+                },
                 self.tiling_specification,
             );
 
@@ -437,6 +510,129 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_serialization_percentile_circle() {
+        let params = NeighborhoodAggregateParams {
+            neighborhood: NeighborhoodParams::Circle { radius: 2 },
+            aggregate_function: AggregateFunctionParams::Percentile(0.9),
+        };
+
+        let serialized = serde_json::to_value(&params).unwrap();
+
+        assert_eq!(
+            serde_json::json!({
+                "neighborhood": {
+                    "type": "circle",
+                    "radius": 2
+                },
+                "aggregateFunction": {
+                    "percentile": 0.9
+                }
+            }),
+            serialized
+        );
+
+        serde_json::from_value::<NeighborhoodAggregateParams>(serialized).unwrap();
+
+        let params: NeighborhoodAggregateParams = serde_json::from_value(serde_json::json!({
+            "neighborhood": {
+                "type": "rectangle",
+                "dimensions": [3, 3]
+            },
+            "aggregateFunction": "variety"
+        }))
+        .unwrap();
+
+        assert!(matches!(
+            params.aggregate_function,
+            AggregateFunctionParams::Variety
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_percentile() {
+        let exe_ctx = MockExecutionContext::test_default();
+
+        let result = NeighborhoodAggregate {
+            params: NeighborhoodAggregateParams {
+                neighborhood: NeighborhoodParams::Rectangle { dimensions: [3, 3] },
+                aggregate_function: AggregateFunctionParams::Percentile(1.5),
+            },
+            sources: SingleRasterSource {
+                raster: make_raster(),
+            },
+        }
+        .boxed()
+        .initialize(WorkflowOperatorPath::initialize_root(), &exe_ctx)
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(crate::error::Error::RasterKernel {
+                source: NeighborhoodAggregateError::InvalidPercentile { .. }
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_median_in_circle() {
+        let exe_ctx = MockExecutionContext::new_with_tiling_spec(TilingSpecification::new(
+            (0., 0.).into(),
+            [3, 3].into(),
+        ));
+
+        let operator = NeighborhoodAggregate {
+            params: NeighborhoodAggregateParams {
+                neighborhood: NeighborhoodParams::Circle { radius: 1 },
+                aggregate_function: AggregateFunctionParams::Median,
+            },
+            sources: SingleRasterSource {
+                raster: make_raster(),
+            },
+        }
+        .boxed()
+        .initialize(WorkflowOperatorPath::initialize_root(), &exe_ctx)
+        .await
+        .unwrap();
+
+        let processor = operator.query_processor().unwrap().get_i8().unwrap();
+
+        let query_rect = RasterQueryRectangle {
+            spatial_bounds: SpatialPartition2D::new((0., 3.).into(), (6., 0.).into()).unwrap(),
+            time_interval: TimeInterval::new_unchecked(0, 10),
+            spatial_resolution: SpatialResolution::one(),
+            attributes: BandSelection::first(),
+        };
+        let query_ctx = MockQueryContext::test_default();
+
+        let result_stream = processor.query(query_rect, &query_ctx).await.unwrap();
+
+        let result: Vec<Result<RasterTile2D<i8>>> = result_stream.collect().await;
+        let result = result.into_iter().collect::<Result<Vec<_>>>().unwrap();
+
+        // the circle with radius 1 consists of the pixel and its four direct neighbors,
+        // pixels outside of the raster are NODATA and thus ignored
+        let data = [
+            vec![2, 2, 3, 7, 8, 9, 13, 13, 14],
+            vec![4, 5, 6, 10, 11, 11, 15, 16, 17],
+        ];
+
+        assert_eq!(result.len(), 2);
+
+        for (tile, data) in result.into_iter().zip(data) {
+            let tile = tile.into_materialized_tile();
+            assert_eq!(tile.time, TimeInterval::new_unchecked(0, 10));
+            assert_eq!(tile.grid_array.inner_grid.data, data);
+            assert!(
+                tile.grid_array
+                    .validity_mask
+                    .data
+                    .iter()
+                    .all(|valid| *valid)
+            );
+        }
+    }
+
     #[tokio::test]
     async fn test_mean_convolution() {
         let exe_ctx = MockExecutionContext::new_with_tiling_spec(TilingSpecification::new(
@@ -21,6 +21,72 @@ use rayon::ThreadPool;
 use std::{marker::PhantomData, sync::Arc};
 use tokio::task::JoinHandle;
 
+/// A kernel that computes each output pixel from the input pixels within its radii around it
+pub trait NeighborhoodKernel<P: Pixel>: Clone + Send + Sync + 'static {
+    fn x_radius(&self) -> usize;
+
+    fn y_radius(&self) -> usize;
+
+    /// Compute the output pixel at `pixel` of the output tile `info_out`.
+    /// Its neighborhood starts at the same grid index of the enlarged `input` tile.
+    fn apply(
+        &self,
+        input: &RasterTile2D<P>,
+        pixel: GridIdx2D,
+        info_out: &TileInformation,
+    ) -> Option<P>;
+}
+
+/// Applies an aggregate function to the weighted values of a neighborhood
+#[derive(Debug, Clone)]
+pub struct NeighborhoodAggregateKernel<A> {
+    pub neighborhood: Neighborhood,
+    pub aggregate_function: A,
+}
+
+impl<P, A> NeighborhoodKernel<P> for NeighborhoodAggregateKernel<A>
+where
+    P: Pixel,
+    f64: AsPrimitive<P>,
+    A: AggregateFunction + 'static,
+{
+    fn x_radius(&self) -> usize {
+        self.neighborhood.x_radius()
+    }
+
+    fn y_radius(&self) -> usize {
+        self.neighborhood.y_radius()
+    }
+
+    fn apply(
+        &self,
+        input: &RasterTile2D<P>,
+        pixel: GridIdx2D,
+        _info_out: &TileInformation,
+    ) -> Option<P> {
+        let GridIdx([y, x]) = pixel;
+
+        let mut neighborhood_matrix =
+            Vec::<Option<f64>>::with_capacity(self.neighborhood.matrix().number_of_elements());
+
+        let y_stop = y + self.neighborhood.y_width() as isize;
+        let x_stop = x + self.neighborhood.x_width() as isize;
+        // copy row-by-row all pixels in x direction into kernel matrix
+        for y_index in y..y_stop {
+            for x_index in x..x_stop {
+                neighborhood_matrix.push(
+                    input
+                        .get_at_grid_index_unchecked([y_index, x_index])
+                        .map(AsPrimitive::as_),
+                );
+            }
+        }
+
+        self.aggregate_function
+            .apply(&self.neighborhood.apply(neighborhood_matrix))
+    }
+}
+
 /// A sub-query aggregator that queries for each output tile an enlarged input tiles.
 /// This means itself plus parts of the 8 surrounding tiles.
 ///
@@ -40,33 +106,32 @@ use tokio::task::JoinHandle;
 /// It then applies a kernel function to each pixel and its surrounding.
 ///
 #[derive(Debug, Clone)]
-pub struct NeighborhoodAggregateTileNeighborhood<P, A> {
-    neighborhood: Neighborhood,
+pub struct NeighborhoodTileSubQuery<P, K> {
+    kernel: K,
     tiling_specification: TilingSpecification,
-    _phantom_types: PhantomData<(P, A)>,
+    _phantom_types: PhantomData<P>,
 }
 
-impl<P, A> NeighborhoodAggregateTileNeighborhood<P, A> {
-    pub fn new(neighborhood: Neighborhood, tiling_specification: TilingSpecification) -> Self {
+impl<P, K> NeighborhoodTileSubQuery<P, K> {
+    pub fn new(kernel: K, tiling_specification: TilingSpecification) -> Self {
         Self {
-            neighborhood,
+            kernel,
             tiling_specification,
             _phantom_types: PhantomData,
         }
     }
 }
 
-impl<'a, P, A> SubQueryTileAggregator<'a, P> for NeighborhoodAggregateTileNeighborhood<P, A>
+impl<'a, P, K> SubQueryTileAggregator<'a, P> for NeighborhoodTileSubQuery<P, K>
 where
     P: Pixel,
-    f64: AsPrimitive<P>,
-    A: AggregateFunction + 'static,
+    K: NeighborhoodKernel<P>,
 {
-    type FoldFuture = FoldFuture<P, A>;
+    type FoldFuture = FoldFuture<P, K>;
 
-    type FoldMethod = fn(NeighborhoodAggregateAccu<P, A>, RasterTile2D<P>) -> Self::FoldFuture;
+    type FoldMethod = fn(NeighborhoodAccu<P, K>, RasterTile2D<P>) -> Self::FoldFuture;
 
-    type TileAccu = NeighborhoodAggregateAccu<P, A>;
+    type TileAccu = NeighborhoodAccu<P, K>;
     type TileAccuFuture = BoxFuture<'a, Result<Self::TileAccu>>;
 
     /// Create an enlarged tile to store the values of the neighborhood
@@ -78,15 +143,9 @@ where
     ) -> Self::TileAccuFuture {
         let pool = pool.clone();
         let tiling_specification = self.tiling_specification;
-        let neighborhood = self.neighborhood.clone();
+        let kernel = self.kernel.clone();
         crate::util::spawn_blocking(move || {
-            create_enlarged_tile(
-                tile_info,
-                &query_rect,
-                pool,
-                tiling_specification,
-                neighborhood,
-            )
+            create_enlarged_tile(tile_info, &query_rect, pool, tiling_specification, kernel)
         })
         .map_err(From::from)
         .boxed()
@@ -103,8 +162,8 @@ where
         let spatial_bounds = tile_info.spatial_partition();
 
         let margin_pixels = Coordinate2D::from((
-            self.neighborhood.x_radius() as f64 * tile_info.global_geo_transform.x_pixel_size(),
-            self.neighborhood.y_radius() as f64 * tile_info.global_geo_transform.y_pixel_size(),
+            self.kernel.x_radius() as f64 * tile_info.global_geo_transform.x_pixel_size(),
+            self.kernel.y_radius() as f64 * tile_info.global_geo_transform.y_pixel_size(),
         ));
 
         let enlarged_spatial_bounds = SpatialPartition2D::new(
@@ -129,49 +188,25 @@ where
 }
 
 #[derive(Clone, Debug)]
-pub struct NeighborhoodAggregateAccu<P: Pixel, A> {
+pub struct NeighborhoodAccu<P: Pixel, K> {
     pub output_info: TileInformation,
     pub input_tile: RasterTile2D<P>,
     pub pool: Arc<ThreadPool>,
-    pub neighborhood: Neighborhood,
-    phantom_aggregate_fn: PhantomData<A>,
-}
-
-impl<P: Pixel, A> NeighborhoodAggregateAccu<P, A> {
-    pub fn new(
-        input_tile: RasterTile2D<P>,
-        output_info: TileInformation,
-        pool: Arc<ThreadPool>,
-        neighborhood: Neighborhood,
-    ) -> Self {
-        NeighborhoodAggregateAccu {
-            output_info,
-            input_tile,
-            pool,
-            neighborhood,
-            phantom_aggregate_fn: PhantomData,
-        }
-    }
+    pub kernel: K,
 }
 
 #[async_trait]
-impl<P, A> FoldTileAccu for NeighborhoodAggregateAccu<P, A>
+impl<P, K> FoldTileAccu for NeighborhoodAccu<P, K>
 where
     P: Pixel,
-    f64: AsPrimitive<P>,
-    A: AggregateFunction + 'static,
+    K: NeighborhoodKernel<P>,
 {
     type RasterType = P;
 
     /// now that we collected all the input tile pixels we perform the actual raster kernel
     async fn into_tile(self) -> Result<RasterTile2D<Self::RasterType>> {
-        let neighborhood = self.neighborhood.clone();
         let output_tile = crate::util::spawn_blocking_with_thread_pool(self.pool, move || {
-            apply_kernel_for_each_inner_pixel::<P, A>(
-                &self.input_tile,
-                &self.output_info,
-                &neighborhood,
-            )
+            apply_kernel_for_each_inner_pixel(&self.input_tile, &self.output_info, &self.kernel)
         })
         .await?;
 
@@ -184,47 +219,26 @@ where
 }
 
 /// Apply kernel function to all pixels of the inner input tile in the 9x9 grid
-fn apply_kernel_for_each_inner_pixel<P, A>(
+fn apply_kernel_for_each_inner_pixel<P, K>(
     input: &RasterTile2D<P>,
     info_out: &TileInformation,
-    neighborhood: &Neighborhood,
+    kernel: &K,
 ) -> RasterTile2D<P>
 where
     P: Pixel,
-    f64: AsPrimitive<P>,
-    A: AggregateFunction,
+    K: NeighborhoodKernel<P>,
 {
     if input.is_empty() {
         return RasterTile2D::new_with_tile_info(
             input.time,
             *info_out,
-            0, // TODO
+            input.band,
             EmptyGrid::new(info_out.tile_size_in_pixels).into(),
             CacheHint::max_duration(),
         );
     }
 
-    let map_fn = |gidx: GridIdx2D| {
-        let GridIdx([y, x]) = gidx;
-
-        let mut neighborhood_matrix =
-            Vec::<Option<f64>>::with_capacity(neighborhood.matrix().number_of_elements());
-
-        let y_stop = y + neighborhood.y_width() as isize;
-        let x_stop = x + neighborhood.x_width() as isize;
-        // copy row-by-row all pixels in x direction into kernel matrix
-        for y_index in y..y_stop {
-            for x_index in x..x_stop {
-                neighborhood_matrix.push(
-                    input
-                        .get_at_grid_index_unchecked([y_index, x_index])
-                        .map(AsPrimitive::as_),
-                );
-            }
-        }
-
-        A::apply(&neighborhood.apply(neighborhood_matrix))
-    };
+    let map_fn = |gidx: GridIdx2D| kernel.apply(input, gidx, info_out);
 
     // TODO: this will check for empty tiles. Change to MaskedGrid::from(…) to avoid this.
     let out_data = GridOrEmpty::from_index_fn_parallel(&info_out.tile_size_in_pixels, map_fn);
@@ -239,13 +253,13 @@ where
     )
 }
 
-fn create_enlarged_tile<P: Pixel, A: AggregateFunction>(
+fn create_enlarged_tile<P: Pixel, K: NeighborhoodKernel<P>>(
     tile_info: TileInformation,
     query_rect: &RasterQueryRectangle,
     pool: Arc<ThreadPool>,
     tiling_specification: TilingSpecification,
-    neighborhood: Neighborhood,
-) -> NeighborhoodAggregateAccu<P, A> {
+    kernel: K,
+) -> NeighborhoodAccu<P, K> {
     // create an accumulator as a single tile that fits all the input tiles + some margin for the kernel size
 
     let tiling = tiling_specification.strategy(
@@ -262,8 +276,8 @@ fn create_enlarged_tile<P: Pixel, A: AggregateFunction>(
     );
 
     let shape = [
-        tiling.tile_size_in_pixels.axis_size_y() + 2 * neighborhood.y_radius(),
-        tiling.tile_size_in_pixels.axis_size_x() + 2 * neighborhood.x_radius(),
+        tiling.tile_size_in_pixels.axis_size_y() + 2 * kernel.y_radius(),
+        tiling.tile_size_in_pixels.axis_size_x() + 2 * kernel.x_radius(),
     ];
 
     // create a non-aligned (w.r.t. the tiling specification) grid by setting the origin to the top-left of the tile and the tile-index to [0, 0]
@@ -278,22 +292,24 @@ fn create_enlarged_tile<P: Pixel, A: AggregateFunction>(
         CacheHint::max_duration(),
     );
 
-    NeighborhoodAggregateAccu::new(input_tile, tile_info, pool, neighborhood)
+    NeighborhoodAccu {
+        output_info: tile_info,
+        input_tile,
+        pool,
+        kernel,
+    }
 }
 
-type FoldFutureFn<P, F> = fn(
-    Result<Result<NeighborhoodAggregateAccu<P, F>>, tokio::task::JoinError>,
-) -> Result<NeighborhoodAggregateAccu<P, F>>;
-type FoldFuture<P, F> =
-    futures::future::Map<JoinHandle<Result<NeighborhoodAggregateAccu<P, F>>>, FoldFutureFn<P, F>>;
+type FoldFutureFn<P, K> = fn(
+    Result<Result<NeighborhoodAccu<P, K>>, tokio::task::JoinError>,
+) -> Result<NeighborhoodAccu<P, K>>;
+type FoldFuture<P, K> =
+    futures::future::Map<JoinHandle<Result<NeighborhoodAccu<P, K>>>, FoldFutureFn<P, K>>;
 
 /// Turn a result of results into a result
-fn flatten_result<P: Pixel, F: AggregateFunction>(
-    result: Result<Result<NeighborhoodAggregateAccu<P, F>>, tokio::task::JoinError>,
-) -> Result<NeighborhoodAggregateAccu<P, F>>
-where
-    f64: AsPrimitive<P>,
-{
+fn flatten_result<P: Pixel, K: NeighborhoodKernel<P>>(
+    result: Result<Result<NeighborhoodAccu<P, K>>, tokio::task::JoinError>,
+) -> Result<NeighborhoodAccu<P, K>> {
     match result {
         Ok(r) => r,
         Err(e) => Err(e.into()),
@@ -301,13 +317,10 @@ where
 }
 
 /// Merge, step by step, the 9 input tiles into the larger accumulator tile
-pub fn merge_tile_into_enlarged_tile<P: Pixel, F: AggregateFunction>(
-    mut accu: NeighborhoodAggregateAccu<P, F>,
+pub fn merge_tile_into_enlarged_tile<P: Pixel, K: NeighborhoodKernel<P>>(
+    mut accu: NeighborhoodAccu<P, K>,
     tile: RasterTile2D<P>,
-) -> Result<NeighborhoodAggregateAccu<P, F>>
-where
-    f64: AsPrimitive<P>,
-{
+) -> Result<NeighborhoodAccu<P, K>> {
     // get the time now because it is not known when the accu was created
     accu.input_tile.time = tile.time;
 
@@ -320,14 +333,10 @@ where
     let mut accu_input_tile = accu.input_tile.into_materialized_tile();
     accu_input_tile.blit(tile)?;
 
-    let accu_input_tile: RasterTile2D<P> = accu_input_tile.into();
-
-    Ok(NeighborhoodAggregateAccu::new(
-        accu_input_tile,
-        accu.output_info,
-        accu.pool,
-        accu.neighborhood,
-    ))
+    Ok(NeighborhoodAccu {
+        input_tile: accu_input_tile.into(),
+        ..accu
+    })
 }
 
 #[cfg(test)]
@@ -372,10 +381,15 @@ mod tests {
             attributes: BandSelection::first(),
         };
 
-        let aggregator = NeighborhoodAggregateTileNeighborhood::<u8, StandardDeviation>::new(
-            NeighborhoodParams::Rectangle { dimensions: [5, 5] }
-                .try_into()
-                .unwrap(),
+        let neighborhood: Neighborhood = NeighborhoodParams::Rectangle { dimensions: [5, 5] }
+            .try_into()
+            .unwrap();
+
+        let aggregator = NeighborhoodTileSubQuery::<u8, _>::new(
+            NeighborhoodAggregateKernel {
+                neighborhood: neighborhood.clone(),
+                aggregate_function: StandardDeviation,
+            },
             execution_context.tiling_specification,
         );
 
@@ -393,12 +407,15 @@ mod tests {
             SpatialPartition2D::new((-2., 514.).into(), (514., -2.).into()).unwrap()
         );
 
-        let accu = create_enlarged_tile::<u8, Sum>(
+        let accu = create_enlarged_tile::<u8, _>(
             tile_info,
             &tile_query_rectangle,
             execution_context.thread_pool.clone(),
             execution_context.tiling_specification,
-            aggregator.neighborhood,
+            NeighborhoodAggregateKernel {
+                neighborhood,
+                aggregate_function: Sum,
+            },
         );
 
         assert_eq!(tile_info.tile_size_in_pixels.axis_size(), [512, 512]);
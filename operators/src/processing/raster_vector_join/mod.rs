@@ -21,6 +21,8 @@ use geoengine_datatypes::raster::{Pixel, RasterDataType, RenameBands};
 use serde::{Deserialize, Serialize};
 use snafu::ensure;
 
+pub(crate) use self::util::{CoveredPixels, FeatureTimeSpanIter, PixelCoverCreator};
+
 use self::aggregator::{
     Aggregator, FirstValueFloatAggregator, FirstValueIntAggregator, MeanValueAggregator,
     TypedAggregator,
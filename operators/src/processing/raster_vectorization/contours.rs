@@ -0,0 +1,575 @@
+use std::collections::HashMap;
+
+use super::{
+    Mosaic, RasterVectorizationError, VALUE_COLUMN, error, first_band_measurement, query_mosaics,
+};
+use crate::engine::{
+    BoxRasterQueryProcessor, CanonicOperatorName, ExecutionContext, InitializedRasterOperator,
+    InitializedSources, InitializedVectorOperator, Operator, OperatorName, QueryContext,
+    QueryProcessor, SingleRasterSource, TypedVectorQueryProcessor, VectorColumnInfo,
+    VectorOperator, VectorQueryProcessor, VectorResultDescriptor, WorkflowOperatorPath,
+};
+use crate::util::Result;
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use geoengine_datatypes::collections::{MultiLineStringCollection, VectorDataType};
+use geoengine_datatypes::primitives::{
+    AxisAlignedRectangle, BoundingBox2D, ColumnSelection, Coordinate2D, FeatureData,
+    FeatureDataType, MultiLineString, VectorQueryRectangle,
+};
+use serde::{Deserialize, Serialize};
+use snafu::ensure;
+
+/// The `Contours` operator creates isolines of the raster values.
+///
+/// Each contour level becomes a feature with the level in the `value` column.
+/// The lines connect the pixel centers, so cells with a NODATA pixel at a corner are skipped.
+/// The first band of the source is contoured. Each time step is processed as a whole, so queries
+/// are limited to 4096 x 4096 pixels.
+pub type Contours = Operator<ContoursParams, SingleRasterSource>;
+
+impl OperatorName for Contours {
+    const TYPE_NAME: &'static str = "Contours";
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContoursParams {
+    pub levels: ContourLevels,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum ContourLevels {
+    /// Levels at `base + k * interval` for all integers `k` within the value range of the data
+    Interval {
+        interval: f64,
+        #[serde(default)]
+        base: f64,
+    },
+    /// Fixed levels
+    Levels { levels: Vec<f64> },
+}
+
+impl ContourLevels {
+    /// The maximum number of levels that an interval may result in
+    const MAX_LEVELS: usize = 1000;
+
+    fn validate(&self) -> Result<(), RasterVectorizationError> {
+        match self {
+            Self::Interval { interval, base } => {
+                ensure!(
+                    interval.is_finite() && *interval > 0.,
+                    error::InvalidContourInterval {
+                        interval: *interval
+                    }
+                );
+                ensure!(
+                    base.is_finite(),
+                    error::InvalidContourLevel { level: *base }
+                );
+            }
+            Self::Levels { levels } => {
+                ensure!(!levels.is_empty(), error::NoContourLevels);
+                if let Some(level) = levels.iter().find(|level| !level.is_finite()) {
+                    return Err(RasterVectorizationError::InvalidContourLevel { level: *level });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The sorted levels within the value range of the mosaic
+    fn levels(&self, mosaic: &Mosaic) -> Result<Vec<f64>, RasterVectorizationError> {
+        let (min, max) = mosaic
+            .values
+            .iter()
+            .flatten()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &value| {
+                (min.min(value), max.max(value))
+            });
+
+        if min > max {
+            // there is no data
+            return Ok(Vec::new());
+        }
+
+        let mut levels = match self {
+            Self::Interval { interval, base } => {
+                let first = ((min - base) / interval).ceil();
+                let last = ((max - base) / interval).floor();
+
+                ensure!(
+                    last - first < Self::MAX_LEVELS as f64,
+                    error::TooManyContourLevels {
+                        max_levels: Self::MAX_LEVELS
+                    }
+                );
+
+                (first as i64..=last as i64)
+                    .map(|k| base + k as f64 * interval)
+                    .collect()
+            }
+            Self::Levels { levels } => levels
+                .iter()
+                .copied()
+                .filter(|level| (min..=max).contains(level))
+                .collect::<Vec<_>>(),
+        };
+
+        levels.sort_unstable_by(f64::total_cmp);
+        levels.dedup_by(|a, b| a.total_cmp(b).is_eq());
+
+        Ok(levels)
+    }
+}
+
+#[typetag::serde]
+#[async_trait]
+impl VectorOperator for Contours {
+    async fn _initialize(
+        self: Box<Self>,
+        path: WorkflowOperatorPath,
+        context: &dyn ExecutionContext,
+    ) -> Result<Box<dyn InitializedVectorOperator>> {
+        let name = CanonicOperatorName::from(&self);
+
+        self.params.levels.validate()?;
+
+        let initialized_sources = self
+            .sources
+            .initialize_sources(path.clone(), context)
+            .await?;
+        let raster_source = initialized_sources.raster;
+        let in_desc = raster_source.result_descriptor();
+
+        let result_descriptor = VectorResultDescriptor {
+            data_type: VectorDataType::MultiLineString,
+            spatial_reference: in_desc.spatial_reference,
+            columns: [(
+                VALUE_COLUMN.to_string(),
+                VectorColumnInfo {
+                    data_type: FeatureDataType::Float,
+                    measurement: first_band_measurement(in_desc),
+                },
+            )]
+            .into_iter()
+            .collect(),
+            time: in_desc.time,
+            bbox: in_desc.bbox.map(|bbox| bbox.as_bbox()),
+        };
+
+        Ok(InitializedContours {
+            name,
+            path,
+            result_descriptor,
+            raster_source,
+            levels: self.params.levels,
+        }
+        .boxed())
+    }
+
+    span_fn!(Contours);
+}
+
+pub struct InitializedContours {
+    name: CanonicOperatorName,
+    path: WorkflowOperatorPath,
+    result_descriptor: VectorResultDescriptor,
+    raster_source: Box<dyn InitializedRasterOperator>,
+    levels: ContourLevels,
+}
+
+impl InitializedVectorOperator for InitializedContours {
+    fn result_descriptor(&self) -> &VectorResultDescriptor {
+        &self.result_descriptor
+    }
+
+    fn query_processor(&self) -> Result<TypedVectorQueryProcessor> {
+        Ok(TypedVectorQueryProcessor::MultiLineString(
+            ContoursProcessor {
+                source: self.raster_source.query_processor()?.into_f64(),
+                result_descriptor: self.result_descriptor.clone(),
+                levels: self.levels.clone(),
+            }
+            .boxed(),
+        ))
+    }
+
+    fn canonic_name(&self) -> CanonicOperatorName {
+        self.name.clone()
+    }
+
+    fn name(&self) -> &'static str {
+        Contours::TYPE_NAME
+    }
+
+    fn path(&self) -> WorkflowOperatorPath {
+        self.path.clone()
+    }
+}
+
+struct ContoursProcessor {
+    source: BoxRasterQueryProcessor<f64>,
+    result_descriptor: VectorResultDescriptor,
+    levels: ContourLevels,
+}
+
+#[async_trait]
+impl QueryProcessor for ContoursProcessor {
+    type Output = MultiLineStringCollection;
+    type SpatialBounds = BoundingBox2D;
+    type Selection = ColumnSelection;
+    type ResultDescription = VectorResultDescriptor;
+
+    async fn _query<'a>(
+        &'a self,
+        query: VectorQueryRectangle,
+        ctx: &'a dyn QueryContext,
+    ) -> Result<BoxStream<'a, Result<Self::Output>>> {
+        let mosaics = query_mosaics(self.source.as_ref(), &query, ctx).await?;
+
+        let collections = mosaics.and_then(move |mosaic| async move {
+            let levels = self.levels.levels(&mosaic)?;
+
+            crate::util::spawn_blocking_with_thread_pool(ctx.thread_pool().clone(), move || {
+                contour_collection(&mosaic, &levels)
+            })
+            .await?
+        });
+
+        Ok(collections.boxed())
+    }
+
+    fn result_descriptor(&self) -> &VectorResultDescriptor {
+        &self.result_descriptor
+    }
+}
+
+fn contour_collection(mosaic: &Mosaic, levels: &[f64]) -> Result<MultiLineStringCollection> {
+    let mut values = Vec::with_capacity(levels.len());
+    let mut lines = Vec::with_capacity(levels.len());
+
+    for &level in levels {
+        let contour = contour_lines(mosaic, level);
+
+        if contour.is_empty() {
+            continue;
+        }
+
+        values.push(level);
+        lines.push(MultiLineString::new(contour)?);
+    }
+
+    Ok(MultiLineStringCollection::from_data(
+        lines,
+        vec![mosaic.time; values.len()],
+        [(VALUE_COLUMN.to_string(), FeatureData::Float(values))]
+            .into_iter()
+            .collect(),
+        mosaic.cache_hint,
+    )?)
+}
+
+/// A point on the edge between two neighboring pixel centers where the contour crosses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum EdgePoint {
+    /// Between the pixel `(row, column)` and its right neighbor
+    Horizontal(usize, usize),
+    /// Between the pixel `(row, column)` and its lower neighbor
+    Vertical(usize, usize),
+}
+
+/// Create the contour lines of one level using marching squares on the grid of pixel centers
+fn contour_lines(mosaic: &Mosaic, level: f64) -> Vec<Vec<Coordinate2D>> {
+    let segments = contour_segments(mosaic, level);
+
+    let mut segments_at: HashMap<EdgePoint, Vec<usize>> = HashMap::new();
+    for (i, (a, b)) in segments.iter().enumerate() {
+        segments_at.entry(*a).or_default().push(i);
+        segments_at.entry(*b).or_default().push(i);
+    }
+
+    let is_line_end = |point: &EdgePoint| segments_at[point].len() == 1;
+
+    // start with the open lines, so that they are not traced from the middle
+    let starts = segments
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (a, b))| {
+            if is_line_end(a) {
+                Some((i, *a))
+            } else if is_line_end(b) {
+                Some((i, *b))
+            } else {
+                None
+            }
+        })
+        .chain(segments.iter().enumerate().map(|(i, (a, _))| (i, *a)))
+        .collect::<Vec<_>>();
+
+    let mut used = vec![false; segments.len()];
+    let mut lines = Vec::new();
+
+    for (first_segment, start) in starts {
+        if used[first_segment] {
+            continue;
+        }
+
+        let mut line = vec![edge_point_coordinate(mosaic, level, start)];
+
+        let (mut segment, mut point) = (first_segment, start);
+        loop {
+            used[segment] = true;
+
+            let (a, b) = segments[segment];
+            point = if a == point { b } else { a };
+            line.push(edge_point_coordinate(mosaic, level, point));
+
+            match segments_at[&point].iter().find(|&&next| !used[next]) {
+                Some(&next) => segment = next,
+                None => break,
+            }
+        }
+
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// The line segments of each cell between four pixel centers
+fn contour_segments(mosaic: &Mosaic, level: f64) -> Vec<(EdgePoint, EdgePoint)> {
+    let mut segments = Vec::new();
+
+    for row in 0..mosaic.rows.saturating_sub(1) {
+        for column in 0..mosaic.columns.saturating_sub(1) {
+            let (Some(top_left), Some(top_right), Some(bottom_right), Some(bottom_left)) = (
+                mosaic.get(row, column),
+                mosaic.get(row, column + 1),
+                mosaic.get(row + 1, column + 1),
+                mosaic.get(row + 1, column),
+            ) else {
+                continue;
+            };
+
+            let case = u8::from(top_left >= level) << 3
+                | u8::from(top_right >= level) << 2
+                | u8::from(bottom_right >= level) << 1
+                | u8::from(bottom_left >= level);
+
+            let top = EdgePoint::Horizontal(row, column);
+            let bottom = EdgePoint::Horizontal(row + 1, column);
+            let left = EdgePoint::Vertical(row, column);
+            let right = EdgePoint::Vertical(row, column + 1);
+
+            // resolve the saddles by the mean of the corners
+            let center_above = (top_left + top_right + bottom_right + bottom_left) / 4. >= level;
+
+            match case {
+                1 | 14 => segments.push((left, bottom)),
+                2 | 13 => segments.push((bottom, right)),
+                3 | 12 => segments.push((left, right)),
+                4 | 11 => segments.push((top, right)),
+                6 | 9 => segments.push((top, bottom)),
+                7 | 8 => segments.push((left, top)),
+                5 | 10 => {
+                    // for case 5, the top right and bottom left corners are above the level
+                    let cut_top_right_and_bottom_left = (case == 5) != center_above;
+                    if cut_top_right_and_bottom_left {
+                        segments.push((top, right));
+                        segments.push((left, bottom));
+                    } else {
+                        segments.push((left, top));
+                        segments.push((bottom, right));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    segments
+}
+
+/// The coordinate of the contour crossing, linearly interpolated between the pixel centers
+fn edge_point_coordinate(mosaic: &Mosaic, level: f64, point: EdgePoint) -> Coordinate2D {
+    let fraction = |a: Option<f64>, b: Option<f64>| match (a, b) {
+        (Some(a), Some(b)) if b - a != 0. => (level - a) / (b - a),
+        _ => 0.5,
+    };
+
+    let (row, column) = match point {
+        EdgePoint::Horizontal(row, column) => (
+            row as f64,
+            column as f64 + fraction(mosaic.get(row, column), mosaic.get(row, column + 1)),
+        ),
+        EdgePoint::Vertical(row, column) => (
+            row as f64 + fraction(mosaic.get(row, column), mosaic.get(row + 1, column)),
+            column as f64,
+        ),
+    };
+
+    mosaic.coordinate(row + 0.5, column + 0.5)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{make_mosaic, make_raster};
+    use super::*;
+    use crate::engine::{MockExecutionContext, MockQueryContext};
+    use geoengine_datatypes::collections::{FeatureCollectionInfos, IntoGeometryIterator};
+    use geoengine_datatypes::primitives::{
+        FeatureDataRef, MultiLineStringAccess, SpatialResolution, TimeInterval,
+    };
+    use geoengine_datatypes::raster::TilingSpecification;
+    use geoengine_datatypes::util::test::TestDefault;
+
+    #[test]
+    fn it_computes_levels() {
+        let mosaic = make_mosaic(1, 3, vec![Some(0.2), None, Some(2.7)]);
+
+        let levels = ContourLevels::Interval {
+            interval: 1.,
+            base: 0.5,
+        }
+        .levels(&mosaic)
+        .unwrap();
+        assert_eq!(levels, vec![0.5, 1.5, 2.5]);
+
+        let levels = ContourLevels::Levels {
+            levels: vec![3., 1., 0., 1.],
+        }
+        .levels(&mosaic)
+        .unwrap();
+        assert_eq!(levels, vec![1.]);
+
+        assert!(matches!(
+            ContourLevels::Interval {
+                interval: 1e-6,
+                base: 0.
+            }
+            .levels(&mosaic),
+            Err(RasterVectorizationError::TooManyContourLevels { .. })
+        ));
+
+        assert!(
+            ContourLevels::Interval {
+                interval: 0.,
+                base: 0.
+            }
+            .validate()
+            .is_err()
+        );
+        assert!(ContourLevels::Levels { levels: vec![] }.validate().is_err());
+    }
+
+    #[test]
+    fn it_closes_rings() {
+        let mosaic = make_mosaic(
+            3,
+            3,
+            [0., 0., 0., 0., 1., 0., 0., 0., 0.]
+                .into_iter()
+                .map(Some)
+                .collect(),
+        );
+
+        let lines = contour_lines(&mosaic, 0.5);
+
+        assert_eq!(lines.len(), 1);
+        let ring = &lines[0];
+        assert_eq!(ring.len(), 5);
+        assert_eq!(ring.first(), ring.last());
+
+        // the diamond around the center pixel at (1.5, -1.5)
+        for coordinate in &ring[..4] {
+            let distance = (coordinate.x - 1.5).abs() + (coordinate.y + 1.5).abs();
+            assert!((distance - 0.5).abs() < f64::EPSILON);
+        }
+    }
+
+    #[test]
+    fn it_resolves_saddles() {
+        let mosaic = make_mosaic(2, 2, vec![Some(1.), Some(0.), Some(0.), Some(1.)]);
+
+        // the mean is above the level, so the high corners are connected
+        let lines = contour_lines(&mosaic, 0.4);
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| line.len() == 2));
+
+        let segments = contour_segments(&mosaic, 0.4);
+        assert!(segments.contains(&(EdgePoint::Horizontal(0, 0), EdgePoint::Vertical(0, 1))));
+
+        let segments = contour_segments(&mosaic, 0.6);
+        assert!(segments.contains(&(EdgePoint::Vertical(0, 0), EdgePoint::Horizontal(0, 0))));
+    }
+
+    #[tokio::test]
+    async fn it_contours_across_tiles() {
+        let contours = Contours {
+            params: ContoursParams {
+                levels: ContourLevels::Interval {
+                    interval: 1.,
+                    base: 0.5,
+                },
+            },
+            sources: SingleRasterSource {
+                raster: make_raster(vec![0, 1, 0, 1], vec![2, 3, 2, 3]),
+            },
+        }
+        .boxed()
+        .initialize(
+            WorkflowOperatorPath::initialize_root(),
+            &MockExecutionContext::new_with_tiling_spec(TilingSpecification::new(
+                (0., 0.).into(),
+                [2, 2].into(),
+            )),
+        )
+        .await
+        .unwrap();
+
+        let processor = contours
+            .query_processor()
+            .unwrap()
+            .multi_line_string()
+            .unwrap();
+
+        let collections = processor
+            .query(
+                VectorQueryRectangle {
+                    spatial_bounds: BoundingBox2D::new((0., 0.).into(), (4., 2.).into()).unwrap(),
+                    time_interval: TimeInterval::new_unchecked(0, 10),
+                    spatial_resolution: SpatialResolution::one(),
+                    attributes: ColumnSelection::all(),
+                },
+                &MockQueryContext::test_default(),
+            )
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(collections.len(), 1);
+        let collection = &collections[0];
+
+        assert_eq!(collection.len(), 3);
+
+        let FeatureDataRef::Float(values) = collection.data(VALUE_COLUMN).unwrap() else {
+            panic!("expected float values");
+        };
+        assert_eq!(values.as_ref(), &[0.5, 1.5, 2.5]);
+
+        // the line of 1.5 lies between the tiles
+        for (line, level) in collection.geometries().zip(values.as_ref()) {
+            let line = MultiLineString::from(line);
+            assert_eq!(line.lines().len(), 1);
+            for coordinate in &line.lines()[0] {
+                assert!((coordinate.x - (level + 0.5)).abs() < f64::EPSILON);
+            }
+        }
+    }
+}
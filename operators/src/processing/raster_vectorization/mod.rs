@@ -0,0 +1,272 @@
+mod contours;
+mod polygonize;
+
+pub use contours::{ContourLevels, Contours, ContoursParams};
+pub use polygonize::{Polygonize, PolygonizeParams};
+
+use crate::adapters::RasterStreamExt;
+use crate::engine::{QueryContext, RasterQueryProcessor, RasterResultDescriptor};
+use crate::util::Result;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use geoengine_datatypes::primitives::{
+    AxisAlignedRectangle, BandSelection, CacheHint, Coordinate2D, Measurement,
+    RasterQueryRectangle, TimeInterval, VectorQueryRectangle,
+};
+use geoengine_datatypes::raster::{GridIdx, GridOrEmpty, GridSize, RasterTile2D};
+use snafu::{Snafu, ensure};
+
+/// The name of the column that holds the pixel value or the contour level
+const VALUE_COLUMN: &str = "value";
+
+/// The maximum number of pixels of a query, since each time step is vectorized as a whole in memory
+const MAX_PIXELS: usize = 4096 * 4096;
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)), context(suffix(false)), module(error))]
+pub enum RasterVectorizationError {
+    #[snafu(display("The contour interval must be a positive number, but it is {interval}"))]
+    InvalidContourInterval { interval: f64 },
+
+    #[snafu(display("The contour levels must be finite numbers, but there is {level}"))]
+    InvalidContourLevel { level: f64 },
+
+    #[snafu(display("At least one contour level is required"))]
+    NoContourLevels,
+
+    #[snafu(display(
+        "The contour interval results in more than {max_levels} levels for the queried data"
+    ))]
+    TooManyContourLevels { max_levels: usize },
+
+    #[snafu(display(
+        "The query covers {pixels} pixels, but at most {max_pixels} pixels can be vectorized at once. Use a smaller extent or a coarser resolution."
+    ))]
+    TooManyPixels { pixels: f64, max_pixels: usize },
+}
+
+/// The measurement of the first band, which is the one that is vectorized
+fn first_band_measurement(result_descriptor: &RasterResultDescriptor) -> Measurement {
+    result_descriptor
+        .bands
+        .first()
+        .map(|band| band.measurement.clone())
+        .unwrap_or_default()
+}
+
+/// Query the first band of the raster and merge the tiles of each time step into a `Mosaic`
+async fn query_mosaics<'a>(
+    source: &'a dyn RasterQueryProcessor<RasterType = f64>,
+    query: &VectorQueryRectangle,
+    ctx: &'a dyn QueryContext,
+) -> Result<BoxStream<'a, Result<Mosaic>>> {
+    let pixels = (query.spatial_bounds.size_x() / query.spatial_resolution.x).ceil()
+        * (query.spatial_bounds.size_y() / query.spatial_resolution.y).ceil();
+    ensure!(
+        pixels <= MAX_PIXELS as f64,
+        error::TooManyPixels {
+            pixels,
+            max_pixels: MAX_PIXELS
+        }
+    );
+
+    let raster_query = RasterQueryRectangle::from_qrect_and_bands(query, BandSelection::first());
+
+    let tiles = source.raster_query(raster_query, ctx).await?;
+
+    let mosaics = tiles
+        .time_multi_fold(
+            || Ok(Vec::new()),
+            |tiles: Result<Vec<RasterTile2D<f64>>>, tile| async move {
+                let mut tiles = tiles?;
+                tiles.push(tile?);
+                Ok(tiles)
+            },
+        )
+        .and_then(move |tiles| async move {
+            crate::util::spawn_blocking_with_thread_pool(ctx.thread_pool().clone(), move || {
+                Mosaic::from_tiles(tiles)
+            })
+            .await
+            .map_err(Into::into)
+        })
+        .try_filter_map(|mosaic| async move { Ok(mosaic) });
+
+    Ok(mosaics.boxed())
+}
+
+/// The tiles of one time step merged into a single grid.
+/// Regions and lines are traced on the whole grid, so they are merged across tile boundaries.
+struct Mosaic {
+    values: Vec<Option<f64>>,
+    rows: usize,
+    columns: usize,
+    /// The upper left corner of the upper left pixel
+    origin: Coordinate2D,
+    x_pixel_size: f64,
+    y_pixel_size: f64,
+    time: TimeInterval,
+    cache_hint: CacheHint,
+}
+
+impl Mosaic {
+    /// Merge the tiles of one time step. Returns `None` if there are no tiles.
+    fn from_tiles(tiles: Vec<RasterTile2D<f64>>) -> Option<Self> {
+        let first = tiles.first()?;
+
+        let tile_shape = first.tile_information().tile_size_in_pixels;
+        let (tile_rows, tile_columns) = (tile_shape.axis_size_y(), tile_shape.axis_size_x());
+
+        let GridIdx([first_y, first_x]) = first.tile_position;
+        let (mut min_y, mut min_x, mut max_y, mut max_x) = (first_y, first_x, first_y, first_x);
+        for tile in &tiles {
+            let GridIdx([y, x]) = tile.tile_position;
+            (min_y, min_x) = (min_y.min(y), min_x.min(x));
+            (max_y, max_x) = (max_y.max(y), max_x.max(x));
+        }
+
+        let rows = (max_y - min_y + 1) as usize * tile_rows;
+        let columns = (max_x - min_x + 1) as usize * tile_columns;
+
+        let origin = first
+            .global_geo_transform
+            .grid_idx_to_pixel_upper_left_coordinate_2d(GridIdx([
+                min_y * tile_rows as isize,
+                min_x * tile_columns as isize,
+            ]));
+
+        let mut mosaic = Self {
+            values: vec![None; rows * columns],
+            rows,
+            columns,
+            origin,
+            x_pixel_size: first.global_geo_transform.x_pixel_size(),
+            y_pixel_size: first.global_geo_transform.y_pixel_size(),
+            time: first.time,
+            cache_hint: first.cache_hint,
+        };
+
+        for tile in tiles {
+            mosaic.cache_hint.merge_with(&tile.cache_hint);
+
+            let GridOrEmpty::Grid(grid) = tile.grid_array else {
+                continue;
+            };
+
+            let GridIdx([y, x]) = tile.tile_position;
+            let row_offset = (y - min_y) as usize * tile_rows;
+            let column_offset = (x - min_x) as usize * tile_columns;
+
+            for (i, value) in grid.masked_element_deref_iterator().enumerate() {
+                let (row, column) = (
+                    row_offset + i / tile_columns,
+                    column_offset + i % tile_columns,
+                );
+                mosaic.values[row * columns + column] = value;
+            }
+        }
+
+        Some(mosaic)
+    }
+
+    fn get(&self, row: usize, column: usize) -> Option<f64> {
+        self.values[row * self.columns + column]
+    }
+
+    /// The coordinate of the fractional pixel position, e.g., `(0., 0.)` is the upper left corner
+    /// of the mosaic and `(0.5, 0.5)` is the center of the upper left pixel.
+    fn coordinate(&self, row: f64, column: f64) -> Coordinate2D {
+        Coordinate2D::new(
+            self.origin.x + column * self.x_pixel_size,
+            self.origin.y + row * self.y_pixel_size,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{RasterBandDescriptors, RasterOperator};
+    use crate::mock::{MockRasterSource, MockRasterSourceParams};
+    use geoengine_datatypes::raster::{Grid2D, RasterDataType, TileInformation};
+    use geoengine_datatypes::spatial_reference::SpatialReference;
+    use geoengine_datatypes::util::test::TestDefault;
+
+    /// Two horizontally adjacent 2x2 tiles with the given values
+    pub(super) fn make_raster(left: Vec<u8>, right: Vec<u8>) -> Box<dyn RasterOperator> {
+        let tile = |x: isize, data: Vec<u8>| {
+            RasterTile2D::new_with_tile_info(
+                TimeInterval::new_unchecked(0, 10),
+                TileInformation {
+                    global_tile_position: [-1, x].into(),
+                    tile_size_in_pixels: [2, 2].into(),
+                    global_geo_transform: TestDefault::test_default(),
+                },
+                0,
+                GridOrEmpty::from(Grid2D::new([2, 2].into(), data).unwrap()),
+                CacheHint::default(),
+            )
+        };
+
+        MockRasterSource {
+            params: MockRasterSourceParams {
+                data: vec![tile(0, left), tile(1, right)],
+                result_descriptor: RasterResultDescriptor {
+                    data_type: RasterDataType::U8,
+                    spatial_reference: SpatialReference::epsg_4326().into(),
+                    time: None,
+                    bbox: None,
+                    resolution: None,
+                    bands: RasterBandDescriptors::new_single_band(),
+                },
+            },
+        }
+        .boxed()
+    }
+
+    pub(super) fn make_mosaic(rows: usize, columns: usize, values: Vec<Option<f64>>) -> Mosaic {
+        Mosaic {
+            values,
+            rows,
+            columns,
+            origin: Coordinate2D::new(0., 0.),
+            x_pixel_size: 1.,
+            y_pixel_size: -1.,
+            time: TimeInterval::default(),
+            cache_hint: CacheHint::default(),
+        }
+    }
+
+    #[test]
+    fn it_merges_tiles() {
+        let tile = |x: isize, data: Vec<f64>| {
+            RasterTile2D::new_with_tile_info(
+                TimeInterval::default(),
+                TileInformation {
+                    global_tile_position: [-1, x].into(),
+                    tile_size_in_pixels: [2, 2].into(),
+                    global_geo_transform: TestDefault::test_default(),
+                },
+                0,
+                GridOrEmpty::from(Grid2D::new([2, 2].into(), data).unwrap()),
+                CacheHint::default(),
+            )
+        };
+
+        let mosaic = Mosaic::from_tiles(vec![
+            tile(1, vec![3., 4., 7., 8.]),
+            tile(0, vec![1., 2., 5., 6.]),
+        ])
+        .unwrap();
+
+        assert_eq!((mosaic.rows, mosaic.columns), (2, 4));
+        assert_eq!(mosaic.origin, Coordinate2D::new(0., 2.));
+        assert_eq!(
+            mosaic.values,
+            (1..=8).map(|v| Some(f64::from(v))).collect::<Vec<_>>()
+        );
+        assert_eq!(mosaic.coordinate(0.5, 3.5), Coordinate2D::new(3.5, 1.5));
+
+        assert!(Mosaic::from_tiles(vec![]).is_none());
+    }
+}
@@ -0,0 +1,726 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::{Mosaic, VALUE_COLUMN, first_band_measurement, query_mosaics};
+use crate::engine::{
+    BoxRasterQueryProcessor, CanonicOperatorName, ExecutionContext, InitializedRasterOperator,
+    InitializedSources, InitializedVectorOperator, Operator, OperatorName, QueryContext,
+    QueryProcessor, SingleRasterSource, TypedVectorQueryProcessor, VectorColumnInfo,
+    VectorOperator, VectorQueryProcessor, VectorResultDescriptor, WorkflowOperatorPath,
+};
+use crate::util::Result;
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use geoengine_datatypes::collections::{MultiPolygonCollection, VectorDataType};
+use geoengine_datatypes::primitives::{
+    AxisAlignedRectangle, BoundingBox2D, ColumnSelection, Coordinate2D, FeatureData,
+    FeatureDataType, MultiPolygon, VectorQueryRectangle,
+};
+use geoengine_datatypes::raster::RasterDataType;
+use serde::{Deserialize, Serialize};
+
+/// The `Polygonize` operator turns connected regions of equal pixel values into polygons.
+///
+/// Pixels are connected if they share an edge. Each region becomes a feature with the pixel value
+/// in the `value` column. NODATA pixels are not polygonized. The operator is meant for classified
+/// rasters like land cover, as continuous rasters result in a feature for nearly every pixel.
+/// The first band of the source is polygonized. Each time step is processed as a whole, so queries
+/// are limited to 4096 x 4096 pixels.
+pub type Polygonize = Operator<PolygonizeParams, SingleRasterSource>;
+
+impl OperatorName for Polygonize {
+    const TYPE_NAME: &'static str = "Polygonize";
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PolygonizeParams {}
+
+#[typetag::serde]
+#[async_trait]
+impl VectorOperator for Polygonize {
+    async fn _initialize(
+        self: Box<Self>,
+        path: WorkflowOperatorPath,
+        context: &dyn ExecutionContext,
+    ) -> Result<Box<dyn InitializedVectorOperator>> {
+        let name = CanonicOperatorName::from(&self);
+
+        let initialized_sources = self
+            .sources
+            .initialize_sources(path.clone(), context)
+            .await?;
+        let raster_source = initialized_sources.raster;
+        let in_desc = raster_source.result_descriptor();
+
+        let integer_values =
+            !matches!(in_desc.data_type, RasterDataType::F32 | RasterDataType::F64);
+
+        let result_descriptor = VectorResultDescriptor {
+            data_type: VectorDataType::MultiPolygon,
+            spatial_reference: in_desc.spatial_reference,
+            columns: [(
+                VALUE_COLUMN.to_string(),
+                VectorColumnInfo {
+                    data_type: if integer_values {
+                        FeatureDataType::Int
+                    } else {
+                        FeatureDataType::Float
+                    },
+                    measurement: first_band_measurement(in_desc),
+                },
+            )]
+            .into_iter()
+            .collect(),
+            time: in_desc.time,
+            bbox: in_desc.bbox.map(|bbox| bbox.as_bbox()),
+        };
+
+        Ok(InitializedPolygonize {
+            name,
+            path,
+            result_descriptor,
+            raster_source,
+            integer_values,
+        }
+        .boxed())
+    }
+
+    span_fn!(Polygonize);
+}
+
+pub struct InitializedPolygonize {
+    name: CanonicOperatorName,
+    path: WorkflowOperatorPath,
+    result_descriptor: VectorResultDescriptor,
+    raster_source: Box<dyn InitializedRasterOperator>,
+    integer_values: bool,
+}
+
+impl InitializedVectorOperator for InitializedPolygonize {
+    fn result_descriptor(&self) -> &VectorResultDescriptor {
+        &self.result_descriptor
+    }
+
+    fn query_processor(&self) -> Result<TypedVectorQueryProcessor> {
+        Ok(TypedVectorQueryProcessor::MultiPolygon(
+            PolygonizeProcessor {
+                source: self.raster_source.query_processor()?.into_f64(),
+                result_descriptor: self.result_descriptor.clone(),
+                integer_values: self.integer_values,
+            }
+            .boxed(),
+        ))
+    }
+
+    fn canonic_name(&self) -> CanonicOperatorName {
+        self.name.clone()
+    }
+
+    fn name(&self) -> &'static str {
+        Polygonize::TYPE_NAME
+    }
+
+    fn path(&self) -> WorkflowOperatorPath {
+        self.path.clone()
+    }
+}
+
+struct PolygonizeProcessor {
+    source: BoxRasterQueryProcessor<f64>,
+    result_descriptor: VectorResultDescriptor,
+    integer_values: bool,
+}
+
+#[async_trait]
+impl QueryProcessor for PolygonizeProcessor {
+    type Output = MultiPolygonCollection;
+    type SpatialBounds = BoundingBox2D;
+    type Selection = ColumnSelection;
+    type ResultDescription = VectorResultDescriptor;
+
+    async fn _query<'a>(
+        &'a self,
+        query: VectorQueryRectangle,
+        ctx: &'a dyn QueryContext,
+    ) -> Result<BoxStream<'a, Result<Self::Output>>> {
+        let mosaics = query_mosaics(self.source.as_ref(), &query, ctx).await?;
+
+        let integer_values = self.integer_values;
+
+        let collections = mosaics.and_then(move |mosaic| async move {
+            crate::util::spawn_blocking_with_thread_pool(ctx.thread_pool().clone(), move || {
+                polygon_collection(&mosaic, integer_values)
+            })
+            .await?
+        });
+
+        Ok(collections.boxed())
+    }
+
+    fn result_descriptor(&self) -> &VectorResultDescriptor {
+        &self.result_descriptor
+    }
+}
+
+fn polygon_collection(mosaic: &Mosaic, integer_values: bool) -> Result<MultiPolygonCollection> {
+    let (values, polygons): (Vec<f64>, Vec<MultiPolygon>) = polygonize(mosaic)?.into_iter().unzip();
+    let number_of_features = values.len();
+
+    let data = if integer_values {
+        FeatureData::Int(values.into_iter().map(|value| value as i64).collect())
+    } else {
+        FeatureData::Float(values)
+    };
+
+    Ok(MultiPolygonCollection::from_data(
+        polygons,
+        vec![mosaic.time; number_of_features],
+        [(VALUE_COLUMN.to_string(), data)].into_iter().collect(),
+        mosaic.cache_hint,
+    )?)
+}
+
+/// A corner of the pixel grid as (row, column)
+type Vertex = (usize, usize);
+
+/// The direction of a boundary edge in the pixel grid, where rows grow downwards
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    East,
+    South,
+    West,
+    North,
+}
+
+impl Direction {
+    fn step(self, (row, column): Vertex) -> Vertex {
+        match self {
+            Self::East => (row, column + 1),
+            Self::South => (row + 1, column),
+            Self::West => (row, column - 1),
+            Self::North => (row - 1, column),
+        }
+    }
+
+    fn right(self) -> Self {
+        match self {
+            Self::East => Self::South,
+            Self::South => Self::West,
+            Self::West => Self::North,
+            Self::North => Self::East,
+        }
+    }
+
+    fn left(self) -> Self {
+        self.right().right().right()
+    }
+}
+
+/// Trace the boundaries of the connected regions of equal value.
+/// Returns the value and the polygon of each region.
+fn polygonize(mosaic: &Mosaic) -> Result<Vec<(f64, MultiPolygon)>> {
+    let (labels, region_values) = label_regions(mosaic);
+
+    let mut outgoing = boundary_edges(mosaic, &labels);
+
+    let mut rings = vec![(Vec::new(), Vec::new()); region_values.len()];
+
+    while let Some((&(label, start), _)) = outgoing.first_key_value() {
+        let ring = trace_ring(&mut outgoing, label, start);
+
+        for ring in split_at_touching_vertices(ring) {
+            let area = ring_area(&ring);
+            let ring = remove_collinear_vertices(&ring);
+
+            // rings are traced with the region on their right, so exteriors are clockwise in
+            // the downwards growing rows, i.e., they have a positive area
+            let (exteriors, holes) = &mut rings[label];
+            if area > 0. {
+                exteriors.push((area, ring));
+            } else {
+                holes.push(ring);
+            }
+        }
+    }
+
+    region_values
+        .into_iter()
+        .zip(rings)
+        .map(|(value, (exteriors, holes))| {
+            let polygons = assign_holes(exteriors, holes)
+                .into_iter()
+                .map(|polygon| {
+                    polygon
+                        .into_iter()
+                        .map(|ring| {
+                            ring.into_iter()
+                                .map(|(row, column)| mosaic.coordinate(row as f64, column as f64))
+                                .collect::<Vec<Coordinate2D>>()
+                        })
+                        .collect()
+                })
+                .collect();
+
+            Ok((value, MultiPolygon::new(polygons)?))
+        })
+        .collect()
+}
+
+/// Label the 4-connected regions of equal value. NODATA pixels have no label.
+fn label_regions(mosaic: &Mosaic) -> (Vec<Option<usize>>, Vec<f64>) {
+    let mut labels = vec![None; mosaic.values.len()];
+    let mut region_values = Vec::new();
+
+    let mut stack = Vec::new();
+    for start in 0..mosaic.values.len() {
+        let Some(value) = mosaic.values[start] else {
+            continue;
+        };
+        if labels[start].is_some() {
+            continue;
+        }
+
+        let label = region_values.len();
+        region_values.push(value);
+
+        labels[start] = Some(label);
+        stack.push(start);
+
+        while let Some(index) = stack.pop() {
+            let (row, column) = (index / mosaic.columns, index % mosaic.columns);
+
+            let neighbors = [
+                (row > 0, index.wrapping_sub(mosaic.columns)),
+                (row + 1 < mosaic.rows, index + mosaic.columns),
+                (column > 0, index.wrapping_sub(1)),
+                (column + 1 < mosaic.columns, index + 1),
+            ];
+
+            for (exists, neighbor) in neighbors {
+                if !exists {
+                    continue;
+                }
+
+                let same_value = mosaic.values[neighbor]
+                    .is_some_and(|neighbor_value| neighbor_value.total_cmp(&value).is_eq());
+
+                if same_value && labels[neighbor].is_none() {
+                    labels[neighbor] = Some(label);
+                    stack.push(neighbor);
+                }
+            }
+        }
+    }
+
+    (labels, region_values)
+}
+
+/// The edges between pixels of different regions, directed so that the region is on their right
+fn boundary_edges(
+    mosaic: &Mosaic,
+    labels: &[Option<usize>],
+) -> BTreeMap<(usize, Vertex), Vec<Direction>> {
+    let mut outgoing: BTreeMap<(usize, Vertex), Vec<Direction>> = BTreeMap::new();
+
+    let label_at = |row: Option<usize>, column: Option<usize>| match (row, column) {
+        (Some(row), Some(column)) if row < mosaic.rows && column < mosaic.columns => {
+            labels[row * mosaic.columns + column]
+        }
+        _ => None,
+    };
+
+    for row in 0..mosaic.rows {
+        for column in 0..mosaic.columns {
+            let Some(label) = labels[row * mosaic.columns + column] else {
+                continue;
+            };
+
+            let sides = [
+                (
+                    label_at(row.checked_sub(1), Some(column)),
+                    (row, column),
+                    Direction::East,
+                ),
+                (
+                    label_at(Some(row), Some(column + 1)),
+                    (row, column + 1),
+                    Direction::South,
+                ),
+                (
+                    label_at(Some(row + 1), Some(column)),
+                    (row + 1, column + 1),
+                    Direction::West,
+                ),
+                (
+                    label_at(Some(row), column.checked_sub(1)),
+                    (row + 1, column),
+                    Direction::North,
+                ),
+            ];
+
+            for (neighbor, start, direction) in sides {
+                if neighbor != Some(label) {
+                    outgoing.entry((label, start)).or_default().push(direction);
+                }
+            }
+        }
+    }
+
+    outgoing
+}
+
+/// Follow the boundary edges of the region `label` from `start` until it is reached again.
+/// At vertices where two pixels of the region only touch diagonally, the ring turns right,
+/// so that these pixels are not connected.
+fn trace_ring(
+    outgoing: &mut BTreeMap<(usize, Vertex), Vec<Direction>>,
+    label: usize,
+    start: Vertex,
+) -> Vec<Vertex> {
+    let mut take_edge = |vertex: Vertex, incoming: Option<Direction>| {
+        let directions = outgoing
+            .get_mut(&(label, vertex))
+            .expect("boundary edges form closed rings");
+
+        let position = match incoming {
+            Some(incoming) => [incoming.right(), incoming, incoming.left()]
+                .into_iter()
+                .find_map(|preferred| directions.iter().position(|&d| d == preferred))
+                .expect("boundary edges form closed rings"),
+            None => 0,
+        };
+
+        let direction = directions.swap_remove(position);
+        if directions.is_empty() {
+            outgoing.remove(&(label, vertex));
+        }
+        direction
+    };
+
+    let mut vertices = vec![start];
+
+    let mut direction = take_edge(start, None);
+    let mut current = direction.step(start);
+
+    while current != start {
+        vertices.push(current);
+        direction = take_edge(current, Some(direction));
+        current = direction.step(current);
+    }
+
+    vertices.push(start);
+    vertices
+}
+
+/// Split a closed ring into closed rings without repeated vertices
+fn split_at_touching_vertices(ring: Vec<Vertex>) -> Vec<Vec<Vertex>> {
+    let mut rings = Vec::new();
+
+    let mut path: Vec<Vertex> = Vec::with_capacity(ring.len());
+    let mut positions: HashMap<Vertex, usize> = HashMap::with_capacity(ring.len());
+
+    for vertex in ring {
+        if let Some(&position) = positions.get(&vertex) {
+            let mut closed = path.split_off(position);
+            for vertex in &closed[1..] {
+                positions.remove(vertex);
+            }
+            closed.push(vertex);
+            path.push(vertex);
+            rings.push(closed);
+        } else {
+            positions.insert(vertex, path.len());
+            path.push(vertex);
+        }
+    }
+
+    rings
+}
+
+/// The signed area of a closed ring in pixel units, positive for clockwise rings in the pixel grid
+fn ring_area(ring: &[Vertex]) -> f64 {
+    ring.windows(2)
+        .map(|edge| {
+            let ((row_a, column_a), (row_b, column_b)) = (edge[0], edge[1]);
+            column_a as f64 * row_b as f64 - column_b as f64 * row_a as f64
+        })
+        .sum::<f64>()
+        / 2.
+}
+
+/// Keep only the corners of a closed ring
+fn remove_collinear_vertices(ring: &[Vertex]) -> Vec<Vertex> {
+    let open_ring = &ring[..ring.len() - 1];
+    let n = open_ring.len();
+
+    let mut corners: Vec<Vertex> = (0..n)
+        .filter(|&i| {
+            let (previous, current, next) = (
+                open_ring[(i + n - 1) % n],
+                open_ring[i],
+                open_ring[(i + 1) % n],
+            );
+            // the edges are axis-parallel, so a vertex is a corner if it changes the axis
+            (previous.0 == current.0) != (current.0 == next.0)
+        })
+        .map(|i| open_ring[i])
+        .collect();
+
+    corners.extend(corners.first().copied());
+    corners
+}
+
+/// Build polygons from the exteriors and holes of a region.
+/// A hole belongs to the smallest exterior that encloses its bounds.
+fn assign_holes(
+    exteriors: Vec<(f64, Vec<Vertex>)>,
+    holes: Vec<Vec<Vertex>>,
+) -> Vec<Vec<Vec<Vertex>>> {
+    if let [(_, exterior)] = exteriors.as_slice() {
+        let mut polygon = vec![exterior.clone()];
+        polygon.extend(holes);
+        return vec![polygon];
+    }
+
+    let bounds = |ring: &[Vertex]| {
+        ring.iter().fold(
+            (usize::MAX, usize::MAX, 0, 0),
+            |(min_row, min_column, max_row, max_column), &(row, column)| {
+                (
+                    min_row.min(row),
+                    min_column.min(column),
+                    max_row.max(row),
+                    max_column.max(column),
+                )
+            },
+        )
+    };
+
+    let exterior_bounds = exteriors
+        .iter()
+        .map(|(_, exterior)| bounds(exterior))
+        .collect::<Vec<_>>();
+
+    let mut polygons = exteriors
+        .iter()
+        .map(|(_, exterior)| vec![exterior.clone()])
+        .collect::<Vec<_>>();
+
+    for hole in holes {
+        let (min_row, min_column, max_row, max_column) = bounds(&hole);
+
+        let enclosing = exterior_bounds
+            .iter()
+            .enumerate()
+            .filter(|(_, bounds)| {
+                bounds.0 <= min_row
+                    && bounds.1 <= min_column
+                    && bounds.2 >= max_row
+                    && bounds.3 >= max_column
+            })
+            .min_by(|(a, _), (b, _)| exteriors[*a].0.total_cmp(&exteriors[*b].0))
+            .map(|(i, _)| i);
+
+        if let Some(i) = enclosing {
+            polygons[i].push(hole);
+        }
+    }
+
+    polygons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::RasterVectorizationError;
+    use super::super::tests::{make_mosaic, make_raster};
+    use super::*;
+    use crate::engine::{MockExecutionContext, MockQueryContext};
+    use geoengine_datatypes::collections::{FeatureCollectionInfos, IntoGeometryIterator};
+    use geoengine_datatypes::primitives::{
+        FeatureDataRef, MultiPolygonAccess, SpatialResolution, TimeInterval,
+    };
+    use geoengine_datatypes::raster::TilingSpecification;
+    use geoengine_datatypes::util::test::TestDefault;
+
+    fn area(polygon: &MultiPolygon) -> f64 {
+        polygon
+            .polygons()
+            .iter()
+            .flat_map(|rings| rings.iter().enumerate())
+            .map(|(i, ring)| {
+                let area = ring
+                    .windows(2)
+                    .map(|edge| edge[0].x * edge[1].y - edge[1].x * edge[0].y)
+                    .sum::<f64>()
+                    .abs()
+                    / 2.;
+                if i == 0 { area } else { -area }
+            })
+            .sum()
+    }
+
+    #[test]
+    fn it_polygonizes_holes() {
+        let mosaic = make_mosaic(
+            3,
+            3,
+            [1., 1., 1., 1., 2., 1., 1., 1., 1.]
+                .into_iter()
+                .map(Some)
+                .collect(),
+        );
+
+        let regions = polygonize(&mosaic).unwrap();
+
+        assert_eq!(regions.len(), 2);
+
+        let (value, outer) = &regions[0];
+        assert!((value - 1.).abs() < f64::EPSILON);
+        assert_eq!(outer.polygons().len(), 1);
+        // the exterior and the hole
+        assert_eq!(outer.polygons()[0].len(), 2);
+        assert_eq!(outer.polygons()[0][0].len(), 5);
+        assert!((area(outer) - 8.).abs() < f64::EPSILON);
+
+        let (value, inner) = &regions[1];
+        assert!((value - 2.).abs() < f64::EPSILON);
+        assert_eq!(inner.polygons()[0][0].len(), 5);
+        assert!((area(inner) - 1.).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn it_separates_diagonal_pixels() {
+        let mosaic = make_mosaic(2, 2, vec![Some(1.), Some(2.), Some(2.), Some(1.)]);
+
+        let regions = polygonize(&mosaic).unwrap();
+
+        // pixels that touch diagonally are not connected
+        assert_eq!(regions.len(), 4);
+        for (_, polygon) in regions {
+            assert!((area(&polygon) - 1.).abs() < f64::EPSILON);
+        }
+    }
+
+    #[test]
+    fn it_ignores_no_data() {
+        let mosaic = make_mosaic(1, 3, vec![Some(1.), None, Some(1.)]);
+
+        let regions = polygonize(&mosaic).unwrap();
+
+        assert_eq!(regions.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn it_merges_regions_across_tiles() {
+        let polygonize = Polygonize {
+            params: PolygonizeParams {},
+            sources: SingleRasterSource {
+                raster: make_raster(vec![1, 1, 2, 1], vec![1, 2, 1, 1]),
+            },
+        }
+        .boxed()
+        .initialize(
+            WorkflowOperatorPath::initialize_root(),
+            &MockExecutionContext::new_with_tiling_spec(TilingSpecification::new(
+                (0., 0.).into(),
+                [2, 2].into(),
+            )),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            polygonize.result_descriptor().columns[VALUE_COLUMN].data_type,
+            FeatureDataType::Int
+        );
+
+        let processor = polygonize
+            .query_processor()
+            .unwrap()
+            .multi_polygon()
+            .unwrap();
+
+        let collections = processor
+            .query(
+                VectorQueryRectangle {
+                    spatial_bounds: BoundingBox2D::new((0., 0.).into(), (4., 2.).into()).unwrap(),
+                    time_interval: TimeInterval::new_unchecked(0, 10),
+                    spatial_resolution: SpatialResolution::one(),
+                    attributes: ColumnSelection::all(),
+                },
+                &MockQueryContext::test_default(),
+            )
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(collections.len(), 1);
+        let collection = &collections[0];
+
+        // the ones form one region across the tile boundary, the twos form two regions
+        assert_eq!(collection.len(), 3);
+
+        let FeatureDataRef::Int(values) = collection.data(VALUE_COLUMN).unwrap() else {
+            panic!("expected integer values");
+        };
+        let ones = collection
+            .geometries()
+            .zip(values.as_ref())
+            .filter(|(_, value)| **value == 1)
+            .map(|(polygon, _)| MultiPolygon::from(polygon))
+            .collect::<Vec<_>>();
+
+        assert_eq!(ones.len(), 1);
+        assert!((area(&ones[0]) - 6.).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn it_rejects_too_many_pixels() {
+        let processor = Polygonize {
+            params: PolygonizeParams {},
+            sources: SingleRasterSource {
+                raster: make_raster(vec![1, 1, 2, 1], vec![1, 2, 1, 1]),
+            },
+        }
+        .boxed()
+        .initialize(
+            WorkflowOperatorPath::initialize_root(),
+            &MockExecutionContext::new_with_tiling_spec(TilingSpecification::new(
+                (0., 0.).into(),
+                [2, 2].into(),
+            )),
+        )
+        .await
+        .unwrap()
+        .query_processor()
+        .unwrap()
+        .multi_polygon()
+        .unwrap();
+
+        let result = processor
+            .query(
+                VectorQueryRectangle {
+                    spatial_bounds: BoundingBox2D::new(
+                        (0., 0.).into(),
+                        (100_000., 100_000.).into(),
+                    )
+                    .unwrap(),
+                    time_interval: TimeInterval::new_unchecked(0, 10),
+                    spatial_resolution: SpatialResolution::one(),
+                    attributes: ColumnSelection::all(),
+                },
+                &MockQueryContext::test_default(),
+            )
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(crate::error::Error::RasterVectorization {
+                source: RasterVectorizationError::TooManyPixels { .. }
+            })
+        ));
+    }
+}
@@ -0,0 +1,1122 @@
+use crate::engine::{
+    CanonicOperatorName, InitializedRasterOperator, InitializedVectorOperator, OperatorName,
+    QueryContext, RasterBandDescriptor, RasterBandDescriptors, RasterQueryProcessor,
+    RasterResultDescriptor, TypedRasterQueryProcessor, TypedVectorQueryProcessor,
+    VectorQueryProcessor, WorkflowOperatorPath,
+};
+use crate::error;
+use crate::processing::rasterization::Rasterization;
+use crate::util::{self, spawn_blocking};
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::{StreamExt, stream};
+use geoengine_datatypes::collections::{
+    FeatureCollection, FeatureCollectionInfos, IntoGeometryIterator, MultiLineStringCollection,
+    MultiPointCollection, MultiPolygonCollection, VectorDataType,
+};
+use geoengine_datatypes::primitives::{
+    AxisAlignedRectangle, CacheHint, ClassificationMeasurement, ColumnSelection, Coordinate2D,
+    Geometry, Measurement, MultiLineStringAccess, MultiPointAccess, MultiPolygonAccess,
+    RasterQueryRectangle, SpatialPartitioned, VectorQueryRectangle,
+};
+use geoengine_datatypes::raster::{
+    EmptyGrid2D, GeoTransform, Grid2D, GridOrEmpty, GridOrEmpty2D, GridShape2D, GridSize,
+    MaskedGrid2D, Pixel, RasterDataType, RasterTile2D, TileInformation, TilingSpecification,
+};
+use geoengine_datatypes::util::arrow::ArrowTyped;
+use serde::{Deserialize, Serialize};
+use snafu::ensure;
+use std::marker::PhantomData;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BurnParams {
+    /// The attribute whose values are burned into the raster.
+    /// If it is not set, each feature has the value `1`, e.g. for counting features or masks.
+    #[serde(default)]
+    attribute: Option<String>,
+    /// Combines the values of all features that cover the same pixel
+    aggregation: BurnAggregation,
+    /// Determines which pixels a polygon covers
+    #[serde(default)]
+    pixel_selection: PixelSelection,
+    /// Turns the output into a classification. The attribute values are looked up in this list
+    /// and each feature burns the index of its class. Features of other classes are ignored.
+    /// The output has the data type `U8`, so there are at most 256 classes.
+    #[serde(default)]
+    classes: Option<Vec<String>>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BurnAggregation {
+    Sum,
+    Mean,
+    Min,
+    Max,
+    /// The most frequent value, preferring the smallest value for ties
+    Majority,
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PixelSelection {
+    /// A polygon covers all pixels whose center lies inside of it
+    #[default]
+    Center,
+    /// A polygon covers all pixels that it touches
+    AllTouched,
+}
+
+pub struct InitializedBurnRasterization {
+    name: CanonicOperatorName,
+    path: WorkflowOperatorPath,
+    source: Box<dyn InitializedVectorOperator>,
+    result_descriptor: RasterResultDescriptor,
+    tiling_specification: TilingSpecification,
+    params: BurnParams,
+}
+
+impl InitializedBurnRasterization {
+    pub(super) fn new(
+        name: CanonicOperatorName,
+        path: WorkflowOperatorPath,
+        source: Box<dyn InitializedVectorOperator>,
+        mut result_descriptor: RasterResultDescriptor,
+        tiling_specification: TilingSpecification,
+        params: BurnParams,
+    ) -> Result<Self, error::Error> {
+        let in_desc = source.result_descriptor();
+
+        ensure!(
+            in_desc.data_type != VectorDataType::Data,
+            error::InvalidType {
+                expected: format!(
+                    "{}, {} or {}",
+                    VectorDataType::MultiPoint,
+                    VectorDataType::MultiLineString,
+                    VectorDataType::MultiPolygon
+                ),
+                found: in_desc.data_type.to_string(),
+            }
+        );
+
+        let measurement = match (&params.attribute, &params.classes) {
+            (None, None) => Measurement::Unitless,
+            (None, Some(_)) => {
+                return Err(error::Error::InvalidOperatorSpec {
+                    reason: "Burning classes requires an attribute.".to_string(),
+                });
+            }
+            (Some(attribute), classes) => {
+                let column = in_desc.columns.get(attribute).ok_or_else(|| {
+                    error::Error::ColumnDoesNotExist {
+                        column: attribute.clone(),
+                    }
+                })?;
+
+                if let Some(classes) = classes {
+                    ensure!(
+                        !classes.is_empty() && classes.len() <= usize::from(u8::MAX) + 1,
+                        error::InvalidOperatorSpec {
+                            reason: "There must be between 1 and 256 classes.".to_string()
+                        }
+                    );
+                    ensure!(
+                        !matches!(
+                            params.aggregation,
+                            BurnAggregation::Sum | BurnAggregation::Mean
+                        ),
+                        error::InvalidOperatorSpec {
+                            reason: "Classes cannot be summed up or averaged.".to_string()
+                        }
+                    );
+
+                    result_descriptor.data_type = RasterDataType::U8;
+
+                    Measurement::Classification(ClassificationMeasurement {
+                        measurement: attribute.clone(),
+                        classes: classes
+                            .iter()
+                            .enumerate()
+                            .map(|(index, class)| (index as u8, class.clone()))
+                            .collect(),
+                    })
+                } else {
+                    ensure!(
+                        column.data_type.is_numeric(),
+                        error::InvalidOperatorSpec {
+                            reason: format!(
+                                "The attribute `{attribute}` must be numeric or have classes."
+                            )
+                        }
+                    );
+
+                    match (params.aggregation, &column.measurement) {
+                        // sums are no longer in the unit of the attribute
+                        (BurnAggregation::Sum, _)
+                        | (BurnAggregation::Mean, Measurement::Classification(_)) => {
+                            Measurement::Unitless
+                        }
+                        (_, measurement) => measurement.clone(),
+                    }
+                }
+            }
+        };
+
+        result_descriptor.bands = RasterBandDescriptors::new(vec![RasterBandDescriptor::new(
+            "band".into(),
+            measurement,
+        )])?;
+
+        Ok(Self {
+            name,
+            path,
+            source,
+            result_descriptor,
+            tiling_specification,
+            params,
+        })
+    }
+}
+
+impl InitializedRasterOperator for InitializedBurnRasterization {
+    fn result_descriptor(&self) -> &RasterResultDescriptor {
+        &self.result_descriptor
+    }
+
+    fn query_processor(&self) -> util::Result<TypedRasterQueryProcessor> {
+        let input = self.source.query_processor()?;
+
+        Ok(match self.result_descriptor.data_type {
+            RasterDataType::U8 => TypedRasterQueryProcessor::U8(
+                BurnRasterizationQueryProcessor::new(input, self).boxed(),
+            ),
+            _ => TypedRasterQueryProcessor::F64(
+                BurnRasterizationQueryProcessor::new(input, self).boxed(),
+            ),
+        })
+    }
+
+    fn canonic_name(&self) -> CanonicOperatorName {
+        self.name.clone()
+    }
+
+    fn name(&self) -> &'static str {
+        Rasterization::TYPE_NAME
+    }
+
+    fn path(&self) -> WorkflowOperatorPath {
+        self.path.clone()
+    }
+}
+
+pub struct BurnRasterizationQueryProcessor<P> {
+    input: TypedVectorQueryProcessor,
+    result_descriptor: RasterResultDescriptor,
+    tiling_specification: TilingSpecification,
+    params: BurnParams,
+    _pixel: PhantomData<P>,
+}
+
+impl<P: Pixel> BurnRasterizationQueryProcessor<P> {
+    fn new(input: TypedVectorQueryProcessor, operator: &InitializedBurnRasterization) -> Self {
+        Self {
+            input,
+            result_descriptor: operator.result_descriptor.clone(),
+            tiling_specification: operator.tiling_specification,
+            params: operator.params.clone(),
+            _pixel: PhantomData,
+        }
+    }
+
+    /// Queries the features of each tile and burns them into the tile's pixels
+    fn burn_tiles<'a, G>(
+        &'a self,
+        input: &'a dyn VectorQueryProcessor<VectorType = FeatureCollection<G>>,
+        query: RasterQueryRectangle,
+        ctx: &'a dyn QueryContext,
+    ) -> BoxStream<'a, util::Result<RasterTile2D<P>>>
+    where
+        G: Geometry + ArrowTyped + 'static,
+        FeatureCollection<G>: BurnCollection,
+    {
+        let tiling_strategy = self
+            .tiling_specification
+            .strategy(query.spatial_resolution.x, -query.spatial_resolution.y);
+
+        stream::iter(tiling_strategy.tile_information_iterator(query.spatial_bounds))
+            .then(move |tile_info| async move {
+                let vector_query = VectorQueryRectangle {
+                    spatial_bounds: tile_info.spatial_partition().as_bbox(),
+                    time_interval: query.time_interval,
+                    spatial_resolution: query.spatial_resolution,
+                    attributes: ColumnSelection::all(),
+                };
+
+                let mut chunks = input.vector_query(vector_query, ctx).await?;
+
+                let mut cache_hint = CacheHint::max_duration();
+
+                let mut raster = BurnRaster::new(
+                    tile_info,
+                    self.params.aggregation,
+                    self.params.pixel_selection,
+                );
+                while let Some(chunk) = chunks.next().await {
+                    let chunk = chunk?;
+
+                    cache_hint.merge_with(&chunk.cache_hint);
+
+                    let values = self.feature_values(&chunk)?;
+
+                    raster = spawn_blocking(move || {
+                        chunk.burn(&values, &mut raster);
+                        raster
+                    })
+                    .await?;
+                }
+
+                let grid = spawn_blocking(move || raster.into_grid::<P>()).await??;
+
+                Ok(RasterTile2D::new_with_tile_info(
+                    query.time_interval,
+                    tile_info,
+                    0,
+                    grid,
+                    cache_hint,
+                ))
+            })
+            .boxed()
+    }
+
+    /// Returns the value that each feature burns, or `None` if it is ignored
+    fn feature_values(
+        &self,
+        collection: &impl FeatureCollectionInfos,
+    ) -> util::Result<Vec<Option<f64>>> {
+        let Some(attribute) = &self.params.attribute else {
+            return Ok(vec![Some(1.); collection.len()]);
+        };
+
+        let data = collection.data(attribute)?;
+
+        let Some(classes) = &self.params.classes else {
+            return Ok(data.float_options_iter().collect());
+        };
+
+        Ok(data
+            .strings_iter()
+            .zip(data.nulls())
+            .map(|(value, is_null)| {
+                if is_null {
+                    return None;
+                }
+                classes
+                    .iter()
+                    .position(|class| *class == value)
+                    .map(|index| index as f64)
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl<P: Pixel> RasterQueryProcessor for BurnRasterizationQueryProcessor<P> {
+    type RasterType = P;
+
+    /// Burns the features of the vector input into the raster.
+    /// Points cover the pixel they lie in and lines cover all pixels they pass through.
+    /// Polygons cover either the pixels whose centers are inside of them or additionally all
+    /// pixels their boundary touches. A feature counts at most once for each pixel.
+    /// Pixels that are not covered by any feature are empty.
+    async fn raster_query<'a>(
+        &'a self,
+        query: RasterQueryRectangle,
+        ctx: &'a dyn QueryContext,
+    ) -> util::Result<BoxStream<'a, util::Result<RasterTile2D<Self::RasterType>>>> {
+        match &self.input {
+            TypedVectorQueryProcessor::MultiPoint(input) => {
+                Ok(self.burn_tiles(input.as_ref(), query, ctx))
+            }
+            TypedVectorQueryProcessor::MultiLineString(input) => {
+                Ok(self.burn_tiles(input.as_ref(), query, ctx))
+            }
+            TypedVectorQueryProcessor::MultiPolygon(input) => {
+                Ok(self.burn_tiles(input.as_ref(), query, ctx))
+            }
+            TypedVectorQueryProcessor::Data(_) => Err(error::Error::InvalidType {
+                expected: "a geo data collection".to_string(),
+                found: VectorDataType::Data.to_string(),
+            }),
+        }
+    }
+
+    fn raster_result_descriptor(&self) -> &RasterResultDescriptor {
+        &self.result_descriptor
+    }
+}
+
+/// A feature collection whose geometries can be burned into a raster
+pub trait BurnCollection: FeatureCollectionInfos + Send + 'static {
+    /// Burns each feature with its value into the `raster`, skipping features without a value
+    fn burn(&self, values: &[Option<f64>], raster: &mut BurnRaster);
+}
+
+impl BurnCollection for MultiPointCollection {
+    fn burn(&self, values: &[Option<f64>], raster: &mut BurnRaster) {
+        for (geometry, value) in self.geometries().zip(values) {
+            let Some(value) = value else { continue };
+            raster.start_feature(*value);
+
+            for &point in geometry.points() {
+                let (x, y) = raster.to_pixel_space(point);
+                raster.burn_pixel(x.floor(), y.floor());
+            }
+        }
+    }
+}
+
+impl BurnCollection for MultiLineStringCollection {
+    fn burn(&self, values: &[Option<f64>], raster: &mut BurnRaster) {
+        for (geometry, value) in self.geometries().zip(values) {
+            let Some(value) = value else { continue };
+            raster.start_feature(*value);
+
+            for line in geometry.lines() {
+                raster.burn_line(line);
+            }
+        }
+    }
+}
+
+impl BurnCollection for MultiPolygonCollection {
+    fn burn(&self, values: &[Option<f64>], raster: &mut BurnRaster) {
+        for (geometry, value) in self.geometries().zip(values) {
+            let Some(value) = value else { continue };
+            raster.start_feature(*value);
+
+            for polygon in geometry.polygons() {
+                let rings = polygon
+                    .iter()
+                    .map(|ring| {
+                        ring.iter()
+                            .map(|&coordinate| raster.to_pixel_space(coordinate))
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>();
+
+                raster.burn_polygon(&rings);
+            }
+        }
+    }
+}
+
+/// Accumulates the values of the features that cover the pixels of a tile
+pub struct BurnRaster {
+    shape: GridShape2D,
+    geo_transform: GeoTransform,
+    aggregation: BurnAggregation,
+    pixel_selection: PixelSelection,
+    values: Vec<f64>,
+    counts: Vec<u32>,
+    /// the frequency of each value for the majority aggregation
+    frequencies: Vec<Vec<(f64, u32)>>,
+    /// the last feature that covered each pixel, so that a feature counts only once per pixel
+    last_features: Vec<u32>,
+    feature: u32,
+    value: f64,
+}
+
+impl BurnRaster {
+    fn new(
+        tile_info: TileInformation,
+        aggregation: BurnAggregation,
+        pixel_selection: PixelSelection,
+    ) -> Self {
+        let shape = tile_info.tile_size_in_pixels;
+        let size = shape.number_of_elements();
+
+        Self {
+            shape,
+            geo_transform: tile_info.tile_geo_transform(),
+            aggregation,
+            pixel_selection,
+            values: vec![0.; size],
+            counts: vec![0; size],
+            frequencies: if aggregation == BurnAggregation::Majority {
+                vec![Vec::new(); size]
+            } else {
+                Vec::new()
+            },
+            last_features: vec![0; size],
+            feature: 0,
+            value: 0.,
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.shape.axis_size_x()
+    }
+
+    fn height(&self) -> usize {
+        self.shape.axis_size_y()
+    }
+
+    fn start_feature(&mut self, value: f64) {
+        self.feature += 1;
+        self.value = value;
+    }
+
+    /// Transforms the `coordinate` into continuous pixel coordinates of the tile
+    fn to_pixel_space(&self, coordinate: Coordinate2D) -> (f64, f64) {
+        (
+            (coordinate.x - self.geo_transform.origin_coordinate.x)
+                / self.geo_transform.x_pixel_size(),
+            (coordinate.y - self.geo_transform.origin_coordinate.y)
+                / self.geo_transform.y_pixel_size(),
+        )
+    }
+
+    /// Adds the value of the current feature to the pixel, if it is inside the tile
+    fn burn_pixel(&mut self, x: f64, y: f64) {
+        let is_inside =
+            (0. ..self.width() as f64).contains(&x) && (0. ..self.height() as f64).contains(&y);
+        if !is_inside {
+            return;
+        }
+        let index = y as usize * self.width() + x as usize;
+
+        if self.last_features[index] == self.feature {
+            return;
+        }
+        self.last_features[index] = self.feature;
+
+        let value = self.value;
+        self.counts[index] += 1;
+        let first = self.counts[index] == 1;
+
+        match self.aggregation {
+            BurnAggregation::Sum | BurnAggregation::Mean => self.values[index] += value,
+            BurnAggregation::Min => {
+                if first || value < self.values[index] {
+                    self.values[index] = value;
+                }
+            }
+            BurnAggregation::Max => {
+                if first || value > self.values[index] {
+                    self.values[index] = value;
+                }
+            }
+            BurnAggregation::Majority => {
+                let frequencies = &mut self.frequencies[index];
+                match frequencies
+                    .iter_mut()
+                    .find(|(other, _)| other.to_bits() == value.to_bits())
+                {
+                    Some((_, frequency)) => *frequency += 1,
+                    None => frequencies.push((value, 1)),
+                }
+            }
+        }
+    }
+
+    /// Burns all pixels that the line passes through
+    fn burn_line(&mut self, line: &[Coordinate2D]) {
+        for segment in line.windows(2) {
+            let start = self.to_pixel_space(segment[0]);
+            let end = self.to_pixel_space(segment[1]);
+            self.burn_segment(start, end);
+        }
+    }
+
+    /// Burns all pixels that the segment between the pixel coordinates passes through
+    fn burn_segment(&mut self, start: (f64, f64), end: (f64, f64)) {
+        // clip to the tile with a margin of one pixel to bound the traversal
+        let Some((start, end)) = clip_segment(
+            start,
+            end,
+            (-1., -1.),
+            (self.width() as f64 + 1., self.height() as f64 + 1.),
+        ) else {
+            return;
+        };
+
+        let (mut x, mut y) = (start.0.floor(), start.1.floor());
+        let (end_x, end_y) = (end.0.floor(), end.1.floor());
+        let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+
+        // distances along the segment (as fraction) to the next vertical and horizontal pixel border
+        let (step_x, mut next_x, delta_x) = traversal_parameters(start.0, x, dx);
+        let (step_y, mut next_y, delta_y) = traversal_parameters(start.1, y, dy);
+
+        let steps = (end_x - x).abs() + (end_y - y).abs();
+        for _ in 0..=steps as usize {
+            self.burn_pixel(x, y);
+
+            if next_x < next_y {
+                next_x += delta_x;
+                x += step_x;
+            } else {
+                next_y += delta_y;
+                y += step_y;
+            }
+        }
+    }
+
+    /// Burns the polygon, whose rings are given in pixel coordinates, using the even-odd rule
+    fn burn_polygon(&mut self, rings: &[Vec<(f64, f64)>]) {
+        let (min_y, max_y) = rings
+            .iter()
+            .flatten()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &(_, y)| {
+                (min.min(y), max.max(y))
+            });
+        if !(min_y.is_finite() && max_y.is_finite()) {
+            return;
+        }
+
+        let first_row = ((min_y - 0.5).ceil() as i64).max(0);
+        let last_row = ((max_y - 0.5).floor() as i64).min(self.height() as i64 - 1);
+
+        let mut crossings = Vec::new();
+        for row in first_row..=last_row {
+            let center_y = row as f64 + 0.5;
+
+            crossings.clear();
+            for ring in rings {
+                for edge in ring.windows(2) {
+                    let ((x0, y0), (x1, y1)) = (edge[0], edge[1]);
+                    if (y0 <= center_y && center_y < y1) || (y1 <= center_y && center_y < y0) {
+                        crossings.push(x0 + (center_y - y0) / (y1 - y0) * (x1 - x0));
+                    }
+                }
+            }
+            crossings.sort_by(f64::total_cmp);
+
+            for span in crossings.chunks_exact(2) {
+                let first = ((span[0] - 0.5).ceil() as i64).max(0);
+                let last = ((span[1] - 0.5).ceil() as i64 - 1).min(self.width() as i64 - 1);
+
+                for column in first..=last {
+                    self.burn_pixel(column as f64, row as f64);
+                }
+            }
+        }
+
+        if self.pixel_selection == PixelSelection::AllTouched {
+            for ring in rings {
+                for edge in ring.windows(2) {
+                    self.burn_segment(edge[0], edge[1]);
+                }
+            }
+        }
+    }
+
+    /// Aggregates the burned values into the tile's grid of the output data type
+    fn into_grid<P: Pixel>(self) -> util::Result<GridOrEmpty2D<P>> {
+        if self.counts.iter().all(|&count| count == 0) {
+            return Ok(GridOrEmpty::Empty(EmptyGrid2D::new(self.shape)));
+        }
+
+        let data: Vec<f64> = match self.aggregation {
+            BurnAggregation::Sum | BurnAggregation::Min | BurnAggregation::Max => self.values,
+            BurnAggregation::Mean => self
+                .values
+                .iter()
+                .zip(&self.counts)
+                .map(|(&sum, &count)| {
+                    if count > 0 {
+                        sum / f64::from(count)
+                    } else {
+                        0.
+                    }
+                })
+                .collect(),
+            BurnAggregation::Majority => self
+                .frequencies
+                .iter()
+                .map(|frequencies| {
+                    frequencies
+                        .iter()
+                        .max_by(|(a, a_frequency), (b, b_frequency)| {
+                            a_frequency.cmp(b_frequency).then(b.total_cmp(a))
+                        })
+                        .map_or(0., |&(value, _)| value)
+                })
+                .collect(),
+        };
+        let validity_mask = self.counts.iter().map(|&count| count > 0).collect();
+
+        Ok(GridOrEmpty::Grid(MaskedGrid2D::new(
+            Grid2D::new(self.shape, data.into_iter().map(P::from_).collect())?,
+            Grid2D::new(self.shape, validity_mask)?,
+        )?))
+    }
+}
+
+/// Returns the direction, the fraction of the segment until the first pixel border and the fraction
+/// between two pixel borders along one axis
+fn traversal_parameters(start: f64, pixel: f64, delta: f64) -> (f64, f64, f64) {
+    if delta > 0. {
+        (1., (pixel + 1. - start) / delta, 1. / delta)
+    } else if delta < 0. {
+        (-1., (pixel - start) / delta, -1. / delta)
+    } else {
+        (0., f64::INFINITY, f64::INFINITY)
+    }
+}
+
+/// Clips the segment to the rectangle using the Liang-Barsky algorithm
+fn clip_segment(
+    start: (f64, f64),
+    end: (f64, f64),
+    min: (f64, f64),
+    max: (f64, f64),
+) -> Option<((f64, f64), (f64, f64))> {
+    if ![start.0, start.1, end.0, end.1]
+        .iter()
+        .all(|c| c.is_finite())
+    {
+        return None;
+    }
+
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let (mut t0, mut t1) = (0., 1.);
+
+    for (p, q) in [
+        (-dx, start.0 - min.0),
+        (dx, max.0 - start.0),
+        (-dy, start.1 - min.1),
+        (dy, max.1 - start.1),
+    ] {
+        if p == 0. {
+            if q < 0. {
+                return None;
+            }
+            continue;
+        }
+
+        let t = q / p;
+        if p < 0. {
+            t0 = f64::max(t0, t);
+        } else {
+            t1 = f64::min(t1, t);
+        }
+    }
+
+    if t0 <= t1 {
+        Some((
+            (start.0 + t0 * dx, start.1 + t0 * dy),
+            (start.0 + t1 * dx, start.1 + t1 * dy),
+        ))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{
+        MockExecutionContext, MockQueryContext, QueryProcessor, RasterOperator, SingleVectorSource,
+        VectorOperator,
+    };
+    use crate::mock::MockFeatureCollectionSource;
+    use crate::processing::rasterization::GridOrDensity;
+    use geoengine_datatypes::primitives::{
+        BandSelection, ContinuousMeasurement, FeatureData, MultiLineString, MultiPoint,
+        MultiPolygon, SpatialPartition2D, SpatialResolution, TimeInterval,
+    };
+    use geoengine_datatypes::util::test::TestDefault;
+    use num_traits::AsPrimitive;
+
+    const N: Option<f64> = None;
+
+    fn rectangle(x_min: f64, y_min: f64, x_max: f64, y_max: f64) -> MultiPolygon {
+        MultiPolygon::new(vec![vec![vec![
+            (x_min, y_min).into(),
+            (x_max, y_min).into(),
+            (x_max, y_max).into(),
+            (x_min, y_max).into(),
+            (x_min, y_min).into(),
+        ]]])
+        .unwrap()
+    }
+
+    async fn initialize(
+        source: Box<dyn VectorOperator>,
+        params: BurnParams,
+    ) -> util::Result<Box<dyn InitializedRasterOperator>> {
+        Rasterization {
+            params: GridOrDensity::Burn(params),
+            sources: SingleVectorSource { vector: source },
+        }
+        .boxed()
+        .initialize(
+            WorkflowOperatorPath::initialize_root(),
+            &MockExecutionContext::new_with_tiling_spec(TilingSpecification::new(
+                [0., 0.].into(),
+                [4, 4].into(),
+            )),
+        )
+        .await
+    }
+
+    /// Burns the features into a single 4x4 tile that spans from (0, 0) to (4, 4)
+    async fn burn(source: Box<dyn VectorOperator>, params: BurnParams) -> Vec<Option<f64>> {
+        let rasterization = initialize(source, params).await.unwrap();
+
+        match rasterization.query_processor().unwrap() {
+            TypedRasterQueryProcessor::U8(processor) => query_tile(processor).await,
+            TypedRasterQueryProcessor::F64(processor) => query_tile(processor).await,
+            _ => panic!("the rasterization must produce u8 or f64 values"),
+        }
+    }
+
+    async fn query_tile<P: Pixel>(
+        processor: Box<dyn RasterQueryProcessor<RasterType = P>>,
+    ) -> Vec<Option<f64>> {
+        let query = RasterQueryRectangle {
+            spatial_bounds: SpatialPartition2D::new([0., 4.].into(), [4., 0.].into()).unwrap(),
+            time_interval: TimeInterval::default(),
+            spatial_resolution: SpatialResolution::one(),
+            attributes: BandSelection::first(),
+        };
+
+        let tiles: Vec<_> = processor
+            .query(query, &MockQueryContext::test_default())
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(tiles.len(), 1);
+
+        let grid = tiles[0].grid_array.clone().into_materialized_masked_grid();
+        grid.inner_grid
+            .data
+            .iter()
+            .zip(&grid.validity_mask.data)
+            .map(|(&value, &is_valid)| is_valid.then_some(value.as_()))
+            .collect()
+    }
+
+    fn params(attribute: Option<&str>, aggregation: BurnAggregation) -> BurnParams {
+        BurnParams {
+            attribute: attribute.map(ToString::to_string),
+            aggregation,
+            pixel_selection: PixelSelection::Center,
+            classes: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn polygon_pixel_selection() {
+        let collection = MultiPolygonCollection::from_slices(
+            &[rectangle(0.6, 0.6, 2.4, 3.4)],
+            &[TimeInterval::default()],
+            &[("value", FeatureData::Float(vec![5.]))],
+        )
+        .unwrap();
+
+        let center = burn(
+            MockFeatureCollectionSource::single(collection.clone()).boxed(),
+            params(Some("value"), BurnAggregation::Max),
+        )
+        .await;
+        let v = Some(5.);
+        assert_eq!(
+            center,
+            vec![
+                N, N, N, N, //
+                N, v, N, N, //
+                N, v, N, N, //
+                N, N, N, N,
+            ]
+        );
+
+        let all_touched = burn(
+            MockFeatureCollectionSource::single(collection).boxed(),
+            BurnParams {
+                pixel_selection: PixelSelection::AllTouched,
+                ..params(Some("value"), BurnAggregation::Max)
+            },
+        )
+        .await;
+        assert_eq!(
+            all_touched,
+            vec![
+                v, v, v, N, //
+                v, v, v, N, //
+                v, v, v, N, //
+                v, v, v, N,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn overlapping_polygons() {
+        let collection = MultiPolygonCollection::from_slices(
+            &[rectangle(-1., -1., 5., 5.), rectangle(-1., -1., 2., 5.)],
+            &[TimeInterval::default(); 2],
+            &[("value", FeatureData::Float(vec![1., 3.]))],
+        )
+        .unwrap();
+
+        for (aggregation, row) in [
+            (BurnAggregation::Sum, [4., 4., 1., 1.]),
+            (BurnAggregation::Mean, [2., 2., 1., 1.]),
+            (BurnAggregation::Min, [1., 1., 1., 1.]),
+            (BurnAggregation::Max, [3., 3., 1., 1.]),
+            (BurnAggregation::Majority, [1., 1., 1., 1.]),
+        ] {
+            let result = burn(
+                MockFeatureCollectionSource::single(collection.clone()).boxed(),
+                params(Some("value"), aggregation),
+            )
+            .await;
+
+            assert_eq!(
+                result,
+                row.iter()
+                    .cycle()
+                    .take(16)
+                    .copied()
+                    .map(Some)
+                    .collect::<Vec<_>>(),
+                "{aggregation:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn lines() {
+        let collection = MultiLineStringCollection::from_slices(
+            &[
+                MultiLineString::new(vec![vec![
+                    (0.5, 2.5).into(),
+                    (3.5, 2.5).into(),
+                    (0.2, 2.7).into(),
+                ]])
+                .unwrap(),
+                MultiLineString::new(vec![vec![(1.5, 0.5).into(), (1.5, 3.5).into()]]).unwrap(),
+            ],
+            &[TimeInterval::default(); 2],
+            &[] as &[(&str, FeatureData)],
+        )
+        .unwrap();
+
+        let result = burn(
+            MockFeatureCollectionSource::single(collection).boxed(),
+            params(None, BurnAggregation::Sum),
+        )
+        .await;
+
+        let (a, b) = (Some(1.), Some(2.));
+        assert_eq!(
+            result,
+            vec![
+                N, a, N, N, //
+                a, b, a, a, //
+                N, a, N, N, //
+                N, a, N, N,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn points() {
+        let collection = MultiPointCollection::from_slices(
+            &MultiPoint::many(vec![
+                vec![(0.5, 3.5), (0.7, 3.2)],
+                vec![(0.2, 3.9)],
+                vec![(3.5, 0.5)],
+            ])
+            .unwrap(),
+            &[TimeInterval::default(); 3],
+            &[] as &[(&str, FeatureData)],
+        )
+        .unwrap();
+
+        let result = burn(
+            MockFeatureCollectionSource::single(collection).boxed(),
+            params(None, BurnAggregation::Sum),
+        )
+        .await;
+
+        let (a, b) = (Some(1.), Some(2.));
+        assert_eq!(
+            result,
+            vec![
+                b, N, N, N, //
+                N, N, N, N, //
+                N, N, N, N, //
+                N, N, N, a,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn classes() {
+        let collection = MultiPolygonCollection::from_slices(
+            &[
+                rectangle(-1., -1., 2., 5.),
+                rectangle(2., -1., 5., 5.),
+                rectangle(-1., -1., 5., 5.),
+            ],
+            &[TimeInterval::default(); 3],
+            &[(
+                "landuse",
+                FeatureData::NullableText(vec![
+                    Some("forest".to_string()),
+                    Some("water".to_string()),
+                    Some("urban".to_string()),
+                ]),
+            )],
+        )
+        .unwrap();
+
+        let params = BurnParams {
+            classes: Some(vec!["water".to_string(), "forest".to_string()]),
+            ..params(Some("landuse"), BurnAggregation::Majority)
+        };
+
+        let rasterization = initialize(
+            MockFeatureCollectionSource::single(collection.clone()).boxed(),
+            params.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            rasterization.result_descriptor().bands[0].measurement,
+            Measurement::Classification(ClassificationMeasurement {
+                measurement: "landuse".to_string(),
+                classes: [(0, "water".to_string()), (1, "forest".to_string())].into(),
+            })
+        );
+        assert_eq!(
+            rasterization.result_descriptor().data_type,
+            RasterDataType::U8
+        );
+
+        let result = burn(
+            MockFeatureCollectionSource::single(collection).boxed(),
+            params,
+        )
+        .await;
+        assert_eq!(
+            result,
+            [1., 1., 0., 0.]
+                .iter()
+                .cycle()
+                .take(16)
+                .copied()
+                .map(Some)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn measurements() {
+        let collection = MultiPolygonCollection::from_slices(
+            &[rectangle(0., 0., 1., 1.)],
+            &[TimeInterval::default()],
+            &[("population", FeatureData::Float(vec![42.]))],
+        )
+        .unwrap();
+        let measurement = Measurement::Continuous(ContinuousMeasurement {
+            measurement: "population density".to_string(),
+            unit: Some("people/km²".to_string()),
+        });
+        let source = || {
+            MockFeatureCollectionSource::with_collections_and_measurements(
+                vec![collection.clone()],
+                [("population".to_string(), measurement.clone())].into(),
+            )
+            .boxed()
+        };
+
+        for (aggregation, expected) in [
+            (BurnAggregation::Sum, Measurement::Unitless),
+            (BurnAggregation::Mean, measurement.clone()),
+            (BurnAggregation::Max, measurement.clone()),
+        ] {
+            let rasterization = initialize(source(), params(Some("population"), aggregation))
+                .await
+                .unwrap();
+
+            assert_eq!(
+                rasterization.result_descriptor().bands[0].measurement,
+                expected
+            );
+            assert_eq!(
+                rasterization.result_descriptor().data_type,
+                RasterDataType::F64
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn invalid_params() {
+        let collection = MultiPolygonCollection::from_slices(
+            &[rectangle(0., 0., 1., 1.)],
+            &[TimeInterval::default()],
+            &[("landuse", FeatureData::Text(vec!["forest".to_string()]))],
+        )
+        .unwrap();
+        let source = || MockFeatureCollectionSource::single(collection.clone()).boxed();
+
+        assert!(matches!(
+            initialize(source(), params(Some("foo"), BurnAggregation::Sum)).await,
+            Err(error::Error::ColumnDoesNotExist { column }) if column == "foo"
+        ));
+        assert!(matches!(
+            initialize(source(), params(Some("landuse"), BurnAggregation::Sum)).await,
+            Err(error::Error::InvalidOperatorSpec { .. })
+        ));
+        assert!(matches!(
+            initialize(
+                source(),
+                BurnParams {
+                    classes: Some(vec!["forest".to_string()]),
+                    ..params(Some("landuse"), BurnAggregation::Sum)
+                }
+            )
+            .await,
+            Err(error::Error::InvalidOperatorSpec { .. })
+        ));
+        assert!(matches!(
+            initialize(
+                source(),
+                BurnParams {
+                    classes: Some(vec!["forest".to_string()]),
+                    ..params(None, BurnAggregation::Max)
+                }
+            )
+            .await,
+            Err(error::Error::InvalidOperatorSpec { .. })
+        ));
+    }
+
+    #[test]
+    fn deserialize() {
+        let params: GridOrDensity = serde_json::from_value(serde_json::json!({
+            "type": "burn",
+            "attribute": "value",
+            "aggregation": "mean",
+            "pixelSelection": "allTouched",
+        }))
+        .unwrap();
+
+        assert_eq!(
+            params,
+            GridOrDensity::Burn(BurnParams {
+                attribute: Some("value".to_string()),
+                aggregation: BurnAggregation::Mean,
+                pixel_selection: PixelSelection::AllTouched,
+                classes: None,
+            })
+        );
+    }
+}
@@ -37,6 +37,10 @@ use crate::util::{spawn_blocking, spawn_blocking_with_thread_pool};
 
 use typetag::serde;
 
+use self::burn::{BurnParams, InitializedBurnRasterization};
+
+mod burn;
+
 /// An operator that rasterizes vector data
 pub type Rasterization = Operator<GridOrDensity, SingleVectorSource>;
 
@@ -53,7 +57,7 @@ pub enum GridSizeMode {
     Relative,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[serde(tag = "type")]
 pub enum GridOrDensity {
@@ -61,6 +65,8 @@ pub enum GridOrDensity {
     Grid(GridParams),
     /// A heatmap calculated from a gaussian density function
     Density(DensityParams),
+    /// Burns points, lines and polygons with an attribute value into the pixels they cover
+    Burn(BurnParams),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
@@ -133,6 +139,15 @@ impl RasterOperator for Rasterization {
                 params.stddev,
             )
             .map(InitializedRasterOperator::boxed),
+            GridOrDensity::Burn(params) => InitializedBurnRasterization::new(
+                name,
+                path,
+                vector_source,
+                out_desc,
+                tiling_specification,
+                params,
+            )
+            .map(InitializedRasterOperator::boxed),
         }
     }
 
@@ -0,0 +1,631 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::{StreamExt, TryFutureExt, TryStreamExt, stream::BoxStream};
+use geoengine_datatypes::{
+    primitives::{
+        BandSelection, ClassificationMeasurement, Measurement, RasterQueryRectangle,
+        SpatialPartition2D,
+    },
+    raster::{MapElementsParallel, Pixel, RasterDataType, RasterTile2D},
+};
+use serde::{Deserialize, Serialize};
+use snafu::{Snafu, ensure};
+
+use crate::engine::{
+    CanonicOperatorName, ExecutionContext, InitializedRasterOperator, InitializedSources, Operator,
+    OperatorName, QueryContext, QueryProcessor, RasterBandDescriptor, RasterBandDescriptors,
+    RasterOperator, RasterQueryProcessor, RasterResultDescriptor, SingleRasterSource,
+    TypedRasterQueryProcessor, WorkflowOperatorPath,
+};
+use crate::util::Result;
+
+/// The `Reclassify` operator maps the pixel values of a raster to new values using a lookup table.
+///
+/// A pixel value is first looked up in the exact value mappings, then in the ranges in their given order.
+/// If neither matches, the `default` rule applies. NODATA pixels are handled by the `noDataValue`.
+/// All bands of the source are reclassified with the same table.
+pub type Reclassify = Operator<ReclassifyParams, SingleRasterSource>;
+
+impl OperatorName for Reclassify {
+    const TYPE_NAME: &'static str = "Reclassify";
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ReclassifyParams {
+    /// Input values that are mapped to new values. They take precedence over the ranges.
+    #[serde(default)]
+    pub mapping: Vec<ValueMapping>,
+    /// Input value ranges that are mapped to new values. The first matching range is used.
+    #[serde(default)]
+    pub ranges: Vec<RangeMapping>,
+    /// What to do with input values that neither match a value nor a range
+    #[serde(default)]
+    pub default: ReclassifyDefault,
+    /// The output value for NODATA input pixels. If it is not set, they stay NODATA.
+    #[serde(default)]
+    pub no_data_value: Option<f64>,
+    pub output_data_type: RasterDataType,
+    /// The names of the output classes. If they are set, the output bands get a classification measurement.
+    #[serde(default)]
+    pub classes: Option<ClassificationMeasurement>,
+}
+
+/// Maps the input value `from` to `to`, or to NODATA if `to` is not set
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ValueMapping {
+    pub from: f64,
+    pub to: Option<f64>,
+}
+
+/// Maps the input values in `[min, max)` to `to`, or to NODATA if `to` is not set
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RangeMapping {
+    pub min: f64,
+    pub max: f64,
+    pub to: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum ReclassifyDefault {
+    /// Unmatched values become NODATA
+    #[default]
+    NoData,
+    /// Unmatched values are kept. They are clipped at the bounds of the output data type.
+    Keep,
+    /// Unmatched values are mapped to `value`
+    Value { value: f64 },
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)), context(suffix(false)), module(error))]
+pub enum ReclassifyError {
+    #[snafu(display("The value {value} is mapped more than once"))]
+    DuplicateMapping { value: f64 },
+
+    #[snafu(display("The range [{min}, {max}) is empty or not a number"))]
+    InvalidRange { min: f64, max: f64 },
+
+    #[snafu(display("The output value {value} cannot be represented as {data_type:?}"))]
+    ValueNotRepresentable {
+        value: f64,
+        data_type: RasterDataType,
+    },
+
+    #[snafu(display("Class names require an integer output data type, but it is {data_type:?}"))]
+    ClassesRequireIntegerDataType { data_type: RasterDataType },
+}
+
+impl ReclassifyParams {
+    fn validate(&self) -> Result<(), ReclassifyError> {
+        let data_type = self.output_data_type;
+
+        let mut seen = HashSet::with_capacity(self.mapping.len());
+        for mapping in &self.mapping {
+            ensure!(
+                seen.insert(value_key(mapping.from)),
+                error::DuplicateMapping {
+                    value: mapping.from
+                }
+            );
+        }
+
+        for range in &self.ranges {
+            ensure!(
+                range.min < range.max,
+                error::InvalidRange {
+                    min: range.min,
+                    max: range.max
+                }
+            );
+        }
+
+        let default_value = match self.default {
+            ReclassifyDefault::Value { value } => Some(value),
+            ReclassifyDefault::NoData | ReclassifyDefault::Keep => None,
+        };
+
+        let output_values = self
+            .mapping
+            .iter()
+            .filter_map(|mapping| mapping.to)
+            .chain(self.ranges.iter().filter_map(|range| range.to))
+            .chain(default_value)
+            .chain(self.no_data_value);
+
+        for value in output_values {
+            ensure!(
+                data_type.is_valid(value),
+                error::ValueNotRepresentable { value, data_type }
+            );
+        }
+
+        if self.classes.is_some() {
+            ensure!(
+                !matches!(data_type, RasterDataType::F32 | RasterDataType::F64),
+                error::ClassesRequireIntegerDataType { data_type }
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// A hashable key for a pixel value that treats `0.0` and `-0.0` as equal
+fn value_key(value: f64) -> u64 {
+    if value == 0. {
+        0_f64.to_bits()
+    } else {
+        value.to_bits()
+    }
+}
+
+/// The lookup table of the reclassification
+#[derive(Debug, Clone)]
+struct ReclassificationTable {
+    mapping: HashMap<u64, Option<f64>>,
+    ranges: Vec<RangeMapping>,
+    default: ReclassifyDefault,
+    no_data_value: Option<f64>,
+}
+
+impl ReclassificationTable {
+    fn new(params: &ReclassifyParams) -> Self {
+        Self {
+            mapping: params
+                .mapping
+                .iter()
+                .map(|mapping| (value_key(mapping.from), mapping.to))
+                .collect(),
+            ranges: params.ranges.clone(),
+            default: params.default,
+            no_data_value: params.no_data_value,
+        }
+    }
+
+    fn reclassify(&self, value: Option<f64>) -> Option<f64> {
+        let Some(value) = value else {
+            return self.no_data_value;
+        };
+
+        if let Some(&to) = self.mapping.get(&value_key(value)) {
+            return to;
+        }
+
+        if let Some(range) = self
+            .ranges
+            .iter()
+            .find(|range| range.min <= value && value < range.max)
+        {
+            return range.to;
+        }
+
+        match self.default {
+            ReclassifyDefault::NoData => None,
+            ReclassifyDefault::Keep => Some(value),
+            ReclassifyDefault::Value { value: default } => Some(default),
+        }
+    }
+}
+
+#[typetag::serde]
+#[async_trait]
+impl RasterOperator for Reclassify {
+    async fn _initialize(
+        self: Box<Self>,
+        path: WorkflowOperatorPath,
+        context: &dyn ExecutionContext,
+    ) -> Result<Box<dyn InitializedRasterOperator>> {
+        let name = CanonicOperatorName::from(&self);
+
+        self.params.validate()?;
+
+        let initialized_sources = self
+            .sources
+            .initialize_sources(path.clone(), context)
+            .await?;
+        let in_desc = initialized_sources.raster.result_descriptor();
+
+        let measurement = self
+            .params
+            .classes
+            .clone()
+            .map_or(Measurement::Unitless, Measurement::Classification);
+
+        let bands = in_desc
+            .bands
+            .iter()
+            .map(|band| RasterBandDescriptor::new(band.name.clone(), measurement.clone()))
+            .collect::<Vec<_>>();
+
+        let result_descriptor = RasterResultDescriptor {
+            spatial_reference: in_desc.spatial_reference,
+            data_type: self.params.output_data_type,
+            bbox: in_desc.bbox,
+            time: in_desc.time,
+            resolution: in_desc.resolution,
+            bands: RasterBandDescriptors::new(bands)?,
+        };
+
+        let initialized_operator = InitializedReclassify {
+            name,
+            path,
+            result_descriptor,
+            source: initialized_sources.raster,
+            table: Arc::new(ReclassificationTable::new(&self.params)),
+        };
+
+        Ok(initialized_operator.boxed())
+    }
+
+    span_fn!(Reclassify);
+}
+
+pub struct InitializedReclassify {
+    name: CanonicOperatorName,
+    path: WorkflowOperatorPath,
+    result_descriptor: RasterResultDescriptor,
+    source: Box<dyn InitializedRasterOperator>,
+    table: Arc<ReclassificationTable>,
+}
+
+impl InitializedRasterOperator for InitializedReclassify {
+    fn result_descriptor(&self) -> &RasterResultDescriptor {
+        &self.result_descriptor
+    }
+
+    fn query_processor(&self) -> Result<TypedRasterQueryProcessor> {
+        let source = self.source.query_processor()?;
+        let out_data_type = self.result_descriptor.data_type;
+
+        let res_op = call_on_generic_raster_processor!(source, source_proc => {
+            call_generic_raster_processor!(out_data_type,
+                ReclassifyProcessor::new(
+                    source_proc,
+                    self.result_descriptor.clone(),
+                    self.table.clone(),
+                )
+                .boxed()
+            )
+        });
+
+        Ok(res_op)
+    }
+
+    fn canonic_name(&self) -> CanonicOperatorName {
+        self.name.clone()
+    }
+
+    fn name(&self) -> &'static str {
+        Reclassify::TYPE_NAME
+    }
+
+    fn path(&self) -> WorkflowOperatorPath {
+        self.path.clone()
+    }
+}
+
+struct ReclassifyProcessor<Q, PIn, POut>
+where
+    Q: RasterQueryProcessor<RasterType = PIn>,
+{
+    source: Q,
+    result_descriptor: RasterResultDescriptor,
+    table: Arc<ReclassificationTable>,
+    _p_out: std::marker::PhantomData<POut>,
+}
+
+impl<Q, PIn, POut> ReclassifyProcessor<Q, PIn, POut>
+where
+    Q: RasterQueryProcessor<RasterType = PIn>,
+    PIn: Pixel,
+    POut: Pixel,
+{
+    fn new(
+        source: Q,
+        result_descriptor: RasterResultDescriptor,
+        table: Arc<ReclassificationTable>,
+    ) -> Self {
+        Self {
+            source,
+            result_descriptor,
+            table,
+            _p_out: std::marker::PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<Q, PIn, POut> QueryProcessor for ReclassifyProcessor<Q, PIn, POut>
+where
+    Q: RasterQueryProcessor<RasterType = PIn>,
+    PIn: Pixel,
+    POut: Pixel,
+{
+    type Output = RasterTile2D<POut>;
+    type SpatialBounds = SpatialPartition2D;
+    type Selection = BandSelection;
+    type ResultDescription = RasterResultDescriptor;
+
+    async fn _query<'a>(
+        &'a self,
+        query: RasterQueryRectangle,
+        ctx: &'a dyn QueryContext,
+    ) -> Result<BoxStream<'a, Result<Self::Output>>> {
+        let stream = self.source.raster_query(query, ctx).await?;
+
+        let reclassified_stream = stream.and_then(move |tile| {
+            let table = self.table.clone();
+
+            crate::util::spawn_blocking_with_thread_pool(ctx.thread_pool().clone(), move || {
+                let map_fn = |value: Option<PIn>| {
+                    let value: Option<f64> = value.map(|value| value.as_());
+                    table.reclassify(value).map(POut::from_)
+                };
+
+                tile.map_elements_parallel(map_fn)
+            })
+            .map_err(Into::into)
+        });
+
+        Ok(reclassified_stream.boxed())
+    }
+
+    fn result_descriptor(&self) -> &Self::ResultDescription {
+        &self.result_descriptor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{ChunkByteSize, MockExecutionContext};
+    use crate::mock::{MockRasterSource, MockRasterSourceParams};
+    use geoengine_datatypes::primitives::{CacheHint, SpatialResolution, TimeInterval};
+    use geoengine_datatypes::raster::{
+        EmptyGrid2D, Grid2D, GridOrEmpty, MaskedGrid2D, TileInformation, TilingSpecification,
+    };
+    use geoengine_datatypes::spatial_reference::SpatialReference;
+    use geoengine_datatypes::util::test::TestDefault;
+
+    fn params() -> ReclassifyParams {
+        ReclassifyParams {
+            mapping: vec![
+                ValueMapping {
+                    from: 1.,
+                    to: Some(10.),
+                },
+                ValueMapping { from: 2., to: None },
+            ],
+            ranges: vec![
+                RangeMapping {
+                    min: 0.,
+                    max: 5.,
+                    to: Some(20.),
+                },
+                RangeMapping {
+                    min: 3.,
+                    max: 10.,
+                    to: Some(30.),
+                },
+            ],
+            default: ReclassifyDefault::Value { value: 40. },
+            no_data_value: Some(50.),
+            output_data_type: RasterDataType::U8,
+            classes: Some(ClassificationMeasurement {
+                measurement: "land cover".to_string(),
+                classes: [(10, "forest".to_string()), (20, "water".to_string())]
+                    .into_iter()
+                    .collect(),
+            }),
+        }
+    }
+
+    #[test]
+    fn it_deserializes() {
+        let params: ReclassifyParams = serde_json::from_value(serde_json::json!({
+            "mapping": [{ "from": 1, "to": 10 }, { "from": 2, "to": null }],
+            "ranges": [{ "min": 0, "max": 5, "to": 20 }, { "min": 3, "max": 10, "to": 30 }],
+            "default": { "type": "value", "value": 40 },
+            "noDataValue": 50,
+            "outputDataType": "U8",
+            "classes": {
+                "measurement": "land cover",
+                "classes": { "10": "forest", "20": "water" }
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(params, self::params());
+
+        let params: ReclassifyParams = serde_json::from_value(serde_json::json!({
+            "outputDataType": "F32"
+        }))
+        .unwrap();
+
+        assert!(params.mapping.is_empty());
+        assert_eq!(params.default, ReclassifyDefault::NoData);
+        assert_eq!(params.no_data_value, None);
+    }
+
+    #[test]
+    fn it_reclassifies_values() {
+        let table = ReclassificationTable::new(&params());
+
+        assert_eq!(table.reclassify(Some(1.)), Some(10.));
+        assert_eq!(table.reclassify(Some(2.)), None);
+        // the first matching range wins
+        assert_eq!(table.reclassify(Some(4.)), Some(20.));
+        assert_eq!(table.reclassify(Some(5.)), Some(30.));
+        assert_eq!(table.reclassify(Some(10.)), Some(40.));
+        assert_eq!(table.reclassify(None), Some(50.));
+
+        let table = ReclassificationTable::new(&ReclassifyParams {
+            default: ReclassifyDefault::Keep,
+            no_data_value: None,
+            ..params()
+        });
+
+        assert_eq!(table.reclassify(Some(-0.)), Some(20.));
+        assert_eq!(table.reclassify(Some(12.)), Some(12.));
+        assert_eq!(table.reclassify(None), None);
+    }
+
+    #[test]
+    fn it_validates() {
+        assert!(params().validate().is_ok());
+
+        assert!(matches!(
+            ReclassifyParams {
+                mapping: vec![
+                    ValueMapping {
+                        from: 1.,
+                        to: Some(1.)
+                    };
+                    2
+                ],
+                ..params()
+            }
+            .validate(),
+            Err(ReclassifyError::DuplicateMapping { .. })
+        ));
+
+        assert!(matches!(
+            ReclassifyParams {
+                ranges: vec![RangeMapping {
+                    min: 5.,
+                    max: f64::NAN,
+                    to: Some(1.)
+                }],
+                ..params()
+            }
+            .validate(),
+            Err(ReclassifyError::InvalidRange { .. })
+        ));
+
+        assert!(matches!(
+            ReclassifyParams {
+                default: ReclassifyDefault::Value { value: 256. },
+                ..params()
+            }
+            .validate(),
+            Err(ReclassifyError::ValueNotRepresentable { .. })
+        ));
+
+        assert!(matches!(
+            ReclassifyParams {
+                output_data_type: RasterDataType::F32,
+                ..params()
+            }
+            .validate(),
+            Err(ReclassifyError::ClassesRequireIntegerDataType { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_reclassifies_rasters() {
+        let tiling_specification = TilingSpecification {
+            origin_coordinate: [0.0, 0.0].into(),
+            tile_size_in_pixels: [2, 2].into(),
+        };
+
+        let tile = |x: isize, grid: GridOrEmpty<_, i16>| {
+            RasterTile2D::new_with_tile_info(
+                TimeInterval::default(),
+                TileInformation {
+                    global_geo_transform: TestDefault::test_default(),
+                    global_tile_position: [-1, x].into(),
+                    tile_size_in_pixels: [2, 2].into(),
+                },
+                0,
+                grid,
+                CacheHint::default(),
+            )
+        };
+
+        let grid = MaskedGrid2D::new(
+            Grid2D::new([2, 2].into(), vec![1_i16, 4, 7, 0]).unwrap(),
+            Grid2D::new([2, 2].into(), vec![true, true, true, false]).unwrap(),
+        )
+        .unwrap();
+
+        let source = MockRasterSource {
+            params: MockRasterSourceParams {
+                data: vec![
+                    tile(0, grid.into()),
+                    tile(1, EmptyGrid2D::new([2, 2].into()).into()),
+                ],
+                result_descriptor: RasterResultDescriptor {
+                    data_type: RasterDataType::I16,
+                    spatial_reference: SpatialReference::epsg_4326().into(),
+                    bbox: None,
+                    time: None,
+                    resolution: None,
+                    bands: RasterBandDescriptors::new_single_band(),
+                },
+            },
+        }
+        .boxed();
+
+        let ctx = MockExecutionContext::new_with_tiling_spec(tiling_specification);
+        let query_ctx = ctx.mock_query_context(ChunkByteSize::test_default());
+
+        let operator = Reclassify {
+            params: params(),
+            sources: SingleRasterSource { raster: source },
+        }
+        .boxed()
+        .initialize(WorkflowOperatorPath::initialize_root(), &ctx)
+        .await
+        .unwrap();
+
+        assert_eq!(operator.result_descriptor().data_type, RasterDataType::U8);
+        assert_eq!(
+            operator.result_descriptor().bands[0].measurement,
+            Measurement::Classification(params().classes.unwrap())
+        );
+
+        let TypedRasterQueryProcessor::U8(processor) = operator.query_processor().unwrap() else {
+            panic!("expected TypedRasterQueryProcessor::U8");
+        };
+
+        let tiles = processor
+            .raster_query(
+                RasterQueryRectangle {
+                    spatial_bounds: SpatialPartition2D::new((0., 2.).into(), (4., 0.).into())
+                        .unwrap(),
+                    time_interval: TimeInterval::default(),
+                    spatial_resolution: SpatialResolution::one(),
+                    attributes: BandSelection::first(),
+                },
+                &query_ctx,
+            )
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(tiles.len(), 2);
+
+        let values = |tile: &RasterTile2D<u8>| {
+            tile.grid_array
+                .clone()
+                .into_materialized_masked_grid()
+                .masked_element_deref_iterator()
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            values(&tiles[0]),
+            vec![Some(10), Some(20), Some(30), Some(50)]
+        );
+        // empty tiles are filled with the NODATA replacement
+        assert_eq!(values(&tiles[1]), vec![Some(50); 4]);
+    }
+}
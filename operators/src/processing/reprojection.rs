@@ -1,10 +1,12 @@
 use std::marker::PhantomData;
 
 use super::map_query::MapQueryProcessor;
+use super::{DownsamplingMethod, InterpolationMethod};
 use crate::{
     adapters::{
-        FillerTileCacheExpirationStrategy, FillerTimeBounds, RasterSubQueryAdapter,
-        SparseTilesFillAdapter, TileReprojectionSubQuery, fold_by_coordinate_lookup_future,
+        FillerTileCacheExpirationStrategy, FillerTimeBounds, RasterSubQueryAdapter, Resampling,
+        SparseTilesFillAdapter, TileReprojectionResamplingSubQuery, TileReprojectionSubQuery,
+        fold_by_coordinate_lookup_future,
     },
     engine::{
         CanonicOperatorName, ExecutionContext, InitializedRasterOperator, InitializedSources,
@@ -20,7 +22,12 @@ use async_trait::async_trait;
 use futures::stream::BoxStream;
 use futures::{StreamExt, stream};
 use geoengine_datatypes::{
-    collections::FeatureCollection,
+    collections::{
+        FeatureCollection, GeoFeatureCollectionModifications, IntoGeometryIterator,
+        MultiLineStringCollection, MultiPointCollection, MultiPolygonCollection,
+    },
+    operations::antimeridian::SplitAtAntimeridian,
+    operations::densify::{Densification, ReprojectDensified},
     operations::reproject::{
         CoordinateProjection, CoordinateProjector, Reproject, ReprojectClipped,
         reproject_and_unify_bbox, reproject_query, suggest_pixel_size_from_diag_cross_projected,
@@ -29,16 +36,66 @@ use geoengine_datatypes::{
         BandSelection, BoundingBox2D, ColumnSelection, Geometry, RasterQueryRectangle,
         SpatialPartition2D, SpatialPartitioned, SpatialResolution, VectorQueryRectangle,
     },
-    raster::{Pixel, RasterTile2D, TilingSpecification},
+    raster::{Bicubic, Bilinear, Lanczos, Pixel, RasterTile2D, TilingSpecification},
     spatial_reference::SpatialReference,
     util::arrow::ArrowTyped,
 };
 use serde::{Deserialize, Serialize};
+use snafu::ensure;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
 pub struct ReprojectionParams {
     pub target_spatial_reference: SpatialReference,
+    /// How to sample the source raster at the projected pixel positions
+    #[serde(default)]
+    pub interpolation: InterpolationMethod,
+    /// How to aggregate the source pixels if an output pixel covers multiple source pixels.
+    /// If it is not set, the source is queried in the coarser resolution.
+    #[serde(default)]
+    pub downsampling: Option<DownsamplingMethod>,
+    /// Insert additional vertices into lines and polygon edges before reprojecting them
+    #[serde(default)]
+    pub densification: Option<Densification>,
+    /// Split lines and polygons that cross the antimeridian when reprojecting to EPSG:4326
+    #[serde(default)]
+    pub split_at_antimeridian: bool,
+}
+
+/// The settings for computing the output pixels of a raster reprojection from the source pixels
+#[derive(Debug, Clone, Copy)]
+pub struct RasterResampling {
+    interpolation: InterpolationMethod,
+    downsampling: Option<DownsamplingMethod>,
+    source_resolution: Option<SpatialResolution>,
+}
+
+impl RasterResampling {
+    /// The resampling for an output pixel of `in_spatial_res` size in the source projection
+    fn resampling(
+        &self,
+        in_spatial_res: SpatialResolution,
+    ) -> Option<(Resampling, SpatialResolution)> {
+        // aggregate the source pixels if they are finer than the output pixels
+        let downsampling =
+            self.downsampling
+                .zip(self.source_resolution)
+                .filter(|(_, source_resolution)| {
+                    source_resolution.x < in_spatial_res.x && source_resolution.y < in_spatial_res.y
+                });
+        if let Some((method, source_resolution)) = downsampling {
+            return Some((Resampling::Aggregate(method), source_resolution));
+        }
+
+        let (radius, kernel): (usize, fn(f64) -> f64) = match self.interpolation {
+            InterpolationMethod::NearestNeighbor => return None,
+            InterpolationMethod::BiLinear => (1, Bilinear::kernel),
+            InterpolationMethod::BiCubic => (2, Bicubic::kernel),
+            InterpolationMethod::Lanczos => (3, Lanczos::kernel),
+        };
+
+        Some((Resampling::Kernel { radius, kernel }, in_spatial_res))
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
@@ -62,6 +119,8 @@ pub struct InitializedVectorReprojection {
     source: Box<dyn InitializedVectorOperator>,
     source_srs: SpatialReference,
     target_srs: SpatialReference,
+    densification: Option<Densification>,
+    split_at_antimeridian: bool,
 }
 
 pub struct InitializedRasterReprojection {
@@ -73,6 +132,7 @@ pub struct InitializedRasterReprojection {
     source_srs: SpatialReference,
     target_srs: SpatialReference,
     tiling_spec: TilingSpecification,
+    resampling: RasterResampling,
 }
 
 impl InitializedVectorReprojection {
@@ -93,6 +153,15 @@ impl InitializedVectorReprojection {
         let in_srs = Into::<Option<SpatialReference>>::into(in_desc.spatial_reference)
             .ok_or(Error::AllSourcesMustHaveSameSpatialReference)?;
 
+        if let Some(densification) = params.densification {
+            ensure!(
+                densification.is_valid(),
+                error::InvalidOperatorSpec {
+                    reason: "the densification length or tolerance must be positive".to_string(),
+                }
+            );
+        }
+
         let bbox = if let Some(bbox) = in_desc.bbox {
             let projector =
                 CoordinateProjector::from_known_srs(in_srs, params.target_spatial_reference)?;
@@ -117,6 +186,10 @@ impl InitializedVectorReprojection {
             source: source_vector_operator,
             source_srs: in_srs,
             target_srs: params.target_spatial_reference,
+            densification: params.densification,
+            // only geographic coordinates wrap around at the antimeridian
+            split_at_antimeridian: params.split_at_antimeridian
+                && params.target_spatial_reference == SpatialReference::epsg_4326(),
         })
     }
 }
@@ -168,6 +241,11 @@ impl InitializedRasterReprojection {
             source_srs: in_srs,
             target_srs: params.target_spatial_reference,
             tiling_spec,
+            resampling: RasterResampling {
+                interpolation: params.interpolation,
+                downsampling: params.downsampling,
+                source_resolution: in_desc.resolution,
+            },
         })
     }
 
@@ -264,6 +342,8 @@ impl InitializedVectorOperator for InitializedVectorReprojection {
                         self.result_descriptor.clone(),
                         source_srs,
                         target_srs,
+                        self.densification,
+                        self.split_at_antimeridian,
                     )
                     .boxed(),
                 ))
@@ -275,6 +355,8 @@ impl InitializedVectorOperator for InitializedVectorReprojection {
                         self.result_descriptor.clone(),
                         source_srs,
                         target_srs,
+                        self.densification,
+                        self.split_at_antimeridian,
                     )
                     .boxed(),
                 ))
@@ -286,6 +368,8 @@ impl InitializedVectorOperator for InitializedVectorReprojection {
                         self.result_descriptor.clone(),
                         source_srs,
                         target_srs,
+                        self.densification,
+                        self.split_at_antimeridian,
                     )
                     .boxed(),
                 ))
@@ -314,6 +398,8 @@ where
     result_descriptor: VectorResultDescriptor,
     from: SpatialReference,
     to: SpatialReference,
+    densification: Option<Densification>,
+    split_at_antimeridian: bool,
 }
 
 impl<Q, G> VectorReprojectionProcessor<Q, G>
@@ -325,16 +411,84 @@ where
         result_descriptor: VectorResultDescriptor,
         from: SpatialReference,
         to: SpatialReference,
+        densification: Option<Densification>,
+        split_at_antimeridian: bool,
     ) -> Self {
         Self {
             source,
             result_descriptor,
             from,
             to,
+            densification,
+            split_at_antimeridian,
         }
     }
 }
 
+/// Reprojection of the geometries of a feature collection
+trait ReprojectCollection: Sized {
+    fn reproject_collection(
+        &self,
+        projector: &CoordinateProjector,
+        densification: Option<Densification>,
+        split_at_antimeridian: bool,
+    ) -> Result<Self>;
+}
+
+impl ReprojectCollection for MultiPointCollection {
+    /// Points are neither densified nor split
+    fn reproject_collection(
+        &self,
+        projector: &CoordinateProjector,
+        _densification: Option<Densification>,
+        _split_at_antimeridian: bool,
+    ) -> Result<Self> {
+        Ok(self.reproject(projector)?)
+    }
+}
+
+/// Implements `ReprojectCollection` for collections whose geometries can be densified and split at the antimeridian
+macro_rules! impl_reproject_collection_densified {
+    ($($collection:ty),*) => {
+        $(
+            impl ReprojectCollection for $collection {
+                fn reproject_collection(
+                    &self,
+                    projector: &CoordinateProjector,
+                    densification: Option<Densification>,
+                    split_at_antimeridian: bool,
+                ) -> Result<Self> {
+                    if densification.is_none() && !split_at_antimeridian {
+                        return Ok(self.reproject(projector)?);
+                    }
+
+                    let geometries = self
+                        .geometries()
+                        .map(|geometry| {
+                            let projected = match densification {
+                                Some(densification) => {
+                                    geometry.reproject_densified(projector, densification)?
+                                }
+                                None => geometry.reproject(projector)?,
+                            };
+
+                            if split_at_antimeridian {
+                                projected.split_at_antimeridian()
+                            } else {
+                                Ok(projected)
+                            }
+                        })
+                        .collect::<geoengine_datatypes::util::Result<Vec<_>>>()?;
+
+                    Ok(self.replace_geometries(geometries)?)
+                }
+            }
+        )*
+    };
+}
+
+impl_reproject_collection_densified!(MultiLineStringCollection, MultiPolygonCollection);
+
 #[async_trait]
 impl<Q, G> QueryProcessor for VectorReprojectionProcessor<Q, G>
 where
@@ -344,7 +498,7 @@ where
             Selection = ColumnSelection,
             ResultDescription = VectorResultDescriptor,
         >,
-    FeatureCollection<G>: Reproject<CoordinateProjector, Out = FeatureCollection<G>>,
+    FeatureCollection<G>: ReprojectCollection,
     G: Geometry + ArrowTyped,
 {
     type Output = FeatureCollection<G>;
@@ -366,9 +520,12 @@ where
                 .await?
                 .map(move |collection_result| {
                     collection_result.and_then(|collection| {
-                        CoordinateProjector::from_known_srs(self.from, self.to)
-                            .and_then(|projector| collection.reproject(projector.as_ref()))
-                            .map_err(Into::into)
+                        let projector = CoordinateProjector::from_known_srs(self.from, self.to)?;
+                        collection.reproject_collection(
+                            &projector,
+                            self.densification,
+                            self.split_at_antimeridian,
+                        )
                     })
                 })
                 .boxed())
@@ -441,6 +598,7 @@ impl InitializedRasterOperator for InitializedRasterReprojection {
                     self.target_srs,
                     self.tiling_spec,
                     self.state,
+                    self.resampling,
                 )))
             }
             geoengine_datatypes::raster::RasterDataType::U16 => {
@@ -454,6 +612,7 @@ impl InitializedRasterOperator for InitializedRasterReprojection {
                     self.target_srs,
                     self.tiling_spec,
                     self.state,
+                    self.resampling,
                 )))
             }
 
@@ -468,6 +627,7 @@ impl InitializedRasterOperator for InitializedRasterReprojection {
                     self.target_srs,
                     self.tiling_spec,
                     self.state,
+                    self.resampling,
                 )))
             }
             geoengine_datatypes::raster::RasterDataType::U64 => {
@@ -481,6 +641,7 @@ impl InitializedRasterOperator for InitializedRasterReprojection {
                     self.target_srs,
                     self.tiling_spec,
                     self.state,
+                    self.resampling,
                 )))
             }
             geoengine_datatypes::raster::RasterDataType::I8 => {
@@ -494,6 +655,7 @@ impl InitializedRasterOperator for InitializedRasterReprojection {
                     self.target_srs,
                     self.tiling_spec,
                     self.state,
+                    self.resampling,
                 )))
             }
             geoengine_datatypes::raster::RasterDataType::I16 => {
@@ -507,6 +669,7 @@ impl InitializedRasterOperator for InitializedRasterReprojection {
                     self.target_srs,
                     self.tiling_spec,
                     self.state,
+                    self.resampling,
                 )))
             }
             geoengine_datatypes::raster::RasterDataType::I32 => {
@@ -520,6 +683,7 @@ impl InitializedRasterOperator for InitializedRasterReprojection {
                     self.target_srs,
                     self.tiling_spec,
                     self.state,
+                    self.resampling,
                 )))
             }
             geoengine_datatypes::raster::RasterDataType::I64 => {
@@ -533,6 +697,7 @@ impl InitializedRasterOperator for InitializedRasterReprojection {
                     self.target_srs,
                     self.tiling_spec,
                     self.state,
+                    self.resampling,
                 )))
             }
             geoengine_datatypes::raster::RasterDataType::F32 => {
@@ -546,6 +711,7 @@ impl InitializedRasterOperator for InitializedRasterReprojection {
                     self.target_srs,
                     self.tiling_spec,
                     self.state,
+                    self.resampling,
                 )))
             }
             geoengine_datatypes::raster::RasterDataType::F64 => {
@@ -559,6 +725,7 @@ impl InitializedRasterOperator for InitializedRasterReprojection {
                     self.target_srs,
                     self.tiling_spec,
                     self.state,
+                    self.resampling,
                 )))
             }
         })
@@ -587,6 +754,7 @@ where
     to: SpatialReference,
     tiling_spec: TilingSpecification,
     state: Option<ReprojectionBounds>,
+    resampling: RasterResampling,
     _phantom_data: PhantomData<P>,
 }
 
@@ -607,6 +775,7 @@ where
         to: SpatialReference,
         tiling_spec: TilingSpecification,
         state: Option<ReprojectionBounds>,
+        resampling: RasterResampling,
     ) -> Self {
         Self {
             source,
@@ -615,6 +784,7 @@ where
             to,
             tiling_spec,
             state,
+            resampling,
             _phantom_data: PhantomData,
         }
     }
@@ -652,6 +822,30 @@ where
                 query.spatial_resolution,
             )?;
 
+            if let Some((resampling, query_spatial_res)) =
+                self.resampling.resampling(in_spatial_res)
+            {
+                let sub_query_spec = TileReprojectionResamplingSubQuery {
+                    in_srs: self.from,
+                    out_srs: self.to,
+                    in_spatial_res,
+                    query_spatial_res,
+                    valid_bounds_in,
+                    valid_bounds_out,
+                    resampling,
+                    _phantom_data: PhantomData,
+                };
+
+                return Ok(RasterSubQueryAdapter::<'a, P, _, _>::new(
+                    &self.source,
+                    query,
+                    self.tiling_spec,
+                    ctx,
+                    sub_query_spec,
+                )
+                .filter_and_fill(FillerTileCacheExpirationStrategy::DerivedFromSurroundingTiles));
+            }
+
             // setup the subquery
             let sub_query_spec = TileReprojectionSubQuery {
                 in_srs: self.from,
@@ -731,8 +925,9 @@ mod tests {
         dataset::{DataId, DatasetId},
         hashmap,
         primitives::{
-            BoundingBox2D, MultiLineString, MultiPoint, MultiPolygon, QueryRectangle,
-            SpatialResolution, TimeGranularity, TimeInstance, TimeInterval, TimeStep,
+            BoundingBox2D, MultiLineString, MultiLineStringAccess, MultiPoint, MultiPolygon,
+            QueryRectangle, SpatialResolution, TimeGranularity, TimeInstance, TimeInterval,
+            TimeStep,
         },
         raster::{Grid, GridShape, GridShape2D, GridSize, RasterDataType, RasterTile2D},
         spatial_reference::SpatialReferenceAuthority,
@@ -778,6 +973,10 @@ mod tests {
         let initialized_operator = VectorOperator::boxed(Reprojection {
             params: ReprojectionParams {
                 target_spatial_reference,
+                interpolation: InterpolationMethod::NearestNeighbor,
+                downsampling: None,
+                densification: None,
+                split_at_antimeridian: false,
             },
             sources: SingleRasterOrVectorSource {
                 source: point_source.into(),
@@ -855,6 +1054,10 @@ mod tests {
         let initialized_operator = VectorOperator::boxed(Reprojection {
             params: ReprojectionParams {
                 target_spatial_reference,
+                interpolation: InterpolationMethod::NearestNeighbor,
+                downsampling: None,
+                densification: None,
+                split_at_antimeridian: false,
             },
             sources: SingleRasterOrVectorSource {
                 source: lines_source.into(),
@@ -939,6 +1142,10 @@ mod tests {
         let initialized_operator = VectorOperator::boxed(Reprojection {
             params: ReprojectionParams {
                 target_spatial_reference,
+                interpolation: InterpolationMethod::NearestNeighbor,
+                downsampling: None,
+                densification: None,
+                split_at_antimeridian: false,
             },
             sources: SingleRasterOrVectorSource {
                 source: polygon_source.into(),
@@ -985,6 +1192,121 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn multi_lines_densified() -> Result<()> {
+        let lines = MultiLineStringCollection::from_data(
+            vec![MultiLineString::new(vec![vec![COLOGNE_EPSG_4326, HAMBURG_EPSG_4326]]).unwrap()],
+            vec![TimeInterval::new_unchecked(0, 1); 1],
+            Default::default(),
+            CacheHint::default(),
+        )?;
+
+        let lines_source = MockFeatureCollectionSource::single(lines).boxed();
+
+        let initialized_operator = VectorOperator::boxed(Reprojection {
+            params: ReprojectionParams {
+                target_spatial_reference: SpatialReference::new(
+                    SpatialReferenceAuthority::Epsg,
+                    900_913,
+                ),
+                interpolation: InterpolationMethod::NearestNeighbor,
+                downsampling: None,
+                densification: Some(Densification::MaxSegmentLength {
+                    max_segment_length: 1.,
+                }),
+                split_at_antimeridian: false,
+            },
+            sources: SingleRasterOrVectorSource {
+                source: lines_source.into(),
+            },
+        })
+        .initialize(
+            WorkflowOperatorPath::initialize_root(),
+            &MockExecutionContext::test_default(),
+        )
+        .await?;
+
+        let query_processor = initialized_operator
+            .query_processor()?
+            .multi_line_string()
+            .unwrap();
+
+        let query_rectangle = VectorQueryRectangle {
+            spatial_bounds: BoundingBox2D::new(
+                (COLOGNE_EPSG_4326.x, COLOGNE_EPSG_4326.y).into(),
+                (HAMBURG_EPSG_4326.x, HAMBURG_EPSG_4326.y).into(),
+            )
+            .unwrap(),
+            time_interval: TimeInterval::default(),
+            spatial_resolution: SpatialResolution::zero_point_one(),
+            attributes: ColumnSelection::all(),
+        };
+        let ctx = MockQueryContext::new(ChunkByteSize::MAX);
+
+        let result = query_processor
+            .query(query_rectangle, &ctx)
+            .await?
+            .map(Result::unwrap)
+            .collect::<Vec<MultiLineStringCollection>>()
+            .await;
+
+        assert_eq!(result.len(), 1);
+
+        let line: MultiLineString = result[0].geometries().next().unwrap().into();
+        let line = &line.lines()[0];
+
+        // the line is about four degrees long and gets split into five segments
+        assert_eq!(line.len(), 6);
+        assert!(approx_eq!(
+            Coordinate2D,
+            line[0],
+            COLOGNE_EPSG_900_913,
+            epsilon = 0.00001
+        ));
+        assert!(approx_eq!(
+            Coordinate2D,
+            line[5],
+            HAMBURG_EPSG_900_913,
+            epsilon = 0.00001
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn it_rejects_invalid_densification() {
+        let lines = MultiLineStringCollection::from_data(
+            vec![MultiLineString::new(vec![vec![COLOGNE_EPSG_4326, HAMBURG_EPSG_4326]]).unwrap()],
+            vec![TimeInterval::new_unchecked(0, 1); 1],
+            Default::default(),
+            CacheHint::default(),
+        )
+        .unwrap();
+
+        let result = VectorOperator::boxed(Reprojection {
+            params: ReprojectionParams {
+                target_spatial_reference: SpatialReference::new(
+                    SpatialReferenceAuthority::Epsg,
+                    900_913,
+                ),
+                interpolation: InterpolationMethod::NearestNeighbor,
+                downsampling: None,
+                densification: Some(Densification::ErrorTolerance { tolerance: -1. }),
+                split_at_antimeridian: false,
+            },
+            sources: SingleRasterOrVectorSource {
+                source: MockFeatureCollectionSource::single(lines).boxed().into(),
+            },
+        })
+        .initialize(
+            WorkflowOperatorPath::initialize_root(),
+            &MockExecutionContext::test_default(),
+        )
+        .await;
+
+        assert!(matches!(result, Err(Error::InvalidOperatorSpec { .. })));
+    }
+
     #[tokio::test]
     async fn raster_identity() -> Result<()> {
         let projection = SpatialReference::new(
@@ -1061,6 +1383,10 @@ mod tests {
         let initialized_operator = RasterOperator::boxed(Reprojection {
             params: ReprojectionParams {
                 target_spatial_reference: projection, // This test will do a identity reprojection
+                interpolation: InterpolationMethod::NearestNeighbor,
+                downsampling: None,
+                densification: None,
+                split_at_antimeridian: false,
             },
             sources: SingleRasterOrVectorSource {
                 source: mrs1.into(),
@@ -1093,6 +1419,111 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn raster_identity_with_downsampling() -> Result<()> {
+        let data = [
+            ((0, 5), [-1, 0], vec![1, 2, 3, 4]),
+            ((0, 5), [-1, 1], vec![7, 8, 9, 10]),
+            ((5, 10), [-1, 0], vec![13, 14, 15, 16]),
+            ((5, 10), [-1, 1], vec![19, 20, 21, 22]),
+        ]
+        .into_iter()
+        .map(|((start, end), tile_position, values)| RasterTile2D {
+            time: TimeInterval::new_unchecked(start, end),
+            tile_position: tile_position.into(),
+            band: 0,
+            global_geo_transform: TestDefault::test_default(),
+            grid_array: Grid::new([2, 2].into(), values).unwrap().into(),
+            properties: Default::default(),
+            cache_hint: CacheHint::default(),
+        })
+        .collect::<Vec<_>>();
+
+        let mrs1 = MockRasterSource {
+            params: MockRasterSourceParams {
+                data,
+                result_descriptor: RasterResultDescriptor {
+                    data_type: RasterDataType::U8,
+                    spatial_reference: SpatialReference::epsg_4326().into(),
+                    time: None,
+                    bbox: None,
+                    resolution: Some(SpatialResolution::one()),
+                    bands: RasterBandDescriptors::new_single_band(),
+                },
+            },
+        }
+        .boxed();
+
+        let mut exe_ctx = MockExecutionContext::test_default();
+        exe_ctx.tiling_specification.tile_size_in_pixels = GridShape {
+            shape_array: [2, 2],
+        };
+
+        let query_ctx = MockQueryContext::test_default();
+
+        let initialized_operator = RasterOperator::boxed(Reprojection {
+            params: ReprojectionParams {
+                target_spatial_reference: SpatialReference::epsg_4326(),
+                interpolation: InterpolationMethod::BiCubic,
+                downsampling: Some(DownsamplingMethod::Max),
+                densification: None,
+                split_at_antimeridian: false,
+            },
+            sources: SingleRasterOrVectorSource {
+                source: mrs1.into(),
+            },
+        })
+        .initialize(WorkflowOperatorPath::initialize_root(), &exe_ctx)
+        .await?;
+
+        let qp = initialized_operator
+            .query_processor()
+            .unwrap()
+            .get_u8()
+            .unwrap();
+
+        // an output pixel covers 2x2 source pixels
+        let query_rect = RasterQueryRectangle {
+            spatial_bounds: SpatialPartition2D::new_unchecked((0., 2.).into(), (4., 0.).into()),
+            time_interval: TimeInterval::new_unchecked(0, 10),
+            spatial_resolution: SpatialResolution::new_unchecked(2., 2.),
+            attributes: BandSelection::first(),
+        };
+
+        let res = qp
+            .raster_query(query_rect, &query_ctx)
+            .await?
+            .map(Result::unwrap)
+            .collect::<Vec<RasterTile2D<u8>>>()
+            .await;
+
+        assert_eq!(res.len(), 2);
+
+        // the upper row of the output tile lies outside of the source raster
+        let expected = [vec![0, 0, 4, 10], vec![0, 0, 16, 22]];
+        for (tile, expected) in res.into_iter().zip(expected) {
+            let tile = tile.into_materialized_tile();
+            assert_eq!(tile.grid_array.inner_grid.data, expected);
+            assert_eq!(
+                tile.grid_array.validity_mask.data,
+                vec![false, false, true, true]
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn it_deserializes_params_with_default_resampling() {
+        let params: ReprojectionParams = serde_json::from_value(serde_json::json!({
+            "targetSpatialReference": "EPSG:4326",
+        }))
+        .unwrap();
+
+        assert_eq!(params.interpolation, InterpolationMethod::NearestNeighbor);
+        assert_eq!(params.downsampling, None);
+    }
+
     #[tokio::test]
     async fn raster_ndvi_3857() -> Result<()> {
         let mut exe_ctx = MockExecutionContext::test_default();
@@ -1120,6 +1551,10 @@ mod tests {
         let initialized_operator = RasterOperator::boxed(Reprojection {
             params: ReprojectionParams {
                 target_spatial_reference: projection,
+                interpolation: InterpolationMethod::NearestNeighbor,
+                downsampling: None,
+                densification: None,
+                split_at_antimeridian: false,
             },
             sources: SingleRasterOrVectorSource {
                 source: gdal_op.into(),
@@ -1284,6 +1719,10 @@ mod tests {
         let initialized_operator = RasterOperator::boxed(Reprojection {
             params: ReprojectionParams {
                 target_spatial_reference: SpatialReference::epsg_4326(),
+                interpolation: InterpolationMethod::NearestNeighbor,
+                downsampling: None,
+                densification: None,
+                split_at_antimeridian: false,
             },
             sources: SingleRasterOrVectorSource {
                 source: gdal_op.into(),
@@ -1419,6 +1858,10 @@ mod tests {
         let initialized_operator = RasterOperator::boxed(Reprojection {
             params: ReprojectionParams {
                 target_spatial_reference: SpatialReference::epsg_4326(),
+                interpolation: InterpolationMethod::NearestNeighbor,
+                downsampling: None,
+                densification: None,
+                split_at_antimeridian: false,
             },
             sources: SingleRasterOrVectorSource {
                 source: gdal_op.into(),
@@ -1489,6 +1932,10 @@ mod tests {
                     SpatialReferenceAuthority::Epsg,
                     32636, // utm36n
                 ),
+                interpolation: InterpolationMethod::NearestNeighbor,
+                downsampling: None,
+                densification: None,
+                split_at_antimeridian: false,
             },
             sources: SingleRasterOrVectorSource {
                 source: point_source.into(),
@@ -1569,6 +2016,10 @@ mod tests {
                     SpatialReferenceAuthority::Epsg,
                     4326, // utm36n
                 ),
+                interpolation: InterpolationMethod::NearestNeighbor,
+                downsampling: None,
+                densification: None,
+                split_at_antimeridian: false,
             },
             sources: SingleRasterOrVectorSource {
                 source: point_source.into(),
@@ -1652,6 +2103,10 @@ mod tests {
                     SpatialReferenceAuthority::Epsg,
                     4326, // utm36n
                 ),
+                interpolation: InterpolationMethod::NearestNeighbor,
+                downsampling: None,
+                densification: None,
+                split_at_antimeridian: false,
             },
             sources: SingleRasterOrVectorSource {
                 source: point_source.into(),
@@ -0,0 +1,711 @@
+mod trend;
+
+use self::trend::{LinearFit, detect_break, mann_kendall_p_value, sen_slope};
+use crate::engine::{
+    CanonicOperatorName, ExecutionContext, InitializedRasterOperator, InitializedSources, Operator,
+    OperatorName, QueryContext, QueryProcessor, RasterBandDescriptor, RasterBandDescriptors,
+    RasterOperator, RasterQueryProcessor, RasterResultDescriptor, SingleRasterSource,
+    TypedRasterQueryProcessor, WorkflowOperatorPath,
+};
+use crate::util::Result;
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use geoengine_datatypes::primitives::{
+    BandSelection, CacheHint, RasterQueryRectangle, SpatialPartition2D, SpatialPartitioned,
+    TimeGranularity,
+};
+use geoengine_datatypes::raster::{
+    FromIndexFnParallel, Grid2D, GridIndexAccess, GridOrEmpty, GridOrEmpty2D, Pixel,
+    RasterDataType, RasterTile2D, TileInformation, TilingSpecification,
+};
+use serde::{Deserialize, Serialize};
+use snafu::{Snafu, ensure};
+
+/// The temporal trend operator fits a per-pixel trend over all time steps of the source
+/// that intersect the query's time interval.
+///
+/// The output is a multi-band raster with the band order
+/// `slope`, `intercept`, `rSquared`, `senSlope`, `pValue` and, if break detection is enabled,
+/// `breakTime` and `breakMagnitude`.
+/// Each output tile is valid for the whole query time interval.
+pub type TemporalTrend = Operator<TemporalTrendParams, SingleRasterSource>;
+
+impl OperatorName for TemporalTrend {
+    const TYPE_NAME: &'static str = "TemporalTrend";
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct TemporalTrendParams {
+    /// The time unit of the slopes, e.g., the change per year.
+    /// The intercept refers to the start of the query time interval.
+    pub time_unit: TimeGranularity,
+    /// If specified, the most prominent break of each pixel's time series is detected
+    /// and emitted as two additional bands.
+    #[serde(default)]
+    pub break_detection: Option<BreakDetectionParams>,
+    /// The maximum number of time steps of a query.
+    /// The statistics of each pixel take quadratic time in the number of time steps,
+    /// so queries with more time steps are rejected.
+    /// The default is 500.
+    #[serde(default = "default_max_time_steps")]
+    pub max_time_steps: usize,
+}
+
+fn default_max_time_steps() -> usize {
+    500
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakDetectionParams {
+    /// The minimum number of observations before and after a break.
+    /// Must be at least 2.
+    pub min_segment_size: usize,
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)), context(suffix(false)), module(error))]
+pub enum TemporalTrendError {
+    #[snafu(display("The source raster must have a single band, but it has {found} bands"))]
+    SourceMustHaveSingleBand { found: u32 },
+
+    #[snafu(display("The minimum segment size must be at least 2, but it is {found}"))]
+    MinSegmentSizeTooSmall { found: usize },
+
+    #[snafu(display(
+        "The query contains more than {max_time_steps} time steps. Shorten the query's time interval or increase `maxTimeSteps`."
+    ))]
+    TooManyTimeSteps { max_time_steps: usize },
+}
+
+/// The output bands of the `TemporalTrend` operator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TrendBand {
+    Slope,
+    Intercept,
+    RSquared,
+    SenSlope,
+    PValue,
+    BreakTime,
+    BreakMagnitude,
+}
+
+impl TrendBand {
+    const ALL: [Self; 7] = [
+        Self::Slope,
+        Self::Intercept,
+        Self::RSquared,
+        Self::SenSlope,
+        Self::PValue,
+        Self::BreakTime,
+        Self::BreakMagnitude,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Slope => "slope",
+            Self::Intercept => "intercept",
+            Self::RSquared => "rSquared",
+            Self::SenSlope => "senSlope",
+            Self::PValue => "pValue",
+            Self::BreakTime => "breakTime",
+            Self::BreakMagnitude => "breakMagnitude",
+        }
+    }
+
+    fn bands(break_detection: bool) -> &'static [Self] {
+        if break_detection {
+            &Self::ALL
+        } else {
+            &Self::ALL[..5]
+        }
+    }
+
+    fn value(self, statistics: &TrendStatistics) -> Option<f64> {
+        let value = match self {
+            Self::Slope => statistics.fit.slope,
+            Self::Intercept => statistics.fit.intercept,
+            Self::RSquared => statistics.fit.r_squared,
+            Self::SenSlope => statistics.sen_slope?,
+            Self::PValue => statistics.p_value?,
+            Self::BreakTime => statistics.break_time?,
+            Self::BreakMagnitude => statistics.break_magnitude?,
+        };
+
+        value.is_finite().then_some(value)
+    }
+}
+
+/// The trend statistics of a single pixel's time series
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TrendStatistics {
+    fit: LinearFit,
+    sen_slope: Option<f64>,
+    p_value: Option<f64>,
+    /// The start of the first time step after the break in unix milliseconds
+    break_time: Option<f64>,
+    break_magnitude: Option<f64>,
+}
+
+impl TrendStatistics {
+    /// Computes the statistics for a series of `(time in units, value)` observations that is sorted by time.
+    /// `to_millis` converts the time of an observation back into unix milliseconds.
+    fn compute(
+        series: &[(f64, f64)],
+        break_detection: Option<BreakDetectionParams>,
+        to_millis: impl Fn(f64) -> f64,
+    ) -> Option<Self> {
+        let fit = LinearFit::fit(series)?;
+
+        let trend_break =
+            break_detection.and_then(|params| detect_break(series, params.min_segment_size));
+
+        Some(Self {
+            fit,
+            sen_slope: sen_slope(series),
+            p_value: mann_kendall_p_value(series),
+            break_time: trend_break.map(|b| to_millis(series[b.index].0)),
+            break_magnitude: trend_break.map(|b| b.magnitude),
+        })
+    }
+}
+
+/// The (average) length of a time unit in milliseconds
+fn time_unit_millis(time_unit: TimeGranularity) -> f64 {
+    const DAY: f64 = 86_400_000.;
+    const YEAR: f64 = 365.2425 * DAY;
+
+    match time_unit {
+        TimeGranularity::Millis => 1.,
+        TimeGranularity::Seconds => 1_000.,
+        TimeGranularity::Minutes => 60_000.,
+        TimeGranularity::Hours => 3_600_000.,
+        TimeGranularity::Days => DAY,
+        TimeGranularity::Months => YEAR / 12.,
+        TimeGranularity::Years => YEAR,
+    }
+}
+
+#[typetag::serde]
+#[async_trait]
+impl RasterOperator for TemporalTrend {
+    async fn _initialize(
+        self: Box<Self>,
+        path: WorkflowOperatorPath,
+        context: &dyn ExecutionContext,
+    ) -> Result<Box<dyn InitializedRasterOperator>> {
+        let name = CanonicOperatorName::from(&self);
+
+        if let Some(break_detection) = self.params.break_detection {
+            ensure!(
+                break_detection.min_segment_size >= 2,
+                error::MinSegmentSizeTooSmall {
+                    found: break_detection.min_segment_size
+                }
+            );
+        }
+
+        let initialized_sources = self
+            .sources
+            .initialize_sources(path.clone(), context)
+            .await?;
+        let source = initialized_sources.raster;
+
+        let in_desc = source.result_descriptor();
+
+        ensure!(
+            in_desc.bands.count() == 1,
+            error::SourceMustHaveSingleBand {
+                found: in_desc.bands.count()
+            }
+        );
+
+        let result_descriptor = RasterResultDescriptor {
+            data_type: RasterDataType::F64,
+            spatial_reference: in_desc.spatial_reference,
+            time: in_desc.time,
+            bbox: in_desc.bbox,
+            resolution: in_desc.resolution,
+            bands: RasterBandDescriptors::new(
+                TrendBand::bands(self.params.break_detection.is_some())
+                    .iter()
+                    .map(|band| RasterBandDescriptor::new_unitless(band.name().to_string()))
+                    .collect(),
+            )?,
+        };
+
+        let initialized_operator = InitializedTemporalTrend {
+            name,
+            path,
+            result_descriptor,
+            source,
+            params: self.params,
+            tiling_specification: context.tiling_specification(),
+        };
+
+        Ok(initialized_operator.boxed())
+    }
+
+    span_fn!(TemporalTrend);
+}
+
+pub struct InitializedTemporalTrend {
+    name: CanonicOperatorName,
+    path: WorkflowOperatorPath,
+    result_descriptor: RasterResultDescriptor,
+    source: Box<dyn InitializedRasterOperator>,
+    params: TemporalTrendParams,
+    tiling_specification: TilingSpecification,
+}
+
+impl InitializedRasterOperator for InitializedTemporalTrend {
+    fn result_descriptor(&self) -> &RasterResultDescriptor {
+        &self.result_descriptor
+    }
+
+    fn query_processor(&self) -> Result<TypedRasterQueryProcessor> {
+        let source_processor = self.source.query_processor()?;
+
+        Ok(call_on_generic_raster_processor!(
+            source_processor, p => TypedRasterQueryProcessor::F64(
+                TemporalTrendProcessor::new(
+                    p,
+                    self.result_descriptor.clone(),
+                    self.params,
+                    self.tiling_specification,
+                ).boxed()
+            )
+        ))
+    }
+
+    fn canonic_name(&self) -> CanonicOperatorName {
+        self.name.clone()
+    }
+
+    fn name(&self) -> &'static str {
+        TemporalTrend::TYPE_NAME
+    }
+
+    fn path(&self) -> WorkflowOperatorPath {
+        self.path.clone()
+    }
+}
+
+pub struct TemporalTrendProcessor<Q, P>
+where
+    Q: RasterQueryProcessor<RasterType = P>,
+{
+    source: Q,
+    result_descriptor: RasterResultDescriptor,
+    params: TemporalTrendParams,
+    tiling_specification: TilingSpecification,
+}
+
+impl<Q, P> TemporalTrendProcessor<Q, P>
+where
+    Q: RasterQueryProcessor<RasterType = P>,
+    P: Pixel,
+{
+    pub fn new(
+        source: Q,
+        result_descriptor: RasterResultDescriptor,
+        params: TemporalTrendParams,
+        tiling_specification: TilingSpecification,
+    ) -> Self {
+        Self {
+            source,
+            result_descriptor,
+            params,
+            tiling_specification,
+        }
+    }
+
+    /// Query the whole time series of a tile and compute the output tiles of all selected bands.
+    async fn trend_tiles(
+        &self,
+        tile_info: TileInformation,
+        query: RasterQueryRectangle,
+        ctx: &dyn QueryContext,
+    ) -> Result<Vec<RasterTile2D<f64>>> {
+        let source_query = RasterQueryRectangle {
+            spatial_bounds: tile_info.spatial_partition(),
+            time_interval: query.time_interval,
+            spatial_resolution: query.spatial_resolution,
+            attributes: BandSelection::first(),
+        };
+
+        let mut source_stream = self.source.raster_query(source_query, ctx).await?;
+
+        // the source has a single band, so there is one tile per time step
+        let mut source_tiles: Vec<RasterTile2D<P>> = Vec::new();
+        while let Some(tile) = source_stream.next().await {
+            ensure!(
+                source_tiles.len() < self.params.max_time_steps,
+                error::TooManyTimeSteps {
+                    max_time_steps: self.params.max_time_steps
+                }
+            );
+
+            source_tiles.push(tile?);
+        }
+
+        let mut cache_hint = CacheHint::max_duration();
+        for tile in &source_tiles {
+            cache_hint.merge_with(&tile.cache_hint);
+        }
+
+        let reference_millis = query.time_interval.start().inner() as f64;
+        let unit_millis = time_unit_millis(self.params.time_unit);
+        let break_detection = self.params.break_detection;
+
+        crate::util::spawn_blocking_with_thread_pool(ctx.thread_pool().clone(), move || {
+            // the source tiles arrive ordered by time
+            let observations: Vec<(f64, GridOrEmpty2D<P>)> = source_tiles
+                .into_iter()
+                .filter(|tile| !tile.is_empty())
+                .map(|tile| {
+                    let millis = tile.time.start().inner() as f64;
+                    ((millis - reference_millis) / unit_millis, tile.grid_array)
+                })
+                .collect();
+
+            let to_millis = |x: f64| reference_millis + x * unit_millis;
+
+            let statistics = Grid2D::<Option<TrendStatistics>>::from_index_fn_parallel(
+                &tile_info.tile_size_in_pixels,
+                |lin_idx: usize| {
+                    let series = observations
+                        .iter()
+                        .filter_map(|(x, grid)| {
+                            let value: Option<P> = grid.get_at_grid_index_unchecked(lin_idx);
+                            value.map(|v| (*x, v.as_()))
+                        })
+                        .collect::<Vec<(f64, f64)>>();
+
+                    TrendStatistics::compute(&series, break_detection, to_millis)
+                },
+            );
+
+            query
+                .attributes
+                .as_slice()
+                .iter()
+                .enumerate()
+                .map(|(output_band, band)| {
+                    let trend_band = TrendBand::ALL[*band as usize];
+
+                    let grid = GridOrEmpty::from_index_fn_parallel(
+                        &tile_info.tile_size_in_pixels,
+                        |lin_idx: usize| {
+                            statistics.data[lin_idx]
+                                .as_ref()
+                                .and_then(|s| trend_band.value(s))
+                        },
+                    );
+
+                    RasterTile2D::new_with_tile_info(
+                        query.time_interval,
+                        tile_info,
+                        output_band as u32,
+                        grid,
+                        cache_hint,
+                    )
+                })
+                .collect()
+        })
+        .await
+        .map_err(Into::into)
+    }
+}
+
+#[async_trait]
+impl<Q, P> QueryProcessor for TemporalTrendProcessor<Q, P>
+where
+    Q: QueryProcessor<
+            Output = RasterTile2D<P>,
+            SpatialBounds = SpatialPartition2D,
+            Selection = BandSelection,
+            ResultDescription = RasterResultDescriptor,
+        >,
+    P: Pixel,
+{
+    type Output = RasterTile2D<f64>;
+    type SpatialBounds = SpatialPartition2D;
+    type Selection = BandSelection;
+    type ResultDescription = RasterResultDescriptor;
+
+    async fn _query<'a>(
+        &'a self,
+        query: RasterQueryRectangle,
+        ctx: &'a dyn QueryContext,
+    ) -> Result<BoxStream<'a, Result<Self::Output>>> {
+        let tiling_strategy = self
+            .tiling_specification
+            .strategy(query.spatial_resolution.x, -query.spatial_resolution.y);
+
+        let tiles = tiling_strategy
+            .tile_information_iterator(query.spatial_partition())
+            .collect::<Vec<_>>();
+
+        let stream = futures::stream::iter(tiles)
+            .then(move |tile_info| self.trend_tiles(tile_info, query.clone(), ctx))
+            .map_ok(|tiles| futures::stream::iter(tiles.into_iter().map(Result::Ok)))
+            .try_flatten();
+
+        Ok(stream.boxed())
+    }
+
+    fn result_descriptor(&self) -> &Self::ResultDescription {
+        &self.result_descriptor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        engine::{MockExecutionContext, MockQueryContext},
+        mock::{MockRasterSource, MockRasterSourceParams},
+    };
+    use float_cmp::approx_eq;
+    use geoengine_datatypes::{
+        primitives::{SpatialResolution, TimeInterval},
+        raster::{Grid2D, MaskedGrid2D},
+        spatial_reference::SpatialReference,
+        util::test::TestDefault,
+    };
+
+    fn make_source() -> Box<dyn RasterOperator> {
+        // four time steps of 10 ms each; pixel values:
+        // 1st pixel: 1, 3, 5, 7     (linear increase)
+        // 2nd pixel: 8, 6, 4, 2     (linear decrease)
+        // 3rd pixel: 5, 5, 5, 5     (constant)
+        // 4th pixel: 1, -, -, 2     (too few observations)
+        let data = (0..4)
+            .map(|i| {
+                let grid = MaskedGrid2D::new(
+                    Grid2D::new(
+                        [2, 2].into(),
+                        vec![1 + 2 * i, 8 - 2 * i, 5, if i == 3 { 2 } else { 1 }],
+                    )
+                    .unwrap(),
+                    Grid2D::new([2, 2].into(), vec![true, true, true, i == 0 || i == 3]).unwrap(),
+                )
+                .unwrap();
+
+                RasterTile2D::new_with_tile_info(
+                    TimeInterval::new_unchecked(i64::from(i) * 10, i64::from(i + 1) * 10),
+                    TileInformation {
+                        global_tile_position: [-1, 0].into(),
+                        tile_size_in_pixels: [2, 2].into(),
+                        global_geo_transform: TestDefault::test_default(),
+                    },
+                    0,
+                    GridOrEmpty::from(grid),
+                    CacheHint::default(),
+                )
+            })
+            .collect::<Vec<RasterTile2D<i32>>>();
+
+        MockRasterSource {
+            params: MockRasterSourceParams {
+                data,
+                result_descriptor: RasterResultDescriptor {
+                    data_type: RasterDataType::I32,
+                    spatial_reference: SpatialReference::epsg_4326().into(),
+                    time: None,
+                    bbox: None,
+                    resolution: None,
+                    bands: RasterBandDescriptors::new_single_band(),
+                },
+            },
+        }
+        .boxed()
+    }
+
+    #[test]
+    fn test_serialization() {
+        let params = serde_json::json!({
+            "timeUnit": "years",
+            "breakDetection": {
+                "minSegmentSize": 4
+            }
+        });
+
+        let params: TemporalTrendParams = serde_json::from_value(params).unwrap();
+
+        assert_eq!(params.time_unit, TimeGranularity::Years);
+        assert_eq!(params.break_detection.unwrap().min_segment_size, 4);
+
+        let params: TemporalTrendParams =
+            serde_json::from_value(serde_json::json!({ "timeUnit": "days" })).unwrap();
+
+        assert!(params.break_detection.is_none());
+        assert_eq!(params.max_time_steps, 500);
+    }
+
+    #[tokio::test]
+    async fn test_trend_bands() {
+        let exe_ctx = MockExecutionContext::new_with_tiling_spec(TilingSpecification::new(
+            (0., 0.).into(),
+            [2, 2].into(),
+        ));
+
+        let operator = TemporalTrend {
+            params: TemporalTrendParams {
+                time_unit: TimeGranularity::Millis,
+                break_detection: None,
+                max_time_steps: 500,
+            },
+            sources: SingleRasterSource {
+                raster: make_source(),
+            },
+        }
+        .boxed()
+        .initialize(WorkflowOperatorPath::initialize_root(), &exe_ctx)
+        .await
+        .unwrap();
+
+        assert_eq!(
+            operator
+                .result_descriptor()
+                .bands
+                .iter()
+                .map(|b| b.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["slope", "intercept", "rSquared", "senSlope", "pValue"]
+        );
+
+        let processor = operator.query_processor().unwrap().get_f64().unwrap();
+
+        let query_rect = RasterQueryRectangle {
+            spatial_bounds: SpatialPartition2D::new_unchecked((0., 2.).into(), (2., 0.).into()),
+            time_interval: TimeInterval::new_unchecked(0, 40),
+            spatial_resolution: SpatialResolution::one(),
+            attributes: BandSelection::first_n(5),
+        };
+        let query_ctx = MockQueryContext::test_default();
+
+        let result = processor
+            .query(query_rect, &query_ctx)
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(result.len(), 5);
+
+        for (band, tile) in result.iter().enumerate() {
+            assert_eq!(tile.band, band as u32);
+            assert_eq!(tile.time, TimeInterval::new_unchecked(0, 40));
+        }
+
+        let values = |band: usize| {
+            result[band]
+                .clone()
+                .into_materialized_tile()
+                .grid_array
+                .masked_element_deref_iterator()
+                .collect::<Vec<_>>()
+        };
+
+        let slopes = values(0);
+        assert!(approx_eq!(f64, slopes[0].unwrap(), 0.2));
+        assert!(approx_eq!(f64, slopes[1].unwrap(), -0.2));
+        assert!(approx_eq!(f64, slopes[2].unwrap(), 0.));
+        assert!(approx_eq!(f64, slopes[3].unwrap(), 1. / 30.));
+
+        let intercepts = values(1);
+        assert!(approx_eq!(f64, intercepts[0].unwrap(), 1.));
+        assert!(approx_eq!(f64, intercepts[1].unwrap(), 8.));
+
+        let r_squared = values(2);
+        assert!(approx_eq!(f64, r_squared[0].unwrap(), 1.));
+        // undefined for constant series
+        assert!(r_squared[2].is_none());
+
+        let sen_slopes = values(3);
+        assert!(approx_eq!(f64, sen_slopes[0].unwrap(), 0.2));
+        assert!(approx_eq!(f64, sen_slopes[1].unwrap(), -0.2));
+
+        let p_values = values(4);
+        assert!(p_values[0].unwrap() < p_values[2].unwrap());
+        // Mann-Kendall needs at least three observations
+        assert!(p_values[3].is_none());
+    }
+
+    #[tokio::test]
+    async fn test_invalid_min_segment_size() {
+        let exe_ctx = MockExecutionContext::test_default();
+
+        let result = TemporalTrend {
+            params: TemporalTrendParams {
+                time_unit: TimeGranularity::Days,
+                break_detection: Some(BreakDetectionParams {
+                    min_segment_size: 1,
+                }),
+                max_time_steps: 500,
+            },
+            sources: SingleRasterSource {
+                raster: make_source(),
+            },
+        }
+        .boxed()
+        .initialize(WorkflowOperatorPath::initialize_root(), &exe_ctx)
+        .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_too_many_time_steps() {
+        let exe_ctx = MockExecutionContext::new_with_tiling_spec(TilingSpecification::new(
+            (0., 0.).into(),
+            [2, 2].into(),
+        ));
+
+        let operator = TemporalTrend {
+            params: TemporalTrendParams {
+                time_unit: TimeGranularity::Millis,
+                break_detection: None,
+                max_time_steps: 3,
+            },
+            sources: SingleRasterSource {
+                raster: make_source(),
+            },
+        }
+        .boxed()
+        .initialize(WorkflowOperatorPath::initialize_root(), &exe_ctx)
+        .await
+        .unwrap();
+
+        let processor = operator.query_processor().unwrap().get_f64().unwrap();
+
+        let query_rect = RasterQueryRectangle {
+            spatial_bounds: SpatialPartition2D::new_unchecked((0., 2.).into(), (2., 0.).into()),
+            time_interval: TimeInterval::new_unchecked(0, 40),
+            spatial_resolution: SpatialResolution::one(),
+            attributes: BandSelection::first(),
+        };
+        let query_ctx = MockQueryContext::test_default();
+
+        let result = processor
+            .query(query_rect, &query_ctx)
+            .await
+            .unwrap()
+            .collect::<Vec<_>>()
+            .await;
+
+        assert!(matches!(
+            result.as_slice(),
+            [Err(crate::error::Error::TemporalTrend {
+                source: TemporalTrendError::TooManyTimeSteps { max_time_steps: 3 }
+            })]
+        ));
+    }
+}
@@ -0,0 +1,305 @@
+/// A least-squares line fitted to a series of `(x, y)` observations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearFit {
+    pub slope: f64,
+    pub intercept: f64,
+    pub r_squared: f64,
+    /// The residual sum of squares
+    pub rss: f64,
+}
+
+impl LinearFit {
+    /// Fit a line to the observations.
+    /// Returns `None` if there are less than two observations or all `x` values are equal.
+    pub fn fit(series: &[(f64, f64)]) -> Option<Self> {
+        if series.len() < 2 {
+            return None;
+        }
+
+        let n = series.len() as f64;
+        let mean_x = series.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = series.iter().map(|(_, y)| y).sum::<f64>() / n;
+
+        let (mut sxx, mut sxy, mut syy) = (0., 0., 0.);
+        for (x, y) in series {
+            let dx = x - mean_x;
+            let dy = y - mean_y;
+            sxx += dx * dx;
+            sxy += dx * dy;
+            syy += dy * dy;
+        }
+
+        if sxx == 0. {
+            return None;
+        }
+
+        let slope = sxy / sxx;
+        let rss = (syy - slope * sxy).max(0.);
+
+        Some(Self {
+            slope,
+            intercept: mean_y - slope * mean_x,
+            // undefined (NaN) for constant series
+            r_squared: 1. - rss / syy,
+            rss,
+        })
+    }
+
+    pub fn predict(&self, x: f64) -> f64 {
+        self.intercept + self.slope * x
+    }
+}
+
+/// The two-sided p-value of the Mann-Kendall trend test, including the correction for ties.
+/// Expects the series to be sorted by `x`.
+pub fn mann_kendall_p_value(series: &[(f64, f64)]) -> Option<f64> {
+    let n = series.len();
+    if n < 3 {
+        return None;
+    }
+
+    let mut s = 0_i64;
+    for (i, (_, y_i)) in series.iter().enumerate() {
+        for (_, y_j) in &series[i + 1..] {
+            s += match y_j.partial_cmp(y_i)? {
+                std::cmp::Ordering::Less => -1,
+                std::cmp::Ordering::Equal => 0,
+                std::cmp::Ordering::Greater => 1,
+            };
+        }
+    }
+
+    let mut values = series.iter().map(|(_, y)| *y).collect::<Vec<_>>();
+    values.sort_unstable_by(f64::total_cmp);
+
+    let tie_correction = values
+        .chunk_by(|a, b| a.total_cmp(b).is_eq())
+        .map(|group| {
+            let t = group.len() as f64;
+            t * (t - 1.) * (2. * t + 5.)
+        })
+        .sum::<f64>();
+
+    let n = n as f64;
+    let variance = (n * (n - 1.) * (2. * n + 5.) - tie_correction) / 18.;
+
+    if variance <= 0. {
+        // all values are tied, so there is no trend at all
+        return Some(1.);
+    }
+
+    let z = match s {
+        s if s > 0 => (s - 1) as f64 / variance.sqrt(),
+        s if s < 0 => (s + 1) as f64 / variance.sqrt(),
+        _ => 0.,
+    };
+
+    Some(erfc(z.abs() / std::f64::consts::SQRT_2))
+}
+
+/// The Theil-Sen estimator, i.e., the median of the slopes between all pairs of observations.
+pub fn sen_slope(series: &[(f64, f64)]) -> Option<f64> {
+    let mut slopes = Vec::with_capacity(series.len() * series.len().saturating_sub(1) / 2);
+
+    for (i, (x_i, y_i)) in series.iter().enumerate() {
+        for (x_j, y_j) in &series[i + 1..] {
+            let dx = x_j - x_i;
+            if dx != 0. {
+                slopes.push((y_j - y_i) / dx);
+            }
+        }
+    }
+
+    if slopes.is_empty() {
+        return None;
+    }
+
+    slopes.sort_unstable_by(f64::total_cmp);
+
+    let mid = slopes.len() / 2;
+    if slopes.len() % 2 == 0 {
+        Some(f64::midpoint(slopes[mid - 1], slopes[mid]))
+    } else {
+        Some(slopes[mid])
+    }
+}
+
+/// A structural break in a series, i.e., a change of the linear trend.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrendBreak {
+    /// The index of the first observation after the break
+    pub index: usize,
+    /// The difference of the two segment fits at the break
+    pub magnitude: f64,
+}
+
+/// Detect the most prominent break in a series in the spirit of BFAST Lite.
+///
+/// The series is split at the position that minimizes the residual sum of squares of two independent linear fits.
+/// The break is only reported if the segmented model has a lower BIC than a single linear fit.
+/// Expects the series to be sorted by `x`.
+pub fn detect_break(series: &[(f64, f64)], min_segment_size: usize) -> Option<TrendBreak> {
+    let n = series.len();
+    if min_segment_size < 2 || n < 2 * min_segment_size {
+        return None;
+    }
+
+    let full_fit = LinearFit::fit(series)?;
+
+    let mut best: Option<(usize, f64, LinearFit, LinearFit)> = None;
+    for index in min_segment_size..=(n - min_segment_size) {
+        let (Some(left), Some(right)) = (
+            LinearFit::fit(&series[..index]),
+            LinearFit::fit(&series[index..]),
+        ) else {
+            continue;
+        };
+
+        let rss = left.rss + right.rss;
+        if best
+            .as_ref()
+            .is_none_or(|(_, best_rss, _, _)| rss < *best_rss)
+        {
+            best = Some((index, rss, left, right));
+        }
+    }
+
+    let (index, rss, left, right) = best?;
+
+    // avoid comparing numerical noise of (almost) perfect fits
+    let rss_floor = f64::EPSILON * series.iter().map(|(_, y)| y * y).sum::<f64>().max(1.);
+
+    let n = n as f64;
+    // two parameters per segment plus the break position
+    let bic =
+        |rss: f64, num_parameters: f64| n * (rss.max(rss_floor) / n).ln() + num_parameters * n.ln();
+
+    if bic(rss, 5.) >= bic(full_fit.rss, 2.) {
+        return None;
+    }
+
+    let x = series[index].0;
+
+    Some(TrendBreak {
+        index,
+        magnitude: right.predict(x) - left.predict(x),
+    })
+}
+
+/// The complementary error function with a fractional error below 1.2e-7.
+///
+/// cf. Press et al., Numerical Recipes, 2nd edition, section 6.2
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1. / (1. + 0.5 * z);
+
+    let polynomial = -1.265_512_23
+        + t * (1.000_023_68
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98
+                                + t * (1.488_515_87 + t * (-0.822_152_23 + t * 0.170_872_77))))))));
+
+    let result = t * (-z * z + polynomial).exp();
+
+    if x >= 0. { result } else { 2. - result }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_cmp::approx_eq;
+
+    #[test]
+    fn it_fits_a_line() {
+        let series = [(0., 1.), (1., 3.), (2., 5.), (3., 7.)];
+
+        let fit = LinearFit::fit(&series).unwrap();
+
+        assert!(approx_eq!(f64, fit.slope, 2.));
+        assert!(approx_eq!(f64, fit.intercept, 1.));
+        assert!(approx_eq!(f64, fit.r_squared, 1.));
+        assert!(approx_eq!(f64, fit.rss, 0.));
+        assert!(approx_eq!(f64, fit.predict(4.), 9.));
+
+        assert!(LinearFit::fit(&[(0., 1.)]).is_none());
+        assert!(LinearFit::fit(&[(1., 1.), (1., 2.)]).is_none());
+    }
+
+    #[test]
+    fn it_computes_the_sen_slope() {
+        // an outlier does not influence the median slope
+        let series = [(0., 0.), (1., 1.), (2., 2.), (3., 30.), (4., 4.)];
+
+        assert!(approx_eq!(f64, sen_slope(&series).unwrap(), 1.));
+
+        let series = [(0., 0.), (1., 1.), (2., 4.)];
+
+        // slopes: 1, 2, 3
+        assert!(approx_eq!(f64, sen_slope(&series).unwrap(), 2.));
+
+        assert!(sen_slope(&[(0., 1.)]).is_none());
+    }
+
+    #[test]
+    fn it_computes_the_mann_kendall_p_value() {
+        let increasing = (0..10)
+            .map(|i| (f64::from(i), f64::from(i)))
+            .collect::<Vec<_>>();
+
+        // S = 45, Var(S) = 125, Z = 3.935
+        let p_value = mann_kendall_p_value(&increasing).unwrap();
+        assert!(approx_eq!(f64, p_value, 8.3e-5, epsilon = 1e-5));
+
+        let constant = (0..10).map(|i| (f64::from(i), 1.)).collect::<Vec<_>>();
+        assert!(approx_eq!(
+            f64,
+            mann_kendall_p_value(&constant).unwrap(),
+            1.
+        ));
+
+        let alternating = (0..10)
+            .map(|i| (f64::from(i), f64::from(i % 2)))
+            .collect::<Vec<_>>();
+        assert!(mann_kendall_p_value(&alternating).unwrap() > 0.5);
+
+        assert!(mann_kendall_p_value(&[(0., 1.), (1., 2.)]).is_none());
+    }
+
+    #[test]
+    fn it_detects_a_break() {
+        let series = (0..20)
+            .map(|i| {
+                let x = f64::from(i);
+                let y = if i < 12 { x } else { x + 10. };
+                (x, y)
+            })
+            .collect::<Vec<_>>();
+
+        let trend_break = detect_break(&series, 3).unwrap();
+
+        assert_eq!(trend_break.index, 12);
+        assert!(approx_eq!(f64, trend_break.magnitude, 10., epsilon = 1e-9));
+    }
+
+    #[test]
+    fn it_does_not_detect_a_break_in_a_linear_series() {
+        let series = (0..20)
+            .map(|i| (f64::from(i), 2. * f64::from(i) + 1.))
+            .collect::<Vec<_>>();
+
+        assert!(detect_break(&series, 3).is_none());
+
+        // too short
+        assert!(detect_break(&series[..5], 3).is_none());
+    }
+
+    #[test]
+    fn it_computes_erfc() {
+        assert!(approx_eq!(f64, erfc(0.), 1., epsilon = 1e-7));
+        assert!(approx_eq!(f64, erfc(1.), 0.157_299_207, epsilon = 1e-7));
+        assert!(approx_eq!(f64, erfc(-1.), 1.842_700_793, epsilon = 1e-7));
+    }
+}
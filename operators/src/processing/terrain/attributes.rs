@@ -0,0 +1,349 @@
+use serde::{Deserialize, Serialize};
+
+/// The terrain attribute that is derived from the elevation model
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TerrainAttribute {
+    /// The slope in degrees
+    Slope,
+    /// The compass direction of the steepest descent in degrees (clockwise from north).
+    /// Flat areas have no aspect and are NODATA.
+    Aspect,
+    /// A hillshade with values between 0 (shadow) and 255 (fully lit)
+    #[serde(rename_all = "camelCase")]
+    Hillshade {
+        /// The compass direction of the light source in degrees
+        azimuth: f64,
+        /// The altitude of the light source above the horizon in degrees
+        altitude: f64,
+    },
+    /// A hillshade that combines light sources from the north-west, west, south-west and north,
+    /// weighted by the aspect of each pixel.
+    #[serde(rename_all = "camelCase")]
+    MultidirectionalHillshade {
+        /// The altitude of the light sources above the horizon in degrees
+        altitude: f64,
+    },
+    /// The curvature in the direction of the steepest slope.
+    /// Negative values indicate convex, positive values concave surfaces.
+    ProfileCurvature,
+    /// The curvature perpendicular to the direction of the steepest slope.
+    /// Positive values indicate convex, negative values concave surfaces.
+    PlanCurvature,
+    /// The topographic position index, i.e., the difference of the elevation to the mean of its neighbors
+    Tpi,
+    /// The terrain ruggedness index after Riley et al. (1999)
+    Tri,
+}
+
+impl TerrainAttribute {
+    pub fn band_name(&self) -> &'static str {
+        match self {
+            Self::Slope => "slope",
+            Self::Aspect => "aspect",
+            Self::Hillshade { .. } => "hillshade",
+            Self::MultidirectionalHillshade { .. } => "multidirectionalHillshade",
+            Self::ProfileCurvature => "profileCurvature",
+            Self::PlanCurvature => "planCurvature",
+            Self::Tpi => "tpi",
+            Self::Tri => "tri",
+        }
+    }
+
+    /// The unit of the attribute if the elevation is given in meters
+    pub fn unit(&self) -> Option<&'static str> {
+        match self {
+            Self::Slope | Self::Aspect => Some("degrees"),
+            Self::ProfileCurvature | Self::PlanCurvature => Some("1/m"),
+            Self::Tpi | Self::Tri => Some("m"),
+            Self::Hillshade { .. } | Self::MultidirectionalHillshade { .. } => None,
+        }
+    }
+
+    pub fn apply(&self, window: &ElevationWindow) -> Option<f64> {
+        let value = match *self {
+            Self::Slope => window.slope(),
+            Self::Aspect => window.aspect()?,
+            Self::Hillshade { azimuth, altitude } => window.hillshade(azimuth, altitude),
+            Self::MultidirectionalHillshade { altitude } => {
+                window.multidirectional_hillshade(altitude)
+            }
+            Self::ProfileCurvature => window.profile_curvature(),
+            Self::PlanCurvature => window.plan_curvature(),
+            Self::Tpi => window.tpi(),
+            Self::Tri => window.tri(),
+        };
+
+        value.is_finite().then_some(value)
+    }
+}
+
+/// A 3x3 window of elevations around a center pixel.
+///
+/// The elevations are stored row by row, starting in the north-west:
+///
+/// ```notest
+/// a b c
+/// d e f
+/// g h i
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ElevationWindow {
+    z: [f64; 9],
+    /// The pixel width in the unit of the elevations
+    dx: f64,
+    /// The pixel height in the unit of the elevations
+    dy: f64,
+}
+
+impl ElevationWindow {
+    pub fn new(z: [f64; 9], dx: f64, dy: f64) -> Self {
+        Self { z, dx, dy }
+    }
+
+    /// The gradient `(dz/dx, dz/dy)` after Horn (1981), with `y` pointing south.
+    fn gradient(&self) -> (f64, f64) {
+        let [a, b, c, d, _e, f, g, h, i] = self.z;
+
+        let dz_dx = ((c + 2. * f + i) - (a + 2. * d + g)) / (8. * self.dx);
+        let dz_dy = ((g + 2. * h + i) - (a + 2. * b + c)) / (8. * self.dy);
+
+        (dz_dx, dz_dy)
+    }
+
+    fn slope_radians(&self) -> f64 {
+        let (dz_dx, dz_dy) = self.gradient();
+        dz_dx.hypot(dz_dy).atan()
+    }
+
+    pub fn slope(&self) -> f64 {
+        self.slope_radians().to_degrees()
+    }
+
+    pub fn aspect(&self) -> Option<f64> {
+        let (dz_dx, dz_dy) = self.gradient();
+
+        if dz_dx == 0. && dz_dy == 0. {
+            return None;
+        }
+
+        // the downslope direction, counterclockwise from east
+        let math_aspect = dz_dy.atan2(-dz_dx).to_degrees();
+
+        Some((90. - math_aspect).rem_euclid(360.))
+    }
+
+    pub fn hillshade(&self, azimuth: f64, altitude: f64) -> f64 {
+        let zenith = (90. - altitude).to_radians();
+        let slope = self.slope_radians();
+
+        let (dz_dx, dz_dy) = self.gradient();
+        // the downslope direction, counterclockwise from east
+        let aspect = dz_dy.atan2(-dz_dx);
+        // the direction of the light source, counterclockwise from east
+        let azimuth = (450. - azimuth).to_radians();
+
+        let illumination =
+            zenith.cos() * slope.cos() + zenith.sin() * slope.sin() * (azimuth - aspect).cos();
+
+        255. * illumination.max(0.)
+    }
+
+    /// A multidirectional hillshade after Mark (1992), as it is implemented in GDAL
+    pub fn multidirectional_hillshade(&self, altitude: f64) -> f64 {
+        const AZIMUTHS: [f64; 4] = [225., 270., 315., 360.];
+
+        let aspect = self.aspect().unwrap_or_default();
+
+        // the weights sum up to 2
+        AZIMUTHS
+            .iter()
+            .map(|azimuth| {
+                let weight = (aspect - azimuth).to_radians().sin().powi(2);
+                weight * self.hillshade(*azimuth, altitude)
+            })
+            .sum::<f64>()
+            / 2.
+    }
+
+    /// The partial derivatives after Zevenbergen & Thorne (1987)
+    fn zevenbergen_thorne(&self) -> [f64; 5] {
+        let [a, b, c, d, e, f, g, h, i] = self.z;
+
+        let dd = ((d + f) / 2. - e) / (self.dx * self.dx);
+        let ee = ((b + h) / 2. - e) / (self.dy * self.dy);
+        let ff = (-a + c + g - i) / (4. * self.dx * self.dy);
+        let gg = (f - d) / (2. * self.dx);
+        let hh = (b - h) / (2. * self.dy);
+
+        [dd, ee, ff, gg, hh]
+    }
+
+    pub fn profile_curvature(&self) -> f64 {
+        let [d, e, f, g, h] = self.zevenbergen_thorne();
+
+        let squared_gradient = g * g + h * h;
+        if squared_gradient == 0. {
+            return 0.;
+        }
+
+        2. * (d * g * g + e * h * h + f * g * h) / squared_gradient
+    }
+
+    pub fn plan_curvature(&self) -> f64 {
+        let [d, e, f, g, h] = self.zevenbergen_thorne();
+
+        let squared_gradient = g * g + h * h;
+        if squared_gradient == 0. {
+            return 0.;
+        }
+
+        -2. * (d * h * h + e * g * g - f * g * h) / squared_gradient
+    }
+
+    fn neighbors(&self) -> impl Iterator<Item = f64> + '_ {
+        self.z
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| *index != 4)
+            .map(|(_, z)| *z)
+    }
+
+    pub fn tpi(&self) -> f64 {
+        self.z[4] - self.neighbors().sum::<f64>() / 8.
+    }
+
+    pub fn tri(&self) -> f64 {
+        let center = self.z[4];
+        self.neighbors()
+            .map(|z| (z - center) * (z - center))
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+/// The length of a degree of longitude and latitude in meters at the given latitude on the WGS 84 ellipsoid
+pub fn degree_lengths_in_meters(latitude: f64) -> (f64, f64) {
+    let phi = latitude.to_radians();
+
+    let longitude_length = 111_412.84 * phi.cos() - 93.5 * (3. * phi).cos();
+    let latitude_length = 111_132.92 - 559.82 * (2. * phi).cos() + 1.175 * (4. * phi).cos();
+
+    (longitude_length, latitude_length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_cmp::approx_eq;
+
+    /// A plane that rises by `rise` per pixel towards the east
+    fn east_rising_plane(rise: f64) -> ElevationWindow {
+        ElevationWindow::new(
+            [
+                0.,
+                rise,
+                2. * rise,
+                0.,
+                rise,
+                2. * rise,
+                0.,
+                rise,
+                2. * rise,
+            ],
+            1.,
+            1.,
+        )
+    }
+
+    #[test]
+    fn it_computes_slope_and_aspect() {
+        let window = east_rising_plane(1.);
+
+        assert!(approx_eq!(f64, window.slope(), 45.));
+        // it descends towards the west
+        assert!(approx_eq!(f64, window.aspect().unwrap(), 270.));
+
+        // rises towards the north, i.e., descends towards the south
+        let window = ElevationWindow::new([2., 2., 2., 1., 1., 1., 0., 0., 0.], 1., 1.);
+        assert!(approx_eq!(f64, window.aspect().unwrap(), 180.));
+
+        let flat = ElevationWindow::new([1.; 9], 1., 1.);
+        assert!(approx_eq!(f64, flat.slope(), 0.));
+        assert!(flat.aspect().is_none());
+        assert!(TerrainAttribute::Aspect.apply(&flat).is_none());
+    }
+
+    #[test]
+    fn it_computes_hillshades() {
+        let flat = ElevationWindow::new([1.; 9], 1., 1.);
+
+        assert!(approx_eq!(
+            f64,
+            flat.hillshade(315., 45.),
+            255. * 45_f64.to_radians().cos(),
+            epsilon = 1e-9
+        ));
+        assert!(approx_eq!(
+            f64,
+            flat.multidirectional_hillshade(45.),
+            255. * 45_f64.to_radians().cos(),
+            epsilon = 1e-9
+        ));
+
+        // a slope facing west is lit by a light source from the west
+        let window = east_rising_plane(1.);
+        assert!(window.hillshade(270., 45.) > window.hillshade(90., 45.));
+        assert!(approx_eq!(f64, window.hillshade(90., 0.), 0.));
+        assert!(approx_eq!(
+            f64,
+            window.hillshade(270., 45.),
+            255.,
+            epsilon = 1e-9
+        ));
+    }
+
+    #[test]
+    fn it_computes_curvatures() {
+        let plane = east_rising_plane(1.);
+        assert!(approx_eq!(f64, plane.profile_curvature(), 0.));
+        assert!(approx_eq!(f64, plane.plan_curvature(), 0.));
+
+        // a valley running north-south with a slope towards the south
+        let valley = ElevationWindow::new([3., 2., 3., 2., 1., 2., 1., 0., 1.], 1., 1.);
+        assert!(valley.plan_curvature() < 0.);
+        assert!(approx_eq!(f64, valley.profile_curvature(), 0.));
+    }
+
+    #[test]
+    fn it_computes_tpi_and_tri() {
+        let peak = ElevationWindow::new([0., 0., 0., 0., 8., 0., 0., 0., 0.], 1., 1.);
+
+        assert!(approx_eq!(f64, peak.tpi(), 8.));
+        assert!(approx_eq!(f64, peak.tri(), (8. * 64_f64).sqrt()));
+
+        let flat = ElevationWindow::new([1.; 9], 1., 1.);
+        assert!(approx_eq!(f64, flat.tpi(), 0.));
+        assert!(approx_eq!(f64, flat.tri(), 0.));
+    }
+
+    #[test]
+    fn it_computes_degree_lengths() {
+        let (longitude_length, latitude_length) = degree_lengths_in_meters(0.);
+        assert!(approx_eq!(
+            f64,
+            longitude_length,
+            111_319.34,
+            epsilon = 1e-2
+        ));
+        assert!(approx_eq!(
+            f64,
+            latitude_length,
+            110_574.275,
+            epsilon = 1e-2
+        ));
+
+        let (longitude_length, _) = degree_lengths_in_meters(60.);
+        assert!(approx_eq!(f64, longitude_length, 55_799.92, epsilon = 1e-2));
+    }
+}
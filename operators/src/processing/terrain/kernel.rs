@@ -0,0 +1,63 @@
+use super::attributes::{ElevationWindow, TerrainAttribute, degree_lengths_in_meters};
+use crate::processing::neighborhood_aggregate::NeighborhoodKernel;
+use geoengine_datatypes::raster::{
+    GridIdx, GridIdx2D, GridIndexAccess, RasterTile2D, TileInformation,
+};
+
+/// The settings for computing a terrain attribute from a 3x3 window of elevations
+#[derive(Debug, Clone, Copy)]
+pub struct TerrainKernel {
+    pub attribute: TerrainAttribute,
+    pub z_factor: f64,
+    /// Scale the pixel sizes from degrees to meters
+    pub geodesic: bool,
+}
+
+impl NeighborhoodKernel<f32> for TerrainKernel {
+    fn x_radius(&self) -> usize {
+        1
+    }
+
+    fn y_radius(&self) -> usize {
+        1
+    }
+
+    fn apply(
+        &self,
+        input: &RasterTile2D<f32>,
+        pixel: GridIdx2D,
+        info_out: &TileInformation,
+    ) -> Option<f32> {
+        let GridIdx([y, x]) = pixel;
+
+        let mut z = [0.; 9];
+        for (i, z) in z.iter_mut().enumerate() {
+            let y_index = y + (i / 3) as isize;
+            let x_index = x + (i % 3) as isize;
+
+            // the attribute is undefined if any elevation of the window is missing
+            *z = f64::from(input.get_at_grid_index_unchecked([y_index, x_index])?) * self.z_factor;
+        }
+
+        let x_pixel_size = info_out.global_geo_transform.x_pixel_size().abs();
+        let y_pixel_size = info_out.global_geo_transform.y_pixel_size().abs();
+
+        let (dx, dy) = if self.geodesic {
+            let latitude = info_out
+                .tile_geo_transform()
+                .grid_idx_to_pixel_center_coordinate_2d(pixel)
+                .y;
+            let (longitude_length, latitude_length) = degree_lengths_in_meters(latitude);
+            (
+                x_pixel_size * longitude_length,
+                y_pixel_size * latitude_length,
+            )
+        } else {
+            (x_pixel_size, y_pixel_size)
+        };
+
+        self.attribute
+            .apply(&ElevationWindow::new(z, dx, dy))
+            .map(|value| value as f32)
+    }
+}
@@ -0,0 +1,480 @@
+mod attributes;
+mod kernel;
+
+pub use self::attributes::TerrainAttribute;
+use self::kernel::TerrainKernel;
+use crate::adapters::{
+    FillerTileCacheExpirationStrategy, RasterSubQueryAdapter, stack_individual_aligned_raster_bands,
+};
+use crate::engine::{
+    CanonicOperatorName, ExecutionContext, InitializedRasterOperator, InitializedSources, Operator,
+    OperatorName, QueryContext, QueryProcessor, RasterBandDescriptor, RasterBandDescriptors,
+    RasterOperator, RasterQueryProcessor, RasterResultDescriptor, SingleRasterSource,
+    TypedRasterQueryProcessor, WorkflowOperatorPath,
+};
+use crate::processing::neighborhood_aggregate::NeighborhoodTileSubQuery;
+use crate::util::Result;
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use geoengine_datatypes::primitives::{
+    BandSelection, Measurement, RasterQueryRectangle, SpatialPartition2D,
+};
+use geoengine_datatypes::raster::{RasterDataType, RasterTile2D, TilingSpecification};
+use geoengine_datatypes::spatial_reference::{SpatialReference, SpatialReferenceOption};
+use serde::{Deserialize, Serialize};
+use snafu::{Snafu, ensure};
+
+/// The `Terrain` operator derives terrain attributes like slope, aspect or hillshade from a digital elevation model.
+///
+/// For each pixel, the attribute is computed from the 3x3 window around it, taking the pixel size into account.
+/// For rasters in EPSG:4326, the pixel size is converted from degrees to meters at the latitude of each pixel.
+/// Pixels at the border of the data or next to NODATA pixels are NODATA.
+pub type Terrain = Operator<TerrainParams, SingleRasterSource>;
+
+impl OperatorName for Terrain {
+    const TYPE_NAME: &'static str = "Terrain";
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TerrainParams {
+    /// The attribute to compute
+    pub attribute: TerrainAttribute,
+    /// A factor to convert the elevation unit to the unit of the pixel size, e.g., from feet to meters.
+    /// Defaults to 1.
+    #[serde(default)]
+    pub z_factor: Option<f64>,
+}
+
+#[derive(Debug, Snafu)]
+#[snafu(visibility(pub(crate)), context(suffix(false)), module(error))]
+pub enum TerrainError {
+    #[snafu(display("The z factor must be a positive number, but it is {z_factor}"))]
+    InvalidZFactor { z_factor: f64 },
+
+    #[snafu(display(
+        "The altitude of the light source must be between 0 and 90 degrees, but it is {altitude}"
+    ))]
+    InvalidAltitude { altitude: f64 },
+
+    #[snafu(display(
+        "The azimuth of the light source must be between 0 and 360 degrees, but it is {azimuth}"
+    ))]
+    InvalidAzimuth { azimuth: f64 },
+}
+
+impl TerrainParams {
+    fn validate(&self) -> Result<(), TerrainError> {
+        if let Some(z_factor) = self.z_factor {
+            ensure!(
+                z_factor.is_finite() && z_factor > 0.,
+                error::InvalidZFactor { z_factor }
+            );
+        }
+
+        let altitude = match self.attribute {
+            TerrainAttribute::Hillshade { azimuth, altitude } => {
+                ensure!(
+                    (0. ..=360.).contains(&azimuth),
+                    error::InvalidAzimuth { azimuth }
+                );
+                altitude
+            }
+            TerrainAttribute::MultidirectionalHillshade { altitude } => altitude,
+            _ => return Ok(()),
+        };
+
+        ensure!(
+            (0. ..=90.).contains(&altitude),
+            error::InvalidAltitude { altitude }
+        );
+
+        Ok(())
+    }
+}
+
+#[typetag::serde]
+#[async_trait]
+impl RasterOperator for Terrain {
+    async fn _initialize(
+        self: Box<Self>,
+        path: WorkflowOperatorPath,
+        context: &dyn ExecutionContext,
+    ) -> Result<Box<dyn InitializedRasterOperator>> {
+        let name = CanonicOperatorName::from(&self);
+
+        self.params.validate()?;
+
+        let initialized_source = self
+            .sources
+            .initialize_sources(path.clone(), context)
+            .await?;
+        let raster_source = initialized_source.raster;
+
+        let in_descriptor = raster_source.result_descriptor();
+
+        let attribute = self.params.attribute;
+        let bands = in_descriptor
+            .bands
+            .iter()
+            .map(|band| {
+                RasterBandDescriptor::new(
+                    band.name.clone(),
+                    Measurement::continuous(
+                        attribute.band_name().to_string(),
+                        attribute.unit().map(ToString::to_string),
+                    ),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let result_descriptor = RasterResultDescriptor {
+            data_type: RasterDataType::F32,
+            spatial_reference: in_descriptor.spatial_reference,
+            time: in_descriptor.time,
+            bbox: in_descriptor.bbox,
+            resolution: in_descriptor.resolution,
+            bands: RasterBandDescriptors::new(bands)?,
+        };
+
+        let geodesic = in_descriptor.spatial_reference
+            == SpatialReferenceOption::SpatialReference(SpatialReference::epsg_4326());
+
+        let initialized_operator = InitializedTerrain {
+            name,
+            path,
+            result_descriptor,
+            raster_source,
+            kernel: TerrainKernel {
+                attribute,
+                z_factor: self.params.z_factor.unwrap_or(1.),
+                geodesic,
+            },
+            tiling_specification: context.tiling_specification(),
+        };
+
+        Ok(initialized_operator.boxed())
+    }
+
+    span_fn!(Terrain);
+}
+
+pub struct InitializedTerrain {
+    name: CanonicOperatorName,
+    path: WorkflowOperatorPath,
+    result_descriptor: RasterResultDescriptor,
+    raster_source: Box<dyn InitializedRasterOperator>,
+    kernel: TerrainKernel,
+    tiling_specification: TilingSpecification,
+}
+
+impl InitializedRasterOperator for InitializedTerrain {
+    fn query_processor(&self) -> Result<TypedRasterQueryProcessor> {
+        let source_processor = self.raster_source.query_processor()?.into_f32();
+
+        Ok(TerrainProcessor::new(
+            source_processor,
+            self.result_descriptor.clone(),
+            self.tiling_specification,
+            self.kernel,
+        )
+        .boxed()
+        .into())
+    }
+
+    fn result_descriptor(&self) -> &RasterResultDescriptor {
+        &self.result_descriptor
+    }
+
+    fn canonic_name(&self) -> CanonicOperatorName {
+        self.name.clone()
+    }
+
+    fn name(&self) -> &'static str {
+        Terrain::TYPE_NAME
+    }
+
+    fn path(&self) -> WorkflowOperatorPath {
+        self.path.clone()
+    }
+}
+
+pub struct TerrainProcessor<Q> {
+    source: Q,
+    result_descriptor: RasterResultDescriptor,
+    tiling_specification: TilingSpecification,
+    kernel: TerrainKernel,
+}
+
+impl<Q> TerrainProcessor<Q>
+where
+    Q: RasterQueryProcessor<RasterType = f32>,
+{
+    pub fn new(
+        source: Q,
+        result_descriptor: RasterResultDescriptor,
+        tiling_specification: TilingSpecification,
+        kernel: TerrainKernel,
+    ) -> Self {
+        Self {
+            source,
+            result_descriptor,
+            tiling_specification,
+            kernel,
+        }
+    }
+}
+
+#[async_trait]
+impl<Q> QueryProcessor for TerrainProcessor<Q>
+where
+    Q: QueryProcessor<
+            Output = RasterTile2D<f32>,
+            SpatialBounds = SpatialPartition2D,
+            Selection = BandSelection,
+            ResultDescription = RasterResultDescriptor,
+        >,
+{
+    type Output = RasterTile2D<f32>;
+    type SpatialBounds = SpatialPartition2D;
+    type Selection = BandSelection;
+    type ResultDescription = RasterResultDescriptor;
+
+    async fn _query<'a>(
+        &'a self,
+        query: RasterQueryRectangle,
+        ctx: &'a dyn QueryContext,
+    ) -> Result<BoxStream<'a, Result<Self::Output>>> {
+        stack_individual_aligned_raster_bands(&query, ctx, |query, ctx| async move {
+            let sub_query =
+                NeighborhoodTileSubQuery::<f32, _>::new(self.kernel, self.tiling_specification);
+
+            Ok(RasterSubQueryAdapter::<'a, f32, _, _>::new(
+                &self.source,
+                query,
+                self.tiling_specification,
+                ctx,
+                sub_query,
+            )
+            .filter_and_fill(FillerTileCacheExpirationStrategy::DerivedFromSurroundingTiles))
+        })
+        .await
+    }
+
+    fn result_descriptor(&self) -> &RasterResultDescriptor {
+        &self.result_descriptor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{MockExecutionContext, MockQueryContext};
+    use crate::mock::{MockRasterSource, MockRasterSourceParams};
+    use futures::StreamExt;
+    use geoengine_datatypes::primitives::{CacheHint, SpatialResolution, TimeInterval};
+    use geoengine_datatypes::raster::{Grid2D, GridOrEmpty, TileInformation};
+    use geoengine_datatypes::spatial_reference::SpatialReferenceAuthority;
+    use geoengine_datatypes::util::test::TestDefault;
+
+    #[test]
+    fn it_serializes() {
+        let params = TerrainParams {
+            attribute: TerrainAttribute::Hillshade {
+                azimuth: 315.,
+                altitude: 45.,
+            },
+            z_factor: Some(2.),
+        };
+
+        let serialized = serde_json::to_value(&params).unwrap();
+
+        assert_eq!(
+            serialized,
+            serde_json::json!({
+                "attribute": {
+                    "type": "hillshade",
+                    "azimuth": 315.0,
+                    "altitude": 45.0
+                },
+                "zFactor": 2.0
+            })
+        );
+
+        let deserialized: TerrainParams = serde_json::from_value(serde_json::json!({
+            "attribute": {
+                "type": "slope"
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            deserialized,
+            TerrainParams {
+                attribute: TerrainAttribute::Slope,
+                z_factor: None,
+            }
+        );
+    }
+
+    /// A DEM that rises by one unit per pixel towards the east
+    fn make_dem(spatial_reference: SpatialReferenceOption) -> Box<dyn RasterOperator> {
+        let tile = |x: isize, data: Vec<i16>| {
+            RasterTile2D::new_with_tile_info(
+                TimeInterval::new_unchecked(0, 10),
+                TileInformation {
+                    global_tile_position: [-1, x].into(),
+                    tile_size_in_pixels: [3, 3].into(),
+                    global_geo_transform: TestDefault::test_default(),
+                },
+                0,
+                GridOrEmpty::from(Grid2D::new([3, 3].into(), data).unwrap()),
+                CacheHint::default(),
+            )
+        };
+
+        MockRasterSource {
+            params: MockRasterSourceParams {
+                data: vec![
+                    tile(0, vec![0, 1, 2, 0, 1, 2, 0, 1, 2]),
+                    tile(1, vec![3, 4, 5, 3, 4, 5, 3, 4, 5]),
+                ],
+                result_descriptor: RasterResultDescriptor {
+                    data_type: RasterDataType::I16,
+                    spatial_reference,
+                    time: None,
+                    bbox: None,
+                    resolution: None,
+                    bands: RasterBandDescriptors::new_single_band(),
+                },
+            },
+        }
+        .boxed()
+    }
+
+    async fn compute(
+        attribute: TerrainAttribute,
+        spatial_reference: SpatialReferenceOption,
+    ) -> Result<Vec<RasterTile2D<f32>>> {
+        let exe_ctx = MockExecutionContext::new_with_tiling_spec(TilingSpecification::new(
+            (0., 0.).into(),
+            [3, 3].into(),
+        ));
+
+        let operator = Terrain {
+            params: TerrainParams {
+                attribute,
+                z_factor: None,
+            },
+            sources: SingleRasterSource {
+                raster: make_dem(spatial_reference),
+            },
+        }
+        .boxed()
+        .initialize(WorkflowOperatorPath::initialize_root(), &exe_ctx)
+        .await?;
+
+        assert_eq!(
+            operator.result_descriptor().bands[0].measurement,
+            Measurement::continuous(
+                attribute.band_name().to_string(),
+                attribute.unit().map(ToString::to_string)
+            )
+        );
+
+        let processor = operator.query_processor()?.get_f32().unwrap();
+
+        let query_rect = RasterQueryRectangle {
+            spatial_bounds: SpatialPartition2D::new((0., 3.).into(), (6., 0.).into()).unwrap(),
+            time_interval: TimeInterval::new_unchecked(0, 10),
+            spatial_resolution: SpatialResolution::one(),
+            attributes: BandSelection::first(),
+        };
+        let query_ctx = MockQueryContext::test_default();
+
+        processor
+            .query(query_rect, &query_ctx)
+            .await?
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn it_computes_slope_and_aspect() {
+        let projected = SpatialReference::new(SpatialReferenceAuthority::Epsg, 32632).into();
+
+        let result = compute(TerrainAttribute::Slope, projected).await.unwrap();
+
+        assert_eq!(result.len(), 2);
+
+        let expected_validity = [
+            vec![false, false, false, false, true, true, false, false, false],
+            vec![false, false, false, true, true, false, false, false, false],
+        ];
+
+        for (tile, expected_validity) in result.into_iter().zip(expected_validity) {
+            let tile = tile.into_materialized_tile();
+            assert_eq!(tile.grid_array.validity_mask.data, expected_validity);
+
+            for (value, valid) in tile
+                .grid_array
+                .inner_grid
+                .data
+                .iter()
+                .zip(expected_validity)
+            {
+                if valid {
+                    assert!(float_cmp::approx_eq!(f32, *value, 45., epsilon = 1e-5));
+                }
+            }
+        }
+
+        let result = compute(TerrainAttribute::Aspect, projected).await.unwrap();
+        let tile = result[0].clone().into_materialized_tile();
+        assert!(float_cmp::approx_eq!(
+            f32,
+            tile.grid_array.inner_grid.data[4],
+            270.,
+            epsilon = 1e-5
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_scales_degrees_to_meters() {
+        let result = compute(
+            TerrainAttribute::Slope,
+            SpatialReference::epsg_4326().into(),
+        )
+        .await
+        .unwrap();
+
+        let tile = result[0].clone().into_materialized_tile();
+
+        // one unit of elevation over roughly 111 km
+        let expected = (1. / 111_319.49_f64).atan().to_degrees() as f32;
+        assert!(float_cmp::approx_eq!(
+            f32,
+            tile.grid_array.inner_grid.data[4],
+            expected,
+            epsilon = 1e-6
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_rejects_invalid_parameters() {
+        let result = compute(
+            TerrainAttribute::MultidirectionalHillshade { altitude: 100. },
+            SpatialReference::epsg_4326().into(),
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(crate::error::Error::Terrain {
+                source: TerrainError::InvalidAltitude { .. }
+            })
+        ));
+    }
+}
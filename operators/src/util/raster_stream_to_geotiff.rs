@@ -541,6 +541,13 @@ impl ToGeoTiffProgressConsumer for () {
     }
 }
 
+#[async_trait]
+impl<T: ToGeoTiffProgressConsumer + Sync> ToGeoTiffProgressConsumer for &T {
+    async fn consume_progress(&self, status: ToGeoTiffProgress) {
+        (*self).consume_progress(status).await;
+    }
+}
+
 const COG_BLOCK_SIZE: &str = "512";
 const COMPRESSION_FORMAT: &str = "LZW";
 const COMPRESSION_LEVEL: &str = "9"; // maximum compression
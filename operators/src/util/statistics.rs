@@ -601,9 +601,154 @@ impl PSquareHistogramBucket {
     }
 }
 
+/// Quantile sketch after Karnin, Lang and Liberty (KLL)
+///
+/// The sketch keeps a hierarchy of compactors. Samples enter the lowest level and whenever a level
+/// exceeds its capacity, it is sorted and every other sample is promoted to the next level, doubling its weight.
+/// Lower levels have smaller capacities, so the memory is bounded by about `3k` samples, independent of the
+/// number of samples. All quantiles can be queried from the same sketch and are exact as long as no compaction
+/// happened. Otherwise, the rank error is in the order of `1/k`.
+///
+/// For further details, see
+///
+/// Z. Karnin, K. Lang and E. Liberty, Optimal Quantile Approximation in Streams,
+/// IEEE 57th Annual Symposium on Foundations of Computer Science (FOCS), 2016, p. 71-78.
+/// <https://arxiv.org/abs/1603.05346>
+///
+#[derive(Debug, Clone)]
+pub struct KllSketch {
+    k: usize,
+    compactors: Vec<Vec<f64>>,
+    sample_count: u64,
+    /// alternates the samples that are promoted during compaction to avoid a bias
+    odd_offset: bool,
+}
+
+impl KllSketch {
+    const MIN_K: usize = 8;
+    const CAPACITY_DECAY: f64 = 2. / 3.;
+    const MIN_CAPACITY: usize = 2;
+
+    /// Creates a new sketch with accuracy parameter `k`.
+    ///
+    /// # Errors
+    /// If `k` is smaller than 8.
+    ///
+    pub fn new(k: usize) -> Result<Self, StatisticsError> {
+        if k < Self::MIN_K {
+            return Err(StatisticsError::Initialization {
+                reason: format!("The sketch accuracy must be at least {}", Self::MIN_K),
+            });
+        }
+
+        Ok(Self {
+            k,
+            compactors: vec![Vec::new()],
+            sample_count: 0,
+            odd_offset: false,
+        })
+    }
+
+    /// Adds a sample to the sketch.
+    ///
+    /// # Note
+    /// A `sample` that does not satisfy `f64::is_finite` is silently ignored.
+    ///
+    pub fn update(&mut self, sample: f64) {
+        if !sample.is_finite() {
+            return;
+        }
+
+        self.compactors[0].push(sample);
+        self.sample_count += 1;
+
+        while self.size() >= self.max_size() {
+            self.compress();
+        }
+    }
+
+    /// Returns the number of samples that were added to the sketch
+    pub fn sample_count(&self) -> u64 {
+        self.sample_count
+    }
+
+    /// Estimates the `quantile` as the smallest sample whose (weighted) rank is at least `quantile` times
+    /// the number of samples. Returns `None` if the sketch is empty.
+    pub fn quantile_estimate(&self, quantile: f64) -> Option<f64> {
+        let mut weighted_samples = self
+            .compactors
+            .iter()
+            .enumerate()
+            .flat_map(|(level, compactor)| {
+                compactor
+                    .iter()
+                    .map(move |&sample| (sample, 1_u64 << level))
+            })
+            .collect::<Vec<_>>();
+        weighted_samples.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+
+        let total_weight: u64 = weighted_samples.iter().map(|(_, weight)| weight).sum();
+        let target_rank = quantile * total_weight as f64;
+
+        let mut rank = 0;
+        for (sample, weight) in &weighted_samples {
+            rank += weight;
+            if rank as f64 >= target_rank {
+                return Some(*sample);
+            }
+        }
+
+        weighted_samples.last().map(|(sample, _)| *sample)
+    }
+
+    fn size(&self) -> usize {
+        self.compactors.iter().map(Vec::len).sum()
+    }
+
+    fn max_size(&self) -> usize {
+        (0..self.compactors.len())
+            .map(|level| self.capacity(level))
+            .sum()
+    }
+
+    /// The capacity decreases geometrically from the highest level downwards
+    fn capacity(&self, level: usize) -> usize {
+        let depth = self.compactors.len() - level - 1;
+        let capacity = (self.k as f64 * Self::CAPACITY_DECAY.powi(depth as i32)).ceil() as usize;
+        capacity.max(Self::MIN_CAPACITY)
+    }
+
+    /// Compacts the lowest level that exceeds its capacity
+    fn compress(&mut self) {
+        let Some(level) = (0..self.compactors.len())
+            .find(|&level| self.compactors[level].len() >= self.capacity(level))
+        else {
+            return;
+        };
+
+        if level + 1 == self.compactors.len() {
+            self.compactors.push(Vec::new());
+        }
+
+        let mut samples = std::mem::take(&mut self.compactors[level]);
+        samples.sort_unstable_by(f64::total_cmp);
+
+        // an odd sample stays on its level
+        if samples.len() % 2 == 1 {
+            self.compactors[level].extend(samples.pop());
+        }
+
+        let offset = usize::from(self.odd_offset);
+        self.odd_offset = !self.odd_offset;
+
+        self.compactors[level + 1].extend(samples.into_iter().skip(offset).step_by(2));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::SeedableRng;
     use rand::seq::SliceRandom;
 
     #[test]
@@ -877,4 +1022,46 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn kll_sketch_is_exact_for_small_samples() {
+        let mut sketch = KllSketch::new(200).unwrap();
+
+        assert_eq!(sketch.quantile_estimate(0.5), None);
+
+        for value in [6., 1., f64::NAN, 5., 2., 4., 3.] {
+            sketch.update(value);
+        }
+
+        assert_eq!(sketch.sample_count(), 6);
+        assert_eq!(sketch.quantile_estimate(0.25), Some(2.));
+        assert_eq!(sketch.quantile_estimate(0.5), Some(3.));
+        assert_eq!(sketch.quantile_estimate(0.75), Some(5.));
+        assert_eq!(sketch.quantile_estimate(1.), Some(6.));
+    }
+
+    #[test]
+    fn kll_sketch_bounds_memory_and_error() {
+        let mut values = (0..100_000).map(f64::from).collect::<Vec<_>>();
+        values.shuffle(&mut rand::rngs::StdRng::seed_from_u64(42));
+
+        let mut sketch = KllSketch::new(200).unwrap();
+        for value in values {
+            sketch.update(value);
+        }
+
+        assert_eq!(sketch.sample_count(), 100_000);
+        assert!(sketch.size() < 4 * 200);
+
+        for quantile in [0.01, 0.25, 0.5, 0.75, 0.99] {
+            let estimate = sketch.quantile_estimate(quantile).unwrap();
+            let rank_error = (estimate / 100_000. - quantile).abs();
+            assert!(rank_error < 0.02, "{quantile}: {estimate}");
+        }
+    }
+
+    #[test]
+    fn kll_sketch_checks_accuracy() {
+        assert!(KllSketch::new(7).is_err());
+    }
 }
@@ -0,0 +1,429 @@
+use crate::engine::{QueryContext, VectorColumnInfo, VectorQueryProcessor, VectorResultDescriptor};
+use crate::source::{
+    OgrSourceColumnSpec, OgrSourceDataset, OgrSourceDatasetTimeType, OgrSourceErrorSpec,
+    OgrSourceTimeFormat,
+};
+use crate::util::Result;
+use futures::StreamExt;
+use futures::future::BoxFuture;
+use gdal::vector::{Feature, LayerAccess, LayerOptions, OGRFieldType, OGRwkbGeometryType};
+use gdal::{Dataset, DriverManager};
+use geoengine_datatypes::collections::{
+    DataCollection, FeatureCollection, FeatureCollectionInfos, IntoGeometryIterator,
+    MultiLineStringCollection, MultiPointCollection, MultiPolygonCollection, VectorDataType,
+};
+use geoengine_datatypes::primitives::{
+    BoundingBox2D, FeatureDataType, FeatureDataValue, Geometry, GeometryRef, Measurement,
+    TimeInterval, VectorQueryRectangle,
+};
+use geoengine_datatypes::spatial_reference::SpatialReference;
+use geoengine_datatypes::util::arrow::ArrowTyped;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use wkt::ToWkt;
+
+use super::{abortable_query_execution, spawn_blocking};
+
+/// The file formats a vector stream can be written to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OgrVectorFormat {
+    GeoPackage,
+    FlatGeobuf,
+    GeoParquet,
+}
+
+impl OgrVectorFormat {
+    pub fn driver_name(self) -> &'static str {
+        match self {
+            OgrVectorFormat::GeoPackage => "GPKG",
+            OgrVectorFormat::FlatGeobuf => "FlatGeobuf",
+            OgrVectorFormat::GeoParquet => "Parquet",
+        }
+    }
+
+    pub fn file_extension(self) -> &'static str {
+        match self {
+            OgrVectorFormat::GeoPackage => "gpkg",
+            OgrVectorFormat::FlatGeobuf => "fgb",
+            OgrVectorFormat::GeoParquet => "parquet",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OgrVectorDatasetMetadata {
+    pub format: OgrVectorFormat,
+    pub layer_name: String,
+    pub spatial_reference: SpatialReference,
+}
+
+/// The result of writing a vector stream to a file.
+/// Feature time is stored as epoch milliseconds in the `time_start_field` and `time_end_field` columns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OgrVectorDatasetInfo {
+    pub file_path: PathBuf,
+    pub layer_name: String,
+    pub data_type: VectorDataType,
+    pub spatial_reference: SpatialReference,
+    pub columns: Vec<OgrVectorColumn>,
+    pub time_start_field: String,
+    pub time_end_field: String,
+    pub feature_count: u64,
+    pub time: Option<TimeInterval>,
+    pub bbox: Option<BoundingBox2D>,
+}
+
+/// A column of the written layer with the type that is used to read it again
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OgrVectorColumn {
+    pub name: String,
+    pub data_type: FeatureDataType,
+    pub measurement: Measurement,
+}
+
+impl OgrVectorDatasetInfo {
+    /// The result descriptor of the written layer
+    pub fn result_descriptor(&self) -> VectorResultDescriptor {
+        VectorResultDescriptor {
+            data_type: self.data_type,
+            spatial_reference: self.spatial_reference.into(),
+            columns: self
+                .columns
+                .iter()
+                .map(|column| {
+                    (
+                        column.name.clone(),
+                        VectorColumnInfo {
+                            data_type: column.data_type,
+                            measurement: column.measurement.clone(),
+                        },
+                    )
+                })
+                .collect(),
+            time: self.time,
+            bbox: self.bbox,
+        }
+    }
+
+    /// The loading info for reading the written file with the `OgrSource`
+    pub fn loading_info(&self) -> OgrSourceDataset {
+        let columns_of_type = |data_type: FeatureDataType| {
+            self.columns
+                .iter()
+                .filter(|column| column.data_type == data_type)
+                .map(|column| column.name.clone())
+                .collect::<Vec<_>>()
+        };
+
+        OgrSourceDataset {
+            file_name: self.file_path.clone(),
+            layer_name: self.layer_name.clone(),
+            data_type: Some(self.data_type),
+            time: OgrSourceDatasetTimeType::StartEnd {
+                start_field: self.time_start_field.clone(),
+                start_format: OgrSourceTimeFormat::milliseconds(),
+                end_field: self.time_end_field.clone(),
+                end_format: OgrSourceTimeFormat::milliseconds(),
+            },
+            default_geometry: None,
+            columns: Some(OgrSourceColumnSpec {
+                format_specifics: None,
+                x: String::new(),
+                y: None,
+                int: columns_of_type(FeatureDataType::Int),
+                float: columns_of_type(FeatureDataType::Float),
+                text: columns_of_type(FeatureDataType::Text),
+                bool: columns_of_type(FeatureDataType::Bool),
+                datetime: columns_of_type(FeatureDataType::DateTime),
+                rename: None,
+            }),
+            force_ogr_time_filter: false,
+            force_ogr_spatial_filter: false,
+            on_error: OgrSourceErrorSpec::Abort,
+            sql_query: None,
+            attribute_query: None,
+            cache_ttl: Default::default(),
+        }
+    }
+}
+
+/// A feature collection whose features can be written to an OGR layer
+pub trait OgrWritableCollection: FeatureCollectionInfos + Send + 'static {
+    const VECTOR_DATA_TYPE: VectorDataType;
+
+    /// The geometries of the features as OGR geometries, or `None` for collections without geometries
+    fn ogr_geometries(&self) -> Result<Option<Vec<gdal::vector::Geometry>>>;
+
+    /// The bounding box of all geometries of the collection
+    fn geometry_bbox(&self) -> Option<BoundingBox2D>;
+}
+
+macro_rules! impl_ogr_writable_collection {
+    ($collection:ty, $data_type:expr) => {
+        impl OgrWritableCollection for $collection {
+            const VECTOR_DATA_TYPE: VectorDataType = $data_type;
+
+            fn ogr_geometries(&self) -> Result<Option<Vec<gdal::vector::Geometry>>> {
+                self.geometries()
+                    .map(|geometry| {
+                        gdal::vector::Geometry::from_wkt(&geometry.to_wkt().to_string())
+                            .map_err(Into::into)
+                    })
+                    .collect::<Result<Vec<_>>>()
+                    .map(Some)
+            }
+
+            fn geometry_bbox(&self) -> Option<BoundingBox2D> {
+                self.geometries()
+                    .filter_map(|geometry| geometry.bbox())
+                    .reduce(|a, b| a.union(&b))
+            }
+        }
+    };
+}
+
+impl_ogr_writable_collection!(MultiPointCollection, VectorDataType::MultiPoint);
+impl_ogr_writable_collection!(MultiLineStringCollection, VectorDataType::MultiLineString);
+impl_ogr_writable_collection!(MultiPolygonCollection, VectorDataType::MultiPolygon);
+
+impl OgrWritableCollection for DataCollection {
+    const VECTOR_DATA_TYPE: VectorDataType = VectorDataType::Data;
+
+    fn ogr_geometries(&self) -> Result<Option<Vec<gdal::vector::Geometry>>> {
+        Ok(None)
+    }
+
+    fn geometry_bbox(&self) -> Option<BoundingBox2D> {
+        None
+    }
+}
+
+/// Consume a vector stream and write its features into a single layer of a new file at `file_path`.
+/// Category columns are stored as integers and bool columns are stored as integers that are read as bools.
+pub async fn vector_stream_to_ogr<G, C: QueryContext + 'static>(
+    file_path: &Path,
+    processor: Box<dyn VectorQueryProcessor<VectorType = FeatureCollection<G>>>,
+    query_rect: VectorQueryRectangle,
+    mut query_ctx: C,
+    result_descriptor: &VectorResultDescriptor,
+    metadata: OgrVectorDatasetMetadata,
+    conn_closed: BoxFuture<'_, ()>,
+) -> Result<OgrVectorDatasetInfo>
+where
+    G: Geometry + ArrowTyped + 'static,
+    FeatureCollection<G>: OgrWritableCollection,
+{
+    let query_abort_trigger = query_ctx.abort_trigger()?;
+
+    let mut columns = result_descriptor
+        .columns
+        .iter()
+        .map(|(name, info)| OgrVectorColumn {
+            name: name.clone(),
+            data_type: match info.data_type {
+                FeatureDataType::Category => FeatureDataType::Int,
+                data_type => data_type,
+            },
+            measurement: info.measurement.clone(),
+        })
+        .collect::<Vec<_>>();
+    columns.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let time_start_field = unique_column_name("time_start", &columns);
+    let time_end_field = unique_column_name("time_end", &columns);
+
+    let mut writer = OgrLayerWriter {
+        dataset: None,
+        info: OgrVectorDatasetInfo {
+            file_path: file_path.to_owned(),
+            layer_name: metadata.layer_name.clone(),
+            data_type: <FeatureCollection<G>>::VECTOR_DATA_TYPE,
+            spatial_reference: metadata.spatial_reference,
+            columns,
+            time_start_field,
+            time_end_field,
+            feature_count: 0,
+            time: None,
+            bbox: None,
+        },
+    };
+
+    writer = spawn_blocking(move || -> Result<OgrLayerWriter> {
+        writer.create_dataset(&metadata)?;
+        Ok(writer)
+    })
+    .await??;
+
+    let written = async move {
+        let mut collection_stream = processor.vector_query(query_rect, &query_ctx).await?;
+
+        while let Some(collection) = collection_stream.next().await {
+            let collection = collection?;
+
+            writer = spawn_blocking(move || -> Result<OgrLayerWriter> {
+                writer.write_collection(&collection)?;
+                Ok(writer)
+            })
+            .await??;
+        }
+
+        // closing the dataset flushes the layer to disk
+        let info = spawn_blocking(move || {
+            writer.dataset.take();
+            writer.info
+        })
+        .await?;
+
+        Ok(info)
+    };
+
+    abortable_query_execution(written, conn_closed, query_abort_trigger).await
+}
+
+/// Append `_` to the `name` until it does not collide with any column name
+fn unique_column_name(name: &str, columns: &[OgrVectorColumn]) -> String {
+    let mut name = name.to_string();
+    while columns.iter().any(|column| column.name == name) {
+        name.push('_');
+    }
+    name
+}
+
+struct OgrLayerWriter {
+    dataset: Option<Dataset>,
+    info: OgrVectorDatasetInfo,
+}
+
+impl OgrLayerWriter {
+    fn create_dataset(&mut self, metadata: &OgrVectorDatasetMetadata) -> Result<()> {
+        let driver = DriverManager::get_driver_by_name(metadata.format.driver_name())?;
+        let mut dataset = driver.create_vector_only(&self.info.file_path)?;
+
+        let geometry_type = match self.info.data_type {
+            VectorDataType::Data => OGRwkbGeometryType::wkbNone,
+            VectorDataType::MultiPoint => OGRwkbGeometryType::wkbMultiPoint,
+            VectorDataType::MultiLineString => OGRwkbGeometryType::wkbMultiLineString,
+            VectorDataType::MultiPolygon => OGRwkbGeometryType::wkbMultiPolygon,
+        };
+
+        let layer = dataset.create_layer(LayerOptions {
+            name: &self.info.layer_name,
+            srs: Some(&self.info.spatial_reference.try_into()?),
+            ty: geometry_type,
+            options: None,
+        })?;
+
+        let mut fields = vec![
+            (
+                self.info.time_start_field.as_str(),
+                OGRFieldType::OFTInteger64,
+            ),
+            (
+                self.info.time_end_field.as_str(),
+                OGRFieldType::OFTInteger64,
+            ),
+        ];
+        fields.extend(self.info.columns.iter().map(|column| {
+            let field_type = match column.data_type {
+                FeatureDataType::Category | FeatureDataType::Bool => OGRFieldType::OFTInteger,
+                FeatureDataType::Int | FeatureDataType::DateTime => OGRFieldType::OFTInteger64,
+                FeatureDataType::Float => OGRFieldType::OFTReal,
+                FeatureDataType::Text => OGRFieldType::OFTString,
+            };
+            (column.name.as_str(), field_type)
+        }));
+        layer.create_defn_fields(&fields)?;
+
+        self.dataset = Some(dataset);
+
+        Ok(())
+    }
+
+    fn write_collection<C: OgrWritableCollection>(&mut self, collection: &C) -> Result<()> {
+        let dataset = self
+            .dataset
+            .as_mut()
+            .expect("dataset should exist after creating it");
+        let mut layer = dataset.layer(0)?;
+
+        let mut geometries = collection.ogr_geometries()?.map(Vec::into_iter);
+        let column_data = self
+            .info
+            .columns
+            .iter()
+            .map(|column| collection.data(&column.name))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (feature_idx, time) in collection.time_intervals().iter().enumerate() {
+            let mut feature = Feature::new(layer.defn())?;
+
+            if let Some(geometry) = geometries.as_mut().and_then(Iterator::next) {
+                feature.set_geometry(geometry)?;
+            }
+
+            feature.set_field_integer64(0, time.start().inner())?;
+            feature.set_field_integer64(1, time.end().inner())?;
+
+            for (column_idx, data) in column_data.iter().enumerate() {
+                set_field_value(
+                    &mut feature,
+                    column_idx + 2,
+                    data.get_unchecked(feature_idx),
+                )?;
+            }
+
+            feature.create(&mut layer)?;
+
+            self.info.time = Some(
+                self.info
+                    .time
+                    .map_or(*time, |interval| interval.extend(time)),
+            );
+        }
+
+        if let Some(bbox) = collection.geometry_bbox() {
+            self.info.bbox = Some(
+                self.info
+                    .bbox
+                    .map_or(bbox, |existing| existing.union(&bbox)),
+            );
+        }
+
+        self.info.feature_count += collection.len() as u64;
+
+        Ok(())
+    }
+}
+
+/// Set the field at `field_idx` to the `value`, leaving it unset for null values
+fn set_field_value(feature: &mut Feature, field_idx: usize, value: FeatureDataValue) -> Result<()> {
+    match value {
+        FeatureDataValue::Category(value) | FeatureDataValue::NullableCategory(Some(value)) => {
+            feature.set_field_integer(field_idx, i32::from(value))?;
+        }
+        FeatureDataValue::Int(value) | FeatureDataValue::NullableInt(Some(value)) => {
+            feature.set_field_integer64(field_idx, value)?;
+        }
+        FeatureDataValue::Float(value) | FeatureDataValue::NullableFloat(Some(value)) => {
+            feature.set_field_double(field_idx, value)?;
+        }
+        FeatureDataValue::Text(value) | FeatureDataValue::NullableText(Some(value)) => {
+            feature.set_field_string(field_idx, &value)?;
+        }
+        FeatureDataValue::Bool(value) | FeatureDataValue::NullableBool(Some(value)) => {
+            feature.set_field_integer(field_idx, i32::from(value))?;
+        }
+        FeatureDataValue::DateTime(value) | FeatureDataValue::NullableDateTime(Some(value)) => {
+            feature.set_field_integer64(field_idx, value.inner())?;
+        }
+        FeatureDataValue::NullableCategory(None)
+        | FeatureDataValue::NullableInt(None)
+        | FeatureDataValue::NullableFloat(None)
+        | FeatureDataValue::NullableText(None)
+        | FeatureDataValue::NullableBool(None)
+        | FeatureDataValue::NullableDateTime(None) => {}
+    }
+
+    Ok(())
+}
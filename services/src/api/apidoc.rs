@@ -3,13 +3,15 @@
 use crate::api::handlers;
 use crate::api::handlers::datasets::VolumeFileLayersResponse;
 use crate::api::handlers::permissions::{
-    PermissionListOptions, PermissionListing, PermissionRequest, Resource,
+    PermissionListOptions, PermissionListing, PermissionRequest, Resource, ShareLinkRequest,
 };
-use crate::api::handlers::plots::WrappedPlotOutput;
+use crate::api::handlers::plots::{PlotImageFormat, WrappedPlotOutput};
+use crate::api::handlers::schedules::ScheduleRun;
 use crate::api::handlers::spatial_references::{AxisOrder, SpatialReferenceSpecification};
 use crate::api::handlers::tasks::{TaskAbortOptions, TaskResponse};
 use crate::api::handlers::upload::{UploadFileLayersResponse, UploadFilesResponse};
 use crate::api::handlers::users::AddRole;
+use crate::api::handlers::users::CreateApiToken;
 use crate::api::handlers::users::{Quota, UpdateQuota, UsageSummaryGranularity};
 use crate::api::handlers::wfs::{CollectionType, GeoJson};
 use crate::api::handlers::workflows::{ProvenanceEntry, RasterStreamWebsocketResultType};
@@ -25,7 +27,7 @@ use crate::api::model::datatypes::{
     TimeInstance, TimeInterval, TimeStep, VectorDataType, VectorQueryRectangle,
 };
 use crate::api::model::operators::{
-    CsvHeader, FileNotFoundHandling, FormatSpecifics, GdalDatasetGeoTransform,
+    AddMlModelMetadata, CsvHeader, FileNotFoundHandling, FormatSpecifics, GdalDatasetGeoTransform,
     GdalDatasetParameters, GdalLoadingInfoTemporalSlice, GdalMetaDataList, GdalMetaDataRegular,
     GdalMetaDataStatic, GdalMetadataMapping, GdalMetadataNetCdfCf, GdalSourceTimePlaceholder,
     MlModelMetadata, MockDatasetDataSourceLoadingInfo, MockMetaData, OgrMetaData,
@@ -45,8 +47,9 @@ use crate::api::model::services::DatabaseConnectionConfig;
 use crate::api::model::services::EdrVectorSpec;
 use crate::api::model::services::LayerProviderListing;
 use crate::api::model::services::{
-    AddDataset, CreateDataset, DataPath, DatasetDefinition, MetaDataDefinition, MetaDataSuggestion,
-    MlModel, Provenance, ProvenanceOutput, Provenances, UpdateDataset, Volume,
+    AddDataset, AddMlModel, CreateDataset, DataPath, DatasetDefinition, MetaDataDefinition,
+    MetaDataSuggestion, MlModel, MlModelTrainingMetrics, Provenance, ProvenanceOutput, Provenances,
+    UpdateDataset, Volume,
 };
 use crate::api::model::services::{
     ArunaDataProviderDefinition, CopernicusDataspaceDataProviderDefinition,
@@ -62,7 +65,11 @@ use crate::contexts::SessionId;
 use crate::datasets::listing::{DatasetListing, OrderBy};
 use crate::datasets::storage::{AutoCreateDataset, Dataset, SuggestMetaData};
 use crate::datasets::upload::{UploadId, VolumeName};
-use crate::datasets::{DatasetName, RasterDatasetFromWorkflow, RasterDatasetFromWorkflowResult};
+use crate::datasets::{
+    AppendRasterDatasetFromWorkflow, AppendRasterDatasetFromWorkflowResult, DatasetName,
+    RasterDatasetFromWorkflow, RasterDatasetFromWorkflowResult, VectorDatasetFormat,
+    VectorDatasetFromWorkflow, VectorDatasetFromWorkflowResult,
+};
 use crate::layers::layer::{
     AddLayer, AddLayerCollection, CollectionItem, Layer, LayerCollection, LayerCollectionListing,
     LayerListing, Property, ProviderLayerCollectionId, ProviderLayerId, UpdateLayer,
@@ -72,18 +79,32 @@ use crate::layers::listing::{
     LayerCollectionId, ProviderCapabilities, SearchCapabilities, SearchType, SearchTypes,
 };
 use crate::machine_learning::MlModelId;
-use crate::permissions::{Permission, Role, RoleDescription, RoleId};
+use crate::machine_learning::training::{
+    GradientBoostingTraining, MlModelTraining, MlModelTrainingResult, MlTrainingTarget,
+    RandomForestTraining, TreeEnsembleAlgorithm,
+};
+use crate::permissions::{
+    CreatedShareLink, Permission, Role, RoleDescription, RoleId, ShareLink, ShareLinkId,
+};
 use crate::projects::{
     ColorParam, CreateProject, DerivedColor, DerivedNumber, LayerUpdate, LayerVisibility,
     LineSymbology, NumberParam, Plot, PlotUpdate, PointSymbology, PolygonSymbology, Project,
     ProjectId, ProjectLayer, ProjectListing, ProjectUpdateToken, ProjectVersion, ProjectVersionId,
     RasterSymbology, STRectangle, StrokeParam, Symbology, TextSymbology, UpdateProject,
 };
-use crate::quota::{ComputationQuota, DataUsage, DataUsageSummary, OperatorQuota};
+use crate::quota::{
+    ComputationQuota, CreateQuotaLimit, DataUsage, DataUsageSummary, DatasetQuotaLimit,
+    ExceededQuotaLimit, OperatorQuota, OperatorQuotaEstimate, ProviderQuotaLimit, QuotaEstimate,
+    QuotaLimit, QuotaLimitData, QuotaLimitId, QuotaLimitUsage, QuotaPeriod,
+};
+use crate::schedules::{
+    CreateSchedule, CronExpression, Schedule, ScheduleId, ScheduledAppendRasterDataset,
+    ScheduledRasterDataset, ScheduledTask, ScheduledVectorDataset, UpdateSchedule,
+};
 use crate::tasks::{TaskFilter, TaskId, TaskListOptions, TaskStatus, TaskStatusWithId};
 use crate::users::{
-    AuthCodeRequestURL, AuthCodeResponse, UserCredentials, UserId, UserInfo, UserRegistration,
-    UserSession,
+    ApiToken, ApiTokenId, ApiTokenResource, AuthCodeRequestURL, AuthCodeResponse, CreatedApiToken,
+    UserCredentials, UserId, UserInfo, UserRegistration, UserSession,
 };
 use crate::util::apidoc::DeriveDiscriminatorMapping;
 use crate::util::{apidoc::OpenApiServerInfo, server::ServerInfo};
@@ -136,10 +157,16 @@ use utoipa::{Modify, OpenApi};
         handlers::layers::update_layer,
         handlers::machine_learning::add_ml_model,
         handlers::machine_learning::get_ml_model,
+        handlers::machine_learning::get_ml_model_version,
+        handlers::machine_learning::list_ml_model_versions,
         handlers::machine_learning::list_ml_models,
+        handlers::machine_learning::train_ml_model,
         handlers::permissions::add_permission_handler,
         handlers::permissions::get_resource_permissions_handler,
         handlers::permissions::remove_permission_handler,
+        handlers::permissions::create_share_link_handler,
+        handlers::permissions::list_share_links_handler,
+        handlers::permissions::remove_share_link_handler,
         handlers::plots::get_plot_handler,
         handlers::projects::create_project_handler,
         handlers::projects::delete_project_handler,
@@ -148,6 +175,12 @@ use utoipa::{Modify, OpenApi};
         handlers::projects::load_project_version_handler,
         handlers::projects::project_versions_handler,
         handlers::projects::update_project_handler,
+        handlers::schedules::create_schedule_handler,
+        handlers::schedules::delete_schedule_handler,
+        handlers::schedules::get_schedule_handler,
+        handlers::schedules::list_schedule_runs_handler,
+        handlers::schedules::list_schedules_handler,
+        handlers::schedules::update_schedule_handler,
         handlers::spatial_references::get_spatial_reference_specification_handler,
         handlers::tasks::abort_handler,
         handlers::tasks::list_handler,
@@ -157,19 +190,27 @@ use utoipa::{Modify, OpenApi};
         handlers::upload::upload_handler,
         handlers::users::add_role_handler,
         handlers::users::anonymous_handler,
+        handlers::users::share_link_session_handler,
         handlers::users::assign_role_handler,
         handlers::users::computation_quota_handler,
         handlers::users::computations_quota_handler,
+        handlers::users::create_api_token_handler,
+        handlers::users::create_quota_limit_handler,
         handlers::users::data_usage_handler,
         handlers::users::data_usage_summary_handler,
+        handlers::users::delete_api_token_handler,
+        handlers::users::delete_quota_limit_handler,
         handlers::users::get_role_by_name_handler,
         handlers::users::get_role_descriptions,
         handlers::users::get_user_quota_handler,
+        handlers::users::list_api_tokens_handler,
+        handlers::users::list_quota_limits_handler,
         handlers::users::login_handler,
         handlers::users::logout_handler,
         handlers::users::oidc_init,
         handlers::users::oidc_login,
         handlers::users::quota_handler,
+        handlers::users::quota_limits_handler,
         handlers::users::register_user_handler,
         handlers::users::remove_role_handler,
         handlers::users::revoke_role_handler,
@@ -184,6 +225,9 @@ use utoipa::{Modify, OpenApi};
         handlers::wms::wms_legend_graphic_handler,
         handlers::wms::wms_map_handler,
         handlers::workflows::dataset_from_workflow_handler,
+        handlers::workflows::estimate_workflow_quota_handler,
+        handlers::workflows::append_dataset_from_workflow_handler,
+        handlers::workflows::vector_dataset_from_workflow_handler,
         handlers::workflows::get_workflow_all_metadata_zip_handler,
         handlers::workflows::get_workflow_metadata_handler,
         handlers::workflows::get_workflow_provenance_handler,
@@ -201,6 +245,8 @@ use utoipa::{Modify, OpenApi};
             IdResponse::<LayerCollectionId>,
             IdResponse::<ProjectId>,
             IdResponse::<RoleId>,
+            IdResponse::<ScheduleId>,
+            IdResponse::<QuotaLimitId>,
             UnauthorizedAdminResponse,
             UnauthorizedUserResponse,
             BadRequestQueryResponse,
@@ -218,11 +264,27 @@ use utoipa::{Modify, OpenApi};
             UpdateQuota,
             ComputationQuota,
             OperatorQuota,
+            QuotaEstimate,
+            ExceededQuotaLimit,
+            OperatorQuotaEstimate,
+            QuotaLimitId,
+            QuotaLimit,
+            QuotaLimitUsage,
+            CreateQuotaLimit,
+            QuotaPeriod,
+            QuotaLimitData,
+            DatasetQuotaLimit,
+            ProviderQuotaLimit,
             DataUsage,
             DataUsageSummary,
             UsageSummaryGranularity,
             AuthCodeResponse,
             AuthCodeRequestURL,
+            ApiTokenId,
+            ApiToken,
+            ApiTokenResource,
+            CreateApiToken,
+            CreatedApiToken,
 
             DataId,
             DataProviderId,
@@ -234,6 +296,7 @@ use utoipa::{Modify, OpenApi};
             LayerId,
             ProjectId,
             RoleId,
+            ScheduleId,
             SessionId,
             TaskId,
             UploadId,
@@ -281,6 +344,11 @@ use utoipa::{Modify, OpenApi};
             VectorColumnInfo,
             RasterDatasetFromWorkflow,
             RasterDatasetFromWorkflowResult,
+            AppendRasterDatasetFromWorkflow,
+            AppendRasterDatasetFromWorkflowResult,
+            VectorDatasetFromWorkflow,
+            VectorDatasetFromWorkflowResult,
+            VectorDatasetFormat,
             RasterQueryRectangle,
             VectorQueryRectangle,
             PlotQueryRectangle,
@@ -293,6 +361,16 @@ use utoipa::{Modify, OpenApi};
             TaskStatusWithId,
             TaskResponse,
 
+            Schedule,
+            CreateSchedule,
+            UpdateSchedule,
+            ScheduledTask,
+            ScheduledAppendRasterDataset,
+            ScheduledRasterDataset,
+            ScheduledVectorDataset,
+            CronExpression,
+            ScheduleRun,
+
             Layer,
             LayerListing,
             LayerCollection,
@@ -413,6 +491,7 @@ use utoipa::{Modify, OpenApi};
 
             PlotOutputFormat,
             WrappedPlotOutput,
+            PlotImageFormat,
 
             CreateProject,
             Project,
@@ -454,16 +533,29 @@ use utoipa::{Modify, OpenApi};
             Permission,
             PermissionListing,
             PermissionListOptions,
+            ShareLinkRequest,
+            ShareLink,
+            ShareLinkId,
+            CreatedShareLink,
             AddRole,
             RoleDescription,
             Role,
 
             MlModel,
+            AddMlModel,
+            AddMlModelMetadata,
             MlModelId,
             MlModelName,
             MlModelMetadata,
             MlModelNameResponse,
+            MlModelTrainingMetrics,
+            MlModelTraining,
+            MlModelTrainingResult,
+            MlTrainingTarget,
             MlTensorShape3D,
+            TreeEnsembleAlgorithm,
+            RandomForestTraining,
+            GradientBoostingTraining,
         ),
     ),
     modifiers(&SecurityAddon, &ApiDocInfo, &OpenApiServerInfo, &DeriveDiscriminatorMapping),
@@ -274,7 +274,7 @@ impl<C: SessionContext> Task<C::TaskContext> for EbvMultiOverviewTask<C> {
             let subtask_id = self
                 .ctx
                 .tasks()
-                .schedule_task(subtask, Some(notification_tx))
+                .schedule_subtask(subtask, notification_tx)
                 .await
                 .map_err(ErrorSource::boxed)?;
 
@@ -305,7 +305,7 @@ impl<C: SessionContext> Task<C::TaskContext> for EbvMultiOverviewTask<C> {
 
                         status.error.push(file);
                     }
-                    TaskStatus::Running(_) => {
+                    TaskStatus::Pending { .. } | TaskStatus::Running(_) => {
                         // must not happen, since we used the callback
                         debug!(
                             "Ran into task status that must not happend: running/aborted after finish"
@@ -1,20 +1,28 @@
+use std::{str::FromStr, sync::Arc};
+
 use actix_web::{FromRequest, HttpResponse, ResponseError, web};
-use geoengine_datatypes::machine_learning::MlModelName;
-use geoengine_operators::{
-    engine::ExecutionContext,
-    machine_learning::onnx_util::{
-        check_model_shape, check_onnx_model_matches_metadata, load_onnx_model_from_loading_info,
-    },
+use geoengine_datatypes::machine_learning::{MlModelName, MlModelVersion};
+use geoengine_operators::machine_learning::onnx_util::{
+    check_model_shape, check_onnx_model_matches_metadata, load_onnx_model_from_path,
+    onnx_model_tensors,
 };
+use snafu::ResultExt;
 
 use crate::{
+    api::handlers::tasks::TaskResponse,
     api::model::{
         datatypes::MlModelName as ApiMlModelName,
         responses::{ErrorResponse, ml_models::MlModelNameResponse},
-        services::MlModel,
+        services::{AddMlModel, MlModel},
     },
     contexts::{ApplicationContext, SessionContext},
-    machine_learning::{MlModelDb, MlModelListOptions, error::MachineLearningError},
+    machine_learning::{
+        MlModelDb, MlModelListOptions,
+        error::{MachineLearningError, error::InvalidModelVersionMachineLearningError},
+        ml_model_path,
+        training::{MlModelTraining, schedule_ml_model_training_task},
+    },
+    workflows::registry::WorkflowRegistry,
 };
 
 pub(crate) fn init_ml_routes<C>(cfg: &mut web::ServiceConfig)
@@ -30,7 +38,16 @@ where
                         .route(web::post().to(add_ml_model::<C>))
                         .route(web::get().to(list_ml_models::<C>)),
                 )
-                .service(web::resource("/{model_name}").route(web::get().to(get_ml_model::<C>))),
+                .service(web::resource("/train").route(web::post().to(train_ml_model::<C>)))
+                .service(web::resource("/{model_name}").route(web::get().to(get_ml_model::<C>)))
+                .service(
+                    web::resource("/{model_name}/versions")
+                        .route(web::get().to(list_ml_model_versions::<C>)),
+                )
+                .service(
+                    web::resource("/{model_name}/versions/{version}")
+                        .route(web::get().to(get_ml_model_version::<C>)),
+                ),
         ),
     );
 }
@@ -51,11 +68,17 @@ impl ResponseError for MachineLearningError {
 }
 
 /// Create a new ml model.
+///
+/// The model file is validated against the metadata, i.e., the input and output tensors of the ONNX graph
+/// must match the given types and shapes. Types and shapes that are omitted are inferred from the ONNX file.
+///
+/// If a model with the same name already exists, the model is added as a new version of it.
+/// This requires ownership of the existing model.
 #[utoipa::path(
     tag = "ML",
     post,
     path = "/ml/models",
-    request_body = MlModel,
+    request_body = AddMlModel,
     responses(
         (status = 200, body = MlModelNameResponse)
     ),
@@ -67,31 +90,28 @@ impl ResponseError for MachineLearningError {
 pub(crate) async fn add_ml_model<C: ApplicationContext>(
     session: C::Session,
     app_ctx: web::Data<C>,
-    model: web::Json<MlModel>,
+    model: web::Json<AddMlModel>,
 ) -> Result<web::Json<MlModelNameResponse>, MachineLearningError> {
     let session_context = app_ctx.session_context(session);
-    let exe_context = session_context
-        .execution_context()
-        .expect("Execution Context must exist");
 
-    // convert the payload from json to apy and then to backend type
-    let model: crate::machine_learning::MlModel = model.into_inner().into();
+    let model = model.into_inner();
 
     // This call also checks that the file is available!
-    let ml_model_metadata = model.loading_info()?;
-    // Check that the in/out shapes are ok
-    check_model_shape(
-        &ml_model_metadata.metadata,
-        exe_context.tiling_specification().tile_size_in_pixels,
-    )?;
+    let model_path = ml_model_path(model.upload, &model.file_name)?;
     // initialize model
-    let session = load_onnx_model_from_loading_info(&ml_model_metadata)?;
+    let session = load_onnx_model_from_path(&model_path)?;
+    // infer missing types and shapes from the model
+    let model = model.into_ml_model(onnx_model_tensors(&session)?);
+    // Check that the in/out shapes are ok
+    check_model_shape(&model.metadata)?;
     // Check that the model is initializable and that the types are vaild
-    check_onnx_model_matches_metadata(&session, &ml_model_metadata.metadata)?;
+    check_onnx_model_matches_metadata(&session, &model.metadata)?;
 
     let id_and_name = session_context.db().add_model(model).await?;
-    let api_name: crate::api::model::datatypes::MlModelName = id_and_name.name.into();
-    Ok(web::Json(api_name.into()))
+    Ok(web::Json(MlModelNameResponse::new(
+        id_and_name.name.into(),
+        id_and_name.version,
+    )))
 }
 
 /// List ml models.
@@ -122,7 +142,7 @@ pub(crate) async fn list_ml_models<C: ApplicationContext>(
     Ok(web::Json(models_api))
 }
 
-/// Get ml model by name.
+/// Get the latest version of an ml model by name.
 #[utoipa::path(
     tag = "ML",
     get,
@@ -149,12 +169,117 @@ pub(crate) async fn get_ml_model<C: ApplicationContext>(
     let models = app_ctx
         .session_context(session)
         .db()
-        .load_model(&model_name)
+        .load_model(&model_name, MlModelVersion::Latest)
         .await?;
     let models_api = models.into();
     Ok(web::Json(models_api))
 }
 
+/// List all versions of an ml model, starting with the most recent one.
+#[utoipa::path(
+    tag = "ML",
+    get,
+    path = "/ml/models/{model_name}/versions",
+    responses(
+        (status = 200, body = [MlModel])
+    ),
+    params(
+        ("model_name" = ApiMlModelName, description = "Ml Model Name")
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+
+pub(crate) async fn list_ml_model_versions<C: ApplicationContext>(
+    session: C::Session,
+    app_ctx: web::Data<C>,
+    model_name: web::Path<ApiMlModelName>,
+) -> Result<web::Json<Vec<MlModel>>, MachineLearningError> {
+    let model_name: MlModelName = model_name.into_inner().into();
+
+    let models = app_ctx
+        .session_context(session)
+        .db()
+        .list_model_versions(&model_name)
+        .await?;
+    let models_api = models.into_iter().map(Into::into).collect::<Vec<_>>();
+    Ok(web::Json(models_api))
+}
+
+/// Get a specific version of an ml model by name.
+#[utoipa::path(
+    tag = "ML",
+    get,
+    path = "/ml/models/{model_name}/versions/{version}",
+    responses(
+        (status = 200, body = MlModel)
+    ),
+    params(
+        ("model_name" = ApiMlModelName, description = "Ml Model Name"),
+        ("version" = String, description = "Version number or `latest`", example = "1")
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+
+pub(crate) async fn get_ml_model_version<C: ApplicationContext>(
+    session: C::Session,
+    app_ctx: web::Data<C>,
+    path: web::Path<(ApiMlModelName, String)>,
+) -> Result<web::Json<MlModel>, MachineLearningError> {
+    let (model_name, version) = path.into_inner();
+    let model_name: MlModelName = model_name.into();
+    let version =
+        MlModelVersion::from_str(&version).context(InvalidModelVersionMachineLearningError)?;
+
+    let model = app_ctx
+        .session_context(session)
+        .db()
+        .load_model(&model_name, version)
+        .await?;
+    Ok(web::Json(model.into()))
+}
+
+/// Train a tree ensemble model on raster features sampled at labelled vector features.
+///
+/// The trained model is stored as a new ml model that can be applied with the `Onnx` operator.
+/// The training metrics are computed on a held-back share of the samples.
+/// The number of trees, their depth and the number of samples are limited by the server configuration.
+#[utoipa::path(
+    tag = "ML",
+    post,
+    path = "/ml/models/train",
+    request_body = MlModelTraining,
+    responses(
+        (status = 200, description = "Id of created task", body = TaskResponse,
+            example = json!({
+                "taskId": "7f8a4cfe-76ab-4972-b347-b197e5ef0f3c"
+            })
+        )
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+pub(crate) async fn train_ml_model<C: ApplicationContext>(
+    session: C::Session,
+    app_ctx: web::Data<C>,
+    info: web::Json<MlModelTraining>,
+) -> crate::error::Result<web::Json<TaskResponse>> {
+    let ctx = Arc::new(app_ctx.session_context(session));
+    let info = info.into_inner();
+
+    let samples_workflow = ctx.db().load_workflow(&info.samples).await?;
+    let features_workflow = ctx.db().load_workflow(&info.features).await?;
+
+    let task_id =
+        schedule_ml_model_training_task(samples_workflow, features_workflow, ctx, info).await?;
+
+    Ok(web::Json(TaskResponse::new(task_id)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -213,19 +338,39 @@ mod tests {
                 output_shape: MlTensorShape3D::new_y_x_bands(1, 1, 1),
                 input_no_data_handling: MlModelInputNoDataHandling::SkipIfNoData,
                 output_no_data_handling: MlModelOutputNoDataHandling::NanIsNoData,
+                output_bands: vec![],
+                input_time_steps: 1,
             },
+            training_metrics: None,
+            version: 1,
         };
 
         let api_model: MlModel = model.clone().into();
+
+        // the tensor types and shapes are inferred from the model file
         let req = test::TestRequest::post()
             .uri("/ml/models")
             .append_header((header::AUTHORIZATION, Bearer::new(session_id.to_string())))
-            .set_json(&api_model);
+            .set_json(serde_json::json!({
+                "name": api_model.name,
+                "displayName": api_model.display_name,
+                "description": api_model.description,
+                "upload": api_model.upload,
+                "fileName": api_model.file_name,
+                "metadata": {
+                    "inputNoDataHandling": { "variant": "skipIfNoData" },
+                    "outputNoDataHandling": { "variant": "nanIsNoData" }
+                }
+            }));
 
         let res = send_test_request(req, app_ctx.clone()).await;
 
         assert_eq!(res.status(), 200);
 
+        let name_response: MlModelNameResponse = test::read_body_json(res).await;
+        assert_eq!(name_response.ml_model_name, api_model.name);
+        assert_eq!(name_response.version, 1);
+
         let req = test::TestRequest::get()
             .uri("/ml/models?offset=0&limit=10")
             .append_header((header::AUTHORIZATION, Bearer::new(session_id.to_string())));
@@ -244,12 +389,128 @@ mod tests {
             .uri(&format!("/ml/models/{}", api_model.name))
             .append_header((header::AUTHORIZATION, Bearer::new(session_id.to_string())));
 
-        let res = send_test_request(req, app_ctx).await;
+        let res = send_test_request(req, app_ctx.clone()).await;
 
         assert_eq!(res.status(), 200);
 
         let res_model: MlModel = test::read_body_json(res).await;
 
         assert_eq!(model, res_model.into());
+
+        // adding the model again creates a new version
+        let add_model: AddMlModel = api_model.clone().into();
+        let req = test::TestRequest::post()
+            .uri("/ml/models")
+            .append_header((header::AUTHORIZATION, Bearer::new(session_id.to_string())))
+            .set_json(&add_model);
+
+        let res = send_test_request(req, app_ctx.clone()).await;
+
+        assert_eq!(res.status(), 200);
+
+        let name_response: MlModelNameResponse = test::read_body_json(res).await;
+        assert_eq!(name_response.version, 2);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/ml/models/{}/versions", api_model.name))
+            .append_header((header::AUTHORIZATION, Bearer::new(session_id.to_string())));
+
+        let res = send_test_request(req, app_ctx.clone()).await;
+
+        assert_eq!(res.status(), 200);
+
+        let versions: Vec<MlModel> = test::read_body_json(res).await;
+        assert_eq!(
+            versions.iter().map(|m| m.version).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/ml/models/{}/versions/1", api_model.name))
+            .append_header((header::AUTHORIZATION, Bearer::new(session_id.to_string())));
+
+        let res = send_test_request(req, app_ctx.clone()).await;
+
+        assert_eq!(res.status(), 200);
+
+        let res_model: MlModel = test::read_body_json(res).await;
+        assert_eq!(model, res_model.into());
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/ml/models/{}/versions/latest", api_model.name))
+            .append_header((header::AUTHORIZATION, Bearer::new(session_id.to_string())));
+
+        let res = send_test_request(req, app_ctx.clone()).await;
+
+        assert_eq!(res.status(), 200);
+
+        let res_model: MlModel = test::read_body_json(res).await;
+        assert_eq!(res_model.version, 2);
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/ml/models/{}/versions/newest", api_model.name))
+            .append_header((header::AUTHORIZATION, Bearer::new(session_id.to_string())));
+
+        let res = send_test_request(req, app_ctx).await;
+
+        assert_eq!(res.status(), 400);
+    }
+
+    #[ge_context::test]
+    async fn it_rejects_ml_models_not_matching_the_model_file(app_ctx: PostgresContext<NoTls>) {
+        let mut test_data = TestDataUploads::default(); // remember created folder and remove them on drop
+
+        let session = app_ctx.create_anonymous_session().await.unwrap();
+        let session_id = session.id();
+
+        let body = vec![(
+            "model.onnx",
+            include_bytes!("../../../../test_data/ml/onnx/test_classification.onnx"),
+        )];
+
+        let req = test::TestRequest::post()
+            .uri("/upload")
+            .append_header((header::AUTHORIZATION, Bearer::new(session_id.to_string())))
+            .set_multipart(body);
+
+        let res = send_test_request(req, app_ctx.clone()).await;
+
+        assert_eq!(res.status(), 200);
+
+        let upload: IdResponse<UploadId> = test::read_body_json(res).await;
+        test_data.uploads.push(upload.id);
+
+        let req = test::TestRequest::post()
+            .uri("/ml/models")
+            .append_header((header::AUTHORIZATION, Bearer::new(session_id.to_string())))
+            .set_json(serde_json::json!({
+                "name": format!("{}:test_classification", session.user.id),
+                "displayName": "Test Classification",
+                "description": "Test Classification Model",
+                "upload": upload.id,
+                "fileName": "model.onnx",
+                "metadata": {
+                    "inputShape": {
+                        "y": 1,
+                        "x": 1,
+                        "bands": 3
+                    },
+                    "inputNoDataHandling": { "variant": "skipIfNoData" },
+                    "outputNoDataHandling": { "variant": "nanIsNoData" }
+                }
+            }));
+
+        let res = send_test_request(req, app_ctx.clone()).await;
+
+        assert_eq!(res.status(), 400);
+
+        let req = test::TestRequest::get()
+            .uri("/ml/models?offset=0&limit=10")
+            .append_header((header::AUTHORIZATION, Bearer::new(session_id.to_string())));
+
+        let res = send_test_request(req, app_ctx).await;
+
+        let models: Vec<MlModel> = test::read_body_json(res).await;
+        assert!(models.is_empty());
     }
 }
@@ -1,5 +1,6 @@
 use crate::contexts::SessionId;
 use crate::error::{Error, Result};
+use crate::users::is_api_token;
 use actix_web::HttpRequest;
 use actix_web::http::header;
 use actix_web_httpauth::headers::authorization::{Bearer, Scheme};
@@ -12,6 +13,7 @@ pub mod machine_learning;
 pub mod permissions;
 pub mod plots;
 pub mod projects;
+pub mod schedules;
 pub mod spatial_references;
 pub mod tasks;
 pub mod upload;
@@ -21,7 +23,13 @@ pub mod wfs;
 pub mod wms;
 pub mod workflows;
 
-pub fn get_token(req: &HttpRequest) -> Result<SessionId> {
+/// The Bearer token of a request
+pub enum AuthToken {
+    Session(SessionId),
+    ApiToken(String),
+}
+
+pub fn get_token(req: &HttpRequest) -> Result<AuthToken> {
     let header = req
         .headers()
         .get(header::AUTHORIZATION)
@@ -31,7 +39,14 @@ pub fn get_token(req: &HttpRequest) -> Result<SessionId> {
     let scheme = Bearer::parse(header).map_err(|_| Error::Unauthorized {
         source: Box::new(Error::InvalidAuthorizationScheme),
     })?;
-    SessionId::from_str(scheme.token()).map_err(|_err| Error::Unauthorized {
-        source: Box::new(Error::InvalidUuid),
-    })
+
+    if is_api_token(scheme.token()) {
+        return Ok(AuthToken::ApiToken(scheme.token().to_string()));
+    }
+
+    SessionId::from_str(scheme.token())
+        .map(AuthToken::Session)
+        .map_err(|_err| Error::Unauthorized {
+            source: Box::new(Error::InvalidUuid),
+        })
 }
@@ -2,16 +2,21 @@ use crate::api::model::datatypes::{DataProviderId, LayerId};
 use crate::contexts::{ApplicationContext, GeoEngineDb, SessionContext};
 use crate::datasets::DatasetName;
 use crate::datasets::storage::DatasetDb;
+use crate::datasets::upload::UploadId;
 use crate::error::{self, Error, Result};
 use crate::layers::listing::LayerCollectionId;
 use crate::machine_learning::MlModelDb;
 use crate::permissions::{
-    Permission, PermissionDb, PermissionListing as DbPermissionListing, ResourceId, Role, RoleId,
+    CreatedShareLink, Permission, PermissionDb, PermissionListing as DbPermissionListing,
+    ResourceId, Role, RoleId, ShareLink, ShareLinkId,
 };
 use crate::projects::ProjectId;
+use crate::tasks::TaskId;
+use crate::workflows::workflow::WorkflowId;
 use actix_web::{FromRequest, HttpResponse, web};
 use geoengine_datatypes::error::BoxedResultExt;
 use geoengine_datatypes::machine_learning::MlModelName;
+use geoengine_datatypes::primitives::DateTime;
 use geoengine_macros::type_tag;
 use serde::{Deserialize, Serialize};
 use snafu::ResultExt;
@@ -34,6 +39,15 @@ where
             .service(
                 web::resource("/resources/{resource_type}/{resource_id}")
                     .route(web::get().to(get_resource_permissions_handler::<C>)),
+            )
+            .service(web::resource("/links").route(web::post().to(create_share_link_handler::<C>)))
+            .service(
+                web::resource("/links/resources/{resource_type}/{resource_id}")
+                    .route(web::get().to(list_share_links_handler::<C>)),
+            )
+            .service(
+                web::resource("/links/{link}")
+                    .route(web::delete().to(remove_share_link_handler::<C>)),
             ),
     );
 }
@@ -45,6 +59,9 @@ pub struct PermissionRequest {
     resource: Resource,
     role_id: RoleId,
     permission: Permission,
+    /// The permission is revoked automatically at this time. It never expires if it is empty.
+    #[serde(default)]
+    expires: Option<DateTime>,
 }
 
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, ToSchema)]
@@ -53,6 +70,16 @@ pub struct PermissionListing {
     resource: Resource,
     role: Role,
     permission: Permission,
+    expires: Option<DateTime>,
+}
+
+/// Request for creating a link that gives anonymous read access to the given resource
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareLinkRequest {
+    resource: Resource,
+    #[serde(default)]
+    expires: Option<DateTime>,
 }
 
 impl PermissionListing {
@@ -64,6 +91,7 @@ impl PermissionListing {
             resource,
             role: db_permission_listing.role,
             permission: db_permission_listing.permission,
+            expires: db_permission_listing.expires,
         }
     }
 }
@@ -79,6 +107,9 @@ pub enum Resource {
     Dataset(DatasetResource),
     MlModel(MlModelResource),
     Provider(DataProviderResource),
+    Workflow(WorkflowResource),
+    Upload(UploadResource),
+    Task(TaskResource),
 }
 
 #[type_tag(value = "layer")]
@@ -126,6 +157,27 @@ pub struct MlModelResource {
     // TODO: check model
 }
 
+#[type_tag(value = "workflow")]
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, ToSchema, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowResource {
+    pub id: WorkflowId,
+}
+
+#[type_tag(value = "upload")]
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, ToSchema, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadResource {
+    pub id: UploadId,
+}
+
+#[type_tag(value = "task")]
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, ToSchema, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskResource {
+    pub id: TaskId,
+}
+
 impl Resource {
     pub async fn resolve_resource_id<D: DatasetDb + MlModelDb>(
         &self,
@@ -138,6 +190,9 @@ impl Resource {
             }
             Resource::Project(project_id) => Ok(ResourceId::Project(project_id.id)),
             Resource::Provider(provider_id) => Ok(ResourceId::DataProvider(provider_id.id.into())),
+            Resource::Workflow(workflow) => Ok(ResourceId::Workflow(workflow.id)),
+            Resource::Upload(upload) => Ok(ResourceId::Upload(upload.id)),
+            Resource::Task(task) => Ok(ResourceId::Task(task.id)),
             Resource::Dataset(dataset_name) => {
                 let dataset_id_option = db.resolve_dataset_name_to_id(&dataset_name.id).await?;
                 dataset_id_option
@@ -196,6 +251,18 @@ impl TryFrom<(String, String)> for Resource {
                 r#type: Default::default(),
                 id: DataProviderId(Uuid::from_str(&value.1).context(error::Uuid)?),
             }),
+            "workflow" => Resource::Workflow(WorkflowResource {
+                r#type: Default::default(),
+                id: WorkflowId(Uuid::from_str(&value.1).context(error::Uuid)?),
+            }),
+            "upload" => Resource::Upload(UploadResource {
+                r#type: Default::default(),
+                id: UploadId(Uuid::from_str(&value.1).context(error::Uuid)?),
+            }),
+            "task" => Resource::Task(TaskResource {
+                r#type: Default::default(),
+                id: TaskId(Uuid::from_str(&value.1).context(error::Uuid)?),
+            }),
             _ => {
                 return Err(Error::InvalidResourceId {
                     resource_type: value.0,
@@ -257,6 +324,8 @@ where
 }
 
 /// Adds a new permission.
+///
+/// The permission expires at the given time if `expires` is set.
 #[utoipa::path(
     tag = "Permissions",
     put,
@@ -288,9 +357,19 @@ async fn add_permission_handler<C: ApplicationContext>(
     let db = app_ctx.session_context(session).db();
     let permission_id = permission.resource.resolve_resource_id(&db).await?;
 
-    db.add_permission::<ResourceId>(permission.role_id, permission_id, permission.permission)
+    if let Some(expires) = permission.expires {
+        db.add_expiring_permission::<ResourceId>(
+            permission.role_id,
+            permission_id,
+            permission.permission,
+            expires,
+        )
         .await
-        .boxed_context(crate::error::PermissionDb)?;
+    } else {
+        db.add_permission::<ResourceId>(permission.role_id, permission_id, permission.permission)
+            .await
+    }
+    .boxed_context(crate::error::PermissionDb)?;
 
     Ok(HttpResponse::Ok().finish())
 }
@@ -334,17 +413,130 @@ async fn remove_permission_handler<C: ApplicationContext>(
     Ok(HttpResponse::Ok().finish())
 }
 
+/// Creates a link that gives anonymous read access to a resource.
+///
+/// The token of the link is only returned once. It can be exchanged for a session until the link
+/// expires or is removed.
+#[utoipa::path(
+    tag = "Permissions",
+    post,
+    path = "/permissions/links",
+    request_body(content = ShareLinkRequest, example =
+        json!({
+            "resource": {
+                "type": "layerCollection",
+                "id": "00000000-0000-0000-0000-000000000000",
+            },
+            "expires": "2026-12-31T00:00:00.000Z"
+        })
+    ),
+    responses(
+        (status = 200, description = "The created share link", body = CreatedShareLink),
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+async fn create_share_link_handler<C: ApplicationContext>(
+    session: C::Session,
+    app_ctx: web::Data<C>,
+    request: web::Json<ShareLinkRequest>,
+) -> Result<web::Json<CreatedShareLink>> {
+    let request = request.into_inner();
+
+    let db = app_ctx.session_context(session).db();
+    let resource_id = request.resource.resolve_resource_id(&db).await?;
+
+    let link = db
+        .create_share_link(resource_id, request.expires)
+        .await
+        .boxed_context(crate::error::PermissionDb)?;
+
+    Ok(web::Json(link))
+}
+
+/// Lists the share links of a resource.
+#[utoipa::path(
+    tag = "Permissions",
+    get,
+    path = "/permissions/links/resources/{resource_type}/{resource_id}",
+    responses(
+        (status = 200, description = "List of share links", body = Vec<ShareLink>),
+    ),
+    params(
+        ("resource_type" = String, description = "Resource Type"),
+        ("resource_id" = String, description = "Resource Id"),
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+async fn list_share_links_handler<C: ApplicationContext>(
+    session: C::Session,
+    app_ctx: web::Data<C>,
+    resource_id: web::Path<(String, String)>,
+) -> Result<web::Json<Vec<ShareLink>>>
+where
+    <<C as ApplicationContext>::SessionContext as SessionContext>::GeoEngineDB: GeoEngineDb,
+{
+    let resource = Resource::try_from(resource_id.into_inner())?;
+    let db = app_ctx.session_context(session).db();
+    let resource_id = resource.resolve_resource_id(&db).await?;
+
+    let links = db
+        .list_share_links(resource_id)
+        .await
+        .boxed_context(crate::error::PermissionDb)?;
+
+    Ok(web::Json(links))
+}
+
+/// Removes a share link. Sessions that were created with it lose their access.
+#[utoipa::path(
+    tag = "Permissions",
+    delete,
+    path = "/permissions/links/{link}",
+    responses(
+        (status = 200, description = "OK"),
+    ),
+    params(
+        ("link" = ShareLinkId, description = "Share link id")
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+async fn remove_share_link_handler<C: ApplicationContext>(
+    session: C::Session,
+    app_ctx: web::Data<C>,
+    link: web::Path<ShareLinkId>,
+) -> Result<HttpResponse> {
+    app_ctx
+        .session_context(session)
+        .db()
+        .remove_share_link(link.into_inner())
+        .await
+        .boxed_context(crate::error::PermissionDb)?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
     use crate::{
+        api::model::responses::ErrorResponse,
         contexts::PostgresContext,
         datasets::upload::{Upload, UploadDb, UploadId},
         ge_context,
-        layers::{layer::AddLayer, listing::LayerCollectionProvider, storage::LayerDb},
+        layers::{
+            layer::{AddLayer, AddLayerCollection, UpdateLayerCollection},
+            listing::LayerCollectionProvider,
+            storage::LayerDb,
+        },
         machine_learning::{MlModel, MlModelIdAndName},
-        users::{UserAuth, UserCredentials, UserRegistration},
+        users::{UserAuth, UserCredentials, UserRegistration, UserSession},
         util::tests::{
             add_ndvi_to_datasets2, add_ports_to_datasets, admin_login, read_body_string,
             send_test_request,
@@ -492,6 +684,7 @@ mod tests {
 
         let expected_result = json!([{
                "permission":"Owner",
+               "expires": null,
                "resource":  {
                    "id": dataset_name.to_string(),
                    "type": "dataset"
@@ -503,6 +696,7 @@ mod tests {
                }
            }, {
                "permission": "Read",
+               "expires": null,
                "resource": {
                    "id": dataset_name.to_string(),
                    "type": "dataset"
@@ -513,6 +707,7 @@ mod tests {
                }
            }, {
                "permission": "Read",
+               "expires": null,
                "resource": {
                    "id": dataset_name.to_string(),
                    "type": "dataset",
@@ -552,14 +747,19 @@ mod tests {
                 output_type: RasterDataType::F64,
                 input_no_data_handling: MlModelInputNoDataHandling::SkipIfNoData,
                 output_no_data_handling: MlModelOutputNoDataHandling::NanIsNoData,
+                output_bands: vec![],
+                input_time_steps: 1,
             },
             name: MlModelName::new_unchecked(None, "myUnrealModel"),
             upload: upload_id,
+            training_metrics: None,
+            version: 1,
         };
 
         let MlModelIdAndName {
             id: _model_id,
             name: model_name,
+            ..
         } = db.add_model(model).await.unwrap();
 
         let req = actix_web::test::TestRequest::get()
@@ -581,6 +781,7 @@ mod tests {
             res_body,
             json!([{
                    "permission":"Owner",
+                   "expires": null,
                    "resource":  {
                        "id": model_name.to_string(),
                        "type": "mlModel"
@@ -622,6 +823,7 @@ mod tests {
             res_body,
             json!([{
                    "permission":"Owner",
+                   "expires": null,
                    "resource":  {
                        "id": root_collection.to_string(),
                        "type": "layerCollection"
@@ -633,6 +835,7 @@ mod tests {
                    }
                }, {
                    "permission": "Read",
+                   "expires": null,
                    "resource": {
                        "id": root_collection.to_string(),
                        "type": "layerCollection"
@@ -643,6 +846,7 @@ mod tests {
                    }
                }, {
                    "permission": "Read",
+                   "expires": null,
                    "resource": {
                        "id": root_collection.to_string(),
                        "type": "layerCollection",
@@ -704,6 +908,7 @@ mod tests {
             res_body,
             json!([{
                    "permission":"Owner",
+                   "expires": null,
                    "resource":  {
                        "id": l_id.to_string(),
                        "type": "layer"
@@ -718,6 +923,237 @@ mod tests {
         );
     }
 
+    #[ge_context::test]
+    async fn it_lets_writers_edit_layer_collections(app_ctx: PostgresContext<NoTls>) {
+        let admin_session = admin_login(&app_ctx).await;
+        let admin_db = app_ctx.session_context(admin_session.clone()).db();
+
+        let root_collection = admin_db.get_root_layer_collection_id().await.unwrap();
+        let collection = admin_db
+            .add_layer_collection(
+                AddLayerCollection {
+                    name: "collection".to_string(),
+                    description: "description".to_string(),
+                    properties: Default::default(),
+                },
+                &root_collection,
+            )
+            .await
+            .unwrap();
+
+        let user_id = app_ctx
+            .register_user(UserRegistration {
+                email: "collaborator@localhost".to_string(),
+                real_name: "Collaborator".to_string(),
+                password: "test".to_string(),
+            })
+            .await
+            .unwrap();
+        let user_session = app_ctx
+            .login(UserCredentials {
+                email: "collaborator@localhost".to_string(),
+                password: "test".to_string(),
+            })
+            .await
+            .unwrap();
+        let user_db = app_ctx.session_context(user_session.clone()).db();
+
+        let req = actix_web::test::TestRequest::put()
+            .uri("/permissions")
+            .append_header((
+                header::AUTHORIZATION,
+                Bearer::new(admin_session.id.to_string()),
+            ))
+            .set_json(json!({
+                "resource": {
+                    "type": "layerCollection",
+                    "id": collection.to_string(),
+                },
+                "roleId": user_id.to_string(),
+                "permission": "Write"
+            }));
+        let res = send_test_request(req, app_ctx.clone()).await;
+        assert_eq!(res.status(), 200);
+
+        user_db
+            .update_layer_collection(
+                &collection,
+                UpdateLayerCollection {
+                    name: "renamed collection".to_string(),
+                    description: "description".to_string(),
+                    properties: Default::default(),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            admin_db
+                .load_layer_collection(&collection, Default::default())
+                .await
+                .unwrap()
+                .name,
+            "renamed collection"
+        );
+
+        // writers can neither share nor delete the collection
+        assert!(
+            user_db
+                .add_permission(
+                    Role::anonymous_role_id(),
+                    collection.clone(),
+                    Permission::Read
+                )
+                .await
+                .is_err()
+        );
+        assert!(user_db.remove_layer_collection(&collection).await.is_err());
+
+        // the permission is gone once it has expired
+        admin_db
+            .add_expiring_permission(
+                user_id.into(),
+                collection.clone(),
+                Permission::Write,
+                DateTime::new_utc(2000, 1, 1, 0, 0, 0),
+            )
+            .await
+            .unwrap();
+        assert!(
+            !user_db
+                .has_permission(collection.clone(), Permission::Write)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[ge_context::test]
+    #[allow(clippy::too_many_lines)]
+    async fn it_shares_resources_with_links(app_ctx: PostgresContext<NoTls>) {
+        let admin_session = admin_login(&app_ctx).await;
+        let admin_db = app_ctx.session_context(admin_session.clone()).db();
+
+        let root_collection = admin_db.get_root_layer_collection_id().await.unwrap();
+        let collection = admin_db
+            .add_layer_collection(
+                AddLayerCollection {
+                    name: "collection".to_string(),
+                    description: "description".to_string(),
+                    properties: Default::default(),
+                },
+                &root_collection,
+            )
+            .await
+            .unwrap();
+
+        let anonymous_session = app_ctx.create_anonymous_session().await.unwrap();
+        assert!(
+            !app_ctx
+                .session_context(anonymous_session)
+                .db()
+                .has_permission(collection.clone(), Permission::Read)
+                .await
+                .unwrap()
+        );
+
+        let req = actix_web::test::TestRequest::post()
+            .uri("/permissions/links")
+            .append_header((
+                header::AUTHORIZATION,
+                Bearer::new(admin_session.id.to_string()),
+            ))
+            .set_json(json!({
+                "resource": {
+                    "type": "layerCollection",
+                    "id": collection.to_string(),
+                }
+            }));
+        let res = send_test_request(req, app_ctx.clone()).await;
+        assert_eq!(res.status(), 200);
+        let link: CreatedShareLink = actix_web::test::read_body_json(res).await;
+
+        let req = actix_web::test::TestRequest::post()
+            .uri(&format!("/shareLinks/{}/session", link.token))
+            .append_header((header::CONTENT_LENGTH, 0));
+        let res = send_test_request(req, app_ctx.clone()).await;
+        assert_eq!(res.status(), 200);
+        let link_session: UserSession = actix_web::test::read_body_json(res).await;
+
+        let link_db = app_ctx.session_context(link_session.clone()).db();
+        assert!(
+            link_db
+                .has_permission(collection.clone(), Permission::Read)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !link_db
+                .has_permission(collection.clone(), Permission::Write)
+                .await
+                .unwrap()
+        );
+
+        let req = actix_web::test::TestRequest::get()
+            .uri(&format!(
+                "/permissions/links/resources/layerCollection/{collection}"
+            ))
+            .append_header((
+                header::AUTHORIZATION,
+                Bearer::new(admin_session.id.to_string()),
+            ));
+        let res = send_test_request(req, app_ctx.clone()).await;
+        assert_eq!(res.status(), 200);
+        let links: Vec<ShareLink> = actix_web::test::read_body_json(res).await;
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].id, link.id);
+        assert_eq!(links[0].expires, None);
+
+        let req = actix_web::test::TestRequest::delete()
+            .uri(&format!("/permissions/links/{}", link.id))
+            .append_header((
+                header::AUTHORIZATION,
+                Bearer::new(admin_session.id.to_string()),
+            ));
+        let res = send_test_request(req, app_ctx.clone()).await;
+        assert_eq!(res.status(), 200);
+
+        let link_session = app_ctx.user_session_by_id(link_session.id).await.unwrap();
+        assert!(
+            !app_ctx
+                .session_context(link_session)
+                .db()
+                .has_permission(collection.clone(), Permission::Read)
+                .await
+                .unwrap()
+        );
+
+        let req = actix_web::test::TestRequest::post()
+            .uri(&format!("/shareLinks/{}/session", link.token))
+            .append_header((header::CONTENT_LENGTH, 0));
+        let res = send_test_request(req, app_ctx.clone()).await;
+        ErrorResponse::assert(
+            res,
+            400,
+            "InvalidShareLink",
+            "The share link is invalid or has expired.",
+        )
+        .await;
+
+        // expired links cannot be used
+        let expired_link = admin_db
+            .create_share_link(
+                collection.clone(),
+                Some(DateTime::new_utc(2000, 1, 1, 0, 0, 0)),
+            )
+            .await
+            .unwrap();
+        assert!(
+            app_ctx
+                .create_share_link_session(&expired_link.token)
+                .await
+                .is_err()
+        );
+    }
+
     #[test]
     fn resource_from_str_tuple() {
         let test_uuid = Uuid::new_v4();
@@ -4,16 +4,19 @@ use crate::config;
 use crate::contexts::{ApplicationContext, SessionContext};
 use crate::error;
 use crate::error::Result;
+use crate::quota::{
+    QueryFrequency, QuotaEstimatingExecutionContext, ensure_initialized_quota_available,
+};
 use crate::util::parsing::parse_spatial_resolution;
 use crate::util::server::connection_closed;
 use crate::workflows::registry::WorkflowRegistry;
 use crate::workflows::workflow::WorkflowId;
-use actix_web::{FromRequest, HttpRequest, Responder, web};
+use actix_web::{FromRequest, HttpRequest, HttpResponse, web};
 use base64::Engine;
 use geoengine_datatypes::operations::reproject::reproject_query;
 use geoengine_datatypes::plots::PlotOutputFormat;
 use geoengine_datatypes::primitives::{
-    BoundingBox2D, ColumnSelection, SpatialResolution, VectorQueryRectangle,
+    BoundingBox2D, ColumnSelection, SpatialPartition2D, SpatialResolution, VectorQueryRectangle,
 };
 use geoengine_datatypes::spatial_reference::SpatialReference;
 use geoengine_operators::engine::{
@@ -48,8 +51,31 @@ pub(crate) struct GetPlot {
     #[serde(deserialize_with = "parse_spatial_resolution")]
     #[param(example = "0.1,0.1", value_type = String)]
     pub spatial_resolution: SpatialResolution,
+    /// Renders the plot as an image of this format instead of returning JSON
+    #[serde(default)]
+    #[param(example = "png")]
+    pub format: Option<PlotImageFormat>,
+    /// The width of the image in pixels
+    #[serde(default)]
+    #[param(example = 800)]
+    pub width: Option<u32>,
+    /// The height of the image in pixels
+    #[serde(default)]
+    #[param(example = 500)]
+    pub height: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PlotImageFormat {
+    Png,
+    Svg,
 }
 
+const DEFAULT_PLOT_IMAGE_WIDTH: u32 = 800;
+const DEFAULT_PLOT_IMAGE_HEIGHT: u32 = 500;
+const MAX_PLOT_IMAGE_SIZE: u32 = 4096;
+
 /// Generates a plot.
 ///
 /// # Example
@@ -64,6 +90,10 @@ pub(crate) struct GetPlot {
 /// 2. Create a dataset from it using the "Plain Data" example at `/dataset`.
 /// 3. Create a statistics workflow using the "Statistics Plot" example at `/workflow`.
 /// 4. Generate the plot with this handler.
+///
+/// With the `format` parameter, the plot is rendered as a PNG or SVG image of `width` x `height` pixels.
+/// This is supported by all plots that produce a chart.
+/// Plots that produce a PNG image themselves are returned in their own size and reject `width` and `height`.
 #[utoipa::path(
     tag = "Plots",
     get,
@@ -100,7 +130,12 @@ async fn get_plot_handler<C: ApplicationContext>(
     params: web::Query<GetPlot>,
     session: C::Session,
     app_ctx: web::Data<C>,
-) -> Result<impl Responder> {
+) -> Result<HttpResponse> {
+    // fail before running the workflow if the image cannot be rendered
+    if params.format.is_some() {
+        params.image_size()?;
+    }
+
     let conn_closed = connection_closed(
         &req,
         config::get_config_element::<config::Plots>()?
@@ -115,11 +150,12 @@ async fn get_plot_handler<C: ApplicationContext>(
     let operator = workflow.operator.get_plot()?;
 
     let execution_context = ctx.execution_context()?;
+    let estimating_context = QuotaEstimatingExecutionContext::new(&execution_context);
 
     let workflow_operator_path_root = WorkflowOperatorPath::initialize_root();
 
     let initialized = operator
-        .initialize(workflow_operator_path_root, &execution_context)
+        .initialize(workflow_operator_path_root, &estimating_context)
         .await?;
 
     // handle request and workflow crs matching
@@ -151,6 +187,19 @@ async fn get_plot_handler<C: ApplicationContext>(
         });
     };
 
+    ensure_initialized_quota_available(
+        &ctx,
+        estimating_context,
+        QueryFrequency::Once,
+        SpatialPartition2D::with_bbox_and_resolution(
+            query_rect.spatial_bounds,
+            query_rect.spatial_resolution,
+        ),
+        query_rect.spatial_resolution,
+        query_rect.time_interval,
+    )
+    .await?;
+
     let processor = initialized.query_processor()?;
 
     let mut query_ctx = ctx.query_context(workflow_id.0, Uuid::new_v4())?;
@@ -160,6 +209,54 @@ async fn get_plot_handler<C: ApplicationContext>(
     let output_format = PlotOutputFormat::from(&processor);
     let plot_type = processor.plot_type();
 
+    if let Some(format) = params.format {
+        return match processor {
+            TypedPlotQueryProcessor::JsonPlain(_) => {
+                Err(error::Error::PlotCannotBeRenderedAsImage {
+                    plot_type: plot_type.to_string(),
+                })
+            }
+            TypedPlotQueryProcessor::JsonVega(processor) => {
+                let figure = processor.plot_figure_query(query_rect.into(), &query_ctx);
+                let figure =
+                    abortable_query_execution(figure, conn_closed, query_abort_trigger).await?;
+
+                let figure = figure.ok_or_else(|| error::Error::PlotCannotBeRenderedAsImage {
+                    plot_type: plot_type.to_string(),
+                })?;
+                let (width, height) = params.image_size()?;
+
+                let image = crate::util::spawn_blocking(move || match format {
+                    PlotImageFormat::Png => figure.to_png(width, height),
+                    PlotImageFormat::Svg => Ok(figure.to_svg(width, height).into_bytes()),
+                })
+                .await??;
+
+                Ok(plot_image_response(format, image))
+            }
+            TypedPlotQueryProcessor::ImagePng(processor) => {
+                if format != PlotImageFormat::Png {
+                    return Err(error::Error::PlotCannotBeRenderedAsImage {
+                        plot_type: plot_type.to_string(),
+                    });
+                }
+
+                // the processor renders the image in its own size
+                if params.width.is_some() || params.height.is_some() {
+                    return Err(error::Error::PlotImageSizeNotSupported {
+                        plot_type: plot_type.to_string(),
+                    });
+                }
+
+                let png_bytes = processor.plot_query(query_rect.into(), &query_ctx);
+                let png_bytes =
+                    abortable_query_execution(png_bytes, conn_closed, query_abort_trigger).await?;
+
+                Ok(plot_image_response(format, png_bytes))
+            }
+        };
+    }
+
     let data = match processor {
         TypedPlotQueryProcessor::JsonPlain(processor) => {
             let json = processor.plot_query(query_rect.into(), &query_ctx);
@@ -193,7 +290,33 @@ async fn get_plot_handler<C: ApplicationContext>(
         data,
     };
 
-    Ok(web::Json(output))
+    Ok(HttpResponse::Ok().json(output))
+}
+
+impl GetPlot {
+    /// Returns the requested image size or the default size
+    fn image_size(&self) -> Result<(u32, u32)> {
+        let width = self.width.unwrap_or(DEFAULT_PLOT_IMAGE_WIDTH);
+        let height = self.height.unwrap_or(DEFAULT_PLOT_IMAGE_HEIGHT);
+
+        let valid_range = 1..=MAX_PLOT_IMAGE_SIZE;
+        if !valid_range.contains(&width) || !valid_range.contains(&height) {
+            return Err(error::Error::InvalidPlotImageSize {
+                max: MAX_PLOT_IMAGE_SIZE,
+            });
+        }
+
+        Ok((width, height))
+    }
+}
+
+fn plot_image_response(format: PlotImageFormat, image: Vec<u8>) -> HttpResponse {
+    let content_type = match format {
+        PlotImageFormat::Png => mime::IMAGE_PNG,
+        PlotImageFormat::Svg => mime::IMAGE_SVG,
+    };
+
+    HttpResponse::Ok().content_type(content_type).body(image)
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
@@ -233,7 +356,8 @@ mod tests {
     };
     use geoengine_operators::mock::{MockRasterSource, MockRasterSourceParams};
     use geoengine_operators::plot::{
-        Histogram, HistogramBounds, HistogramBuckets, HistogramParams, Statistics, StatisticsParams,
+        Histogram, HistogramBounds, HistogramBuckets, HistogramParams, PercentileEstimation,
+        Statistics, StatisticsParams,
     };
     use serde_json::{Value, json};
     use tokio_postgres::NoTls;
@@ -282,6 +406,8 @@ mod tests {
                 params: StatisticsParams {
                     column_names: vec![],
                     percentiles: vec![],
+                    percentile_estimation: PercentileEstimation::PSquare,
+                    group_by: None,
                 },
                 sources: vec![example_raster_source()].into(),
             }
@@ -353,6 +479,7 @@ mod tests {
                     },
                     buckets: HistogramBuckets::Number { value: 4 },
                     interactive: false,
+                    group_by: None,
                 },
                 sources: example_raster_source().into(),
             }
@@ -440,6 +567,166 @@ mod tests {
         );
     }
 
+    fn histogram_workflow() -> Workflow {
+        Workflow {
+            operator: Histogram {
+                params: HistogramParams {
+                    attribute_name: "band".to_string(),
+                    bounds: HistogramBounds::Values {
+                        min: 0.0,
+                        max: 10.0,
+                    },
+                    buckets: HistogramBuckets::Number { value: 4 },
+                    interactive: false,
+                    group_by: None,
+                },
+                sources: example_raster_source().into(),
+            }
+            .boxed()
+            .into(),
+        }
+    }
+
+    #[ge_context::test(tiling_spec = "json_vega_tiling_spec")]
+    async fn image_png(app_ctx: PostgresContext<NoTls>) {
+        let session = app_ctx.create_anonymous_session().await.unwrap();
+
+        let session_id = session.id();
+
+        let id = app_ctx
+            .session_context(session.clone())
+            .db()
+            .register_workflow(histogram_workflow())
+            .await
+            .unwrap();
+
+        let params = &[
+            ("bbox", "0,-0.3,0.2,0"),
+            ("crs", "EPSG:4326"),
+            ("time", "2020-01-01T00:00:00.0Z"),
+            ("spatialResolution", "0.1,0.1"),
+            ("format", "png"),
+            ("width", "300"),
+            ("height", "200"),
+        ];
+        let req = actix_web::test::TestRequest::get()
+            .uri(&format!(
+                "/plot/{}?{}",
+                id,
+                &serde_urlencoded::to_string(params).unwrap()
+            ))
+            .append_header((header::AUTHORIZATION, Bearer::new(session_id.to_string())));
+        let res = send_test_request(req, app_ctx).await;
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            mime::IMAGE_PNG.as_ref()
+        );
+
+        let png = actix_web::test::read_body(res).await;
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        // the dimensions of the image header
+        assert_eq!(&png[16..20], 300_u32.to_be_bytes());
+        assert_eq!(&png[20..24], 200_u32.to_be_bytes());
+    }
+
+    #[ge_context::test(tiling_spec = "json_vega_tiling_spec")]
+    async fn image_svg(app_ctx: PostgresContext<NoTls>) {
+        let session = app_ctx.create_anonymous_session().await.unwrap();
+
+        let session_id = session.id();
+
+        let id = app_ctx
+            .session_context(session.clone())
+            .db()
+            .register_workflow(histogram_workflow())
+            .await
+            .unwrap();
+
+        let params = &[
+            ("bbox", "0,-0.3,0.2,0"),
+            ("crs", "EPSG:4326"),
+            ("time", "2020-01-01T00:00:00.0Z"),
+            ("spatialResolution", "0.1,0.1"),
+            ("format", "svg"),
+        ];
+        let req = actix_web::test::TestRequest::get()
+            .uri(&format!(
+                "/plot/{}?{}",
+                id,
+                &serde_urlencoded::to_string(params).unwrap()
+            ))
+            .append_header((header::AUTHORIZATION, Bearer::new(session_id.to_string())));
+        let res = send_test_request(req, app_ctx).await;
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            mime::IMAGE_SVG.as_ref()
+        );
+
+        let svg = read_body_string(res).await;
+
+        assert!(
+            svg.starts_with(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"800\" height=\"500\""
+            )
+        );
+        assert!(svg.contains(">Frequency</text>"));
+    }
+
+    #[ge_context::test(tiling_spec = "json_tiling_spec")]
+    async fn image_of_plain_plot(app_ctx: PostgresContext<NoTls>) {
+        let session = app_ctx.create_anonymous_session().await.unwrap();
+
+        let session_id = session.id();
+
+        let workflow = Workflow {
+            operator: Statistics {
+                params: StatisticsParams {
+                    column_names: vec![],
+                    percentiles: vec![],
+                    percentile_estimation: PercentileEstimation::PSquare,
+                    group_by: None,
+                },
+                sources: vec![example_raster_source()].into(),
+            }
+            .boxed()
+            .into(),
+        };
+
+        let id = app_ctx
+            .session_context(session.clone())
+            .db()
+            .register_workflow(workflow)
+            .await
+            .unwrap();
+
+        let params = &[
+            ("bbox", "0,-0.3,0.2,0"),
+            ("crs", "EPSG:4326"),
+            ("time", "2020-01-01T00:00:00.0Z"),
+            ("spatialResolution", "0.1,0.1"),
+            ("format", "png"),
+        ];
+        let req = actix_web::test::TestRequest::get()
+            .uri(&format!(
+                "/plot/{}?{}",
+                id,
+                &serde_urlencoded::to_string(params).unwrap()
+            ))
+            .append_header((header::AUTHORIZATION, Bearer::new(session_id.to_string())));
+        let res = send_test_request(req, app_ctx).await;
+
+        assert_eq!(res.status(), 400);
+        assert_eq!(
+            read_body_json(res).await["error"],
+            "PlotCannotBeRenderedAsImage"
+        );
+    }
+
     #[test]
     fn deserialize_get_plot() {
         let params = &[
@@ -462,10 +749,54 @@ mod tests {
                 .unwrap()
                 .into(),
                 spatial_resolution: SpatialResolution::zero_point_one(),
+                format: None,
+                width: None,
+                height: None,
             }
         );
     }
 
+    #[test]
+    fn deserialize_get_plot_image() {
+        let params = &[
+            ("bbox", "-180,-90,180,90"),
+            ("crs", "EPSG:4326"),
+            ("time", "2020-01-01T00:00:00.0Z"),
+            ("spatialResolution", "0.1,0.1"),
+            ("format", "svg"),
+            ("width", "300"),
+            ("height", "200"),
+        ];
+
+        let get_plot =
+            serde_urlencoded::from_str::<GetPlot>(&serde_urlencoded::to_string(params).unwrap())
+                .unwrap();
+
+        assert_eq!(get_plot.format, Some(PlotImageFormat::Svg));
+        assert_eq!(get_plot.image_size().unwrap(), (300, 200));
+    }
+
+    #[test]
+    fn it_checks_the_image_size() {
+        let params = &[
+            ("bbox", "-180,-90,180,90"),
+            ("crs", "EPSG:4326"),
+            ("time", "2020-01-01T00:00:00.0Z"),
+            ("spatialResolution", "0.1,0.1"),
+            ("format", "png"),
+            ("width", "0"),
+        ];
+
+        let get_plot =
+            serde_urlencoded::from_str::<GetPlot>(&serde_urlencoded::to_string(params).unwrap())
+                .unwrap();
+
+        assert!(matches!(
+            get_plot.image_size(),
+            Err(error::Error::InvalidPlotImageSize { .. })
+        ));
+    }
+
     #[ge_context::test]
     async fn check_request_types(app_ctx: PostgresContext<NoTls>) {
         async fn get_workflow_json(
@@ -482,6 +813,8 @@ mod tests {
                     params: StatisticsParams {
                         column_names: vec![],
                         percentiles: vec![],
+                        percentile_estimation: PercentileEstimation::PSquare,
+                        group_by: None,
                     },
                     sources: vec![example_raster_source()].into(),
                 }
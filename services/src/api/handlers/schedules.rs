@@ -0,0 +1,630 @@
+use crate::api::model::responses::IdResponse;
+use crate::contexts::{ApplicationContext, SessionContext};
+use crate::error::Result;
+use crate::schedules::{CreateSchedule, Schedule, ScheduleDb, ScheduleId, UpdateSchedule};
+use crate::tasks::{TaskListOptions, TaskManager, TaskStatusWithId};
+use crate::util::extractors::ValidatedQuery;
+use actix_web::{FromRequest, HttpResponse, web};
+use geoengine_datatypes::primitives::DateTime;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+pub(crate) fn init_schedule_routes<C>(cfg: &mut web::ServiceConfig)
+where
+    C: ApplicationContext,
+    C::Session: FromRequest,
+{
+    cfg.service(
+        web::scope("/schedules")
+            .service(
+                web::resource("")
+                    .route(web::get().to(list_schedules_handler::<C>))
+                    .route(web::post().to(create_schedule_handler::<C>)),
+            )
+            .service(
+                web::resource("/{schedule}")
+                    .route(web::get().to(get_schedule_handler::<C>))
+                    .route(web::patch().to(update_schedule_handler::<C>))
+                    .route(web::delete().to(delete_schedule_handler::<C>)),
+            )
+            .service(
+                web::resource("/{schedule}/runs")
+                    .route(web::get().to(list_schedule_runs_handler::<C>)),
+            ),
+    );
+}
+
+/// A run of a schedule.
+/// It has the status of its task if the task was started and an error otherwise.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleRun {
+    #[schema(value_type = String)]
+    pub time_scheduled: DateTime,
+    pub task: Option<TaskStatusWithId>,
+    pub error: Option<String>,
+}
+
+/// Create a schedule that runs a task for a workflow whenever its cron expression matches.
+/// The tasks are run on behalf of the user that creates the schedule.
+/// If the schedule is created with an API token, the tasks are run with the scope of the token
+/// and the schedule is deleted together with the token.
+/// Restricted API tokens cannot create schedules.
+#[utoipa::path(
+    tag = "Schedules",
+    post,
+    path = "/schedules",
+    request_body = CreateSchedule,
+    responses(
+        (status = 200, response = IdResponse::<ScheduleId>)
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+pub(crate) async fn create_schedule_handler<C: ApplicationContext>(
+    session: C::Session,
+    app_ctx: web::Data<C>,
+    schedule: web::Json<CreateSchedule>,
+) -> Result<web::Json<IdResponse<ScheduleId>>> {
+    let id = app_ctx
+        .session_context(session)
+        .db()
+        .create_schedule(schedule.into_inner())
+        .await?;
+
+    Ok(web::Json(IdResponse::from(id)))
+}
+
+/// List all schedules of the user.
+#[utoipa::path(
+    tag = "Schedules",
+    get,
+    path = "/schedules",
+    responses(
+        (status = 200, description = "The schedules of the user", body = [Schedule])
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+pub(crate) async fn list_schedules_handler<C: ApplicationContext>(
+    session: C::Session,
+    app_ctx: web::Data<C>,
+) -> Result<web::Json<Vec<Schedule>>> {
+    let schedules = app_ctx
+        .session_context(session)
+        .db()
+        .list_schedules()
+        .await?;
+
+    Ok(web::Json(schedules))
+}
+
+/// Retrieve a schedule of the user.
+#[utoipa::path(
+    tag = "Schedules",
+    get,
+    path = "/schedules/{schedule}",
+    responses(
+        (status = 200, description = "The schedule", body = Schedule)
+    ),
+    params(
+        ("schedule" = ScheduleId, description = "Schedule id")
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+pub(crate) async fn get_schedule_handler<C: ApplicationContext>(
+    session: C::Session,
+    app_ctx: web::Data<C>,
+    schedule: web::Path<ScheduleId>,
+) -> Result<web::Json<Schedule>> {
+    let schedule = app_ctx
+        .session_context(session)
+        .db()
+        .load_schedule(schedule.into_inner())
+        .await?;
+
+    Ok(web::Json(schedule))
+}
+
+/// Update a schedule of the user.
+/// Its next run is computed anew from the current time.
+#[utoipa::path(
+    tag = "Schedules",
+    patch,
+    path = "/schedules/{schedule}",
+    request_body = UpdateSchedule,
+    responses(
+        (status = 200, description = "OK")
+    ),
+    params(
+        ("schedule" = ScheduleId, description = "Schedule id")
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+pub(crate) async fn update_schedule_handler<C: ApplicationContext>(
+    session: C::Session,
+    app_ctx: web::Data<C>,
+    schedule: web::Path<ScheduleId>,
+    update: web::Json<UpdateSchedule>,
+) -> Result<HttpResponse> {
+    app_ctx
+        .session_context(session)
+        .db()
+        .update_schedule(schedule.into_inner(), update.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Delete a schedule of the user together with its run history.
+/// Tasks that were already started are not affected.
+#[utoipa::path(
+    tag = "Schedules",
+    delete,
+    path = "/schedules/{schedule}",
+    responses(
+        (status = 200, description = "OK")
+    ),
+    params(
+        ("schedule" = ScheduleId, description = "Schedule id")
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+pub(crate) async fn delete_schedule_handler<C: ApplicationContext>(
+    session: C::Session,
+    app_ctx: web::Data<C>,
+    schedule: web::Path<ScheduleId>,
+) -> Result<HttpResponse> {
+    app_ctx
+        .session_context(session)
+        .db()
+        .delete_schedule(schedule.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// List the runs of a schedule, the most recent first, with the status of their tasks.
+/// The status filter applies to the tasks of the runs.
+#[utoipa::path(
+    tag = "Schedules",
+    get,
+    path = "/schedules/{schedule}/runs",
+    responses(
+        (status = 200, description = "The runs of the schedule", body = [ScheduleRun],
+            example = json!([
+                {
+                    "timeScheduled": "2024-01-02T06:00:00.000Z",
+                    "task": {
+                        "taskId": "420b06de-0a7e-45cb-9c1c-ea901b46ab69",
+                        "status": "completed",
+                        "taskType": "append-dataset",
+                        "description": "Appending to dataset ndvi_daily from schedule daily NDVI",
+                        "info": null,
+                        "timeTotal": "00:00:30",
+                        "timeStarted": "2024-01-02T06:00:01.390Z"
+                    },
+                    "error": null
+                },
+                {
+                    "timeScheduled": "2024-01-01T06:00:00.000Z",
+                    "task": null,
+                    "error": "UnknownDatasetName"
+                }
+            ])
+        )
+    ),
+    params(
+        ("schedule" = ScheduleId, description = "Schedule id"),
+        TaskListOptions
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+pub(crate) async fn list_schedule_runs_handler<C: ApplicationContext>(
+    session: C::Session,
+    app_ctx: web::Data<C>,
+    schedule: web::Path<ScheduleId>,
+    options: ValidatedQuery<TaskListOptions>,
+) -> Result<web::Json<Vec<ScheduleRun>>> {
+    let ctx = app_ctx.session_context(session);
+
+    let stored_runs = ctx
+        .db()
+        .list_schedule_runs(schedule.into_inner(), &options.into_inner())
+        .await?;
+
+    let tasks = ctx.tasks();
+
+    let mut runs = Vec::with_capacity(stored_runs.len());
+    for run in stored_runs {
+        let task = match run.task_id {
+            Some(task_id) => Some(TaskStatusWithId {
+                task_id,
+                status: tasks.get_task_status(task_id).await?,
+            }),
+            None => None,
+        };
+
+        runs.push(ScheduleRun {
+            time_scheduled: run.time_scheduled,
+            task,
+            error: run.error,
+        });
+    }
+
+    Ok(web::Json(runs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::model::responses::ErrorResponse;
+    use crate::contexts::{PostgresContext, Session};
+    use crate::datasets::RasterDatasetFromWorkflowResult;
+    use crate::ge_context;
+    use crate::schedules::run_due_schedules;
+    use crate::tasks::TaskStatus;
+    use crate::tasks::util::test::wait_for_task_to_finish;
+    use crate::users::{NewApiToken, UserAuth, UserDb};
+    use crate::util::tests::{
+        TestDataUploads, add_ndvi_to_datasets, read_body_json, send_test_request,
+    };
+    use crate::workflows::registry::WorkflowRegistry;
+    use crate::workflows::workflow::Workflow;
+    use actix_web::{http::header, test};
+    use actix_web_httpauth::headers::authorization::Bearer;
+    use geoengine_datatypes::raster::{GridShape, TilingSpecification};
+    use geoengine_datatypes::util::AsAnyArc;
+    use geoengine_operators::engine::{RasterOperator, TypedOperator};
+    use geoengine_operators::source::{GdalSource, GdalSourceParameters};
+    use serde_json::json;
+    use std::sync::Arc;
+    use tokio_postgres::NoTls;
+
+    fn tiling_spec() -> TilingSpecification {
+        TilingSpecification {
+            origin_coordinate: (0., 0.).into(),
+            tile_size_in_pixels: GridShape::new([600, 600]),
+        }
+    }
+
+    #[ge_context::test(tiling_spec = "tiling_spec")]
+    #[allow(clippy::too_many_lines)]
+    async fn it_runs_schedules_and_lists_their_runs(app_ctx: PostgresContext<NoTls>) {
+        let session = app_ctx.create_anonymous_session().await.unwrap();
+        let ctx = app_ctx.session_context(session.clone());
+
+        let (_, dataset) = add_ndvi_to_datasets(&app_ctx).await;
+
+        let workflow_id = ctx
+            .db()
+            .register_workflow(Workflow {
+                operator: TypedOperator::Raster(
+                    GdalSource {
+                        params: GdalSourceParameters { data: dataset },
+                    }
+                    .boxed(),
+                ),
+            })
+            .await
+            .unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/schedules")
+            .append_header((header::AUTHORIZATION, Bearer::new(session.id().to_string())))
+            .set_json(json!({
+                "name": "monthly NDVI",
+                "workflowId": workflow_id,
+                "cron": "0 0 1 * *",
+                "timeWindow": { "granularity": "months", "step": 1 },
+                "task": {
+                    "type": "rasterDataset",
+                    "displayName": "NDVI",
+                    "description": null,
+                    "spatialBounds": {
+                        "upperLeftCoordinate": { "x": -10.0, "y": 80.0 },
+                        "lowerRightCoordinate": { "x": 50.0, "y": 20.0 }
+                    },
+                    "spatialResolution": { "x": 0.1, "y": 0.1 },
+                    "asCog": false
+                }
+            }));
+        let res = send_test_request(req, app_ctx.clone()).await;
+        assert_eq!(res.status(), 200, "{:?}", res.response());
+
+        let schedule_id: ScheduleId =
+            serde_json::from_value(read_body_json(res).await["id"].clone()).unwrap();
+
+        let schedule = ctx.db().load_schedule(schedule_id).await.unwrap();
+        assert!(schedule.enabled);
+        assert!(schedule.next_run.unwrap() > DateTime::now());
+
+        // other users cannot access the schedule
+        let other_session = app_ctx.create_anonymous_session().await.unwrap();
+        let req = test::TestRequest::get()
+            .uri(&format!("/schedules/{schedule_id}"))
+            .append_header((
+                header::AUTHORIZATION,
+                Bearer::new(other_session.id().to_string()),
+            ));
+        let res = send_test_request(req, app_ctx.clone()).await;
+        assert_eq!(res.status(), 400, "{:?}", res.response());
+
+        // let the schedule be due at the beginning of February 2014 to compute January
+        app_ctx
+            .pool
+            .get()
+            .await
+            .unwrap()
+            .execute(
+                "UPDATE schedules SET next_run = '2014-02-01T00:00:00Z' WHERE id = $1;",
+                &[&schedule_id],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            run_due_schedules(&app_ctx, DateTime::now()).await.unwrap(),
+            1
+        );
+        // the next run is in the future
+        assert_eq!(
+            run_due_schedules(&app_ctx, DateTime::now()).await.unwrap(),
+            0
+        );
+
+        let runs = ctx
+            .db()
+            .list_schedule_runs(schedule_id, &TaskListOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(
+            runs[0].time_scheduled,
+            "2014-02-01T00:00:00Z".parse().unwrap()
+        );
+        assert!(runs[0].error.is_none());
+
+        let task_id = runs[0].task_id.unwrap();
+        let tasks = Arc::new(ctx.tasks());
+        wait_for_task_to_finish(tasks.clone(), task_id).await;
+
+        let status = tasks.get_task_status(task_id).await.unwrap();
+        let TaskStatus::Completed { info, .. } = status else {
+            panic!("Task must be completed");
+        };
+        let result = info
+            .as_any_arc()
+            .downcast::<RasterDatasetFromWorkflowResult>()
+            .unwrap();
+
+        // automatically deletes uploads on drop
+        let _test_uploads = TestDataUploads {
+            uploads: vec![result.upload],
+        };
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/schedules/{schedule_id}/runs?filter=completed"))
+            .append_header((header::AUTHORIZATION, Bearer::new(session.id().to_string())));
+        let res = send_test_request(req, app_ctx.clone()).await;
+        assert_eq!(res.status(), 200, "{:?}", res.response());
+
+        let runs = read_body_json(res).await;
+        assert_eq!(runs.as_array().unwrap().len(), 1);
+        assert_eq!(runs[0]["timeScheduled"], "2014-02-01T00:00:00.000Z");
+        assert_eq!(runs[0]["task"]["taskId"], json!(task_id));
+        assert_eq!(runs[0]["task"]["status"], "completed");
+        assert_eq!(runs[0]["error"], serde_json::Value::Null);
+
+        // disable the schedule
+        let req = test::TestRequest::patch()
+            .uri(&format!("/schedules/{schedule_id}"))
+            .append_header((header::AUTHORIZATION, Bearer::new(session.id().to_string())))
+            .set_json(json!({ "enabled": false }));
+        let res = send_test_request(req, app_ctx.clone()).await;
+        assert_eq!(res.status(), 200, "{:?}", res.response());
+
+        let schedule = ctx.db().load_schedule(schedule_id).await.unwrap();
+        assert!(!schedule.enabled);
+        assert!(schedule.next_run.is_none());
+
+        let req = test::TestRequest::delete()
+            .uri(&format!("/schedules/{schedule_id}"))
+            .append_header((header::AUTHORIZATION, Bearer::new(session.id().to_string())));
+        let res = send_test_request(req, app_ctx.clone()).await;
+        assert_eq!(res.status(), 200, "{:?}", res.response());
+
+        assert!(ctx.db().list_schedules().await.unwrap().is_empty());
+    }
+
+    #[ge_context::test]
+    async fn it_records_runs_that_cannot_start(app_ctx: PostgresContext<NoTls>) {
+        let session = app_ctx.create_anonymous_session().await.unwrap();
+        let ctx = app_ctx.session_context(session.clone());
+
+        let (_, dataset) = add_ndvi_to_datasets(&app_ctx).await;
+
+        let workflow_id = ctx
+            .db()
+            .register_workflow(Workflow {
+                operator: TypedOperator::Raster(
+                    GdalSource {
+                        params: GdalSourceParameters { data: dataset },
+                    }
+                    .boxed(),
+                ),
+            })
+            .await
+            .unwrap();
+
+        let schedule_id = ctx
+            .db()
+            .create_schedule(
+                serde_json::from_value(json!({
+                    "name": "append",
+                    "workflowId": workflow_id,
+                    "cron": "*/5 * * * *",
+                    "timeWindow": { "granularity": "minutes", "step": 5 },
+                    "task": { "type": "appendRasterDataset", "dataset": "does_not_exist" }
+                }))
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        app_ctx
+            .pool
+            .get()
+            .await
+            .unwrap()
+            .execute(
+                "UPDATE schedules SET next_run = now() WHERE id = $1;",
+                &[&schedule_id],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            run_due_schedules(&app_ctx, DateTime::now()).await.unwrap(),
+            1
+        );
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/schedules/{schedule_id}/runs"))
+            .append_header((header::AUTHORIZATION, Bearer::new(session.id().to_string())));
+        let res = send_test_request(req, app_ctx.clone()).await;
+        assert_eq!(res.status(), 200, "{:?}", res.response());
+
+        let runs = read_body_json(res).await;
+        assert_eq!(runs.as_array().unwrap().len(), 1);
+        assert_eq!(runs[0]["task"], serde_json::Value::Null);
+        assert!(runs[0]["error"].is_string());
+    }
+
+    #[ge_context::test]
+    async fn it_keeps_the_api_token_of_schedules(app_ctx: PostgresContext<NoTls>) {
+        let session = app_ctx.create_anonymous_session().await.unwrap();
+        let ctx = app_ctx.session_context(session.clone());
+
+        let (_, dataset) = add_ndvi_to_datasets(&app_ctx).await;
+
+        let workflow_id = ctx
+            .db()
+            .register_workflow(Workflow {
+                operator: TypedOperator::Raster(
+                    GdalSource {
+                        params: GdalSourceParameters { data: dataset },
+                    }
+                    .boxed(),
+                ),
+            })
+            .await
+            .unwrap();
+
+        let create_schedule = json!({
+            "name": "append",
+            "workflowId": workflow_id,
+            "cron": "*/5 * * * *",
+            "timeWindow": { "granularity": "minutes", "step": 5 },
+            "task": { "type": "appendRasterDataset", "dataset": "does_not_exist" }
+        });
+
+        // restricted tokens cannot create schedules
+        let read_only_token = ctx
+            .db()
+            .create_api_token(NewApiToken {
+                name: "read-only".to_string(),
+                expires: DateTime::new_utc(2100, 1, 1, 0, 0, 0),
+                read_only: true,
+                resources: None,
+            })
+            .await
+            .unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/schedules")
+            .append_header((
+                header::AUTHORIZATION,
+                Bearer::new(read_only_token.token.clone()),
+            ))
+            .set_json(create_schedule.clone());
+        let res = send_test_request(req, app_ctx.clone()).await;
+
+        ErrorResponse::assert(
+            res,
+            400,
+            "RestrictedApiToken",
+            "This action is not possible with an API token that is restricted to reading or to specific resources.",
+        )
+        .await;
+
+        let token = ctx
+            .db()
+            .create_api_token(NewApiToken {
+                name: "scheduler".to_string(),
+                expires: DateTime::new_utc(2100, 1, 1, 0, 0, 0),
+                read_only: false,
+                resources: None,
+            })
+            .await
+            .unwrap();
+
+        let req = test::TestRequest::post()
+            .uri("/schedules")
+            .append_header((header::AUTHORIZATION, Bearer::new(token.token.clone())))
+            .set_json(create_schedule);
+        let res = send_test_request(req, app_ctx.clone()).await;
+        assert_eq!(res.status(), 200, "{:?}", res.response());
+
+        let schedule_id: ScheduleId =
+            serde_json::from_value(read_body_json(res).await["id"].clone()).unwrap();
+
+        // the runs of the schedule fail once the token expired
+        let conn = app_ctx.pool.get().await.unwrap();
+        conn.execute(
+            "UPDATE api_tokens SET expires = now() WHERE id = $1;",
+            &[&token.id],
+        )
+        .await
+        .unwrap();
+        conn.execute(
+            "UPDATE schedules SET next_run = now() WHERE id = $1;",
+            &[&schedule_id],
+        )
+        .await
+        .unwrap();
+        drop(conn);
+
+        assert_eq!(
+            run_due_schedules(&app_ctx, DateTime::now()).await.unwrap(),
+            1
+        );
+
+        let runs = ctx
+            .db()
+            .list_schedule_runs(schedule_id, &TaskListOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(runs.len(), 1);
+        assert!(runs[0].task_id.is_none());
+        assert_eq!(
+            runs[0].error.as_deref(),
+            Some("The API token is invalid or has expired.")
+        );
+
+        // the schedule is deleted together with the token
+        ctx.db().delete_api_token(token.id).await.unwrap();
+
+        assert!(ctx.db().list_schedules().await.unwrap().is_empty());
+    }
+}
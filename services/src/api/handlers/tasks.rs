@@ -169,19 +169,22 @@ mod tests {
 
     use super::*;
     use crate::contexts::PostgresContext;
+    use crate::contexts::PostgresDb;
     use crate::contexts::Session;
     use crate::ge_context;
     use crate::tasks::{
-        Task, TaskContext, TaskStatus, TaskStatusInfo, util::test::wait_for_task_to_finish,
+        SimpleTaskManagerBackend, Task, TaskContext, TaskFilter, TaskLimits, TaskStatus,
+        TaskStatusInfo, util::test::wait_for_task_to_finish,
     };
-    use crate::users::UserAuth;
+    use crate::users::{UserAuth, UserId, UserSession};
     use crate::util::tests::{read_body_json, send_test_request};
     use actix_http::header;
     use actix_web_httpauth::headers::authorization::Bearer;
     use futures::{channel::oneshot, lock::Mutex};
     use geoengine_datatypes::error::ErrorSource;
+    use geoengine_datatypes::util::Identifier;
     use serde_json::json;
-    use std::{pin::Pin, sync::Arc};
+    use std::{pin::Pin, sync::Arc, time::Duration};
     use tokio_postgres::NoTls;
 
     struct NopTask {
@@ -319,6 +322,46 @@ mod tests {
         }
     }
 
+    /// A task that schedules a subtask and waits for it to complete
+    struct ParentTask<T, C> {
+        subtask: Arc<Mutex<Option<Box<dyn Task<C>>>>>,
+        task_manager: Arc<T>,
+    }
+
+    #[async_trait::async_trait]
+    impl<T: TaskManager<C>, C: TaskContext + 'static> Task<C> for ParentTask<T, C> {
+        async fn run(&self, _ctx: C) -> Result<Box<dyn TaskStatusInfo>, Box<dyn ErrorSource>> {
+            let subtask = self.subtask.lock().await.take().expect("runs only once");
+
+            let (notification_tx, notification_rx) = oneshot::channel();
+            self.task_manager
+                .schedule_subtask(subtask, notification_tx)
+                .await
+                .map_err(ErrorSource::boxed)?;
+
+            let subtask_status = notification_rx.await.unwrap();
+            assert!(subtask_status.is_completed());
+
+            Ok("completed".to_string().boxed())
+        }
+
+        async fn cleanup_on_error(&self, _ctx: C) -> Result<(), Box<dyn ErrorSource>> {
+            Ok(())
+        }
+
+        fn task_type(&self) -> &'static str {
+            stringify!(ParentTask)
+        }
+
+        fn task_unique_id(&self) -> Option<String> {
+            None
+        }
+
+        fn task_description(&self) -> String {
+            "Parent".to_string()
+        }
+    }
+
     struct FailingTaskWithFailingCleanup;
 
     #[derive(Debug)]
@@ -443,6 +486,47 @@ mod tests {
         assert!(res_body["timeStarted"].is_string());
     }
 
+    #[ge_context::test]
+    async fn it_checks_permissions_of_tasks(app_ctx: PostgresContext<NoTls>) {
+        let owner_session = app_ctx.create_anonymous_session().await.unwrap();
+        let owner_tasks = app_ctx.session_context(owner_session).tasks();
+
+        let other_session = app_ctx.create_anonymous_session().await.unwrap();
+        let other_tasks = app_ctx.session_context(other_session).tasks();
+
+        let (task, _complete_tx) = NopTask::new_with_sender();
+        let task_id = owner_tasks.schedule_task(task.boxed(), None).await.unwrap();
+
+        assert!(owner_tasks.get_task_status(task_id).await.is_ok());
+        assert!(other_tasks.get_task_status(task_id).await.is_err());
+
+        let list_options = TaskListOptions {
+            filter: None,
+            offset: 0,
+            limit: 10,
+        };
+        assert_eq!(
+            owner_tasks
+                .list_tasks(list_options.clone())
+                .await
+                .unwrap()
+                .iter()
+                .map(|task| task.task_id)
+                .collect::<Vec<_>>(),
+            vec![task_id]
+        );
+        assert!(
+            other_tasks
+                .list_tasks(list_options)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        assert!(other_tasks.abort_tasks(task_id, true).await.is_err());
+        assert!(owner_tasks.abort_tasks(task_id, true).await.is_ok());
+    }
+
     #[ge_context::test]
     async fn test_get_list(app_ctx: PostgresContext<NoTls>) {
         let session = app_ctx.create_anonymous_session().await.unwrap();
@@ -862,4 +946,257 @@ mod tests {
             })
         );
     }
+
+    #[tokio::test]
+    async fn it_queues_tasks_beyond_the_limits() {
+        let tasks = Arc::new(SimpleTaskManagerBackend::new(
+            TaskLimits {
+                max_running_tasks: 2,
+                max_running_tasks_per_user: 1,
+            },
+            None,
+        ));
+
+        let user_a = Some(UserId::new());
+        let user_b = Some(UserId::new());
+
+        // 1. start tasks of two users
+
+        let (task_a1, complete_tx_a1) = NopTask::new_with_sender();
+        let (task_a2, complete_tx_a2) = NopTask::new_with_sender();
+        let (task_b, complete_tx_b) = NopTask::new_with_sender();
+
+        let task_a1_id = tasks
+            .schedule_task_for_owner(task_a1.boxed(), None, user_a)
+            .await
+            .unwrap();
+        let task_a2_id = tasks
+            .schedule_task_for_owner(task_a2.boxed(), None, user_a)
+            .await
+            .unwrap();
+        let task_b_id = tasks
+            .schedule_task_for_owner(task_b.boxed(), None, user_b)
+            .await
+            .unwrap();
+
+        assert!(
+            tasks
+                .get_task_status(task_a1_id)
+                .await
+                .unwrap()
+                .is_running()
+        );
+        assert!(
+            tasks
+                .get_task_status(task_a2_id)
+                .await
+                .unwrap()
+                .is_pending()
+        );
+        assert!(tasks.get_task_status(task_b_id).await.unwrap().is_running());
+
+        let pending_tasks = tasks
+            .list_tasks(TaskListOptions {
+                filter: Some(TaskFilter::Pending),
+                offset: 0,
+                limit: 10,
+            })
+            .await
+            .unwrap();
+        assert_eq!(pending_tasks.len(), 1);
+        assert_eq!(pending_tasks[0].task_id, task_a2_id);
+
+        let pending_status = serde_json::to_value(&pending_tasks[0].status).unwrap();
+        assert_eq!(pending_status["status"], json!("pending"));
+        assert_eq!(pending_status["taskType"], json!("nopTask"));
+        assert_eq!(pending_status["description"], json!("No operation"));
+        assert!(pending_status["timeCreated"].is_string());
+
+        // 2. finish the first task of user a, s.t. the pending task starts
+
+        complete_tx_a1.send(()).unwrap();
+
+        wait_for_task_to_finish(tasks.clone(), task_a1_id).await;
+
+        geoengine_operators::util::retry::retry(5, 100, 2., None, || {
+            let tasks = tasks.clone();
+            async move {
+                let status = tasks.get_task_status(task_a2_id).await.unwrap();
+                status.is_running().then_some(()).ok_or(())
+            }
+        })
+        .await
+        .unwrap();
+
+        complete_tx_a2.send(()).unwrap();
+        complete_tx_b.send(()).unwrap();
+
+        wait_for_task_to_finish(tasks.clone(), task_a2_id).await;
+        wait_for_task_to_finish(tasks.clone(), task_b_id).await;
+    }
+
+    #[tokio::test]
+    async fn it_runs_subtasks_beyond_the_limits() {
+        let tasks = Arc::new(SimpleTaskManagerBackend::new(
+            TaskLimits {
+                max_running_tasks: 1,
+                max_running_tasks_per_user: 1,
+            },
+            None,
+        ));
+
+        let (subtask, complete_tx) = NopTask::new_with_sender();
+        complete_tx.send(()).unwrap();
+
+        let parent = ParentTask {
+            subtask: Arc::new(Mutex::new(Some(subtask.boxed()))),
+            task_manager: tasks.clone(),
+        };
+
+        let parent_id = tasks.schedule_task(parent.boxed(), None).await.unwrap();
+
+        wait_for_task_to_finish(tasks.clone(), parent_id).await;
+
+        assert!(
+            tasks
+                .get_task_status(parent_id)
+                .await
+                .unwrap()
+                .is_completed()
+        );
+    }
+
+    #[tokio::test]
+    async fn it_aborts_pending_tasks() {
+        let tasks = Arc::new(SimpleTaskManagerBackend::new(
+            TaskLimits {
+                max_running_tasks: 1,
+                max_running_tasks_per_user: 1,
+            },
+            None,
+        ));
+
+        let (running_task, complete_tx) = NopTask::new_with_sender();
+        let (pending_task, _pending_complete_tx) = NopTask::new_with_sender();
+
+        let running_task_id = tasks
+            .schedule_task(running_task.boxed(), None)
+            .await
+            .unwrap();
+        let pending_task_id = tasks
+            .schedule_task(pending_task.boxed(), None)
+            .await
+            .unwrap();
+
+        assert!(
+            tasks
+                .get_task_status(pending_task_id)
+                .await
+                .unwrap()
+                .is_pending()
+        );
+
+        tasks.abort_tasks(pending_task_id, false).await.unwrap();
+
+        assert_eq!(
+            serde_json::to_value(tasks.get_task_status(pending_task_id).await.unwrap()).unwrap(),
+            json!({
+                "status": "aborted",
+                "cleanUp": {"status": "noCleanUp"}
+            })
+        );
+
+        // the aborted task must not start when the running task finishes
+        complete_tx.send(()).unwrap();
+
+        wait_for_task_to_finish(tasks.clone(), running_task_id).await;
+
+        assert!(
+            tasks
+                .get_task_status(pending_task_id)
+                .await
+                .unwrap()
+                .has_aborted()
+        );
+    }
+
+    #[ge_context::test]
+    async fn it_restores_tasks_after_restart(app_ctx: PostgresContext<NoTls>) {
+        let db = Arc::new(PostgresDb::new(
+            app_ctx.pool.clone(),
+            UserSession::admin_session(),
+        ));
+
+        let lease = Duration::from_secs(60);
+
+        let tasks = Arc::new(SimpleTaskManagerBackend::new(
+            TaskLimits::default(),
+            Some(db.clone()),
+        ));
+        assert_eq!(tasks.renew_lease(lease).await.unwrap(), 0);
+
+        // 1. complete one task and leave another one running
+
+        let (notify_tx, notify_rx) = oneshot::channel();
+        let (task, complete_tx) = NopTask::new_with_sender();
+        let completed_task_id = tasks
+            .schedule_task(task.boxed(), Some(notify_tx))
+            .await
+            .unwrap();
+
+        complete_tx.send(()).unwrap();
+        assert!(notify_rx.await.unwrap().is_completed());
+
+        let (task, _complete_tx) = NopTask::new_with_sender();
+        let running_task_id = tasks.schedule_task(task.boxed(), None).await.unwrap();
+
+        tasks.flush_store().await;
+
+        // 2. another server does not fail the tasks while the lease is renewed
+
+        let other_tasks = SimpleTaskManagerBackend::new(TaskLimits::default(), Some(db.clone()));
+        assert_eq!(other_tasks.renew_lease(lease).await.unwrap(), 0);
+
+        // 3. restart, i.e., the lease expires
+
+        let tasks = Arc::new(SimpleTaskManagerBackend::new(
+            TaskLimits::default(),
+            Some(db),
+        ));
+        assert_eq!(tasks.renew_lease(Duration::ZERO).await.unwrap(), 1);
+
+        // 4. check restored tasks
+
+        let completed_status =
+            serde_json::to_value(tasks.get_task_status(completed_task_id).await.unwrap()).unwrap();
+        assert_eq!(completed_status["status"], json!("completed"));
+        assert_eq!(completed_status["taskType"], json!("nopTask"));
+        assert_eq!(completed_status["description"], json!("No operation"));
+        assert_eq!(completed_status["info"], json!("completed"));
+        assert!(completed_status["timeStarted"].is_string());
+
+        assert_eq!(
+            serde_json::to_value(tasks.get_task_status(running_task_id).await.unwrap()).unwrap(),
+            json!({
+                "status": "failed",
+                "error": format!("Task was interrupted by a restart of the server: {running_task_id}"),
+                "cleanUp": {"status": "noCleanUp"}
+            })
+        );
+
+        let list = tasks
+            .list_tasks(TaskListOptions {
+                filter: None,
+                offset: 0,
+                limit: 10,
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            list.iter().map(|task| task.task_id).collect::<Vec<_>>(),
+            vec![running_task_id, completed_task_id]
+        );
+
+        assert_eq!(tasks.task_type(running_task_id).await.unwrap(), "nopTask");
+    }
 }
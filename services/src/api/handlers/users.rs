@@ -1,3 +1,4 @@
+use crate::api::handlers::permissions::Resource;
 use crate::api::model::responses::IdResponse;
 use crate::config;
 use crate::contexts::ApplicationContext;
@@ -11,16 +12,19 @@ use crate::quota::ComputationQuota;
 use crate::quota::DataUsage;
 use crate::quota::DataUsageSummary;
 use crate::quota::OperatorQuota;
+use crate::quota::{CreateQuotaLimit, QuotaLimitId, QuotaLimitUsage};
 use crate::users::UserAuth;
 use crate::users::UserDb;
 use crate::users::UserId;
 use crate::users::UserRegistration;
 use crate::users::UserSession;
+use crate::users::{ApiToken, ApiTokenId, CreatedApiToken, NewApiToken};
 use crate::users::{AuthCodeRequestURL, AuthCodeResponse, RoleDb, UserCredentials};
 use crate::util::extractors::ValidatedJson;
 use actix_web::FromRequest;
 use actix_web::{HttpResponse, Responder, web};
 use geoengine_datatypes::error::BoxedResultExt;
+use geoengine_datatypes::primitives::DateTime;
 use serde::Deserialize;
 use serde::Serialize;
 use snafu::ResultExt;
@@ -36,6 +40,10 @@ where
 {
     cfg.service(web::resource("/user").route(web::post().to(register_user_handler::<C>)))
         .service(web::resource("/anonymous").route(web::post().to(anonymous_handler::<C>)))
+        .service(
+            web::resource("/shareLinks/{token}/session")
+                .route(web::post().to(share_link_session_handler::<C>)),
+        )
         .service(web::resource("/login").route(web::post().to(login_handler::<C>)))
         .service(web::resource("/logout").route(web::post().to(logout_handler::<C>)))
         .service(web::resource("/session").route(web::get().to(session_handler::<C>)))
@@ -44,6 +52,15 @@ where
                 .route(web::post().to(session_project_handler::<C>)),
         )
         .service(web::resource("/session/view").route(web::post().to(session_view_handler::<C>)))
+        .service(
+            web::resource("/apiTokens")
+                .route(web::get().to(list_api_tokens_handler::<C>))
+                .route(web::post().to(create_api_token_handler::<C>)),
+        )
+        .service(
+            web::resource("/apiTokens/{token}")
+                .route(web::delete().to(delete_api_token_handler::<C>)),
+        )
         .service(web::resource("/quota").route(web::get().to(quota_handler::<C>)))
         .service(
             web::resource("/quota/computations")
@@ -58,6 +75,16 @@ where
             web::resource("/quota/dataUsage/summary")
                 .route(web::get().to(data_usage_summary_handler::<C>)),
         )
+        .service(web::resource("/quota/limits").route(web::get().to(quota_limits_handler::<C>)))
+        .service(
+            web::resource("/quotaLimits")
+                .route(web::get().to(list_quota_limits_handler::<C>))
+                .route(web::post().to(create_quota_limit_handler::<C>)),
+        )
+        .service(
+            web::resource("/quotaLimits/{limit}")
+                .route(web::delete().to(delete_quota_limit_handler::<C>)),
+        )
         .service(
             web::resource("/quotas/{user}")
                 .route(web::get().to(get_user_quota_handler::<C>))
@@ -240,6 +267,47 @@ pub(crate) async fn anonymous_handler<C: ApplicationContext + UserAuth>(
     Ok(web::Json(session))
 }
 
+/// Creates a session for an anonymous user from the token of a share link.
+/// The session can read the shared resource until the link expires or is revoked.
+#[utoipa::path(
+    tag = "Session",
+    post,
+    path = "/shareLinks/{token}/session",
+    responses(
+        (status = 200, description = "The created session", body = UserSession,
+            example = json!({
+                "id": "208fa24e-7a92-4f57-a3fe-d1177d9f18ad",
+                "user": {
+                    "id": "5b4466d2-8bab-4ed8-a182-722af3c80958",
+                    "email": null,
+                    "realName": null
+                },
+                "created": "2021-04-26T13:47:10.579724800Z",
+                "validUntil": "2021-04-26T14:47:10.579775400Z",
+                "project": null,
+                "view": null,
+                "roles": [
+                    "8a27e61f-cc4d-4d0b-ae8c-4f1c91d07f5a",
+                    "fd8e87bf-515c-4f36-8da6-1a53702ff102",
+                    "3c3ba3e9-1f0a-4cd4-a6a4-7d9b44a3e2f1"
+                ]
+            })
+        )
+    ),
+    params(
+        ("token" = String, description = "The token of the share link")
+    )
+)]
+pub(crate) async fn share_link_session_handler<C: ApplicationContext + UserAuth>(
+    app_ctx: web::Data<C>,
+    token: web::Path<String>,
+) -> Result<impl Responder> {
+    let session = app_ctx
+        .create_share_link_session(&token.into_inner())
+        .await?;
+    Ok(web::Json(session))
+}
+
 /// Sets the active project of the session.
 #[utoipa::path(
     tag = "Session",
@@ -562,6 +630,245 @@ pub(crate) async fn update_user_quota_handler<C: ApplicationContext>(
     Ok(actix_web::HttpResponse::Ok().finish())
 }
 
+/// Retrieves the quota limits that apply to the current user and the quota used in their current period.
+#[utoipa::path(
+    tag = "User",
+    get,
+    path = "/quota/limits",
+    responses(
+        (status = 200, description = "The quota limits of the user", body = Vec<QuotaLimitUsage>)
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+pub(crate) async fn quota_limits_handler<C: ApplicationContext>(
+    app_ctx: web::Data<C>,
+    session: C::Session,
+) -> Result<web::Json<Vec<QuotaLimitUsage>>> {
+    let limits = app_ctx
+        .session_context(session)
+        .db()
+        .quota_limits_of_user()
+        .await?;
+
+    Ok(web::Json(limits))
+}
+
+/// Lists all quota limits and the quota used in their current period. Requires admin privilige.
+#[utoipa::path(
+    tag = "User",
+    get,
+    path = "/quotaLimits",
+    responses(
+        (status = 200, description = "All quota limits", body = Vec<QuotaLimitUsage>)
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+pub(crate) async fn list_quota_limits_handler<C: ApplicationContext>(
+    app_ctx: web::Data<C>,
+    session: C::Session,
+) -> Result<web::Json<Vec<QuotaLimitUsage>>> {
+    let limits = app_ctx
+        .session_context(session)
+        .db()
+        .list_quota_limits()
+        .await?;
+
+    Ok(web::Json(limits))
+}
+
+/// Creates a quota limit for a role. Requires admin privilige.
+///
+/// The limit is shared by all members of the role and resets at the start of each day or month.
+/// Its credits are computation units, i.e., raster tiles or chunks of features that operators produce, not pixels.
+/// Use the personal role of a user for a per-user limit and the registered user role for an organisation-wide limit.
+#[utoipa::path(
+    tag = "User",
+    post,
+    path = "/quotaLimits",
+    request_body = CreateQuotaLimit,
+    responses(
+        (status = 200, response = IdResponse::<QuotaLimitId>)
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+pub(crate) async fn create_quota_limit_handler<C: ApplicationContext>(
+    app_ctx: web::Data<C>,
+    session: C::Session,
+    limit: web::Json<CreateQuotaLimit>,
+) -> Result<web::Json<IdResponse<QuotaLimitId>>> {
+    let id = app_ctx
+        .session_context(session)
+        .db()
+        .create_quota_limit(limit.into_inner())
+        .await?;
+
+    Ok(web::Json(IdResponse::from(id)))
+}
+
+/// Deletes a quota limit. Requires admin privilige.
+#[utoipa::path(
+    tag = "User",
+    delete,
+    path = "/quotaLimits/{limit}",
+    responses(
+        (status = 200, description = "Quota limit was deleted")
+    ),
+    params(
+        ("limit" = QuotaLimitId, description = "Quota limit id")
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+pub(crate) async fn delete_quota_limit_handler<C: ApplicationContext>(
+    app_ctx: web::Data<C>,
+    session: C::Session,
+    limit: web::Path<QuotaLimitId>,
+) -> Result<HttpResponse> {
+    app_ctx
+        .session_context(session)
+        .db()
+        .delete_quota_limit(limit.into_inner())
+        .await?;
+
+    Ok(actix_web::HttpResponse::Ok().finish())
+}
+
+/// Request for creating an API token
+#[derive(Debug, PartialEq, Eq, Deserialize, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApiToken {
+    /// A name that is unique among the tokens of the user
+    name: String,
+    expires: DateTime,
+    /// Only allow reading resources
+    #[serde(default)]
+    read_only: bool,
+    /// Restrict the token to these resources. All resources of the user can be accessed if it is not set.
+    #[serde(default)]
+    resources: Option<Vec<Resource>>,
+}
+
+/// Creates a long-lived API token for machine clients like scripts and scheduled jobs.
+///
+/// The token is used as a Bearer token instead of a session id and acts on behalf of the user until it expires or is revoked.
+/// It can be restricted to reading and to a set of resources. Restricted tokens cannot create resources.
+/// Only a hash of the token is stored, so it is only returned once.
+/// API tokens can only be managed with a session from a login.
+#[utoipa::path(
+    tag = "Session",
+    post,
+    path = "/apiTokens",
+    request_body(content = CreateApiToken, example =
+        json!({
+            "name": "nightly-import",
+            "expires": "2027-01-01T00:00:00.000Z",
+            "readOnly": true,
+            "resources": [{
+                "type": "project",
+                "id": "df4ad02e-0d61-4e29-90eb-dc1259c1f5b9",
+            }]
+        })
+    ),
+    responses(
+        (status = 200, description = "The created API token", body = CreatedApiToken)
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+pub(crate) async fn create_api_token_handler<C: ApplicationContext>(
+    app_ctx: web::Data<C>,
+    session: C::Session,
+    token: web::Json<CreateApiToken>,
+) -> Result<web::Json<CreatedApiToken>> {
+    let token = token.into_inner();
+
+    let db = app_ctx.session_context(session).db();
+
+    let resources = match token.resources {
+        Some(resources) => {
+            let mut resource_ids = Vec::with_capacity(resources.len());
+            for resource in resources {
+                resource_ids.push(resource.resolve_resource_id(&db).await?);
+            }
+            Some(resource_ids)
+        }
+        None => None,
+    };
+
+    let created = db
+        .create_api_token(NewApiToken {
+            name: token.name,
+            expires: token.expires,
+            read_only: token.read_only,
+            resources,
+        })
+        .await?;
+
+    Ok(web::Json(created))
+}
+
+/// Lists the API tokens of the user.
+#[utoipa::path(
+    tag = "Session",
+    get,
+    path = "/apiTokens",
+    responses(
+        (status = 200, description = "The API tokens of the user", body = Vec<ApiToken>)
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+pub(crate) async fn list_api_tokens_handler<C: ApplicationContext>(
+    app_ctx: web::Data<C>,
+    session: C::Session,
+) -> Result<web::Json<Vec<ApiToken>>> {
+    let tokens = app_ctx
+        .session_context(session)
+        .db()
+        .list_api_tokens()
+        .await?;
+
+    Ok(web::Json(tokens))
+}
+
+/// Revokes an API token of the user.
+#[utoipa::path(
+    tag = "Session",
+    delete,
+    path = "/apiTokens/{token}",
+    responses(
+        (status = 200, description = "API token was revoked")
+    ),
+    params(
+        ("token" = ApiTokenId, description = "API token id")
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+pub(crate) async fn delete_api_token_handler<C: ApplicationContext>(
+    app_ctx: web::Data<C>,
+    session: C::Session,
+    token: web::Path<ApiTokenId>,
+) -> Result<HttpResponse> {
+    app_ctx
+        .session_context(session)
+        .db()
+        .delete_api_token(token.into_inner())
+        .await?;
+
+    Ok(actix_web::HttpResponse::Ok().finish())
+}
+
 #[derive(Debug, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
 pub struct RedirectUri {
@@ -889,8 +1196,8 @@ mod tests {
     use crate::contexts::PostgresContext;
     use crate::contexts::{Session, SessionContext};
     use crate::ge_context;
-    use crate::permissions::Role;
-    use crate::users::{AuthCodeRequestURL, OidcManager, UserAuth, UserId};
+    use crate::permissions::{Permission, PermissionDb, Role};
+    use crate::users::{ApiTokenResource, AuthCodeRequestURL, OidcManager, UserAuth, UserId};
     use crate::util::tests::mock_oidc::{
         MockRefreshServerConfig, MockTokenConfig, SINGLE_STATE, mock_refresh_server,
         mock_token_response, mock_valid_provider_discovery,
@@ -2010,6 +2317,150 @@ mod tests {
         );
     }
 
+    #[ge_context::test]
+    #[allow(clippy::too_many_lines)]
+    async fn it_checks_quota_limits_on_data(app_ctx: PostgresContext<NoTls>) {
+        let admin_session = admin_login(&app_ctx).await;
+        let admin_db = app_ctx.session_context(admin_session.clone()).db();
+
+        let session = app_ctx.create_anonymous_session().await.unwrap();
+
+        let (_, id) = register_ndvi_workflow_helper(&app_ctx).await;
+
+        let req = test::TestRequest::post()
+            .uri("/quotaLimits")
+            .append_header((
+                header::AUTHORIZATION,
+                format!("Bearer {}", admin_session.id),
+            ))
+            .set_json(json!({
+                "role": Role::anonymous_role_id(),
+                "data": {"type": "dataset", "dataset": "NDVI"},
+                "period": "month",
+                "credits": 1
+            }));
+        let res = send_test_request(req, app_ctx.clone()).await;
+        assert_eq!(res.status(), 200);
+        let limit: IdResponse<QuotaLimitId> = test::read_body_json(res).await;
+
+        admin_db
+            .log_quota_used(vec![ComputationUnit {
+                user: session.user.id.0,
+                workflow: id.0,
+                computation: Uuid::new_v4(),
+                operator_name: "GdalSource",
+                operator_path: WorkflowOperatorPath::initialize_root(),
+                data: Some("NDVI".to_string()),
+            }])
+            .await
+            .unwrap();
+
+        let req = test::TestRequest::get()
+            .uri("/quota/limits")
+            .append_header((header::AUTHORIZATION, format!("Bearer {}", session.id)));
+        let res = send_test_request(req, app_ctx.clone()).await;
+        assert_eq!(res.status(), 200);
+        let limits: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(
+            limits,
+            json!([{
+                "limit": {
+                    "id": limit.id,
+                    "role": Role::anonymous_role_id(),
+                    "data": {"type": "dataset", "dataset": "NDVI"},
+                    "period": "month",
+                    "credits": 1
+                },
+                "used": 1
+            }])
+        );
+
+        let colorizer = Colorizer::linear_gradient(
+            vec![
+                (0.0, RgbaColor::white()).try_into().unwrap(),
+                (255.0, RgbaColor::black()).try_into().unwrap(),
+            ],
+            RgbaColor::transparent(),
+            RgbaColor::white(),
+            RgbaColor::black(),
+        )
+        .unwrap();
+
+        let raster_colorizer = RasterColorizer::SingleBand(SingleBandRasterColorizer {
+            r#type: Default::default(),
+            band: 0,
+            band_colorizer: colorizer.into(),
+        });
+
+        let params = &[
+            ("request", "GetMap"),
+            ("service", "WMS"),
+            ("version", "1.3.0"),
+            ("layers", &id.to_string()),
+            (
+                "bbox",
+                "1.95556640625,0.90087890625,1.9775390625,0.9228515625",
+            ),
+            ("width", "256"),
+            ("height", "256"),
+            ("crs", "EPSG:4326"),
+            (
+                "styles",
+                &format!(
+                    "custom:{}",
+                    serde_json::to_string(&raster_colorizer).unwrap()
+                ),
+            ),
+            ("format", "image/png"),
+            ("time", "2014-04-01T12:00:00.0Z"),
+            ("exceptions", "JSON"),
+        ];
+
+        let req = actix_web::test::TestRequest::get()
+            .uri(&format!(
+                "/wms/{}?{}",
+                id,
+                serde_urlencoded::to_string(params).unwrap()
+            ))
+            .append_header((header::AUTHORIZATION, Bearer::new(session.id.to_string())));
+        let res = send_test_request(req, app_ctx.clone()).await;
+
+        ErrorResponse::assert(
+            res,
+            200,
+            "CreatingProcessorFailed",
+            &format!(
+                "CreatingProcessorFailed: The quota limit {} is exhausted for the current period",
+                limit.id
+            ),
+        )
+        .await;
+
+        let req = test::TestRequest::delete()
+            .uri(&format!("/quotaLimits/{}", limit.id))
+            .append_header((
+                header::AUTHORIZATION,
+                format!("Bearer {}", admin_session.id),
+            ));
+        let res = send_test_request(req, app_ctx.clone()).await;
+        assert_eq!(res.status(), 200);
+
+        let req = actix_web::test::TestRequest::get()
+            .uri(&format!(
+                "/wms/{}?{}",
+                id,
+                serde_urlencoded::to_string(params).unwrap()
+            ))
+            .append_header((header::AUTHORIZATION, Bearer::new(session.id.to_string())));
+        let res = send_test_request(req, app_ctx).await;
+
+        assert_eq!(res.status(), 200);
+        assert_eq!(
+            res.headers().get(&CONTENT_TYPE),
+            Some(&header::HeaderValue::from_static("image/png"))
+        );
+    }
+
     #[ge_context::test]
     async fn it_adds_and_removes_role(app_ctx: PostgresContext<NoTls>) {
         let admin_session = admin_login(&app_ctx).await;
@@ -2327,4 +2778,110 @@ mod tests {
         assert_eq!(usage[1].data, "foo");
         assert_eq!(usage[1].count, 3);
     }
+
+    #[ge_context::test]
+    async fn it_manages_api_tokens(app_ctx: PostgresContext<NoTls>) {
+        let (session, project) = create_project_helper2(&app_ctx).await;
+
+        let req = test::TestRequest::post()
+            .uri("/apiTokens")
+            .append_header((header::AUTHORIZATION, format!("Bearer {}", session.id)))
+            .set_json(json!({
+                "name": "nightly-import",
+                "expires": "2100-01-01T00:00:00.000Z",
+                "readOnly": true,
+                "resources": [{
+                    "type": "project",
+                    "id": project.to_string(),
+                }]
+            }));
+        let res = send_test_request(req, app_ctx.clone()).await;
+
+        assert_eq!(res.status(), 200);
+
+        let created: CreatedApiToken = test::read_body_json(res).await;
+
+        let req = test::TestRequest::get()
+            .uri("/session")
+            .append_header((header::AUTHORIZATION, format!("Bearer {}", created.token)));
+        let res = send_test_request(req, app_ctx.clone()).await;
+
+        assert_eq!(res.status(), 200);
+
+        let token_session: UserSession = test::read_body_json(res).await;
+        assert_eq!(token_session.user.id, session.user.id);
+        // the session id must not reveal the id of the token
+        assert_ne!(token_session.id.0, created.id.0);
+        assert_eq!(
+            token_session.valid_until,
+            DateTime::new_utc(2100, 1, 1, 0, 0, 0)
+        );
+
+        let token_session = app_ctx.session_by_api_token(&created.token).await.unwrap();
+        let token_db = app_ctx.session_context(token_session).db();
+
+        assert!(
+            token_db
+                .has_permission(project, Permission::Read)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !token_db
+                .has_permission(project, Permission::Write)
+                .await
+                .unwrap()
+        );
+
+        let req = test::TestRequest::get()
+            .uri("/apiTokens")
+            .append_header((header::AUTHORIZATION, format!("Bearer {}", created.token)));
+        let res = send_test_request(req, app_ctx.clone()).await;
+
+        let tokens: Vec<ApiToken> = test::read_body_json(res).await;
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].id, created.id);
+        assert_eq!(tokens[0].name, "nightly-import");
+        assert!(tokens[0].read_only);
+        assert_eq!(
+            tokens[0].resources,
+            Some(vec![ApiTokenResource {
+                resource_type: "project".to_string(),
+                resource_id: project.to_string(),
+            }])
+        );
+
+        let req = test::TestRequest::delete()
+            .uri(&format!("/apiTokens/{}", created.id))
+            .append_header((header::AUTHORIZATION, format!("Bearer {}", created.token)));
+        let res = send_test_request(req, app_ctx.clone()).await;
+
+        ErrorResponse::assert(
+            res,
+            400,
+            "ApiTokenManagementRequiresSession",
+            "API tokens can only be managed with a session and not with an API token.",
+        )
+        .await;
+
+        let req = test::TestRequest::delete()
+            .uri(&format!("/apiTokens/{}", created.id))
+            .append_header((header::AUTHORIZATION, format!("Bearer {}", session.id)));
+        let res = send_test_request(req, app_ctx.clone()).await;
+
+        assert_eq!(res.status(), 200);
+
+        let req = test::TestRequest::get()
+            .uri("/session")
+            .append_header((header::AUTHORIZATION, format!("Bearer {}", created.token)));
+        let res = send_test_request(req, app_ctx.clone()).await;
+
+        ErrorResponse::assert(
+            res,
+            401,
+            "Unauthorized",
+            "Authorization error: The API token is invalid or has expired.",
+        )
+        .await;
+    }
 }
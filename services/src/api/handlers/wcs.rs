@@ -7,6 +7,9 @@ use crate::config::get_config_element;
 use crate::contexts::{ApplicationContext, SessionContext};
 use crate::error::Result;
 use crate::error::{self, Error};
+use crate::quota::{
+    QueryFrequency, QuotaEstimatingExecutionContext, ensure_initialized_quota_available,
+};
 use crate::util::server::{CacheControlHeader, connection_closed, not_implemented_handler};
 use crate::workflows::registry::WorkflowRegistry;
 use crate::workflows::workflow::WorkflowId;
@@ -19,7 +22,7 @@ use geoengine_datatypes::{primitives::SpatialResolution, spatial_reference::Spat
 use geoengine_operators::call_on_generic_raster_processor_gdal_types;
 use geoengine_operators::engine::{ExecutionContext, RasterOperator, WorkflowOperatorPath};
 use geoengine_operators::engine::{ResultDescriptor, SingleRasterOrVectorSource};
-use geoengine_operators::processing::{Reprojection, ReprojectionParams};
+use geoengine_operators::processing::{InterpolationMethod, Reprojection, ReprojectionParams};
 use geoengine_operators::util::input::RasterOrVectorOperator;
 use geoengine_operators::util::raster_stream_to_geotiff::{
     GdalGeoTiffDatasetMetadata, GdalGeoTiffOptions, raster_stream_to_multiband_geotiff_bytes,
@@ -387,12 +390,13 @@ async fn wcs_get_coverage_handler<C: ApplicationContext>(
     let operator = workflow.operator.get_raster()?;
 
     let execution_context = ctx.execution_context()?;
+    let mut estimating_context = QuotaEstimatingExecutionContext::new(&execution_context);
 
     let workflow_operator_path_root = WorkflowOperatorPath::initialize_root();
 
     let initialized = operator
         .clone()
-        .initialize(workflow_operator_path_root, &execution_context)
+        .initialize(workflow_operator_path_root, &estimating_context)
         .await?;
 
     // handle request and workflow crs matching
@@ -413,6 +417,10 @@ async fn wcs_get_coverage_handler<C: ApplicationContext>(
 
         let reprojection_params = ReprojectionParams {
             target_spatial_reference: request_spatial_ref,
+            interpolation: InterpolationMethod::NearestNeighbor,
+            downsampling: None,
+            densification: None,
+            split_at_antimeridian: false,
         };
 
         // create the reprojection operator in order to get the canonic operator name
@@ -437,8 +445,10 @@ async fn wcs_get_coverage_handler<C: ApplicationContext>(
         // perform a full initialization. I only added the TODO because we did some optimization here
         // which broke at some point when the workflow operator paths were introduced but no one noticed.
 
+        // only the reprojected workflow is queried, so only its operators are estimated
+        estimating_context = QuotaEstimatingExecutionContext::new(&execution_context);
         let irp = reprojected_workflow
-            .initialize(workflow_operator_path_root, &execution_context)
+            .initialize(workflow_operator_path_root, &estimating_context)
             .await?;
 
         Box::new(irp)
@@ -474,6 +484,16 @@ async fn wcs_get_coverage_handler<C: ApplicationContext>(
         attributes: BandSelection::first(), // TODO: support multi bands in API and set the selection here
     };
 
+    ensure_initialized_quota_available(
+        &ctx,
+        estimating_context,
+        QueryFrequency::PerTile,
+        query_rect.spatial_bounds,
+        query_rect.spatial_resolution,
+        query_rect.time_interval,
+    )
+    .await?;
+
     let query_ctx = ctx.query_context(identifier.0, Uuid::new_v4())?;
 
     let (bytes, cache_hint) = call_on_generic_raster_processor_gdal_types!(processor, p =>
@@ -6,6 +6,9 @@ use crate::config::get_config_element;
 use crate::contexts::{ApplicationContext, SessionContext};
 use crate::error;
 use crate::error::Result;
+use crate::quota::{
+    QueryFrequency, QuotaEstimatingExecutionContext, ensure_initialized_quota_available,
+};
 use crate::util::server::{CacheControlHeader, connection_closed, not_implemented_handler};
 use crate::workflows::registry::WorkflowRegistry;
 use crate::workflows::workflow::{Workflow, WorkflowId};
@@ -14,7 +17,7 @@ use futures::future::BoxFuture;
 use futures_util::TryStreamExt;
 use geoengine_datatypes::collections::ToGeoJson;
 use geoengine_datatypes::primitives::VectorQueryRectangle;
-use geoengine_datatypes::primitives::{CacheHint, ColumnSelection};
+use geoengine_datatypes::primitives::{CacheHint, ColumnSelection, SpatialPartition2D};
 use geoengine_datatypes::{
     collections::{FeatureCollection, MultiPointCollection},
     primitives::SpatialResolution,
@@ -28,7 +31,7 @@ use geoengine_operators::engine::{
     VectorOperator, VectorQueryProcessor,
 };
 use geoengine_operators::engine::{QueryProcessor, WorkflowOperatorPath};
-use geoengine_operators::processing::{Reprojection, ReprojectionParams};
+use geoengine_operators::processing::{InterpolationMethod, Reprojection, ReprojectionParams};
 use geoengine_operators::util::abortable_query_execution;
 use geoengine_operators::util::input::RasterOrVectorOperator;
 use reqwest::Url;
@@ -461,11 +464,12 @@ async fn wfs_feature_handler<C: ApplicationContext>(
     let operator = workflow.operator.get_vector()?;
 
     let execution_context = ctx.execution_context()?;
+    let mut estimating_context = QuotaEstimatingExecutionContext::new(&execution_context);
     let workflow_operator_path_root = WorkflowOperatorPath::initialize_root();
 
     let initialized = operator
         .clone()
-        .initialize(workflow_operator_path_root, &execution_context)
+        .initialize(workflow_operator_path_root, &estimating_context)
         .await?;
 
     // handle request and workflow crs matching
@@ -488,6 +492,10 @@ async fn wfs_feature_handler<C: ApplicationContext>(
 
         let reprojection_params = ReprojectionParams {
             target_spatial_reference: request_spatial_ref,
+            interpolation: InterpolationMethod::NearestNeighbor,
+            downsampling: None,
+            densification: None,
+            split_at_antimeridian: false,
         };
 
         // create the reprojection operator in order to get the canonic operator name
@@ -510,8 +518,11 @@ async fn wfs_feature_handler<C: ApplicationContext>(
         // operator names will be wrong. That's why I now build a new workflow with a reprojection and
         // perform a full initialization. I only added the TODO because we did some optimization here
         // which broke at some point when the workflow operator paths were introduced but no one noticed.
+
+        // only the reprojected workflow is queried, so only its operators are estimated
+        estimating_context = QuotaEstimatingExecutionContext::new(&execution_context);
         let ivp = reprojected_workflow
-            .initialize(workflow_operator_path_root, &execution_context)
+            .initialize(workflow_operator_path_root, &estimating_context)
             .await?;
 
         Box::new(ivp)
@@ -528,6 +539,20 @@ async fn wfs_feature_handler<C: ApplicationContext>(
             .map_or_else(SpatialResolution::zero_point_one, |r| r.0),
         attributes: ColumnSelection::all(),
     };
+
+    ensure_initialized_quota_available(
+        &ctx,
+        estimating_context,
+        QueryFrequency::PerTile,
+        SpatialPartition2D::with_bbox_and_resolution(
+            query_rect.spatial_bounds,
+            query_rect.spatial_resolution,
+        ),
+        query_rect.spatial_resolution,
+        query_rect.time_interval,
+    )
+    .await?;
+
     let query_ctx = ctx.query_context(type_names.0, Uuid::new_v4())?;
 
     let (json, cache_hint) = match processor {
@@ -5,9 +5,14 @@ use crate::api::ogc::util::{parse_bbox, parse_time};
 use crate::config::get_config_element;
 use crate::contexts::{ApplicationContext, SessionContext};
 use crate::datasets::listing::{DatasetProvider, Provenance, ProvenanceOutput};
-use crate::datasets::{RasterDatasetFromWorkflow, schedule_raster_dataset_from_workflow_task};
+use crate::datasets::{
+    AppendRasterDatasetFromWorkflow, RasterDatasetFromWorkflow, VectorDatasetFromWorkflow,
+    schedule_append_raster_dataset_from_workflow_task, schedule_raster_dataset_from_workflow_task,
+    schedule_vector_dataset_from_workflow_task,
+};
 use crate::error::Result;
 use crate::layers::storage::LayerProviderDb;
+use crate::quota::{QuotaEstimate, ensure_estimated_quota_available, estimate_quota};
 use crate::util::parsing::{
     parse_band_selection, parse_spatial_partition, parse_spatial_resolution,
 };
@@ -60,6 +65,10 @@ where
                         web::resource("/allMetadata/zip")
                             .route(web::get().to(get_workflow_all_metadata_zip_handler::<C>)),
                     )
+                    .service(
+                        web::resource("/estimate")
+                            .route(web::get().to(estimate_workflow_quota_handler::<C>)),
+                    )
                     .service(
                         web::resource("/rasterStream")
                             .route(web::get().to(raster_stream_websocket::<C>)),
@@ -73,6 +82,14 @@ where
     .service(
         web::resource("datasetFromWorkflow/{id}")
             .route(web::post().to(dataset_from_workflow_handler::<C>)),
+    )
+    .service(
+        web::resource("datasetFromWorkflow/{id}/append")
+            .route(web::post().to(append_dataset_from_workflow_handler::<C>)),
+    )
+    .service(
+        web::resource("vectorDatasetFromWorkflow/{id}")
+            .route(web::post().to(vector_dataset_from_workflow_handler::<C>)),
     );
 }
 
@@ -220,6 +237,74 @@ async fn workflow_metadata<C: SessionContext>(
     Ok(result_descriptor)
 }
 
+/// The query parameters for `estimate_workflow_quota_handler`.
+#[derive(Copy, Clone, Debug, PartialEq, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkflowQuotaEstimateQuery {
+    #[serde(deserialize_with = "parse_bbox")]
+    #[param(value_type = crate::api::model::datatypes::BoundingBox2D)]
+    pub spatial_bounds: BoundingBox2D,
+    #[serde(deserialize_with = "parse_spatial_resolution")]
+    #[param(value_type = crate::api::model::datatypes::SpatialResolution)]
+    pub spatial_resolution: SpatialResolution,
+    #[serde(deserialize_with = "parse_time")]
+    #[param(value_type = String)]
+    pub time_interval: TimeInterval,
+}
+
+/// Estimates the quota that a query of the workflow uses before it is executed.
+/// The estimate covers all time steps of the query and is compared to the quota that is available to the user
+/// and to the credits that are left of the quota limits of the user's roles.
+#[utoipa::path(
+    tag = "Workflows",
+    get,
+    path = "/workflow/{id}/estimate",
+    responses(
+        (status = 200, description = "Estimated quota of the query", body = QuotaEstimate,
+            example = json!({
+                "count": 2048,
+                "available": 1000,
+                "exceedsAvailable": true,
+                "operators": [
+                    {"operatorName": "Expression", "operatorPath": "[]", "count": 1024},
+                    {"operatorName": "GdalSource", "operatorPath": "[0]", "count": 1024}
+                ]
+            })
+        )
+    ),
+    params(
+        ("id" = WorkflowId, description = "Workflow id"),
+        WorkflowQuotaEstimateQuery,
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+async fn estimate_workflow_quota_handler<C: ApplicationContext>(
+    id: web::Path<WorkflowId>,
+    session: C::Session,
+    app_ctx: web::Data<C>,
+    query: web::Query<WorkflowQuotaEstimateQuery>,
+) -> Result<web::Json<QuotaEstimate>> {
+    let ctx = app_ctx.session_context(session);
+
+    let workflow = ctx.db().load_workflow(&id.into_inner()).await?;
+
+    let estimate = estimate_quota(
+        &ctx,
+        workflow.operator,
+        SpatialPartition2D::with_bbox_and_resolution(
+            query.spatial_bounds,
+            query.spatial_resolution,
+        ),
+        query.spatial_resolution,
+        query.time_interval.into(),
+    )
+    .await?;
+
+    Ok(web::Json(estimate))
+}
+
 /// Gets the provenance of all datasets used in a workflow.
 #[utoipa::path(
     tag = "Workflows",
@@ -457,6 +542,98 @@ async fn dataset_from_workflow_handler<C: ApplicationContext>(
     Ok(web::Json(TaskResponse::new(task_id)))
 }
 
+/// Create a task for appending the time steps of the workflow given by its `id` to an existing raster dataset.
+/// Only the time steps in the requested time interval that are not yet part of the dataset are computed.
+/// They are stored as new files of a new upload and added as new time slices to the dataset.
+/// Only datasets with a static time slice or a list of time slices can be extended.
+/// Datasets with regular time steps fail with `CannotAppendToRegularDataset`.
+/// Returns the id of the created task
+#[utoipa::path(
+    tag = "Workflows",
+    post,
+    path = "/datasetFromWorkflow/{id}/append",
+    request_body = AppendRasterDatasetFromWorkflow,
+    responses(
+        (status = 200, description = "Id of created task", body = TaskResponse,
+            example = json!({"taskId": "7f8a4cfe-76ab-4972-b347-b197e5ef0f3c"})
+        )
+    ),
+    params(
+        ("id" = WorkflowId, description = "Workflow id")
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+async fn append_dataset_from_workflow_handler<C: ApplicationContext>(
+    id: web::Path<WorkflowId>,
+    session: C::Session,
+    app_ctx: web::Data<C>,
+    info: web::Json<AppendRasterDatasetFromWorkflow>,
+) -> Result<web::Json<TaskResponse>> {
+    let ctx = Arc::new(app_ctx.session_context(session));
+
+    let id = id.into_inner();
+    let workflow = ctx.db().load_workflow(&id).await?;
+    let compression_num_threads =
+        get_config_element::<crate::config::Gdal>()?.compression_num_threads;
+
+    let task_id = schedule_append_raster_dataset_from_workflow_task(
+        format!("workflow {id}"),
+        id,
+        workflow,
+        ctx,
+        info.into_inner(),
+        compression_num_threads,
+    )
+    .await?;
+
+    Ok(web::Json(TaskResponse::new(task_id)))
+}
+
+/// Create a task for creating a new vector dataset from the result of the workflow given by its `id` and the dataset parameters in the request body.
+/// The features are written to a `GeoPackage`, `FlatGeobuf` or `GeoParquet` file.
+/// Returns the id of the created task
+#[utoipa::path(
+    tag = "Workflows",
+    post,
+    path = "/vectorDatasetFromWorkflow/{id}",
+    request_body = VectorDatasetFromWorkflow,
+    responses(
+        (status = 200, description = "Id of created task", body = TaskResponse,
+            example = json!({"taskId": "7f8a4cfe-76ab-4972-b347-b197e5ef0f3c"})
+        )
+    ),
+    params(
+        ("id" = WorkflowId, description = "Workflow id")
+    ),
+    security(
+        ("session_token" = [])
+    )
+)]
+async fn vector_dataset_from_workflow_handler<C: ApplicationContext>(
+    id: web::Path<WorkflowId>,
+    session: C::Session,
+    app_ctx: web::Data<C>,
+    info: web::Json<VectorDatasetFromWorkflow>,
+) -> Result<web::Json<TaskResponse>> {
+    let ctx = Arc::new(app_ctx.session_context(session));
+
+    let id = id.into_inner();
+    let workflow = ctx.db().load_workflow(&id).await?;
+
+    let task_id = schedule_vector_dataset_from_workflow_task(
+        format!("workflow {id}"),
+        id,
+        workflow,
+        ctx,
+        info.into_inner(),
+    )
+    .await?;
+
+    Ok(web::Json(TaskResponse::new(task_id)))
+}
+
 /// The query parameters for `raster_stream_websocket`.
 #[derive(Clone, Debug, PartialEq, Deserialize, IntoParams)]
 #[serde(rename_all = "camelCase")]
@@ -521,6 +698,15 @@ async fn raster_stream_websocket<C: ApplicationContext>(
     let workflow_id = id.into_inner();
     let workflow = ctx.db().load_workflow(&workflow_id).await?;
 
+    ensure_estimated_quota_available(
+        &ctx,
+        workflow.operator.clone(),
+        query.spatial_bounds,
+        query.spatial_resolution,
+        query.time_interval.into(),
+    )
+    .await?;
+
     let operator = workflow
         .operator
         .get_raster()
@@ -721,11 +907,18 @@ mod tests {
     use crate::contexts::PostgresContext;
     use crate::contexts::Session;
     use crate::datasets::storage::DatasetStore;
-    use crate::datasets::{DatasetName, RasterDatasetFromWorkflowResult};
+    use crate::datasets::storage::MetaDataDefinition;
+    use crate::datasets::upload::UploadDb;
+    use crate::datasets::{
+        AppendRasterDatasetFromWorkflowResult, DatasetName, RasterDatasetFromWorkflowResult,
+        VectorDatasetFromWorkflowResult,
+    };
     use crate::ge_context;
+    use crate::permissions::Role;
+    use crate::quota::{CreateQuotaLimit, QuotaLimitData, QuotaPeriod};
     use crate::tasks::util::test::wait_for_task_to_finish;
     use crate::tasks::{TaskManager, TaskStatus};
-    use crate::users::UserAuth;
+    use crate::users::{UserAuth, UserDb};
     use crate::util::tests::add_ports_to_datasets;
     use crate::util::tests::admin_login;
     use crate::util::tests::{
@@ -739,7 +932,7 @@ mod tests {
     use actix_web::{http::Method, http::header, test};
     use actix_web_httpauth::headers::authorization::Bearer;
     use futures::StreamExt;
-    use geoengine_datatypes::collections::MultiPointCollection;
+    use geoengine_datatypes::collections::{FeatureCollectionInfos, MultiPointCollection};
     use geoengine_datatypes::primitives::CacheHint;
     use geoengine_datatypes::primitives::DateTime;
     use geoengine_datatypes::primitives::{
@@ -756,12 +949,14 @@ mod tests {
         ExecutionContext, MultipleRasterOrSingleVectorSource, PlotOperator, RasterBandDescriptor,
         RasterBandDescriptors, TypedOperator,
     };
-    use geoengine_operators::engine::{RasterOperator, RasterResultDescriptor, VectorOperator};
+    use geoengine_operators::engine::{
+        RasterOperator, RasterResultDescriptor, VectorOperator, VectorQueryProcessor,
+    };
     use geoengine_operators::mock::{
         MockFeatureCollectionSource, MockPointSource, MockPointSourceParams, MockRasterSource,
         MockRasterSourceParams,
     };
-    use geoengine_operators::plot::{Statistics, StatisticsParams};
+    use geoengine_operators::plot::{PercentileEstimation, Statistics, StatisticsParams};
     use geoengine_operators::source::OgrSource;
     use geoengine_operators::source::OgrSourceParameters;
     use geoengine_operators::source::{GdalSource, GdalSourceParameters};
@@ -1179,6 +1374,8 @@ mod tests {
                 params: StatisticsParams {
                     column_names: vec![],
                     percentiles: vec![],
+                    percentile_estimation: PercentileEstimation::PSquare,
+                    group_by: None,
                 },
                 sources: MultipleRasterOrSingleVectorSource {
                     source: Raster(vec![]),
@@ -1210,6 +1407,101 @@ mod tests {
         );
     }
 
+    fn estimate_tiling_spec() -> TilingSpecification {
+        TilingSpecification {
+            origin_coordinate: (0., 0.).into(),
+            tile_size_in_pixels: GridShape::new([600, 600]),
+        }
+    }
+
+    #[ge_context::test(tiling_spec = "estimate_tiling_spec")]
+    async fn it_estimates_the_quota_of_workflows(app_ctx: PostgresContext<NoTls>) {
+        let session = app_ctx.create_anonymous_session().await.unwrap();
+        let ctx = app_ctx.session_context(session.clone());
+
+        let (_, id) = register_ndvi_workflow_helper(&app_ctx).await;
+
+        // 2x2 tiles of 600x600 pixels for two monthly time steps
+        let req = test::TestRequest::get()
+            .uri(&format!(
+                "/workflow/{id}/estimate?spatialBounds=-60,-60,60,60&spatialResolution=0.1,0.1&timeInterval=2014-01-01T00:00:00.000Z/2014-03-01T00:00:00.000Z"
+            ))
+            .append_header((header::AUTHORIZATION, Bearer::new(session.id().to_string())));
+        let res = send_test_request(req, app_ctx.clone()).await;
+
+        let res_status = res.status();
+        let res_body = read_body_string(res).await;
+        assert_eq!(res_status, 200, "{res_body:?}");
+
+        let available = ctx.db().quota_available().await.unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&res_body).unwrap(),
+            json!({
+                "count": 8,
+                "available": available,
+                "exceedsAvailable": available < 8,
+                "exceededLimits": [],
+                "operators": [{
+                    "operatorName": "GdalSource",
+                    "operatorPath": "[]",
+                    "count": 8
+                }]
+            })
+        );
+    }
+
+    #[ge_context::test(tiling_spec = "estimate_tiling_spec")]
+    async fn it_estimates_the_quota_of_workflows_for_quota_limits(app_ctx: PostgresContext<NoTls>) {
+        let session = app_ctx.create_anonymous_session().await.unwrap();
+
+        let (_, id) = register_ndvi_workflow_helper(&app_ctx).await;
+
+        let admin_session = admin_login(&app_ctx).await;
+        let admin_db = app_ctx.session_context(admin_session).db();
+
+        let dataset_limit = admin_db
+            .create_quota_limit(CreateQuotaLimit {
+                role: Role::anonymous_role_id(),
+                data: Some(QuotaLimitData::dataset(DatasetName::new(None, "NDVI"))),
+                period: QuotaPeriod::Month,
+                credits: 5,
+            })
+            .await
+            .unwrap();
+
+        // the limit on other data does not count the units of the workflow
+        admin_db
+            .create_quota_limit(CreateQuotaLimit {
+                role: Role::anonymous_role_id(),
+                data: Some(QuotaLimitData::dataset(DatasetName::new(None, "other"))),
+                period: QuotaPeriod::Month,
+                credits: 5,
+            })
+            .await
+            .unwrap();
+
+        let req = test::TestRequest::get()
+            .uri(&format!(
+                "/workflow/{id}/estimate?spatialBounds=-60,-60,60,60&spatialResolution=0.1,0.1&timeInterval=2014-01-01T00:00:00.000Z/2014-03-01T00:00:00.000Z"
+            ))
+            .append_header((header::AUTHORIZATION, Bearer::new(session.id().to_string())));
+        let res = send_test_request(req, app_ctx.clone()).await;
+
+        let res_status = res.status();
+        let res_body = read_body_string(res).await;
+        assert_eq!(res_status, 200, "{res_body:?}");
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&res_body).unwrap()["exceededLimits"],
+            json!([{
+                "limit": dataset_limit.to_string(),
+                "count": 8,
+                "remaining": 5
+            }])
+        );
+    }
+
     #[ge_context::test]
     async fn provenance(app_ctx: PostgresContext<NoTls>) {
         let session = app_ctx.create_anonymous_session().await.unwrap();
@@ -1560,6 +1852,356 @@ mod tests {
         );
     }
 
+    #[ge_context::test(tiling_spec = "dataset_from_workflow_task_success_tiling_spec")]
+    #[allow(clippy::too_many_lines)]
+    async fn it_appends_missing_time_steps_to_dataset_from_workflow(
+        app_ctx: PostgresContext<NoTls>,
+    ) {
+        let session = app_ctx.create_anonymous_session().await.unwrap();
+        let ctx = app_ctx.session_context(session.clone());
+
+        let session_id = session.id();
+
+        let (_, dataset) = add_ndvi_to_datasets(&app_ctx).await;
+
+        let workflow = Workflow {
+            operator: TypedOperator::Raster(
+                GdalSource {
+                    params: GdalSourceParameters { data: dataset },
+                }
+                .boxed(),
+            ),
+        };
+
+        let workflow_id = ctx.db().register_workflow(workflow).await.unwrap();
+
+        let tasks = Arc::new(ctx.tasks());
+
+        // create dataset from workflow for January 2014
+        let req = test::TestRequest::post()
+            .uri(&format!("/datasetFromWorkflow/{workflow_id}"))
+            .append_header((header::AUTHORIZATION, Bearer::new(session_id.to_string())))
+            .set_json(json!({
+                "displayName": "foo",
+                "description": null,
+                "query": {
+                    "spatialBounds": {
+                        "upperLeftCoordinate": { "x": -10.0, "y": 80.0 },
+                        "lowerRightCoordinate": { "x": 50.0, "y": 20.0 }
+                    },
+                    "timeInterval": { "start": 1_388_534_400_000_i64, "end": 1_388_534_401_000_i64 },
+                    "spatialResolution": { "x": 0.1, "y": 0.1 }
+                },
+                "asCog": false
+            }));
+        let res = send_test_request(req, app_ctx.clone()).await;
+        assert_eq!(res.status(), 200, "{:?}", res.response());
+
+        let task_response =
+            serde_json::from_str::<TaskResponse>(&read_body_string(res).await).unwrap();
+        wait_for_task_to_finish(tasks.clone(), task_response.task_id).await;
+
+        let created = if let TaskStatus::Completed { info, .. } =
+            tasks.get_task_status(task_response.task_id).await.unwrap()
+        {
+            info.as_any_arc()
+                .downcast::<RasterDatasetFromWorkflowResult>()
+                .unwrap()
+                .as_ref()
+                .clone()
+        } else {
+            panic!("Task must be completed");
+        };
+
+        let mut test_uploads = TestDataUploads {
+            uploads: vec![created.upload],
+        };
+
+        // append January and February 2014, only February is missing
+        let req = test::TestRequest::post()
+            .uri(&format!("/datasetFromWorkflow/{workflow_id}/append"))
+            .append_header((header::AUTHORIZATION, Bearer::new(session_id.to_string())))
+            .set_json(json!({
+                "dataset": created.dataset,
+                "timeInterval": { "start": 1_388_534_400_000_i64, "end": 1_391_212_800_001_i64 },
+                "asCog": false
+            }));
+        let res = send_test_request(req, app_ctx.clone()).await;
+        assert_eq!(res.status(), 200, "{:?}", res.response());
+
+        let task_response =
+            serde_json::from_str::<TaskResponse>(&read_body_string(res).await).unwrap();
+        wait_for_task_to_finish(tasks.clone(), task_response.task_id).await;
+
+        let appended = if let TaskStatus::Completed { info, .. } =
+            tasks.get_task_status(task_response.task_id).await.unwrap()
+        {
+            info.as_any_arc()
+                .downcast::<AppendRasterDatasetFromWorkflowResult>()
+                .unwrap()
+                .as_ref()
+                .clone()
+        } else {
+            panic!("Task must be completed");
+        };
+
+        test_uploads.uploads.push(appended.upload.unwrap());
+
+        // only the file of the appended time step is uploaded
+        assert_eq!(
+            ctx.db()
+                .load_upload(appended.upload.unwrap())
+                .await
+                .unwrap()
+                .files
+                .len(),
+            1
+        );
+
+        assert_eq!(
+            appended
+                .appended_time_steps
+                .iter()
+                .map(|&time| TimeInterval::from(time))
+                .collect::<Vec<_>>(),
+            vec![TimeInterval::new_unchecked(
+                1_391_212_800_000,
+                1_393_632_000_000
+            )]
+        );
+
+        let dataset_id = ctx
+            .db()
+            .resolve_dataset_name_to_id(&created.dataset)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let MetaDataDefinition::GdalMetaDataList(meta_data) =
+            ctx.db().load_loading_info(&dataset_id).await.unwrap()
+        else {
+            panic!("dataset must have a list of time slices");
+        };
+
+        assert_eq!(
+            meta_data
+                .params
+                .iter()
+                .map(|slice| slice.time)
+                .collect::<Vec<_>>(),
+            vec![
+                TimeInterval::new_unchecked(1_388_534_400_000, 1_391_212_800_000),
+                TimeInterval::new_unchecked(1_391_212_800_000, 1_393_632_000_000),
+            ]
+        );
+        assert_eq!(
+            meta_data.result_descriptor.time,
+            Some(TimeInterval::new_unchecked(
+                1_388_534_400_000,
+                1_393_632_000_000
+            ))
+        );
+
+        // appending again does not compute anything
+        let info = AppendRasterDatasetFromWorkflow {
+            dataset: created.dataset.clone(),
+            time_interval: TimeInterval::new_unchecked(1_388_534_400_000, 1_393_632_000_000).into(),
+            as_cog: false,
+        };
+        let task_id = schedule_append_raster_dataset_from_workflow_task(
+            format!("workflow {workflow_id}"),
+            workflow_id,
+            ctx.db().load_workflow(&workflow_id).await.unwrap(),
+            Arc::new(ctx.clone()),
+            info,
+            get_config_element::<crate::config::Gdal>()
+                .unwrap()
+                .compression_num_threads,
+        )
+        .await
+        .unwrap();
+        wait_for_task_to_finish(tasks.clone(), task_id).await;
+
+        let TaskStatus::Completed { info, .. } = tasks.get_task_status(task_id).await.unwrap()
+        else {
+            panic!("Task must be completed");
+        };
+        let appended_again = info
+            .as_any_arc()
+            .downcast::<AppendRasterDatasetFromWorkflowResult>()
+            .unwrap();
+        assert!(appended_again.upload.is_none());
+        assert!(appended_again.appended_time_steps.is_empty());
+    }
+
+    #[ge_context::test]
+    #[allow(clippy::too_many_lines)]
+    async fn vector_dataset_from_workflow_task_success(app_ctx: PostgresContext<NoTls>) {
+        let session = app_ctx.create_anonymous_session().await.unwrap();
+        let ctx = app_ctx.session_context(session.clone());
+
+        let session_id = session.id();
+
+        let workflow = Workflow {
+            operator: MockFeatureCollectionSource::single(
+                MultiPointCollection::from_data(
+                    MultiPoint::many(vec![(0.0, 0.1), (1.0, 1.1)]).unwrap(),
+                    vec![
+                        TimeInterval::new_unchecked(0, 1000),
+                        TimeInterval::new_unchecked(1000, 2000),
+                    ],
+                    [
+                        ("foo".to_string(), FeatureData::Float(vec![1.5, 2.5])),
+                        ("bar".to_string(), FeatureData::Int(vec![1, 2])),
+                        (
+                            "baz".to_string(),
+                            FeatureData::Text(vec!["a".to_string(), "b".to_string()]),
+                        ),
+                    ]
+                    .iter()
+                    .cloned()
+                    .collect(),
+                    CacheHint::default(),
+                )
+                .unwrap(),
+            )
+            .boxed()
+            .into(),
+        };
+
+        let workflow_id = ctx.db().register_workflow(workflow).await.unwrap();
+
+        // create dataset from workflow
+        let req = test::TestRequest::post()
+            .uri(&format!("/vectorDatasetFromWorkflow/{workflow_id}"))
+            .append_header((header::AUTHORIZATION, Bearer::new(session_id.to_string())))
+            .set_json(json!({
+                "displayName": "foo",
+                "description": null,
+                "query": {
+                    "spatialBounds": {
+                        "lowerLeftCoordinate": { "x": -10.0, "y": -10.0 },
+                        "upperRightCoordinate": { "x": 10.0, "y": 10.0 }
+                    },
+                    "timeInterval": { "start": 0, "end": 2000 },
+                    "spatialResolution": { "x": 0.1, "y": 0.1 }
+                },
+                "format": "geoPackage"
+            }));
+        let res = send_test_request(req, app_ctx.clone()).await;
+
+        assert_eq!(res.status(), 200, "{:?}", res.response());
+
+        let task_response =
+            serde_json::from_str::<TaskResponse>(&read_body_string(res).await).unwrap();
+
+        let tasks = Arc::new(ctx.tasks());
+
+        wait_for_task_to_finish(tasks.clone(), task_response.task_id).await;
+
+        let status = tasks.get_task_status(task_response.task_id).await.unwrap();
+
+        let response = if let TaskStatus::Completed { info, .. } = status {
+            info.as_any_arc()
+                .downcast::<VectorDatasetFromWorkflowResult>()
+                .unwrap()
+                .as_ref()
+                .clone()
+        } else {
+            panic!("Task must be completed");
+        };
+
+        // automatically deletes uploads on drop
+        let _test_uploads = TestDataUploads {
+            uploads: vec![response.upload],
+        };
+
+        assert_eq!(response.feature_count, 2);
+
+        let dataset_id = ctx
+            .db()
+            .resolve_dataset_name_to_id(&response.dataset)
+            .await
+            .unwrap()
+            .unwrap();
+        let dataset = ctx.db().load_dataset(&dataset_id).await.unwrap();
+        assert_eq!(dataset.source_operator, "OgrSource");
+        assert_eq!(
+            dataset.provenance.unwrap().last().unwrap().uri,
+            format!("/workflow/{workflow_id}")
+        );
+
+        // query the newly created dataset
+        let op = OgrSource {
+            params: OgrSourceParameters {
+                data: response.dataset.into(),
+                attribute_projection: None,
+                attribute_filters: None,
+            },
+        }
+        .boxed();
+
+        let exe_ctx = ctx.execution_context().unwrap();
+
+        let o = op
+            .initialize(WorkflowOperatorPath::initialize_root(), &exe_ctx)
+            .await
+            .unwrap();
+
+        let query_ctx = ctx.query_context(workflow_id.0, Uuid::new_v4()).unwrap();
+        let query_rect = VectorQueryRectangle {
+            spatial_bounds: BoundingBox2D::new((-10., -10.).into(), (10., 10.).into()).unwrap(),
+            time_interval: TimeInterval::new_unchecked(0, 2000),
+            spatial_resolution: SpatialResolution::zero_point_one(),
+            attributes: ColumnSelection::all(),
+        };
+
+        let processor = o.query_processor().unwrap().multi_point().unwrap();
+
+        let collections = processor
+            .vector_query(query_rect, &query_ctx)
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(collections.len(), 1);
+        let collection = &collections[0];
+
+        assert_eq!(
+            collection.time_intervals(),
+            &[
+                TimeInterval::new_unchecked(0, 1000),
+                TimeInterval::new_unchecked(1000, 2000),
+            ]
+        );
+        assert_eq!(
+            collection
+                .data("foo")
+                .unwrap()
+                .float_options_iter()
+                .collect::<Vec<_>>(),
+            vec![Some(1.5), Some(2.5)]
+        );
+        assert_eq!(
+            collection
+                .data("bar")
+                .unwrap()
+                .float_options_iter()
+                .collect::<Vec<_>>(),
+            vec![Some(1.), Some(2.)]
+        );
+        assert_eq!(
+            collection
+                .data("baz")
+                .unwrap()
+                .strings_iter()
+                .collect::<Vec<_>>(),
+            vec!["a".to_string(), "b".to_string()]
+        );
+    }
+
     #[ge_context::test]
     #[allow(clippy::too_many_lines)]
     async fn it_serves_raster_streams_via_websockets(app_ctx: PostgresContext<NoTls>) {
@@ -1053,6 +1053,17 @@ impl From<RasterQueryRectangle> for geoengine_datatypes::primitives::RasterQuery
     }
 }
 
+impl From<VectorQueryRectangle> for geoengine_datatypes::primitives::VectorQueryRectangle {
+    fn from(value: VectorQueryRectangle) -> Self {
+        Self {
+            spatial_bounds: value.spatial_bounds.into(),
+            time_interval: value.time_interval.into(),
+            spatial_resolution: value.spatial_resolution.into(),
+            attributes: geoengine_datatypes::primitives::ColumnSelection::all(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize, ToSchema)]
 pub struct BandSelection(pub Vec<usize>);
 
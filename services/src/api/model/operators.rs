@@ -11,6 +11,7 @@ use crate::error::{
 use geoengine_datatypes::primitives::ColumnSelection;
 use geoengine_datatypes::util::ByteSize;
 use geoengine_macros::type_tag;
+use geoengine_operators::machine_learning::onnx_util::MlModelTensors;
 use geoengine_operators::util::input::float_option_with_nan;
 use serde::{Deserialize, Deserializer, Serialize};
 use snafu::ensure;
@@ -1549,6 +1550,10 @@ pub struct MlModelMetadata {
     pub output_shape: MlTensorShape3D,
     pub input_no_data_handling: MlModelInputNoDataHandling,
     pub output_no_data_handling: MlModelOutputNoDataHandling,
+    #[serde(default)]
+    pub output_bands: Vec<RasterBandDescriptor>,
+    #[serde(default = "num_traits::One::one")]
+    pub input_time_steps: u32,
 }
 
 impl From<MlModelMetadata> for geoengine_operators::machine_learning::MlModelMetadata {
@@ -1560,6 +1565,8 @@ impl From<MlModelMetadata> for geoengine_operators::machine_learning::MlModelMet
             output_shape: value.output_shape.into(),
             input_no_data_handling: value.input_no_data_handling.into(),
             output_no_data_handling: value.output_no_data_handling.into(),
+            output_bands: value.output_bands.into_iter().map(Into::into).collect(),
+            input_time_steps: value.input_time_steps,
         }
     }
 }
@@ -1573,6 +1580,63 @@ impl From<geoengine_operators::machine_learning::MlModelMetadata> for MlModelMet
             output_shape: value.output_shape.into(),
             input_no_data_handling: value.input_no_data_handling.into(),
             output_no_data_handling: value.output_no_data_handling.into(),
+            output_bands: value.output_bands.into_iter().map(Into::into).collect(),
+            input_time_steps: value.input_time_steps,
+        }
+    }
+}
+
+/// The metadata of a model to add.
+/// The tensor types and shapes that are omitted are inferred from the model file.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AddMlModelMetadata {
+    #[serde(default)]
+    pub input_type: Option<RasterDataType>,
+    #[serde(default)]
+    pub output_type: Option<RasterDataType>,
+    #[serde(default)]
+    pub input_shape: Option<MlTensorShape3D>,
+    #[serde(default)]
+    pub output_shape: Option<MlTensorShape3D>,
+    pub input_no_data_handling: MlModelInputNoDataHandling,
+    pub output_no_data_handling: MlModelOutputNoDataHandling,
+    #[serde(default)]
+    pub output_bands: Vec<RasterBandDescriptor>,
+    #[serde(default = "num_traits::One::one")]
+    pub input_time_steps: u32,
+}
+
+impl AddMlModelMetadata {
+    /// Completes the metadata with the tensors of the model file
+    pub fn into_metadata(
+        self,
+        tensors: MlModelTensors,
+    ) -> geoengine_operators::machine_learning::MlModelMetadata {
+        geoengine_operators::machine_learning::MlModelMetadata {
+            input_type: self.input_type.map_or(tensors.input_type, Into::into),
+            output_type: self.output_type.map_or(tensors.output_type, Into::into),
+            input_shape: self.input_shape.map_or(tensors.input_shape, Into::into),
+            output_shape: self.output_shape.map_or(tensors.output_shape, Into::into),
+            input_no_data_handling: self.input_no_data_handling.into(),
+            output_no_data_handling: self.output_no_data_handling.into(),
+            output_bands: self.output_bands.into_iter().map(Into::into).collect(),
+            input_time_steps: self.input_time_steps,
+        }
+    }
+}
+
+impl From<MlModelMetadata> for AddMlModelMetadata {
+    fn from(value: MlModelMetadata) -> Self {
+        AddMlModelMetadata {
+            input_type: Some(value.input_type),
+            output_type: Some(value.output_type),
+            input_shape: Some(value.input_shape),
+            output_shape: Some(value.output_shape),
+            input_no_data_handling: value.input_no_data_handling,
+            output_no_data_handling: value.output_no_data_handling,
+            output_bands: value.output_bands,
+            input_time_steps: value.input_time_steps,
         }
     }
 }
@@ -5,15 +5,20 @@ use crate::api::model::datatypes::MlModelName;
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToResponse, ToSchema)]
 #[serde(rename_all = "camelCase")]
-#[response(description = "Name of generated resource", example = json!({
-    "name": "ns:name"
+#[response(description = "Name and version of generated resource", example = json!({
+    "mlModelName": "ns:name",
+    "version": 1
 }))]
 pub struct MlModelNameResponse {
     pub ml_model_name: MlModelName,
+    pub version: u32,
 }
 
-impl From<MlModelName> for MlModelNameResponse {
-    fn from(ml_model_name: MlModelName) -> Self {
-        Self { ml_model_name }
+impl MlModelNameResponse {
+    pub fn new(ml_model_name: MlModelName, version: u32) -> Self {
+        Self {
+            ml_model_name,
+            version,
+        }
     }
 }
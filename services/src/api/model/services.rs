@@ -1,8 +1,8 @@
 use super::datatypes::{CacheTtlSeconds, DataId, DataProviderId, GdalConfigOption, RasterDataType};
 use crate::api::model::datatypes::MlModelName;
 use crate::api::model::operators::{
-    GdalMetaDataList, GdalMetaDataRegular, GdalMetaDataStatic, GdalMetadataNetCdfCf,
-    MlModelMetadata, MockMetaData, OgrMetaData,
+    AddMlModelMetadata, GdalMetaDataList, GdalMetaDataRegular, GdalMetaDataStatic,
+    GdalMetadataNetCdfCf, MlModelMetadata, MockMetaData, OgrMetaData,
 };
 use crate::datasets::DatasetName;
 use crate::datasets::external::{GdalRetries, WildliveDataConnectorAuth};
@@ -14,6 +14,7 @@ use crate::util::oidc::RefreshToken;
 use crate::util::parsing::deserialize_base_url;
 use geoengine_datatypes::primitives::DateTime;
 use geoengine_macros::type_tag;
+use geoengine_operators::machine_learning::onnx_util::MlModelTensors;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use url::Url;
@@ -1138,6 +1139,50 @@ pub struct MlModel {
     pub upload: UploadId,
     pub metadata: MlModelMetadata,
     pub file_name: String,
+    #[serde(default)]
+    pub training_metrics: Option<MlModelTrainingMetrics>,
+    pub version: u32,
+}
+
+/// A model to add as a new version of its name
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AddMlModel {
+    pub name: MlModelName,
+    pub display_name: String,
+    pub description: String,
+    pub upload: UploadId,
+    pub metadata: AddMlModelMetadata,
+    pub file_name: String,
+}
+
+impl AddMlModel {
+    /// Creates the model with the metadata completed by the tensors of the model file
+    pub fn into_ml_model(self, tensors: MlModelTensors) -> crate::machine_learning::MlModel {
+        crate::machine_learning::MlModel {
+            name: self.name.into(),
+            display_name: self.display_name,
+            description: self.description,
+            upload: self.upload,
+            metadata: self.metadata.into_metadata(tensors),
+            file_name: self.file_name,
+            training_metrics: None,
+            version: 0, // assigned when adding the model
+        }
+    }
+}
+
+impl From<MlModel> for AddMlModel {
+    fn from(value: MlModel) -> Self {
+        AddMlModel {
+            name: value.name,
+            display_name: value.display_name,
+            description: value.description,
+            upload: value.upload,
+            metadata: value.metadata.into(),
+            file_name: value.file_name,
+        }
+    }
 }
 
 impl From<MlModel> for crate::machine_learning::MlModel {
@@ -1149,6 +1194,8 @@ impl From<MlModel> for crate::machine_learning::MlModel {
             upload: value.upload,
             metadata: value.metadata.into(),
             file_name: value.file_name,
+            training_metrics: value.training_metrics.map(Into::into),
+            version: value.version,
         }
     }
 }
@@ -1162,6 +1209,49 @@ impl From<crate::machine_learning::MlModel> for MlModel {
             upload: value.upload,
             metadata: value.metadata.into(),
             file_name: value.file_name,
+            training_metrics: value.training_metrics.map(Into::into),
+            version: value.version,
+        }
+    }
+}
+
+/// Measures of the model quality on the validation samples that were held back during training
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MlModelTrainingMetrics {
+    pub num_training_samples: u32,
+    pub num_validation_samples: u32,
+    pub accuracy: Option<f64>,
+    pub kappa: Option<f64>,
+    pub root_mean_squared_error: Option<f64>,
+    pub mean_absolute_error: Option<f64>,
+    pub r_squared: Option<f64>,
+}
+
+impl From<MlModelTrainingMetrics> for crate::machine_learning::MlModelTrainingMetrics {
+    fn from(value: MlModelTrainingMetrics) -> Self {
+        crate::machine_learning::MlModelTrainingMetrics {
+            num_training_samples: value.num_training_samples,
+            num_validation_samples: value.num_validation_samples,
+            accuracy: value.accuracy,
+            kappa: value.kappa,
+            root_mean_squared_error: value.root_mean_squared_error,
+            mean_absolute_error: value.mean_absolute_error,
+            r_squared: value.r_squared,
+        }
+    }
+}
+
+impl From<crate::machine_learning::MlModelTrainingMetrics> for MlModelTrainingMetrics {
+    fn from(value: crate::machine_learning::MlModelTrainingMetrics) -> Self {
+        MlModelTrainingMetrics {
+            num_training_samples: value.num_training_samples,
+            num_validation_samples: value.num_validation_samples,
+            accuracy: value.accuracy,
+            kappa: value.kappa,
+            root_mean_squared_error: value.root_mean_squared_error,
+            mean_absolute_error: value.mean_absolute_error,
+            r_squared: value.r_squared,
         }
     }
 }
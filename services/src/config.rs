@@ -225,12 +225,25 @@ impl ConfigElement for LayerService {
 pub struct TaskManager {
     pub list_limit: u32,
     pub list_default_limit: u32,
+    pub max_running_tasks: usize,
+    pub max_running_tasks_per_user: usize,
+    pub lease_seconds: u64,
 }
 
 impl ConfigElement for TaskManager {
     const KEY: &'static str = "task_manager";
 }
 
+#[derive(Debug, Deserialize)]
+pub struct Schedules {
+    pub enabled: bool,
+    pub check_interval_seconds: u64,
+}
+
+impl ConfigElement for Schedules {
+    const KEY: &'static str = "schedules";
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Upload {
     pub path: PathBuf,
@@ -406,6 +419,19 @@ impl ConfigElement for MachineLearning {
     const KEY: &'static str = "machine_learning";
 }
 
+/// Limits of the training of tree ensembles, which keeps all samples in memory
+#[derive(Debug, Deserialize)]
+pub struct MlTraining {
+    /// The maximum number of trees of random forests and rounds of gradient boosting
+    pub max_trees: u32,
+    pub max_depth: u32,
+    pub max_samples: usize,
+}
+
+impl ConfigElement for MlTraining {
+    const KEY: &'static str = "ml_training";
+}
+
 #[derive(Debug, Deserialize)]
 pub struct User {
     pub registration: bool,
@@ -424,6 +450,8 @@ pub struct Quota {
     pub initial_credits: i64,
     pub increment_quota_buffer_size: usize,
     pub increment_quota_buffer_timeout_seconds: u64,
+    #[serde(default)]
+    pub estimation: QuotaEstimationMode,
 }
 
 impl ConfigElement for Quota {
@@ -438,6 +466,16 @@ pub enum QuotaTrackingMode {
     Disabled,
 }
 
+/// What to do with queries whose estimated quota exceeds the available quota
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum QuotaEstimationMode {
+    Reject,
+    Warn,
+    #[default]
+    Disabled,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct Oidc {
     pub enabled: bool,
@@ -1028,19 +1028,34 @@ CREATE TYPE "MlModelMetadata" AS (
     input_shape "MlTensorShape3D",
     output_shape "MlTensorShape3D",
     input_no_data_handling "MlModelInputNoDataHandling",
-    output_no_data_handling "MlModelOutputNoDataHandling"
+    output_no_data_handling "MlModelOutputNoDataHandling",
+    output_bands "RasterBandDescriptor" [],
+    input_time_steps OID
+);
+
+CREATE TYPE "MlModelTrainingMetrics" AS (
+    num_training_samples OID,
+    num_validation_samples OID,
+    accuracy double precision,
+    kappa double precision,
+    root_mean_squared_error double precision,
+    mean_absolute_error double precision,
+    r_squared double precision
 );
 
 CREATE TYPE "MlModelName" AS (namespace text, name text);
 
 CREATE TABLE ml_models ( -- noqa: 
     id uuid PRIMARY KEY,
-    name "MlModelName" UNIQUE NOT NULL,
+    name "MlModelName" NOT NULL,
     display_name text NOT NULL,
     description text NOT NULL,
     upload uuid REFERENCES uploads (id) ON DELETE CASCADE NOT NULL,
     metadata "MlModelMetadata",
-    file_name text
+    file_name text,
+    training_metrics "MlModelTrainingMetrics",
+    version OID NOT NULL,
+    UNIQUE (name, version)
 );
 
 -- TODO: distinguish between roles that are (correspond to) users
@@ -1281,3 +1296,277 @@ CREATE TABLE quota_log (
 );
 
 CREATE INDEX ON quota_log (user_id, timestamp, computation_id);
+
+CREATE TYPE "TaskStatusType" AS ENUM (
+    'Pending',
+    'Running',
+    'Completed',
+    'Aborted',
+    'Failed'
+);
+
+CREATE TABLE tasks (
+    id uuid PRIMARY KEY,
+    user_id uuid,
+    task_type text NOT NULL,
+    description text,
+    status "TaskStatusType" NOT NULL,
+    status_info json NOT NULL,
+    time_created timestamp with time zone NOT NULL
+    DEFAULT clock_timestamp(),
+    time_updated timestamp with time zone NOT NULL
+    DEFAULT clock_timestamp()
+);
+
+CREATE INDEX ON tasks (time_created);
+
+CREATE INDEX ON tasks (status);
+
+CREATE TABLE schedules (
+    id uuid PRIMARY KEY,
+    user_id uuid NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    name text NOT NULL,
+    workflow_id uuid NOT NULL REFERENCES workflows (id),
+    cron text NOT NULL,
+    time_window "TimeStep" NOT NULL,
+    task json NOT NULL,
+    enabled boolean NOT NULL,
+    next_run timestamp with time zone,
+    time_created timestamp with time zone NOT NULL
+    DEFAULT clock_timestamp()
+);
+
+CREATE INDEX ON schedules (user_id);
+
+CREATE INDEX ON schedules (next_run);
+
+CREATE TABLE schedule_runs (
+    id uuid PRIMARY KEY,
+    schedule_id uuid NOT NULL REFERENCES schedules (id) ON DELETE CASCADE,
+    time_scheduled timestamp with time zone NOT NULL,
+    task_id uuid REFERENCES tasks (id) ON DELETE SET NULL,
+    error text
+);
+
+CREATE INDEX ON schedule_runs (schedule_id, time_scheduled);
+
+CREATE TYPE "QuotaPeriod" AS ENUM ('Day', 'Month');
+
+CREATE TABLE quota_limits (
+    id uuid PRIMARY KEY,
+    role_id uuid NOT NULL REFERENCES roles (id) ON DELETE CASCADE,
+    dataset text,
+    provider_id uuid,
+    period "QuotaPeriod" NOT NULL,
+    credits bigint NOT NULL CHECK (credits >= 0),
+    CHECK (dataset IS NULL OR provider_id IS NULL)
+);
+
+CREATE INDEX ON quota_limits (role_id);
+
+CREATE INDEX ON quota_log (data, timestamp);
+
+ALTER TYPE "Permission" ADD VALUE 'Write' BEFORE 'Owner';
+
+ALTER TABLE permissions
+ADD COLUMN workflow_id uuid REFERENCES workflows (id) ON DELETE CASCADE,
+ADD COLUMN upload_id uuid REFERENCES uploads (id) ON DELETE CASCADE,
+ADD COLUMN task_id uuid REFERENCES tasks (id) ON DELETE CASCADE,
+ADD COLUMN expires timestamp with time zone;
+
+ALTER TABLE permissions
+DROP CONSTRAINT permissions_check;
+
+ALTER TABLE permissions
+ADD CONSTRAINT permissions_check CHECK (
+    (
+        (dataset_id IS NOT NULL)::integer
+        + (layer_id IS NOT NULL)::integer
+        + (layer_collection_id IS NOT NULL)::integer
+        + (project_id IS NOT NULL)::integer
+        + (ml_model_id IS NOT NULL)::integer
+        + (provider_id IS NOT NULL)::integer
+        + (workflow_id IS NOT NULL)::integer
+        + (upload_id IS NOT NULL)::integer
+        + (task_id IS NOT NULL)::integer
+    ) = 1
+);
+
+CREATE UNIQUE INDEX ON permissions (
+    role_id,
+    permission,
+    workflow_id
+);
+
+CREATE UNIQUE INDEX ON permissions (
+    role_id,
+    permission,
+    upload_id
+);
+
+CREATE UNIQUE INDEX ON permissions (
+    role_id,
+    permission,
+    task_id
+);
+
+-- uploads and tasks of users become resources that are owned by the users
+INSERT INTO permissions (role_id, permission, upload_id)
+SELECT
+    user_id,
+    'Owner',
+    upload_id
+FROM user_uploads
+WHERE user_id IN (SELECT id FROM roles);
+
+INSERT INTO permissions (role_id, permission, task_id)
+SELECT
+    user_id,
+    'Owner',
+    id
+FROM tasks
+WHERE user_id IN (SELECT id FROM roles);
+
+CREATE OR REPLACE VIEW user_permitted_datasets
+AS
+SELECT
+    r.user_id,
+    p.dataset_id,
+    MAX(p.permission) AS max_permission
+FROM user_roles AS r
+INNER JOIN permissions AS p
+    ON (
+        r.role_id = p.role_id
+        AND p.dataset_id IS NOT NULL
+        AND (p.expires IS NULL OR p.expires > CURRENT_TIMESTAMP)
+    )
+GROUP BY r.user_id, p.dataset_id;
+
+CREATE OR REPLACE VIEW user_permitted_projects
+AS
+SELECT
+    r.user_id,
+    p.project_id,
+    MAX(p.permission) AS max_permission
+FROM user_roles AS r
+INNER JOIN permissions AS p
+    ON (
+        r.role_id = p.role_id
+        AND p.project_id IS NOT NULL
+        AND (p.expires IS NULL OR p.expires > CURRENT_TIMESTAMP)
+    )
+GROUP BY r.user_id, p.project_id;
+
+CREATE OR REPLACE VIEW user_permitted_layer_collections
+AS
+SELECT
+    r.user_id,
+    p.layer_collection_id,
+    MAX(p.permission) AS max_permission
+FROM user_roles AS r
+INNER JOIN permissions AS p
+    ON (
+        r.role_id = p.role_id
+        AND p.layer_collection_id IS NOT NULL
+        AND (p.expires IS NULL OR p.expires > CURRENT_TIMESTAMP)
+    )
+GROUP BY r.user_id, p.layer_collection_id;
+
+CREATE OR REPLACE VIEW user_permitted_layers
+AS
+SELECT
+    r.user_id,
+    p.layer_id,
+    MAX(p.permission) AS max_permission
+FROM user_roles AS r
+INNER JOIN permissions AS p
+    ON (
+        r.role_id = p.role_id
+        AND p.layer_id IS NOT NULL
+        AND (p.expires IS NULL OR p.expires > CURRENT_TIMESTAMP)
+    )
+GROUP BY r.user_id, p.layer_id;
+
+CREATE OR REPLACE VIEW user_permitted_providers
+AS
+SELECT
+    r.user_id,
+    p.provider_id,
+    MAX(p.permission) AS max_permission
+FROM user_roles AS r
+INNER JOIN permissions AS p
+    ON (
+        r.role_id = p.role_id
+        AND p.provider_id IS NOT NULL
+        AND (p.expires IS NULL OR p.expires > CURRENT_TIMESTAMP)
+    )
+GROUP BY r.user_id, p.provider_id;
+
+CREATE OR REPLACE VIEW user_permitted_ml_models
+AS
+SELECT
+    r.user_id,
+    p.ml_model_id,
+    MAX(p.permission) AS max_permission
+FROM user_roles AS r
+INNER JOIN permissions AS p
+    ON (
+        r.role_id = p.role_id
+        AND p.ml_model_id IS NOT NULL
+        AND (p.expires IS NULL OR p.expires > CURRENT_TIMESTAMP)
+    )
+GROUP BY r.user_id, p.ml_model_id;
+
+-- share links give anonymous read access to a resource via the link's role
+CREATE TABLE share_links (
+    id uuid PRIMARY KEY,
+    role_id uuid NOT NULL REFERENCES roles (id) ON DELETE CASCADE,
+    token_hash bytea NOT NULL UNIQUE,
+    resource_type text NOT NULL,
+    resource_id text NOT NULL,
+    expires timestamp with time zone,
+    time_created timestamp with time zone NOT NULL
+    DEFAULT clock_timestamp()
+);
+
+CREATE INDEX ON share_links (resource_type, resource_id);
+
+-- long-lived tokens that authenticate machine clients on behalf of a user
+CREATE TABLE api_tokens (
+    id uuid PRIMARY KEY,
+    user_id uuid NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    name text NOT NULL,
+    token_hash bytea NOT NULL UNIQUE,
+    read_only boolean NOT NULL,
+    expires timestamp with time zone NOT NULL,
+    time_created timestamp with time zone NOT NULL
+    DEFAULT clock_timestamp(),
+    UNIQUE (user_id, name)
+);
+
+-- tokens without resources can access all resources of their user
+CREATE TABLE api_token_resources (
+    api_token_id uuid NOT NULL REFERENCES api_tokens (id) ON DELETE CASCADE,
+    resource_type text NOT NULL,
+    resource_id text NOT NULL,
+    PRIMARY KEY (api_token_id, resource_type, resource_id)
+);
+
+-- usage counters of quota limits, the usage is reset when a new period starts
+CREATE TABLE quota_limit_usage (
+    limit_id uuid PRIMARY KEY REFERENCES quota_limits (id) ON DELETE CASCADE,
+    period_start timestamp with time zone NOT NULL,
+    used bigint NOT NULL
+);
+
+-- schedules that were created with an API token run with the scope of the token
+ALTER TABLE schedules
+ADD COLUMN api_token_id uuid REFERENCES api_tokens (id) ON DELETE CASCADE;
+
+-- task managers renew their lease periodically, unfinished tasks of expired leases are failed
+CREATE TABLE task_manager_leases (
+    id uuid PRIMARY KEY,
+    heartbeat timestamp with time zone NOT NULL
+);
+
+ALTER TABLE tasks ADD COLUMN task_manager_id uuid;
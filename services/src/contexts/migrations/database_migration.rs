@@ -382,6 +382,7 @@ pub mod tests {
                 project: None,
                 view: None,
                 roles: vec![RoleId::from_u128(0xb589a590_9c0c_4b55_9aa2_d178a5f42a78)],
+                api_token: None,
             },
         );
 
@@ -0,0 +1,25 @@
+use super::database_migration::{DatabaseVersion, Migration};
+use crate::{contexts::migrations::Migration0023WildliveOidc, error::Result};
+use async_trait::async_trait;
+use tokio_postgres::Transaction;
+
+/// This migration adds the output band descriptors to the `MlModel` metadata
+pub struct Migration0024MlModelOutputBands;
+
+#[async_trait]
+impl Migration for Migration0024MlModelOutputBands {
+    fn prev_version(&self) -> Option<DatabaseVersion> {
+        Some(Migration0023WildliveOidc.version())
+    }
+
+    fn version(&self) -> DatabaseVersion {
+        "0024_ml_model_output_bands".into()
+    }
+
+    async fn migrate(&self, tx: &Transaction<'_>) -> Result<()> {
+        tx.batch_execute(include_str!("migration_0024_ml_model_output_bands.sql"))
+            .await?;
+
+        Ok(())
+    }
+}
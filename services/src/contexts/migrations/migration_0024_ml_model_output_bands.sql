@@ -0,0 +1,6 @@
+ALTER TYPE "MlModelMetadata" ADD ATTRIBUTE
+output_bands "RasterBandDescriptor" [];
+
+UPDATE ml_models
+SET metadata.output_bands = '{}'
+WHERE metadata IS NOT NULL;
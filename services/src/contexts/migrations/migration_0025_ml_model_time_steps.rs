@@ -0,0 +1,25 @@
+use super::database_migration::{DatabaseVersion, Migration};
+use crate::{contexts::migrations::Migration0024MlModelOutputBands, error::Result};
+use async_trait::async_trait;
+use tokio_postgres::Transaction;
+
+/// This migration adds the number of input time steps to the `MlModel` metadata
+pub struct Migration0025MlModelTimeSteps;
+
+#[async_trait]
+impl Migration for Migration0025MlModelTimeSteps {
+    fn prev_version(&self) -> Option<DatabaseVersion> {
+        Some(Migration0024MlModelOutputBands.version())
+    }
+
+    fn version(&self) -> DatabaseVersion {
+        "0025_ml_model_time_steps".into()
+    }
+
+    async fn migrate(&self, tx: &Transaction<'_>) -> Result<()> {
+        tx.batch_execute(include_str!("migration_0025_ml_model_time_steps.sql"))
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,6 @@
+ALTER TYPE "MlModelMetadata" ADD ATTRIBUTE
+input_time_steps OID;
+
+UPDATE ml_models
+SET metadata.input_time_steps = 1
+WHERE metadata IS NOT NULL;
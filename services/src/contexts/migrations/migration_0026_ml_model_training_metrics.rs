@@ -0,0 +1,25 @@
+use super::database_migration::{DatabaseVersion, Migration};
+use crate::{contexts::migrations::Migration0025MlModelTimeSteps, error::Result};
+use async_trait::async_trait;
+use tokio_postgres::Transaction;
+
+/// This migration adds the training metrics to the `MlModel`s
+pub struct Migration0026MlModelTrainingMetrics;
+
+#[async_trait]
+impl Migration for Migration0026MlModelTrainingMetrics {
+    fn prev_version(&self) -> Option<DatabaseVersion> {
+        Some(Migration0025MlModelTimeSteps.version())
+    }
+
+    fn version(&self) -> DatabaseVersion {
+        "0026_ml_model_training_metrics".into()
+    }
+
+    async fn migrate(&self, tx: &Transaction<'_>) -> Result<()> {
+        tx.batch_execute(include_str!("migration_0026_ml_model_training_metrics.sql"))
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,11 @@
+CREATE TYPE "MlModelTrainingMetrics" AS (
+    num_training_samples OID,
+    num_validation_samples OID,
+    accuracy double precision,
+    kappa double precision,
+    root_mean_squared_error double precision,
+    mean_absolute_error double precision,
+    r_squared double precision
+);
+
+ALTER TABLE ml_models ADD COLUMN training_metrics "MlModelTrainingMetrics";
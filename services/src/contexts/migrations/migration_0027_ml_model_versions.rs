@@ -0,0 +1,25 @@
+use super::database_migration::{DatabaseVersion, Migration};
+use crate::{contexts::migrations::Migration0026MlModelTrainingMetrics, error::Result};
+use async_trait::async_trait;
+use tokio_postgres::Transaction;
+
+/// This migration adds versions to the `MlModel`s, s.t., a name can refer to multiple immutable models
+pub struct Migration0027MlModelVersions;
+
+#[async_trait]
+impl Migration for Migration0027MlModelVersions {
+    fn prev_version(&self) -> Option<DatabaseVersion> {
+        Some(Migration0026MlModelTrainingMetrics.version())
+    }
+
+    fn version(&self) -> DatabaseVersion {
+        "0027_ml_model_versions".into()
+    }
+
+    async fn migrate(&self, tx: &Transaction<'_>) -> Result<()> {
+        tx.batch_execute(include_str!("migration_0027_ml_model_versions.sql"))
+            .await?;
+
+        Ok(())
+    }
+}
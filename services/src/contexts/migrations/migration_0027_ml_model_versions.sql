@@ -0,0 +1,9 @@
+ALTER TABLE ml_models ADD COLUMN version OID NOT NULL DEFAULT 1;
+
+ALTER TABLE ml_models ALTER COLUMN version DROP DEFAULT;
+
+ALTER TABLE ml_models DROP CONSTRAINT ml_models_name_key;
+
+ALTER TABLE ml_models ADD CONSTRAINT ml_models_name_version_key UNIQUE (
+    name, version
+);
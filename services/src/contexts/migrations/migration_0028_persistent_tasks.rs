@@ -0,0 +1,25 @@
+use super::database_migration::{DatabaseVersion, Migration};
+use crate::{contexts::migrations::Migration0027MlModelVersions, error::Result};
+use async_trait::async_trait;
+use tokio_postgres::Transaction;
+
+/// This migration adds a table for persisting the status of tasks across restarts
+pub struct Migration0028PersistentTasks;
+
+#[async_trait]
+impl Migration for Migration0028PersistentTasks {
+    fn prev_version(&self) -> Option<DatabaseVersion> {
+        Some(Migration0027MlModelVersions.version())
+    }
+
+    fn version(&self) -> DatabaseVersion {
+        "0028_persistent_tasks".into()
+    }
+
+    async fn migrate(&self, tx: &Transaction<'_>) -> Result<()> {
+        tx.batch_execute(include_str!("migration_0028_persistent_tasks.sql"))
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+CREATE TYPE "TaskStatusType" AS ENUM (
+    'Pending',
+    'Running',
+    'Completed',
+    'Aborted',
+    'Failed'
+);
+
+CREATE TABLE tasks (
+    id uuid PRIMARY KEY,
+    user_id uuid,
+    task_type text NOT NULL,
+    description text,
+    status "TaskStatusType" NOT NULL,
+    status_info json NOT NULL,
+    time_created timestamp with time zone NOT NULL
+    DEFAULT clock_timestamp(),
+    time_updated timestamp with time zone NOT NULL
+    DEFAULT clock_timestamp()
+);
+
+CREATE INDEX ON tasks (time_created);
+
+CREATE INDEX ON tasks (status);
@@ -0,0 +1,25 @@
+use super::database_migration::{DatabaseVersion, Migration};
+use crate::{contexts::migrations::Migration0028PersistentTasks, error::Result};
+use async_trait::async_trait;
+use tokio_postgres::Transaction;
+
+/// This migration adds tables for schedules that run tasks periodically and their runs
+pub struct Migration0029Schedules;
+
+#[async_trait]
+impl Migration for Migration0029Schedules {
+    fn prev_version(&self) -> Option<DatabaseVersion> {
+        Some(Migration0028PersistentTasks.version())
+    }
+
+    fn version(&self) -> DatabaseVersion {
+        "0029_schedules".into()
+    }
+
+    async fn migrate(&self, tx: &Transaction<'_>) -> Result<()> {
+        tx.batch_execute(include_str!("migration_0029_schedules.sql"))
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+CREATE TABLE schedules (
+    id uuid PRIMARY KEY,
+    user_id uuid NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    name text NOT NULL,
+    workflow_id uuid NOT NULL REFERENCES workflows (id),
+    cron text NOT NULL,
+    time_window "TimeStep" NOT NULL,
+    task json NOT NULL,
+    enabled boolean NOT NULL,
+    next_run timestamp with time zone,
+    time_created timestamp with time zone NOT NULL
+    DEFAULT clock_timestamp()
+);
+
+CREATE INDEX ON schedules (user_id);
+
+CREATE INDEX ON schedules (next_run);
+
+CREATE TABLE schedule_runs (
+    id uuid PRIMARY KEY,
+    schedule_id uuid NOT NULL REFERENCES schedules (id) ON DELETE CASCADE,
+    time_scheduled timestamp with time zone NOT NULL,
+    task_id uuid REFERENCES tasks (id) ON DELETE SET NULL,
+    error text
+);
+
+CREATE INDEX ON schedule_runs (schedule_id, time_scheduled);
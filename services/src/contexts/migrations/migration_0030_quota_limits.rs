@@ -0,0 +1,25 @@
+use super::database_migration::{DatabaseVersion, Migration};
+use crate::{contexts::migrations::Migration0029Schedules, error::Result};
+use async_trait::async_trait;
+use tokio_postgres::Transaction;
+
+/// This migration adds a table for quota limits that reset periodically and apply to roles, datasets or providers
+pub struct Migration0030QuotaLimits;
+
+#[async_trait]
+impl Migration for Migration0030QuotaLimits {
+    fn prev_version(&self) -> Option<DatabaseVersion> {
+        Some(Migration0029Schedules.version())
+    }
+
+    fn version(&self) -> DatabaseVersion {
+        "0030_quota_limits".into()
+    }
+
+    async fn migrate(&self, tx: &Transaction<'_>) -> Result<()> {
+        tx.batch_execute(include_str!("migration_0030_quota_limits.sql"))
+            .await?;
+
+        Ok(())
+    }
+}
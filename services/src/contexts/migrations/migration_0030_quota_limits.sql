@@ -0,0 +1,15 @@
+CREATE TYPE "QuotaPeriod" AS ENUM ('Day', 'Month');
+
+CREATE TABLE quota_limits (
+    id uuid PRIMARY KEY,
+    role_id uuid NOT NULL REFERENCES roles (id) ON DELETE CASCADE,
+    dataset text,
+    provider_id uuid,
+    period "QuotaPeriod" NOT NULL,
+    credits bigint NOT NULL CHECK (credits >= 0),
+    CHECK (dataset IS NULL OR provider_id IS NULL)
+);
+
+CREATE INDEX ON quota_limits (role_id);
+
+CREATE INDEX ON quota_log (data, timestamp);
@@ -0,0 +1,26 @@
+use super::database_migration::{DatabaseVersion, Migration};
+use crate::{contexts::migrations::Migration0030QuotaLimits, error::Result};
+use async_trait::async_trait;
+use tokio_postgres::Transaction;
+
+/// This migration adds the `Write` permission, permissions for workflows, uploads and tasks,
+/// grants that expire and share links
+pub struct Migration0031PermissionGrants;
+
+#[async_trait]
+impl Migration for Migration0031PermissionGrants {
+    fn prev_version(&self) -> Option<DatabaseVersion> {
+        Some(Migration0030QuotaLimits.version())
+    }
+
+    fn version(&self) -> DatabaseVersion {
+        "0031_permission_grants".into()
+    }
+
+    async fn migrate(&self, tx: &Transaction<'_>) -> Result<()> {
+        tx.batch_execute(include_str!("migration_0031_permission_grants.sql"))
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+ALTER TYPE "Permission" ADD VALUE 'Write' BEFORE 'Owner';
+
+ALTER TABLE permissions
+ADD COLUMN workflow_id uuid REFERENCES workflows (id) ON DELETE CASCADE,
+ADD COLUMN upload_id uuid REFERENCES uploads (id) ON DELETE CASCADE,
+ADD COLUMN task_id uuid REFERENCES tasks (id) ON DELETE CASCADE,
+ADD COLUMN expires timestamp with time zone;
+
+ALTER TABLE permissions
+DROP CONSTRAINT permissions_check;
+
+ALTER TABLE permissions
+ADD CONSTRAINT permissions_check CHECK (
+    (
+        (dataset_id IS NOT NULL)::integer
+        + (layer_id IS NOT NULL)::integer
+        + (layer_collection_id IS NOT NULL)::integer
+        + (project_id IS NOT NULL)::integer
+        + (ml_model_id IS NOT NULL)::integer
+        + (provider_id IS NOT NULL)::integer
+        + (workflow_id IS NOT NULL)::integer
+        + (upload_id IS NOT NULL)::integer
+        + (task_id IS NOT NULL)::integer
+    ) = 1
+);
+
+CREATE UNIQUE INDEX ON permissions (
+    role_id,
+    permission,
+    workflow_id
+);
+
+CREATE UNIQUE INDEX ON permissions (
+    role_id,
+    permission,
+    upload_id
+);
+
+CREATE UNIQUE INDEX ON permissions (
+    role_id,
+    permission,
+    task_id
+);
+
+-- uploads and tasks of users become resources that are owned by the users
+INSERT INTO permissions (role_id, permission, upload_id)
+SELECT
+    user_id,
+    'Owner',
+    upload_id
+FROM user_uploads
+WHERE user_id IN (SELECT id FROM roles);
+
+INSERT INTO permissions (role_id, permission, task_id)
+SELECT
+    user_id,
+    'Owner',
+    id
+FROM tasks
+WHERE user_id IN (SELECT id FROM roles);
+
+CREATE OR REPLACE VIEW user_permitted_datasets
+AS
+SELECT
+    r.user_id,
+    p.dataset_id,
+    MAX(p.permission) AS max_permission
+FROM user_roles AS r
+INNER JOIN permissions AS p
+    ON (
+        r.role_id = p.role_id
+        AND p.dataset_id IS NOT NULL
+        AND (p.expires IS NULL OR p.expires > CURRENT_TIMESTAMP)
+    )
+GROUP BY r.user_id, p.dataset_id;
+
+CREATE OR REPLACE VIEW user_permitted_projects
+AS
+SELECT
+    r.user_id,
+    p.project_id,
+    MAX(p.permission) AS max_permission
+FROM user_roles AS r
+INNER JOIN permissions AS p
+    ON (
+        r.role_id = p.role_id
+        AND p.project_id IS NOT NULL
+        AND (p.expires IS NULL OR p.expires > CURRENT_TIMESTAMP)
+    )
+GROUP BY r.user_id, p.project_id;
+
+CREATE OR REPLACE VIEW user_permitted_layer_collections
+AS
+SELECT
+    r.user_id,
+    p.layer_collection_id,
+    MAX(p.permission) AS max_permission
+FROM user_roles AS r
+INNER JOIN permissions AS p
+    ON (
+        r.role_id = p.role_id
+        AND p.layer_collection_id IS NOT NULL
+        AND (p.expires IS NULL OR p.expires > CURRENT_TIMESTAMP)
+    )
+GROUP BY r.user_id, p.layer_collection_id;
+
+CREATE OR REPLACE VIEW user_permitted_layers
+AS
+SELECT
+    r.user_id,
+    p.layer_id,
+    MAX(p.permission) AS max_permission
+FROM user_roles AS r
+INNER JOIN permissions AS p
+    ON (
+        r.role_id = p.role_id
+        AND p.layer_id IS NOT NULL
+        AND (p.expires IS NULL OR p.expires > CURRENT_TIMESTAMP)
+    )
+GROUP BY r.user_id, p.layer_id;
+
+CREATE OR REPLACE VIEW user_permitted_providers
+AS
+SELECT
+    r.user_id,
+    p.provider_id,
+    MAX(p.permission) AS max_permission
+FROM user_roles AS r
+INNER JOIN permissions AS p
+    ON (
+        r.role_id = p.role_id
+        AND p.provider_id IS NOT NULL
+        AND (p.expires IS NULL OR p.expires > CURRENT_TIMESTAMP)
+    )
+GROUP BY r.user_id, p.provider_id;
+
+CREATE OR REPLACE VIEW user_permitted_ml_models
+AS
+SELECT
+    r.user_id,
+    p.ml_model_id,
+    MAX(p.permission) AS max_permission
+FROM user_roles AS r
+INNER JOIN permissions AS p
+    ON (
+        r.role_id = p.role_id
+        AND p.ml_model_id IS NOT NULL
+        AND (p.expires IS NULL OR p.expires > CURRENT_TIMESTAMP)
+    )
+GROUP BY r.user_id, p.ml_model_id;
+
+-- share links give anonymous read access to a resource via the link's role
+CREATE TABLE share_links (
+    id uuid PRIMARY KEY,
+    role_id uuid NOT NULL REFERENCES roles (id) ON DELETE CASCADE,
+    token_hash bytea NOT NULL UNIQUE,
+    resource_type text NOT NULL,
+    resource_id text NOT NULL,
+    expires timestamp with time zone,
+    time_created timestamp with time zone NOT NULL
+    DEFAULT clock_timestamp()
+);
+
+CREATE INDEX ON share_links (resource_type, resource_id);
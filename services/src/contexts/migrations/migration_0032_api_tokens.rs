@@ -0,0 +1,25 @@
+use super::database_migration::{DatabaseVersion, Migration};
+use crate::{contexts::migrations::Migration0031PermissionGrants, error::Result};
+use async_trait::async_trait;
+use tokio_postgres::Transaction;
+
+/// This migration adds API tokens for machine clients
+pub struct Migration0032ApiTokens;
+
+#[async_trait]
+impl Migration for Migration0032ApiTokens {
+    fn prev_version(&self) -> Option<DatabaseVersion> {
+        Some(Migration0031PermissionGrants.version())
+    }
+
+    fn version(&self) -> DatabaseVersion {
+        "0032_api_tokens".into()
+    }
+
+    async fn migrate(&self, tx: &Transaction<'_>) -> Result<()> {
+        tx.batch_execute(include_str!("migration_0032_api_tokens.sql"))
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,20 @@
+-- long-lived tokens that authenticate machine clients on behalf of a user
+CREATE TABLE api_tokens (
+    id uuid PRIMARY KEY,
+    user_id uuid NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    name text NOT NULL,
+    token_hash bytea NOT NULL UNIQUE,
+    read_only boolean NOT NULL,
+    expires timestamp with time zone NOT NULL,
+    time_created timestamp with time zone NOT NULL
+    DEFAULT clock_timestamp(),
+    UNIQUE (user_id, name)
+);
+
+-- tokens without resources can access all resources of their user
+CREATE TABLE api_token_resources (
+    api_token_id uuid NOT NULL REFERENCES api_tokens (id) ON DELETE CASCADE,
+    resource_type text NOT NULL,
+    resource_id text NOT NULL,
+    PRIMARY KEY (api_token_id, resource_type, resource_id)
+);
@@ -0,0 +1,25 @@
+use super::database_migration::{DatabaseVersion, Migration};
+use crate::{contexts::migrations::Migration0032ApiTokens, error::Result};
+use async_trait::async_trait;
+use tokio_postgres::Transaction;
+
+/// This migration adds counters for the usage of quota limits in their current period
+pub struct Migration0033QuotaLimitUsage;
+
+#[async_trait]
+impl Migration for Migration0033QuotaLimitUsage {
+    fn prev_version(&self) -> Option<DatabaseVersion> {
+        Some(Migration0032ApiTokens.version())
+    }
+
+    fn version(&self) -> DatabaseVersion {
+        "0033_quota_limit_usage".into()
+    }
+
+    async fn migrate(&self, tx: &Transaction<'_>) -> Result<()> {
+        tx.batch_execute(include_str!("migration_0033_quota_limit_usage.sql"))
+            .await?;
+
+        Ok(())
+    }
+}
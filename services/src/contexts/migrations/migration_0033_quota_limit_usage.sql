@@ -0,0 +1,29 @@
+-- usage counters of quota limits, the usage is reset when a new period starts
+CREATE TABLE quota_limit_usage (
+    limit_id uuid PRIMARY KEY REFERENCES quota_limits (id) ON DELETE CASCADE,
+    period_start timestamp with time zone NOT NULL,
+    used bigint NOT NULL
+);
+
+-- initialize the counters with the usage of the current periods
+INSERT INTO quota_limit_usage (limit_id, period_start, used)
+SELECT
+    l.id,
+    date_trunc(lower(l.period::text), CURRENT_TIMESTAMP),
+    (
+        SELECT COUNT(*)
+        FROM
+            quota_log AS q
+        WHERE
+            q.timestamp >= date_trunc(lower(l.period::text), CURRENT_TIMESTAMP)
+            AND q.user_id IN (
+                SELECT r.user_id FROM user_roles AS r
+                WHERE r.role_id = l.role_id
+            )
+            AND (l.dataset IS NULL OR q.data = l.dataset)
+            AND (
+                l.provider_id IS NULL
+                OR position(':' || l.provider_id::text || ':' IN q.data) > 0
+            )
+    )
+FROM quota_limits AS l;
@@ -0,0 +1,25 @@
+use super::database_migration::{DatabaseVersion, Migration};
+use crate::{contexts::migrations::Migration0033QuotaLimitUsage, error::Result};
+use async_trait::async_trait;
+use tokio_postgres::Transaction;
+
+/// This migration grants read access to workflows that have no permissions, since loading workflows requires a permission now
+pub struct Migration0034WorkflowPermissions;
+
+#[async_trait]
+impl Migration for Migration0034WorkflowPermissions {
+    fn prev_version(&self) -> Option<DatabaseVersion> {
+        Some(Migration0033QuotaLimitUsage.version())
+    }
+
+    fn version(&self) -> DatabaseVersion {
+        "0034_workflow_permissions".into()
+    }
+
+    async fn migrate(&self, tx: &Transaction<'_>) -> Result<()> {
+        tx.batch_execute(include_str!("migration_0034_workflow_permissions.sql"))
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,17 @@
+-- workflows that were registered before they became resources stay readable by all users
+INSERT INTO permissions (role_id, permission, workflow_id)
+SELECT
+    r.id,
+    'Read',
+    w.id
+FROM workflows AS w
+CROSS JOIN roles AS r
+WHERE
+    r.id IN (
+        '4e8081b6-8aa6-4275-af0c-2fa2da557d28', -- registered users
+        'fd8e87bf-515c-4f36-8da6-1a53702ff102' -- anonymous users
+    )
+    AND NOT EXISTS (
+        SELECT 1 FROM permissions AS p
+        WHERE p.workflow_id = w.id
+    );
@@ -0,0 +1,25 @@
+use super::database_migration::{DatabaseVersion, Migration};
+use crate::{contexts::migrations::Migration0034WorkflowPermissions, error::Result};
+use async_trait::async_trait;
+use tokio_postgres::Transaction;
+
+/// This migration stores the API token that a schedule was created with
+pub struct Migration0035ScheduleApiTokens;
+
+#[async_trait]
+impl Migration for Migration0035ScheduleApiTokens {
+    fn prev_version(&self) -> Option<DatabaseVersion> {
+        Some(Migration0034WorkflowPermissions.version())
+    }
+
+    fn version(&self) -> DatabaseVersion {
+        "0035_schedule_api_tokens".into()
+    }
+
+    async fn migrate(&self, tx: &Transaction<'_>) -> Result<()> {
+        tx.batch_execute(include_str!("migration_0035_schedule_api_tokens.sql"))
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,3 @@
+-- schedules that were created with an API token run with the scope of the token
+ALTER TABLE schedules
+ADD COLUMN api_token_id uuid REFERENCES api_tokens (id) ON DELETE CASCADE;
@@ -0,0 +1,25 @@
+use super::database_migration::{DatabaseVersion, Migration};
+use crate::{contexts::migrations::Migration0035ScheduleApiTokens, error::Result};
+use async_trait::async_trait;
+use tokio_postgres::Transaction;
+
+/// This migration stores which task manager runs a task, s.t. only the tasks of stopped task managers are failed
+pub struct Migration0036TaskManagerLeases;
+
+#[async_trait]
+impl Migration for Migration0036TaskManagerLeases {
+    fn prev_version(&self) -> Option<DatabaseVersion> {
+        Some(Migration0035ScheduleApiTokens.version())
+    }
+
+    fn version(&self) -> DatabaseVersion {
+        "0036_task_manager_leases".into()
+    }
+
+    async fn migrate(&self, tx: &Transaction<'_>) -> Result<()> {
+        tx.batch_execute(include_str!("migration_0036_task_manager_leases.sql"))
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,7 @@
+-- task managers renew their lease periodically, unfinished tasks of expired leases are failed
+CREATE TABLE task_manager_leases (
+    id uuid PRIMARY KEY,
+    heartbeat timestamp with time zone NOT NULL
+);
+
+ALTER TABLE tasks ADD COLUMN task_manager_id uuid;
@@ -1,3 +1,4 @@
+use crate::contexts::migrations::migration_0019_ml_model_no_data::Migration0019MlModelNoData;
 pub use crate::contexts::migrations::{
     current_schema::CurrentSchemaMigration,
     migration_0016_merge_providers::Migration0016MergeProviders,
@@ -6,10 +7,20 @@ pub use crate::contexts::migrations::{
     migration_0020_provider_permissions::Migration0020ProviderPermissions,
     migration_0021_default_permissions_for_existing_providers::Migration0021DefaultPermissionsForExistingProviders,
     migration_0022_permission_queries::Migration0022PermissionQueries,
-};
-use crate::contexts::migrations::{
-    migration_0019_ml_model_no_data::Migration0019MlModelNoData,
     migration_0023_wildlive_oidc::Migration0023WildliveOidc,
+    migration_0024_ml_model_output_bands::Migration0024MlModelOutputBands,
+    migration_0025_ml_model_time_steps::Migration0025MlModelTimeSteps,
+    migration_0026_ml_model_training_metrics::Migration0026MlModelTrainingMetrics,
+    migration_0027_ml_model_versions::Migration0027MlModelVersions,
+    migration_0028_persistent_tasks::Migration0028PersistentTasks,
+    migration_0029_schedules::Migration0029Schedules,
+    migration_0030_quota_limits::Migration0030QuotaLimits,
+    migration_0031_permission_grants::Migration0031PermissionGrants,
+    migration_0032_api_tokens::Migration0032ApiTokens,
+    migration_0033_quota_limit_usage::Migration0033QuotaLimitUsage,
+    migration_0034_workflow_permissions::Migration0034WorkflowPermissions,
+    migration_0035_schedule_api_tokens::Migration0035ScheduleApiTokens,
+    migration_0036_task_manager_leases::Migration0036TaskManagerLeases,
 };
 pub use database_migration::{
     DatabaseVersion, Migration, MigrationResult, initialize_database, migrate_database,
@@ -25,6 +36,19 @@ mod migration_0020_provider_permissions;
 mod migration_0021_default_permissions_for_existing_providers;
 mod migration_0022_permission_queries;
 mod migration_0023_wildlive_oidc;
+mod migration_0024_ml_model_output_bands;
+mod migration_0025_ml_model_time_steps;
+mod migration_0026_ml_model_training_metrics;
+mod migration_0027_ml_model_versions;
+mod migration_0028_persistent_tasks;
+mod migration_0029_schedules;
+mod migration_0030_quota_limits;
+mod migration_0031_permission_grants;
+mod migration_0032_api_tokens;
+mod migration_0033_quota_limit_usage;
+mod migration_0034_workflow_permissions;
+mod migration_0035_schedule_api_tokens;
+mod migration_0036_task_manager_leases;
 
 #[cfg(test)]
 mod schema_info;
@@ -54,6 +78,19 @@ pub fn all_migrations() -> Vec<Box<dyn Migration>> {
         Box::new(Migration0021DefaultPermissionsForExistingProviders),
         Box::new(Migration0022PermissionQueries),
         Box::new(Migration0023WildliveOidc),
+        Box::new(Migration0024MlModelOutputBands),
+        Box::new(Migration0025MlModelTimeSteps),
+        Box::new(Migration0026MlModelTrainingMetrics),
+        Box::new(Migration0027MlModelVersions),
+        Box::new(Migration0028PersistentTasks),
+        Box::new(Migration0029Schedules),
+        Box::new(Migration0030QuotaLimits),
+        Box::new(Migration0031PermissionGrants),
+        Box::new(Migration0032ApiTokens),
+        Box::new(Migration0033QuotaLimitUsage),
+        Box::new(Migration0034WorkflowPermissions),
+        Box::new(Migration0035ScheduleApiTokens),
+        Box::new(Migration0036TaskManagerLeases),
     ]
 }
 
@@ -8,12 +8,13 @@ use crate::layers::listing::LayerCollectionProvider;
 use crate::layers::storage::{LayerDb, LayerProviderDb};
 use crate::machine_learning::MlModelDb;
 use crate::permissions::PermissionDb;
+use crate::schedules::ScheduleDb;
 use crate::tasks::{TaskContext, TaskManager};
 use crate::users::{OidcManager, RoleDb, UserAuth, UserDb};
 use crate::{projects::ProjectDb, workflows::registry::WorkflowRegistry};
 use async_trait::async_trait;
 use geoengine_datatypes::dataset::{DataId, DataProviderId, ExternalDataId, LayerId};
-use geoengine_datatypes::machine_learning::MlModelName;
+use geoengine_datatypes::machine_learning::{MlModelName, MlModelVersion};
 use geoengine_datatypes::primitives::{RasterQueryRectangle, VectorQueryRectangle};
 use geoengine_datatypes::raster::TilingSpecification;
 use geoengine_operators::cache::cache_operator::InitializedCacheOperator;
@@ -108,6 +109,7 @@ pub trait GeoEngineDb:
     + PermissionDb
     + ProjectDb
     + RoleDb
+    + ScheduleDb
     + UserDb
     + WorkflowRegistry
 {
@@ -322,8 +324,9 @@ where
     async fn ml_model_loading_info(
         &self,
         name: &MlModelName,
+        version: MlModelVersion,
     ) -> Result<MlModelLoadingInfo, geoengine_operators::error::Error> {
-        let ml_model = self.db.load_model(&name.clone()).await.map_err(|source| {
+        let ml_model = self.db.load_model(name, version).await.map_err(|source| {
             geoengine_operators::error::Error::CannotResolveMlModelName {
                 name: name.clone(),
                 source: Box::new(source),
@@ -470,20 +473,48 @@ pub struct QuotaCheckerImpl<U: UserDb> {
     pub(crate) user_db: U,
 }
 
-#[async_trait]
-impl<U: UserDb> QuotaCheck for QuotaCheckerImpl<U> {
-    async fn ensure_quota_available(&self) -> geoengine_operators::util::Result<()> {
-        // TODO: cache the result, s.th. other operators in the same workflow can re-use it
-        let quota_check_enabled = crate::config::get_config_element::<crate::config::Quota>()
+impl<U: UserDb> QuotaCheckerImpl<U> {
+    fn quota_check_enabled() -> geoengine_operators::util::Result<bool> {
+        Ok(crate::config::get_config_element::<crate::config::Quota>()
             .map_err(
                 |e| geoengine_operators::error::Error::CreatingProcessorFailed {
                     source: Box::new(e),
                 },
             )?
             .mode
-            == QuotaTrackingMode::Check;
+            == QuotaTrackingMode::Check)
+    }
+
+    /// Fails if one of the quota limits of the user on `data` (or without data) is exhausted
+    async fn ensure_quota_limits_available(
+        &self,
+        data: Option<&str>,
+    ) -> geoengine_operators::util::Result<()> {
+        let exhausted_limit = self
+            .user_db
+            .exhausted_quota_limit(data)
+            .await
+            .map_err(
+                |e| geoengine_operators::error::Error::CreatingProcessorFailed {
+                    source: Box::new(e),
+                },
+            )?;
+
+        if let Some(limit) = exhausted_limit {
+            return Err(geoengine_operators::error::Error::CreatingProcessorFailed {
+                source: Box::new(crate::quota::QuotaError::QuotaLimitExhausted { limit }),
+            });
+        }
+
+        Ok(())
+    }
+}
 
-        if !quota_check_enabled {
+#[async_trait]
+impl<U: UserDb> QuotaCheck for QuotaCheckerImpl<U> {
+    async fn ensure_quota_available(&self) -> geoengine_operators::util::Result<()> {
+        // TODO: cache the result, s.th. other operators in the same workflow can re-use it
+        if !Self::quota_check_enabled()? {
             return Ok(());
         }
 
@@ -499,6 +530,17 @@ impl<U: UserDb> QuotaCheck for QuotaCheckerImpl<U> {
             });
         }
 
-        Ok(())
+        self.ensure_quota_limits_available(None).await
+    }
+
+    async fn ensure_data_quota_available(
+        &self,
+        data: &str,
+    ) -> geoengine_operators::util::Result<()> {
+        if !Self::quota_check_enabled()? {
+            return Ok(());
+        }
+
+        self.ensure_quota_limits_available(Some(data)).await
     }
 }
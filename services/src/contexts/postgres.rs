@@ -16,7 +16,7 @@ use crate::layers::add_from_directory::{
 };
 use crate::machine_learning::error::MachineLearningError;
 use crate::quota::{QuotaTrackingFactory, initialize_quota_tracking};
-use crate::tasks::SimpleTaskManagerContext;
+use crate::tasks::{SimpleTaskManagerBackend, SimpleTaskManagerContext, TaskLimits};
 use crate::tasks::{TypedTaskManagerBackend, UserTaskManager};
 use crate::users::OidcManager;
 use crate::users::{UserAuth, UserSession};
@@ -38,6 +38,7 @@ use rayon::ThreadPool;
 use snafu::ResultExt;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio_postgres::error::SqlState;
 use tracing::info;
 use uuid::Uuid;
@@ -85,6 +86,8 @@ where
 
         Self::create_pro_database(pool.get().await?).await?;
 
+        let task_manager = Self::create_task_manager(&pool).await?;
+
         let db = PostgresDb::new(pool.clone(), UserSession::admin_session());
         let quota = initialize_quota_tracking(
             quota_config.mode,
@@ -94,7 +97,7 @@ where
         );
 
         Ok(PostgresContext {
-            task_manager: Default::default(),
+            task_manager,
             thread_pool: create_rayon_thread_pool(0),
             exe_ctx_tiling_spec,
             query_ctx_chunk_size,
@@ -120,6 +123,8 @@ where
 
         Self::create_pro_database(pool.get().await?).await?;
 
+        let task_manager = Self::create_task_manager(&pool).await?;
+
         let db = PostgresDb::new(pool.clone(), UserSession::admin_session());
         let quota = initialize_quota_tracking(
             quota_config.mode,
@@ -129,7 +134,7 @@ where
         );
 
         Ok(PostgresContext {
-            task_manager: Default::default(),
+            task_manager,
             thread_pool: create_rayon_thread_pool(0),
             exe_ctx_tiling_spec: TestDefault::test_default(),
             query_ctx_chunk_size: TestDefault::test_default(),
@@ -165,6 +170,8 @@ where
 
         let created_schema = Self::create_pro_database(pool.get().await?).await?;
 
+        let task_manager = Self::create_task_manager(&pool).await?;
+
         let db = PostgresDb::new(pool.clone(), UserSession::admin_session());
         let quota = initialize_quota_tracking(
             quota_config.mode,
@@ -174,7 +181,7 @@ where
         );
 
         let app_ctx = PostgresContext {
-            task_manager: Default::default(),
+            task_manager,
             thread_pool: create_rayon_thread_pool(0),
             exe_ctx_tiling_spec,
             query_ctx_chunk_size,
@@ -204,6 +211,35 @@ where
         Ok(app_ctx)
     }
 
+    /// Creates the task manager that persists tasks in the database.
+    /// Tasks that were pending or running when their server stopped cannot be resumed, so they are marked as failed.
+    /// Since several servers may share the database, this only applies to tasks of servers whose lease expired.
+    async fn create_task_manager(
+        pool: &Pool<PostgresConnectionManager<Tls>>,
+    ) -> Result<Arc<TypedTaskManagerBackend>> {
+        let config = get_config_element::<crate::config::TaskManager>()?;
+
+        let db = PostgresDb::new(pool.clone(), UserSession::admin_session());
+
+        let limits = TaskLimits {
+            max_running_tasks: config.max_running_tasks,
+            max_running_tasks_per_user: config.max_running_tasks_per_user,
+        };
+
+        let task_manager = SimpleTaskManagerBackend::new(limits, Some(Arc::new(db)));
+
+        let lease = Duration::from_secs(config.lease_seconds);
+
+        let failed_tasks = task_manager.renew_lease(lease).await?;
+        if failed_tasks > 0 {
+            info!("Marked {failed_tasks} unfinished tasks of stopped servers as failed.");
+        }
+
+        crate::util::spawn(task_manager.clone().run_lease_renewal(lease));
+
+        Ok(Arc::new(TypedTaskManagerBackend::new(task_manager)))
+    }
+
     #[allow(clippy::too_many_lines)]
     /// Creates the database schema. Returns true if the schema was created, false if it already existed.
     pub(crate) async fn create_pro_database(
@@ -483,14 +519,14 @@ mod tests {
         INTERNAL_PROVIDER_ID, LayerDb, LayerProviderDb, LayerProviderListing,
         LayerProviderListingOptions,
     };
-    use crate::machine_learning::{MlModel, MlModelDb, MlModelIdAndName};
-    use crate::permissions::{Permission, PermissionDb, Role, RoleDescription, RoleId};
+    use crate::machine_learning::{MlModel, MlModelDb, MlModelIdAndName, MlModelListOptions};
+    use crate::permissions::{Permission, PermissionDb, ResourceId, Role, RoleDescription, RoleId};
     use crate::projects::{
         CreateProject, LayerUpdate, LoadVersion, OrderBy, Plot, PlotUpdate, PointSymbology,
         ProjectDb, ProjectId, ProjectLayer, ProjectListOptions, ProjectListing, STRectangle,
         UpdateProject,
     };
-    use crate::users::{OidcTokens, SessionTokenStore};
+    use crate::users::{ApiTokenId, ApiTokenScope, OidcTokens, SessionTokenStore};
     use crate::users::{RoleDb, UserClaims, UserCredentials, UserDb, UserId, UserRegistration};
     use crate::util::tests::mock_oidc::{MockRefreshServerConfig, mock_refresh_server};
     use crate::util::tests::{MockQuotaTracking, admin_login, register_ndvi_workflow_helper};
@@ -500,7 +536,7 @@ mod tests {
     use futures::join;
     use geoengine_datatypes::collections::VectorDataType;
     use geoengine_datatypes::dataset::{DataProviderId, LayerId};
-    use geoengine_datatypes::machine_learning::MlTensorShape3D;
+    use geoengine_datatypes::machine_learning::{MlModelVersion, MlTensorShape3D};
     use geoengine_datatypes::primitives::{
         BoundingBox2D, Coordinate2D, DateTime, Duration, FeatureDataType, Measurement,
         RasterQueryRectangle, SpatialResolution, TimeGranularity, TimeInstance, TimeInterval,
@@ -518,7 +554,7 @@ mod tests {
     };
     use geoengine_operators::machine_learning::MlModelMetadata;
     use geoengine_operators::mock::{MockPointSource, MockPointSourceParams};
-    use geoengine_operators::plot::{Statistics, StatisticsParams};
+    use geoengine_operators::plot::{PercentileEstimation, Statistics, StatisticsParams};
     use geoengine_operators::source::{
         CsvHeader, FileNotFoundHandling, FormatSpecifics, GdalDatasetGeoTransform,
         GdalDatasetParameters, GdalLoadingInfo, GdalMetaDataList, GdalMetaDataRegular,
@@ -736,6 +772,8 @@ mod tests {
                     params: StatisticsParams {
                         column_names: vec![],
                         percentiles: vec![],
+                        percentile_estimation: PercentileEstimation::PSquare,
+                        group_by: None,
                     },
                     sources: MultipleRasterOrSingleVectorSource {
                         source: Raster(vec![]),
@@ -989,6 +1027,105 @@ mod tests {
         );
     }
 
+    #[ge_context::test]
+    async fn it_checks_permissions_of_workflows(app_ctx: PostgresContext<NoTls>) {
+        let workflow = Workflow {
+            operator: TypedOperator::Vector(
+                MockPointSource {
+                    params: MockPointSourceParams {
+                        points: vec![Coordinate2D::new(1., 2.); 3],
+                    },
+                }
+                .boxed(),
+            ),
+        };
+
+        let session1 = app_ctx.create_anonymous_session().await.unwrap();
+        let session2 = app_ctx.create_anonymous_session().await.unwrap();
+        let session3 = app_ctx.create_anonymous_session().await.unwrap();
+
+        let db1 = app_ctx.session_context(session1).db();
+        let db2 = app_ctx.session_context(session2).db();
+        let db3 = app_ctx.session_context(session3).db();
+
+        let id = db1.register_workflow(workflow.clone()).await.unwrap();
+        assert!(db1.has_permission(id, Permission::Owner).await.unwrap());
+
+        // registering an existing workflow only grants read access
+        assert_eq!(db2.register_workflow(workflow).await.unwrap(), id);
+        assert!(db2.load_workflow(&id).await.is_ok());
+        assert!(!db2.has_permission(id, Permission::Owner).await.unwrap());
+
+        assert!(db3.load_workflow(&id).await.is_err());
+    }
+
+    #[ge_context::test]
+    async fn it_reads_workflows_of_shared_projects(app_ctx: PostgresContext<NoTls>) {
+        let session1 = app_ctx.create_anonymous_session().await.unwrap();
+        let session2 = app_ctx.create_anonymous_session().await.unwrap();
+
+        let db1 = app_ctx.session_context(session1).db();
+        let db2 = app_ctx.session_context(session2.clone()).db();
+
+        let workflow_id = db1
+            .register_workflow(Workflow {
+                operator: TypedOperator::Vector(
+                    MockPointSource {
+                        params: MockPointSourceParams {
+                            points: vec![Coordinate2D::new(1., 2.); 3],
+                        },
+                    }
+                    .boxed(),
+                ),
+            })
+            .await
+            .unwrap();
+
+        let project_id = db1
+            .create_project(CreateProject {
+                name: "Shared".into(),
+                description: "Shared project".into(),
+                bounds: STRectangle::new(
+                    SpatialReferenceOption::Unreferenced,
+                    0.,
+                    0.,
+                    1.,
+                    1.,
+                    0,
+                    1,
+                )
+                .unwrap(),
+                time_step: None,
+            })
+            .await
+            .unwrap();
+
+        db1.update_project(UpdateProject {
+            id: project_id,
+            name: None,
+            description: None,
+            layers: Some(vec![LayerUpdate::UpdateOrInsert(ProjectLayer {
+                workflow: workflow_id,
+                name: "Layer".into(),
+                symbology: PointSymbology::default().into(),
+                visibility: Default::default(),
+            })]),
+            plots: None,
+            bounds: None,
+            time_step: None,
+        })
+        .await
+        .unwrap();
+
+        assert!(db2.load_workflow(&workflow_id).await.is_err());
+
+        db1.add_permission(session2.user.id.into(), project_id, Permission::Read)
+            .await
+            .unwrap();
+
+        assert!(db2.load_workflow(&workflow_id).await.is_ok());
+    }
+
     #[allow(clippy::too_many_lines)]
     #[ge_context::test]
     async fn it_persists_datasets(app_ctx: PostgresContext<NoTls>) {
@@ -4964,14 +5101,19 @@ mod tests {
                     geoengine_operators::machine_learning::MlModelInputNoDataHandling::SkipIfNoData,
                 output_no_data_handling:
                     geoengine_operators::machine_learning::MlModelOutputNoDataHandling::NanIsNoData,
+                output_bands: vec![],
+                input_time_steps: 1,
             },
             name: MlModelName::try_new(None::<&str>, "myUnrealModel").unwrap(),
             upload: upload_id,
+            training_metrics: None,
+            version: 1,
         };
 
         let MlModelIdAndName {
             id: model_id,
             name: model_name,
+            ..
         } = db.add_model(model).await.unwrap();
 
         assert_eq!(
@@ -4982,4 +5124,338 @@ mod tests {
             model_id
         );
     }
+
+    #[ge_context::test]
+    async fn it_versions_ml_models(app_ctx: PostgresContext<NoTls>) {
+        let admin_session = UserSession::admin_session();
+        let db = app_ctx.session_context(admin_session.clone()).db();
+
+        let upload_id = UploadId::new();
+        let upload = Upload {
+            id: upload_id,
+            files: vec![],
+        };
+        db.create_upload(upload).await.unwrap();
+
+        let model = MlModel {
+            description: "No real model here".to_owned(),
+            display_name: "my unreal model".to_owned(),
+            file_name: "myUnrealmodel.onnx".to_owned(),
+            metadata: MlModelMetadata {
+                input_type: RasterDataType::F32,
+                input_shape: MlTensorShape3D::new_single_pixel_bands(17),
+                output_shape: MlTensorShape3D::new_single_pixel_single_band(),
+                output_type: RasterDataType::F64,
+                input_no_data_handling:
+                    geoengine_operators::machine_learning::MlModelInputNoDataHandling::SkipIfNoData,
+                output_no_data_handling:
+                    geoengine_operators::machine_learning::MlModelOutputNoDataHandling::NanIsNoData,
+                output_bands: vec![],
+                input_time_steps: 1,
+            },
+            name: MlModelName::try_new(None::<&str>, "myUnrealModel").unwrap(),
+            upload: upload_id,
+            training_metrics: None,
+            version: 1,
+        };
+
+        let first = db.add_model(model.clone()).await.unwrap();
+        assert_eq!(first.version, 1);
+
+        let second = db
+            .add_model(MlModel {
+                description: "A second version".to_owned(),
+                ..model.clone()
+            })
+            .await
+            .unwrap();
+        assert_eq!(second.version, 2);
+        assert_eq!(second.name, first.name);
+
+        let latest = db
+            .load_model(&model.name, MlModelVersion::Latest)
+            .await
+            .unwrap();
+        assert_eq!(latest.version, 2);
+        assert_eq!(latest.description, "A second version");
+
+        let pinned = db
+            .load_model(&model.name, MlModelVersion::Version(1))
+            .await
+            .unwrap();
+        assert_eq!(pinned, model);
+
+        assert!(matches!(
+            db.load_model(&model.name, MlModelVersion::Version(3)).await,
+            Err(MachineLearningError::ModelVersionNotFound { version: 3, .. })
+        ));
+
+        let versions = db.list_model_versions(&model.name).await.unwrap();
+        assert_eq!(
+            versions.iter().map(|m| m.version).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+
+        let models = db
+            .list_models(&MlModelListOptions {
+                offset: 0,
+                limit: 10,
+            })
+            .await
+            .unwrap();
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].version, 2);
+
+        assert_eq!(
+            db.resolve_model_name_to_id(&model.name)
+                .await
+                .unwrap()
+                .unwrap(),
+            second.id
+        );
+
+        // the latest version of a user who may only read the first version is the first one
+        let session2 = app_ctx.create_anonymous_session().await.unwrap();
+        db.add_permission(
+            session2.user.id.into(),
+            ResourceId::MlModel(first.id),
+            Permission::Read,
+        )
+        .await
+        .unwrap();
+
+        let latest = app_ctx
+            .session_context(session2)
+            .db()
+            .load_model(&model.name, MlModelVersion::Latest)
+            .await
+            .unwrap();
+        assert_eq!(latest.version, 1);
+    }
+
+    #[ge_context::test]
+    async fn it_restricts_datasets_and_models_to_api_token_resources(
+        app_ctx: PostgresContext<NoTls>,
+    ) {
+        let session = app_ctx.create_anonymous_session().await.unwrap();
+        let db = app_ctx.session_context(session.clone()).db();
+
+        let meta = StaticMetaData {
+            loading_info: OgrSourceDataset {
+                file_name: Default::default(),
+                layer_name: String::new(),
+                data_type: None,
+                time: Default::default(),
+                default_geometry: None,
+                columns: None,
+                force_ogr_time_filter: false,
+                force_ogr_spatial_filter: false,
+                on_error: OgrSourceErrorSpec::Ignore,
+                sql_query: None,
+                attribute_query: None,
+                cache_ttl: CacheTtlSeconds::default(),
+            },
+            result_descriptor: VectorResultDescriptor {
+                data_type: VectorDataType::Data,
+                spatial_reference: SpatialReferenceOption::Unreferenced,
+                columns: Default::default(),
+                time: None,
+                bbox: None,
+            },
+            phantom: Default::default(),
+        };
+
+        let mut dataset_ids = vec![];
+        for display_name in ["first", "second"] {
+            let ds = AddDataset {
+                name: None,
+                display_name: display_name.to_string(),
+                description: String::new(),
+                source_operator: "OgrSource".to_string(),
+                symbology: None,
+                provenance: None,
+                tags: None,
+            };
+
+            dataset_ids.push(db.add_dataset(ds, meta.clone().into()).await.unwrap().id);
+        }
+
+        let upload_id = UploadId::new();
+        db.create_upload(Upload {
+            id: upload_id,
+            files: vec![],
+        })
+        .await
+        .unwrap();
+
+        let mut models = vec![];
+        for name in ["firstModel", "secondModel"] {
+            let model = MlModel {
+                description: String::new(),
+                display_name: name.to_owned(),
+                file_name: "model.onnx".to_owned(),
+                metadata: MlModelMetadata {
+                    input_type: RasterDataType::F32,
+                    input_shape: MlTensorShape3D::new_single_pixel_bands(1),
+                    output_shape: MlTensorShape3D::new_single_pixel_single_band(),
+                    output_type: RasterDataType::F32,
+                    input_no_data_handling:
+                        geoengine_operators::machine_learning::MlModelInputNoDataHandling::SkipIfNoData,
+                    output_no_data_handling:
+                        geoengine_operators::machine_learning::MlModelOutputNoDataHandling::NanIsNoData,
+                    output_bands: vec![],
+                    input_time_steps: 1,
+                },
+                name: MlModelName::try_new(None::<&str>, name).unwrap(),
+                upload: upload_id,
+                training_metrics: None,
+                version: 1,
+            };
+
+            let MlModelIdAndName { id, .. } = db.add_model(model.clone()).await.unwrap();
+            models.push((id, model.name));
+        }
+
+        let token_session = UserSession {
+            api_token: Some(ApiTokenScope {
+                token: ApiTokenId::new(),
+                read_only: true,
+                resources: Some(vec![
+                    dataset_ids[0].into(),
+                    ResourceId::MlModel(models[0].0),
+                ]),
+            }),
+            ..session
+        };
+        let token_db = app_ctx.session_context(token_session).db();
+
+        let datasets = token_db
+            .list_datasets(DatasetListOptions {
+                filter: None,
+                order: crate::datasets::listing::OrderBy::NameAsc,
+                offset: 0,
+                limit: 10,
+                tags: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            datasets.iter().map(|d| d.id).collect::<Vec<_>>(),
+            vec![dataset_ids[0]]
+        );
+
+        assert!(token_db.load_dataset(&dataset_ids[0]).await.is_ok());
+        assert!(token_db.load_dataset(&dataset_ids[1]).await.is_err());
+        assert!(token_db.load_provenance(&dataset_ids[1]).await.is_err());
+        assert!(token_db.load_loading_info(&dataset_ids[1]).await.is_err());
+
+        let listed_models = token_db
+            .list_models(&MlModelListOptions {
+                offset: 0,
+                limit: 10,
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            listed_models
+                .iter()
+                .map(|m| m.name.clone())
+                .collect::<Vec<_>>(),
+            vec![models[0].1.clone()]
+        );
+
+        assert!(
+            token_db
+                .load_model(&models[0].1, MlModelVersion::Latest)
+                .await
+                .is_ok()
+        );
+        assert!(
+            token_db
+                .load_model(&models[1].1, MlModelVersion::Latest)
+                .await
+                .is_err()
+        );
+    }
+
+    #[ge_context::test]
+    async fn it_rejects_creating_resources_with_restricted_api_tokens(
+        app_ctx: PostgresContext<NoTls>,
+    ) {
+        let session = app_ctx.create_anonymous_session().await.unwrap();
+
+        let token_session = UserSession {
+            api_token: Some(ApiTokenScope {
+                token: ApiTokenId::new(),
+                read_only: true,
+                resources: None,
+            }),
+            ..session.clone()
+        };
+        let db = app_ctx.session_context(token_session).db();
+
+        // workflows can be registered for querying them, but they are not owned
+        let workflow_id = db
+            .register_workflow(Workflow {
+                operator: TypedOperator::Vector(
+                    MockPointSource {
+                        params: MockPointSourceParams {
+                            points: vec![Coordinate2D::new(1., 2.); 3],
+                        },
+                    }
+                    .boxed(),
+                ),
+            })
+            .await
+            .unwrap();
+        assert!(db.load_workflow(&workflow_id).await.is_ok());
+        assert!(
+            !app_ctx
+                .session_context(session)
+                .db()
+                .has_permission(workflow_id, Permission::Owner)
+                .await
+                .unwrap()
+        );
+
+        assert!(
+            db.create_upload(Upload {
+                id: UploadId::new(),
+                files: vec![],
+            })
+            .await
+            .is_err()
+        );
+
+        assert!(
+            db.create_project(CreateProject {
+                name: "Test".to_string(),
+                description: "Test".to_string(),
+                bounds: STRectangle::new(
+                    SpatialReferenceOption::Unreferenced,
+                    0.,
+                    0.,
+                    1.,
+                    1.,
+                    0,
+                    1,
+                )
+                .unwrap(),
+                time_step: None,
+            })
+            .await
+            .is_err()
+        );
+
+        assert!(
+            db.list_projects(ProjectListOptions {
+                order: OrderBy::NameAsc,
+                offset: 0,
+                limit: 10,
+            })
+            .await
+            .unwrap()
+            .is_empty()
+        );
+    }
 }
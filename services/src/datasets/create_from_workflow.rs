@@ -1,21 +1,25 @@
 use super::{DatasetIdAndName, DatasetName};
-use crate::api::model::datatypes::RasterQueryRectangle;
+use crate::api::model::datatypes::{RasterQueryRectangle, VectorQueryRectangle};
 use crate::contexts::SessionContext;
 use crate::datasets::AddDataset;
-use crate::datasets::listing::DatasetProvider;
+use crate::datasets::listing::{DatasetProvider, Provenance};
 use crate::datasets::storage::{DatasetDefinition, DatasetStore, MetaDataDefinition};
-use crate::datasets::upload::{UploadId, UploadRootPath};
+use crate::datasets::upload::{FileId, FileUpload, Upload, UploadDb, UploadId, UploadRootPath};
 use crate::error;
+use crate::layers::storage::LayerProviderDb;
+use crate::quota::ensure_estimated_quota_available;
 use crate::tasks::{Task, TaskContext, TaskId, TaskManager, TaskStatusInfo};
 use crate::workflows::workflow::{Workflow, WorkflowId};
 use async_trait::async_trait;
+use geoengine_datatypes::dataset::DataId;
 use geoengine_datatypes::error::ErrorSource;
-use geoengine_datatypes::primitives::TimeInterval;
+use geoengine_datatypes::primitives::{BandSelection, SpatialPartition2D, TimeInterval};
 use geoengine_datatypes::spatial_reference::SpatialReference;
 use geoengine_datatypes::util::Identifier;
-use geoengine_operators::call_on_generic_raster_processor_gdal_types;
+use geoengine_operators::engine::StaticMetaData;
 use geoengine_operators::engine::{
-    ExecutionContext, InitializedRasterOperator, RasterResultDescriptor, WorkflowOperatorPath,
+    ExecutionContext, InitializedRasterOperator, InitializedVectorOperator, RasterResultDescriptor,
+    WorkflowOperatorPath,
 };
 use geoengine_operators::source::{
     GdalLoadingInfoTemporalSlice, GdalMetaDataList, GdalMetaDataStatic,
@@ -24,6 +28,12 @@ use geoengine_operators::util::raster_stream_to_geotiff::{
     GdalCompressionNumThreads, GdalGeoTiffDatasetMetadata, GdalGeoTiffOptions, ToGeoTiffProgress,
     ToGeoTiffProgressConsumer, raster_stream_to_geotiff,
 };
+use geoengine_operators::util::vector_stream_to_ogr::{
+    OgrVectorDatasetInfo, OgrVectorDatasetMetadata, OgrVectorFormat, vector_stream_to_ogr,
+};
+use geoengine_operators::{
+    call_on_generic_raster_processor_gdal_types, call_on_generic_vector_processor,
+};
 use serde::{Deserialize, Serialize};
 use snafu::{ResultExt, ensure};
 use std::path::PathBuf;
@@ -219,6 +229,15 @@ pub async fn schedule_raster_dataset_from_workflow_task<C: SessionContext>(
         }
     }
 
+    ensure_estimated_quota_available(
+        ctx.as_ref(),
+        workflow.operator.clone(),
+        info.query.spatial_bounds.into(),
+        info.query.spatial_resolution.into(),
+        info.query.time_interval.into(),
+    )
+    .await?;
+
     let upload = UploadId::new();
     let upload_path = upload.root_path()?;
     fs::create_dir_all(&upload_path)
@@ -313,3 +332,733 @@ async fn create_dataset<C: SessionContext>(
 
     Ok(result)
 }
+
+/// parameter for the append to dataset from workflow handler (body)
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+#[schema(example = json!({"dataset": "foo", "timeInterval": {"start": 1_388_534_400_000_i64, "end": 1_391_212_800_000_i64}}))]
+#[serde(rename_all = "camelCase")]
+pub struct AppendRasterDatasetFromWorkflow {
+    pub dataset: DatasetName,
+    /// Time steps of the workflow in this interval that are not yet part of the dataset are appended
+    pub time_interval: crate::api::model::datatypes::TimeInterval,
+    #[schema(default = default_as_cog)]
+    #[serde(default = "default_as_cog")]
+    pub as_cog: bool,
+}
+
+/// response of the append to dataset from workflow handler
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AppendRasterDatasetFromWorkflowResult {
+    pub dataset: DatasetName,
+    /// The upload that contains the appended files, if any time step was appended
+    pub upload: Option<UploadId>,
+    pub appended_time_steps: Vec<crate::api::model::datatypes::TimeInterval>,
+}
+
+impl TaskStatusInfo for AppendRasterDatasetFromWorkflowResult {}
+
+pub struct AppendRasterDatasetFromWorkflowTask<C: SessionContext> {
+    pub source_name: String,
+    pub workflow_id: WorkflowId,
+    pub workflow: Workflow,
+    pub ctx: Arc<C>,
+    pub info: AppendRasterDatasetFromWorkflow,
+    pub upload: UploadId,
+    pub file_path: PathBuf,
+    pub compression_num_threads: GdalCompressionNumThreads,
+}
+
+fn cannot_append(dataset_name: &DatasetName, reason: &str) -> error::Error {
+    error::Error::CannotAppendToDataset {
+        dataset_name: dataset_name.to_string(),
+        reason: reason.to_owned(),
+    }
+}
+
+/// The result descriptor and time slices of a dataset that can be extended
+fn appendable_time_slices(
+    dataset_name: &DatasetName,
+    meta_data: MetaDataDefinition,
+) -> error::Result<(RasterResultDescriptor, Vec<GdalLoadingInfoTemporalSlice>)> {
+    match meta_data {
+        MetaDataDefinition::GdalStatic(meta_data) => {
+            let time = meta_data
+                .time
+                .ok_or_else(|| cannot_append(dataset_name, "the dataset has no time interval"))?;
+            let slice = GdalLoadingInfoTemporalSlice {
+                time,
+                params: Some(meta_data.params),
+                cache_ttl: meta_data.cache_ttl,
+            };
+            Ok((meta_data.result_descriptor, vec![slice]))
+        }
+        MetaDataDefinition::GdalMetaDataList(meta_data) => {
+            Ok((meta_data.result_descriptor, meta_data.params))
+        }
+        // the time steps of a regular dataset are derived from its file name pattern, so files of other time steps cannot be added
+        MetaDataDefinition::GdalMetaDataRegular(_) => {
+            Err(error::Error::CannotAppendToRegularDataset {
+                dataset_name: dataset_name.to_string(),
+            })
+        }
+        _ => Err(cannot_append(
+            dataset_name,
+            "only datasets with a static or a list of time slices can be extended",
+        )),
+    }
+}
+
+impl<C: SessionContext> AppendRasterDatasetFromWorkflowTask<C> {
+    fn cannot_append(&self, reason: &str) -> error::Error {
+        cannot_append(&self.info.dataset, reason)
+    }
+
+    /// Registers the files of the upload directory as an upload of the session's user
+    async fn create_upload(&self) -> error::Result<()> {
+        let mut files = Vec::new();
+
+        let mut entries = fs::read_dir(&self.file_path)
+            .await
+            .context(crate::error::Io)?;
+        while let Some(entry) = entries.next_entry().await.context(crate::error::Io)? {
+            let metadata = entry.metadata().await.context(crate::error::Io)?;
+            if !metadata.is_file() {
+                continue;
+            }
+
+            files.push(FileUpload {
+                id: FileId::new(),
+                name: entry.file_name().to_string_lossy().into_owned(),
+                byte_size: metadata.len(),
+            });
+        }
+
+        self.ctx
+            .db()
+            .create_upload(Upload {
+                id: self.upload,
+                files,
+            })
+            .await
+    }
+
+    #[allow(clippy::too_many_lines)]
+    async fn process(
+        &self,
+        task_ctx: ToGeoTiffTaskContext<C::TaskContext>,
+    ) -> error::Result<AppendRasterDatasetFromWorkflowResult> {
+        let db = self.ctx.db();
+
+        let dataset_id = db
+            .resolve_dataset_name_to_id(&self.info.dataset)
+            .await?
+            .ok_or_else(|| error::Error::UnknownDatasetName {
+                dataset_name: self.info.dataset.to_string(),
+            })?;
+
+        let (result_descriptor, mut slices) =
+            appendable_time_slices(&self.info.dataset, db.load_loading_info(&dataset_id).await?)?;
+
+        let (Some(spatial_bounds), Some(spatial_resolution)) =
+            (result_descriptor.bbox, result_descriptor.resolution)
+        else {
+            return Err(self.cannot_append("the dataset has no bounding box or resolution"));
+        };
+
+        let operator = self.workflow.operator.clone().get_raster()?;
+
+        let execution_context = self.ctx.execution_context()?;
+
+        let initialized = operator
+            .initialize(WorkflowOperatorPath::initialize_root(), &execution_context)
+            .await?;
+
+        let workflow_result_descriptor = initialized.result_descriptor();
+
+        if workflow_result_descriptor.data_type != result_descriptor.data_type
+            || workflow_result_descriptor.bands != result_descriptor.bands
+        {
+            return Err(self.cannot_append(
+                "the workflow result does not match the data type and bands of the dataset",
+            ));
+        }
+
+        let spatial_reference =
+            Option::<SpatialReference>::from(result_descriptor.spatial_reference)
+                .ok_or(crate::error::Error::MissingSpatialReference)?;
+
+        let existing_times = slices.iter().map(|slice| slice.time).collect::<Vec<_>>();
+        let missing_intervals =
+            uncovered_time_intervals(self.info.time_interval.into(), &existing_times);
+
+        let mut appended_slices = Vec::<GdalLoadingInfoTemporalSlice>::new();
+        let mut skipped_slices = Vec::<GdalLoadingInfoTemporalSlice>::new();
+
+        for time_interval in missing_intervals {
+            let query_rect = geoengine_datatypes::primitives::RasterQueryRectangle {
+                spatial_bounds,
+                time_interval,
+                spatial_resolution,
+                attributes: BandSelection::first(),
+            };
+
+            let processor = initialized.query_processor()?;
+            let query_ctx = self.ctx.query_context(self.workflow_id.0, Uuid::new_v4())?;
+
+            let written =
+                call_on_generic_raster_processor_gdal_types!(processor, p => raster_stream_to_geotiff(
+                &self.file_path,
+                p,
+                query_rect,
+                query_ctx,
+                GdalGeoTiffDatasetMetadata {
+                    no_data_value: Default::default(),
+                    spatial_reference,
+                },
+                GdalGeoTiffOptions {
+                    compression_num_threads: self.compression_num_threads,
+                    as_cog: self.info.as_cog,
+                    force_big_tiff: false,
+                },
+                None,
+                Box::pin(futures::future::pending()), // datasets shall continue to be built in the background and not cancelled
+                execution_context.tiling_specification(),
+                &task_ctx
+            ).await)?
+                .map_err(crate::error::Error::from)?;
+
+            // time steps of the workflow may extend beyond the missing interval, so skip those that are already present
+            for slice in written {
+                let is_present = existing_times
+                    .iter()
+                    .chain(appended_slices.iter().map(|appended| &appended.time))
+                    .any(|time| time.intersects(&slice.time));
+
+                if is_present {
+                    skipped_slices.push(slice);
+                } else {
+                    appended_slices.push(slice);
+                }
+            }
+        }
+
+        if appended_slices.is_empty() {
+            fs::remove_dir_all(&self.file_path)
+                .await
+                .context(crate::error::Io)?;
+
+            return Ok(AppendRasterDatasetFromWorkflowResult {
+                dataset: self.info.dataset.clone(),
+                upload: None,
+                appended_time_steps: vec![],
+            });
+        }
+
+        // the upload consists of all files in the directory, so remove those of skipped time steps
+        for skipped_file in skipped_slices
+            .iter()
+            .filter_map(|slice| slice.params.as_ref().map(|params| &params.file_path))
+        {
+            let is_appended = appended_slices.iter().any(|slice| {
+                slice
+                    .params
+                    .as_ref()
+                    .is_some_and(|params| &params.file_path == skipped_file)
+            });
+
+            if !is_appended {
+                fs::remove_file(skipped_file)
+                    .await
+                    .context(crate::error::Io)?;
+            }
+        }
+
+        let appended_time_steps = appended_slices
+            .iter()
+            .map(|slice| slice.time.into())
+            .collect();
+
+        slices.extend(appended_slices);
+
+        self.create_upload().await?;
+
+        db.update_dataset_loading_info(
+            dataset_id,
+            &time_slices_meta_data(result_descriptor, slices)?,
+        )
+        .await?;
+
+        Ok(AppendRasterDatasetFromWorkflowResult {
+            dataset: self.info.dataset.clone(),
+            upload: Some(self.upload),
+            appended_time_steps,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl<C: SessionContext> Task<C::TaskContext> for AppendRasterDatasetFromWorkflowTask<C> {
+    async fn run(
+        &self,
+        ctx: C::TaskContext,
+    ) -> error::Result<Box<dyn crate::tasks::TaskStatusInfo>, Box<dyn ErrorSource>> {
+        let response = self.process(ToGeoTiffTaskContext { context: ctx }).await;
+
+        response
+            .map(TaskStatusInfo::boxed)
+            .map_err(ErrorSource::boxed)
+    }
+
+    async fn cleanup_on_error(
+        &self,
+        _ctx: C::TaskContext,
+    ) -> error::Result<(), Box<dyn ErrorSource>> {
+        fs::remove_dir_all(&self.file_path)
+            .await
+            .context(crate::error::Io)
+            .map_err(ErrorSource::boxed)?;
+
+        Ok(())
+    }
+
+    fn task_type(&self) -> &'static str {
+        "append-dataset"
+    }
+
+    fn task_unique_id(&self) -> Option<String> {
+        // only one task may extend a dataset at a time
+        Some(self.info.dataset.to_string())
+    }
+
+    fn task_description(&self) -> String {
+        format!(
+            "Appending to dataset {} from {}",
+            self.info.dataset, self.source_name
+        )
+    }
+}
+
+pub async fn schedule_append_raster_dataset_from_workflow_task<C: SessionContext>(
+    source_name: String,
+    workflow_id: WorkflowId,
+    workflow: Workflow,
+    ctx: Arc<C>,
+    info: AppendRasterDatasetFromWorkflow,
+    compression_num_threads: GdalCompressionNumThreads,
+) -> error::Result<TaskId> {
+    let db = ctx.db();
+    let dataset_id = db
+        .resolve_dataset_name_to_id(&info.dataset)
+        .await?
+        .ok_or_else(|| error::Error::UnknownDatasetName {
+            dataset_name: info.dataset.to_string(),
+        })?;
+
+    // fail early if the dataset cannot be extended
+    let (result_descriptor, _) =
+        appendable_time_slices(&info.dataset, db.load_loading_info(&dataset_id).await?)?;
+
+    if let (Some(spatial_bounds), Some(spatial_resolution)) =
+        (result_descriptor.bbox, result_descriptor.resolution)
+    {
+        ensure_estimated_quota_available(
+            ctx.as_ref(),
+            workflow.operator.clone(),
+            spatial_bounds,
+            spatial_resolution,
+            info.time_interval.into(),
+        )
+        .await?;
+    }
+
+    let upload = UploadId::new();
+    let upload_path = upload.root_path()?;
+    fs::create_dir_all(&upload_path)
+        .await
+        .context(crate::error::Io)?;
+
+    let task = AppendRasterDatasetFromWorkflowTask {
+        source_name,
+        workflow_id,
+        workflow,
+        ctx: ctx.clone(),
+        info,
+        upload,
+        file_path: upload_path,
+        compression_num_threads,
+    }
+    .boxed();
+
+    let task_id = ctx.tasks().schedule_task(task, None).await?;
+
+    Ok(task_id)
+}
+
+/// A list of time slices sorted by time with a result descriptor that covers all of them
+fn time_slices_meta_data(
+    mut result_descriptor: RasterResultDescriptor,
+    mut slices: Vec<GdalLoadingInfoTemporalSlice>,
+) -> error::Result<MetaDataDefinition> {
+    slices.sort_by_key(|slice| slice.time.start());
+
+    let first_start = slices
+        .first()
+        .expect("slices should have at least one element")
+        .time
+        .start();
+    let last_end = slices
+        .last()
+        .expect("slices should have at least one element")
+        .time
+        .end();
+    result_descriptor.time = Some(TimeInterval::new(first_start, last_end)?);
+
+    Ok(MetaDataDefinition::GdalMetaDataList(GdalMetaDataList {
+        result_descriptor,
+        params: slices,
+    }))
+}
+
+/// The parts of `interval` that are not covered by any of the `covered` intervals
+fn uncovered_time_intervals(interval: TimeInterval, covered: &[TimeInterval]) -> Vec<TimeInterval> {
+    let mut covered = covered
+        .iter()
+        .filter(|time| time.intersects(&interval))
+        .collect::<Vec<_>>();
+    covered.sort_by_key(|time| time.start());
+
+    let mut uncovered = Vec::new();
+    let mut start = interval.start();
+
+    for time in covered {
+        if time.start() > start {
+            uncovered.push(TimeInterval::new_unchecked(start, time.start()));
+        }
+        start = start.max(time.end());
+    }
+
+    if start < interval.end() {
+        uncovered.push(TimeInterval::new_unchecked(start, interval.end()));
+    }
+
+    uncovered
+}
+
+/// parameter for the vector dataset from workflow handler (body)
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+#[schema(example = json!({"name": "foo", "displayName": "a new dataset", "description": null, "query": {"spatialBounds": {"lowerLeftCoordinate": {"x": -10.0, "y": 20.0}, "upperRightCoordinate": {"x": 50.0, "y": 80.0}}, "timeInterval": {"start": 1_388_534_400_000_i64, "end": 1_388_534_401_000_i64}, "spatialResolution": {"x": 0.1, "y": 0.1}}, "format": "geoPackage"}))]
+#[serde(rename_all = "camelCase")]
+pub struct VectorDatasetFromWorkflow {
+    pub name: Option<DatasetName>,
+    pub display_name: String,
+    pub description: Option<String>,
+    pub query: VectorQueryRectangle,
+    #[serde(default)]
+    pub format: VectorDatasetFormat,
+}
+
+/// The file format of a vector dataset that is created from a workflow
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum VectorDatasetFormat {
+    #[default]
+    GeoPackage,
+    FlatGeobuf,
+    GeoParquet,
+}
+
+impl From<VectorDatasetFormat> for OgrVectorFormat {
+    fn from(value: VectorDatasetFormat) -> Self {
+        match value {
+            VectorDatasetFormat::GeoPackage => Self::GeoPackage,
+            VectorDatasetFormat::FlatGeobuf => Self::FlatGeobuf,
+            VectorDatasetFormat::GeoParquet => Self::GeoParquet,
+        }
+    }
+}
+
+/// response of the vector dataset from workflow handler
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VectorDatasetFromWorkflowResult {
+    pub dataset: DatasetName,
+    pub upload: UploadId,
+    pub feature_count: u64,
+}
+
+impl TaskStatusInfo for VectorDatasetFromWorkflowResult {}
+
+/// The name of the layer in the files of vector datasets that are created from workflows
+const VECTOR_DATASET_LAYER_NAME: &str = "features";
+
+pub struct VectorDatasetFromWorkflowTask<C: SessionContext> {
+    pub source_name: String,
+    pub workflow_id: WorkflowId,
+    pub workflow: Workflow,
+    pub ctx: Arc<C>,
+    pub info: VectorDatasetFromWorkflow,
+    pub upload: UploadId,
+    pub upload_path: PathBuf,
+}
+
+impl<C: SessionContext> VectorDatasetFromWorkflowTask<C> {
+    async fn process(&self) -> error::Result<VectorDatasetFromWorkflowResult> {
+        let operator = self.workflow.operator.clone();
+
+        let operator = operator.get_vector()?;
+
+        let execution_context = self.ctx.execution_context()?;
+
+        let workflow_operator_path_root = WorkflowOperatorPath::initialize_root();
+
+        let initialized = operator
+            .initialize(workflow_operator_path_root, &execution_context)
+            .await?;
+
+        let result_descriptor = initialized.result_descriptor().clone();
+
+        let processor = initialized.query_processor()?;
+
+        let query_ctx = self.ctx.query_context(self.workflow_id.0, Uuid::new_v4())?;
+        let spatial_reference =
+            Option::<SpatialReference>::from(result_descriptor.spatial_reference)
+                .ok_or(crate::error::Error::MissingSpatialReference)?;
+
+        let format = OgrVectorFormat::from(self.info.format);
+        let file_path = self.upload_path.join(format!(
+            "{VECTOR_DATASET_LAYER_NAME}.{}",
+            format.file_extension()
+        ));
+
+        let written = call_on_generic_vector_processor!(processor, p => vector_stream_to_ogr(
+            &file_path,
+            p,
+            self.info.query.into(),
+            query_ctx,
+            &result_descriptor,
+            OgrVectorDatasetMetadata {
+                format,
+                layer_name: VECTOR_DATASET_LAYER_NAME.to_owned(),
+                spatial_reference,
+            },
+            Box::pin(futures::future::pending()), // datasets shall continue to be built in the background and not cancelled
+        ).await)?;
+
+        let provenance =
+            workflow_provenance(self.workflow_id, &self.workflow, self.ctx.as_ref()).await?;
+
+        let feature_count = written.feature_count;
+
+        let dataset =
+            create_vector_dataset(self.info.clone(), &written, provenance, self.ctx.as_ref())
+                .await?;
+
+        Ok(VectorDatasetFromWorkflowResult {
+            dataset: dataset.name,
+            upload: self.upload,
+            feature_count,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl<C: SessionContext> Task<C::TaskContext> for VectorDatasetFromWorkflowTask<C> {
+    async fn run(
+        &self,
+        _ctx: C::TaskContext,
+    ) -> error::Result<Box<dyn crate::tasks::TaskStatusInfo>, Box<dyn ErrorSource>> {
+        let response = self.process().await;
+
+        response
+            .map(TaskStatusInfo::boxed)
+            .map_err(ErrorSource::boxed)
+    }
+
+    async fn cleanup_on_error(
+        &self,
+        _ctx: C::TaskContext,
+    ) -> error::Result<(), Box<dyn ErrorSource>> {
+        fs::remove_dir_all(&self.upload_path)
+            .await
+            .context(crate::error::Io)
+            .map_err(ErrorSource::boxed)?;
+
+        Ok(())
+    }
+
+    fn task_type(&self) -> &'static str {
+        "create-vector-dataset"
+    }
+
+    fn task_unique_id(&self) -> Option<String> {
+        Some(self.upload.to_string())
+    }
+
+    fn task_description(&self) -> String {
+        format!(
+            "Creating vector dataset {} from {}",
+            self.info.display_name, self.source_name
+        )
+    }
+}
+
+pub async fn schedule_vector_dataset_from_workflow_task<C: SessionContext>(
+    source_name: String,
+    workflow_id: WorkflowId,
+    workflow: Workflow,
+    ctx: Arc<C>,
+    info: VectorDatasetFromWorkflow,
+) -> error::Result<TaskId> {
+    if let Some(dataset_name) = &info.name {
+        let db = ctx.db();
+
+        if let Some(dataset_id) = db.resolve_dataset_name_to_id(dataset_name).await? {
+            return Err(error::Error::DatasetNameAlreadyExists {
+                dataset_name: dataset_name.to_string(),
+                dataset_id: dataset_id.into(),
+            });
+        }
+    }
+
+    ensure_estimated_quota_available(
+        ctx.as_ref(),
+        workflow.operator.clone(),
+        SpatialPartition2D::with_bbox_and_resolution(
+            info.query.spatial_bounds.into(),
+            info.query.spatial_resolution.into(),
+        ),
+        info.query.spatial_resolution.into(),
+        info.query.time_interval.into(),
+    )
+    .await?;
+
+    let upload = UploadId::new();
+    let upload_path = upload.root_path()?;
+    fs::create_dir_all(&upload_path)
+        .await
+        .context(crate::error::Io)?;
+
+    let task = VectorDatasetFromWorkflowTask {
+        source_name,
+        workflow_id,
+        workflow,
+        ctx: ctx.clone(),
+        info,
+        upload,
+        upload_path,
+    }
+    .boxed();
+
+    let task_id = ctx.tasks().schedule_task(task, None).await?;
+
+    Ok(task_id)
+}
+
+/// The provenance of a dataset that is created from a workflow: the provenance of all data
+/// the workflow uses and an entry that references the workflow itself
+async fn workflow_provenance<C: SessionContext>(
+    workflow_id: WorkflowId,
+    workflow: &Workflow,
+    ctx: &C,
+) -> error::Result<Vec<Provenance>> {
+    let db = ctx.db();
+    let execution_context = ctx.execution_context()?;
+
+    let mut provenance = Vec::new();
+
+    for data_name in workflow.operator.data_names() {
+        let data_id = execution_context.resolve_named_data(&data_name).await?;
+
+        let output = match &data_id {
+            DataId::Internal { dataset_id } => db.load_provenance(dataset_id).await?,
+            DataId::External(external) => {
+                db.load_layer_provider(external.provider_id)
+                    .await?
+                    .provenance(&data_id)
+                    .await?
+            }
+        };
+
+        for item in output.provenance.unwrap_or_default() {
+            if !provenance.contains(&item) {
+                provenance.push(item);
+            }
+        }
+    }
+
+    provenance.push(Provenance {
+        citation: format!("Created from workflow {workflow_id}"),
+        license: String::new(),
+        uri: format!("/workflow/{workflow_id}"),
+    });
+
+    Ok(provenance)
+}
+
+async fn create_vector_dataset<C: SessionContext>(
+    info: VectorDatasetFromWorkflow,
+    written: &OgrVectorDatasetInfo,
+    provenance: Vec<Provenance>,
+    ctx: &C,
+) -> error::Result<DatasetIdAndName> {
+    ensure!(
+        written.feature_count > 0,
+        error::EmptyDatasetCannotBeImported
+    );
+
+    let meta_data = MetaDataDefinition::OgrMetaData(StaticMetaData {
+        loading_info: written.loading_info(),
+        result_descriptor: written.result_descriptor(),
+        phantom: Default::default(),
+    });
+
+    let properties = AddDataset {
+        name: info.name,
+        display_name: info.display_name,
+        description: info.description.unwrap_or_default(),
+        source_operator: "OgrSource".to_owned(),
+        symbology: None,
+        provenance: Some(provenance),
+        tags: Some(vec!["workflow".to_owned()]),
+    };
+
+    let db = ctx.db();
+    let result = db.add_dataset(properties, meta_data).await?;
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_computes_uncovered_time_intervals() {
+        let covered = [
+            TimeInterval::new_unchecked(10, 20),
+            TimeInterval::new_unchecked(30, 40),
+            TimeInterval::new_unchecked(35, 50),
+            TimeInterval::new_unchecked(100, 110),
+        ];
+
+        assert_eq!(
+            uncovered_time_intervals(TimeInterval::new_unchecked(0, 60), &covered),
+            vec![
+                TimeInterval::new_unchecked(0, 10),
+                TimeInterval::new_unchecked(20, 30),
+                TimeInterval::new_unchecked(50, 60),
+            ]
+        );
+
+        assert_eq!(
+            uncovered_time_intervals(TimeInterval::new_unchecked(12, 18), &covered),
+            vec![]
+        );
+
+        assert_eq!(
+            uncovered_time_intervals(TimeInterval::new_unchecked(60, 70), &covered),
+            vec![TimeInterval::new_unchecked(60, 70)]
+        );
+    }
+}
@@ -7,9 +7,12 @@ pub mod postgres;
 pub mod storage;
 pub mod upload;
 
+pub use create_from_workflow::VectorDatasetFormat;
 pub(crate) use create_from_workflow::{
-    RasterDatasetFromWorkflow, RasterDatasetFromWorkflowResult,
-    schedule_raster_dataset_from_workflow_task,
+    AppendRasterDatasetFromWorkflow, AppendRasterDatasetFromWorkflowResult,
+    RasterDatasetFromWorkflow, RasterDatasetFromWorkflowResult, VectorDatasetFromWorkflow,
+    VectorDatasetFromWorkflowResult, schedule_append_raster_dataset_from_workflow_task,
+    schedule_raster_dataset_from_workflow_task, schedule_vector_dataset_from_workflow_task,
 };
 pub use name::{DatasetIdAndName, DatasetName, DatasetNameError};
 pub use storage::AddDataset;
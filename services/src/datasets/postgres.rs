@@ -8,8 +8,8 @@ use crate::datasets::upload::FileId;
 use crate::datasets::upload::{Upload, UploadDb, UploadId};
 use crate::datasets::{AddDataset, DatasetIdAndName, DatasetName};
 use crate::error::{self, Error, Result};
+use crate::permissions::Permission;
 use crate::permissions::TxPermissionDb;
-use crate::permissions::{Permission, RoleId};
 use crate::projects::Symbology;
 use crate::util::postgres::PostgresErrorExt;
 use async_trait::async_trait;
@@ -127,7 +127,7 @@ where
     async fn list_datasets(&self, options: DatasetListOptions) -> Result<Vec<DatasetListing>> {
         let conn = self.conn_pool.get().await?;
 
-        let mut pos = 3;
+        let mut pos = 4;
         let order_sql = if options.order == OrderBy::NameAsc {
             "display_name ASC"
         } else {
@@ -165,6 +165,7 @@ where
                     ON (p.dataset_id = d.id)
             WHERE 
                 p.user_id = $1
+                AND ($4::uuid[] IS NULL OR d.id = ANY($4))
                 {filter_sql}
                 {filter_tags_sql}
             ORDER BY {order_sql}
@@ -174,6 +175,8 @@ where
             ))
             .await?;
 
+        let api_token_datasets = self.session.api_token_datasets();
+
         let rows = match (options.filter, options.tags) {
             (Some(filter), Some(_)) => {
                 conn.query(
@@ -182,6 +185,7 @@ where
                         &self.session.user.id,
                         &i64::from(options.limit),
                         &i64::from(options.offset),
+                        &api_token_datasets,
                         &format!("%{}%", filter.replace('%', "\\%").replace('_', "\\_")),
                         &filter_tags_list,
                     ],
@@ -195,6 +199,7 @@ where
                         &self.session.user.id,
                         &i64::from(options.limit),
                         &i64::from(options.offset),
+                        &api_token_datasets,
                         &format!("%{}%", filter.replace('%', "\\%").replace('_', "\\_")),
                     ],
                 )
@@ -207,6 +212,7 @@ where
                         &self.session.user.id,
                         &i64::from(options.limit),
                         &i64::from(options.offset),
+                        &api_token_datasets,
                         &filter_tags_list,
                     ],
                 )
@@ -219,6 +225,7 @@ where
                         &self.session.user.id,
                         &i64::from(options.limit),
                         &i64::from(options.offset),
+                        &api_token_datasets,
                     ],
                 )
                 .await?
@@ -262,14 +269,21 @@ where
                 user_permitted_datasets p JOIN datasets d 
                     ON (p.dataset_id = d.id)
             WHERE 
-                p.user_id = $1 AND d.id = $2
+                p.user_id = $1 AND d.id = $2 AND ($3::uuid[] IS NULL OR d.id = ANY($3))
             LIMIT 
                 1",
             )
             .await?;
 
         let row = conn
-            .query_opt(&stmt, &[&self.session.user.id, dataset])
+            .query_opt(
+                &stmt,
+                &[
+                    &self.session.user.id,
+                    dataset,
+                    &self.session.api_token_datasets(),
+                ],
+            )
             .await?;
 
         let row = row.ok_or(error::Error::UnknownDatasetId)?;
@@ -299,14 +313,21 @@ where
                 user_permitted_datasets p JOIN datasets d
                     ON(p.dataset_id = d.id)
             WHERE 
-                p.user_id = $1 AND d.id = $2
+                p.user_id = $1 AND d.id = $2 AND ($3::uuid[] IS NULL OR d.id = ANY($3))
             LIMIT 
                 1",
             )
             .await?;
 
         let row = conn
-            .query_opt(&stmt, &[&self.session.user.id, dataset])
+            .query_opt(
+                &stmt,
+                &[
+                    &self.session.user.id,
+                    dataset,
+                    &self.session.api_token_datasets(),
+                ],
+            )
             .await?;
 
         let row = row.ok_or(error::Error::UnknownDatasetId)?;
@@ -329,14 +350,21 @@ where
                 user_permitted_datasets p JOIN datasets d
                     ON(p.dataset_id = d.id)
             WHERE 
-                p.user_id = $1 AND d.id = $2
+                p.user_id = $1 AND d.id = $2 AND ($3::uuid[] IS NULL OR d.id = ANY($3))
             LIMIT 
                 1",
             )
             .await?;
 
         let row = conn
-            .query_one(&stmt, &[&self.session.user.id, dataset])
+            .query_one(
+                &stmt,
+                &[
+                    &self.session.user.id,
+                    dataset,
+                    &self.session.api_token_datasets(),
+                ],
+            )
             .await?;
 
         Ok(row.get(0))
@@ -366,12 +394,19 @@ where
             search_string.replace('%', "\\%").replace('_', "\\_")
         );
 
-        let mut query_params: Vec<&(dyn ToSql + Sync)> =
-            vec![&self.session.user.id, &limit, &offset, &search_string];
+        let api_token_datasets = self.session.api_token_datasets();
+
+        let mut query_params: Vec<&(dyn ToSql + Sync)> = vec![
+            &self.session.user.id,
+            &limit,
+            &offset,
+            &search_string,
+            &api_token_datasets,
+        ];
 
         let tags_clause = if let Some(tags) = &tags {
             query_params.push(tags);
-            " AND tags @> $5::text[]".to_string()
+            " AND tags @> $6::text[]".to_string()
         } else {
             String::new()
         };
@@ -386,6 +421,7 @@ where
             WHERE 
                 p.user_id = $1
                 AND display_name ILIKE $4 ESCAPE '\\'
+                AND ($5::uuid[] IS NULL OR d.id = ANY($5))
                 {tags_clause}
             ORDER BY display_name ASC
             LIMIT $2
@@ -652,23 +688,9 @@ where
         .await
         .map_unique_violation("datasets", "name", || error::Error::InvalidDatasetName)?;
 
-        let stmt = tx
-            .prepare(
-                "
-            INSERT INTO permissions (
-                role_id,
-                dataset_id,
-                permission
-            )
-            VALUES ($1, $2, $3)",
-            )
-            .await?;
-
-        tx.execute(
-            &stmt,
-            &[&RoleId::from(self.session.user.id), &id, &Permission::Owner],
-        )
-        .await?;
+        self.create_resource_in_tx(id, &tx)
+            .await
+            .boxed_context(crate::error::PermissionDb)?;
 
         tx.commit().await?;
 
@@ -680,7 +702,7 @@ where
 
         let tx = conn.build_transaction().start().await?;
 
-        self.ensure_permission_in_tx(dataset.into(), Permission::Owner, &tx)
+        self.ensure_permission_in_tx(dataset.into(), Permission::Write, &tx)
             .await
             .boxed_context(crate::error::PermissionDb)?;
 
@@ -710,7 +732,7 @@ where
 
         let tx = conn.build_transaction().start().await?;
 
-        self.ensure_permission_in_tx(dataset.into(), Permission::Owner, &tx)
+        self.ensure_permission_in_tx(dataset.into(), Permission::Write, &tx)
             .await
             .boxed_context(crate::error::PermissionDb)?;
 
@@ -736,7 +758,7 @@ where
 
         let tx = conn.build_transaction().start().await?;
 
-        self.ensure_permission_in_tx(dataset.into(), Permission::Owner, &tx)
+        self.ensure_permission_in_tx(dataset.into(), Permission::Write, &tx)
             .await
             .boxed_context(crate::error::PermissionDb)?;
 
@@ -760,7 +782,7 @@ where
 
         let tx = conn.build_transaction().start().await?;
 
-        self.ensure_permission_in_tx(dataset.into(), Permission::Owner, &tx)
+        self.ensure_permission_in_tx(dataset.into(), Permission::Write, &tx)
             .await
             .boxed_context(crate::error::PermissionDb)?;
 
@@ -823,21 +845,21 @@ where
     <<Tls as MakeTlsConnect<Socket>>::TlsConnect as TlsConnect<Socket>>::Future: Send,
 {
     async fn load_upload(&self, upload: UploadId) -> Result<Upload> {
-        let conn = self.conn_pool.get().await?;
+        let mut conn = self.conn_pool.get().await?;
+        let tx = conn.build_transaction().start().await?;
 
-        let stmt = conn
-            .prepare(
-                "
-            SELECT u.id, u.files 
-            FROM uploads u JOIN user_uploads uu ON(u.id = uu.upload_id)
-            WHERE u.id = $1 AND uu.user_id = $2",
-            )
-            .await?;
+        self.ensure_permission_in_tx(upload.into(), Permission::Read, &tx)
+            .await
+            .boxed_context(crate::error::PermissionDb)?;
 
-        let row = conn
-            .query_one(&stmt, &[&upload, &self.session.user.id])
+        let stmt = tx
+            .prepare("SELECT u.id, u.files FROM uploads u WHERE u.id = $1")
             .await?;
 
+        let row = tx.query_one(&stmt, &[&upload]).await?;
+
+        tx.commit().await?;
+
         Ok(Upload {
             id: row.get(0),
             files: row
@@ -876,6 +898,10 @@ where
         tx.execute(&stmt, &[&self.session.user.id, &upload.id])
             .await?;
 
+        self.create_resource_in_tx(upload.id, &tx)
+            .await
+            .boxed_context(crate::error::PermissionDb)?;
+
         tx.commit().await?;
 
         Ok(())
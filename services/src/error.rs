@@ -91,6 +91,8 @@ pub enum Error {
     LogoutFailed,
     #[snafu(display("The session id is invalid."))]
     InvalidSession,
+    #[snafu(display("The share link is invalid or has expired."))]
+    InvalidShareLink,
     #[snafu(display("Invalid admin token"))]
     InvalidAdminToken,
     #[snafu(display("Header with authorization token not provided."))]
@@ -206,6 +208,11 @@ pub enum Error {
     #[snafu(display("Permission denied"))]
     PermissionDenied,
 
+    #[snafu(display("User {user_id} does not exist or is inactive"))]
+    UnknownOrInactiveUser {
+        user_id: crate::users::UserId,
+    },
+
     #[snafu(display("Parameter {} must have length between {} and {}", parameter, min, max))]
     InvalidStringLength {
         parameter: String,
@@ -250,6 +257,81 @@ pub enum Error {
         source: gdal::errors::GdalError,
     },
     EmptyDatasetCannotBeImported,
+    #[snafu(display("Cannot append to dataset '{dataset_name}': {reason}"))]
+    CannotAppendToDataset {
+        dataset_name: String,
+        reason: String,
+    },
+    #[snafu(display(
+        "Cannot append to dataset '{dataset_name}': datasets with regular time steps are generated from a file name pattern and cannot be extended"
+    ))]
+    CannotAppendToRegularDataset {
+        dataset_name: String,
+    },
+
+    #[snafu(display("Invalid cron expression '{expression}': {reason}"))]
+    InvalidCronExpression {
+        expression: String,
+        reason: String,
+    },
+
+    #[snafu(display("Unknown schedule id {schedule_id}"))]
+    UnknownScheduleId {
+        schedule_id: crate::schedules::ScheduleId,
+    },
+
+    #[snafu(display(
+        "The query is estimated to use {estimate} computation units, but only {available} are available"
+    ))]
+    QuotaEstimateExceeded {
+        estimate: u64,
+        available: i64,
+    },
+
+    #[snafu(display(
+        "The query is estimated to use {estimate} computation units of the quota limit {limit}, but only {remaining} are left in the current period"
+    ))]
+    QuotaEstimateExceedsLimit {
+        limit: crate::quota::QuotaLimitId,
+        estimate: u64,
+        remaining: u64,
+    },
+
+    #[snafu(display("Unknown quota limit id {limit}"))]
+    UnknownQuotaLimitId {
+        limit: crate::quota::QuotaLimitId,
+    },
+
+    #[snafu(display("Unknown role id {role}"))]
+    UnknownQuotaLimitRole {
+        role: crate::permissions::RoleId,
+    },
+
+    #[snafu(display("The API token is invalid or has expired."))]
+    InvalidApiToken,
+
+    #[snafu(display("API tokens can only be managed with a session and not with an API token."))]
+    ApiTokenManagementRequiresSession,
+
+    #[snafu(display(
+        "An API token must be restricted to at least one resource if resources are given."
+    ))]
+    EmptyApiTokenResources,
+
+    #[snafu(display("An API token with the name {name} already exists."))]
+    DuplicateApiTokenName {
+        name: String,
+    },
+
+    #[snafu(display("Unknown API token id {token}"))]
+    UnknownApiTokenId {
+        token: crate::users::ApiTokenId,
+    },
+
+    #[snafu(display(
+        "This action is not possible with an API token that is restricted to reading or to specific resources."
+    ))]
+    RestrictedApiToken,
     NoMainFileCandidateFound,
     NoFeatureDataTypeForColumnDataType,
 
@@ -468,6 +550,25 @@ pub enum Error {
 
     InvalidWorkflowOutputType,
 
+    #[snafu(display(
+        "The plot of type '{plot_type}' cannot be rendered as an image of the requested format"
+    ))]
+    PlotCannotBeRenderedAsImage {
+        plot_type: String,
+    },
+
+    #[snafu(display(
+        "The plot of type '{plot_type}' is rendered in a fixed size and does not support a width or height"
+    ))]
+    PlotImageSizeNotSupported {
+        plot_type: String,
+    },
+
+    #[snafu(display("The width and height of plot images must be between 1 and {max} pixels"))]
+    InvalidPlotImageSize {
+        max: u32,
+    },
+
     #[snafu(display("Functionality is not implemented: '{}'", message))]
     NotImplemented {
         message: String,
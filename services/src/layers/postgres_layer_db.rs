@@ -69,7 +69,7 @@ where
         let mut conn = self.conn_pool.get().await?;
         let transaction = conn.build_transaction().start().await?;
 
-        self.ensure_permission_in_tx(id.clone().into(), Permission::Owner, &transaction)
+        self.ensure_permission_in_tx(id.clone().into(), Permission::Write, &transaction)
             .await
             .boxed_context(crate::error::PermissionDb)?;
 
@@ -131,33 +131,18 @@ where
         let mut conn = self.conn_pool.get().await?;
         let trans = conn.build_transaction().start().await?;
 
-        self.ensure_permission_in_tx(collection.clone().into(), Permission::Owner, &trans)
+        self.ensure_permission_in_tx(collection.clone().into(), Permission::Write, &trans)
             .await
             .boxed_context(crate::error::PermissionDb)?;
 
-        let layer_id = insert_layer(self, &trans, id, layer, collection).await?;
+        insert_layer(self, &trans, id, layer, collection).await?;
 
         // TODO: `ON CONFLICT DO NOTHING` means, we do not get an error if the permission already exists.
         //       Do we want that, or should we report an error and let the caller decide whether to ignore it?
         //       We should decide that and adjust all places where `ON CONFLICT DO NOTHING` is used.
-        let stmt = trans
-            .prepare(
-                "
-            INSERT INTO permissions (role_id, permission, layer_id)
-            VALUES ($1, $2, $3) ON CONFLICT DO NOTHING;",
-            )
-            .await?;
-
-        trans
-            .execute(
-                &stmt,
-                &[
-                    &RoleId::from(self.session.user.id),
-                    &Permission::Owner,
-                    &layer_id,
-                ],
-            )
-            .await?;
+        self.create_resource_in_tx(id.clone(), &trans)
+            .await
+            .boxed_context(crate::error::PermissionDb)?;
 
         trans.commit().await?;
 
@@ -172,7 +157,7 @@ where
         let mut conn = self.conn_pool.get().await?;
         let tx = conn.build_transaction().start().await?;
 
-        self.ensure_permission_in_tx(collection.clone().into(), Permission::Owner, &tx)
+        self.ensure_permission_in_tx(collection.clone().into(), Permission::Write, &tx)
             .await
             .boxed_context(crate::error::PermissionDb)?;
 
@@ -224,30 +209,15 @@ where
         let mut conn = self.conn_pool.get().await?;
         let trans = conn.build_transaction().start().await?;
 
-        self.ensure_permission_in_tx(parent.clone().into(), Permission::Owner, &trans)
+        self.ensure_permission_in_tx(parent.clone().into(), Permission::Write, &trans)
             .await
             .boxed_context(crate::error::PermissionDb)?;
 
-        let collection_id = insert_layer_collection_with_id(&trans, id, collection, parent).await?;
+        insert_layer_collection_with_id(&trans, id, collection, parent).await?;
 
-        let stmt = trans
-            .prepare(
-                "
-            INSERT INTO permissions (role_id, permission, layer_collection_id)
-            VALUES ($1, $2, $3) ON CONFLICT DO NOTHING;",
-            )
-            .await?;
-
-        trans
-            .execute(
-                &stmt,
-                &[
-                    &RoleId::from(self.session.user.id),
-                    &Permission::Owner,
-                    &collection_id,
-                ],
-            )
-            .await?;
+        self.create_resource_in_tx(id.clone(), &trans)
+            .await
+            .boxed_context(crate::error::PermissionDb)?;
 
         trans.commit().await?;
 
@@ -323,7 +293,7 @@ where
         let mut conn = self.conn_pool.get().await?;
         let transaction = conn.build_transaction().start().await?;
 
-        self.ensure_permission_in_tx(collection.clone().into(), Permission::Owner, &transaction)
+        self.ensure_permission_in_tx(collection.clone().into(), Permission::Write, &transaction)
             .await
             .boxed_context(crate::error::PermissionDb)?;
 
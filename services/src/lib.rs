@@ -9,6 +9,7 @@ pub mod machine_learning;
 pub mod permissions;
 pub mod projects;
 pub mod quota;
+pub mod schedules;
 pub mod server;
 pub mod stac;
 pub mod tasks;
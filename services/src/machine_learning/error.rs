@@ -12,12 +12,24 @@ pub enum MachineLearningError {
     ModelNotFound {
         name: MlModelName,
     },
+    ModelVersionNotFound {
+        name: MlModelName,
+        version: u32,
+    },
+    #[snafu(display("{source}"))]
+    InvalidModelVersion {
+        source: geoengine_datatypes::machine_learning::MlModelNameError,
+    },
     DuplicateMlModelName {
         name: MlModelName,
     },
     InvalidModelNamespace {
         name: MlModelName,
     },
+    #[snafu(display(
+        "Models cannot be added with an API token that is restricted to reading or to specific resources."
+    ))]
+    RestrictedApiToken,
     #[snafu(display("An unexpected database error occurred."))]
     Postgres {
         source: tokio_postgres::Error,
@@ -26,6 +38,23 @@ pub enum MachineLearningError {
     Bb8 {
         source: bb8_postgres::bb8::RunError<tokio_postgres::Error>,
     },
+    #[snafu(display("Invalid training parameters: {reason}"))]
+    InvalidTrainingParameters {
+        reason: String,
+    },
+    #[snafu(display("The labels in column `{column}` cannot be used for training: {reason}"))]
+    InvalidTrainingLabels {
+        column: String,
+        reason: String,
+    },
+    #[snafu(display("There are no samples with a label and valid features to train a model."))]
+    NoTrainingSamples,
+    #[snafu(display(
+        "There are more than {max_samples} training samples. Use a smaller extent or fewer samples."
+    ))]
+    TooManyTrainingSamples {
+        max_samples: usize,
+    },
     #[snafu(display("An underlying MachineLearningError occured: {source}"))]
     MachineLearning {
         source: Box<geoengine_operators::machine_learning::MachineLearningError>,
@@ -45,3 +74,11 @@ impl From<geoengine_operators::machine_learning::MachineLearningError> for Machi
         }
     }
 }
+
+impl From<MachineLearningError> for crate::error::Error {
+    fn from(e: MachineLearningError) -> Self {
+        Self::MachineLearning {
+            source: Box::new(e),
+        }
+    }
+}
@@ -6,7 +6,7 @@ use crate::{
 };
 use async_trait::async_trait;
 use error::{MachineLearningError, error::CouldNotFindMlModelFileMachineLearningError};
-use geoengine_datatypes::machine_learning::MlModelName;
+use geoengine_datatypes::machine_learning::{MlModelName, MlModelVersion};
 use geoengine_operators::machine_learning::{MlModelLoadingInfo, MlModelMetadata};
 use postgres_types::{FromSql, ToSql};
 use serde::{Deserialize, Serialize};
@@ -18,6 +18,7 @@ use validator::{Validate, ValidationError};
 pub mod error;
 pub mod name;
 mod postgres;
+pub mod training;
 
 identifier!(MlModelId);
 
@@ -26,11 +27,12 @@ identifier!(MlModelId);
 pub struct MlModelIdAndName {
     pub id: MlModelId,
     pub name: MlModelName,
+    pub version: u32,
 }
 
 impl MlModelIdAndName {
-    pub fn new(id: MlModelId, name: MlModelName) -> Self {
-        Self { id, name }
+    pub fn new(id: MlModelId, name: MlModelName, version: u32) -> Self {
+        Self { id, name, version }
     }
 }
 
@@ -42,20 +44,30 @@ pub struct MlModel {
     pub upload: UploadId,
     pub metadata: MlModelMetadata,
     pub file_name: String,
+    pub training_metrics: Option<MlModelTrainingMetrics>,
+    /// The version of the model under its name, which is assigned when the model is added.
+    pub version: u32,
+}
+
+/// Measures of the model quality on the validation samples that were held back during training.
+/// Depending on whether the model is a classifier or a regressor, only some of the measures are available.
+#[derive(Debug, Clone, PartialEq, FromSql, ToSql)]
+pub struct MlModelTrainingMetrics {
+    pub num_training_samples: u32,
+    pub num_validation_samples: u32,
+    /// the share of correctly classified samples
+    pub accuracy: Option<f64>,
+    /// the agreement of predictions and labels, corrected for chance (Cohen's kappa)
+    pub kappa: Option<f64>,
+    pub root_mean_squared_error: Option<f64>,
+    pub mean_absolute_error: Option<f64>,
+    /// the coefficient of determination
+    pub r_squared: Option<f64>,
 }
 
 impl MlModel {
     pub fn model_path(&self) -> Result<PathBuf, MachineLearningError> {
-        path_with_base_path(
-            &self
-                .upload
-                .root_path()
-                .map_err(Box::new)
-                .context(CouldNotFindMlModelFileMachineLearningError)?,
-            self.file_name.as_ref(),
-        )
-        .map_err(Box::new)
-        .context(CouldNotFindMlModelFileMachineLearningError)
+        ml_model_path(self.upload, &self.file_name)
     }
 
     pub fn loading_info(&self) -> Result<MlModelLoadingInfo, MachineLearningError> {
@@ -66,6 +78,19 @@ impl MlModel {
     }
 }
 
+/// The path of a model file in an upload
+pub fn ml_model_path(upload: UploadId, file_name: &str) -> Result<PathBuf, MachineLearningError> {
+    path_with_base_path(
+        &upload
+            .root_path()
+            .map_err(Box::new)
+            .context(CouldNotFindMlModelFileMachineLearningError)?,
+        file_name.as_ref(),
+    )
+    .map_err(Box::new)
+    .context(CouldNotFindMlModelFileMachineLearningError)
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, IntoParams, Validate)]
 #[into_params(parameter_in = Query)]
 pub struct MlModelListOptions {
@@ -91,15 +116,30 @@ fn validate_list_limit(value: u32) -> Result<(), ValidationError> {
 
 #[async_trait]
 pub trait MlModelDb {
+    /// Lists the latest version of each model.
     async fn list_models(
         &self,
         options: &MlModelListOptions,
     ) -> Result<Vec<MlModel>, MachineLearningError>;
 
-    async fn load_model(&self, name: &MlModelName) -> Result<MlModel, MachineLearningError>;
+    /// Lists all versions of a model, starting with the most recent one.
+    async fn list_model_versions(
+        &self,
+        name: &MlModelName,
+    ) -> Result<Vec<MlModel>, MachineLearningError>;
+
+    async fn load_model(
+        &self,
+        name: &MlModelName,
+        version: MlModelVersion,
+    ) -> Result<MlModel, MachineLearningError>;
 
+    /// Adds the model as the next version of its name.
+    /// The first version of a name is `1`. Further versions can only be added by the owner of the latest version and inherit its permissions.
+    /// The `version` field of the model is ignored.
     async fn add_model(&self, model: MlModel) -> Result<MlModelIdAndName, MachineLearningError>;
 
+    /// Resolves the name to the id of the latest version of the model.
     async fn resolve_model_name_to_id(
         &self,
         name: &MlModelName,
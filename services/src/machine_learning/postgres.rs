@@ -4,15 +4,18 @@ use crate::{
         MlModel, MlModelDb, MlModelId, MlModelIdAndName, MlModelListOptions,
         error::{
             MachineLearningError,
-            error::{Bb8MachineLearningError, PostgresMachineLearningError},
+            error::{self, Bb8MachineLearningError, PostgresMachineLearningError},
         },
     },
     permissions::Permission,
     util::postgres::PostgresErrorExt,
 };
 use async_trait::async_trait;
-use geoengine_datatypes::{machine_learning::MlModelName, util::Identifier};
-use snafu::ResultExt;
+use geoengine_datatypes::{
+    machine_learning::{MlModelName, MlModelVersion},
+    util::Identifier,
+};
+use snafu::{ResultExt, ensure};
 use tokio_postgres::{
     Socket,
     tls::{MakeTlsConnect, TlsConnect},
@@ -39,20 +42,22 @@ where
         let rows = conn
             .query(
                 "
-                SELECT
+                SELECT DISTINCT ON (m.name)
                     m.id,
                     m.name,
                     m.display_name,
                     m.description,
                     m.upload,
                     m.metadata,
-                    m.file_name
+                    m.file_name,
+                    m.training_metrics,
+                    m.version
                 FROM 
                     user_permitted_ml_models u JOIN ml_models m ON (u.ml_model_id = m.id)
                 WHERE 
-                    u.user_id = $1
+                    u.user_id = $1 AND ($4::uuid[] IS NULL OR m.id = ANY($4))
                 ORDER BY
-                    name
+                    m.name, m.version DESC
                 OFFSET
                     $2
                 LIMIT 
@@ -61,33 +66,73 @@ where
                     &self.session.user.id,
                     &i64::from(options.offset),
                     &i64::from(options.limit),
+                    &self.session.api_token_ml_models(),
+                ],
+            )
+            .await
+            .context(PostgresMachineLearningError)?;
+
+        Ok(rows.iter().map(ml_model_from_row).collect())
+    }
+
+    async fn list_model_versions(
+        &self,
+        name: &MlModelName,
+    ) -> Result<Vec<MlModel>, MachineLearningError> {
+        let conn = self
+            .conn_pool
+            .get()
+            .await
+            .context(Bb8MachineLearningError)?;
+
+        let rows = conn
+            .query(
+                "
+                SELECT
+                    m.id,
+                    m.name,
+                    m.display_name,
+                    m.description,
+                    m.upload,
+                    m.metadata,
+                    m.file_name,
+                    m.training_metrics,
+                    m.version
+                FROM 
+                    user_permitted_ml_models u JOIN ml_models m ON (u.ml_model_id = m.id)
+                WHERE 
+                    u.user_id = $1 AND m.name = $2::\"MlModelName\"
+                    AND ($3::uuid[] IS NULL OR m.id = ANY($3))
+                ORDER BY
+                    m.version DESC",
+                &[
+                    &self.session.user.id,
+                    name,
+                    &self.session.api_token_ml_models(),
                 ],
             )
             .await
             .context(PostgresMachineLearningError)?;
 
-        let models = rows
-            .into_iter()
-            .map(|row| MlModel {
-                name: row.get(1),
-                display_name: row.get(2),
-                description: row.get(3),
-                upload: row.get(4),
-                metadata: row.get(5),
-                file_name: row.get(6),
-            })
-            .collect();
-
-        Ok(models)
+        if rows.is_empty() {
+            return Err(MachineLearningError::ModelNotFound { name: name.clone() });
+        }
+
+        Ok(rows.iter().map(ml_model_from_row).collect())
     }
 
-    async fn load_model(&self, name: &MlModelName) -> Result<MlModel, MachineLearningError> {
+    async fn load_model(
+        &self,
+        name: &MlModelName,
+        version: MlModelVersion,
+    ) -> Result<MlModel, MachineLearningError> {
         let conn = self
             .conn_pool
             .get()
             .await
             .context(Bb8MachineLearningError)?;
 
+        // the latest version is the one with the highest version number that may be read
         let Some(row) = conn
             .query_opt(
                 "SELECT
@@ -97,30 +142,48 @@ where
                     m.description,
                     m.upload,
                     m.metadata,
-                    m.file_name
+                    m.file_name,
+                    m.training_metrics,
+                    m.version
                 FROM 
                     user_permitted_ml_models u JOIN ml_models m ON (u.ml_model_id = m.id)
                 WHERE 
-                    u.user_id = $1 AND m.name = $2::\"MlModelName\"",
-                &[&self.session.user.id, name],
+                    u.user_id = $1 AND m.name = $2::\"MlModelName\"
+                    AND ($3::oid IS NULL OR m.version = $3)
+                    AND ($4::uuid[] IS NULL OR m.id = ANY($4))
+                ORDER BY
+                    m.version DESC
+                LIMIT 1",
+                &[
+                    &self.session.user.id,
+                    name,
+                    &version.version(),
+                    &self.session.api_token_ml_models(),
+                ],
             )
             .await
             .context(PostgresMachineLearningError)?
         else {
-            return Err(MachineLearningError::ModelNotFound { name: name.clone() });
+            return Err(match version {
+                MlModelVersion::Latest => {
+                    MachineLearningError::ModelNotFound { name: name.clone() }
+                }
+                MlModelVersion::Version(version) => MachineLearningError::ModelVersionNotFound {
+                    name: name.clone(),
+                    version,
+                },
+            });
         };
 
-        Ok(MlModel {
-            name: row.get(1),
-            display_name: row.get(2),
-            description: row.get(3),
-            upload: row.get(4),
-            metadata: row.get(5),
-            file_name: row.get(6),
-        })
+        Ok(ml_model_from_row(&row))
     }
 
     async fn add_model(&self, model: MlModel) -> Result<MlModelIdAndName, MachineLearningError> {
+        ensure!(
+            !self.session.has_restricted_api_token(),
+            error::RestrictedApiTokenMachineLearningError
+        );
+
         self.check_ml_model_namespace(&model.name)?;
 
         let mut conn = self
@@ -135,6 +198,39 @@ where
             .await
             .context(PostgresMachineLearningError)?;
 
+        let latest_version = tx
+            .query_opt(
+                "
+                SELECT
+                    m.id,
+                    m.version,
+                    COALESCE(u.max_permission = 'Owner', FALSE)
+                FROM
+                    ml_models m LEFT JOIN user_permitted_ml_models u ON (
+                        u.ml_model_id = m.id AND u.user_id = $2
+                    )
+                WHERE
+                    m.name = $1::\"MlModelName\"
+                ORDER BY
+                    m.version DESC
+                LIMIT 1",
+                &[&model.name, &self.session.user.id],
+            )
+            .await
+            .context(PostgresMachineLearningError)?
+            .map(|row| (row.get::<_, MlModelId>(0), row.get::<_, u32>(1), row.get(2)));
+
+        // only the owner of the latest version may add further versions under the name
+        let version = match latest_version {
+            None => 1,
+            Some((_, version, true)) => version + 1,
+            Some((_, _, false)) => {
+                return Err(MachineLearningError::DuplicateMlModelName {
+                    name: model.name.clone(),
+                });
+            }
+        };
+
         let id = MlModelId::new();
 
         tx.query_one(
@@ -145,8 +241,10 @@ where
                     description,
                     upload,
                     metadata,
-                    file_name
-                ) VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING id;",
+                    file_name,
+                    training_metrics,
+                    version
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) RETURNING id;",
             &[
                 &id,
                 &model.name,
@@ -155,31 +253,46 @@ where
                 &model.upload,
                 &model.metadata,
                 &model.file_name,
+                &model.training_metrics,
+                &version,
             ],
         )
         .await
-        .map_unique_violation("ml_models", "name", || {
+        .map_unique_violation("ml_models", "name_version", || {
             MachineLearningError::DuplicateMlModelName {
                 name: model.name.clone(),
             }
         })?;
 
-        let stmt = tx
-            .prepare(
-                "INSERT INTO permissions (role_id, permission, ml_model_id) VALUES ($1, $2, $3);",
+        if let Some((previous_id, _, _)) = latest_version {
+            // the new version is shared with the same roles as the previous one
+            tx.execute(
+                "
+                INSERT INTO permissions (role_id, permission, ml_model_id, expires)
+                SELECT role_id, permission, $1, expires FROM permissions WHERE ml_model_id = $2;",
+                &[&id, &previous_id],
             )
             .await
             .context(PostgresMachineLearningError)?;
+        } else {
+            let stmt = tx
+                .prepare(
+                    "INSERT INTO permissions (role_id, permission, ml_model_id) VALUES ($1, $2, $3);",
+                )
+                .await
+                .context(PostgresMachineLearningError)?;
 
-        tx.execute(&stmt, &[&self.session.user.id, &Permission::Owner, &id])
-            .await
-            .context(PostgresMachineLearningError)?;
+            tx.execute(&stmt, &[&self.session.user.id, &Permission::Owner, &id])
+                .await
+                .context(PostgresMachineLearningError)?;
+        }
 
         tx.commit().await.context(PostgresMachineLearningError)?;
 
         Ok(MlModelIdAndName {
             id,
             name: model.name,
+            version,
         })
     }
 
@@ -197,7 +310,9 @@ where
             .prepare(
                 "SELECT id
         FROM ml_models
-        WHERE name = $1::\"MlModelName\"",
+        WHERE name = $1::\"MlModelName\"
+        ORDER BY version DESC
+        LIMIT 1",
             )
             .await?;
 
@@ -206,3 +321,16 @@ where
         Ok(row_option.map(|row| row.get(0)))
     }
 }
+
+fn ml_model_from_row(row: &tokio_postgres::Row) -> MlModel {
+    MlModel {
+        name: row.get(1),
+        display_name: row.get(2),
+        description: row.get(3),
+        upload: row.get(4),
+        metadata: row.get(5),
+        file_name: row.get(6),
+        training_metrics: row.get(7),
+        version: row.get(8),
+    }
+}
@@ -0,0 +1,985 @@
+use self::onnx::{EnsembleOutput, tree_ensemble_to_onnx};
+use self::trees::{
+    FeatureMatrix, GradientBoostingParams, RandomForestParams, TreeEnsemble, TreeParams,
+    train_gradient_boosting_classifier, train_gradient_boosting_regressor,
+    train_random_forest_classifier, train_random_forest_regressor,
+};
+use crate::api::model::datatypes::{MlModelName as ApiMlModelName, VectorQueryRectangle};
+use crate::config;
+use crate::contexts::SessionContext;
+use crate::datasets::upload::{FileId, FileUpload, Upload, UploadDb, UploadId, UploadRootPath};
+use crate::error;
+use crate::machine_learning::error::{
+    MachineLearningError,
+    error::{InvalidTrainingLabelsMachineLearningError, NoTrainingSamplesMachineLearningError},
+};
+use crate::machine_learning::{MlModel, MlModelDb, MlModelTrainingMetrics};
+use crate::tasks::{Task, TaskContext, TaskId, TaskManager, TaskStatusInfo};
+use crate::workflows::workflow::{Workflow, WorkflowId};
+use async_trait::async_trait;
+use futures::StreamExt;
+use geoengine_datatypes::collections::FeatureCollectionInfos;
+use geoengine_datatypes::error::ErrorSource;
+use geoengine_datatypes::machine_learning::MlTensorShape3D;
+use geoengine_datatypes::primitives::{
+    ColumnSelection, FeatureDataRef, FeatureDataType, Measurement,
+};
+use geoengine_datatypes::raster::RasterDataType;
+use geoengine_datatypes::util::Identifier;
+use geoengine_macros::type_tag;
+use geoengine_operators::call_on_generic_vector_processor;
+use geoengine_operators::engine::{
+    ExecutionContext, InitializedRasterOperator, InitializedVectorOperator, QueryProcessor,
+    RasterBandDescriptor, SingleVectorMultipleRasterSources, VectorOperator, WorkflowOperatorPath,
+};
+use geoengine_operators::machine_learning::onnx_util::{
+    check_onnx_model_matches_metadata, load_onnx_model_from_loading_info,
+};
+use geoengine_operators::machine_learning::{
+    MlModelInputNoDataHandling, MlModelMetadata, MlModelOutputNoDataHandling,
+};
+use geoengine_operators::processing::{
+    ColumnNames, FeatureAggregationMethod, RasterVectorJoin, RasterVectorJoinParams,
+    TemporalAggregationMethod,
+};
+use rand::seq::SliceRandom;
+use rand::{SeedableRng, rngs::StdRng};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, ensure};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use tokio::fs;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+mod onnx;
+mod trees;
+
+const MODEL_FILE_NAME: &str = "model.onnx";
+
+/// parameter for the ml model training handler (body)
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MlModelTraining {
+    /// the name of the model to create
+    pub name: ApiMlModelName,
+    pub display_name: String,
+    #[serde(default)]
+    pub description: String,
+    /// a vector workflow with labelled points or polygons
+    pub samples: WorkflowId,
+    /// the column of the samples that contains the labels
+    pub label_column: String,
+    /// a raster workflow whose bands are the features of the model
+    pub features: WorkflowId,
+    /// the extent and resolution in which the features are sampled
+    pub query: VectorQueryRectangle,
+    pub target: MlTrainingTarget,
+    pub algorithm: TreeEnsembleAlgorithm,
+    /// the share of samples that is held back for computing the training metrics
+    #[serde(default = "default_validation_fraction")]
+    pub validation_fraction: f64,
+    /// the seed for sampling the validation samples and randomizing the trees
+    #[serde(default)]
+    pub seed: u64,
+}
+
+#[inline]
+const fn default_validation_fraction() -> f64 {
+    0.2
+}
+
+/// Whether the model predicts classes or continuous values
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum MlTrainingTarget {
+    Classification,
+    Regression,
+}
+
+/// The tree ensemble that is trained
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase", untagged)]
+#[schema(discriminator = "type")]
+pub enum TreeEnsembleAlgorithm {
+    RandomForest(RandomForestTraining),
+    GradientBoosting(GradientBoostingTraining),
+}
+
+#[type_tag(value = "randomForest")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RandomForestTraining {
+    pub num_trees: u32,
+    pub max_depth: u32,
+    pub min_samples_leaf: u32,
+    /// the number of features that are considered for each split, defaults to the square root
+    /// of the number of features for classifications and to one third for regressions
+    pub max_features: Option<u32>,
+}
+
+#[type_tag(value = "gradientBoosting")]
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GradientBoostingTraining {
+    pub num_rounds: u32,
+    pub learning_rate: f64,
+    pub max_depth: u32,
+    pub min_samples_leaf: u32,
+}
+
+impl RandomForestTraining {
+    fn params(&self, num_features: usize, default_max_features: usize) -> RandomForestParams {
+        let max_features = self
+            .max_features
+            .map_or(default_max_features, |max_features| max_features as usize);
+
+        RandomForestParams {
+            num_trees: self.num_trees as usize,
+            tree: tree_params(
+                num_features,
+                self.max_depth,
+                self.min_samples_leaf,
+                max_features,
+            ),
+        }
+    }
+}
+
+impl GradientBoostingTraining {
+    fn params(&self, num_features: usize) -> GradientBoostingParams {
+        GradientBoostingParams {
+            num_rounds: self.num_rounds as usize,
+            learning_rate: self.learning_rate,
+            tree: tree_params(
+                num_features,
+                self.max_depth,
+                self.min_samples_leaf,
+                num_features,
+            ),
+        }
+    }
+}
+
+impl MlModelTraining {
+    fn validate(&self, limits: &config::MlTraining) -> Result<(), MachineLearningError> {
+        let invalid = |reason: &str| MachineLearningError::InvalidTrainingParameters {
+            reason: reason.to_string(),
+        };
+
+        if !(0. ..1.).contains(&self.validation_fraction) {
+            return Err(invalid("the validation fraction must be in [0, 1)"));
+        }
+
+        let (num_trees, max_depth) = match self.algorithm {
+            TreeEnsembleAlgorithm::RandomForest(RandomForestTraining {
+                num_trees,
+                max_depth,
+                ..
+            }) => (num_trees, max_depth),
+            TreeEnsembleAlgorithm::GradientBoosting(GradientBoostingTraining {
+                num_rounds,
+                max_depth,
+                ..
+            }) => (num_rounds, max_depth),
+        };
+
+        if num_trees > limits.max_trees {
+            return Err(invalid(&format!(
+                "the number of trees or rounds must be at most {}",
+                limits.max_trees
+            )));
+        }
+
+        if max_depth > limits.max_depth {
+            return Err(invalid(&format!(
+                "the maximum depth must be at most {}",
+                limits.max_depth
+            )));
+        }
+
+        match self.algorithm {
+            TreeEnsembleAlgorithm::RandomForest(RandomForestTraining { num_trees: 0, .. }) => {
+                Err(invalid("the number of trees must be positive"))
+            }
+            TreeEnsembleAlgorithm::GradientBoosting(GradientBoostingTraining {
+                num_rounds: 0,
+                ..
+            }) => Err(invalid("the number of rounds must be positive")),
+            TreeEnsembleAlgorithm::GradientBoosting(GradientBoostingTraining {
+                learning_rate,
+                ..
+            }) if !(learning_rate > 0. && learning_rate <= 1.) => {
+                Err(invalid("the learning rate must be in (0, 1]"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// response of the ml model training task
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MlModelTrainingResult {
+    pub model: ApiMlModelName,
+    pub version: u32,
+    pub upload: UploadId,
+    pub training_metrics: crate::api::model::services::MlModelTrainingMetrics,
+}
+
+impl TaskStatusInfo for MlModelTrainingResult {}
+
+pub struct MlModelTrainingTask<C: SessionContext> {
+    pub samples_workflow_id: WorkflowId,
+    pub samples_workflow: Workflow,
+    pub features_workflow: Workflow,
+    pub ctx: Arc<C>,
+    pub info: MlModelTraining,
+    pub upload: UploadId,
+}
+
+impl<C: SessionContext> MlModelTrainingTask<C> {
+    async fn process(&self, task_ctx: &C::TaskContext) -> error::Result<MlModelTrainingResult> {
+        let execution_context = self.ctx.execution_context()?;
+
+        let features = self.features_workflow.operator.clone().get_raster()?;
+        let samples = self.samples_workflow.operator.clone().get_vector()?;
+
+        let features_descriptor = features
+            .clone()
+            .initialize(WorkflowOperatorPath::initialize_root(), &execution_context)
+            .await?
+            .result_descriptor()
+            .clone();
+
+        let feature_columns = (0..features_descriptor.bands.count())
+            .map(|band| format!("__feature_{band}"))
+            .collect::<Vec<_>>();
+
+        let join = RasterVectorJoin {
+            params: RasterVectorJoinParams {
+                names: ColumnNames::Names(feature_columns.clone()),
+                feature_aggregation: FeatureAggregationMethod::Mean,
+                feature_aggregation_ignore_no_data: true,
+                temporal_aggregation: TemporalAggregationMethod::Mean,
+                temporal_aggregation_ignore_no_data: true,
+            },
+            sources: SingleVectorMultipleRasterSources {
+                vector: samples,
+                rasters: vec![features],
+            },
+        }
+        .boxed()
+        .initialize(WorkflowOperatorPath::initialize_root(), &execution_context)
+        .await?;
+
+        let label_column = &self.info.label_column;
+        let Some(label_info) = join.result_descriptor().columns.get(label_column).cloned() else {
+            return Err(MachineLearningError::InvalidTrainingLabels {
+                column: label_column.clone(),
+                reason: "the column does not exist".to_string(),
+            }
+            .into());
+        };
+
+        let mut training_samples = TrainingSamples::new(feature_columns, label_info.data_type);
+        let max_samples = config::get_config_element::<config::MlTraining>()?.max_samples;
+
+        let query = geoengine_datatypes::primitives::VectorQueryRectangle {
+            spatial_bounds: self.info.query.spatial_bounds.into(),
+            time_interval: self.info.query.time_interval.into(),
+            spatial_resolution: self.info.query.spatial_resolution.into(),
+            attributes: ColumnSelection::all(),
+        };
+        let query_ctx = self
+            .ctx
+            .query_context(self.samples_workflow_id.0, Uuid::new_v4())?;
+
+        let processor = join.query_processor()?;
+        call_on_generic_vector_processor!(processor, p => {
+            let mut collections = p.query(query, &query_ctx).await?;
+            while let Some(collection) = collections.next().await {
+                training_samples.add_collection(&collection?, label_column)?;
+
+                if training_samples.len() > max_samples {
+                    return Err(MachineLearningError::TooManyTrainingSamples { max_samples }.into());
+                }
+            }
+        });
+
+        task_ctx
+            .set_completion(
+                0.5,
+                format!("Sampled {} training samples", training_samples.len()).boxed(),
+            )
+            .await;
+
+        let info = self.info.clone();
+        let input_type = features_descriptor.data_type;
+        let trained_model = crate::util::spawn_blocking(move || {
+            train_model(&info, training_samples, label_info.measurement, input_type)
+        })
+        .await??;
+
+        // store the model file and register it as a new model
+        let upload_path = self.upload.root_path()?;
+        fs::create_dir_all(&upload_path).await.context(error::Io)?;
+        fs::write(upload_path.join(MODEL_FILE_NAME), &trained_model.onnx)
+            .await
+            .context(error::Io)?;
+
+        let db = self.ctx.db();
+        db.create_upload(Upload {
+            id: self.upload,
+            files: vec![FileUpload {
+                id: FileId::new(),
+                name: MODEL_FILE_NAME.to_string(),
+                byte_size: trained_model.onnx.len() as u64,
+            }],
+        })
+        .await?;
+
+        let model = MlModel {
+            name: self.info.name.clone().into(),
+            display_name: self.info.display_name.clone(),
+            description: self.info.description.clone(),
+            upload: self.upload,
+            metadata: trained_model.metadata,
+            file_name: MODEL_FILE_NAME.to_string(),
+            training_metrics: Some(trained_model.metrics.clone()),
+            version: 0, // assigned when adding the model
+        };
+
+        // check that the exported model can be applied by the `Onnx` operator
+        let loading_info = model.loading_info()?;
+        let session =
+            load_onnx_model_from_loading_info(&loading_info).map_err(MachineLearningError::from)?;
+        check_onnx_model_matches_metadata(&session, &loading_info.metadata)
+            .map_err(MachineLearningError::from)?;
+
+        let id_and_name = db.add_model(model).await?;
+
+        Ok(MlModelTrainingResult {
+            model: id_and_name.name.into(),
+            version: id_and_name.version,
+            upload: self.upload,
+            training_metrics: trained_model.metrics.into(),
+        })
+    }
+}
+
+#[async_trait]
+impl<C: SessionContext> Task<C::TaskContext> for MlModelTrainingTask<C> {
+    async fn run(
+        &self,
+        ctx: C::TaskContext,
+    ) -> error::Result<Box<dyn TaskStatusInfo>, Box<dyn ErrorSource>> {
+        let response = self.process(&ctx).await;
+
+        response
+            .map(TaskStatusInfo::boxed)
+            .map_err(ErrorSource::boxed)
+    }
+
+    async fn cleanup_on_error(
+        &self,
+        _ctx: C::TaskContext,
+    ) -> error::Result<(), Box<dyn ErrorSource>> {
+        let upload_path = self.upload.root_path().map_err(ErrorSource::boxed)?;
+
+        if fs::try_exists(&upload_path).await.unwrap_or(false) {
+            fs::remove_dir_all(&upload_path)
+                .await
+                .context(crate::error::Io)
+                .map_err(ErrorSource::boxed)?;
+        }
+
+        Ok(())
+    }
+
+    fn task_type(&self) -> &'static str {
+        "train-ml-model"
+    }
+
+    fn task_unique_id(&self) -> Option<String> {
+        Some(self.upload.to_string())
+    }
+
+    fn task_description(&self) -> String {
+        format!("Training ml model {}", self.info.display_name)
+    }
+}
+
+pub async fn schedule_ml_model_training_task<C: SessionContext>(
+    samples_workflow: Workflow,
+    features_workflow: Workflow,
+    ctx: Arc<C>,
+    info: MlModelTraining,
+) -> error::Result<TaskId> {
+    info.validate(&config::get_config_element::<config::MlTraining>()?)?;
+
+    let task = MlModelTrainingTask {
+        samples_workflow_id: info.samples,
+        samples_workflow,
+        features_workflow,
+        ctx: ctx.clone(),
+        info,
+        upload: UploadId::new(),
+    }
+    .boxed();
+
+    let task_id = ctx.tasks().schedule_task(task, None).await?;
+
+    Ok(task_id)
+}
+
+/// The labels of the samples in the type of the label column
+enum Labels {
+    Numbers(Vec<f64>),
+    Texts(Vec<String>),
+}
+
+/// Feature vectors and labels of all samples that have a label and valid values for all features
+struct TrainingSamples {
+    feature_columns: Vec<String>,
+    features: FeatureMatrix,
+    labels: Labels,
+}
+
+impl TrainingSamples {
+    fn new(feature_columns: Vec<String>, label_type: FeatureDataType) -> Self {
+        let labels = if label_type == FeatureDataType::Text {
+            Labels::Texts(Vec::new())
+        } else {
+            Labels::Numbers(Vec::new())
+        };
+
+        Self {
+            features: FeatureMatrix::new(feature_columns.len()),
+            feature_columns,
+            labels,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    fn add_collection<C: FeatureCollectionInfos>(
+        &mut self,
+        collection: &C,
+        label_column: &str,
+    ) -> error::Result<()> {
+        let feature_values = self
+            .feature_columns
+            .iter()
+            .map(|column| Ok(collection.data(column)?.float_options_iter().collect()))
+            .collect::<error::Result<Vec<Vec<Option<f64>>>>>()?;
+
+        let label_data = collection.data(label_column)?;
+        let label_nulls = label_data.nulls();
+        let label_numbers = label_data.float_options_iter().collect::<Vec<_>>();
+        let label_texts = match &label_data {
+            FeatureDataRef::Text(_) => label_data.strings_iter().collect::<Vec<_>>(),
+            _ => Vec::new(),
+        };
+
+        let mut row_features = Vec::with_capacity(feature_values.len());
+
+        for row in 0..collection.len() {
+            if label_nulls[row] {
+                continue;
+            }
+
+            row_features.clear();
+            for values in &feature_values {
+                match values[row] {
+                    Some(value) if value.is_finite() => row_features.push(value as f32),
+                    _ => break,
+                }
+            }
+            if row_features.len() < feature_values.len() {
+                continue;
+            }
+
+            match &mut self.labels {
+                Labels::Numbers(labels) => {
+                    let Some(label) = label_numbers[row].filter(|l| l.is_finite()) else {
+                        continue;
+                    };
+                    labels.push(label);
+                }
+                Labels::Texts(labels) => labels.push(label_texts[row].clone()),
+            }
+
+            self.features.push(&row_features);
+        }
+
+        Ok(())
+    }
+}
+
+struct TrainedModel {
+    onnx: Vec<u8>,
+    metadata: MlModelMetadata,
+    metrics: MlModelTrainingMetrics,
+}
+
+fn train_model(
+    info: &MlModelTraining,
+    samples: TrainingSamples,
+    label_measurement: Measurement,
+    input_type: RasterDataType,
+) -> Result<TrainedModel, MachineLearningError> {
+    ensure!(
+        !samples.features.is_empty(),
+        NoTrainingSamplesMachineLearningError
+    );
+
+    let mut rng = StdRng::seed_from_u64(info.seed);
+
+    // hold back a random subset of the samples for validation
+    let mut order = (0..samples.len()).collect::<Vec<_>>();
+    order.shuffle(&mut rng);
+    let num_validation = ((samples.len() as f64 * info.validation_fraction) as usize)
+        .min(samples.len().saturating_sub(1));
+    let (validation, training) = order.split_at(num_validation);
+
+    let num_features = samples.features.num_features();
+    let subset = |rows: &[usize]| {
+        let mut features = FeatureMatrix::new(num_features);
+        for &row in rows {
+            features.push(samples.features.row(row));
+        }
+        features
+    };
+    let training_features = subset(training);
+    let validation_features = subset(validation);
+
+    let mut metrics = MlModelTrainingMetrics {
+        num_training_samples: training.len() as u32,
+        num_validation_samples: validation.len() as u32,
+        accuracy: None,
+        kappa: None,
+        root_mean_squared_error: None,
+        mean_absolute_error: None,
+        r_squared: None,
+    };
+
+    let (onnx, output_type, output_band) = match info.target {
+        MlTrainingTarget::Classification => {
+            let classes = Classes::new(&samples.labels, &info.label_column, label_measurement)?;
+            let class_indices = |rows: &[usize]| {
+                rows.iter()
+                    .map(|&row| classes.indices[row])
+                    .collect::<Vec<_>>()
+            };
+
+            let ensemble = train_classifier(
+                info.algorithm,
+                &training_features,
+                &class_indices(training),
+                classes.labels.len(),
+                &mut rng,
+            );
+
+            let validation_classes = class_indices(validation);
+            if !validation_classes.is_empty() {
+                let predictions = (0..validation_features.len())
+                    .map(|row| ensemble.predict_class(validation_features.row(row)))
+                    .collect::<Vec<_>>();
+                let (accuracy, kappa) =
+                    classification_metrics(&validation_classes, &predictions, classes.labels.len());
+                metrics.accuracy = Some(accuracy);
+                metrics.kappa = Some(kappa);
+            }
+
+            let onnx = tree_ensemble_to_onnx(
+                &ensemble,
+                input_type,
+                num_features,
+                EnsembleOutput::Classes {
+                    labels: &classes.labels,
+                },
+            );
+
+            (onnx, RasterDataType::I64, classes.band_descriptor)
+        }
+        MlTrainingTarget::Regression => {
+            let Labels::Numbers(values) = &samples.labels else {
+                return Err(MachineLearningError::InvalidTrainingLabels {
+                    column: info.label_column.clone(),
+                    reason: "a regression requires numeric labels".to_string(),
+                });
+            };
+            let values_of =
+                |rows: &[usize]| rows.iter().map(|&row| values[row]).collect::<Vec<_>>();
+
+            let ensemble = train_regressor(
+                info.algorithm,
+                &training_features,
+                &values_of(training),
+                &mut rng,
+            );
+
+            let validation_values = values_of(validation);
+            if !validation_values.is_empty() {
+                let predictions = (0..validation_features.len())
+                    .map(|row| ensemble.predict_value(validation_features.row(row)))
+                    .collect::<Vec<_>>();
+                let (rmse, mae, r_squared) = regression_metrics(&validation_values, &predictions);
+                metrics.root_mean_squared_error = Some(rmse);
+                metrics.mean_absolute_error = Some(mae);
+                metrics.r_squared = r_squared;
+            }
+
+            let onnx =
+                tree_ensemble_to_onnx(&ensemble, input_type, num_features, EnsembleOutput::Value);
+
+            (
+                onnx,
+                RasterDataType::F32,
+                RasterBandDescriptor::new(info.label_column.clone(), label_measurement),
+            )
+        }
+    };
+
+    Ok(TrainedModel {
+        onnx,
+        metadata: MlModelMetadata {
+            input_type,
+            output_type,
+            input_shape: MlTensorShape3D::new_single_pixel_bands(num_features as u32),
+            output_shape: MlTensorShape3D::new_single_pixel_single_band(),
+            input_no_data_handling: MlModelInputNoDataHandling::SkipIfNoData,
+            output_no_data_handling: MlModelOutputNoDataHandling::NanIsNoData,
+            output_bands: vec![output_band],
+            input_time_steps: 1,
+        },
+        metrics,
+    })
+}
+
+fn tree_params(
+    num_features: usize,
+    max_depth: u32,
+    min_samples_leaf: u32,
+    max_features: usize,
+) -> TreeParams {
+    TreeParams {
+        max_depth,
+        min_samples_leaf: min_samples_leaf as usize,
+        max_features: max_features.clamp(1, num_features),
+    }
+}
+
+fn train_classifier(
+    algorithm: TreeEnsembleAlgorithm,
+    features: &FeatureMatrix,
+    classes: &[usize],
+    num_classes: usize,
+    rng: &mut StdRng,
+) -> TreeEnsemble {
+    let num_features = features.num_features();
+
+    match algorithm {
+        TreeEnsembleAlgorithm::RandomForest(training) => train_random_forest_classifier(
+            features,
+            classes,
+            num_classes,
+            training.params(num_features, (num_features as f64).sqrt().round() as usize),
+            rng,
+        ),
+        TreeEnsembleAlgorithm::GradientBoosting(training) => train_gradient_boosting_classifier(
+            features,
+            classes,
+            num_classes,
+            training.params(num_features),
+            rng,
+        ),
+    }
+}
+
+fn train_regressor(
+    algorithm: TreeEnsembleAlgorithm,
+    features: &FeatureMatrix,
+    values: &[f64],
+    rng: &mut StdRng,
+) -> TreeEnsemble {
+    let num_features = features.num_features();
+
+    match algorithm {
+        TreeEnsembleAlgorithm::RandomForest(training) => train_random_forest_regressor(
+            features,
+            values,
+            training.params(num_features, num_features / 3),
+            rng,
+        ),
+        TreeEnsembleAlgorithm::GradientBoosting(training) => {
+            train_gradient_boosting_regressor(features, values, training.params(num_features), rng)
+        }
+    }
+}
+
+/// The classes of a classification, i.e., the distinct labels in ascending order
+struct Classes {
+    /// the class label that is predicted by the model
+    labels: Vec<i64>,
+    /// the index of the class of each sample
+    indices: Vec<usize>,
+    band_descriptor: RasterBandDescriptor,
+}
+
+impl Classes {
+    fn new(
+        labels: &Labels,
+        label_column: &str,
+        label_measurement: Measurement,
+    ) -> Result<Self, MachineLearningError> {
+        let (class_labels, indices, class_names) = match labels {
+            Labels::Numbers(numbers) => {
+                ensure!(
+                    numbers.iter().all(|n| n.fract() == 0.),
+                    InvalidTrainingLabelsMachineLearningError {
+                        column: label_column.to_string(),
+                        reason: "a classification requires integer or text labels".to_string(),
+                    }
+                );
+
+                let numbers = numbers.iter().map(|&n| n as i64).collect::<Vec<_>>();
+                let class_labels = numbers.iter().copied().collect::<BTreeSet<_>>();
+                let class_labels = class_labels.into_iter().collect::<Vec<_>>();
+                let indices = numbers
+                    .iter()
+                    .map(|n| class_labels.binary_search(n).unwrap_or_default())
+                    .collect();
+                let class_names = class_labels.iter().map(ToString::to_string).collect();
+
+                (class_labels, indices, class_names)
+            }
+            Labels::Texts(texts) => {
+                let class_names = texts.iter().cloned().collect::<BTreeSet<_>>();
+                let class_names = class_names.into_iter().collect::<Vec<_>>();
+                let indices = texts
+                    .iter()
+                    .map(|t| class_names.binary_search(t).unwrap_or_default())
+                    .collect();
+                let class_labels = (0..class_names.len() as i64).collect();
+
+                (class_labels, indices, class_names)
+            }
+        };
+
+        let measurement = match (labels, label_measurement) {
+            (Labels::Numbers(_), measurement @ Measurement::Classification(_)) => measurement,
+            _ => class_labels
+                .iter()
+                .zip(class_names)
+                .map(|(label, name)| u8::try_from(*label).map(|label| (label, name)))
+                .collect::<Result<HashMap<_, _>, _>>()
+                .map_or(Measurement::Unitless, |classes| {
+                    Measurement::classification(label_column.to_string(), classes)
+                }),
+        };
+
+        Ok(Self {
+            labels: class_labels,
+            indices,
+            band_descriptor: RasterBandDescriptor::new(label_column.to_string(), measurement),
+        })
+    }
+}
+
+/// The accuracy and Cohen's kappa of the predicted classes
+fn classification_metrics(
+    classes: &[usize],
+    predictions: &[usize],
+    num_classes: usize,
+) -> (f64, f64) {
+    let n = classes.len() as f64;
+
+    let mut label_counts = vec![0.; num_classes];
+    let mut prediction_counts = vec![0.; num_classes];
+    let mut correct = 0.;
+
+    for (&class, &prediction) in classes.iter().zip(predictions) {
+        label_counts[class] += 1.;
+        prediction_counts[prediction] += 1.;
+        if class == prediction {
+            correct += 1.;
+        }
+    }
+
+    let accuracy = correct / n;
+    let expected = label_counts
+        .iter()
+        .zip(&prediction_counts)
+        .map(|(l, p)| l * p)
+        .sum::<f64>()
+        / (n * n);
+
+    let kappa = if expected < 1. {
+        (accuracy - expected) / (1. - expected)
+    } else {
+        1.
+    };
+
+    (accuracy, kappa)
+}
+
+/// The root mean squared error, the mean absolute error and the coefficient of determination of the predicted values
+fn regression_metrics(values: &[f64], predictions: &[f64]) -> (f64, f64, Option<f64>) {
+    let n = values.len() as f64;
+
+    let squared_error = values
+        .iter()
+        .zip(predictions)
+        .map(|(v, p)| (v - p).powi(2))
+        .sum::<f64>();
+    let absolute_error = values
+        .iter()
+        .zip(predictions)
+        .map(|(v, p)| (v - p).abs())
+        .sum::<f64>();
+
+    let mean = values.iter().sum::<f64>() / n;
+    let total_variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>();
+
+    let r_squared = (total_variance > 0.).then(|| 1. - squared_error / total_variance);
+
+    ((squared_error / n).sqrt(), absolute_error / n, r_squared)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_cmp::approx_eq;
+
+    #[test]
+    fn it_computes_classification_metrics() {
+        let (accuracy, kappa) = classification_metrics(&[0, 0, 1, 1], &[0, 1, 1, 1], 2);
+
+        assert!(approx_eq!(f64, accuracy, 0.75));
+        assert!(approx_eq!(f64, kappa, 0.5));
+    }
+
+    #[test]
+    fn it_computes_regression_metrics() {
+        let (rmse, mae, r_squared) = regression_metrics(&[1., 2., 3.], &[1., 2., 5.]);
+
+        assert!(approx_eq!(f64, rmse, (4_f64 / 3.).sqrt()));
+        assert!(approx_eq!(f64, mae, 2. / 3.));
+        assert!(approx_eq!(f64, r_squared.unwrap(), -1.));
+
+        assert!(regression_metrics(&[1., 1.], &[1., 1.]).2.is_none());
+    }
+
+    #[test]
+    fn it_maps_labels_to_classes() {
+        let classes = Classes::new(
+            &Labels::Texts(vec!["water".into(), "forest".into(), "water".into()]),
+            "landcover",
+            Measurement::Unitless,
+        )
+        .unwrap();
+
+        assert_eq!(classes.labels, vec![0, 1]);
+        assert_eq!(classes.indices, vec![1, 0, 1]);
+        assert_eq!(
+            classes.band_descriptor.measurement,
+            Measurement::classification(
+                "landcover".to_string(),
+                [(0, "forest".to_string()), (1, "water".to_string())].into()
+            )
+        );
+
+        let classes = Classes::new(
+            &Labels::Numbers(vec![300., 7., 7.]),
+            "class",
+            Measurement::Unitless,
+        )
+        .unwrap();
+
+        assert_eq!(classes.labels, vec![7, 300]);
+        assert_eq!(classes.indices, vec![1, 0, 0]);
+        assert_eq!(classes.band_descriptor.measurement, Measurement::Unitless);
+
+        assert!(Classes::new(&Labels::Numbers(vec![0.5]), "class", Measurement::Unitless).is_err());
+    }
+
+    #[test]
+    fn it_trains_models_from_samples() {
+        let mut samples = TrainingSamples::new(
+            vec!["__feature_0".to_string(), "__feature_1".to_string()],
+            FeatureDataType::Text,
+        );
+        for i in 0..100 {
+            let x = (i % 10) as f32;
+            let y = (i / 10) as f32;
+            samples.features.push(&[x, y]);
+            let Labels::Texts(labels) = &mut samples.labels else {
+                unreachable!()
+            };
+            labels.push(if x < 5. { "a" } else { "b" }.to_string());
+        }
+
+        let info: MlModelTraining = serde_json::from_value(serde_json::json!({
+            "name": "model",
+            "displayName": "Model",
+            "samples": "00000000-0000-0000-0000-000000000000",
+            "labelColumn": "label",
+            "features": "00000000-0000-0000-0000-000000000000",
+            "query": {
+                "spatialBounds": {
+                    "lowerLeftCoordinate": {"x": 0.0, "y": 0.0},
+                    "upperRightCoordinate": {"x": 10.0, "y": 10.0}
+                },
+                "timeInterval": {"start": 0, "end": 1},
+                "spatialResolution": {"x": 1.0, "y": 1.0}
+            },
+            "target": "classification",
+            "algorithm": {
+                "type": "randomForest",
+                "numTrees": 5,
+                "maxDepth": 3,
+                "minSamplesLeaf": 1,
+                "maxFeatures": 2
+            }
+        }))
+        .unwrap();
+        let limits = config::MlTraining {
+            max_trees: 10,
+            max_depth: 3,
+            max_samples: 100,
+        };
+        info.validate(&limits).unwrap();
+        assert!(
+            info.validate(&config::MlTraining {
+                max_trees: 4,
+                ..limits
+            })
+            .is_err()
+        );
+        assert!(
+            info.validate(&config::MlTraining {
+                max_depth: 2,
+                ..limits
+            })
+            .is_err()
+        );
+
+        let model = train_model(&info, samples, Measurement::Unitless, RasterDataType::U8).unwrap();
+
+        assert_eq!(model.metadata.input_type, RasterDataType::U8);
+        assert_eq!(model.metadata.output_type, RasterDataType::I64);
+        assert_eq!(
+            model.metadata.input_shape,
+            MlTensorShape3D::new_single_pixel_bands(2)
+        );
+        assert_eq!(model.metrics.num_training_samples, 80);
+        assert_eq!(model.metrics.num_validation_samples, 20);
+        assert!(approx_eq!(f64, model.metrics.accuracy.unwrap(), 1.));
+        assert!(model.metrics.root_mean_squared_error.is_none());
+        assert!(!model.onnx.is_empty());
+    }
+}
@@ -0,0 +1,582 @@
+use super::trees::{TreeEnsemble, TreeNode};
+use geoengine_datatypes::raster::RasterDataType;
+use prost::Message;
+
+const IR_VERSION: i64 = 8;
+const DEFAULT_OPSET_VERSION: i64 = 17;
+const ML_DOMAIN: &str = "ai.onnx.ml";
+const ML_OPSET_VERSION: i64 = 3;
+
+const INPUT_NAME: &str = "input";
+const FEATURES_NAME: &str = "features";
+const BATCH_DIMENSION: &str = "N";
+
+/// The outputs of an exported tree ensemble
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EnsembleOutput<'l> {
+    /// A classifier whose first output is the label of the class with the highest score
+    Classes { labels: &'l [i64] },
+    /// A regressor that outputs a single value
+    Value,
+}
+
+/// Encodes a tree ensemble as an ONNX model that consumes a `[N, num_features]` tensor of the `input_type`.
+/// The model uses the `TreeEnsembleClassifier` and `TreeEnsembleRegressor` operators of the `ai.onnx.ml` domain.
+pub fn tree_ensemble_to_onnx(
+    ensemble: &TreeEnsemble,
+    input_type: RasterDataType,
+    num_features: usize,
+    output: EnsembleOutput,
+) -> Vec<u8> {
+    let mut nodes = Vec::new();
+
+    let input_elem_type = tensor_element_type(input_type);
+    let features_name = if input_elem_type == proto::FLOAT {
+        INPUT_NAME
+    } else {
+        nodes.push(proto::NodeProto {
+            input: vec![INPUT_NAME.to_string()],
+            output: vec![FEATURES_NAME.to_string()],
+            name: "cast".to_string(),
+            op_type: "Cast".to_string(),
+            domain: String::new(),
+            attribute: vec![proto::AttributeProto::int("to", proto::FLOAT.into())],
+        });
+        FEATURES_NAME
+    };
+
+    let tree_attributes = TreeAttributes::new(ensemble);
+
+    let outputs = match output {
+        EnsembleOutput::Classes { labels } => {
+            nodes.push(proto::NodeProto {
+                input: vec![features_name.to_string()],
+                output: vec!["label".to_string(), "probabilities".to_string()],
+                name: "classifier".to_string(),
+                op_type: "TreeEnsembleClassifier".to_string(),
+                domain: ML_DOMAIN.to_string(),
+                attribute: tree_attributes.into_classifier_attributes(ensemble, labels),
+            });
+
+            vec![
+                proto::ValueInfoProto::tensor("label", proto::INT64, &[None]),
+                proto::ValueInfoProto::tensor(
+                    "probabilities",
+                    proto::FLOAT,
+                    &[None, Some(labels.len())],
+                ),
+            ]
+        }
+        EnsembleOutput::Value => {
+            nodes.push(proto::NodeProto {
+                input: vec![features_name.to_string()],
+                output: vec!["variable".to_string()],
+                name: "regressor".to_string(),
+                op_type: "TreeEnsembleRegressor".to_string(),
+                domain: ML_DOMAIN.to_string(),
+                attribute: tree_attributes.into_regressor_attributes(ensemble),
+            });
+
+            vec![proto::ValueInfoProto::tensor(
+                "variable",
+                proto::FLOAT,
+                &[None, Some(1)],
+            )]
+        }
+    };
+
+    proto::ModelProto {
+        ir_version: IR_VERSION,
+        producer_name: "geoengine".to_string(),
+        producer_version: env!("CARGO_PKG_VERSION").to_string(),
+        graph: Some(proto::GraphProto {
+            node: nodes,
+            name: "tree_ensemble".to_string(),
+            input: vec![proto::ValueInfoProto::tensor(
+                INPUT_NAME,
+                input_elem_type,
+                &[None, Some(num_features)],
+            )],
+            output: outputs,
+        }),
+        opset_import: vec![
+            proto::OperatorSetIdProto {
+                domain: String::new(),
+                version: DEFAULT_OPSET_VERSION,
+            },
+            proto::OperatorSetIdProto {
+                domain: ML_DOMAIN.to_string(),
+                version: ML_OPSET_VERSION,
+            },
+        ],
+    }
+    .encode_to_vec()
+}
+
+fn tensor_element_type(data_type: RasterDataType) -> i32 {
+    match data_type {
+        RasterDataType::U8 => proto::UINT8,
+        RasterDataType::U16 => proto::UINT16,
+        RasterDataType::U32 => proto::UINT32,
+        RasterDataType::U64 => proto::UINT64,
+        RasterDataType::I8 => proto::INT8,
+        RasterDataType::I16 => proto::INT16,
+        RasterDataType::I32 => proto::INT32,
+        RasterDataType::I64 => proto::INT64,
+        RasterDataType::F32 => proto::FLOAT,
+        RasterDataType::F64 => proto::DOUBLE,
+    }
+}
+
+/// The flattened nodes and leaf weights of all trees as expected by the tree ensemble operators
+#[derive(Default)]
+struct TreeAttributes {
+    tree_ids: Vec<i64>,
+    node_ids: Vec<i64>,
+    feature_ids: Vec<i64>,
+    thresholds: Vec<f32>,
+    modes: Vec<Vec<u8>>,
+    true_node_ids: Vec<i64>,
+    false_node_ids: Vec<i64>,
+    weight_tree_ids: Vec<i64>,
+    weight_node_ids: Vec<i64>,
+    weight_output_ids: Vec<i64>,
+    weights: Vec<f32>,
+}
+
+impl TreeAttributes {
+    fn new(ensemble: &TreeEnsemble) -> Self {
+        let mut attributes = Self::default();
+
+        for (tree_id, tree) in ensemble.trees.iter().enumerate() {
+            for (node_id, node) in tree.nodes.iter().enumerate() {
+                attributes.tree_ids.push(tree_id as i64);
+                attributes.node_ids.push(node_id as i64);
+
+                match node {
+                    TreeNode::Split {
+                        feature,
+                        threshold,
+                        left,
+                        right,
+                    } => {
+                        attributes.feature_ids.push(*feature as i64);
+                        attributes.thresholds.push(*threshold);
+                        attributes.modes.push(b"BRANCH_LEQ".to_vec());
+                        attributes.true_node_ids.push(*left as i64);
+                        attributes.false_node_ids.push(*right as i64);
+                    }
+                    TreeNode::Leaf { values } => {
+                        attributes.feature_ids.push(0);
+                        attributes.thresholds.push(0.);
+                        attributes.modes.push(b"LEAF".to_vec());
+                        attributes.true_node_ids.push(0);
+                        attributes.false_node_ids.push(0);
+
+                        // we write the weights of all outputs, s.t., classifiers with two classes are not treated as binary
+                        for (output_id, value) in values.iter().enumerate() {
+                            attributes.weight_tree_ids.push(tree_id as i64);
+                            attributes.weight_node_ids.push(node_id as i64);
+                            attributes.weight_output_ids.push(output_id as i64);
+                            attributes.weights.push(*value as f32);
+                        }
+                    }
+                }
+            }
+        }
+
+        attributes
+    }
+
+    fn into_node_attributes(self, weight_prefix: &str) -> Vec<proto::AttributeProto> {
+        use proto::AttributeProto;
+
+        vec![
+            AttributeProto::ints("nodes_treeids", self.tree_ids),
+            AttributeProto::ints("nodes_nodeids", self.node_ids),
+            AttributeProto::ints("nodes_featureids", self.feature_ids),
+            AttributeProto::floats("nodes_values", self.thresholds),
+            AttributeProto::strings("nodes_modes", self.modes),
+            AttributeProto::ints("nodes_truenodeids", self.true_node_ids),
+            AttributeProto::ints("nodes_falsenodeids", self.false_node_ids),
+            AttributeProto::ints(&format!("{weight_prefix}_treeids"), self.weight_tree_ids),
+            AttributeProto::ints(&format!("{weight_prefix}_nodeids"), self.weight_node_ids),
+            AttributeProto::ints(&format!("{weight_prefix}_ids"), self.weight_output_ids),
+            AttributeProto::floats(&format!("{weight_prefix}_weights"), self.weights),
+        ]
+    }
+
+    fn into_classifier_attributes(
+        self,
+        ensemble: &TreeEnsemble,
+        labels: &[i64],
+    ) -> Vec<proto::AttributeProto> {
+        use proto::AttributeProto;
+
+        let mut attributes = self.into_node_attributes("class");
+        attributes.extend([
+            AttributeProto::ints("classlabels_int64s", labels.to_vec()),
+            AttributeProto::floats("base_values", base_values(ensemble)),
+            AttributeProto::string(
+                "post_transform",
+                if ensemble.softmax { "SOFTMAX" } else { "NONE" },
+            ),
+        ]);
+        attributes
+    }
+
+    fn into_regressor_attributes(self, ensemble: &TreeEnsemble) -> Vec<proto::AttributeProto> {
+        use proto::AttributeProto;
+
+        let mut attributes = self.into_node_attributes("target");
+        attributes.extend([
+            AttributeProto::int("n_targets", 1),
+            AttributeProto::string("aggregate_function", "SUM"),
+            AttributeProto::floats("base_values", base_values(ensemble)),
+            AttributeProto::string("post_transform", "NONE"),
+        ]);
+        attributes
+    }
+}
+
+fn base_values(ensemble: &TreeEnsemble) -> Vec<f32> {
+    ensemble.base_values.iter().map(|v| *v as f32).collect()
+}
+
+/// The subset of the ONNX protobuf schema (`onnx.proto`) that is necessary to describe tree ensembles
+mod proto {
+    pub const FLOAT: i32 = 1;
+    pub const UINT8: i32 = 2;
+    pub const INT8: i32 = 3;
+    pub const UINT16: i32 = 4;
+    pub const INT16: i32 = 5;
+    pub const INT32: i32 = 6;
+    pub const INT64: i32 = 7;
+    pub const DOUBLE: i32 = 11;
+    pub const UINT32: i32 = 12;
+    pub const UINT64: i32 = 13;
+
+    const ATTRIBUTE_INT: i32 = 2;
+    const ATTRIBUTE_STRING: i32 = 3;
+    const ATTRIBUTE_FLOATS: i32 = 6;
+    const ATTRIBUTE_INTS: i32 = 7;
+    const ATTRIBUTE_STRINGS: i32 = 8;
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ModelProto {
+        #[prost(int64, tag = "1")]
+        pub ir_version: i64,
+        #[prost(string, tag = "2")]
+        pub producer_name: String,
+        #[prost(string, tag = "3")]
+        pub producer_version: String,
+        #[prost(message, optional, tag = "7")]
+        pub graph: Option<GraphProto>,
+        #[prost(message, repeated, tag = "8")]
+        pub opset_import: Vec<OperatorSetIdProto>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct OperatorSetIdProto {
+        #[prost(string, tag = "1")]
+        pub domain: String,
+        #[prost(int64, tag = "2")]
+        pub version: i64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct GraphProto {
+        #[prost(message, repeated, tag = "1")]
+        pub node: Vec<NodeProto>,
+        #[prost(string, tag = "2")]
+        pub name: String,
+        #[prost(message, repeated, tag = "11")]
+        pub input: Vec<ValueInfoProto>,
+        #[prost(message, repeated, tag = "12")]
+        pub output: Vec<ValueInfoProto>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct NodeProto {
+        #[prost(string, repeated, tag = "1")]
+        pub input: Vec<String>,
+        #[prost(string, repeated, tag = "2")]
+        pub output: Vec<String>,
+        #[prost(string, tag = "3")]
+        pub name: String,
+        #[prost(string, tag = "4")]
+        pub op_type: String,
+        #[prost(message, repeated, tag = "5")]
+        pub attribute: Vec<AttributeProto>,
+        #[prost(string, tag = "7")]
+        pub domain: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct AttributeProto {
+        #[prost(string, tag = "1")]
+        pub name: String,
+        #[prost(int64, tag = "3")]
+        pub i: i64,
+        #[prost(bytes = "vec", tag = "4")]
+        pub s: Vec<u8>,
+        #[prost(float, repeated, tag = "7")]
+        pub floats: Vec<f32>,
+        #[prost(int64, repeated, tag = "8")]
+        pub ints: Vec<i64>,
+        #[prost(bytes = "vec", repeated, tag = "9")]
+        pub strings: Vec<Vec<u8>>,
+        #[prost(int32, tag = "20")]
+        pub r#type: i32,
+    }
+
+    impl AttributeProto {
+        fn new(name: &str, r#type: i32) -> Self {
+            Self {
+                name: name.to_string(),
+                r#type,
+                ..Default::default()
+            }
+        }
+
+        pub fn int(name: &str, i: i64) -> Self {
+            Self {
+                i,
+                ..Self::new(name, ATTRIBUTE_INT)
+            }
+        }
+
+        pub fn string(name: &str, s: &str) -> Self {
+            Self {
+                s: s.as_bytes().to_vec(),
+                ..Self::new(name, ATTRIBUTE_STRING)
+            }
+        }
+
+        pub fn floats(name: &str, floats: Vec<f32>) -> Self {
+            Self {
+                floats,
+                ..Self::new(name, ATTRIBUTE_FLOATS)
+            }
+        }
+
+        pub fn ints(name: &str, ints: Vec<i64>) -> Self {
+            Self {
+                ints,
+                ..Self::new(name, ATTRIBUTE_INTS)
+            }
+        }
+
+        pub fn strings(name: &str, strings: Vec<Vec<u8>>) -> Self {
+            Self {
+                strings,
+                ..Self::new(name, ATTRIBUTE_STRINGS)
+            }
+        }
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ValueInfoProto {
+        #[prost(string, tag = "1")]
+        pub name: String,
+        #[prost(message, optional, tag = "2")]
+        pub r#type: Option<TypeProto>,
+    }
+
+    impl ValueInfoProto {
+        /// A tensor whose dimensions are either fixed or the batch dimension (`None`)
+        pub fn tensor(name: &str, elem_type: i32, dimensions: &[Option<usize>]) -> Self {
+            Self {
+                name: name.to_string(),
+                r#type: Some(TypeProto {
+                    tensor_type: Some(TensorTypeProto {
+                        elem_type,
+                        shape: Some(TensorShapeProto {
+                            dim: dimensions
+                                .iter()
+                                .map(|dimension| match dimension {
+                                    Some(size) => Dimension {
+                                        dim_value: Some(*size as i64),
+                                        dim_param: None,
+                                    },
+                                    None => Dimension {
+                                        dim_value: None,
+                                        dim_param: Some(super::BATCH_DIMENSION.to_string()),
+                                    },
+                                })
+                                .collect(),
+                        }),
+                    }),
+                }),
+            }
+        }
+    }
+
+    /// The `oneof` of the type is encoded as an optional field, which is equivalent on the wire
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct TypeProto {
+        #[prost(message, optional, tag = "1")]
+        pub tensor_type: Option<TensorTypeProto>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct TensorTypeProto {
+        #[prost(int32, tag = "1")]
+        pub elem_type: i32,
+        #[prost(message, optional, tag = "2")]
+        pub shape: Option<TensorShapeProto>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct TensorShapeProto {
+        #[prost(message, repeated, tag = "1")]
+        pub dim: Vec<Dimension>,
+    }
+
+    /// The `oneof` of the value is encoded as optional fields, which is equivalent on the wire
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Dimension {
+        #[prost(int64, optional, tag = "1")]
+        pub dim_value: Option<i64>,
+        #[prost(string, optional, tag = "2")]
+        pub dim_param: Option<String>,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine_learning::training::trees::DecisionTree;
+    use geoengine_datatypes::machine_learning::MlTensorShape3D;
+    use geoengine_operators::machine_learning::{
+        MlModelInputNoDataHandling, MlModelLoadingInfo, MlModelMetadata,
+        MlModelOutputNoDataHandling,
+        onnx_util::{check_onnx_model_matches_metadata, load_onnx_model_from_loading_info},
+    };
+    use ndarray::Array2;
+    use ort::session::Session;
+    use ort::value::TensorRef;
+
+    fn stump(feature: usize, threshold: f32, left: Vec<f64>, right: Vec<f64>) -> DecisionTree {
+        DecisionTree {
+            nodes: vec![
+                TreeNode::Split {
+                    feature,
+                    threshold,
+                    left: 1,
+                    right: 2,
+                },
+                TreeNode::Leaf { values: left },
+                TreeNode::Leaf { values: right },
+            ],
+        }
+    }
+
+    fn check_model(bytes: &[u8], metadata: MlModelMetadata) -> Session {
+        let dir = tempfile::tempdir().unwrap();
+        let storage_path = dir.path().join("model.onnx");
+        std::fs::write(&storage_path, bytes).unwrap();
+
+        let loading_info = MlModelLoadingInfo {
+            storage_path,
+            metadata,
+        };
+
+        let session = load_onnx_model_from_loading_info(&loading_info).unwrap();
+        check_onnx_model_matches_metadata(&session, &loading_info.metadata).unwrap();
+
+        session
+    }
+
+    #[test]
+    fn it_exports_classifiers() {
+        let ensemble = TreeEnsemble {
+            trees: vec![
+                stump(0, 0.5, vec![1., 0.], vec![0., 1.]),
+                stump(1, 2.5, vec![0.5, 0.5], vec![0., 1.]),
+            ],
+            base_values: vec![0., 0.],
+            softmax: false,
+        };
+
+        let bytes = tree_ensemble_to_onnx(
+            &ensemble,
+            RasterDataType::U8,
+            2,
+            EnsembleOutput::Classes { labels: &[3, 7] },
+        );
+
+        let mut session = check_model(
+            &bytes,
+            MlModelMetadata {
+                input_type: RasterDataType::U8,
+                output_type: RasterDataType::I64,
+                input_shape: MlTensorShape3D::new_single_pixel_bands(2),
+                output_shape: MlTensorShape3D::new_single_pixel_single_band(),
+                input_no_data_handling: MlModelInputNoDataHandling::SkipIfNoData,
+                output_no_data_handling: MlModelOutputNoDataHandling::NanIsNoData,
+                output_bands: vec![],
+                input_time_steps: 1,
+            },
+        );
+
+        // the samples cover both sides of each split without ties between the classes
+        let samples = Array2::from_shape_vec((3, 2), vec![0_u8, 2, 1, 2, 1, 3]).unwrap();
+
+        let outputs = session
+            .run(ort::inputs![INPUT_NAME => TensorRef::from_array_view(&samples).unwrap()])
+            .unwrap();
+        let (_shape, predictions) = outputs["label"].try_extract_tensor::<i64>().unwrap();
+
+        let expected = samples
+            .rows()
+            .into_iter()
+            .map(|sample| {
+                let features = sample.iter().map(|&v| f32::from(v)).collect::<Vec<_>>();
+                [3, 7][ensemble.predict_class(&features)]
+            })
+            .collect::<Vec<i64>>();
+
+        assert_eq!(predictions, expected.as_slice());
+    }
+
+    #[test]
+    fn it_exports_regressors() {
+        let ensemble = TreeEnsemble {
+            trees: vec![stump(0, 0.5, vec![-1.], vec![1.])],
+            base_values: vec![2.],
+            softmax: false,
+        };
+
+        let bytes = tree_ensemble_to_onnx(&ensemble, RasterDataType::F32, 3, EnsembleOutput::Value);
+
+        let mut session = check_model(
+            &bytes,
+            MlModelMetadata {
+                input_type: RasterDataType::F32,
+                output_type: RasterDataType::F32,
+                input_shape: MlTensorShape3D::new_single_pixel_bands(3),
+                output_shape: MlTensorShape3D::new_single_pixel_single_band(),
+                input_no_data_handling: MlModelInputNoDataHandling::SkipIfNoData,
+                output_no_data_handling: MlModelOutputNoDataHandling::NanIsNoData,
+                output_bands: vec![],
+                input_time_steps: 1,
+            },
+        );
+
+        // the second sample lies exactly on the threshold
+        let samples =
+            Array2::from_shape_vec((3, 3), vec![0_f32, 1., 2., 0.5, 1., 2., 0.7, 0., 0.]).unwrap();
+
+        let outputs = session
+            .run(ort::inputs![INPUT_NAME => TensorRef::from_array_view(&samples).unwrap()])
+            .unwrap();
+        let (_shape, predictions) = outputs["variable"].try_extract_tensor::<f32>().unwrap();
+
+        assert_eq!(predictions.len(), 3);
+        for (sample, &prediction) in samples.rows().into_iter().zip(predictions) {
+            let expected = ensemble.predict_value(sample.as_slice().unwrap());
+            assert!((f64::from(prediction) - expected).abs() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,716 @@
+use rand::{Rng, seq::index::sample};
+
+/// A row-major matrix of training samples with one column per feature
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeatureMatrix {
+    values: Vec<f32>,
+    num_features: usize,
+}
+
+impl FeatureMatrix {
+    pub fn new(num_features: usize) -> Self {
+        debug_assert!(num_features > 0, "there must be at least one feature");
+
+        Self {
+            values: Vec::new(),
+            num_features,
+        }
+    }
+
+    pub fn push(&mut self, row: &[f32]) {
+        debug_assert_eq!(row.len(), self.num_features);
+
+        self.values.extend_from_slice(row);
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len() / self.num_features
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn num_features(&self) -> usize {
+        self.num_features
+    }
+
+    pub fn row(&self, sample: usize) -> &[f32] {
+        &self.values[sample * self.num_features..(sample + 1) * self.num_features]
+    }
+
+    fn value(&self, sample: usize, feature: usize) -> f32 {
+        self.values[sample * self.num_features + feature]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TreeParams {
+    pub max_depth: u32,
+    pub min_samples_leaf: usize,
+    /// the number of randomly chosen features that are considered for each split
+    pub max_features: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TreeNode {
+    /// Samples with `feature <= threshold` continue with the `left` node, all others with the `right` node.
+    Split {
+        feature: usize,
+        threshold: f32,
+        left: usize,
+        right: usize,
+    },
+    /// The contribution of the leaf to each output of the ensemble
+    Leaf { values: Vec<f64> },
+}
+
+/// A binary decision tree whose root is the first node
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecisionTree {
+    pub nodes: Vec<TreeNode>,
+}
+
+impl DecisionTree {
+    pub fn leaf_values(&self, row: &[f32]) -> &[f64] {
+        let mut node = 0;
+        loop {
+            match &self.nodes[node] {
+                TreeNode::Split {
+                    feature,
+                    threshold,
+                    left,
+                    right,
+                } => {
+                    node = if row[*feature] <= *threshold {
+                        *left
+                    } else {
+                        *right
+                    }
+                }
+                TreeNode::Leaf { values } => return values,
+            }
+        }
+    }
+}
+
+/// An ensemble of trees whose leaf values are summed up per output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeEnsemble {
+    pub trees: Vec<DecisionTree>,
+    /// the initial score of each output before adding the leaf values of the trees
+    pub base_values: Vec<f64>,
+    /// whether the scores are transformed to class probabilities using the softmax function
+    pub softmax: bool,
+}
+
+impl TreeEnsemble {
+    pub fn num_outputs(&self) -> usize {
+        self.base_values.len()
+    }
+
+    pub fn scores(&self, row: &[f32]) -> Vec<f64> {
+        let mut scores = self.base_values.clone();
+
+        for tree in &self.trees {
+            for (score, value) in scores.iter_mut().zip(tree.leaf_values(row)) {
+                *score += value;
+            }
+        }
+
+        scores
+    }
+
+    /// The index of the output with the highest score
+    pub fn predict_class(&self, row: &[f32]) -> usize {
+        self.scores(row)
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map_or(0, |(class, _)| class)
+    }
+
+    pub fn predict_value(&self, row: &[f32]) -> f64 {
+        self.scores(row)[0]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RandomForestParams {
+    pub num_trees: usize,
+    pub tree: TreeParams,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GradientBoostingParams {
+    pub num_rounds: usize,
+    pub learning_rate: f64,
+    pub tree: TreeParams,
+}
+
+/// Trains a random forest whose leaves contain the class frequencies, s.t., the scores are the averaged class probabilities.
+pub fn train_random_forest_classifier<R: Rng>(
+    features: &FeatureMatrix,
+    classes: &[usize],
+    num_classes: usize,
+    params: RandomForestParams,
+    rng: &mut R,
+) -> TreeEnsemble {
+    let target = SplitTarget::Classes {
+        classes,
+        num_classes,
+    };
+    let weight = 1. / params.num_trees as f64;
+
+    let leaf = |samples: &[usize]| -> Vec<f64> {
+        let mut frequencies = vec![0.; num_classes];
+        for &sample in samples {
+            frequencies[classes[sample]] += 1.;
+        }
+        let total = samples.len() as f64;
+        frequencies
+            .into_iter()
+            .map(|count| count / total * weight)
+            .collect()
+    };
+
+    let trees = (0..params.num_trees)
+        .map(|_| {
+            let samples = bootstrap(features.len(), rng);
+            grow_tree(features, &target, &leaf, params.tree, samples, rng)
+        })
+        .collect();
+
+    TreeEnsemble {
+        trees,
+        base_values: vec![0.; num_classes],
+        softmax: false,
+    }
+}
+
+/// Trains a random forest whose leaves contain the mean values, s.t., the score is the averaged prediction.
+pub fn train_random_forest_regressor<R: Rng>(
+    features: &FeatureMatrix,
+    values: &[f64],
+    params: RandomForestParams,
+    rng: &mut R,
+) -> TreeEnsemble {
+    let target = SplitTarget::Values(values);
+    let weight = 1. / params.num_trees as f64;
+
+    let leaf = |samples: &[usize]| vec![mean(values, samples) * weight];
+
+    let trees = (0..params.num_trees)
+        .map(|_| {
+            let samples = bootstrap(features.len(), rng);
+            grow_tree(features, &target, &leaf, params.tree, samples, rng)
+        })
+        .collect();
+
+    TreeEnsemble {
+        trees,
+        base_values: vec![0.],
+        softmax: false,
+    }
+}
+
+/// Trains gradient-boosted trees that minimize the squared error.
+pub fn train_gradient_boosting_regressor<R: Rng>(
+    features: &FeatureMatrix,
+    values: &[f64],
+    params: GradientBoostingParams,
+    rng: &mut R,
+) -> TreeEnsemble {
+    let all_samples = (0..features.len()).collect::<Vec<_>>();
+    let base_value = mean(values, &all_samples);
+
+    let mut predictions = vec![base_value; values.len()];
+    let mut trees = Vec::with_capacity(params.num_rounds);
+
+    for _ in 0..params.num_rounds {
+        let residuals = values
+            .iter()
+            .zip(&predictions)
+            .map(|(value, prediction)| value - prediction)
+            .collect::<Vec<_>>();
+
+        let target = SplitTarget::Values(&residuals);
+        let leaf = |samples: &[usize]| vec![mean(&residuals, samples) * params.learning_rate];
+
+        let tree = grow_tree(
+            features,
+            &target,
+            &leaf,
+            params.tree,
+            all_samples.clone(),
+            rng,
+        );
+
+        for (sample, prediction) in predictions.iter_mut().enumerate() {
+            *prediction += tree.leaf_values(features.row(sample))[0];
+        }
+
+        trees.push(tree);
+    }
+
+    TreeEnsemble {
+        trees,
+        base_values: vec![base_value],
+        softmax: false,
+    }
+}
+
+/// Trains gradient-boosted trees that minimize the multinomial deviance.
+/// Each round adds one tree per class and the class scores are turned into probabilities using the softmax function.
+pub fn train_gradient_boosting_classifier<R: Rng>(
+    features: &FeatureMatrix,
+    classes: &[usize],
+    num_classes: usize,
+    params: GradientBoostingParams,
+    rng: &mut R,
+) -> TreeEnsemble {
+    let all_samples = (0..features.len()).collect::<Vec<_>>();
+
+    // start with the (smoothed) log priors of the classes
+    let mut counts = vec![1.; num_classes];
+    for &class in classes {
+        counts[class] += 1.;
+    }
+    let total = counts.iter().sum::<f64>();
+    let base_values = counts
+        .into_iter()
+        .map(|count| (count / total).ln())
+        .collect::<Vec<_>>();
+
+    let mut scores = vec![base_values.clone(); classes.len()];
+    let mut trees = Vec::with_capacity(params.num_rounds * num_classes);
+
+    let shrinkage = (num_classes as f64 - 1.) / num_classes as f64 * params.learning_rate;
+
+    for _ in 0..params.num_rounds {
+        let probabilities = scores.iter().map(|s| softmax(s)).collect::<Vec<_>>();
+
+        let mut round_trees = Vec::with_capacity(num_classes);
+
+        for class in 0..num_classes {
+            let residuals = classes
+                .iter()
+                .zip(&probabilities)
+                .map(|(&label, p)| f64::from(u8::from(label == class)) - p[class])
+                .collect::<Vec<_>>();
+
+            let target = SplitTarget::Values(&residuals);
+            let leaf = |samples: &[usize]| {
+                // a single Newton-Raphson step
+                let numerator = samples.iter().map(|&s| residuals[s]).sum::<f64>();
+                let denominator = samples
+                    .iter()
+                    .map(|&s| residuals[s].abs() * (1. - residuals[s].abs()))
+                    .sum::<f64>();
+
+                let mut values = vec![0.; num_classes];
+                if denominator > f64::EPSILON {
+                    values[class] = shrinkage * numerator / denominator;
+                }
+                values
+            };
+
+            round_trees.push(grow_tree(
+                features,
+                &target,
+                &leaf,
+                params.tree,
+                all_samples.clone(),
+                rng,
+            ));
+        }
+
+        for (sample, sample_scores) in scores.iter_mut().enumerate() {
+            for tree in &round_trees {
+                for (score, value) in sample_scores
+                    .iter_mut()
+                    .zip(tree.leaf_values(features.row(sample)))
+                {
+                    *score += value;
+                }
+            }
+        }
+
+        trees.extend(round_trees);
+    }
+
+    TreeEnsemble {
+        trees,
+        base_values,
+        softmax: true,
+    }
+}
+
+fn bootstrap<R: Rng>(num_samples: usize, rng: &mut R) -> Vec<usize> {
+    (0..num_samples)
+        .map(|_| rng.random_range(0..num_samples))
+        .collect()
+}
+
+fn mean(values: &[f64], samples: &[usize]) -> f64 {
+    if samples.is_empty() {
+        return 0.;
+    }
+
+    samples.iter().map(|&s| values[s]).sum::<f64>() / samples.len() as f64
+}
+
+fn softmax(scores: &[f64]) -> Vec<f64> {
+    let max = scores.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let exps = scores.iter().map(|s| (s - max).exp()).collect::<Vec<_>>();
+    let sum = exps.iter().sum::<f64>();
+    exps.into_iter().map(|e| e / sum).collect()
+}
+
+/// The target a tree is fitted to when searching for splits
+enum SplitTarget<'t> {
+    /// class indices, the impurity is measured by the Gini index
+    Classes {
+        classes: &'t [usize],
+        num_classes: usize,
+    },
+    /// continuous values, the impurity is measured by the variance
+    Values(&'t [f64]),
+}
+
+#[derive(Debug, Clone)]
+struct NodeStatistics {
+    count: f64,
+    sums: Vec<f64>,
+    sum_of_squares: f64,
+}
+
+impl SplitTarget<'_> {
+    fn empty_statistics(&self) -> NodeStatistics {
+        let num_sums = match self {
+            SplitTarget::Classes { num_classes, .. } => *num_classes,
+            SplitTarget::Values(_) => 1,
+        };
+
+        NodeStatistics {
+            count: 0.,
+            sums: vec![0.; num_sums],
+            sum_of_squares: 0.,
+        }
+    }
+
+    /// Adds (`sign = 1`) or removes (`sign = -1`) a sample from the statistics
+    fn update(&self, statistics: &mut NodeStatistics, sample: usize, sign: f64) {
+        statistics.count += sign;
+
+        match self {
+            SplitTarget::Classes { classes, .. } => statistics.sums[classes[sample]] += sign,
+            SplitTarget::Values(values) => {
+                let value = values[sample];
+                statistics.sums[0] += sign * value;
+                statistics.sum_of_squares += sign * value * value;
+            }
+        }
+    }
+
+    /// The impurity of a node weighted by its number of samples
+    fn cost(&self, statistics: &NodeStatistics) -> f64 {
+        if statistics.count <= 0. {
+            return 0.;
+        }
+
+        match self {
+            SplitTarget::Classes { .. } => {
+                statistics.count
+                    - statistics.sums.iter().map(|c| c * c).sum::<f64>() / statistics.count
+            }
+            SplitTarget::Values(_) => {
+                statistics.sum_of_squares
+                    - statistics.sums[0] * statistics.sums[0] / statistics.count
+            }
+        }
+    }
+}
+
+fn grow_tree<R: Rng>(
+    features: &FeatureMatrix,
+    target: &SplitTarget,
+    leaf: &dyn Fn(&[usize]) -> Vec<f64>,
+    params: TreeParams,
+    samples: Vec<usize>,
+    rng: &mut R,
+) -> DecisionTree {
+    let mut builder = TreeBuilder {
+        features,
+        target,
+        leaf,
+        params,
+        rng,
+        nodes: Vec::new(),
+    };
+
+    builder.grow(samples, 0);
+
+    DecisionTree {
+        nodes: builder.nodes,
+    }
+}
+
+struct TreeBuilder<'b, R: Rng> {
+    features: &'b FeatureMatrix,
+    target: &'b SplitTarget<'b>,
+    leaf: &'b dyn Fn(&[usize]) -> Vec<f64>,
+    params: TreeParams,
+    rng: &'b mut R,
+    nodes: Vec<TreeNode>,
+}
+
+impl<R: Rng> TreeBuilder<'_, R> {
+    /// Grows the subtree for the `samples` and returns the index of its root node
+    fn grow(&mut self, samples: Vec<usize>, depth: u32) -> usize {
+        let index = self.nodes.len();
+        self.nodes.push(TreeNode::Leaf { values: Vec::new() });
+
+        let split = if depth < self.params.max_depth
+            && samples.len() >= 2 * self.params.min_samples_leaf.max(1)
+        {
+            self.best_split(&samples)
+        } else {
+            None
+        };
+
+        let Some((feature, threshold)) = split else {
+            self.nodes[index] = TreeNode::Leaf {
+                values: (self.leaf)(&samples),
+            };
+            return index;
+        };
+
+        let (left_samples, right_samples): (Vec<usize>, Vec<usize>) = samples
+            .into_iter()
+            .partition(|&sample| self.features.value(sample, feature) <= threshold);
+
+        let left = self.grow(left_samples, depth + 1);
+        let right = self.grow(right_samples, depth + 1);
+
+        self.nodes[index] = TreeNode::Split {
+            feature,
+            threshold,
+            left,
+            right,
+        };
+
+        index
+    }
+
+    /// Finds the split with the lowest impurity among a random subset of the features
+    fn best_split(&mut self, samples: &[usize]) -> Option<(usize, f32)> {
+        let mut total = self.target.empty_statistics();
+        for &sample in samples {
+            self.target.update(&mut total, sample, 1.);
+        }
+        let parent_cost = self.target.cost(&total);
+
+        let num_features = self.features.num_features();
+        let candidates = sample(
+            &mut *self.rng,
+            num_features,
+            self.params.max_features.clamp(1, num_features),
+        );
+
+        let min_samples_leaf = self.params.min_samples_leaf.max(1);
+        let mut sorted = samples.to_vec();
+        let mut best: Option<(f64, usize, f32)> = None;
+
+        for feature in candidates {
+            sorted.sort_by(|&a, &b| {
+                self.features
+                    .value(a, feature)
+                    .total_cmp(&self.features.value(b, feature))
+            });
+
+            let mut left = self.target.empty_statistics();
+            let mut right = total.clone();
+
+            for (i, pair) in sorted.windows(2).enumerate() {
+                self.target.update(&mut left, pair[0], 1.);
+                self.target.update(&mut right, pair[0], -1.);
+
+                let num_left = i + 1;
+                if num_left < min_samples_leaf || sorted.len() - num_left < min_samples_leaf {
+                    continue;
+                }
+
+                let lower = self.features.value(pair[0], feature);
+                let upper = self.features.value(pair[1], feature);
+                if lower >= upper {
+                    // equal values cannot be separated
+                    continue;
+                }
+
+                let cost = self.target.cost(&left) + self.target.cost(&right);
+                if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                    best = Some((cost, feature, split_threshold(lower, upper)));
+                }
+            }
+        }
+
+        best.filter(|(cost, _, _)| *cost < parent_cost - 1e-9 * parent_cost.abs().max(1.))
+            .map(|(_, feature, threshold)| (feature, threshold))
+    }
+}
+
+/// A threshold `t` with `lower <= t < upper`
+fn split_threshold(lower: f32, upper: f32) -> f32 {
+    let threshold = lower + (upper - lower) / 2.;
+    if threshold < upper { threshold } else { lower }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use float_cmp::approx_eq;
+    use rand::{SeedableRng, rngs::StdRng};
+
+    fn grid_samples() -> (FeatureMatrix, Vec<usize>) {
+        let mut features = FeatureMatrix::new(2);
+        let mut classes = Vec::new();
+
+        for i in 0..100 {
+            let x = (i % 10) as f32;
+            let y = (i / 10) as f32;
+            features.push(&[x, y]);
+            classes.push(if x < 3. {
+                0
+            } else if y < 5. {
+                1
+            } else {
+                2
+            });
+        }
+
+        (features, classes)
+    }
+
+    #[test]
+    fn it_trains_random_forest_classifiers() {
+        let (features, classes) = grid_samples();
+
+        let ensemble = train_random_forest_classifier(
+            &features,
+            &classes,
+            3,
+            RandomForestParams {
+                num_trees: 10,
+                tree: TreeParams {
+                    max_depth: 4,
+                    min_samples_leaf: 1,
+                    max_features: 2,
+                },
+            },
+            &mut StdRng::seed_from_u64(42),
+        );
+
+        assert_eq!(ensemble.trees.len(), 10);
+        assert_eq!(ensemble.num_outputs(), 3);
+
+        for (sample, &class) in classes.iter().enumerate() {
+            let scores = ensemble.scores(features.row(sample));
+            assert!(approx_eq!(
+                f64,
+                scores.iter().sum::<f64>(),
+                1.,
+                epsilon = 1e-9
+            ));
+            assert_eq!(ensemble.predict_class(features.row(sample)), class);
+        }
+    }
+
+    #[test]
+    fn it_trains_gradient_boosting_classifiers() {
+        let (features, classes) = grid_samples();
+
+        let ensemble = train_gradient_boosting_classifier(
+            &features,
+            &classes,
+            3,
+            GradientBoostingParams {
+                num_rounds: 20,
+                learning_rate: 0.5,
+                tree: TreeParams {
+                    max_depth: 3,
+                    min_samples_leaf: 1,
+                    max_features: 2,
+                },
+            },
+            &mut StdRng::seed_from_u64(42),
+        );
+
+        assert_eq!(ensemble.trees.len(), 60);
+        assert!(ensemble.softmax);
+
+        for (sample, &class) in classes.iter().enumerate() {
+            assert_eq!(ensemble.predict_class(features.row(sample)), class);
+        }
+    }
+
+    #[test]
+    fn it_trains_regressors() {
+        let mut features = FeatureMatrix::new(1);
+        let mut values = Vec::new();
+        for i in 0..50 {
+            features.push(&[i as f32]);
+            values.push(if i < 25 { 1. } else { 3. });
+        }
+
+        let tree = TreeParams {
+            max_depth: 2,
+            min_samples_leaf: 1,
+            max_features: 1,
+        };
+
+        let forest = train_random_forest_regressor(
+            &features,
+            &values,
+            RandomForestParams { num_trees: 5, tree },
+            &mut StdRng::seed_from_u64(42),
+        );
+        let boosting = train_gradient_boosting_regressor(
+            &features,
+            &values,
+            GradientBoostingParams {
+                num_rounds: 50,
+                learning_rate: 0.3,
+                tree,
+            },
+            &mut StdRng::seed_from_u64(42),
+        );
+
+        assert!(approx_eq!(f64, boosting.base_values[0], 2.));
+
+        for ensemble in [forest, boosting] {
+            assert!(approx_eq!(
+                f64,
+                ensemble.predict_value(&[3.]),
+                1.,
+                epsilon = 1e-3
+            ));
+            assert!(approx_eq!(
+                f64,
+                ensemble.predict_value(&[40.]),
+                3.,
+                epsilon = 1e-3
+            ));
+        }
+    }
+
+    #[test]
+    fn it_splits_between_values() {
+        assert!(approx_eq!(f32, split_threshold(1., 2.), 1.5));
+        assert!(approx_eq!(
+            f32,
+            split_threshold(1., f32::from_bits(1_f32.to_bits() + 1)),
+            1.
+        ));
+    }
+}
@@ -1,13 +1,18 @@
+use crate::datasets::upload::UploadId;
 use crate::error::{self, Error, Result};
 use crate::identifier;
 use crate::layers::listing::LayerCollectionId;
 use crate::machine_learning::MlModelId;
 use crate::projects::ProjectId;
+use crate::tasks::TaskId;
 use crate::users::UserId;
+use crate::workflows::workflow::WorkflowId;
 use async_trait::async_trait;
 use geoengine_datatypes::dataset::{DataProviderId, DatasetId, LayerId};
+use geoengine_datatypes::primitives::DateTime;
 use postgres_types::{FromSql, ToSql};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use snafu::{ResultExt, Snafu};
 use std::str::FromStr;
 use utoipa::ToSchema;
@@ -61,6 +66,8 @@ impl Role {
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, Hash, ToSchema, ToSql, FromSql)]
 pub enum Permission {
     Read,
+    /// Modify the resource without being able to share it, to delete it or to remove its children
+    Write,
     Owner,
 }
 
@@ -68,6 +75,7 @@ impl std::fmt::Display for Permission {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Permission::Read => write!(f, "Read"),
+            Permission::Write => write!(f, "Write"),
             Permission::Owner => write!(f, "Owner"),
         }
     }
@@ -76,14 +84,15 @@ impl std::fmt::Display for Permission {
 impl Permission {
     /// Return true if this permission includes the given permission.
     pub fn allows(&self, permission: &Permission) -> bool {
-        self == permission || (self == &Permission::Owner)
+        self.implied_permissions().contains(permission)
     }
 
     /// Return the implied permissions for the given permission.
     pub fn implied_permissions(&self) -> Vec<Permission> {
         match self {
             Permission::Read => vec![Permission::Read],
-            Permission::Owner => vec![Permission::Owner, Permission::Read],
+            Permission::Write => vec![Permission::Write, Permission::Read],
+            Permission::Owner => vec![Permission::Owner, Permission::Write, Permission::Read],
         }
     }
 
@@ -91,7 +100,8 @@ impl Permission {
     /// One of the returned permissions must be granted to the user.
     pub fn required_permissions(&self) -> Vec<Permission> {
         match self {
-            Permission::Read => vec![Permission::Owner, Permission::Read],
+            Permission::Read => vec![Permission::Owner, Permission::Write, Permission::Read],
+            Permission::Write => vec![Permission::Owner, Permission::Write],
             Permission::Owner => vec![Permission::Owner],
         }
     }
@@ -110,23 +120,48 @@ pub enum ResourceId {
     DatasetId(DatasetId),
     MlModel(MlModelId),
     DataProvider(DataProviderId),
+    Workflow(WorkflowId),
+    Upload(UploadId),
+    Task(TaskId),
 }
 
-impl std::fmt::Display for ResourceId {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl ResourceId {
+    /// The name of the resource type as it is used in the API
+    pub fn resource_type(&self) -> &'static str {
         match self {
-            ResourceId::Layer(layer_id) => write!(f, "layer:{}", layer_id.0),
-            ResourceId::LayerCollection(layer_collection_id) => {
-                write!(f, "layerCollection:{}", layer_collection_id.0)
-            }
-            ResourceId::Project(project_id) => write!(f, "project:{}", project_id.0),
-            ResourceId::DatasetId(dataset_id) => write!(f, "dataset:{}", dataset_id.0),
-            ResourceId::MlModel(ml_model_id) => write!(f, "mlModel:{}", ml_model_id.0),
-            ResourceId::DataProvider(provider_id) => write!(f, "provider:{}", provider_id.0),
+            ResourceId::Layer(_) => "layer",
+            ResourceId::LayerCollection(_) => "layerCollection",
+            ResourceId::Project(_) => "project",
+            ResourceId::DatasetId(_) => "dataset",
+            ResourceId::MlModel(_) => "mlModel",
+            ResourceId::DataProvider(_) => "provider",
+            ResourceId::Workflow(_) => "workflow",
+            ResourceId::Upload(_) => "upload",
+            ResourceId::Task(_) => "task",
+        }
+    }
+
+    pub fn resource_id(&self) -> String {
+        match self {
+            ResourceId::Layer(layer_id) => layer_id.0.clone(),
+            ResourceId::LayerCollection(layer_collection_id) => layer_collection_id.0.clone(),
+            ResourceId::Project(project_id) => project_id.0.to_string(),
+            ResourceId::DatasetId(dataset_id) => dataset_id.0.to_string(),
+            ResourceId::MlModel(ml_model_id) => ml_model_id.0.to_string(),
+            ResourceId::DataProvider(provider_id) => provider_id.0.to_string(),
+            ResourceId::Workflow(workflow_id) => workflow_id.0.to_string(),
+            ResourceId::Upload(upload_id) => upload_id.0.to_string(),
+            ResourceId::Task(task_id) => task_id.0.to_string(),
         }
     }
 }
 
+impl std::fmt::Display for ResourceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.resource_type(), self.resource_id())
+    }
+}
+
 impl From<LayerId> for ResourceId {
     fn from(layer_id: LayerId) -> Self {
         ResourceId::Layer(layer_id)
@@ -157,6 +192,24 @@ impl From<DataProviderId> for ResourceId {
     }
 }
 
+impl From<WorkflowId> for ResourceId {
+    fn from(workflow_id: WorkflowId) -> Self {
+        ResourceId::Workflow(workflow_id)
+    }
+}
+
+impl From<UploadId> for ResourceId {
+    fn from(upload_id: UploadId) -> Self {
+        ResourceId::Upload(upload_id)
+    }
+}
+
+impl From<TaskId> for ResourceId {
+    fn from(task_id: TaskId) -> Self {
+        ResourceId::Task(task_id)
+    }
+}
+
 impl TryFrom<(String, String)> for ResourceId {
     type Error = Error;
 
@@ -170,9 +223,19 @@ impl TryFrom<(String, String)> for ResourceId {
             "dataset" => {
                 ResourceId::DatasetId(DatasetId(Uuid::from_str(&value.1).context(error::Uuid)?))
             }
+            "mlModel" => {
+                ResourceId::MlModel(MlModelId(Uuid::from_str(&value.1).context(error::Uuid)?))
+            }
             "provider" => ResourceId::DataProvider(DataProviderId(
                 Uuid::from_str(&value.1).context(error::Uuid)?,
             )),
+            "workflow" => {
+                ResourceId::Workflow(WorkflowId(Uuid::from_str(&value.1).context(error::Uuid)?))
+            }
+            "upload" => {
+                ResourceId::Upload(UploadId(Uuid::from_str(&value.1).context(error::Uuid)?))
+            }
+            "task" => ResourceId::Task(TaskId(Uuid::from_str(&value.1).context(error::Uuid)?)),
             _ => {
                 return Err(Error::InvalidResourceId {
                     resource_type: value.0,
@@ -189,6 +252,32 @@ pub struct PermissionListing {
     pub resource_id: ResourceId,
     pub role: Role,
     pub permission: Permission,
+    pub expires: Option<DateTime>,
+}
+
+identifier!(ShareLinkId);
+
+/// A link that grants anonymous read access to a resource until it expires or is revoked.
+/// Only the hash of its token is stored, so the token is only known when the link is created.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareLink {
+    pub id: ShareLinkId,
+    pub expires: Option<DateTime>,
+    pub created: DateTime,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedShareLink {
+    pub id: ShareLinkId,
+    /// The secret of the link that is exchanged for a session
+    pub token: String,
+}
+
+/// Hash a secret token, e.g., of a share link, for storing and looking it up in the database
+pub(crate) fn secret_token_hash(token: &str) -> Vec<u8> {
+    Sha256::digest(token.as_bytes()).to_vec()
 }
 
 #[derive(Debug, Snafu)]
@@ -211,10 +300,24 @@ pub enum PermissionDbError {
     },
     #[snafu(display("Cannot revoke own permission"))]
     CannotRevokeOwnPermission,
+    #[snafu(display(
+        "Resources cannot be created with an API token that is restricted to reading or to specific resources."
+    ))]
+    RestrictedApiToken,
     #[snafu(display("Cannot grant Owner permission, because there can only be one owner."))]
     CannotGrantOwnerPermission,
     #[snafu(display("Resource Id {resource_id} is not a valid Uuid."))]
     ResourceIdIsNotAValidUuid { resource_id: String },
+    #[snafu(display("Share link {link} does not exist."))]
+    ShareLinkDoesNotExist { link: ShareLinkId },
+    #[snafu(display(
+        "Share link {link} refers to the invalid resource {resource_type}:{resource_id}."
+    ))]
+    InvalidShareLinkResource {
+        link: ShareLinkId,
+        resource_type: String,
+        resource_id: String,
+    },
     #[snafu(display("An unexpected database error occurred."))]
     Postgres { source: tokio_postgres::Error },
     #[snafu(display("An unexpected database error occurred."))]
@@ -263,6 +366,16 @@ pub trait PermissionDb {
         permission: Permission,
     ) -> Result<(), PermissionDbError>;
 
+    /// Give `permission` to `role` for `resource` until `expires`.
+    /// Requires `Owner` permission for `resource`.
+    async fn add_expiring_permission<R: Into<ResourceId> + Send + Sync>(
+        &self,
+        role: RoleId,
+        resource: R,
+        permission: Permission,
+        expires: DateTime,
+    ) -> Result<(), PermissionDbError>;
+
     /// Remove `permission` from `role` for `resource`.
     /// Requires `Owner` permission for `resource`.
     async fn remove_permission<R: Into<ResourceId> + Send + Sync>(
@@ -287,4 +400,23 @@ pub trait PermissionDb {
         offset: u32,
         limit: u32,
     ) -> Result<Vec<PermissionListing>, PermissionDbError>;
+
+    /// Create a link that gives anonymous `Read` access to `resource` until `expires`.
+    /// Requires `Owner` permission for `resource`.
+    async fn create_share_link<R: Into<ResourceId> + Send + Sync>(
+        &self,
+        resource: R,
+        expires: Option<DateTime>,
+    ) -> Result<CreatedShareLink, PermissionDbError>;
+
+    /// List all share links of `resource`.
+    /// Requires `Owner` permission for `resource`.
+    async fn list_share_links<R: Into<ResourceId> + Send + Sync>(
+        &self,
+        resource: R,
+    ) -> Result<Vec<ShareLink>, PermissionDbError>;
+
+    /// Revoke the share `link`. Sessions that were created with it lose their access.
+    /// Requires `Owner` permission for the resource of the link.
+    async fn remove_share_link(&self, link: ShareLinkId) -> Result<(), PermissionDbError>;
 }
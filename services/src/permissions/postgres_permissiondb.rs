@@ -1,14 +1,18 @@
 use super::{
-    Bb8PermissionDbError, Permission, PermissionDb, PermissionDbError, PermissionListing,
-    PostgresPermissionDbError, ResourceId, RoleId,
+    Bb8PermissionDbError, CreatedShareLink, Permission, PermissionDb, PermissionDbError,
+    PermissionListing, PostgresPermissionDbError, ResourceId, RoleId, ShareLink, ShareLinkId,
+    secret_token_hash,
 };
 use crate::contexts::PostgresDb;
 use crate::error::Result;
 use crate::permissions::{
     CannotGrantOwnerPermissionPermissionDbError, CannotRevokeOwnPermissionPermissionDbError,
-    MustBeAdminPermissionDbError, PermissionDeniedPermissionDbError, Role,
+    MustBeAdminPermissionDbError, PermissionDeniedPermissionDbError,
+    RestrictedApiTokenPermissionDbError, Role,
 };
 use async_trait::async_trait;
+use geoengine_datatypes::primitives::DateTime;
+use geoengine_datatypes::util::Identifier;
 use snafu::{ResultExt, ensure};
 use tokio_postgres::{
     Socket,
@@ -33,6 +37,9 @@ impl ResourceTypeName for ResourceId {
             ResourceId::DatasetId(_) => "dataset_id",
             ResourceId::MlModel(_) => "ml_model_id",
             ResourceId::DataProvider(_) => "provider_id",
+            ResourceId::Workflow(_) => "workflow_id",
+            ResourceId::Upload(_) => "upload_id",
+            ResourceId::Task(_) => "task_id",
         }
     }
 
@@ -52,6 +59,9 @@ impl ResourceTypeName for ResourceId {
             ResourceId::DatasetId(id) => Ok(id.0),
             ResourceId::MlModel(id) => Ok(id.0),
             ResourceId::DataProvider(id) => Ok(id.0),
+            ResourceId::Workflow(id) => Ok(id.0),
+            ResourceId::Upload(id) => Ok(id.0),
+            ResourceId::Task(id) => Ok(id.0),
         }
     }
 }
@@ -63,6 +73,7 @@ impl ResourceTypeName for ResourceId {
 #[async_trait]
 pub trait TxPermissionDb {
     /// Create a new resource. Gives the current user the owner permission.
+    /// Fails for sessions of restricted API tokens.
     async fn create_resource_in_tx<R: Into<ResourceId> + Send + Sync>(
         &self,
         resource: R,
@@ -92,13 +103,14 @@ pub trait TxPermissionDb {
         tx: &tokio_postgres::Transaction<'_>,
     ) -> Result<(), PermissionDbError>;
 
-    /// Give `permission` to `role` for `resource`.
+    /// Give `permission` to `role` for `resource`, optionally until `expires`.
     /// Requires `Owner` permission for `resource`.
     async fn add_permission_in_tx<R: Into<ResourceId> + Send + Sync>(
         &self,
         role: RoleId,
         resource: R,
         permission: Permission,
+        expires: Option<DateTime>,
         tx: &tokio_postgres::Transaction<'_>,
     ) -> Result<(), PermissionDbError>;
 
@@ -142,6 +154,11 @@ where
         resource: R,
         tx: &tokio_postgres::Transaction<'_>,
     ) -> Result<(), PermissionDbError> {
+        ensure!(
+            !self.session.has_restricted_api_token(),
+            RestrictedApiTokenPermissionDbError
+        );
+
         let resource: ResourceId = resource.into();
 
         let stmt = tx
@@ -176,11 +193,21 @@ where
     ) -> Result<bool, PermissionDbError> {
         let resource: ResourceId = resource.into();
 
+        if self
+            .session
+            .api_token
+            .as_ref()
+            .is_some_and(|scope| !scope.allows(&resource, &permission))
+        {
+            return Ok(false);
+        }
+
         // TODO: perform join to get all roles of a user instead of using the roles from the session object?
         let stmt = tx
             .prepare(&format!(
                 "
-            SELECT COUNT(*) FROM permissions WHERE role_id = ANY($1) AND permission = ANY($2) AND {resource_type} = $3;",
+            SELECT COUNT(*) FROM permissions
+            WHERE role_id = ANY($1) AND permission = ANY($2) AND {resource_type} = $3 AND (expires IS NULL OR expires > CURRENT_TIMESTAMP);",
                 resource_type = resource.resource_type_name()
             ))
             .await.context(PostgresPermissionDbError)?;
@@ -240,6 +267,7 @@ where
         role: RoleId,
         resource: R,
         permission: Permission,
+        expires: Option<DateTime>,
         tx: &tokio_postgres::Transaction<'_>,
     ) -> Result<(), PermissionDbError> {
         let resource: ResourceId = resource.into();
@@ -247,17 +275,19 @@ where
         self.ensure_permission_in_tx(resource.clone(), Permission::Owner, tx)
             .await?;
 
+        // granting an existing permission again replaces its expiry
         let stmt = tx
             .prepare(&format!(
                 "
-            INSERT INTO permissions (role_id, permission, {resource_type})
-            VALUES ($1, $2, $3) ON CONFLICT DO NOTHING;",
+            INSERT INTO permissions (role_id, permission, {resource_type}, expires)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (role_id, permission, {resource_type}) DO UPDATE SET expires = EXCLUDED.expires;",
                 resource_type = resource.resource_type_name()
             ))
             .await
             .context(PostgresPermissionDbError)?;
 
-        tx.execute(&stmt, &[&role, &permission, &resource.uuid()?])
+        tx.execute(&stmt, &[&role, &permission, &resource.uuid()?, &expires])
             .await
             .context(PostgresPermissionDbError)?;
 
@@ -345,7 +375,7 @@ where
             .prepare(&format!(
                 "
             SELECT
-                r.id, r.name, p.permission
+                r.id, r.name, p.permission, p.expires
             FROM
                 permissions p JOIN {roles} r ON (p.role_id = r.id)
             WHERE
@@ -395,6 +425,7 @@ where
                     name: row.get(1),
                 },
                 permission: row.get(2),
+                expires: row.get(3),
             })
             .collect();
 
@@ -503,7 +534,34 @@ where
             .await
             .context(PostgresPermissionDbError)?;
 
-        self.add_permission_in_tx(role, resource, permission, &tx)
+        self.add_permission_in_tx(role, resource, permission, None, &tx)
+            .await?;
+
+        tx.commit().await.context(PostgresPermissionDbError)?;
+
+        Ok(())
+    }
+
+    async fn add_expiring_permission<R: Into<ResourceId> + Send + Sync>(
+        &self,
+        role: RoleId,
+        resource: R,
+        permission: Permission,
+        expires: DateTime,
+    ) -> Result<(), PermissionDbError> {
+        ensure!(
+            permission != Permission::Owner,
+            CannotGrantOwnerPermissionPermissionDbError
+        );
+
+        let mut conn = self.conn_pool.get().await.context(Bb8PermissionDbError)?;
+        let tx = conn
+            .build_transaction()
+            .start()
+            .await
+            .context(PostgresPermissionDbError)?;
+
+        self.add_permission_in_tx(role, resource, permission, Some(expires), &tx)
             .await?;
 
         tx.commit().await.context(PostgresPermissionDbError)?;
@@ -571,4 +629,140 @@ where
 
         Ok(permissions)
     }
+
+    async fn create_share_link<R: Into<ResourceId> + Send + Sync>(
+        &self,
+        resource: R,
+        expires: Option<DateTime>,
+    ) -> Result<CreatedShareLink, PermissionDbError> {
+        let resource: ResourceId = resource.into();
+
+        let mut conn = self.conn_pool.get().await.context(Bb8PermissionDbError)?;
+        let tx = conn
+            .build_transaction()
+            .start()
+            .await
+            .context(PostgresPermissionDbError)?;
+
+        self.ensure_permission_in_tx(resource.clone(), Permission::Owner, &tx)
+            .await?;
+
+        let id = ShareLinkId::new();
+        let role = RoleId::new();
+        let token = Uuid::new_v4().to_string();
+
+        // each link has its own role, so revoking the link removes the access of its sessions
+        tx.execute(
+            "INSERT INTO roles (id, name) VALUES ($1, $2);",
+            &[&role, &format!("share_link_{id}")],
+        )
+        .await
+        .context(PostgresPermissionDbError)?;
+
+        self.add_permission_in_tx(role, resource.clone(), Permission::Read, expires, &tx)
+            .await?;
+
+        tx.execute(
+            "
+            INSERT INTO share_links (id, role_id, token_hash, resource_type, resource_id, expires)
+            VALUES ($1, $2, $3, $4, $5, $6);",
+            &[
+                &id,
+                &role,
+                &secret_token_hash(&token),
+                &resource.resource_type(),
+                &resource.resource_id(),
+                &expires,
+            ],
+        )
+        .await
+        .context(PostgresPermissionDbError)?;
+
+        tx.commit().await.context(PostgresPermissionDbError)?;
+
+        Ok(CreatedShareLink { id, token })
+    }
+
+    async fn list_share_links<R: Into<ResourceId> + Send + Sync>(
+        &self,
+        resource: R,
+    ) -> Result<Vec<ShareLink>, PermissionDbError> {
+        let resource: ResourceId = resource.into();
+
+        let mut conn = self.conn_pool.get().await.context(Bb8PermissionDbError)?;
+        let tx = conn
+            .build_transaction()
+            .start()
+            .await
+            .context(PostgresPermissionDbError)?;
+
+        self.ensure_permission_in_tx(resource.clone(), Permission::Owner, &tx)
+            .await?;
+
+        let rows = tx
+            .query(
+                "
+                SELECT id, expires, time_created
+                FROM share_links
+                WHERE resource_type = $1 AND resource_id = $2
+                ORDER BY time_created DESC;",
+                &[&resource.resource_type(), &resource.resource_id()],
+            )
+            .await
+            .context(PostgresPermissionDbError)?;
+
+        tx.commit().await.context(PostgresPermissionDbError)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ShareLink {
+                id: row.get(0),
+                expires: row.get(1),
+                created: row.get(2),
+            })
+            .collect())
+    }
+
+    async fn remove_share_link(&self, link: ShareLinkId) -> Result<(), PermissionDbError> {
+        let mut conn = self.conn_pool.get().await.context(Bb8PermissionDbError)?;
+        let tx = conn
+            .build_transaction()
+            .start()
+            .await
+            .context(PostgresPermissionDbError)?;
+
+        let row = tx
+            .query_opt(
+                "SELECT role_id, resource_type, resource_id FROM share_links WHERE id = $1;",
+                &[&link],
+            )
+            .await
+            .context(PostgresPermissionDbError)?
+            .ok_or(PermissionDbError::ShareLinkDoesNotExist { link })?;
+
+        let role: RoleId = row.get(0);
+        let resource_type: String = row.get(1);
+        let resource_id: String = row.get(2);
+
+        let resource =
+            ResourceId::try_from((resource_type.clone(), resource_id.clone())).map_err(|_| {
+                PermissionDbError::InvalidShareLinkResource {
+                    link,
+                    resource_type,
+                    resource_id,
+                }
+            })?;
+
+        self.ensure_permission_in_tx(resource, Permission::Owner, &tx)
+            .await?;
+
+        // removes the link, its permission and its sessions' role assignments
+        tx.execute("DELETE FROM roles WHERE id = $1;", &[&role])
+            .await
+            .context(PostgresPermissionDbError)?;
+
+        tx.commit().await.context(PostgresPermissionDbError)?;
+
+        Ok(())
+    }
 }
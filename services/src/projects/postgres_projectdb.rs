@@ -108,20 +108,11 @@ where
             .await
             .context(PostgresProjectDbError)?;
 
-        let stmt = trans
-            .prepare(
-                "INSERT INTO permissions (role_id, permission, project_id) VALUES ($1, $2, $3);",
-            )
+        self.create_resource_in_tx(project.id, &trans)
             .await
-            .context(PostgresProjectDbError)?;
-
-        trans
-            .execute(
-                &stmt,
-                &[&self.session.user.id, &Permission::Owner, &project.id],
-            )
-            .await
-            .context(PostgresProjectDbError)?;
+            .boxed_context(AccessFailedProjectDbError {
+                project: project.id,
+            })?;
 
         trans.commit().await.context(PostgresProjectDbError)?;
 
@@ -143,7 +134,7 @@ where
             .await
             .context(PostgresProjectDbError)?;
 
-        self.ensure_permission_in_tx(update.id.into(), Permission::Owner, &trans)
+        self.ensure_permission_in_tx(update.id.into(), Permission::Write, &trans)
             .await
             .boxed_context(AccessFailedProjectDbError { project: update.id })?;
 
@@ -0,0 +1,490 @@
+use crate::config::{QuotaEstimationMode, QuotaTrackingMode, get_config_element};
+use crate::contexts::SessionContext;
+use crate::error::{self, Result};
+use crate::quota::QuotaLimitId;
+use crate::users::UserDb;
+use async_trait::async_trait;
+use geoengine_datatypes::dataset::{DataId, NamedData};
+use geoengine_datatypes::machine_learning::{MlModelName, MlModelVersion};
+use geoengine_datatypes::primitives::{
+    BandSelection, RasterQueryRectangle, SpatialPartition2D, SpatialResolution, TimeInterval,
+    VectorQueryRectangle,
+};
+use geoengine_datatypes::raster::TilingSpecification;
+use geoengine_operators::engine::{
+    CreateSpan, ExecutionContext, InitializedPlotOperator, InitializedRasterOperator,
+    InitializedVectorOperator, MetaData, MetaDataProvider, RasterResultDescriptor, TypedOperator,
+    VectorResultDescriptor, WorkflowOperatorPath,
+};
+use geoengine_operators::machine_learning::MlModelLoadingInfo;
+use geoengine_operators::mock::MockDatasetDataSourceLoadingInfo;
+use geoengine_operators::source::{GdalLoadingInfo, OgrSourceDataset};
+use rayon::ThreadPool;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use utoipa::ToSchema;
+
+/// The estimated quota of a query before it is executed.
+///
+/// A computation unit is a result that an operator produces and not the pixels or features it contains,
+/// i.e., a raster tile of the server's tiling specification (512x512 pixels by default) or a chunk of features.
+/// Each operator of the workflow uses one computation unit per result it produces.
+/// Raster operators produce one tile per band for each tile position in the query bounds
+/// and vector operators are expected to produce a single chunk.
+/// The tiles of raster operators are multiplied by the number of time steps of the query,
+/// i.e., the maximum number of time slices that a raster source of the workflow has in the query's time interval.
+#[derive(Debug, Serialize, Deserialize, ToSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaEstimate {
+    /// The estimated number of computation units of the query
+    pub count: u64,
+    /// The quota that is available to the user
+    pub available: i64,
+    pub exceeds_available: bool,
+    /// The quota limits of the user's roles whose remaining credits of the current period the query exceeds
+    pub exceeded_limits: Vec<ExceededQuotaLimit>,
+    pub operators: Vec<OperatorQuotaEstimate>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExceededQuotaLimit {
+    pub limit: QuotaLimitId,
+    /// The estimated number of computation units of the query that count towards the limit
+    pub count: u64,
+    /// The credits of the limit that are left in the current period
+    pub remaining: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OperatorQuotaEstimate {
+    pub operator_name: String,
+    pub operator_path: String,
+    pub count: u64,
+}
+
+/// How often a query is issued, which determines whether its quota is estimated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryFrequency {
+    /// The query is issued once, e.g., to create a dataset or a plot
+    Once,
+    /// The query is issued for each tile of a map, e.g., by OGC clients
+    PerTile,
+}
+
+/// Estimate the quota of querying the `operator` in the given bounds, resolution and time interval
+pub async fn estimate_quota<C: SessionContext>(
+    ctx: &C,
+    operator: TypedOperator,
+    spatial_bounds: SpatialPartition2D,
+    spatial_resolution: SpatialResolution,
+    time_interval: TimeInterval,
+) -> Result<QuotaEstimate> {
+    let execution_context = ctx.execution_context()?;
+    let estimating_context = QuotaEstimatingExecutionContext::new(&execution_context);
+
+    initialize_operator(operator, &estimating_context).await?;
+
+    estimating_context
+        .estimate(ctx, spatial_bounds, spatial_resolution, time_interval)
+        .await
+}
+
+/// Estimate the quota of a query before it is executed and, depending on the configuration,
+/// reject it or log a warning if it exceeds the available quota.
+///
+/// The `operator` is initialized for the estimate only.
+/// Use [`ensure_initialized_quota_available`] if the caller initializes the operator anyway.
+pub async fn ensure_estimated_quota_available<C: SessionContext>(
+    ctx: &C,
+    operator: TypedOperator,
+    spatial_bounds: SpatialPartition2D,
+    spatial_resolution: SpatialResolution,
+    time_interval: TimeInterval,
+) -> Result<()> {
+    let Some(mode) = estimation_mode(QueryFrequency::Once)? else {
+        return Ok(());
+    };
+
+    let estimate = estimate_quota(
+        ctx,
+        operator,
+        spatial_bounds,
+        spatial_resolution,
+        time_interval,
+    )
+    .await?;
+
+    check_estimate(&estimate, mode)
+}
+
+/// Estimate the quota of a query from the operators that were initialized with the `estimating_context`
+/// and, depending on the configuration, reject it or log a warning if it exceeds the available quota.
+pub async fn ensure_initialized_quota_available<C: SessionContext>(
+    ctx: &C,
+    estimating_context: QuotaEstimatingExecutionContext<'_>,
+    frequency: QueryFrequency,
+    spatial_bounds: SpatialPartition2D,
+    spatial_resolution: SpatialResolution,
+    time_interval: TimeInterval,
+) -> Result<()> {
+    let Some(mode) = estimation_mode(frequency)? else {
+        return Ok(());
+    };
+
+    let estimate = estimating_context
+        .estimate(ctx, spatial_bounds, spatial_resolution, time_interval)
+        .await?;
+
+    check_estimate(&estimate, mode)
+}
+
+/// The estimation mode for queries of the given frequency or `None` if their quota is not estimated
+fn estimation_mode(frequency: QueryFrequency) -> Result<Option<QuotaEstimationMode>> {
+    let config = get_config_element::<crate::config::Quota>()?;
+
+    if config.mode == QuotaTrackingMode::Disabled {
+        return Ok(None);
+    }
+
+    Ok(match (config.estimation, frequency) {
+        (QuotaEstimationMode::Disabled, _) => None,
+        // a warning for each tile would only flood the log, so tiles are only estimated to reject them
+        (QuotaEstimationMode::Warn, QueryFrequency::PerTile) => None,
+        (mode, _) => Some(mode),
+    })
+}
+
+fn check_estimate(estimate: &QuotaEstimate, mode: QuotaEstimationMode) -> Result<()> {
+    if estimate.exceeds_available {
+        if mode == QuotaEstimationMode::Reject {
+            return Err(error::Error::QuotaEstimateExceeded {
+                estimate: estimate.count,
+                available: estimate.available,
+            });
+        }
+
+        tracing::warn!(
+            "Query is estimated to use {} computation units, but only {} are available",
+            estimate.count,
+            estimate.available
+        );
+    }
+
+    for exceeded in &estimate.exceeded_limits {
+        if mode == QuotaEstimationMode::Reject {
+            return Err(error::Error::QuotaEstimateExceedsLimit {
+                limit: exceeded.limit,
+                estimate: exceeded.count,
+                remaining: exceeded.remaining,
+            });
+        }
+
+        tracing::warn!(
+            "Query is estimated to use {} computation units of the quota limit {}, but only {} are left in the current period",
+            exceeded.count,
+            exceeded.limit,
+            exceeded.remaining
+        );
+    }
+
+    Ok(())
+}
+
+async fn initialize_operator(
+    operator: TypedOperator,
+    context: &dyn ExecutionContext,
+) -> geoengine_operators::util::Result<()> {
+    let path = WorkflowOperatorPath::initialize_root();
+
+    match operator {
+        TypedOperator::Raster(operator) => {
+            operator.initialize(path, context).await?;
+        }
+        TypedOperator::Vector(operator) => {
+            operator.initialize(path, context).await?;
+        }
+        TypedOperator::Plot(operator) => {
+            operator.initialize(path, context).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// An operator of the initialized workflow that uses quota when it is queried
+struct EstimatedOperator {
+    name: &'static str,
+    path: WorkflowOperatorPath,
+    /// The number of bands of raster operators
+    bands: Option<u32>,
+    /// The data that source operators load
+    data: Option<String>,
+}
+
+type RasterMetaData =
+    Box<dyn MetaData<GdalLoadingInfo, RasterResultDescriptor, RasterQueryRectangle>>;
+
+/// An execution context that records the operators and raster sources of a workflow while it is initialized
+/// and wraps the operators with the underlying context, s.th. they can be queried afterwards.
+pub struct QuotaEstimatingExecutionContext<'c> {
+    context: &'c dyn ExecutionContext,
+    operators: Mutex<Vec<EstimatedOperator>>,
+    raster_meta_data: Mutex<Vec<RasterMetaData>>,
+}
+
+impl<'c> QuotaEstimatingExecutionContext<'c> {
+    pub fn new(context: &'c dyn ExecutionContext) -> Self {
+        Self {
+            context,
+            operators: Mutex::new(Vec::new()),
+            raster_meta_data: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Estimate the quota of querying the operators that were initialized with this context
+    /// in the given bounds, resolution and time interval
+    async fn estimate<C: SessionContext>(
+        self,
+        ctx: &C,
+        spatial_bounds: SpatialPartition2D,
+        spatial_resolution: SpatialResolution,
+        time_interval: TimeInterval,
+    ) -> Result<QuotaEstimate> {
+        let time_steps = self
+            .time_steps(RasterQueryRectangle {
+                spatial_bounds,
+                time_interval,
+                spatial_resolution,
+                attributes: BandSelection::first(),
+            })
+            .await?;
+
+        let tiles = self
+            .tiling_specification()
+            .strategy(spatial_resolution.x, -spatial_resolution.y)
+            .num_tiles_intersecting(spatial_bounds) as u64;
+
+        let operators = self.into_operators();
+        let operator_count = |operator: &EstimatedOperator| match operator.bands {
+            Some(bands) => tiles * u64::from(bands) * time_steps,
+            None => 1,
+        };
+
+        let count = operators.iter().map(operator_count).sum();
+        let available = ctx.db().quota_available().await?;
+
+        let exceeded_limits = ctx
+            .db()
+            .quota_limits_of_user()
+            .await?
+            .into_iter()
+            .filter_map(|usage| {
+                // limits on data only count the units of the operators that load it
+                let limit_count = match &usage.limit.data {
+                    None => count,
+                    Some(data) => operators
+                        .iter()
+                        .filter(|operator| {
+                            operator
+                                .data
+                                .as_deref()
+                                .is_some_and(|operator_data| data.applies_to(operator_data))
+                        })
+                        .map(operator_count)
+                        .sum(),
+                };
+                let remaining = usage.limit.credits.saturating_sub(usage.used);
+
+                (limit_count > remaining).then_some(ExceededQuotaLimit {
+                    limit: usage.limit.id,
+                    count: limit_count,
+                    remaining,
+                })
+            })
+            .collect();
+
+        let operators = operators
+            .iter()
+            .map(|operator| OperatorQuotaEstimate {
+                operator_name: operator.name.to_string(),
+                operator_path: operator.path.to_string(),
+                count: operator_count(operator),
+            })
+            .collect();
+
+        Ok(QuotaEstimate {
+            count,
+            available,
+            exceeds_available: i64::try_from(count).map_or(true, |count| count > available),
+            exceeded_limits,
+            operators,
+        })
+    }
+
+    /// The maximum number of time slices of the recorded raster sources in the query, but at least one
+    async fn time_steps(&self, query: RasterQueryRectangle) -> Result<u64> {
+        let raster_meta_data = self
+            .raster_meta_data
+            .lock()
+            .map(|meta_data| meta_data.clone())
+            .unwrap_or_default();
+
+        let mut time_steps = 1;
+
+        for meta_data in raster_meta_data {
+            let loading_info = meta_data.loading_info(query.clone()).await?;
+            time_steps = time_steps.max(loading_info.info.count() as u64);
+        }
+
+        Ok(time_steps)
+    }
+
+    fn record(&self, operator: EstimatedOperator) {
+        if let Ok(mut operators) = self.operators.lock() {
+            operators.push(operator);
+        }
+    }
+
+    fn into_operators(self) -> Vec<EstimatedOperator> {
+        self.operators.into_inner().unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl ExecutionContext for QuotaEstimatingExecutionContext<'_> {
+    fn thread_pool(&self) -> &Arc<ThreadPool> {
+        self.context.thread_pool()
+    }
+
+    fn tiling_specification(&self) -> TilingSpecification {
+        self.context.tiling_specification()
+    }
+
+    fn wrap_initialized_raster_operator(
+        &self,
+        op: Box<dyn InitializedRasterOperator>,
+        span: CreateSpan,
+    ) -> Box<dyn InitializedRasterOperator> {
+        self.record(EstimatedOperator {
+            name: op.name(),
+            path: op.path(),
+            bands: Some(op.result_descriptor().bands.count()),
+            data: op.data(),
+        });
+
+        self.context.wrap_initialized_raster_operator(op, span)
+    }
+
+    fn wrap_initialized_vector_operator(
+        &self,
+        op: Box<dyn InitializedVectorOperator>,
+        span: CreateSpan,
+    ) -> Box<dyn InitializedVectorOperator> {
+        self.record(EstimatedOperator {
+            name: op.name(),
+            path: op.path(),
+            bands: None,
+            data: op.data(),
+        });
+
+        self.context.wrap_initialized_vector_operator(op, span)
+    }
+
+    fn wrap_initialized_plot_operator(
+        &self,
+        op: Box<dyn InitializedPlotOperator>,
+        span: CreateSpan,
+    ) -> Box<dyn InitializedPlotOperator> {
+        // plots are not tracked, cf. the `ExecutionContextImpl`
+        self.context.wrap_initialized_plot_operator(op, span)
+    }
+
+    async fn resolve_named_data(
+        &self,
+        data: &NamedData,
+    ) -> Result<DataId, geoengine_operators::error::Error> {
+        self.context.resolve_named_data(data).await
+    }
+
+    async fn ml_model_loading_info(
+        &self,
+        name: &MlModelName,
+        version: MlModelVersion,
+    ) -> Result<MlModelLoadingInfo, geoengine_operators::error::Error> {
+        self.context.ml_model_loading_info(name, version).await
+    }
+}
+
+#[async_trait]
+impl
+    MetaDataProvider<MockDatasetDataSourceLoadingInfo, VectorResultDescriptor, VectorQueryRectangle>
+    for QuotaEstimatingExecutionContext<'_>
+{
+    async fn meta_data(
+        &self,
+        id: &DataId,
+    ) -> Result<
+        Box<
+            dyn MetaData<
+                    MockDatasetDataSourceLoadingInfo,
+                    VectorResultDescriptor,
+                    VectorQueryRectangle,
+                >,
+        >,
+        geoengine_operators::error::Error,
+    > {
+        MetaDataProvider::<
+            MockDatasetDataSourceLoadingInfo,
+            VectorResultDescriptor,
+            VectorQueryRectangle,
+        >::meta_data(self.context, id)
+        .await
+    }
+}
+
+#[async_trait]
+impl MetaDataProvider<OgrSourceDataset, VectorResultDescriptor, VectorQueryRectangle>
+    for QuotaEstimatingExecutionContext<'_>
+{
+    async fn meta_data(
+        &self,
+        id: &DataId,
+    ) -> Result<
+        Box<dyn MetaData<OgrSourceDataset, VectorResultDescriptor, VectorQueryRectangle>>,
+        geoengine_operators::error::Error,
+    > {
+        MetaDataProvider::<OgrSourceDataset, VectorResultDescriptor, VectorQueryRectangle>::meta_data(
+            self.context,
+            id,
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl MetaDataProvider<GdalLoadingInfo, RasterResultDescriptor, RasterQueryRectangle>
+    for QuotaEstimatingExecutionContext<'_>
+{
+    async fn meta_data(
+        &self,
+        id: &DataId,
+    ) -> Result<
+        Box<dyn MetaData<GdalLoadingInfo, RasterResultDescriptor, RasterQueryRectangle>>,
+        geoengine_operators::error::Error,
+    > {
+        let meta_data = MetaDataProvider::<
+            GdalLoadingInfo,
+            RasterResultDescriptor,
+            RasterQueryRectangle,
+        >::meta_data(self.context, id)
+        .await?;
+
+        if let Ok(mut raster_meta_data) = self.raster_meta_data.lock() {
+            raster_meta_data.push(meta_data.clone());
+        }
+
+        Ok(meta_data)
+    }
+}
@@ -0,0 +1,113 @@
+use crate::api::model::datatypes::DataProviderId;
+use crate::datasets::DatasetName;
+use crate::identifier;
+use crate::permissions::RoleId;
+use geoengine_macros::type_tag;
+use postgres_types::{FromSql, ToSql};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+identifier!(QuotaLimitId);
+
+/// A limit on the computation units that the members of a role can use together within a period.
+/// The used quota is reset at the start of each period.
+///
+/// Limits without data apply to all computations.
+/// Limits on a dataset or provider only count the computation units that are spent on loading its data.
+///
+/// A computation unit is a result that an operator produces, e.g., a raster tile or a chunk of features.
+/// Limits count these units and not the pixels or features they contain.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaLimit {
+    pub id: QuotaLimitId,
+    pub role: RoleId,
+    pub data: Option<QuotaLimitData>,
+    pub period: QuotaPeriod,
+    /// The computation units that can be used in each period
+    pub credits: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+    "role": "4e8081b6-8aa6-4275-af0c-2fa2da557d28",
+    "data": {"type": "dataset", "dataset": "commercial_imagery"},
+    "period": "month",
+    "credits": 100_000
+}))]
+pub struct CreateQuotaLimit {
+    pub role: RoleId,
+    pub data: Option<QuotaLimitData>,
+    pub period: QuotaPeriod,
+    /// The computation units that can be used in each period.
+    /// A raster tile of the default tiling specification has 512x512 pixels per band,
+    /// so a pixel allowance corresponds to its number of pixels divided by 262,144.
+    pub credits: u64,
+}
+
+/// A quota limit together with the computation units that were used in the current period
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaLimitUsage {
+    pub limit: QuotaLimit,
+    pub used: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ToSchema, ToSql, FromSql)]
+#[serde(rename_all = "camelCase")]
+#[postgres(name = "QuotaPeriod")]
+pub enum QuotaPeriod {
+    Day,
+    Month,
+}
+
+/// The data whose usage is limited
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", untagged)]
+#[schema(discriminator = "type")]
+pub enum QuotaLimitData {
+    Dataset(DatasetQuotaLimit),
+    Provider(ProviderQuotaLimit),
+}
+
+/// Limit the usage of a dataset
+#[type_tag(value = "dataset")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DatasetQuotaLimit {
+    pub dataset: DatasetName,
+}
+
+/// Limit the usage of all data of a provider
+#[type_tag(value = "provider")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderQuotaLimit {
+    pub provider: DataProviderId,
+}
+
+impl QuotaLimitData {
+    pub fn dataset(dataset: DatasetName) -> Self {
+        Self::Dataset(DatasetQuotaLimit {
+            r#type: Default::default(),
+            dataset,
+        })
+    }
+
+    pub fn provider(provider: DataProviderId) -> Self {
+        Self::Provider(ProviderQuotaLimit {
+            r#type: Default::default(),
+            provider,
+        })
+    }
+
+    /// Whether the limit applies to the `data` that an operator loads.
+    /// This matches the data in the same way as the usage of the limits is counted in the database.
+    pub fn applies_to(&self, data: &str) -> bool {
+        match self {
+            Self::Dataset(limit) => limit.dataset.to_string() == data,
+            Self::Provider(limit) => data.contains(&format!(":{}:", limit.provider)),
+        }
+    }
+}
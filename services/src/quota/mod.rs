@@ -1,3 +1,16 @@
+mod estimation;
+mod limits;
+
+pub use estimation::{
+    ExceededQuotaLimit, OperatorQuotaEstimate, QueryFrequency, QuotaEstimate,
+    QuotaEstimatingExecutionContext, ensure_estimated_quota_available,
+    ensure_initialized_quota_available, estimate_quota,
+};
+pub use limits::{
+    CreateQuotaLimit, DatasetQuotaLimit, ProviderQuotaLimit, QuotaLimit, QuotaLimitData,
+    QuotaLimitId, QuotaLimitUsage, QuotaPeriod,
+};
+
 use crate::config::QuotaTrackingMode;
 use crate::users::{UserDb, UserId, UserSession};
 use geoengine_datatypes::{primitives::DateTime, util::test::TestDefault};
@@ -14,6 +27,10 @@ use uuid::Uuid;
 #[snafu(context(suffix(false)))]
 pub enum QuotaError {
     QuotaExhausted,
+    #[snafu(display("The quota limit {limit} is exhausted for the current period"))]
+    QuotaLimitExhausted {
+        limit: QuotaLimitId,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,330 @@
+use crate::error::{self, Result};
+use chrono::{Datelike, Duration, TimeZone, Timelike, Utc};
+use geoengine_datatypes::primitives::DateTime;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use utoipa::{PartialSchema, ToSchema};
+
+/// A cron expression with the five fields `minute hour day-of-month month day-of-week`.
+///
+/// Each field is either `*`, a single value, a range `a-b` or a comma separated list of those.
+/// Wildcards and ranges may have a step, e.g., `*/15` or `1-5/2`.
+/// Days of week range from `0` (Sunday) to `7` (Sunday).
+/// If both, the day of month and the day of week, are restricted, a day matches if either of them matches.
+/// All times are in UTC.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CronExpression {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    days_of_month_restricted: bool,
+    days_of_week_restricted: bool,
+}
+
+/// The maximum time span to search for the next matching time.
+/// Expressions like `0 0 30 2 *` never match.
+const MAX_SEARCH_YEARS: i64 = 5;
+
+struct CronField {
+    name: &'static str,
+    min: u32,
+    max: u32,
+}
+
+const MINUTE: CronField = CronField {
+    name: "minute",
+    min: 0,
+    max: 59,
+};
+const HOUR: CronField = CronField {
+    name: "hour",
+    min: 0,
+    max: 23,
+};
+const DAY_OF_MONTH: CronField = CronField {
+    name: "day of month",
+    min: 1,
+    max: 31,
+};
+const MONTH: CronField = CronField {
+    name: "month",
+    min: 1,
+    max: 12,
+};
+const DAY_OF_WEEK: CronField = CronField {
+    name: "day of week",
+    min: 0,
+    max: 7,
+};
+
+impl CronField {
+    /// Parse the field into a bit set of the matching values
+    fn parse(&self, field: &str) -> Result<u64, String> {
+        let mut values = 0;
+
+        for part in field.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => (range, Some(self.parse_step(step)?)),
+                None => (part, None),
+            };
+
+            let (start, end) = if range == "*" {
+                (self.min, self.max)
+            } else if let Some((start, end)) = range.split_once('-') {
+                (self.parse_value(start)?, self.parse_value(end)?)
+            } else {
+                let value = self.parse_value(range)?;
+                // `a/n` is short for `a-max/n`
+                (value, if step.is_some() { self.max } else { value })
+            };
+
+            if start > end {
+                return Err(format!("invalid {name} range `{range}`", name = self.name));
+            }
+
+            for value in (start..=end).step_by(step.unwrap_or(1) as usize) {
+                values |= 1 << value;
+            }
+        }
+
+        Ok(values)
+    }
+
+    fn parse_value(&self, value: &str) -> Result<u32, String> {
+        match value.parse::<u32>() {
+            Ok(value) if (self.min..=self.max).contains(&value) => Ok(value),
+            _ => Err(format!(
+                "{name} `{value}` is not a number between {min} and {max}",
+                name = self.name,
+                min = self.min,
+                max = self.max
+            )),
+        }
+    }
+
+    fn parse_step(&self, step: &str) -> Result<u32, String> {
+        match step.parse::<u32>() {
+            Ok(step) if step > 0 => Ok(step),
+            _ => Err(format!(
+                "{name} step `{step}` is not a positive number",
+                name = self.name
+            )),
+        }
+    }
+}
+
+impl CronExpression {
+    /// The first matching time that is strictly after `time`.
+    /// Returns `None` if the expression does not match within the next years.
+    pub fn next_after(&self, time: DateTime) -> Option<DateTime> {
+        let time: chrono::DateTime<Utc> = time.into();
+
+        let limit = time + Duration::days(366 * MAX_SEARCH_YEARS);
+        let mut next = time.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+
+        while next <= limit {
+            if !contains(self.months, next.month()) {
+                let (year, month) = if next.month() == 12 {
+                    (next.year() + 1, 1)
+                } else {
+                    (next.year(), next.month() + 1)
+                };
+                next = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?;
+                continue;
+            }
+
+            if !self.matches_day(&next) {
+                next = (next.date_naive() + Duration::days(1))
+                    .and_hms_opt(0, 0, 0)?
+                    .and_utc();
+                continue;
+            }
+
+            if !contains(self.hours, next.hour()) {
+                next = next.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+
+            if !contains(self.minutes, next.minute()) {
+                next += Duration::minutes(1);
+                continue;
+            }
+
+            return Some(next.into());
+        }
+
+        None
+    }
+
+    fn matches_day(&self, time: &chrono::DateTime<Utc>) -> bool {
+        let day_of_month = contains(self.days_of_month, time.day());
+        let day_of_week = contains(self.days_of_week, time.weekday().num_days_from_sunday());
+
+        match (self.days_of_month_restricted, self.days_of_week_restricted) {
+            (true, true) => day_of_month || day_of_week,
+            (true, false) => day_of_month,
+            (false, true) => day_of_week,
+            (false, false) => true,
+        }
+    }
+}
+
+fn contains(values: u64, value: u32) -> bool {
+    values & (1 << value) != 0
+}
+
+impl FromStr for CronExpression {
+    type Err = error::Error;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: String| error::Error::InvalidCronExpression {
+            expression: expression.to_string(),
+            reason,
+        };
+
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+
+        let [minutes, hours, days_of_month, months, days_of_week] = fields.as_slice() else {
+            return Err(invalid(format!(
+                "expected 5 fields, found {}",
+                fields.len()
+            )));
+        };
+
+        let mut days_of_week_values = DAY_OF_WEEK.parse(days_of_week).map_err(invalid)?;
+        // 7 is an alias for Sunday
+        if contains(days_of_week_values, 7) {
+            days_of_week_values |= 1;
+        }
+
+        Ok(Self {
+            expression: fields.join(" "),
+            minutes: MINUTE.parse(minutes).map_err(invalid)?,
+            hours: HOUR.parse(hours).map_err(invalid)?,
+            days_of_month: DAY_OF_MONTH.parse(days_of_month).map_err(invalid)?,
+            months: MONTH.parse(months).map_err(invalid)?,
+            days_of_week: days_of_week_values,
+            days_of_month_restricted: !days_of_month.starts_with('*'),
+            days_of_week_restricted: !days_of_week.starts_with('*'),
+        })
+    }
+}
+
+impl fmt::Display for CronExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+impl Serialize for CronExpression {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.expression)
+    }
+}
+
+impl<'de> Deserialize<'de> for CronExpression {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl PartialSchema for CronExpression {
+    fn schema() -> utoipa::openapi::RefOr<utoipa::openapi::schema::Schema> {
+        use utoipa::openapi::schema::{ObjectBuilder, SchemaType, Type};
+        ObjectBuilder::new()
+            .schema_type(SchemaType::Type(Type::String))
+            .description(Some(
+                "A cron expression `minute hour day-of-month month day-of-week` in UTC",
+            ))
+            .into()
+    }
+}
+
+impl ToSchema for CronExpression {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn next(expression: &str, time: &str) -> Option<String> {
+        let expression: CronExpression = expression.parse().unwrap();
+        expression
+            .next_after(time.parse().unwrap())
+            .map(DateTime::to_datetime_string)
+    }
+
+    #[test]
+    fn it_parses_expressions() {
+        assert!("* * * * *".parse::<CronExpression>().is_ok());
+        assert!("*/15 0-6,22 1 1-12/3 0,7".parse::<CronExpression>().is_ok());
+        assert!("0 6 * * 1-5".parse::<CronExpression>().is_ok());
+
+        assert!("* * * *".parse::<CronExpression>().is_err());
+        assert!("60 * * * *".parse::<CronExpression>().is_err());
+        assert!("* * 0 * *".parse::<CronExpression>().is_err());
+        assert!("* * * * 8".parse::<CronExpression>().is_err());
+        assert!("5-1 * * * *".parse::<CronExpression>().is_err());
+        assert!("*/0 * * * *".parse::<CronExpression>().is_err());
+        assert!("a * * * *".parse::<CronExpression>().is_err());
+    }
+
+    #[test]
+    fn it_computes_the_next_time() {
+        assert_eq!(
+            next("* * * * *", "2024-01-01T00:00:30+00:00").as_deref(),
+            Some("2024-01-01T00:01:00+00:00")
+        );
+        assert_eq!(
+            next("*/15 * * * *", "2024-01-01T00:15:00+00:00").as_deref(),
+            Some("2024-01-01T00:30:00+00:00")
+        );
+        assert_eq!(
+            next("0 6 * * *", "2024-01-01T06:00:00+00:00").as_deref(),
+            Some("2024-01-02T06:00:00+00:00")
+        );
+        // first of the next month
+        assert_eq!(
+            next("0 0 1 * *", "2024-01-15T12:00:00+00:00").as_deref(),
+            Some("2024-02-01T00:00:00+00:00")
+        );
+        // next year
+        assert_eq!(
+            next("30 2 1 1 *", "2024-01-01T03:00:00+00:00").as_deref(),
+            Some("2025-01-01T02:30:00+00:00")
+        );
+        // leap day
+        assert_eq!(
+            next("0 0 29 2 *", "2024-03-01T00:00:00+00:00").as_deref(),
+            Some("2028-02-29T00:00:00+00:00")
+        );
+        // 2024-01-06 is a Saturday
+        assert_eq!(
+            next("0 8 * * 1-5", "2024-01-05T09:00:00+00:00").as_deref(),
+            Some("2024-01-08T08:00:00+00:00")
+        );
+        assert_eq!(
+            next("0 8 * * 7", "2024-01-05T09:00:00+00:00").as_deref(),
+            Some("2024-01-07T08:00:00+00:00")
+        );
+        // either the day of month or the day of week
+        assert_eq!(
+            next("0 0 10 * 0", "2024-01-05T00:00:00+00:00").as_deref(),
+            Some("2024-01-07T00:00:00+00:00")
+        );
+        assert_eq!(
+            next("0 0 30 2 *", "2024-01-01T00:00:00+00:00").as_deref(),
+            None
+        );
+    }
+}
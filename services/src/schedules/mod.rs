@@ -0,0 +1,199 @@
+mod cron;
+mod postgres;
+mod scheduler;
+
+pub use cron::CronExpression;
+pub use scheduler::{run_due_schedules, run_scheduler};
+
+use crate::api::model::datatypes::{
+    BoundingBox2D, SpatialPartition2D, SpatialResolution, TimeStep,
+};
+use crate::datasets::{DatasetName, VectorDatasetFormat};
+use crate::error::Result;
+use crate::identifier;
+use crate::tasks::{TaskId, TaskListOptions};
+use crate::users::{ApiTokenId, UserId};
+use crate::workflows::workflow::WorkflowId;
+use async_trait::async_trait;
+use geoengine_datatypes::primitives::DateTime;
+use geoengine_macros::type_tag;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+identifier!(ScheduleId);
+
+identifier!(ScheduleRunId);
+
+/// A schedule runs a task for a workflow whenever its cron expression matches.
+/// Tasks are run on behalf of the user that owns the schedule.
+/// If the schedule was created with an API token, the tasks are run with the scope of the token.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Schedule {
+    pub id: ScheduleId,
+    pub name: String,
+    pub workflow_id: WorkflowId,
+    pub cron: CronExpression,
+    /// The length of the time interval that ends at the scheduled time and is queried from the workflow
+    pub time_window: TimeStep,
+    pub task: ScheduledTask,
+    pub enabled: bool,
+    /// The next time the schedule runs. It is empty if the schedule is disabled.
+    #[schema(value_type = Option<String>)]
+    pub next_run: Option<DateTime>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({
+    "name": "daily NDVI",
+    "workflowId": "3f2c7b2a-5d3e-4b8f-9a59-1b7b3d6f8e7a",
+    "cron": "0 6 * * *",
+    "timeWindow": {"granularity": "days", "step": 1},
+    "task": {"type": "appendRasterDataset", "dataset": "ndvi_daily"}
+}))]
+pub struct CreateSchedule {
+    pub name: String,
+    pub workflow_id: WorkflowId,
+    pub cron: CronExpression,
+    /// The length of the time interval that ends at the scheduled time and is queried from the workflow
+    pub time_window: TimeStep,
+    pub task: ScheduledTask,
+    #[serde(default = "default_enabled")]
+    #[schema(default = default_enabled)]
+    pub enabled: bool,
+}
+
+const fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(example = json!({"enabled": false}))]
+pub struct UpdateSchedule {
+    pub name: Option<String>,
+    pub workflow_id: Option<WorkflowId>,
+    pub cron: Option<CronExpression>,
+    pub time_window: Option<TimeStep>,
+    pub task: Option<ScheduledTask>,
+    pub enabled: Option<bool>,
+}
+
+/// The task that a schedule runs.
+/// Each run queries the workflow for the time window that ends at the scheduled time.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", untagged)]
+#[schema(discriminator = "type")]
+pub enum ScheduledTask {
+    AppendRasterDataset(ScheduledAppendRasterDataset),
+    RasterDataset(ScheduledRasterDataset),
+    VectorDataset(ScheduledVectorDataset),
+}
+
+/// Append the missing time steps to an existing raster dataset
+#[type_tag(value = "appendRasterDataset")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledAppendRasterDataset {
+    pub dataset: DatasetName,
+    #[serde(default = "default_as_cog")]
+    #[schema(default = default_as_cog)]
+    pub as_cog: bool,
+}
+
+/// Create a new raster dataset for each run
+#[type_tag(value = "rasterDataset")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledRasterDataset {
+    pub display_name: String,
+    pub description: Option<String>,
+    pub spatial_bounds: SpatialPartition2D,
+    pub spatial_resolution: SpatialResolution,
+    #[serde(default = "default_as_cog")]
+    #[schema(default = default_as_cog)]
+    pub as_cog: bool,
+}
+
+/// Create a new vector dataset for each run
+#[type_tag(value = "vectorDataset")]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduledVectorDataset {
+    pub display_name: String,
+    pub description: Option<String>,
+    pub spatial_bounds: BoundingBox2D,
+    pub spatial_resolution: SpatialResolution,
+    #[serde(default)]
+    pub format: VectorDatasetFormat,
+}
+
+const fn default_as_cog() -> bool {
+    true
+}
+
+/// A run of a schedule as it is stored in a [`ScheduleDb`].
+/// The run has a task if it was started successfully and an error otherwise.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StoredScheduleRun {
+    pub time_scheduled: DateTime,
+    pub task_id: Option<TaskId>,
+    pub error: Option<String>,
+}
+
+/// A schedule that is due, together with the run that was created for it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DueSchedule {
+    pub run_id: ScheduleRunId,
+    pub owner: UserId,
+    /// The API token that the schedule was created with
+    pub api_token: Option<ApiTokenId>,
+    pub time_scheduled: DateTime,
+    pub schedule: Schedule,
+}
+
+/// Storage of the schedules of users
+#[async_trait]
+pub trait ScheduleDb: Send + Sync {
+    /// Create a schedule that is owned by the session's user.
+    /// It keeps the API token of the session, if any.
+    async fn create_schedule(&self, schedule: CreateSchedule) -> Result<ScheduleId>;
+
+    /// List all schedules of the session's user
+    async fn list_schedules(&self) -> Result<Vec<Schedule>>;
+
+    /// Load a schedule of the session's user
+    async fn load_schedule(&self, schedule: ScheduleId) -> Result<Schedule>;
+
+    /// Update a schedule of the session's user.
+    /// The next run is computed anew from the current time.
+    async fn update_schedule(&self, schedule: ScheduleId, update: UpdateSchedule) -> Result<()>;
+
+    /// Delete a schedule of the session's user together with its runs
+    async fn delete_schedule(&self, schedule: ScheduleId) -> Result<()>;
+
+    /// List the runs of a schedule of the session's user, the most recent first.
+    /// The status filter of the `options` applies to the tasks of the runs.
+    async fn list_schedule_runs(
+        &self,
+        schedule: ScheduleId,
+        options: &TaskListOptions,
+    ) -> Result<Vec<StoredScheduleRun>>;
+
+    /// Claim the schedules of all users that are due at `now`.
+    /// For each of them, a run is created and the next run is advanced.
+    ///
+    /// Requires admin privileges.
+    async fn claim_due_schedules(&self, now: DateTime) -> Result<Vec<DueSchedule>>;
+
+    /// Store the task that was started for a run.
+    ///
+    /// Requires admin privileges.
+    async fn set_schedule_run_task(&self, run: ScheduleRunId, task_id: TaskId) -> Result<()>;
+
+    /// Store the error that prevented a run from starting its task.
+    ///
+    /// Requires admin privileges.
+    async fn set_schedule_run_error(&self, run: ScheduleRunId, error: &str) -> Result<()>;
+}
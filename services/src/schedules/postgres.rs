@@ -0,0 +1,363 @@
+use super::{
+    CreateSchedule, CronExpression, DueSchedule, Schedule, ScheduleDb, ScheduleId, ScheduleRunId,
+    ScheduledTask, StoredScheduleRun, UpdateSchedule,
+};
+use crate::contexts::PostgresDb;
+use crate::error::{self, Result};
+use crate::tasks::{TaskId, TaskListOptions};
+use crate::util::Identifier;
+use crate::workflows::registry::WorkflowRegistry;
+use crate::workflows::workflow::Workflow;
+use async_trait::async_trait;
+use geoengine_datatypes::primitives::DateTime;
+use snafu::{ResultExt, ensure};
+use tokio_postgres::{
+    Row, Socket,
+    tls::{MakeTlsConnect, TlsConnect},
+};
+
+#[async_trait]
+impl<Tls> ScheduleDb for PostgresDb<Tls>
+where
+    Tls: MakeTlsConnect<Socket> + Clone + Send + Sync + 'static + std::fmt::Debug,
+    <Tls as MakeTlsConnect<Socket>>::Stream: Send + Sync,
+    <Tls as MakeTlsConnect<Socket>>::TlsConnect: Send,
+    <<Tls as MakeTlsConnect<Socket>>::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    async fn create_schedule(&self, schedule: CreateSchedule) -> Result<ScheduleId> {
+        ensure!(
+            !self.session.has_restricted_api_token(),
+            error::RestrictedApiToken
+        );
+
+        let workflow = self.load_workflow(&schedule.workflow_id).await?;
+        check_task_fits_workflow(&schedule.task, &workflow)?;
+
+        let conn = self.conn_pool.get().await?;
+
+        let stmt = conn
+            .prepare(
+                "
+            INSERT INTO schedules (
+                id,
+                user_id,
+                name,
+                workflow_id,
+                cron,
+                time_window,
+                task,
+                enabled,
+                next_run,
+                api_token_id
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10);",
+            )
+            .await?;
+
+        let id = ScheduleId::new();
+
+        conn.execute(
+            &stmt,
+            &[
+                &id,
+                &self.session.user.id,
+                &schedule.name,
+                &schedule.workflow_id,
+                &schedule.cron.to_string(),
+                &schedule.time_window,
+                &serde_json::to_value(&schedule.task).context(error::SerdeJson)?,
+                &schedule.enabled,
+                &next_run(&schedule.cron, schedule.enabled),
+                &self.session.api_token.as_ref().map(|scope| scope.token),
+            ],
+        )
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn list_schedules(&self) -> Result<Vec<Schedule>> {
+        let conn = self.conn_pool.get().await?;
+
+        let stmt = conn
+            .prepare(
+                "
+            SELECT id, name, workflow_id, cron, time_window, task, enabled, next_run
+            FROM schedules
+            WHERE user_id = $1
+            ORDER BY time_created, id;",
+            )
+            .await?;
+
+        let rows = conn.query(&stmt, &[&self.session.user.id]).await?;
+
+        rows.iter().map(schedule_from_row).collect()
+    }
+
+    async fn load_schedule(&self, schedule: ScheduleId) -> Result<Schedule> {
+        let conn = self.conn_pool.get().await?;
+
+        let stmt = conn
+            .prepare(
+                "
+            SELECT id, name, workflow_id, cron, time_window, task, enabled, next_run
+            FROM schedules
+            WHERE id = $1 AND user_id = $2;",
+            )
+            .await?;
+
+        let row = conn
+            .query_opt(&stmt, &[&schedule, &self.session.user.id])
+            .await?
+            .ok_or(error::Error::UnknownScheduleId {
+                schedule_id: schedule,
+            })?;
+
+        schedule_from_row(&row)
+    }
+
+    async fn update_schedule(&self, schedule: ScheduleId, update: UpdateSchedule) -> Result<()> {
+        ensure!(
+            !self.session.has_restricted_api_token(),
+            error::RestrictedApiToken
+        );
+
+        let current = self.load_schedule(schedule).await?;
+
+        let workflow_id = update.workflow_id.unwrap_or(current.workflow_id);
+        let task = update.task.unwrap_or(current.task);
+        let cron = update.cron.unwrap_or(current.cron);
+        let enabled = update.enabled.unwrap_or(current.enabled);
+
+        let workflow = self.load_workflow(&workflow_id).await?;
+        check_task_fits_workflow(&task, &workflow)?;
+
+        let conn = self.conn_pool.get().await?;
+
+        let stmt = conn
+            .prepare(
+                "
+            UPDATE schedules
+            SET
+                name = $3,
+                workflow_id = $4,
+                cron = $5,
+                time_window = $6,
+                task = $7,
+                enabled = $8,
+                next_run = $9
+            WHERE id = $1 AND user_id = $2;",
+            )
+            .await?;
+
+        conn.execute(
+            &stmt,
+            &[
+                &schedule,
+                &self.session.user.id,
+                &update.name.unwrap_or(current.name),
+                &workflow_id,
+                &cron.to_string(),
+                &update.time_window.unwrap_or(current.time_window),
+                &serde_json::to_value(&task).context(error::SerdeJson)?,
+                &enabled,
+                &next_run(&cron, enabled),
+            ],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_schedule(&self, schedule: ScheduleId) -> Result<()> {
+        let conn = self.conn_pool.get().await?;
+
+        let stmt = conn
+            .prepare("DELETE FROM schedules WHERE id = $1 AND user_id = $2;")
+            .await?;
+
+        let deleted = conn
+            .execute(&stmt, &[&schedule, &self.session.user.id])
+            .await?;
+
+        ensure!(
+            deleted > 0,
+            error::UnknownScheduleId {
+                schedule_id: schedule
+            }
+        );
+
+        Ok(())
+    }
+
+    async fn list_schedule_runs(
+        &self,
+        schedule: ScheduleId,
+        options: &TaskListOptions,
+    ) -> Result<Vec<StoredScheduleRun>> {
+        // only the owner may see the runs
+        self.load_schedule(schedule).await?;
+
+        let conn = self.conn_pool.get().await?;
+
+        let stmt = conn
+            .prepare(
+                "
+            SELECT r.time_scheduled, r.task_id, r.error
+            FROM schedule_runs r LEFT JOIN tasks t ON (r.task_id = t.id)
+            WHERE
+                r.schedule_id = $1 AND
+                ($2::\"TaskStatusType\" IS NULL OR t.status = $2)
+            ORDER BY r.time_scheduled DESC, r.id
+            OFFSET $3
+            LIMIT $4;",
+            )
+            .await?;
+
+        let rows = conn
+            .query(
+                &stmt,
+                &[
+                    &schedule,
+                    &options.filter,
+                    &i64::from(options.offset),
+                    &i64::from(options.limit),
+                ],
+            )
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| StoredScheduleRun {
+                time_scheduled: row.get(0),
+                task_id: row.get(1),
+                error: row.get(2),
+            })
+            .collect())
+    }
+
+    async fn claim_due_schedules(&self, now: DateTime) -> Result<Vec<DueSchedule>> {
+        ensure!(self.session.is_admin(), error::PermissionDenied);
+
+        let mut conn = self.conn_pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        // other instances skip the schedules that are claimed by this one
+        let rows = tx
+            .query(
+                "
+            SELECT
+                id, name, workflow_id, cron, time_window, task, enabled, next_run, user_id, api_token_id
+            FROM schedules
+            WHERE enabled AND next_run <= $1
+            FOR UPDATE SKIP LOCKED;",
+                &[&now],
+            )
+            .await?;
+
+        let update_stmt = tx
+            .prepare("UPDATE schedules SET next_run = $2 WHERE id = $1;")
+            .await?;
+
+        let insert_stmt = tx
+            .prepare(
+                "
+            INSERT INTO schedule_runs (id, schedule_id, time_scheduled)
+            VALUES ($1, $2, $3);",
+            )
+            .await?;
+
+        let mut due_schedules = Vec::with_capacity(rows.len());
+
+        for row in &rows {
+            let mut schedule = schedule_from_row(row)?;
+            let time_scheduled: DateTime = row.get(7);
+
+            // runs that were missed, e.g., during a downtime, are not repeated
+            schedule.next_run = schedule.cron.next_after(now);
+
+            tx.execute(&update_stmt, &[&schedule.id, &schedule.next_run])
+                .await?;
+
+            let run_id = ScheduleRunId::new();
+
+            tx.execute(&insert_stmt, &[&run_id, &schedule.id, &time_scheduled])
+                .await?;
+
+            due_schedules.push(DueSchedule {
+                run_id,
+                owner: row.get(8),
+                api_token: row.get(9),
+                time_scheduled,
+                schedule,
+            });
+        }
+
+        tx.commit().await?;
+
+        Ok(due_schedules)
+    }
+
+    async fn set_schedule_run_task(&self, run: ScheduleRunId, task_id: TaskId) -> Result<()> {
+        ensure!(self.session.is_admin(), error::PermissionDenied);
+
+        let conn = self.conn_pool.get().await?;
+
+        let stmt = conn
+            .prepare("UPDATE schedule_runs SET task_id = $2 WHERE id = $1;")
+            .await?;
+
+        conn.execute(&stmt, &[&run, &task_id]).await?;
+
+        Ok(())
+    }
+
+    async fn set_schedule_run_error(&self, run: ScheduleRunId, error: &str) -> Result<()> {
+        ensure!(self.session.is_admin(), error::PermissionDenied);
+
+        let conn = self.conn_pool.get().await?;
+
+        let stmt = conn
+            .prepare("UPDATE schedule_runs SET error = $2 WHERE id = $1;")
+            .await?;
+
+        conn.execute(&stmt, &[&run, &error]).await?;
+
+        Ok(())
+    }
+}
+
+fn schedule_from_row(row: &Row) -> Result<Schedule> {
+    Ok(Schedule {
+        id: row.get(0),
+        name: row.get(1),
+        workflow_id: row.get(2),
+        cron: row.get::<_, String>(3).parse()?,
+        time_window: row.get(4),
+        task: serde_json::from_value(row.get(5)).context(error::SerdeJson)?,
+        enabled: row.get(6),
+        next_run: row.get(7),
+    })
+}
+
+fn next_run(cron: &CronExpression, enabled: bool) -> Option<DateTime> {
+    if enabled {
+        cron.next_after(DateTime::now())
+    } else {
+        None
+    }
+}
+
+/// Raster tasks require a raster workflow and vector tasks a vector workflow
+fn check_task_fits_workflow(task: &ScheduledTask, workflow: &Workflow) -> Result<()> {
+    match task {
+        ScheduledTask::AppendRasterDataset(_) | ScheduledTask::RasterDataset(_) => {
+            workflow.operator.clone().get_raster()?;
+        }
+        ScheduledTask::VectorDataset(_) => {
+            workflow.operator.clone().get_vector()?;
+        }
+    }
+
+    Ok(())
+}
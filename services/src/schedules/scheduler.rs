@@ -0,0 +1,155 @@
+use super::{DueSchedule, ScheduleDb, ScheduledTask};
+use crate::api::model::datatypes::{self, RasterQueryRectangle, VectorQueryRectangle};
+use crate::config::get_config_element;
+use crate::contexts::{ApplicationContext, SessionContext};
+use crate::datasets::{
+    AppendRasterDatasetFromWorkflow, RasterDatasetFromWorkflow, VectorDatasetFromWorkflow,
+    schedule_append_raster_dataset_from_workflow_task, schedule_raster_dataset_from_workflow_task,
+    schedule_vector_dataset_from_workflow_task,
+};
+use crate::error::Result;
+use crate::tasks::TaskId;
+use crate::users::{UserAuth, UserSession};
+use crate::workflows::registry::WorkflowRegistry;
+use geoengine_datatypes::primitives::{DateTime, TimeInstance, TimeInterval, TimeStep};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+
+/// Periodically start the tasks of all schedules that are due.
+/// This function runs forever and should be spawned.
+pub async fn run_scheduler<C>(app_ctx: C, check_interval: Duration)
+where
+    C: ApplicationContext<Session = UserSession>,
+{
+    let mut interval = tokio::time::interval(check_interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(error) = run_due_schedules(&app_ctx, DateTime::now()).await {
+            tracing::error!("Failed to run due schedules: {error:?}");
+        }
+    }
+}
+
+/// Start the tasks of all schedules that are due at `now`, each on behalf of the schedule's owner.
+/// Schedules that were created with an API token keep its scope and fail to run once it expired.
+/// The outcome of starting a task is stored as a run of the schedule.
+///
+/// Returns the number of runs.
+pub async fn run_due_schedules<C>(app_ctx: &C, now: DateTime) -> Result<usize>
+where
+    C: ApplicationContext<Session = UserSession>,
+{
+    let admin_db = app_ctx.session_context(UserSession::admin_session()).db();
+
+    let due_schedules = admin_db.claim_due_schedules(now).await?;
+
+    for due in &due_schedules {
+        let recorded = match start_scheduled_task(app_ctx, due).await {
+            Ok(task_id) => admin_db.set_schedule_run_task(due.run_id, task_id).await,
+            Err(error) => {
+                tracing::debug!(
+                    "Could not start the task of schedule {}: {error:?}",
+                    due.schedule.id
+                );
+                admin_db
+                    .set_schedule_run_error(due.run_id, &error.to_string())
+                    .await
+            }
+        };
+
+        if let Err(error) = recorded {
+            tracing::error!(
+                "Failed to store the run of schedule {}: {error:?}",
+                due.schedule.id
+            );
+        }
+    }
+
+    Ok(due_schedules.len())
+}
+
+async fn start_scheduled_task<C>(app_ctx: &C, due: &DueSchedule) -> Result<TaskId>
+where
+    C: ApplicationContext<Session = UserSession>,
+{
+    let schedule = &due.schedule;
+
+    let session = match due.api_token {
+        Some(token) => app_ctx.internal_session_for_api_token(token).await?,
+        None => app_ctx.internal_session_for_user(due.owner).await?,
+    };
+    let ctx = Arc::new(app_ctx.session_context(session));
+
+    let workflow = ctx.db().load_workflow(&schedule.workflow_id).await?;
+
+    let end = TimeInstance::from(due.time_scheduled);
+    let start = (end - TimeStep::from(schedule.time_window))?;
+    let time_interval: datatypes::TimeInterval = TimeInterval::new(start, end)?.into();
+
+    let source_name = format!("schedule {}", schedule.name);
+    let compression_num_threads =
+        get_config_element::<crate::config::Gdal>()?.compression_num_threads;
+
+    match schedule.task.clone() {
+        ScheduledTask::AppendRasterDataset(task) => {
+            schedule_append_raster_dataset_from_workflow_task(
+                source_name,
+                schedule.workflow_id,
+                workflow,
+                ctx,
+                AppendRasterDatasetFromWorkflow {
+                    dataset: task.dataset,
+                    time_interval,
+                    as_cog: task.as_cog,
+                },
+                compression_num_threads,
+            )
+            .await
+        }
+        ScheduledTask::RasterDataset(task) => {
+            schedule_raster_dataset_from_workflow_task(
+                source_name,
+                schedule.workflow_id,
+                workflow,
+                ctx,
+                RasterDatasetFromWorkflow {
+                    name: None,
+                    display_name: task.display_name,
+                    description: task.description,
+                    query: RasterQueryRectangle {
+                        spatial_bounds: task.spatial_bounds,
+                        time_interval,
+                        spatial_resolution: task.spatial_resolution,
+                    },
+                    as_cog: task.as_cog,
+                },
+                compression_num_threads,
+            )
+            .await
+        }
+        ScheduledTask::VectorDataset(task) => {
+            schedule_vector_dataset_from_workflow_task(
+                source_name,
+                schedule.workflow_id,
+                workflow,
+                ctx,
+                VectorDatasetFromWorkflow {
+                    name: None,
+                    display_name: task.display_name,
+                    description: task.description,
+                    query: VectorQueryRectangle {
+                        spatial_bounds: task.spatial_bounds,
+                        time_interval,
+                        spatial_resolution: task.spatial_resolution,
+                    },
+                    format: task.format,
+                },
+            )
+            .await
+        }
+    }
+}
@@ -19,6 +19,7 @@ use geoengine_operators::engine::ChunkByteSize;
 use geoengine_operators::util::gdal::register_gdal_drivers_from_list;
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::time::Duration;
 use tracing::info;
 use tracing_actix_web::TracingLogger;
 use utoipa::OpenApi;
@@ -47,6 +48,7 @@ where
             .configure(handlers::permissions::init_permissions_routes::<C>)
             .configure(handlers::plots::init_plot_routes::<C>)
             .configure(handlers::projects::init_project_routes::<C>)
+            .configure(handlers::schedules::init_schedule_routes::<C>)
             .configure(handlers::users::init_user_routes::<C>)
             .configure(handlers::spatial_references::init_spatial_reference_routes::<C>)
             .configure(handlers::upload::init_upload_routes::<C>)
@@ -214,6 +216,14 @@ async fn start_postgres(
         )
         .await?;
 
+        let schedules_config: config::Schedules = get_config_element()?;
+        if schedules_config.enabled {
+            crate::util::spawn(crate::schedules::run_scheduler(
+                ctx.clone(),
+                Duration::from_secs(schedules_config.check_interval_seconds),
+            ));
+        }
+
         start(
             static_files_dir,
             web_config.bind_address,
@@ -24,6 +24,13 @@ pub enum TaskError {
         task_unique_id: String,
     },
 
+    #[snafu(display("Task was interrupted by a restart of the server: {task_id}"))]
+    TaskInterrupted { task_id: TaskId },
+
+    /// An error of a task that was restored from the task storage.
+    #[snafu(display("{message}"))]
+    RestoredTaskFailure { message: String },
+
     TaskManagerOperationFailed {
         source: Box<dyn std::error::Error + Send + Sync>,
     },
@@ -1,8 +1,14 @@
 use super::{
-    RunningTaskStatusInfo, Task, TaskCleanUpStatus, TaskContext, TaskError, TaskFilter, TaskId,
-    TaskListOptions, TaskManager, TaskStatus, TaskStatusInfo, TaskStatusWithId,
+    RunningTaskStatusInfo, Task, TaskCleanUpStatus, TaskContext, TaskDb, TaskError, TaskFilter,
+    TaskId, TaskListOptions, TaskManager, TaskManagerId, TaskStatus, TaskStatusInfo,
+    TaskStatusWithId,
+};
+use crate::{
+    contexts::Db,
+    error::Result,
+    permissions::{Permission, RoleId},
+    users::UserId,
 };
-use crate::{contexts::Db, error::Result};
 use futures::StreamExt;
 use futures::channel::oneshot;
 use geoengine_datatypes::{
@@ -12,23 +18,164 @@ use geoengine_datatypes::{
 use std::{
     collections::{HashMap, HashSet, VecDeque},
     sync::Arc,
+    time::Duration,
 };
 use tokio::{
-    sync::{RwLock, RwLockWriteGuard},
+    sync::{RwLock, RwLockWriteGuard, mpsc},
     task::JoinHandle,
+    time::MissedTickBehavior,
 };
 use tracing::warn;
 
 type SharedTask = Arc<Box<dyn Task<SimpleTaskManagerContext>>>;
 
 /// An in-memory implementation of the [`TaskManager`] trait.
+///
+/// Tasks that exceed the [`TaskLimits`] are queued as pending.
+/// If a [`TaskDb`] is given, all status transitions are persisted, s.t. finished tasks survive restarts.
+/// They are written in the background, s.t. no lock is held while waiting for the database.
 #[derive(Default, Clone)]
 pub struct SimpleTaskManagerBackend {
     tasks_by_id: Db<HashMap<TaskId, TaskHandle>>,
     unique_tasks: Db<HashSet<(&'static str, String)>>,
     // these two lists won't be cleaned-up
-    status_by_id: Db<HashMap<TaskId, Db<TaskStatus>>>,
+    status_by_id: Db<HashMap<TaskId, TaskUpdateStatusWithTaskId>>,
     status_list: Db<VecDeque<TaskUpdateStatusWithTaskId>>,
+    queue: Db<TaskQueue>,
+    limits: TaskLimits,
+    store: Option<TaskStore>,
+}
+
+/// The [`TaskDb`] of a task manager and the queue of changes that are written to it in order.
+#[derive(Clone)]
+struct TaskStore {
+    db: Arc<dyn TaskDb>,
+    task_manager: TaskManagerId,
+    writes: mpsc::UnboundedSender<TaskStoreWrite>,
+}
+
+enum TaskStoreWrite {
+    Insert {
+        task_id: TaskId,
+        owner: Option<UserId>,
+        task_type: &'static str,
+        description: Option<String>,
+        status: TaskStatus,
+    },
+    UpdateStatus {
+        task_id: TaskId,
+        status: TaskStatus,
+    },
+    Flush(oneshot::Sender<()>),
+}
+
+impl TaskStore {
+    fn new(db: Arc<dyn TaskDb>) -> Self {
+        let task_manager = TaskManagerId::new();
+        let (writes, receiver) = mpsc::unbounded_channel();
+
+        crate::util::spawn(write_to_task_db(db.clone(), task_manager, receiver));
+
+        Self {
+            db,
+            task_manager,
+            writes,
+        }
+    }
+
+    fn write(&self, write: TaskStoreWrite) {
+        if self.writes.send(write).is_err() {
+            tracing::error!("Failed to persist a task, because the task store stopped");
+        }
+    }
+}
+
+/// Write the changes of tasks to the [`TaskDb`] in the order they were queued.
+async fn write_to_task_db(
+    db: Arc<dyn TaskDb>,
+    task_manager: TaskManagerId,
+    mut receiver: mpsc::UnboundedReceiver<TaskStoreWrite>,
+) {
+    while let Some(write) = receiver.recv().await {
+        match write {
+            TaskStoreWrite::Insert {
+                task_id,
+                owner,
+                task_type,
+                description,
+                status,
+            } => {
+                if let Err(error) = db
+                    .insert_task(
+                        task_id,
+                        task_manager,
+                        owner,
+                        task_type,
+                        description.as_deref(),
+                        &status,
+                    )
+                    .await
+                {
+                    tracing::error!("Failed to persist task {task_id}: {}", ge_report(error));
+                }
+            }
+            TaskStoreWrite::UpdateStatus { task_id, status } => {
+                if let Err(error) = db.update_task_status(task_id, &status).await {
+                    tracing::error!(
+                        "Failed to persist status of task {task_id}: {}",
+                        ge_report(error)
+                    );
+                }
+            }
+            TaskStoreWrite::Flush(done) => {
+                // the receiver may have been dropped, which is fine
+                done.send(()).unwrap_or_default();
+            }
+        }
+    }
+}
+
+/// Limits for the number of tasks that run concurrently.
+#[derive(Debug, Clone, Copy)]
+pub struct TaskLimits {
+    /// Subtasks are not limited, since their parent task occupies a slot while waiting for them.
+    pub max_running_tasks: usize,
+    pub max_running_tasks_per_user: usize,
+}
+
+impl Default for TaskLimits {
+    fn default() -> Self {
+        Self {
+            max_running_tasks: usize::MAX,
+            max_running_tasks_per_user: usize::MAX,
+        }
+    }
+}
+
+#[derive(Default)]
+struct TaskQueue {
+    pending: VecDeque<TaskId>,
+    running: HashMap<TaskId, Option<UserId>>,
+}
+
+impl TaskQueue {
+    fn has_capacity(&self, owner: Option<UserId>, limits: &TaskLimits) -> bool {
+        if self.running.len() >= limits.max_running_tasks {
+            return false;
+        }
+
+        let Some(owner) = owner else {
+            return true;
+        };
+
+        let running_tasks_of_owner = self
+            .running
+            .values()
+            .filter(|running_owner| **running_owner == Some(owner))
+            .count();
+
+        running_tasks_of_owner < limits.max_running_tasks_per_user
+    }
 }
 
 struct TaskHandle {
@@ -36,58 +183,102 @@ struct TaskHandle {
     handle: Option<JoinHandle<()>>,
     status: Db<TaskStatus>,
     unique_key: Option<(&'static str, String)>,
+    owner: Option<UserId>,
+    // is handed over to the running task once a pending task starts
+    notify: Option<oneshot::Sender<TaskStatus>>,
 }
 
 impl SimpleTaskManagerBackend {
-    async fn write_lock_all(&self) -> WriteLockAll<'_> {
-        let (tasks_by_id, status_by_id, task_list, unique_tasks) = tokio::join!(
-            self.tasks_by_id.write(),
-            self.status_by_id.write(),
-            self.status_list.write(),
-            self.unique_tasks.write(),
-        );
-        WriteLockAll {
-            tasks_by_id,
-            status_by_id,
-            status_list: task_list,
-            unique_tasks,
+    pub fn new(limits: TaskLimits, store: Option<Arc<dyn TaskDb>>) -> Self {
+        Self {
+            limits: TaskLimits {
+                max_running_tasks: limits.max_running_tasks.max(1),
+                max_running_tasks_per_user: limits.max_running_tasks_per_user.max(1),
+            },
+            store: store.map(TaskStore::new),
+            ..Default::default()
         }
     }
 
-    async fn write_lock_for_update(&self) -> WriteLockForUpdate<'_> {
-        let (tasks_by_id, unique_tasks) =
-            tokio::join!(self.tasks_by_id.write(), self.unique_tasks.write());
-        WriteLockForUpdate {
-            tasks_by_id,
-            unique_tasks,
+    /// Renew the lease of this task manager in its [`TaskDb`] and mark the unfinished tasks
+    /// of task managers whose lease expired as failed.
+    /// Returns the number of failed tasks.
+    pub async fn renew_lease(&self, lease: Duration) -> Result<u64> {
+        let Some(store) = &self.store else {
+            return Ok(0);
+        };
+
+        store
+            .db
+            .renew_task_manager_lease(store.task_manager)
+            .await?;
+
+        store.db.fail_orphaned_tasks(lease).await
+    }
+
+    /// Periodically renew the lease of this task manager, s.t. its tasks are not considered as orphaned.
+    /// This function runs forever and should be spawned.
+    pub async fn run_lease_renewal(self, lease: Duration) {
+        let mut interval = tokio::time::interval((lease / 3).max(Duration::from_secs(1)));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+
+            match self.renew_lease(lease).await {
+                Ok(0) => {}
+                Ok(failed_tasks) => {
+                    tracing::info!("Marked {failed_tasks} orphaned tasks as failed.");
+                }
+                Err(error) => {
+                    tracing::error!(
+                        "Failed to renew the lease of the task manager: {}",
+                        ge_report(error)
+                    );
+                }
+            }
         }
     }
-}
 
-#[derive(Debug)]
-struct TaskUpdateStatusWithTaskId {
-    pub task_id: TaskId,
-    pub status: Db<TaskStatus>,
-}
+    /// Wait until all changes of tasks are written to the [`TaskDb`].
+    pub async fn flush_store(&self) {
+        let Some(store) = &self.store else {
+            return;
+        };
 
-struct WriteLockAll<'a> {
-    pub tasks_by_id: RwLockWriteGuard<'a, HashMap<TaskId, TaskHandle>>,
-    pub status_by_id: RwLockWriteGuard<'a, HashMap<TaskId, Db<TaskStatus>>>,
-    pub status_list: RwLockWriteGuard<'a, VecDeque<TaskUpdateStatusWithTaskId>>,
-    pub unique_tasks: RwLockWriteGuard<'a, HashSet<(&'static str, String)>>,
-}
+        let (done_tx, done_rx) = oneshot::channel();
+        store.write(TaskStoreWrite::Flush(done_tx));
 
-struct WriteLockForUpdate<'a> {
-    pub tasks_by_id: RwLockWriteGuard<'a, HashMap<TaskId, TaskHandle>>,
-    pub unique_tasks: RwLockWriteGuard<'a, HashSet<(&'static str, String)>>,
-}
+        // the writer only stops if the runtime shuts down
+        done_rx.await.unwrap_or_default();
+    }
 
-#[async_trait::async_trait]
-impl TaskManager<SimpleTaskManagerContext> for SimpleTaskManagerBackend {
-    async fn schedule_task(
+    /// Schedule a task on behalf of the `owner`, whose concurrently running tasks are limited.
+    pub async fn schedule_task_for_owner(
         &self,
         task: Box<dyn Task<SimpleTaskManagerContext>>,
         notify: Option<oneshot::Sender<TaskStatus>>,
+        owner: Option<UserId>,
+    ) -> Result<TaskId, TaskError> {
+        self.schedule(task, notify, owner, false).await
+    }
+
+    /// Schedule a subtask on behalf of the `owner`. It starts immediately regardless of the limits.
+    pub async fn schedule_subtask_for_owner(
+        &self,
+        task: Box<dyn Task<SimpleTaskManagerContext>>,
+        notify: oneshot::Sender<TaskStatus>,
+        owner: Option<UserId>,
+    ) -> Result<TaskId, TaskError> {
+        self.schedule(task, Some(notify), owner, true).await
+    }
+
+    async fn schedule(
+        &self,
+        task: Box<dyn Task<SimpleTaskManagerContext>>,
+        notify: Option<oneshot::Sender<TaskStatus>>,
+        owner: Option<UserId>,
+        subtask: bool,
     ) -> Result<TaskId, TaskError> {
         let task_id = TaskId::new();
 
@@ -100,7 +291,7 @@ impl TaskManager<SimpleTaskManagerContext> for SimpleTaskManagerBackend {
             .map(|task_unique_id| (task.task_type(), task_unique_id));
 
         if let Some(task_unique_id) = &task_unique_key
-            && !lock.unique_tasks.insert(task_unique_id.clone())
+            && lock.unique_tasks.contains(task_unique_id)
         {
             return Err(TaskError::DuplicateTask {
                 task_type: task_unique_id.0,
@@ -111,16 +302,92 @@ impl TaskManager<SimpleTaskManagerContext> for SimpleTaskManagerBackend {
         let task_type = task.task_type();
         let description = Some(task.task_description());
 
+        let mut queue = self.queue.write().await;
+
+        let start_immediately = subtask || queue.has_capacity(owner, &self.limits);
+
+        let status = if start_immediately {
+            TaskStatus::Running(RunningTaskStatusInfo::new(
+                task_type,
+                description.clone(),
+                0.,
+                ().boxed(),
+            ))
+        } else {
+            TaskStatus::pending(task_type, description.clone())
+        };
+
+        if let Some(store) = &self.store {
+            store.write(TaskStoreWrite::Insert {
+                task_id,
+                owner,
+                task_type,
+                description,
+                status: status.clone(),
+            });
+        }
+
         let mut task_handle = TaskHandle {
             task: Arc::new(task),
             handle: None,
-            status: Arc::new(RwLock::new(TaskStatus::Running(
-                RunningTaskStatusInfo::new(task_type, description, 0., ().boxed()),
-            ))),
+            status: Arc::new(RwLock::new(status)),
             unique_key: task_unique_key,
+            owner,
+            notify,
         };
 
-        let task = task_handle.task.clone();
+        if start_immediately {
+            queue.running.insert(task_id, owner);
+            self.start_task(task_id, &mut task_handle).await;
+        } else {
+            queue.pending.push_back(task_id);
+        }
+
+        drop(queue);
+
+        let task_status_with_id = TaskUpdateStatusWithTaskId {
+            task_id,
+            task_type,
+            owner,
+            status: task_handle.status.clone(),
+        };
+        lock.status_by_id
+            .insert(task_id, task_status_with_id.clone());
+        lock.status_list.push_front(task_status_with_id);
+
+        if let Some(task_unique_id) = &task_handle.unique_key {
+            lock.unique_tasks.insert(task_unique_id.clone());
+        }
+
+        lock.tasks_by_id.insert(task_id, task_handle);
+
+        drop(lock);
+
+        // the task is found in the store once it is scheduled
+        self.flush_store().await;
+
+        Ok(task_id)
+    }
+
+    /// Start the task, i.e., set it to running if it is pending and spawn it.
+    async fn start_task(&self, task_id: TaskId, task_handle: &mut TaskHandle) {
+        {
+            let mut task_status_lock = task_handle.status.write().await;
+            if let TaskStatus::Pending {
+                task_type,
+                description,
+                ..
+            } = &*task_status_lock
+            {
+                *task_status_lock = TaskStatus::Running(RunningTaskStatusInfo::new(
+                    task_type,
+                    description.clone(),
+                    0.,
+                    ().boxed(),
+                ));
+            }
+        }
+
         let task_ctx = SimpleTaskManagerContext {
             status: task_handle.status.clone(),
         };
@@ -128,48 +395,152 @@ impl TaskManager<SimpleTaskManagerContext> for SimpleTaskManagerBackend {
         let handle = run_task(
             self.clone(), // we can clone here, since all interior stuff is wrapped into `Arc`s
             task_id,
-            task,
+            task_handle.task.clone(),
             task_ctx,
-            notify,
+            task_handle.notify.take(),
         );
 
         task_handle.handle = Some(handle);
+    }
 
-        lock.status_by_id
-            .insert(task_id, task_handle.status.clone());
-        lock.status_list.push_front(TaskUpdateStatusWithTaskId {
-            task_id,
-            status: task_handle.status.clone(),
-        });
+    /// Free the slot of a task that stopped running and start pending tasks as long as the limits allow.
+    async fn start_pending_tasks(
+        &self,
+        stopped_task_id: Option<TaskId>,
+        tasks_by_id: &mut HashMap<TaskId, TaskHandle>,
+    ) {
+        let mut queue = self.queue.write().await;
 
-        if let Some(task_unique_id) = &task_handle.unique_key {
-            lock.unique_tasks.insert(task_unique_id.clone());
+        if let Some(stopped_task_id) = stopped_task_id {
+            queue.running.remove(&stopped_task_id);
         }
 
-        lock.tasks_by_id.insert(task_id, task_handle);
+        let mut index = 0;
+        while index < queue.pending.len() && queue.running.len() < self.limits.max_running_tasks {
+            let task_id = queue.pending[index];
 
-        Ok(task_id)
+            let Some(task_handle) = tasks_by_id.get_mut(&task_id) else {
+                // the task was aborted while pending
+                queue.pending.remove(index);
+                continue;
+            };
+
+            if !queue.has_capacity(task_handle.owner, &self.limits) {
+                index += 1;
+                continue;
+            }
+
+            queue.pending.remove(index);
+            queue.running.insert(task_id, task_handle.owner);
+
+            self.start_task(task_id, task_handle).await;
+            self.persist_status(task_id, &task_handle.status).await;
+        }
     }
 
-    async fn get_task_status(&self, task_id: TaskId) -> Result<TaskStatus, TaskError> {
-        let task_status_map = self.status_by_id.read().await;
-        let task_status = task_status_map
-            .get(&task_id)
-            .ok_or(TaskError::TaskNotFound { task_id })?
-            .read()
+    /// Queue the current status of the task for storing, if there is a [`TaskDb`].
+    async fn persist_status(&self, task_id: TaskId, task_status: &Db<TaskStatus>) {
+        let Some(store) = &self.store else {
+            return;
+        };
+
+        let status = task_status.read().await.clone();
+
+        store.write(TaskStoreWrite::UpdateStatus { task_id, status });
+    }
+
+    /// Get the type of a task, either from memory or from the [`TaskDb`].
+    pub async fn task_type(&self, task_id: TaskId) -> Result<String, TaskError> {
+        if let Some(task_status) = self.status_by_id.read().await.get(&task_id) {
+            return Ok(task_status.task_type.to_string());
+        }
+
+        let Some(store) = &self.store else {
+            return Err(TaskError::TaskNotFound { task_id });
+        };
+
+        store
+            .db
+            .load_task(task_id)
             .await
-            .clone();
+            .map_err(|source| TaskError::TaskManagerOperationFailed {
+                source: Box::new(source),
+            })?
+            .map(|task| task.task_type)
+            .ok_or(TaskError::TaskNotFound { task_id })
+    }
+
+    /// Check whether one of the `roles` has the `permission` for a task.
+    /// Without a [`TaskDb`], only the owner of a task has permissions for it.
+    pub async fn has_permission(
+        &self,
+        task_id: TaskId,
+        roles: &[RoleId],
+        permission: Permission,
+    ) -> Result<bool, TaskError> {
+        if let Some(store) = &self.store {
+            return store
+                .db
+                .has_task_permission(task_id, roles, permission)
+                .await
+                .map_err(|source| TaskError::TaskManagerOperationFailed {
+                    source: Box::new(source),
+                });
+        }
 
-        Ok(task_status)
+        self.status_by_id
+            .read()
+            .await
+            .get(&task_id)
+            .map(|task_status| task_status.is_owned_by_one_of(roles))
+            .ok_or(TaskError::TaskNotFound { task_id })
     }
 
-    async fn list_tasks(
+    /// List tasks, the most recent first, omitting tasks of the `hidden_task_types`.
+    /// If `roles` are given, only tasks that one of them may read are listed.
+    pub async fn list_tasks_without_types(
         &self,
-        options: TaskListOptions,
+        options: &TaskListOptions,
+        hidden_task_types: &[&str],
+        roles: Option<&[RoleId]>,
     ) -> Result<Vec<TaskStatusWithId>, TaskError> {
+        if let Some(store) = &self.store {
+            // list the latest status of the tasks of this task manager
+            self.flush_store().await;
+
+            let stored_tasks = store
+                .db
+                .list_tasks(options, hidden_task_types, roles)
+                .await
+                .map_err(|source| TaskError::TaskManagerOperationFailed {
+                    source: Box::new(source),
+                })?;
+
+            // prefer the in-memory status, since it contains the progress of running tasks
+            let status_by_id = self.status_by_id.read().await;
+
+            let mut result = Vec::with_capacity(stored_tasks.len());
+            for stored_task in stored_tasks {
+                let status = match status_by_id.get(&stored_task.task_id) {
+                    Some(task_status) => task_status.status.read().await.clone(),
+                    None => stored_task.status,
+                };
+
+                result.push(TaskStatusWithId {
+                    task_id: stored_task.task_id,
+                    status,
+                });
+            }
+
+            return Ok(result);
+        }
+
         let lock = self.status_list.read().await;
 
-        let stream = futures::stream::iter(lock.iter());
+        let stream = futures::stream::iter(lock.iter().filter(|task_status_with_id| {
+            !hidden_task_types.contains(&task_status_with_id.task_type)
+                && roles.is_none_or(|roles| task_status_with_id.is_owned_by_one_of(roles))
+        }));
 
         let result: Vec<TaskStatusWithId> = stream
             .filter_map(|task_status_with_id| async {
@@ -177,6 +548,7 @@ impl TaskManager<SimpleTaskManagerContext> for SimpleTaskManagerBackend {
 
                 match (options.filter, &*task_status) {
                     (None, _)
+                    | (Some(TaskFilter::Pending), &TaskStatus::Pending { .. })
                     | (Some(TaskFilter::Running), &TaskStatus::Running(_))
                     | (Some(TaskFilter::Completed), &TaskStatus::Completed { .. })
                     | (Some(TaskFilter::Aborted), &TaskStatus::Aborted { .. })
@@ -197,6 +569,111 @@ impl TaskManager<SimpleTaskManagerContext> for SimpleTaskManagerBackend {
         Ok(result)
     }
 
+    async fn write_lock_all(&self) -> WriteLockAll<'_> {
+        let (tasks_by_id, status_by_id, task_list, unique_tasks) = tokio::join!(
+            self.tasks_by_id.write(),
+            self.status_by_id.write(),
+            self.status_list.write(),
+            self.unique_tasks.write(),
+        );
+        WriteLockAll {
+            tasks_by_id,
+            status_by_id,
+            status_list: task_list,
+            unique_tasks,
+        }
+    }
+
+    async fn write_lock_for_update(&self) -> WriteLockForUpdate<'_> {
+        let (tasks_by_id, unique_tasks) =
+            tokio::join!(self.tasks_by_id.write(), self.unique_tasks.write());
+        WriteLockForUpdate {
+            tasks_by_id,
+            unique_tasks,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct TaskUpdateStatusWithTaskId {
+    pub task_id: TaskId,
+    pub task_type: &'static str,
+    pub owner: Option<UserId>,
+    pub status: Db<TaskStatus>,
+}
+
+impl TaskUpdateStatusWithTaskId {
+    /// Without a [`TaskDb`], only the owner of a task has permissions for it
+    fn is_owned_by_one_of(&self, roles: &[RoleId]) -> bool {
+        self.owner
+            .is_some_and(|owner| roles.contains(&RoleId::from(owner)))
+    }
+}
+
+struct WriteLockAll<'a> {
+    pub tasks_by_id: RwLockWriteGuard<'a, HashMap<TaskId, TaskHandle>>,
+    pub status_by_id: RwLockWriteGuard<'a, HashMap<TaskId, TaskUpdateStatusWithTaskId>>,
+    pub status_list: RwLockWriteGuard<'a, VecDeque<TaskUpdateStatusWithTaskId>>,
+    pub unique_tasks: RwLockWriteGuard<'a, HashSet<(&'static str, String)>>,
+}
+
+struct WriteLockForUpdate<'a> {
+    pub tasks_by_id: RwLockWriteGuard<'a, HashMap<TaskId, TaskHandle>>,
+    pub unique_tasks: RwLockWriteGuard<'a, HashSet<(&'static str, String)>>,
+}
+
+#[async_trait::async_trait]
+impl TaskManager<SimpleTaskManagerContext> for SimpleTaskManagerBackend {
+    async fn schedule_task(
+        &self,
+        task: Box<dyn Task<SimpleTaskManagerContext>>,
+        notify: Option<oneshot::Sender<TaskStatus>>,
+    ) -> Result<TaskId, TaskError> {
+        self.schedule_task_for_owner(task, notify, None).await
+    }
+
+    async fn schedule_subtask(
+        &self,
+        task: Box<dyn Task<SimpleTaskManagerContext>>,
+        notify: oneshot::Sender<TaskStatus>,
+    ) -> Result<TaskId, TaskError> {
+        self.schedule_subtask_for_owner(task, notify, None).await
+    }
+
+    async fn get_task_status(&self, task_id: TaskId) -> Result<TaskStatus, TaskError> {
+        let task_status = self
+            .status_by_id
+            .read()
+            .await
+            .get(&task_id)
+            .map(|task_status| task_status.status.clone());
+
+        if let Some(task_status) = task_status {
+            return Ok(task_status.read().await.clone());
+        }
+
+        let Some(store) = &self.store else {
+            return Err(TaskError::TaskNotFound { task_id });
+        };
+
+        store
+            .db
+            .load_task(task_id)
+            .await
+            .map_err(|source| TaskError::TaskManagerOperationFailed {
+                source: Box::new(source),
+            })?
+            .map(|task| task.status)
+            .ok_or(TaskError::TaskNotFound { task_id })
+    }
+
+    async fn list_tasks(
+        &self,
+        options: TaskListOptions,
+    ) -> Result<Vec<TaskStatusWithId>, TaskError> {
+        self.list_tasks_without_types(&options, &[], None).await
+    }
+
     async fn abort_tasks(&self, task_id: TaskId, force: bool) -> Result<(), TaskError> {
         let mut write_lock = self.write_lock_for_update().await;
 
@@ -218,8 +695,26 @@ impl TaskManager<SimpleTaskManagerContext> for SimpleTaskManagerBackend {
             return Err(TaskError::TaskAlreadyAborted { task_id });
         }
 
+        let was_pending = task_status_lock.is_pending();
+
         drop(task_status_lock); // prevent deadlocks on the status lock
 
+        if was_pending {
+            // the task never ran, so there is nothing to stop or clean up
+            self.queue
+                .write()
+                .await
+                .pending
+                .retain(|pending_task_id| *pending_task_id != task_id);
+
+            set_status_to_no_clean_up(&task_handle.status).await;
+            self.persist_status(task_id, &task_handle.status).await;
+
+            remove_unique_key(&task_handle, &mut write_lock.unique_tasks);
+
+            return Ok(());
+        }
+
         let task_finished_before_being_aborted = if let Some(handle) = task_handle.handle.take() {
             handle.abort();
             handle.await.is_ok()
@@ -232,9 +727,13 @@ impl TaskManager<SimpleTaskManagerContext> for SimpleTaskManagerBackend {
 
         if force || task_finished_before_being_aborted {
             set_status_to_no_clean_up(&task_handle.status).await;
+            self.persist_status(task_id, &task_handle.status).await;
 
             remove_unique_key(&task_handle, &mut write_lock.unique_tasks);
 
+            self.start_pending_tasks(Some(task_id), &mut write_lock.tasks_by_id)
+                .await;
+
             // propagate abort to subtasks
             drop(write_lock); // prevent deadlocks because the subtask abort tries to fetch the lock
             abort_subtasks(self.clone(), subtask_ids, force, task_id).await;
@@ -244,8 +743,12 @@ impl TaskManager<SimpleTaskManagerContext> for SimpleTaskManagerBackend {
         }
 
         set_status_to_aborting(&task_handle.status).await;
+        self.persist_status(task_id, &task_handle.status).await;
         clean_up_phase(self.clone(), task_handle, &mut write_lock, task_id);
 
+        self.start_pending_tasks(Some(task_id), &mut write_lock.tasks_by_id)
+            .await;
+
         // propagate abort to subtasks
         drop(write_lock); // prevent deadlocks because the subtask abort tries to fetch the lock
         abort_subtasks(self.clone(), subtask_ids, force, task_id).await;
@@ -320,6 +823,14 @@ fn run_task(
             }
         }
 
+        task_manager.persist_status(task_id, &task_status).await;
+
+        task_manager
+            .start_pending_tasks(Some(task_id), &mut update_lock.tasks_by_id)
+            .await;
+
+        drop(update_lock);
+
         // TODO: move this into clean-up?
         if let Some(notify) = notify {
             // we can ignore the returned error because this means
@@ -355,7 +866,9 @@ async fn set_status_to_clean_up_completed(task_status: &Db<TaskStatus>) {
     };
 
     *task_status_lock = match &*task_status_lock {
-        TaskStatus::Running(_) | TaskStatus::Completed { .. } => return, // must not happen, ignore
+        TaskStatus::Pending { .. } | TaskStatus::Running(_) | TaskStatus::Completed { .. } => {
+            return; // must not happen, ignore
+        }
         TaskStatus::Aborted { .. } => TaskStatus::aborted(task_clean_up_status),
         TaskStatus::Failed { error, .. } => TaskStatus::failed(error.clone(), task_clean_up_status),
     };
@@ -368,7 +881,7 @@ async fn set_status_to_no_clean_up(task_status: &Db<TaskStatus>) {
 
     *task_status_lock = match &*task_status_lock {
         TaskStatus::Completed { .. } => return, // must not happen, ignore
-        TaskStatus::Running(_) | TaskStatus::Aborted { .. } => {
+        TaskStatus::Pending { .. } | TaskStatus::Running(_) | TaskStatus::Aborted { .. } => {
             TaskStatus::aborted(task_clean_up_status)
         }
         TaskStatus::Failed { error, .. } => TaskStatus::failed(error.clone(), task_clean_up_status),
@@ -383,7 +896,9 @@ async fn set_status_to_clean_up_failed(task_status: &Db<TaskStatus>, error: Box<
     };
 
     *task_status_lock = match &*task_status_lock {
-        TaskStatus::Running(_) | TaskStatus::Completed { .. } => return, // must not happen, ignore
+        TaskStatus::Pending { .. } | TaskStatus::Running(_) | TaskStatus::Completed { .. } => {
+            return; // must not happen, ignore
+        }
         TaskStatus::Aborted { .. } => TaskStatus::aborted(task_clean_up_status),
         TaskStatus::Failed { error, .. } => TaskStatus::failed(error.clone(), task_clean_up_status),
     }
@@ -414,6 +929,10 @@ fn clean_up_phase(
             Err(err) => set_status_to_clean_up_failed(&task_handle.status, err).await,
         }
 
+        task_manager
+            .persist_status(task_id, &task_handle.status)
+            .await;
+
         remove_unique_key(&task_handle, &mut update_lock.unique_tasks);
     });
 
@@ -473,6 +992,14 @@ impl TaskManager<SimpleTaskManagerContext> for SimpleTaskManager {
         self.backend.schedule_task(task, notify).await
     }
 
+    async fn schedule_subtask(
+        &self,
+        task: Box<dyn Task<SimpleTaskManagerContext>>,
+        notify: oneshot::Sender<TaskStatus>,
+    ) -> Result<TaskId, TaskError> {
+        self.backend.schedule_subtask(task, notify).await
+    }
+
     async fn get_task_status(&self, task_id: TaskId) -> Result<TaskStatus, TaskError> {
         self.backend.get_task_status(task_id).await
     }
@@ -1,21 +1,28 @@
 mod error;
 mod in_memory;
+mod postgres;
 mod time_estimation;
 mod users;
 pub mod util;
 
 pub use error::TaskError;
-pub use in_memory::{SimpleTaskManager, SimpleTaskManagerBackend, SimpleTaskManagerContext};
+pub use in_memory::{
+    SimpleTaskManager, SimpleTaskManagerBackend, SimpleTaskManagerContext, TaskLimits,
+};
 pub use users::{TypedTaskManagerBackend, UserTaskManager};
 
 use self::time_estimation::TimeEstimation;
 use crate::identifier;
+use crate::permissions::{Permission, RoleId};
+use crate::users::UserId;
 use crate::{config::get_config_element, error::Result};
 use futures::channel::oneshot;
 use geoengine_datatypes::primitives::DateTime;
 use geoengine_datatypes::{error::ErrorSource, util::AsAnyArc};
+use postgres_types::{FromSql, ToSql};
 use serde::{Deserialize, Serialize, Serializer};
 use std::borrow::Cow;
+use std::time::Duration;
 use std::{fmt, sync::Arc};
 use utoipa::{IntoParams, PartialSchema, ToSchema};
 use validator::{Validate, ValidationError};
@@ -30,6 +37,15 @@ pub trait TaskManager<C: TaskContext>: Send + Sync {
         notify: Option<oneshot::Sender<TaskStatus>>,
     ) -> Result<TaskId, TaskError>;
 
+    /// Schedule a subtask of a running task that waits for it via `notify`.
+    /// Subtasks start immediately, since their parent task occupies a slot of the limits while waiting.
+    #[must_use]
+    async fn schedule_subtask(
+        &self,
+        task: Box<dyn Task<C>>,
+        notify: oneshot::Sender<TaskStatus>,
+    ) -> Result<TaskId, TaskError>;
+
     #[must_use]
     async fn get_task_status(&self, task_id: TaskId) -> Result<TaskStatus, TaskError>;
 
@@ -49,6 +65,63 @@ pub trait TaskManager<C: TaskContext>: Send + Sync {
 
 identifier!(TaskId);
 
+identifier!(TaskManagerId);
+
+/// A storage that persists the status of tasks, s.t. it survives restarts.
+#[async_trait::async_trait]
+pub trait TaskDb: Send + Sync {
+    /// Store a newly scheduled task that runs on the `task_manager`.
+    async fn insert_task(
+        &self,
+        task_id: TaskId,
+        task_manager: TaskManagerId,
+        owner: Option<UserId>,
+        task_type: &str,
+        description: Option<&str>,
+        status: &TaskStatus,
+    ) -> Result<()>;
+
+    /// Update the status of a stored task.
+    async fn update_task_status(&self, task_id: TaskId, status: &TaskStatus) -> Result<()>;
+
+    /// Load a stored task.
+    /// Statuses of tasks that did not finish are restored as failed.
+    async fn load_task(&self, task_id: TaskId) -> Result<Option<StoredTask>>;
+
+    /// List stored tasks, the most recent first, omitting tasks of the `hidden_task_types`.
+    /// If `roles` are given, only tasks that one of them may read are listed.
+    async fn list_tasks(
+        &self,
+        options: &TaskListOptions,
+        hidden_task_types: &[&str],
+        roles: Option<&[RoleId]>,
+    ) -> Result<Vec<StoredTask>>;
+
+    /// Check whether one of the `roles` has the `permission` for a stored task.
+    async fn has_task_permission(
+        &self,
+        task_id: TaskId,
+        roles: &[RoleId],
+        permission: Permission,
+    ) -> Result<bool>;
+
+    /// Register the `task_manager` or renew its lease.
+    async fn renew_task_manager_lease(&self, task_manager: TaskManagerId) -> Result<()>;
+
+    /// Mark all tasks that did not finish as failed if the lease of their task manager expired,
+    /// e.g., because the server was stopped.
+    /// Returns the number of affected tasks.
+    async fn fail_orphaned_tasks(&self, lease: Duration) -> Result<u64>;
+}
+
+/// A task as it is stored in a [`TaskDb`].
+#[derive(Debug, Clone)]
+pub struct StoredTask {
+    pub task_id: TaskId,
+    pub task_type: String,
+    pub status: TaskStatus,
+}
+
 /// A task that can run asynchronously and reports its status.
 #[async_trait::async_trait]
 pub trait Task<C: TaskContext>: Send + Sync {
@@ -96,11 +169,16 @@ pub trait TaskContext: Send + Sync {
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase", tag = "status")]
 pub enum TaskStatus {
-    // Pending, // TODO: at some point, don't just run things
+    #[serde(rename_all = "camelCase")]
+    Pending {
+        task_type: &'static str,
+        description: Option<String>,
+        time_created: DateTime,
+    },
     Running(Arc<RunningTaskStatusInfo>),
     #[serde(rename_all = "camelCase")]
     Completed {
-        task_type: &'static str,
+        task_type: String,
         description: Option<String>,
         info: Arc<dyn TaskStatusInfo>,
         time_total: String,
@@ -132,6 +210,31 @@ impl PartialSchema for TaskStatus {
         use utoipa::openapi::schema::{SchemaType, Type};
         use utoipa::openapi::{Discriminator, Object, ObjectBuilder, OneOfBuilder};
         OneOfBuilder::new()
+            .item(
+                ObjectBuilder::new()
+                    .title(Some("TaskStatusPending"))
+                    .property(
+                        "status",
+                        ObjectBuilder::new()
+                            .schema_type(SchemaType::Type(Type::String))
+                            .enum_values::<[&str; 1], &str>(Some(["pending"])),
+                    )
+                    .required("status")
+                    .property(
+                        "taskType",
+                        Object::with_type(SchemaType::Type(Type::String)),
+                    )
+                    .required("taskType")
+                    .property(
+                        "description",
+                        Object::with_type(SchemaType::Type(Type::String)),
+                    )
+                    .property(
+                        "timeCreated",
+                        Object::with_type(SchemaType::Type(Type::String)),
+                    )
+                    .required("timeCreated"),
+            )
             .item(
                 ObjectBuilder::new()
                     .title(Some("TaskStatusRunning"))
@@ -266,7 +369,7 @@ impl TaskStatus {
     #[allow(clippy::missing_panics_doc)]
     pub fn completed(&self, info: Arc<dyn TaskStatusInfo>) -> Self {
         Self::Completed {
-            task_type: self.task_type(),
+            task_type: self.task_type().to_string(),
             description: self.description(),
             info,
             time_total: self.time_total(),
@@ -276,6 +379,14 @@ impl TaskStatus {
         }
     }
 
+    pub fn pending(task_type: &'static str, description: Option<String>) -> Self {
+        Self::Pending {
+            task_type,
+            description,
+            time_created: DateTime::now(),
+        }
+    }
+
     pub fn aborted(clean_up: TaskCleanUpStatus) -> Self {
         Self::Aborted { clean_up }
     }
@@ -284,6 +395,10 @@ impl TaskStatus {
         Self::Failed { error, clean_up }
     }
 
+    pub fn is_pending(&self) -> bool {
+        matches!(self, TaskStatus::Pending { .. })
+    }
+
     pub fn is_running(&self) -> bool {
         matches!(self, TaskStatus::Running(_))
     }
@@ -335,8 +450,9 @@ impl TaskStatus {
         }
     }
 
-    fn task_type(&self) -> &'static str {
+    fn task_type(&self) -> &str {
         match self {
+            TaskStatus::Pending { task_type, .. } => task_type,
             TaskStatus::Completed { task_type, .. } => task_type,
             TaskStatus::Running(info) => info.task_type,
             _ => "",
@@ -345,7 +461,9 @@ impl TaskStatus {
 
     fn description(&self) -> Option<String> {
         match self {
-            TaskStatus::Completed { description, .. } => description.clone(),
+            TaskStatus::Pending { description, .. } | TaskStatus::Completed { description, .. } => {
+                description.clone()
+            }
             TaskStatus::Running(info) => info.description.clone(),
             _ => None,
         }
@@ -433,6 +551,7 @@ erased_serde::serialize_trait_object!(TaskStatusInfo);
 
 impl TaskStatusInfo for () {}
 impl TaskStatusInfo for String {}
+impl TaskStatusInfo for serde_json::Value {}
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema, IntoParams, Validate)]
 pub struct TaskListOptions {
@@ -476,9 +595,11 @@ fn task_list_limit_default() -> u32 {
         .unwrap_or(1)
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema, ToSql, FromSql)]
 #[serde(rename_all = "camelCase")]
+#[postgres(name = "TaskStatusType")]
 pub enum TaskFilter {
+    Pending,
     Running,
     Aborted,
     Failed,
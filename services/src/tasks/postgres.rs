@@ -0,0 +1,405 @@
+use super::{
+    StoredTask, TaskCleanUpStatus, TaskDb, TaskError, TaskFilter, TaskId, TaskListOptions,
+    TaskManagerId, TaskStatus, TaskStatusInfo,
+};
+use crate::contexts::PostgresDb;
+use crate::error::{self, Result};
+use crate::permissions::{Permission, RoleId};
+use crate::users::UserId;
+use async_trait::async_trait;
+use geoengine_datatypes::primitives::DateTime;
+use serde::Deserialize;
+use snafu::ResultExt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_postgres::{
+    Socket,
+    tls::{MakeTlsConnect, TlsConnect},
+};
+
+#[async_trait]
+impl<Tls> TaskDb for PostgresDb<Tls>
+where
+    Tls: MakeTlsConnect<Socket> + Clone + Send + Sync + 'static + std::fmt::Debug,
+    <Tls as MakeTlsConnect<Socket>>::Stream: Send + Sync,
+    <Tls as MakeTlsConnect<Socket>>::TlsConnect: Send,
+    <<Tls as MakeTlsConnect<Socket>>::TlsConnect as TlsConnect<Socket>>::Future: Send,
+{
+    async fn insert_task(
+        &self,
+        task_id: TaskId,
+        task_manager: TaskManagerId,
+        owner: Option<UserId>,
+        task_type: &str,
+        description: Option<&str>,
+        status: &TaskStatus,
+    ) -> Result<()> {
+        let mut conn = self.conn_pool.get().await?;
+        let tx = conn.build_transaction().start().await?;
+
+        let stmt = tx
+            .prepare(
+                "
+            INSERT INTO tasks (
+                id, task_manager_id, user_id, task_type, description, status, status_info
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7);",
+            )
+            .await?;
+
+        tx.execute(
+            &stmt,
+            &[
+                &task_id,
+                &task_manager,
+                &owner,
+                &task_type,
+                &description,
+                &task_status_type(status),
+                &serde_json::to_value(status).context(error::SerdeJson)?,
+            ],
+        )
+        .await?;
+
+        // the task is stored by the task manager, so the owner is not the user of this db's session
+        if let Some(owner) = owner {
+            tx.execute(
+                "
+                INSERT INTO permissions (role_id, permission, task_id)
+                SELECT $1, $2, $3 WHERE EXISTS (SELECT 1 FROM roles WHERE id = $1);",
+                &[&RoleId::from(owner), &Permission::Owner, &task_id],
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn update_task_status(&self, task_id: TaskId, status: &TaskStatus) -> Result<()> {
+        let conn = self.conn_pool.get().await?;
+
+        let stmt = conn
+            .prepare(
+                "
+            UPDATE tasks
+            SET
+                status = $2,
+                status_info = $3,
+                time_updated = clock_timestamp()
+            WHERE id = $1;",
+            )
+            .await?;
+
+        conn.execute(
+            &stmt,
+            &[
+                &task_id,
+                &task_status_type(status),
+                &serde_json::to_value(status).context(error::SerdeJson)?,
+            ],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn load_task(&self, task_id: TaskId) -> Result<Option<StoredTask>> {
+        let conn = self.conn_pool.get().await?;
+
+        let stmt = conn
+            .prepare(
+                "
+            SELECT id, task_type, status_info
+            FROM tasks
+            WHERE id = $1;",
+            )
+            .await?;
+
+        let Some(row) = conn.query_opt(&stmt, &[&task_id]).await? else {
+            return Ok(None);
+        };
+
+        Ok(Some(StoredTask {
+            task_id: row.get(0),
+            task_type: row.get(1),
+            status: restore_task_status(task_id, row.get(2))?,
+        }))
+    }
+
+    async fn list_tasks(
+        &self,
+        options: &TaskListOptions,
+        hidden_task_types: &[&str],
+        roles: Option<&[RoleId]>,
+    ) -> Result<Vec<StoredTask>> {
+        let conn = self.conn_pool.get().await?;
+
+        // every permission includes reading the task
+        let stmt = conn
+            .prepare(
+                "
+            SELECT t.id, t.task_type, t.status_info
+            FROM tasks t
+            WHERE
+                ($1::\"TaskStatusType\" IS NULL OR t.status = $1) AND
+                NOT (t.task_type = ANY($2)) AND
+                (
+                    $5::uuid[] IS NULL OR
+                    EXISTS (
+                        SELECT 1 FROM permissions p
+                        WHERE
+                            p.task_id = t.id AND
+                            p.role_id = ANY($5) AND
+                            (p.expires IS NULL OR p.expires > CURRENT_TIMESTAMP)
+                    )
+                )
+            ORDER BY t.time_created DESC, t.id
+            OFFSET $3
+            LIMIT $4;",
+            )
+            .await?;
+
+        let rows = conn
+            .query(
+                &stmt,
+                &[
+                    &options.filter,
+                    &hidden_task_types,
+                    &i64::from(options.offset),
+                    &i64::from(options.limit),
+                    &roles,
+                ],
+            )
+            .await?;
+
+        rows.into_iter()
+            .map(|row| -> Result<StoredTask> {
+                let task_id = row.get(0);
+                Ok(StoredTask {
+                    task_id,
+                    task_type: row.get(1),
+                    status: restore_task_status(task_id, row.get(2))?,
+                })
+            })
+            .collect()
+    }
+
+    async fn has_task_permission(
+        &self,
+        task_id: TaskId,
+        roles: &[RoleId],
+        permission: Permission,
+    ) -> Result<bool> {
+        let conn = self.conn_pool.get().await?;
+
+        let row = conn
+            .query_one(
+                "
+            SELECT EXISTS (
+                SELECT 1 FROM permissions
+                WHERE
+                    task_id = $1 AND
+                    role_id = ANY($2) AND
+                    permission = ANY($3) AND
+                    (expires IS NULL OR expires > CURRENT_TIMESTAMP)
+            );",
+                &[&task_id, &roles, &permission.required_permissions()],
+            )
+            .await?;
+
+        Ok(row.get(0))
+    }
+
+    async fn renew_task_manager_lease(&self, task_manager: TaskManagerId) -> Result<()> {
+        let conn = self.conn_pool.get().await?;
+
+        conn.execute(
+            "
+            INSERT INTO task_manager_leases (id, heartbeat)
+            VALUES ($1, CURRENT_TIMESTAMP)
+            ON CONFLICT (id) DO UPDATE SET heartbeat = EXCLUDED.heartbeat;",
+            &[&task_manager],
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn fail_orphaned_tasks(&self, lease: Duration) -> Result<u64> {
+        let mut conn = self.conn_pool.get().await?;
+        let tx = conn.transaction().await?;
+
+        // tasks of earlier versions have no task manager and are orphaned as well
+        let rows = tx
+            .query(
+                "
+            SELECT t.id, t.status_info
+            FROM tasks t
+            WHERE
+                (
+                    t.status IN ('Pending', 'Running') OR
+                    t.status_info -> 'cleanUp' ->> 'status' = 'running'
+                ) AND
+                NOT EXISTS (
+                    SELECT 1 FROM task_manager_leases l
+                    WHERE
+                        l.id = t.task_manager_id AND
+                        l.heartbeat > CURRENT_TIMESTAMP - make_interval(secs => $1)
+                )
+            FOR UPDATE OF t;",
+                &[&lease.as_secs_f64()],
+            )
+            .await?;
+
+        let stmt = tx
+            .prepare(
+                "
+            UPDATE tasks
+            SET
+                status = $2,
+                status_info = $3,
+                time_updated = clock_timestamp()
+            WHERE id = $1;",
+            )
+            .await?;
+
+        for row in &rows {
+            let task_id: TaskId = row.get(0);
+            let status = restore_task_status(task_id, row.get(1))?;
+
+            tx.execute(
+                &stmt,
+                &[
+                    &task_id,
+                    &task_status_type(&status),
+                    &serde_json::to_value(&status).context(error::SerdeJson)?,
+                ],
+            )
+            .await?;
+        }
+
+        tx.execute(
+            "
+            DELETE FROM task_manager_leases
+            WHERE heartbeat <= CURRENT_TIMESTAMP - make_interval(secs => $1);",
+            &[&lease.as_secs_f64()],
+        )
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(rows.len() as u64)
+    }
+}
+
+fn task_status_type(status: &TaskStatus) -> TaskFilter {
+    match status {
+        TaskStatus::Pending { .. } => TaskFilter::Pending,
+        TaskStatus::Running(_) => TaskFilter::Running,
+        TaskStatus::Completed { .. } => TaskFilter::Completed,
+        TaskStatus::Aborted { .. } => TaskFilter::Aborted,
+        TaskStatus::Failed { .. } => TaskFilter::Failed,
+    }
+}
+
+/// The serialized form of a [`TaskStatus`].
+/// Task information and errors are only available in their serialized form.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "status")]
+enum StoredTaskStatus {
+    Pending {},
+    Running {},
+    #[serde(rename_all = "camelCase")]
+    Completed {
+        task_type: String,
+        description: Option<String>,
+        #[serde(default)]
+        info: serde_json::Value,
+        time_total: String,
+        time_started: DateTime,
+    },
+    #[serde(rename_all = "camelCase")]
+    Aborted {
+        clean_up: StoredTaskCleanUpStatus,
+    },
+    #[serde(rename_all = "camelCase")]
+    Failed {
+        error: String,
+        clean_up: StoredTaskCleanUpStatus,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "status")]
+enum StoredTaskCleanUpStatus {
+    NoCleanUp {},
+    Running {},
+    Completed {
+        #[serde(default)]
+        info: serde_json::Value,
+    },
+    Aborted {
+        #[serde(default)]
+        info: serde_json::Value,
+    },
+    Failed {
+        error: String,
+    },
+}
+
+/// Restore a [`TaskStatus`] from its serialized form.
+/// Tasks or clean-ups that did not finish cannot continue, so they are marked as failed.
+fn restore_task_status(task_id: TaskId, status_info: serde_json::Value) -> Result<TaskStatus> {
+    let stored_status: StoredTaskStatus =
+        serde_json::from_value(status_info).context(error::SerdeJson)?;
+
+    Ok(match stored_status {
+        StoredTaskStatus::Pending {} | StoredTaskStatus::Running {} => TaskStatus::failed(
+            Arc::new(TaskError::TaskInterrupted { task_id }),
+            TaskCleanUpStatus::NoCleanUp,
+        ),
+        StoredTaskStatus::Completed {
+            task_type,
+            description,
+            info,
+            time_total,
+            time_started,
+        } => TaskStatus::Completed {
+            task_type,
+            description,
+            info: Arc::new(info),
+            time_total,
+            time_started,
+        },
+        StoredTaskStatus::Aborted { clean_up } => {
+            TaskStatus::aborted(restore_clean_up_status(task_id, clean_up))
+        }
+        StoredTaskStatus::Failed { error, clean_up } => TaskStatus::failed(
+            Arc::new(TaskError::RestoredTaskFailure { message: error }),
+            restore_clean_up_status(task_id, clean_up),
+        ),
+    })
+}
+
+fn restore_clean_up_status(
+    task_id: TaskId,
+    clean_up: StoredTaskCleanUpStatus,
+) -> TaskCleanUpStatus {
+    match clean_up {
+        StoredTaskCleanUpStatus::NoCleanUp {} => TaskCleanUpStatus::NoCleanUp,
+        StoredTaskCleanUpStatus::Running {} => TaskCleanUpStatus::Failed {
+            error: Arc::new(TaskError::TaskInterrupted { task_id }),
+        },
+        StoredTaskCleanUpStatus::Completed { info } => TaskCleanUpStatus::Completed {
+            info: Arc::new(info.boxed()),
+        },
+        StoredTaskCleanUpStatus::Aborted { info } => TaskCleanUpStatus::Aborted {
+            info: Arc::new(info.boxed()),
+        },
+        StoredTaskCleanUpStatus::Failed { error } => TaskCleanUpStatus::Failed {
+            error: Arc::new(TaskError::RestoredTaskFailure { message: error }),
+        },
+    }
+}
@@ -1,3 +1,4 @@
+use crate::permissions::{Permission, ResourceId};
 use crate::users::UserSession;
 use crate::{
     error,
@@ -7,8 +8,7 @@ use crate::{
     },
 };
 use futures::channel::oneshot;
-use std::{collections::HashMap, sync::Arc};
-use tokio::sync::RwLock;
+use std::sync::Arc;
 
 // TODO: implement real permissions on task types
 const ADMIN_ONLY_TASKS: [&str; 3] = [
@@ -20,7 +20,14 @@ const ADMIN_ONLY_TASKS: [&str; 3] = [
 #[derive(Default)]
 pub struct TypedTaskManagerBackend {
     simple_task_manager: SimpleTaskManagerBackend,
-    task_type_by_id: RwLock<HashMap<TaskId, &'static str>>,
+}
+
+impl TypedTaskManagerBackend {
+    pub fn new(simple_task_manager: SimpleTaskManagerBackend) -> Self {
+        Self {
+            simple_task_manager,
+        }
+    }
 }
 
 pub struct UserTaskManager {
@@ -34,19 +41,50 @@ impl UserTaskManager {
     }
 }
 
-fn check_task_type_is_allowed(
-    session: &UserSession,
-    task_type: &'static str,
-) -> Result<(), TaskError> {
+fn check_task_type_is_allowed(session: &UserSession, task_type: &str) -> Result<(), TaskError> {
     if ADMIN_ONLY_TASKS.contains(&task_type) && !session.is_admin() {
-        return Err(crate::tasks::TaskError::TaskManagerOperationFailed {
-            source: Box::new(error::Error::PermissionDenied),
-        });
+        return Err(permission_denied());
     }
 
     Ok(())
 }
 
+fn permission_denied() -> TaskError {
+    TaskError::TaskManagerOperationFailed {
+        source: Box::new(error::Error::PermissionDenied),
+    }
+}
+
+impl UserTaskManager {
+    async fn ensure_task_permission(
+        &self,
+        task_id: TaskId,
+        permission: Permission,
+    ) -> Result<(), TaskError> {
+        if let Some(api_token) = &self.session.api_token
+            && !api_token.allows(&ResourceId::Task(task_id), &permission)
+        {
+            return Err(permission_denied());
+        }
+
+        if self.session.is_admin() {
+            return Ok(());
+        }
+
+        let has_permission = self
+            .backend
+            .simple_task_manager
+            .has_permission(task_id, &self.session.roles, permission)
+            .await?;
+
+        if !has_permission {
+            return Err(permission_denied());
+        }
+
+        Ok(())
+    }
+}
+
 #[async_trait::async_trait]
 impl TaskManager<SimpleTaskManagerContext> for UserTaskManager {
     async fn schedule_task(
@@ -54,38 +92,41 @@ impl TaskManager<SimpleTaskManagerContext> for UserTaskManager {
         task: Box<dyn Task<SimpleTaskManagerContext>>,
         notify: Option<oneshot::Sender<TaskStatus>>,
     ) -> Result<TaskId, TaskError> {
-        let task_type = task.task_type();
-        check_task_type_is_allowed(&self.session, task_type)?;
+        check_task_type_is_allowed(&self.session, task.task_type())?;
 
-        // TODO: check permissions for user tasks
+        if self.session.has_restricted_api_token() {
+            return Err(TaskError::TaskManagerOperationFailed {
+                source: Box::new(error::Error::RestrictedApiToken),
+            });
+        }
 
-        let task_id = self
-            .backend
+        self.backend
             .simple_task_manager
-            .schedule_task(task, notify)
-            .await?;
+            .schedule_task_for_owner(task, notify, Some(self.session.user.id))
+            .await
+    }
+
+    async fn schedule_subtask(
+        &self,
+        task: Box<dyn Task<SimpleTaskManagerContext>>,
+        notify: oneshot::Sender<TaskStatus>,
+    ) -> Result<TaskId, TaskError> {
+        check_task_type_is_allowed(&self.session, task.task_type())?;
 
         self.backend
-            .task_type_by_id
-            .write()
+            .simple_task_manager
+            .schedule_subtask_for_owner(task, notify, Some(self.session.user.id))
             .await
-            .insert(task_id, task_type);
-
-        Ok(task_id)
     }
 
     async fn get_task_status(&self, task_id: TaskId) -> Result<TaskStatus, TaskError> {
         check_task_type_is_allowed(
             &self.session,
-            self.backend
-                .task_type_by_id
-                .read()
-                .await
-                .get(&task_id)
-                .ok_or(TaskError::TaskNotFound { task_id })?,
+            &self.backend.simple_task_manager.task_type(task_id).await?,
         )?;
 
-        // TODO: check permissions for user tasks
+        self.ensure_task_permission(task_id, Permission::Read)
+            .await?;
 
         self.backend
             .simple_task_manager
@@ -97,46 +138,35 @@ impl TaskManager<SimpleTaskManagerContext> for UserTaskManager {
         &self,
         options: TaskListOptions,
     ) -> Result<Vec<TaskStatusWithId>, TaskError> {
-        // TODO: check permissions for user tasks
+        let (hidden_task_types, roles): (&[&str], _) = if self.session.is_admin() {
+            (&[], None)
+        } else {
+            (&ADMIN_ONLY_TASKS, Some(self.session.roles.as_slice()))
+        };
 
-        let tasks = self
+        let mut tasks = self
             .backend
             .simple_task_manager
-            .list_tasks(TaskListOptions {
-                filter: options.filter,
-                offset: 0,
-                limit: u32::MAX,
-            })
+            .list_tasks_without_types(&options, hidden_task_types, roles)
             .await?;
 
-        let task_types = self.backend.task_type_by_id.read().await;
-
-        Ok(tasks
-            .iter()
-            .filter(|t| {
-                if let Some(task_type) = task_types.get(&t.task_id) {
-                    return check_task_type_is_allowed(&self.session, task_type).is_ok();
-                }
-                false
-            })
-            .skip(options.offset as usize)
-            .take(options.limit as usize)
-            .cloned()
-            .collect::<Vec<_>>())
+        if let Some(api_token) = &self.session.api_token {
+            tasks.retain(|task| {
+                api_token.allows(&ResourceId::Task(task.task_id), &Permission::Read)
+            });
+        }
+
+        Ok(tasks)
     }
 
     async fn abort_tasks(&self, task_id: TaskId, force: bool) -> Result<(), TaskError> {
         check_task_type_is_allowed(
             &self.session,
-            self.backend
-                .task_type_by_id
-                .read()
-                .await
-                .get(&task_id)
-                .ok_or(TaskError::TaskNotFound { task_id })?,
+            &self.backend.simple_task_manager.task_type(task_id).await?,
         )?;
 
-        // TODO: check permissions for user tasks
+        self.ensure_task_permission(task_id, Permission::Owner)
+            .await?;
 
         self.backend
             .simple_task_manager
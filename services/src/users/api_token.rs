@@ -0,0 +1,122 @@
+use crate::identifier;
+use crate::permissions::{Permission, ResourceId};
+use geoengine_datatypes::primitives::DateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+identifier!(ApiTokenId);
+
+/// The prefix that distinguishes API tokens from session ids in the `Authorization` header
+pub const API_TOKEN_PREFIX: &str = "ge_";
+
+/// A named, long-lived token that authenticates machine clients on behalf of a user.
+/// Only the hash of the token is stored, so the token is only known when it is created.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiToken {
+    pub id: ApiTokenId,
+    pub name: String,
+    pub created: DateTime,
+    pub expires: DateTime,
+    /// Only allow reading resources
+    pub read_only: bool,
+    /// The resources that can be accessed with the token. All resources can be accessed if it is not set.
+    pub resources: Option<Vec<ApiTokenResource>>,
+}
+
+/// A resource that an API token is restricted to
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiTokenResource {
+    pub resource_type: String,
+    pub resource_id: String,
+}
+
+impl From<&ResourceId> for ApiTokenResource {
+    fn from(resource: &ResourceId) -> Self {
+        Self {
+            resource_type: resource.resource_type().to_string(),
+            resource_id: resource.resource_id(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize, Clone, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedApiToken {
+    pub id: ApiTokenId,
+    /// The secret that is used as a Bearer token
+    pub token: String,
+}
+
+/// The definition of a new API token with the resources already resolved
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct NewApiToken {
+    pub name: String,
+    pub expires: DateTime,
+    pub read_only: bool,
+    pub resources: Option<Vec<ResourceId>>,
+}
+
+/// The restrictions of a session that was created from an API token
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ApiTokenScope {
+    pub token: ApiTokenId,
+    pub read_only: bool,
+    pub resources: Option<Vec<ResourceId>>,
+}
+
+impl ApiTokenScope {
+    /// Return true if the token may use `permission` on `resource`
+    pub fn allows(&self, resource: &ResourceId, permission: &Permission) -> bool {
+        if self.read_only && *permission != Permission::Read {
+            return false;
+        }
+
+        self.resources
+            .as_ref()
+            .is_none_or(|resources| resources.contains(resource))
+    }
+
+    /// Return true if the token is restricted to reading or to specific resources
+    pub fn is_restricted(&self) -> bool {
+        self.read_only || self.resources.is_some()
+    }
+}
+
+pub(crate) fn is_api_token(token: &str) -> bool {
+    token.starts_with(API_TOKEN_PREFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::projects::ProjectId;
+    use crate::util::Identifier;
+
+    #[test]
+    fn it_restricts_permissions() {
+        let project = ResourceId::Project(ProjectId::new());
+        let other_project = ResourceId::Project(ProjectId::new());
+
+        let scope = ApiTokenScope {
+            token: ApiTokenId::new(),
+            read_only: true,
+            resources: Some(vec![project.clone()]),
+        };
+
+        assert!(scope.allows(&project, &Permission::Read));
+        assert!(!scope.allows(&project, &Permission::Write));
+        assert!(!scope.allows(&other_project, &Permission::Read));
+        assert!(scope.is_restricted());
+
+        let scope = ApiTokenScope {
+            token: ApiTokenId::new(),
+            read_only: false,
+            resources: None,
+        };
+
+        assert!(scope.allows(&other_project, &Permission::Owner));
+        assert!(!scope.is_restricted());
+    }
+}
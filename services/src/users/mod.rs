@@ -1,9 +1,14 @@
+mod api_token;
 mod oidc;
 mod postgres_userdb;
 mod session;
 mod user;
 mod userdb;
 
+pub(crate) use api_token::{API_TOKEN_PREFIX, is_api_token};
+pub use api_token::{
+    ApiToken, ApiTokenId, ApiTokenResource, ApiTokenScope, CreatedApiToken, NewApiToken,
+};
 pub(crate) use oidc::OidcError;
 pub(crate) use oidc::{AuthCodeRequestURL, AuthCodeResponse, OidcDisabled, OidcManager};
 #[cfg(test)]
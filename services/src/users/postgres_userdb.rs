@@ -1,23 +1,30 @@
 use crate::api::handlers::users::UsageSummaryGranularity;
+use crate::api::model::datatypes::DataProviderId;
 use crate::contexts::PostgresDb;
 use crate::contexts::{ApplicationContext, SessionId};
+use crate::datasets::DatasetName;
 use crate::error::{Error, Result};
 use crate::permissions::TxPermissionDb;
-use crate::permissions::{Role, RoleDescription, RoleId};
+use crate::permissions::{ResourceId, Role, RoleDescription, RoleId, secret_token_hash};
 use crate::projects::{ProjectId, STRectangle};
-use crate::quota::{ComputationQuota, DataUsage, DataUsageSummary, OperatorQuota};
+use crate::quota::{
+    ComputationQuota, CreateQuotaLimit, DataUsage, DataUsageSummary, OperatorQuota, QuotaLimit,
+    QuotaLimitData, QuotaLimitId, QuotaLimitUsage,
+};
 use crate::users::oidc::{FlatMaybeEncryptedOidcTokens, OidcTokens, UserClaims};
 use crate::users::userdb::{
     CannotRevokeRoleThatIsNotAssignedRoleDbError, RoleIdDoesNotExistRoleDbError,
 };
 use crate::users::{
-    SessionTokenStore, StoredOidcTokens, User, UserCredentials, UserDb, UserId, UserInfo,
-    UserRegistration, UserSession,
+    API_TOKEN_PREFIX, ApiToken, ApiTokenId, ApiTokenResource, ApiTokenScope, CreatedApiToken,
+    NewApiToken, SessionTokenStore, StoredOidcTokens, User, UserCredentials, UserDb, UserId,
+    UserInfo, UserRegistration, UserSession,
 };
 use crate::util::Identifier;
 use crate::util::postgres::PostgresErrorExt;
 use crate::{contexts::PostgresContext, error};
 use async_trait::async_trait;
+use geoengine_datatypes::primitives::DateTime;
 use geoengine_operators::meta::quota::ComputationUnit;
 
 use crate::util::encryption::MaybeEncryptedBytes;
@@ -27,7 +34,9 @@ use bb8_postgres::{
 use oauth2::AccessToken;
 use pwhash::bcrypt;
 use snafu::{ResultExt, ensure};
-use tokio_postgres::Transaction;
+use std::str::FromStr;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::{Row, Transaction};
 use uuid::Uuid;
 
 use super::userdb::{
@@ -103,71 +112,34 @@ where
 
         let tx = conn.build_transaction().start().await?;
 
-        let user_id = UserId::new();
-
-        let stmt = tx
-            .prepare("INSERT INTO roles (id, name) VALUES ($1, $2);")
-            .await?;
-        tx.execute(&stmt, &[&user_id, &format!("anonymous_user_{user_id}")])
-            .await?;
-
-        let quota_available =
-            crate::config::get_config_element::<crate::config::Quota>()?.initial_credits;
-
-        let stmt = tx
-            .prepare("INSERT INTO users (id, quota_available, active) VALUES ($1, $2, TRUE);")
-            .await?;
+        let session = create_anonymous_session_in_tx(&tx, None).await?;
 
-        tx.execute(&stmt, &[&user_id, &quota_available]).await?;
+        tx.commit().await?;
 
-        let stmt = tx
-            .prepare("INSERT INTO user_roles (user_id, role_id) VALUES ($1, $2);")
-            .await?;
-        tx.execute(&stmt, &[&user_id, &user_id]).await?;
+        Ok(session)
+    }
 
-        let stmt = tx
-            .prepare("INSERT INTO user_roles (user_id, role_id) VALUES ($1, $2);")
-            .await?;
-        tx.execute(&stmt, &[&user_id, &Role::anonymous_role_id()])
-            .await?;
+    async fn create_share_link_session(&self, token: &str) -> Result<UserSession> {
+        let mut conn = self.pool.get().await?;
 
-        let session_id = SessionId::new();
+        let tx = conn.build_transaction().start().await?;
 
-        // TODO: load from config
-        let session_duration = chrono::Duration::days(30);
         let row = tx
-            .query_one(
+            .query_opt(
                 "
-                INSERT INTO 
-                    sessions (id, user_id, created, valid_until) 
-                VALUES 
-                    ($1, $2, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP + make_interval(secs:=$3))
-                RETURNING 
-                    created, valid_until;
-                ",
-                &[
-                    &session_id,
-                    &user_id,
-                    &(session_duration.num_seconds() as f64),
-                ],
+                SELECT role_id
+                FROM share_links
+                WHERE token_hash = $1 AND (expires IS NULL OR expires > CURRENT_TIMESTAMP);",
+                &[&secret_token_hash(token)],
             )
-            .await?;
+            .await?
+            .ok_or(error::Error::InvalidShareLink)?;
+
+        let session = create_anonymous_session_in_tx(&tx, Some(row.get(0))).await?;
 
         tx.commit().await?;
 
-        Ok(UserSession {
-            id: session_id,
-            user: UserInfo {
-                id: user_id,
-                email: None,
-                real_name: None,
-            },
-            created: row.get(0),
-            valid_until: row.get(1),
-            project: None,
-            view: None,
-            roles: vec![user_id.into(), Role::anonymous_role_id()],
-        })
+        Ok(session)
     }
 
     async fn login(&self, user_credentials: UserCredentials) -> Result<UserSession> {
@@ -238,6 +210,7 @@ where
                 project: None,
                 view: None,
                 roles,
+                api_token: None,
             })
         } else {
             Err(error::Error::LoginFailed)
@@ -364,6 +337,7 @@ where
             project: None,
             view: None,
             roles,
+            api_token: None,
         })
     }
 
@@ -428,6 +402,7 @@ where
             project: row.get::<usize, Option<Uuid>>(5).map(ProjectId),
             view: row.get(6),
             roles: vec![],
+            api_token: None,
         };
 
         let stmt = tx
@@ -446,6 +421,222 @@ where
 
         Ok(session)
     }
+
+    async fn internal_session_for_user(&self, user: UserId) -> Result<UserSession> {
+        let conn = self.pool.get().await?;
+
+        let stmt = conn
+            .prepare(
+                "
+            SELECT
+                COALESCE(u.email, eu.email) AS email,
+                COALESCE(u.real_name, eu.real_name) AS real_name
+            FROM
+                users u LEFT JOIN external_users eu ON (u.id = eu.id)
+            WHERE u.id = $1 AND u.active;",
+            )
+            .await?;
+
+        let row = conn
+            .query_opt(&stmt, &[&user])
+            .await?
+            .ok_or(error::Error::UnknownOrInactiveUser { user_id: user })?;
+
+        let stmt = conn
+            .prepare("SELECT role_id FROM user_roles WHERE user_id = $1;")
+            .await?;
+
+        let roles = conn
+            .query(&stmt, &[&user])
+            .await?
+            .into_iter()
+            .map(|row| row.get(0))
+            .collect();
+
+        let now = DateTime::now();
+
+        Ok(UserSession {
+            id: SessionId::new(),
+            user: UserInfo {
+                id: user,
+                email: row.get(0),
+                real_name: row.get(1),
+            },
+            created: now,
+            valid_until: now,
+            project: None,
+            view: None,
+            roles,
+            api_token: None,
+        })
+    }
+
+    async fn session_by_api_token(&self, token: &str) -> Result<UserSession> {
+        let conn = self.pool.get().await?;
+
+        api_token_session(&conn, "t.token_hash = $1", &secret_token_hash(token)).await
+    }
+
+    async fn internal_session_for_api_token(&self, token: ApiTokenId) -> Result<UserSession> {
+        let conn = self.pool.get().await?;
+
+        api_token_session(&conn, "t.id = $1", &token).await
+    }
+}
+
+/// Create a session with the scope of the valid API token that matches the `condition`
+async fn api_token_session(
+    conn: &tokio_postgres::Client,
+    condition: &str,
+    param: &(dyn ToSql + Sync),
+) -> Result<UserSession> {
+    let stmt = conn
+        .prepare(&format!(
+            "
+        SELECT
+            t.id,
+            u.id,
+            COALESCE(u.email, eu.email) AS email,
+            COALESCE(u.real_name, eu.real_name) AS real_name,
+            t.time_created,
+            t.expires,
+            t.read_only,
+            {API_TOKEN_RESOURCES_COLUMNS}
+        FROM
+            api_tokens t
+                JOIN users u ON (t.user_id = u.id)
+                LEFT JOIN external_users eu ON (u.id = eu.id)
+        WHERE {condition} AND t.expires > CURRENT_TIMESTAMP AND u.active;"
+        ))
+        .await?;
+
+    let row = conn
+        .query_opt(&stmt, &[param])
+        .await?
+        .ok_or(error::Error::InvalidApiToken)?;
+
+    let token_id: ApiTokenId = row.get(0);
+    let user_id: UserId = row.get(1);
+
+    let resources = api_token_resources_from_row(&row, 7)?
+        .map(|resources| {
+            resources
+                .into_iter()
+                .map(|resource| {
+                    ResourceId::try_from((resource.resource_type, resource.resource_id))
+                })
+                .collect::<Result<Vec<_>>>()
+        })
+        .transpose()?;
+
+    let stmt = conn
+        .prepare("SELECT role_id FROM user_roles WHERE user_id = $1;")
+        .await?;
+
+    let roles = conn
+        .query(&stmt, &[&user_id])
+        .await?
+        .into_iter()
+        .map(|row| row.get(0))
+        .collect();
+
+    Ok(UserSession {
+        id: SessionId::new(),
+        user: UserInfo {
+            id: user_id,
+            email: row.get(2),
+            real_name: row.get(3),
+        },
+        created: row.get(4),
+        valid_until: row.get(5),
+        project: None,
+        view: None,
+        roles,
+        api_token: Some(ApiTokenScope {
+            token: token_id,
+            read_only: row.get(6),
+            resources,
+        }),
+    })
+}
+
+/// Create an anonymous user and a session for it.
+/// The user is assigned to the role of a share link if one is given.
+async fn create_anonymous_session_in_tx(
+    tx: &Transaction<'_>,
+    share_link_role: Option<RoleId>,
+) -> Result<UserSession> {
+    let user_id = UserId::new();
+
+    let stmt = tx
+        .prepare("INSERT INTO roles (id, name) VALUES ($1, $2);")
+        .await?;
+    tx.execute(&stmt, &[&user_id, &format!("anonymous_user_{user_id}")])
+        .await?;
+
+    let quota_available =
+        crate::config::get_config_element::<crate::config::Quota>()?.initial_credits;
+
+    let stmt = tx
+        .prepare("INSERT INTO users (id, quota_available, active) VALUES ($1, $2, TRUE);")
+        .await?;
+
+    tx.execute(&stmt, &[&user_id, &quota_available]).await?;
+
+    let stmt = tx
+        .prepare("INSERT INTO user_roles (user_id, role_id) VALUES ($1, $2);")
+        .await?;
+    tx.execute(&stmt, &[&user_id, &user_id]).await?;
+
+    let stmt = tx
+        .prepare("INSERT INTO user_roles (user_id, role_id) VALUES ($1, $2);")
+        .await?;
+    tx.execute(&stmt, &[&user_id, &Role::anonymous_role_id()])
+        .await?;
+
+    let mut roles = vec![user_id.into(), Role::anonymous_role_id()];
+
+    if let Some(share_link_role) = share_link_role {
+        tx.execute(&stmt, &[&user_id, &share_link_role]).await?;
+        roles.push(share_link_role);
+    }
+
+    let session_id = SessionId::new();
+
+    // TODO: load from config
+    let session_duration = chrono::Duration::days(30);
+    let row = tx
+        .query_one(
+            "
+            INSERT INTO 
+                sessions (id, user_id, created, valid_until) 
+            VALUES 
+                ($1, $2, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP + make_interval(secs:=$3))
+            RETURNING 
+                created, valid_until;
+            ",
+            &[
+                &session_id,
+                &user_id,
+                &(session_duration.num_seconds() as f64),
+            ],
+        )
+        .await?;
+
+    Ok(UserSession {
+        id: session_id,
+        user: UserInfo {
+            id: user_id,
+            email: None,
+            real_name: None,
+        },
+        created: row.get(0),
+        valid_until: row.get(1),
+        project: None,
+        view: None,
+        roles,
+        api_token: None,
+    })
 }
 
 #[async_trait]
@@ -796,7 +987,7 @@ where
     ) -> Result<()> {
         ensure!(self.session.is_admin(), error::PermissionDenied);
 
-        let conn = self.conn_pool.get().await?;
+        let mut conn = self.conn_pool.get().await?;
 
         // collect the log into separate vectors to pass them as parameters to the query
         let mut users = Vec::new();
@@ -815,12 +1006,14 @@ where
             datas.push(unit.data);
         }
 
+        let tx = conn.build_transaction().start().await?;
+
         let query = "
             INSERT INTO quota_log (user_id, workflow_id, computation_id, operator_name, operator_path, data)
                 (SELECT * FROM UNNEST($1::uuid[], $2::uuid[], $3::uuid[], $4::text[], $5::text[], $6::text[]))
         ";
 
-        conn.execute(
+        tx.execute(
             query,
             &[
                 &users,
@@ -833,6 +1026,36 @@ where
         )
         .await?;
 
+        // count the computation units of the limits of the users' roles, starting anew in each period
+        tx.execute(
+            "
+            INSERT INTO quota_limit_usage (limit_id, period_start, used)
+                SELECT
+                    l.id,
+                    date_trunc(lower(l.period::text), CURRENT_TIMESTAMP),
+                    COUNT(*)
+                FROM
+                    UNNEST($1::uuid[], $2::text[]) AS q (user_id, data)
+                    JOIN user_roles r ON (q.user_id = r.user_id)
+                    JOIN quota_limits l ON (r.role_id = l.role_id)
+                WHERE
+                    (l.dataset IS NULL OR q.data = l.dataset) AND
+                    (l.provider_id IS NULL OR position(':' || l.provider_id::text || ':' IN q.data) > 0)
+                GROUP BY
+                    l.id, l.period
+            ON CONFLICT (limit_id) DO UPDATE SET
+                used = CASE
+                    WHEN quota_limit_usage.period_start = EXCLUDED.period_start
+                    THEN quota_limit_usage.used + EXCLUDED.used
+                    ELSE EXCLUDED.used
+                END,
+                period_start = EXCLUDED.period_start;",
+            &[&users, &datas],
+        )
+        .await?;
+
+        tx.commit().await?;
+
         Ok(())
     }
 
@@ -1061,6 +1284,325 @@ where
 
         Ok(())
     }
+
+    async fn create_quota_limit(&self, limit: CreateQuotaLimit) -> Result<QuotaLimitId> {
+        ensure!(self.session.is_admin(), error::PermissionDenied);
+
+        let conn = self.conn_pool.get().await?;
+
+        let role_exists: bool = conn
+            .query_one(
+                "SELECT EXISTS(SELECT 1 FROM roles WHERE id = $1);",
+                &[&limit.role],
+            )
+            .await?
+            .get(0);
+
+        ensure!(
+            role_exists,
+            error::UnknownQuotaLimitRole { role: limit.role }
+        );
+
+        let (dataset, provider) = match &limit.data {
+            None => (None, None),
+            Some(QuotaLimitData::Dataset(data)) => (Some(data.dataset.to_string()), None),
+            Some(QuotaLimitData::Provider(data)) => (None, Some(data.provider)),
+        };
+
+        let id = QuotaLimitId::new();
+
+        conn.execute(
+            "
+            INSERT INTO quota_limits (id, role_id, dataset, provider_id, period, credits)
+            VALUES ($1, $2, $3, $4, $5, $6);",
+            &[
+                &id,
+                &limit.role,
+                &dataset,
+                &provider,
+                &limit.period,
+                &(limit.credits as i64),
+            ],
+        )
+        .await?;
+
+        Ok(id)
+    }
+
+    async fn list_quota_limits(&self) -> Result<Vec<QuotaLimitUsage>> {
+        ensure!(self.session.is_admin(), error::PermissionDenied);
+
+        let conn = self.conn_pool.get().await?;
+
+        let rows = conn
+            .query(
+                &format!(
+                    "
+            SELECT
+                l.id, l.role_id, l.dataset, l.provider_id, l.period, l.credits,
+                {QUOTA_LIMIT_USED_QUERY} AS used
+            FROM
+                quota_limits l
+            ORDER BY
+                l.role_id, l.id;"
+                ),
+                &[],
+            )
+            .await?;
+
+        rows.iter().map(quota_limit_usage_from_row).collect()
+    }
+
+    async fn delete_quota_limit(&self, limit: QuotaLimitId) -> Result<()> {
+        ensure!(self.session.is_admin(), error::PermissionDenied);
+
+        let conn = self.conn_pool.get().await?;
+
+        let deleted = conn
+            .execute("DELETE FROM quota_limits WHERE id = $1;", &[&limit])
+            .await?;
+
+        ensure!(deleted > 0, error::UnknownQuotaLimitId { limit });
+
+        Ok(())
+    }
+
+    async fn quota_limits_of_user(&self) -> Result<Vec<QuotaLimitUsage>> {
+        let conn = self.conn_pool.get().await?;
+
+        let rows = conn
+            .query(
+                &format!(
+                    "
+            SELECT
+                l.id, l.role_id, l.dataset, l.provider_id, l.period, l.credits,
+                {QUOTA_LIMIT_USED_QUERY} AS used
+            FROM
+                quota_limits l JOIN user_roles r ON (l.role_id = r.role_id)
+            WHERE
+                r.user_id = $1
+            ORDER BY
+                l.role_id, l.id;"
+                ),
+                &[&self.session.user.id],
+            )
+            .await?;
+
+        rows.iter().map(quota_limit_usage_from_row).collect()
+    }
+
+    async fn exhausted_quota_limit(&self, data: Option<&str>) -> Result<Option<QuotaLimitId>> {
+        let conn = self.conn_pool.get().await?;
+
+        let row = conn
+            .query_opt(
+                &format!(
+                    "
+            SELECT
+                l.id
+            FROM
+                quota_limits l JOIN user_roles r ON (l.role_id = r.role_id)
+            WHERE
+                r.user_id = $1 AND
+                (
+                    ($2::text IS NULL AND l.dataset IS NULL AND l.provider_id IS NULL) OR
+                    l.dataset = $2 OR
+                    position(':' || l.provider_id::text || ':' IN $2) > 0
+                ) AND
+                {QUOTA_LIMIT_USED_QUERY} >= l.credits
+            LIMIT 1;"
+                ),
+                &[&self.session.user.id, &data],
+            )
+            .await?;
+
+        Ok(row.map(|row| row.get(0)))
+    }
+
+    async fn create_api_token(&self, token: NewApiToken) -> Result<CreatedApiToken> {
+        ensure!(
+            self.session.api_token.is_none(),
+            error::ApiTokenManagementRequiresSession
+        );
+        ensure!(
+            token
+                .resources
+                .as_ref()
+                .is_none_or(|resources| !resources.is_empty()),
+            error::EmptyApiTokenResources
+        );
+
+        let mut conn = self.conn_pool.get().await?;
+        let tx = conn.build_transaction().start().await?;
+
+        let id = ApiTokenId::new();
+        let secret = format!("{API_TOKEN_PREFIX}{}", Uuid::new_v4().simple());
+
+        tx.execute(
+            "
+            INSERT INTO api_tokens (id, user_id, name, token_hash, read_only, expires)
+            VALUES ($1, $2, $3, $4, $5, $6);",
+            &[
+                &id,
+                &self.session.user.id,
+                &token.name,
+                &secret_token_hash(&secret),
+                &token.read_only,
+                &token.expires,
+            ],
+        )
+        .await
+        .map_unique_violation("api_tokens", "user_id_name", || {
+            error::Error::DuplicateApiTokenName {
+                name: token.name.clone(),
+            }
+        })?;
+
+        let stmt = tx
+            .prepare(
+                "
+            INSERT INTO api_token_resources (api_token_id, resource_type, resource_id)
+            VALUES ($1, $2, $3) ON CONFLICT DO NOTHING;",
+            )
+            .await?;
+
+        for resource in token.resources.iter().flatten() {
+            tx.execute(
+                &stmt,
+                &[&id, &resource.resource_type(), &resource.resource_id()],
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(CreatedApiToken { id, token: secret })
+    }
+
+    async fn list_api_tokens(&self) -> Result<Vec<ApiToken>> {
+        let conn = self.conn_pool.get().await?;
+
+        let stmt = conn
+            .prepare(&format!(
+                "
+            SELECT
+                t.id,
+                t.name,
+                t.time_created,
+                t.expires,
+                t.read_only,
+                {API_TOKEN_RESOURCES_COLUMNS}
+            FROM api_tokens t
+            WHERE t.user_id = $1
+            ORDER BY t.name;"
+            ))
+            .await?;
+
+        let rows = conn.query(&stmt, &[&self.session.user.id]).await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(ApiToken {
+                    id: row.get(0),
+                    name: row.get(1),
+                    created: row.get(2),
+                    expires: row.get(3),
+                    read_only: row.get(4),
+                    resources: api_token_resources_from_row(row, 5)?,
+                })
+            })
+            .collect()
+    }
+
+    async fn delete_api_token(&self, token: ApiTokenId) -> Result<()> {
+        ensure!(
+            self.session.api_token.is_none(),
+            error::ApiTokenManagementRequiresSession
+        );
+
+        let conn = self.conn_pool.get().await?;
+
+        let deleted = conn
+            .execute(
+                "DELETE FROM api_tokens WHERE id = $1 AND user_id = $2;",
+                &[&token, &self.session.user.id],
+            )
+            .await?;
+
+        ensure!(deleted > 0, error::UnknownApiTokenId { token });
+
+        Ok(())
+    }
+}
+
+/// The resources of the API token `t` as arrays of their types and ids.
+/// They are `NULL` if the token is not restricted to resources.
+const API_TOKEN_RESOURCES_COLUMNS: &str = "
+    (
+        SELECT array_agg(r.resource_type ORDER BY r.resource_type, r.resource_id)
+        FROM api_token_resources r
+        WHERE r.api_token_id = t.id
+    ),
+    (
+        SELECT array_agg(r.resource_id ORDER BY r.resource_type, r.resource_id)
+        FROM api_token_resources r
+        WHERE r.api_token_id = t.id
+    )";
+
+fn api_token_resources_from_row(row: &Row, index: usize) -> Result<Option<Vec<ApiTokenResource>>> {
+    let resource_types: Option<Vec<String>> = row.try_get(index)?;
+    let resource_ids: Option<Vec<String>> = row.try_get(index + 1)?;
+
+    Ok(resource_types.zip(resource_ids).map(|(types, ids)| {
+        types
+            .into_iter()
+            .zip(ids)
+            .map(|(resource_type, resource_id)| ApiTokenResource {
+                resource_type,
+                resource_id,
+            })
+            .collect()
+    }))
+}
+
+/// The computation units that the members of the role of the quota limit `l`
+/// used in the current period on the limit's data.
+/// The counters are updated when the quota is logged, cf. `log_quota_used`.
+/// Data of external providers is logged as `namespace:provider:name`.
+const QUOTA_LIMIT_USED_QUERY: &str = "
+    COALESCE(
+        (
+            SELECT
+                u.used
+            FROM
+                quota_limit_usage u
+            WHERE
+                u.limit_id = l.id AND
+                u.period_start = date_trunc(lower(l.period::text), CURRENT_TIMESTAMP)
+        ),
+        0
+    )";
+
+fn quota_limit_usage_from_row(row: &Row) -> Result<QuotaLimitUsage> {
+    let dataset: Option<String> = row.get(2);
+    let provider: Option<Uuid> = row.get(3);
+
+    let data = match (dataset, provider) {
+        (Some(dataset), _) => Some(QuotaLimitData::dataset(DatasetName::from_str(&dataset)?)),
+        (None, Some(provider)) => Some(QuotaLimitData::provider(DataProviderId(provider))),
+        (None, None) => None,
+    };
+
+    Ok(QuotaLimitUsage {
+        limit: QuotaLimit {
+            id: row.get(0),
+            role: row.get(1),
+            data,
+            period: row.get(4),
+            credits: row.get::<_, i64>(5) as u64,
+        },
+        used: row.get::<_, i64>(6) as u64,
+    })
 }
 
 #[async_trait]
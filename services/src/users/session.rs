@@ -1,10 +1,11 @@
-use crate::api::handlers::get_token;
+use crate::api::handlers::{AuthToken, get_token};
 use crate::contexts::PostgresContext;
 use crate::contexts::{ApplicationContext, Session, SessionId};
 use crate::error;
-use crate::permissions::{Role, RoleId};
+use crate::machine_learning::MlModelId;
+use crate::permissions::{ResourceId, Role, RoleId};
 use crate::projects::{ProjectId, STRectangle};
-use crate::users::UserId;
+use crate::users::{ApiTokenScope, UserAuth, UserId};
 use crate::util::Identifier;
 use actix_http::Payload;
 use actix_web::{FromRequest, HttpRequest, web};
@@ -12,6 +13,7 @@ use bb8_postgres::tokio_postgres::NoTls;
 use futures::future::err;
 use futures_util::FutureExt;
 use futures_util::future::LocalBoxFuture;
+use geoengine_datatypes::dataset::DatasetId;
 use geoengine_datatypes::primitives::DateTime;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
@@ -34,6 +36,9 @@ pub struct UserSession {
     pub project: Option<ProjectId>,
     pub view: Option<STRectangle>,
     pub roles: Vec<RoleId>, // a user has a default role (= its user id) and other additonal roles
+    /// The restrictions of the session if it was created from an API token
+    #[serde(skip)]
+    pub api_token: Option<ApiTokenScope>,
 }
 
 impl UserSession {
@@ -52,11 +57,47 @@ impl UserSession {
             project: None,
             view: None,
             roles: vec![role],
+            api_token: None,
         }
     }
 
+    /// Return true if the user is an admin. Restricted API tokens do not have admin rights.
     pub fn is_admin(&self) -> bool {
-        self.roles.contains(&Role::admin_role_id())
+        self.roles.contains(&Role::admin_role_id()) && !self.has_restricted_api_token()
+    }
+
+    /// Return true if the session was created from an API token that is restricted to reading or to specific resources.
+    /// Such sessions cannot create resources.
+    pub fn has_restricted_api_token(&self) -> bool {
+        self.api_token
+            .as_ref()
+            .is_some_and(ApiTokenScope::is_restricted)
+    }
+
+    /// Return the resources of one type that the API token of the session is restricted to.
+    /// Returns `None` if the session is not restricted to specific resources.
+    pub fn api_token_resources<T>(
+        &self,
+        resource_id: impl FnMut(&ResourceId) -> Option<T>,
+    ) -> Option<Vec<T>> {
+        let resources = self.api_token.as_ref()?.resources.as_ref()?;
+        Some(resources.iter().filter_map(resource_id).collect())
+    }
+
+    /// Return the datasets that the API token of the session is restricted to, if any
+    pub fn api_token_datasets(&self) -> Option<Vec<DatasetId>> {
+        self.api_token_resources(|resource| match resource {
+            ResourceId::DatasetId(dataset_id) => Some(*dataset_id),
+            _ => None,
+        })
+    }
+
+    /// Return the machine learning models that the API token of the session is restricted to, if any
+    pub fn api_token_ml_models(&self) -> Option<Vec<MlModelId>> {
+        self.api_token_resources(|resource| match resource {
+            ResourceId::MlModel(model_id) => Some(*model_id),
+            _ => None,
+        })
     }
 }
 
@@ -96,7 +137,19 @@ impl FromRequest for UserSession {
             "Application context should be present because it is set during server initialization.",
         );
         let pg_ctx = pg_ctx.get_ref().clone();
-        async move { pg_ctx.session_by_id(token).await }.boxed_local()
+        async move {
+            match token {
+                AuthToken::Session(session_id) => pg_ctx.session_by_id(session_id).await,
+                AuthToken::ApiToken(token) => {
+                    pg_ctx.session_by_api_token(&token).await.map_err(|source| {
+                        error::Error::Unauthorized {
+                            source: Box::new(source),
+                        }
+                    })
+                }
+            }
+        }
+        .boxed_local()
     }
 }
 
@@ -132,6 +185,7 @@ mod tests {
             created: DateTime::from_str("2020-01-01T00:00:00Z").unwrap(),
             valid_until: DateTime::from_str("2021-01-01T00:00:00Z").unwrap(),
             roles: vec![RoleId::from_str("da3825dd-6240-460d-a324-02bd06704aaa").unwrap()],
+            api_token: None,
         };
 
         assert_eq!(
@@ -3,9 +3,15 @@ use crate::contexts::SessionId;
 use crate::error::Result;
 use crate::permissions::{RoleDescription, RoleId};
 use crate::projects::{ProjectId, STRectangle};
-use crate::quota::{ComputationQuota, DataUsage, DataUsageSummary, OperatorQuota};
+use crate::quota::{
+    ComputationQuota, CreateQuotaLimit, DataUsage, DataUsageSummary, OperatorQuota, QuotaLimitId,
+    QuotaLimitUsage,
+};
 use crate::users::oidc::{OidcTokens, UserClaims};
-use crate::users::{UserCredentials, UserId, UserRegistration, UserSession};
+use crate::users::{
+    ApiToken, ApiTokenId, CreatedApiToken, NewApiToken, UserCredentials, UserId, UserRegistration,
+    UserSession,
+};
 use async_trait::async_trait;
 use geoengine_datatypes::primitives::DateTime;
 use geoengine_operators::meta::quota::ComputationUnit;
@@ -32,6 +38,23 @@ pub trait UserAuth {
     ///
     async fn create_anonymous_session(&self) -> Result<UserSession>;
 
+    /// Creates a session for an anonymous user that has read access to the resource of a share link
+    ///
+    /// # Errors
+    ///
+    /// This call fails if the share link token is unknown or the link has expired.
+    ///
+    async fn create_share_link_session(&self, token: &str) -> Result<UserSession>;
+
+    /// Creates a session from an API token that is not stored.
+    /// The session is restricted to the scope of the token.
+    ///
+    /// # Errors
+    ///
+    /// This call fails if the token is unknown, has expired or its user is inactive.
+    ///
+    async fn session_by_api_token(&self, token: &str) -> Result<UserSession>;
+
     /// Creates a `Session` by providing `UserCredentials`
     ///
     /// # Errors
@@ -60,6 +83,26 @@ pub trait UserAuth {
     /// This call fails if the session is invalid.
     ///
     async fn user_session_by_id(&self, session: SessionId) -> Result<UserSession>;
+
+    /// Creates a session for the user with the given id that is not stored.
+    /// It allows running tasks on behalf of the user, e.g., for schedules,
+    /// but cannot be used to authenticate requests.
+    ///
+    /// # Errors
+    ///
+    /// This call fails if the user does not exist or is inactive.
+    ///
+    async fn internal_session_for_user(&self, user: UserId) -> Result<UserSession>;
+
+    /// Creates a session with the scope of an API token that is not stored.
+    /// It allows running tasks on behalf of the token, e.g., for schedules,
+    /// but cannot be used to authenticate requests.
+    ///
+    /// # Errors
+    ///
+    /// This call fails if the token is unknown, has expired or its user is inactive.
+    ///
+    async fn internal_session_for_api_token(&self, token: ApiTokenId) -> Result<UserSession>;
 }
 
 #[async_trait]
@@ -188,6 +231,63 @@ pub trait UserDb: Send + Sync {
         user: &UserId,
         new_available_quota: i64,
     ) -> Result<()>;
+
+    /// Creates a quota limit for a role. Requires admin privilige.
+    ///
+    /// # Errors
+    ///
+    /// This call fails if the role is unknown
+    async fn create_quota_limit(&self, limit: CreateQuotaLimit) -> Result<QuotaLimitId>;
+
+    /// Lists all quota limits with the quota used in their current period. Requires admin privilige.
+    ///
+    /// # Errors
+    ///
+    /// This call fails if database cannot be accessed
+    async fn list_quota_limits(&self) -> Result<Vec<QuotaLimitUsage>>;
+
+    /// Deletes a quota limit. Requires admin privilige.
+    ///
+    /// # Errors
+    ///
+    /// This call fails if the quota limit is unknown
+    async fn delete_quota_limit(&self, limit: QuotaLimitId) -> Result<()>;
+
+    /// Lists the quota limits that apply to the current user with the quota used in their current period
+    ///
+    /// # Errors
+    ///
+    /// This call fails if database cannot be accessed
+    async fn quota_limits_of_user(&self) -> Result<Vec<QuotaLimitUsage>>;
+
+    /// Gets a quota limit of the current user that is exhausted in its current period.
+    /// If `data` is given, only limits on this data are considered, otherwise only limits without data.
+    ///
+    /// # Errors
+    ///
+    /// This call fails if database cannot be accessed
+    async fn exhausted_quota_limit(&self, data: Option<&str>) -> Result<Option<QuotaLimitId>>;
+
+    /// Creates an API token for the current user. Requires a session that was not created from an API token.
+    ///
+    /// # Errors
+    ///
+    /// This call fails if the user already has a token with the same name
+    async fn create_api_token(&self, token: NewApiToken) -> Result<CreatedApiToken>;
+
+    /// Lists the API tokens of the current user
+    ///
+    /// # Errors
+    ///
+    /// This call fails if database cannot be accessed
+    async fn list_api_tokens(&self) -> Result<Vec<ApiToken>>;
+
+    /// Revokes an API token of the current user. Requires a session that was not created from an API token.
+    ///
+    /// # Errors
+    ///
+    /// This call fails if the token is unknown
+    async fn delete_api_token(&self, token: ApiTokenId) -> Result<()>;
 }
 
 #[derive(Debug, Snafu)]
@@ -156,13 +156,13 @@ pub async fn register_ndvi_workflow_helper_with_cache_ttl(
     };
 
     let session = admin_login(app_ctx).await;
+    let db = app_ctx.session_context(session).db();
 
-    let id = app_ctx
-        .session_context(session)
-        .db()
-        .register_workflow(workflow.clone())
-        .await
-        .unwrap();
+    let id = db.register_workflow(workflow.clone()).await.unwrap();
+
+    for role in [Role::registered_user_role_id(), Role::anonymous_role_id()] {
+        db.add_permission(role, id, Permission::Read).await.unwrap();
+    }
 
     (workflow, id)
 }
@@ -420,6 +420,13 @@ pub async fn register_ne2_multiband_workflow(
             .unwrap();
     }
 
+    for role in [Role::registered_user_role_id(), Role::anonymous_role_id()] {
+        ctx.db()
+            .add_permission(role, id, Permission::Read)
+            .await
+            .unwrap();
+    }
+
     (workflow, id)
 }
 
@@ -573,6 +580,7 @@ pub fn create_test_app(
         .configure(handlers::permissions::init_permissions_routes::<PostgresContext<NoTls>>)
         .configure(handlers::plots::init_plot_routes::<PostgresContext<NoTls>>)
         .configure(handlers::projects::init_project_routes::<PostgresContext<NoTls>>)
+        .configure(handlers::schedules::init_schedule_routes::<PostgresContext<NoTls>>)
         .configure(handlers::users::init_user_routes::<PostgresContext<NoTls>>)
         .configure(
             handlers::spatial_references::init_spatial_reference_routes::<PostgresContext<NoTls>>,
@@ -818,6 +826,7 @@ pub fn create_random_user_session_helper() -> UserSession {
         project: None,
         view: None,
         roles: vec![user_id.into(), Role::registered_user_role_id()],
+        api_token: None,
     }
 }
 
@@ -1,5 +1,7 @@
 use crate::contexts::PostgresDb;
 use crate::error::Result;
+use crate::permissions::{Permission, PermissionDbError, ResourceId, RoleId, TxPermissionDb};
+use crate::users::UserSession;
 use crate::workflows::registry::TxWorkflowRegistry;
 use crate::workflows::workflow::{Workflow, WorkflowId};
 use crate::{error, workflows::registry::WorkflowRegistry};
@@ -7,6 +9,7 @@ use async_trait::async_trait;
 use bb8_postgres::{
     tokio_postgres::Socket, tokio_postgres::tls::MakeTlsConnect, tokio_postgres::tls::TlsConnect,
 };
+use geoengine_datatypes::error::BoxedResultExt;
 use snafu::ResultExt;
 
 #[async_trait]
@@ -24,15 +27,36 @@ where
     ) -> Result<WorkflowId> {
         let workflow_id = WorkflowId::from_hash(&workflow);
 
-        tx.execute(
-            "INSERT INTO workflows (id, workflow) VALUES ($1, $2) 
+        let inserted = tx
+            .execute(
+                "INSERT INTO workflows (id, workflow) VALUES ($1, $2) 
             ON CONFLICT DO NOTHING;",
-            &[
-                &workflow_id,
-                &serde_json::to_value(&workflow).context(error::SerdeJson)?,
-            ],
-        )
-        .await?;
+                &[
+                    &workflow_id,
+                    &serde_json::to_value(&workflow).context(error::SerdeJson)?,
+                ],
+            )
+            .await?;
+
+        // restricted API tokens cannot own resources, but they may register workflows to query them
+        if inserted > 0 && !self.session.has_restricted_api_token() {
+            self.create_resource_in_tx(workflow_id, tx)
+                .await
+                .boxed_context(error::PermissionDb)?;
+        } else {
+            // workflows are identified by their content, so users that register an existing workflow may read it
+            tx.execute(
+                "
+                INSERT INTO permissions (role_id, permission, workflow_id)
+                VALUES ($1, $2, $3) ON CONFLICT DO NOTHING;",
+                &[
+                    &RoleId::from(self.session.user.id),
+                    &Permission::Read,
+                    &workflow_id,
+                ],
+            )
+            .await?;
+        }
 
         Ok(workflow_id)
     }
@@ -58,18 +82,78 @@ where
     }
 
     async fn load_workflow(&self, id: &WorkflowId) -> Result<Workflow> {
-        // TODO: add authorization beyond the fact that you can only access workflows if you happen to know the id.
-        let conn = self.conn_pool.get().await?;
-        let stmt = conn
+        let mut conn = self.conn_pool.get().await?;
+        let tx = conn.build_transaction().start().await?;
+
+        let stmt = tx
             .prepare("SELECT workflow FROM workflows WHERE id = $1")
             .await?;
 
-        let row = conn.query(&stmt, &[&id]).await?;
+        let row = tx.query(&stmt, &[&id]).await?;
 
         if row.is_empty() {
             return Err(error::Error::NoWorkflowForGivenId);
         }
 
-        Ok(serde_json::from_value(row[0].get(0)).context(error::SerdeJson)?)
+        let permitted = self
+            .has_permission_in_tx(*id, Permission::Read, &tx)
+            .await
+            .boxed_context(error::PermissionDb)?
+            || is_workflow_of_readable_project(&self.session, id, &tx).await?;
+
+        if !permitted {
+            return Err(error::Error::PermissionDb {
+                source: Box::new(PermissionDbError::PermissionDenied {
+                    resource_id: (*id).into(),
+                    permission: Permission::Read,
+                }),
+            });
+        }
+
+        let workflow = serde_json::from_value(row[0].get(0)).context(error::SerdeJson)?;
+
+        tx.commit().await?;
+
+        Ok(workflow)
     }
 }
+
+/// Users that may read a project may also read the workflows of its layers and plots.
+/// Thus, sharing a project or creating a share link for it makes its workflows accessible.
+async fn is_workflow_of_readable_project(
+    session: &UserSession,
+    workflow_id: &WorkflowId,
+    tx: &tokio_postgres::Transaction<'_>,
+) -> Result<bool> {
+    let api_token_projects = session.api_token_resources(|resource| match resource {
+        ResourceId::Project(project_id) => Some(*project_id),
+        _ => None,
+    });
+
+    let row = tx
+        .query_one(
+            "
+            SELECT EXISTS (
+                SELECT 1 FROM permissions p
+                WHERE
+                    p.project_id IN (
+                        SELECT project_id FROM project_version_layers WHERE workflow_id = $1
+                        UNION
+                        SELECT project_id FROM project_version_plots WHERE workflow_id = $1
+                    ) AND
+                    p.role_id = ANY($2) AND
+                    p.permission = ANY($3) AND
+                    (p.expires IS NULL OR p.expires > CURRENT_TIMESTAMP) AND
+                    ($4::uuid[] IS NULL OR p.project_id = ANY($4))
+            );",
+            &[
+                workflow_id,
+                &session.roles,
+                &Permission::Read.required_permissions(),
+                &api_token_projects,
+            ],
+        )
+        .await?;
+
+    Ok(row.get(0))
+}